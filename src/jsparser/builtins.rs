@@ -0,0 +1,1310 @@
+use std::sync::Arc;
+
+use super::expr::ASYNC_ITERATOR_OF;
+use super::object::{
+    BoundFunction, IterationKind, IteratorState, JSObject, JSSymbol, NativeCtor, NativeFn,
+    ObjectId, ObjectKind, Property, PropertyKey,
+};
+use super::program::{Combinator, JSType, Program, ResumeKind, TypedArrayKind};
+use super::string::JSString;
+use super::utility::err;
+
+mod array;
+mod bigint;
+mod collection;
+mod date;
+mod intl;
+mod json;
+mod math;
+mod number;
+mod object;
+mod proxy;
+mod reflect;
+mod string;
+mod symbol;
+mod typed_array;
+
+pub(crate) use date::iso_string;
+pub(crate) use intl::{Collator, DateTimeFormat, NumberFormat};
+pub use math::Random;
+
+/// 内置原型对象
+#[derive(Debug, Clone, Default)]
+pub struct Intrinsics {
+    pub object_prototype: ObjectId,
+    pub function_prototype: ObjectId,
+    pub array: ObjectId,
+    pub array_prototype: ObjectId,
+    pub error_prototype: ObjectId,
+    pub type_error_prototype: ObjectId,
+    pub range_error_prototype: ObjectId,
+    pub reference_error_prototype: ObjectId,
+    pub syntax_error_prototype: ObjectId,
+    pub aggregate_error_prototype: ObjectId,
+    pub string_prototype: ObjectId,
+    pub number_prototype: ObjectId,
+    pub boolean_prototype: ObjectId,
+    pub symbol_prototype: ObjectId,
+    pub bigint_prototype: ObjectId,
+    pub global_object: ObjectId, //globalThis, 内置对象及宿主注册的全局变量为其属性
+    pub iterator_prototype: ObjectId,
+    pub array_iterator_prototype: ObjectId,
+    pub string_iterator_prototype: ObjectId,
+    pub map_iterator_prototype: ObjectId,
+    pub set_iterator_prototype: ObjectId,
+    pub async_from_sync_iterator_prototype: ObjectId,
+    pub generator_prototype: ObjectId,
+    pub promise: ObjectId,
+    pub promise_prototype: ObjectId,
+    pub response_prototype: ObjectId,
+    pub headers_prototype: ObjectId,
+    pub map_prototype: ObjectId,
+    pub set_prototype: ObjectId,
+    pub weak_map_prototype: ObjectId,
+    pub weak_set_prototype: ObjectId,
+    pub date_prototype: ObjectId,
+    pub array_buffer_prototype: ObjectId,
+    pub data_view_prototype: ObjectId,
+    pub typed_array: ObjectId, //%TypedArray%
+    pub typed_array_prototype: ObjectId,
+    pub typed_array_prototypes: [ObjectId; 11], //按TypedArrayKind::ALL的顺序
+    pub number_format_prototype: ObjectId,
+    pub date_time_format_prototype: ObjectId,
+    pub collator_prototype: ObjectId,
+    pub symbol_iterator: Arc<JSSymbol>,
+    pub symbol_async_iterator: Arc<JSSymbol>,
+    pub symbol_has_instance: Arc<JSSymbol>,
+    pub symbol_to_primitive: Arc<JSSymbol>,
+    pub symbol_to_string_tag: Arc<JSSymbol>,
+}
+
+impl Intrinsics {
+    /// 按名称获取内置原型, 用于 new.target 没有prototype时
+    pub fn get(&self, name: &str) -> ObjectId {
+        match name {
+            "function" => self.function_prototype,
+            "array" => self.array_prototype,
+            "Error" => self.error_prototype,
+            "TypeError" => self.type_error_prototype,
+            "RangeError" => self.range_error_prototype,
+            "ReferenceError" => self.reference_error_prototype,
+            "SyntaxError" => self.syntax_error_prototype,
+            "AggregateError" => self.aggregate_error_prototype,
+            "Promise" => self.promise_prototype,
+            "String" => self.string_prototype,
+            "Number" => self.number_prototype,
+            "Boolean" => self.boolean_prototype,
+            "Map" => self.map_prototype,
+            "Set" => self.set_prototype,
+            "WeakMap" => self.weak_map_prototype,
+            "WeakSet" => self.weak_set_prototype,
+            "Date" => self.date_prototype,
+            "ArrayBuffer" => self.array_buffer_prototype,
+            "DataView" => self.data_view_prototype,
+            "Intl.NumberFormat" => self.number_format_prototype,
+            "Intl.DateTimeFormat" => self.date_time_format_prototype,
+            "Intl.Collator" => self.collator_prototype,
+            _ => match TypedArrayKind::from_name(name) {
+                Some(kind) => self.typed_array_prototypes[kind as usize],
+                None => self.object_prototype,
+            },
+        }
+    }
+}
+
+impl Program {
+    pub(crate) fn init_builtins(&mut self) {
+        let object_prototype = self.alloc(JSObject::new(ObjectKind::Ordinary, None));
+        let function_prototype = self.alloc(JSObject::new(
+            ObjectKind::Native("", function_prototype_call, None),
+            Some(object_prototype),
+        ));
+        self.intrinsics.object_prototype = object_prototype;
+        self.intrinsics.function_prototype = function_prototype;
+        self.intrinsics.global_object =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.array_prototype = self.alloc(JSObject::new(
+            ObjectKind::Array(Vec::new(), 0),
+            Some(object_prototype),
+        ));
+        self.intrinsics.string_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.number_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.boolean_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.symbol_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.init_well_known_symbols();
+
+        //Object
+        self.define_method(object_prototype, "toString", 0, object_to_string);
+        self.define_method(object_prototype, "valueOf", 0, object_value_of);
+        self.define_method(
+            object_prototype,
+            "hasOwnProperty",
+            1,
+            object_has_own_property,
+        );
+        self.define_method(object_prototype, "isPrototypeOf", 1, object_is_prototype_of);
+        //Object.prototype.__proto__ 访问器
+        let get_proto = self.native_function("get __proto__", 0, object_get_proto, None);
+        let set_proto = self.native_function("set __proto__", 1, object_set_proto, None);
+        let key = PropertyKey::from("__proto__");
+        self.define_accessor(
+            object_prototype,
+            &key,
+            true,
+            JSType::Object(get_proto),
+            false,
+        );
+        self.define_accessor(
+            object_prototype,
+            &key,
+            false,
+            JSType::Object(set_proto),
+            false,
+        );
+        let object =
+            self.define_constructor("Object", 1, object_prototype, object_call, object_construct);
+        self.define_method(object, "create", 2, object_create);
+        self.define_method(object, "getPrototypeOf", 1, object_get_prototype_of);
+        self.define_method(object, "setPrototypeOf", 2, object_set_prototype_of);
+        self.init_object(object);
+
+        //Function
+        self.define_method(function_prototype, "call", 1, function_call);
+        self.define_method(function_prototype, "apply", 2, function_apply);
+        self.define_method(function_prototype, "bind", 1, function_bind);
+        self.define_constructor(
+            "Function",
+            1,
+            function_prototype,
+            function_constructor_call,
+            function_construct,
+        );
+        let has_instance =
+            self.native_function("[Symbol.hasInstance]", 1, function_has_instance, None);
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_has_instance.clone());
+        let mut prop = Property::readonly(JSType::Object(has_instance));
+        prop.configurable = false;
+        self.define_property(function_prototype, key, prop);
+
+        //Symbol
+        self.init_symbol();
+
+        //Iterator
+        let iterator_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.iterator_prototype = iterator_prototype;
+        self.define_symbol_method(iterator_prototype, "[Symbol.iterator]", iterator_self);
+        let array_iterator_prototype = self.alloc(JSObject::new(
+            ObjectKind::Ordinary,
+            Some(iterator_prototype),
+        ));
+        self.intrinsics.array_iterator_prototype = array_iterator_prototype;
+        self.define_method(array_iterator_prototype, "next", 0, iterator_next);
+        self.define_to_string_tag(array_iterator_prototype, "Array Iterator");
+        let string_iterator_prototype = self.alloc(JSObject::new(
+            ObjectKind::Ordinary,
+            Some(iterator_prototype),
+        ));
+        self.intrinsics.string_iterator_prototype = string_iterator_prototype;
+        self.define_method(string_iterator_prototype, "next", 0, iterator_next);
+        self.define_to_string_tag(string_iterator_prototype, "String Iterator");
+        //for await 遍历同步迭代器时的包装
+        let async_from_sync_iterator_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.async_from_sync_iterator_prototype = async_from_sync_iterator_prototype;
+        self.define_method(
+            async_from_sync_iterator_prototype,
+            "next",
+            1,
+            async_from_sync_iterator_next,
+        );
+        self.define_global_function(ASYNC_ITERATOR_OF, 1, async_iterator_of);
+
+        //Generator
+        let generator_prototype = self.alloc(JSObject::new(
+            ObjectKind::Ordinary,
+            Some(iterator_prototype),
+        ));
+        self.intrinsics.generator_prototype = generator_prototype;
+        self.define_method(generator_prototype, "next", 1, generator_next);
+        self.define_method(generator_prototype, "return", 1, generator_return);
+        self.define_method(generator_prototype, "throw", 1, generator_throw);
+        self.define_to_string_tag(generator_prototype, "Generator");
+
+        //Array
+        let array_prototype = self.intrinsics.array_prototype;
+        let array =
+            self.define_constructor("Array", 1, array_prototype, array_call, array_construct);
+        self.init_array(array);
+        let values = self.get_property(&JSType::Object(array_prototype), "values");
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_iterator.clone());
+        self.define_property(array_prototype, key, Property::hidden(values.unwrap()));
+
+        //String
+        let string_prototype = self.intrinsics.string_prototype;
+        self.define_symbol_method(string_prototype, "[Symbol.iterator]", string_iterator);
+        self.init_string();
+
+        //Number/Boolean
+        self.init_number();
+
+        //BigInt
+        self.init_bigint();
+
+        //Map/Set/WeakMap/WeakSet
+        self.init_collections();
+
+        //Date
+        self.init_date();
+
+        //ArrayBuffer/TypedArray/DataView
+        self.init_typed_arrays();
+
+        //Error
+        let error_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.error_prototype = error_prototype;
+        self.define_method(error_prototype, "toString", 0, error_to_string);
+        let error =
+            self.define_constructor("Error", 1, error_prototype, error_call, error_construct);
+        self.init_error_prototype(error_prototype, "Error");
+        let errors: [(&'static str, NativeFn); 4] = [
+            ("TypeError", type_error_call),
+            ("RangeError", range_error_call),
+            ("ReferenceError", reference_error_call),
+            ("SyntaxError", syntax_error_call),
+        ];
+        for (name, call) in errors {
+            let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(error_prototype)));
+            match name {
+                "TypeError" => self.intrinsics.type_error_prototype = proto,
+                "RangeError" => self.intrinsics.range_error_prototype = proto,
+                "ReferenceError" => self.intrinsics.reference_error_prototype = proto,
+                _ => self.intrinsics.syntax_error_prototype = proto,
+            }
+            let ctor = self.define_constructor(name, 1, proto, call, error_construct);
+            self.obj_mut(ctor).proto = Some(error);
+            self.init_error_prototype(proto, name);
+        }
+        let aggregate_error_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(error_prototype)));
+        self.intrinsics.aggregate_error_prototype = aggregate_error_prototype;
+        let ctor = self.define_constructor(
+            "AggregateError",
+            2,
+            aggregate_error_prototype,
+            aggregate_error_call,
+            aggregate_error_construct,
+        );
+        self.obj_mut(ctor).proto = Some(error);
+        self.init_error_prototype(aggregate_error_prototype, "AggregateError");
+
+        //Promise
+        let promise_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.promise_prototype = promise_prototype;
+        self.define_method(promise_prototype, "then", 2, promise_then);
+        self.define_method(promise_prototype, "catch", 1, promise_catch);
+        self.define_method(promise_prototype, "finally", 1, promise_finally);
+        self.define_to_string_tag(promise_prototype, "Promise");
+        let promise = self.define_constructor(
+            "Promise",
+            1,
+            promise_prototype,
+            promise_call,
+            promise_construct,
+        );
+        self.intrinsics.promise = promise;
+        self.define_method(promise, "resolve", 1, promise_resolve);
+        self.define_method(promise, "reject", 1, promise_reject);
+        self.define_method(promise, "all", 1, promise_all);
+        self.define_method(promise, "allSettled", 1, promise_all_settled);
+        self.define_method(promise, "any", 1, promise_any);
+        self.define_method(promise, "race", 1, promise_race);
+
+        //fetch
+        let response_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.response_prototype = response_prototype;
+        self.define_method(response_prototype, "text", 0, response_text);
+        self.define_method(response_prototype, "json", 0, response_json);
+        self.define_to_string_tag(response_prototype, "Response");
+        let headers_prototype =
+            self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.headers_prototype = headers_prototype;
+        self.define_method(headers_prototype, "get", 1, headers_get);
+        self.define_method(headers_prototype, "has", 1, headers_has);
+        self.define_to_string_tag(headers_prototype, "Headers");
+        self.define_global_function("fetch", 1, fetch);
+
+        //Math
+        self.init_math();
+
+        //JSON
+        self.init_json();
+
+        //Proxy/Reflect
+        self.init_proxy();
+        self.init_reflect();
+
+        //Intl
+        self.init_intl();
+
+        //定时器及微任务
+        self.define_global_function("setTimeout", 2, set_timeout);
+        self.define_global_function("setInterval", 2, set_interval);
+        self.define_global_function("clearTimeout", 1, clear_timer);
+        self.define_global_function("clearInterval", 1, clear_timer);
+        self.define_global_function("queueMicrotask", 1, queue_microtask);
+
+        //全局变量, undefined/NaN/Infinity不可修改
+        let global = self.intrinsics.global_object;
+        for (name, value) in [
+            ("undefined", JSType::Undefined),
+            ("NaN", JSType::Float(f64::NAN)),
+            ("Infinity", JSType::Float(f64::INFINITY)),
+        ] {
+            let mut prop = Property::readonly(value);
+            prop.configurable = false;
+            self.define_property(global, name, prop);
+        }
+        self.bind_value("globalThis".to_string(), JSType::Object(global));
+    }
+
+    fn init_error_prototype(&mut self, proto: ObjectId, name: &str) {
+        self.define_property(proto, "name", Property::hidden(JSType::String(name.into())));
+        self.define_property(
+            proto,
+            "message",
+            Property::hidden(JSType::String(JSString::new())),
+        );
+    }
+
+    /// 创建内置方法
+    pub(crate) fn native_function(
+        &mut self,
+        name: &'static str,
+        length: usize,
+        func: NativeFn,
+        ctor: Option<NativeCtor>,
+    ) -> ObjectId {
+        let proto = self.intrinsics.function_prototype;
+        let id = self.alloc(JSObject::new(
+            ObjectKind::Native(name, func, ctor),
+            Some(proto),
+        ));
+        self.define_property(id, "length", Property::readonly(JSType::Int(length as i64)));
+        self.define_property(id, "name", Property::readonly(JSType::String(name.into())));
+        id
+    }
+
+    /// 在对象上定义不可枚举的内置方法
+    pub(crate) fn define_method(
+        &mut self,
+        target: ObjectId,
+        name: &'static str,
+        length: usize,
+        func: NativeFn,
+    ) -> ObjectId {
+        let id = self.native_function(name, length, func, None);
+        self.define_property(target, name, Property::hidden(JSType::Object(id)));
+        id
+    }
+
+    /// 定义只读的访问器属性
+    fn define_getter(
+        &mut self,
+        target: ObjectId,
+        name: &'static str,
+        getter_name: &'static str,
+        func: NativeFn,
+    ) {
+        let getter = self.native_function(getter_name, 0, func, None);
+        self.define_accessor(target, &name.into(), true, JSType::Object(getter), false);
+    }
+
+    /// 定义全局方法
+    fn define_global_function(&mut self, name: &'static str, length: usize, func: NativeFn) {
+        let id = self.native_function(name, length, func, None);
+        self.bind_value(name.to_string(), JSType::Object(id));
+    }
+
+    /// 定义 [Symbol.iterator] 方法
+    fn define_symbol_method(&mut self, target: ObjectId, name: &'static str, func: NativeFn) {
+        let id = self.native_function(name, 0, func, None);
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_iterator.clone());
+        self.define_property(target, key, Property::hidden(JSType::Object(id)));
+    }
+
+    /// 创建内置迭代器对象
+    pub(crate) fn new_iterator(&mut self, state: IteratorState) -> JSType {
+        let proto = match &state {
+            IteratorState::String(_, _) => self.intrinsics.string_iterator_prototype,
+            IteratorState::AsyncFromSync(_, _) => {
+                self.intrinsics.async_from_sync_iterator_prototype
+            }
+            IteratorState::Map(target, _, _) => match self.obj(*target).kind {
+                ObjectKind::Set(_) => self.intrinsics.set_iterator_prototype,
+                _ => self.intrinsics.map_iterator_prototype,
+            },
+            _ => self.intrinsics.array_iterator_prototype,
+        };
+        JSType::Object(self.alloc(JSObject::new(
+            ObjectKind::Iterator(Box::new(state)),
+            Some(proto),
+        )))
+    }
+
+    /// 迭代结果 { value, done }
+    pub(crate) fn iter_result(&mut self, value: JSType, done: bool) -> JSType {
+        let result = self.new_object();
+        if let JSType::Object(id) = result {
+            self.define_property(id, "value", Property::data(value));
+            self.define_property(id, "done", Property::data(JSType::Bool(done)));
+        }
+        result
+    }
+
+    /// 定义全局构造函数, 关联prototype及constructor
+    pub(crate) fn define_constructor(
+        &mut self,
+        name: &'static str,
+        length: usize,
+        prototype: ObjectId,
+        func: NativeFn,
+        ctor: NativeCtor,
+    ) -> ObjectId {
+        let id = self.native_function(name, length, func, Some(ctor));
+        let mut prop = Property::readonly(JSType::Object(prototype));
+        prop.configurable = false;
+        self.define_property(id, "prototype", prop);
+        self.define_property(
+            prototype,
+            "constructor",
+            Property::hidden(JSType::Object(id)),
+        );
+        self.bind_value(name.to_string(), JSType::Object(id));
+        id
+    }
+}
+
+/// 参数不足时为 undefined
+pub(crate) fn arg(args: &[JSType], index: usize) -> JSType {
+    args.get(index).cloned().unwrap_or(JSType::Undefined)
+}
+
+/// ToIntegerOrInfinity
+pub(crate) fn to_integer(program: &mut Program, value: &JSType) -> Result<f64, String> {
+    let n = program.number_value(value)?;
+    Ok(if n.is_nan() { 0.0 } else { n.trunc() })
+}
+
+/// 相对位置, 负数从末尾计算, 结果在 [0, len]
+pub(crate) fn relative_index(n: f64, len: usize) -> usize {
+    let n = if n < 0.0 { len as f64 + n } else { n };
+    n.max(0.0).min(len as f64) as usize
+}
+
+/// 类数组对象转为参数列表 {length:2, 0:a, 1:b}
+pub(crate) fn list_from_array_like(
+    program: &mut Program,
+    value: &JSType,
+) -> Result<Vec<JSType>, String> {
+    let JSType::Object(id) = value else {
+        return Err(err(
+            "Uncaught TypeError: CreateListFromArrayLike called on non-object",
+        ));
+    };
+    if let ObjectKind::Array(list, len) = &program.obj(*id).kind {
+        if list.len() == *len && list.iter().all(Option::is_some) {
+            return Ok(list
+                .iter()
+                .map(|v| v.clone().unwrap_or(JSType::Undefined))
+                .collect());
+        }
+    }
+    let length = program.get_property(value, "length")?;
+    let length = program.number_value(&length)?;
+    let length = if length.is_nan() || length < 0.0 {
+        0
+    } else {
+        length as usize
+    };
+    let mut list = Vec::with_capacity(length);
+    for index in 0..length {
+        list.push(program.get_property(value, index.to_string())?);
+    }
+    Ok(list)
+}
+
+fn function_prototype_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Undefined)
+}
+
+fn check_callable(program: &Program, this: &JSType, method: &str) -> Result<(), String> {
+    if !program.is_callable(this) {
+        return Err(err(&format!(
+            "Uncaught TypeError: Function.prototype.{} called on non-function",
+            method
+        )));
+    }
+    Ok(())
+}
+
+/// f.call(this, ...args)
+fn function_call(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    check_callable(program, &this, "call")?;
+    let mut args = args.into_iter();
+    let this_arg = args.next().unwrap_or(JSType::Undefined);
+    program.call(&this, this_arg, args.collect())
+}
+
+/// f.apply(this, [args])
+fn function_apply(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    check_callable(program, &this, "apply")?;
+    let list = match arg(&args, 1) {
+        JSType::Undefined | JSType::NULL => Vec::new(),
+        value => list_from_array_like(program, &value)?,
+    };
+    program.call(&this, arg(&args, 0), list)
+}
+
+/// Function.prototype[Symbol.hasInstance]
+fn function_has_instance(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    Ok(JSType::Bool(
+        program.ordinary_has_instance(&this, &arg(&args, 0))?,
+    ))
+}
+
+/// f.bind(this, ...args)
+fn function_bind(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    check_callable(program, &this, "bind")?;
+    let JSType::Object(target) = this else {
+        unreachable!()
+    };
+    let mut args = args.into_iter();
+    let this_arg = args.next().unwrap_or(JSType::Undefined);
+    let args: Vec<JSType> = args.collect();
+    let length = match program.get_property(&this, "length")? {
+        JSType::Int(n) => (n - args.len() as i64).max(0),
+        _ => 0,
+    };
+    let name = match program.get_property(&this, "name")? {
+        JSType::String(name) => name.to_string(),
+        _ => String::new(),
+    };
+    let bound = BoundFunction {
+        target,
+        this: this_arg,
+        args,
+        constructor: program.obj(target).is_constructor(),
+    };
+    let proto = program.obj(target).proto;
+    let id = program.alloc(JSObject::new(ObjectKind::Bound(Box::new(bound)), proto));
+    program.define_property(id, "length", Property::readonly(JSType::Int(length)));
+    program.define_property(
+        id,
+        "name",
+        Property::readonly(JSType::String(format!("bound {}", name).into())),
+    );
+    Ok(JSType::Object(id))
+}
+
+fn function_constructor_call(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    function_construct(program, args, JSType::Undefined)
+}
+
+/// 不支持从字符串创建函数
+fn function_construct(_: &mut Program, _: Vec<JSType>, _: JSType) -> Result<JSType, String> {
+    Err(err(
+        "Uncaught EvalError: Code generation from strings disallowed for this context",
+    ))
+}
+
+/// 原型只能是对象或null
+fn check_prototype(program: &Program, proto: &JSType) -> Result<Option<ObjectId>, String> {
+    match proto {
+        JSType::Object(id) => Ok(Some(*id)),
+        JSType::NULL => Ok(None),
+        _ => Err(err(&format!(
+            "Uncaught TypeError: Object prototype may only be an Object or null: {}",
+            program.display(proto)
+        ))),
+    }
+}
+
+/// Object.create(proto, properties)
+fn object_create(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let proto = check_prototype(program, &arg(&args, 0))?;
+    let id = program.alloc(JSObject::new(ObjectKind::Ordinary, proto));
+    let properties = arg(&args, 1);
+    if !matches!(properties, JSType::Undefined) {
+        program.define_properties(id, &properties)?;
+    }
+    Ok(JSType::Object(id))
+}
+
+fn object_get_prototype_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let proto = program.get_prototype_of(&arg(&args, 0))?;
+    Ok(proto.map(JSType::Object).unwrap_or(JSType::NULL))
+}
+
+fn object_set_prototype_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    if let JSType::Undefined | JSType::NULL = target {
+        return Err(err(
+            "Uncaught TypeError: Object.setPrototypeOf called on null or undefined",
+        ));
+    }
+    let proto = check_prototype(program, &arg(&args, 1))?;
+    if let JSType::Object(id) = target {
+        program.set_prototype_of(id, proto)?;
+    }
+    Ok(target)
+}
+
+/// get Object.prototype.__proto__
+fn object_get_proto(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let id = program.object_value(&this)?;
+    let proto = program.get_prototype_of(&JSType::Object(id))?;
+    Ok(proto.map(JSType::Object).unwrap_or(JSType::NULL))
+}
+
+/// set Object.prototype.__proto__, 值不是对象或null, 或this不是对象时忽略
+fn object_set_proto(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    if let JSType::Undefined | JSType::NULL = this {
+        return Err(err(
+            "Uncaught TypeError: Object.prototype.__proto__ called on null or undefined",
+        ));
+    }
+    let proto = match arg(&args, 0) {
+        JSType::Object(proto) => Some(proto),
+        JSType::NULL => None,
+        _ => return Ok(JSType::Undefined),
+    };
+    if let JSType::Object(id) = this {
+        program.set_prototype_of(id, proto)?;
+    }
+    Ok(JSType::Undefined)
+}
+
+fn object_is_prototype_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let JSType::Object(mut cur) = arg(&args, 0) else {
+        return Ok(JSType::Bool(false));
+    };
+    let JSType::Object(id) = this else {
+        return Err(err(
+            "Uncaught TypeError: Cannot convert undefined or null to object",
+        ));
+    };
+    while let Some(proto) = program.get_prototype_of(&JSType::Object(cur))? {
+        if proto == id {
+            return Ok(JSType::Bool(true));
+        }
+        cur = proto;
+    }
+    Ok(JSType::Bool(false))
+}
+
+/// 内置的类型标签可以被 [Symbol.toStringTag] 覆盖
+fn object_to_string(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let builtin_tag = match &this {
+        JSType::Undefined | JSType::Flag(_) => {
+            return Ok(JSType::String("[object Undefined]".into()))
+        }
+        JSType::NULL => return Ok(JSType::String("[object Null]".into())),
+        JSType::Object(_) if program.is_array(&this) => "Array",
+        JSType::Object(id) => match &program.obj(*id).kind {
+            ObjectKind::Error => "Error",
+            ObjectKind::Date(_) => "Date",
+            ObjectKind::Namespace(_) => "Module",
+            ObjectKind::Primitive(JSType::String(_)) => "String",
+            ObjectKind::Primitive(JSType::Int(_) | JSType::Float(_)) => "Number",
+            ObjectKind::Primitive(JSType::Bool(_)) => "Boolean",
+            _ if program.obj(*id).is_callable() => "Function",
+            _ => "Object",
+        },
+        JSType::Int(_) | JSType::Float(_) => "Number",
+        JSType::String(_) => "String",
+        JSType::Bool(_) => "Boolean",
+        JSType::Symbol(_) | JSType::BigInt(_) => "Object",
+    };
+    let key = PropertyKey::Symbol(program.intrinsics.symbol_to_string_tag.clone());
+    let tag = match program.get_property(&this, key)? {
+        JSType::String(tag) => tag.to_string(),
+        _ => builtin_tag.to_string(),
+    };
+    Ok(JSType::String(format!("[object {}]", tag).into()))
+}
+
+fn object_value_of(_: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    match this {
+        JSType::Undefined | JSType::NULL => Err(err(
+            "Uncaught TypeError: Cannot convert undefined or null to object",
+        )),
+        _ => Ok(this),
+    }
+}
+
+fn object_has_own_property(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let key = args.first().cloned().unwrap_or(JSType::Undefined);
+    let key = program.property_key(&key)?;
+    match &this {
+        JSType::Object(id) => Ok(JSType::Bool(program.own_property(*id, &key)?.is_some())),
+        JSType::String(s) => Ok(JSType::Bool(
+            key.is("length") || key.array_index().is_some_and(|i| i < s.len()),
+        )),
+        JSType::Undefined | JSType::NULL => Err(err(
+            "Uncaught TypeError: Cannot convert undefined or null to object",
+        )),
+        _ => Ok(JSType::Bool(false)),
+    }
+}
+
+fn object_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    object_construct(program, args, JSType::Undefined)
+}
+
+fn object_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    //作为函数调用或new Object时, 参数转为对象
+    let is_object = match &new_target {
+        JSType::Undefined => true,
+        JSType::Object(id) => matches!(program.obj(*id).kind, ObjectKind::Native("Object", ..)),
+        _ => false,
+    };
+    match args.first() {
+        Some(JSType::Undefined | JSType::NULL) | None => {}
+        Some(value) if is_object => return Ok(JSType::Object(program.object_value(value)?)),
+        _ => {}
+    }
+    let proto = program.get_prototype_from_constructor(&new_target, "object")?;
+    Ok(JSType::Object(
+        program.alloc(JSObject::new(ObjectKind::Ordinary, Some(proto))),
+    ))
+}
+
+fn create_array_iterator(
+    program: &mut Program,
+    this: JSType,
+    kind: IterationKind,
+) -> Result<JSType, String> {
+    if let JSType::Undefined | JSType::NULL = this {
+        return Err(err(
+            "Uncaught TypeError: Array.prototype.values called on null or undefined",
+        ));
+    }
+    Ok(program.new_iterator(IteratorState::Array(this, 0, kind)))
+}
+
+fn array_keys(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_array_iterator(program, this, IterationKind::Keys)
+}
+
+fn array_values(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_array_iterator(program, this, IterationKind::Values)
+}
+
+fn array_entries(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_array_iterator(program, this, IterationKind::Entries)
+}
+
+/// String.prototype[Symbol.iterator] 按码点迭代
+fn string_iterator(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    if let JSType::Undefined | JSType::NULL = this {
+        return Err(err(
+            "Uncaught TypeError: String.prototype[Symbol.iterator] called on null or undefined",
+        ));
+    }
+    let s = program.js_string_value(&this)?;
+    Ok(program.new_iterator(IteratorState::String(s, 0)))
+}
+
+/// %IteratorPrototype%[Symbol.iterator] 返回自身
+fn iterator_self(_: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(this)
+}
+
+/// for await 展开后获取异步迭代器
+fn async_iterator_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    program.get_async_iterator(&arg(&args, 0))
+}
+
+/// %AsyncFromSyncIteratorPrototype%.next
+fn async_from_sync_iterator_next(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    program.async_from_sync_next(&this)
+}
+
+/// 内置迭代器的next
+fn iterator_next(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let state = match &this {
+        JSType::Object(id) => match &program.obj(*id).kind {
+            ObjectKind::Iterator(state) if !matches!(**state, IteratorState::AsyncFromSync(..)) => {
+                Some((*id, state.as_ref().clone()))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some((id, state)) = state else {
+        return Err(err(&format!(
+            "Uncaught TypeError: Method Iterator.prototype.next called on incompatible receiver {}",
+            program.display(&this)
+        )));
+    };
+    let (value, next) = match state {
+        IteratorState::Array(target, index, kind) => {
+            let length = match &target {
+                JSType::Object(t) => match &program.obj(*t).kind {
+                    ObjectKind::Array(_, len) => *len,
+                    _ => {
+                        let length = program.get_property(&target, "length")?;
+                        let length = program.number_value(&length)?;
+                        if length > 0.0 {
+                            length as usize
+                        } else {
+                            0
+                        }
+                    }
+                },
+                JSType::String(s) => s.len(),
+                _ => 0,
+            };
+            if index >= length {
+                (None, IteratorState::Done)
+            } else {
+                let value = match kind {
+                    IterationKind::Keys => JSType::Int(index as i64),
+                    IterationKind::Values => program.get_property(&target, index.to_string())?,
+                    IterationKind::Entries => {
+                        let value = program.get_property(&target, index.to_string())?;
+                        program.new_array(vec![JSType::Int(index as i64), value])
+                    }
+                };
+                (Some(value), IteratorState::Array(target, index + 1, kind))
+            }
+        }
+        IteratorState::String(s, pos) => match s.code_point_at(pos) {
+            Some(code) => {
+                let next = pos + if code > 0xFFFF { 2 } else { 1 };
+                let value = JSType::String(s.slice(pos, next));
+                (Some(value), IteratorState::String(s, next))
+            }
+            None => (None, IteratorState::Done),
+        },
+        IteratorState::Map(target, pos, kind) => {
+            let entry = match &program.obj(target).kind {
+                ObjectKind::Map(data) | ObjectKind::Set(data) => data
+                    .next_entry(pos)
+                    .map(|(index, key, value)| (index, key.clone(), value.clone())),
+                _ => None,
+            };
+            match entry {
+                Some((index, key, value)) => {
+                    let value = match kind {
+                        IterationKind::Keys => key,
+                        IterationKind::Values => value,
+                        IterationKind::Entries => program.new_array(vec![key, value]),
+                    };
+                    (Some(value), IteratorState::Map(target, index + 1, kind))
+                }
+                None => (None, IteratorState::Done),
+            }
+        }
+        IteratorState::Done => (None, IteratorState::Done),
+        IteratorState::AsyncFromSync(..) => unreachable!(),
+    };
+    program.obj_mut(id).kind = ObjectKind::Iterator(Box::new(next));
+    Ok(match value {
+        Some(value) => program.iter_result(value, false),
+        None => program.iter_result(JSType::Undefined, true),
+    })
+}
+
+fn generator_next(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let value = args.into_iter().next().unwrap_or(JSType::Undefined);
+    program.generator_resume(&this, ResumeKind::Next, value, "Generator.prototype.next")
+}
+
+fn generator_return(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let value = args.into_iter().next().unwrap_or(JSType::Undefined);
+    program.generator_resume(
+        &this,
+        ResumeKind::Return,
+        value,
+        "Generator.prototype.return",
+    )
+}
+
+fn generator_throw(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let value = args.into_iter().next().unwrap_or(JSType::Undefined);
+    program.generator_resume(&this, ResumeKind::Throw, value, "Generator.prototype.throw")
+}
+
+fn array_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    array_construct(program, args, JSType::Undefined)
+}
+
+/// new Array(3) 创建空位数组, 其余参数作为数组元素
+fn array_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let (list, len) = match args.as_slice() {
+        [len @ (JSType::Int(_) | JSType::Float(_))] => {
+            let n = len.to_number();
+            if n < 0.0 || n.fract() != 0.0 || n > u32::MAX as f64 {
+                return Err(err("Uncaught RangeError: Invalid array length"));
+            }
+            //全为空位, 写入元素时再分配
+            (Vec::new(), n as usize)
+        }
+        _ => {
+            let len = args.len();
+            (args.into_iter().map(Some).collect(), len)
+        }
+    };
+    let proto = program.get_prototype_from_constructor(&new_target, "array")?;
+    Ok(JSType::Object(program.alloc(JSObject::new(
+        ObjectKind::Array(list, len),
+        Some(proto),
+    ))))
+}
+
+fn error_to_string(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    if !matches!(this, JSType::Object(_)) {
+        return Err(err(
+            "Uncaught TypeError: Error.prototype.toString requires that 'this' be an Object",
+        ));
+    }
+    let name = match program.get_property(&this, "name")? {
+        JSType::Undefined => "Error".to_string(),
+        value => program.string_value(&value)?,
+    };
+    let message = match program.get_property(&this, "message")? {
+        JSType::Undefined => String::new(),
+        value => program.string_value(&value)?,
+    };
+    if name.is_empty() {
+        return Ok(JSType::String(message.into()));
+    }
+    if message.is_empty() {
+        return Ok(JSType::String(name.into()));
+    }
+    Ok(JSType::String(format!("{}: {}", name, message).into()))
+}
+
+/// Error(msg) 与 new Error(msg) 相同
+fn call_error(program: &mut Program, name: &str, args: Vec<JSType>) -> Result<JSType, String> {
+    let proto = JSType::Object(program.intrinsics.get(name));
+    let ctor = program.get_property(&proto, "constructor")?;
+    error_construct(program, args, ctor)
+}
+fn error_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    call_error(program, "Error", args)
+}
+fn type_error_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    call_error(program, "TypeError", args)
+}
+fn range_error_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    call_error(program, "RangeError", args)
+}
+fn reference_error_call(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    call_error(program, "ReferenceError", args)
+}
+fn syntax_error_call(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    call_error(program, "SyntaxError", args)
+}
+
+fn error_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let proto = program.get_prototype_from_constructor(&new_target, "Error")?;
+    let id = program.alloc(JSObject::new(ObjectKind::Error, Some(proto)));
+    if let Some(message) = args.first() {
+        if !matches!(message, JSType::Undefined) {
+            let message = program.string_value(message)?;
+            program.define_property(
+                id,
+                "message",
+                Property::hidden(JSType::String(message.into())),
+            );
+        }
+    }
+    Ok(JSType::Object(id))
+}
+
+fn aggregate_error_call(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let proto = JSType::Object(program.intrinsics.aggregate_error_prototype);
+    let ctor = program.get_property(&proto, "constructor")?;
+    aggregate_error_construct(program, args, ctor)
+}
+
+/// new AggregateError(errors, message)
+fn aggregate_error_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let proto = program.get_prototype_from_constructor(&new_target, "AggregateError")?;
+    let message = match arg(&args, 1) {
+        JSType::Undefined => String::new(),
+        message => program.string_value(&message)?,
+    };
+    let errors = program.iterate_to_list(&arg(&args, 0))?;
+    let errors = program.new_array(errors);
+    let id = program.alloc(JSObject::new(ObjectKind::Error, Some(proto)));
+    if !message.is_empty() {
+        program.define_property(
+            id,
+            "message",
+            Property::hidden(JSType::String(message.into())),
+        );
+    }
+    program.define_property(id, "errors", Property::hidden(errors));
+    Ok(JSType::Object(id))
+}
+
+fn promise_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Err(err(
+        "Uncaught TypeError: Promise constructor cannot be invoked without 'new'",
+    ))
+}
+
+/// new Promise((resolve, reject) => {})
+fn promise_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let executor = arg(&args, 0);
+    if !program.is_callable(&executor) {
+        return Err(err(&format!(
+            "Uncaught TypeError: Promise resolver {} is not a function",
+            program.display(&executor)
+        )));
+    }
+    let proto = program.get_prototype_from_constructor(&new_target, "Promise")?;
+    let promise = program.new_promise(proto);
+    let (resolve, reject) = program.create_resolving_functions(promise);
+    if let Err(e) = program.call(&executor, JSType::Undefined, vec![resolve, reject.clone()]) {
+        let reason = program.error_value(e);
+        program.call(&reject, JSType::Undefined, vec![reason])?;
+    }
+    Ok(JSType::Object(promise))
+}
+
+fn promise_then(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.promise_then(&this, arg(&args, 0), arg(&args, 1))
+}
+
+fn promise_catch(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.invoke(&this, "then", vec![JSType::Undefined, arg(&args, 0)])
+}
+
+fn promise_finally(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    program.promise_finally(&this, arg(&args, 0))
+}
+
+/// Promise.resolve / reject 等静态方法的this需要是构造函数
+fn promise_constructor_this(program: &Program, this: &JSType, method: &str) -> Result<(), String> {
+    if matches!(this, JSType::Object(_)) {
+        return Ok(());
+    }
+    Err(err(&format!(
+        "Uncaught TypeError: {} called on non-object {}",
+        method,
+        program.display(this)
+    )))
+}
+
+fn promise_resolve(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    promise_constructor_this(program, &this, "PromiseResolve")?;
+    program.promise_resolve(&this, arg(&args, 0))
+}
+
+fn promise_reject(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let capability = program.new_promise_capability(&this)?;
+    program.call(&capability.reject, JSType::Undefined, vec![arg(&args, 0)])?;
+    Ok(capability.promise)
+}
+
+fn promise_all(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.promise_combinator(&this, &arg(&args, 0), Combinator::All)
+}
+
+fn promise_all_settled(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    program.promise_combinator(&this, &arg(&args, 0), Combinator::AllSettled)
+}
+
+fn promise_any(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.promise_combinator(&this, &arg(&args, 0), Combinator::Any)
+}
+
+fn promise_race(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.promise_combinator(&this, &arg(&args, 0), Combinator::Race)
+}
+
+/// 定时器回调必须为函数
+fn timer_callback(program: &Program, args: &[JSType]) -> Result<JSType, String> {
+    let callback = arg(args, 0);
+    if !program.is_callable(&callback) {
+        return Err(err(&format!(
+            "Uncaught TypeError: The \"callback\" argument must be of type function. Received {}",
+            program.display(&callback)
+        )));
+    }
+    Ok(callback)
+}
+
+/// 定时器延时, NaN及负数为0
+fn timer_delay(program: &mut Program, args: &[JSType]) -> Result<u64, String> {
+    let delay = program.number_value(&arg(args, 1))?;
+    Ok(if delay.is_nan() || delay < 0.0 {
+        0
+    } else {
+        delay as u64
+    })
+}
+
+fn set_timeout(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let callback = timer_callback(program, &args)?;
+    let delay = timer_delay(program, &args)?;
+    let rest = args.get(2..).unwrap_or_default().to_vec();
+    let id = program.set_timer(callback, delay, rest, false);
+    Ok(JSType::Int(id as i64))
+}
+
+fn set_interval(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let callback = timer_callback(program, &args)?;
+    let delay = timer_delay(program, &args)?;
+    let rest = args.get(2..).unwrap_or_default().to_vec();
+    let id = program.set_timer(callback, delay, rest, true);
+    Ok(JSType::Int(id as i64))
+}
+
+/// clearTimeout/clearInterval, 无效的id忽略
+fn clear_timer(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = program.number_value(&arg(&args, 0))?;
+    if id.is_finite() && id >= 1.0 && id.fract() == 0.0 {
+        program.clear_timer(id as u64);
+    }
+    Ok(JSType::Undefined)
+}
+
+fn queue_microtask(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let callback = timer_callback(program, &args)?;
+    program.queue_microtask(callback);
+    Ok(JSType::Undefined)
+}
+
+fn fetch(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(program.fetch(&arg(&args, 0), &arg(&args, 1)))
+}
+
+fn response_text(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    program.response_body(&this, false)
+}
+
+fn response_json(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    program.response_body(&this, true)
+}
+
+fn headers_get(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.header_value(&this, &arg(&args, 0))
+}
+
+fn headers_has(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let value = program.header_value(&this, &arg(&args, 0))?;
+    Ok(JSType::Bool(!matches!(value, JSType::NULL)))
+}
@@ -0,0 +1,1079 @@
+use std::cmp::Ordering;
+
+use super::{arg, object_to_string, relative_index, to_integer};
+use crate::jsparser::object::{JSObject, NativeFn, ObjectId, ObjectKind, Property, PropertyKey};
+use crate::jsparser::program::{JSType, Program, PropertyDescriptor};
+use crate::jsparser::string::JSString;
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_array(&mut self, array: ObjectId) {
+        let proto = self.intrinsics.array_prototype;
+        let methods: [(&'static str, usize, NativeFn); 37] = [
+            ("toString", 0, array_to_string),
+            ("join", 1, array_join),
+            ("at", 1, array_at),
+            ("push", 1, array_push),
+            ("pop", 0, array_pop),
+            ("shift", 0, array_shift),
+            ("unshift", 1, array_unshift),
+            ("splice", 2, array_splice),
+            ("slice", 2, array_slice),
+            ("concat", 1, array_concat),
+            ("reverse", 0, array_reverse),
+            ("sort", 1, array_sort),
+            ("indexOf", 1, array_index_of),
+            ("lastIndexOf", 1, array_last_index_of),
+            ("includes", 1, array_includes),
+            ("find", 1, array_find),
+            ("findIndex", 1, array_find_index),
+            ("findLast", 1, array_find_last),
+            ("findLastIndex", 1, array_find_last_index),
+            ("map", 1, array_map),
+            ("filter", 1, array_filter),
+            ("forEach", 1, array_for_each),
+            ("some", 1, array_some),
+            ("every", 1, array_every),
+            ("reduce", 1, array_reduce),
+            ("reduceRight", 1, array_reduce_right),
+            ("flat", 0, array_flat),
+            ("flatMap", 1, array_flat_map),
+            ("fill", 1, array_fill),
+            ("copyWithin", 2, array_copy_within),
+            ("toSorted", 1, array_to_sorted),
+            ("toReversed", 0, array_to_reversed),
+            ("toSpliced", 2, array_to_spliced),
+            ("with", 2, array_with),
+            ("keys", 0, super::array_keys),
+            ("entries", 0, super::array_entries),
+            ("values", 0, super::array_values),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        self.intrinsics.array = array;
+        self.define_method(array, "isArray", 1, array_is_array);
+        self.define_method(array, "from", 1, array_from);
+        self.define_method(array, "of", 0, array_of);
+    }
+
+    /// 创建数组, None为空位
+    pub(crate) fn new_sparse_array(&mut self, list: Vec<Option<JSType>>) -> JSType {
+        let proto = self.intrinsics.array_prototype;
+        let len = list.len();
+        JSType::Object(self.alloc(JSObject::new(ObjectKind::Array(list, len), Some(proto))))
+    }
+
+    /// IsArray, 透过Proxy检查目标
+    pub(crate) fn is_array(&self, value: &JSType) -> bool {
+        match value {
+            JSType::Object(id) => self
+                .proxy_target(*id)
+                .is_some_and(|id| matches!(self.obj(id).kind, ObjectKind::Array(..))),
+            _ => false,
+        }
+    }
+}
+
+/// this转为对象, 同时返回length
+fn this_array(
+    program: &mut Program,
+    this: &JSType,
+    method: &str,
+) -> Result<(JSType, usize), String> {
+    if let JSType::Undefined | JSType::NULL = this {
+        return Err(err(&format!(
+            "Uncaught TypeError: Array.prototype.{} called on null or undefined",
+            method
+        )));
+    }
+    let target = JSType::Object(program.object_value(this)?);
+    let length = length_of(program, &target)?;
+    Ok((target, length))
+}
+
+/// LengthOfArrayLike
+fn length_of(program: &mut Program, target: &JSType) -> Result<usize, String> {
+    let length = program.get_property(target, "length")?;
+    let length = program.number_value(&length)?;
+    if length.is_nan() || length <= 0.0 {
+        return Ok(0);
+    }
+    Ok(length.min(9007199254740991.0) as usize)
+}
+
+fn has_index(program: &mut Program, target: &JSType, index: usize) -> Result<bool, String> {
+    match target {
+        JSType::Object(id) => program.has_property(*id, &PropertyKey::from(index.to_string())),
+        _ => Ok(false),
+    }
+}
+
+fn get_index(program: &mut Program, target: &JSType, index: usize) -> Result<JSType, String> {
+    program.get_property(target, index.to_string())
+}
+
+fn set_index(
+    program: &mut Program,
+    target: &JSType,
+    index: usize,
+    value: JSType,
+) -> Result<(), String> {
+    program.set_property_or_throw(target, &PropertyKey::from(index.to_string()), value)
+}
+
+fn delete_index(program: &mut Program, target: &JSType, index: usize) -> Result<(), String> {
+    if let JSType::Object(id) = target {
+        program.delete_property_or_throw(*id, &PropertyKey::from(index.to_string()))?;
+    }
+    Ok(())
+}
+
+fn set_length(program: &mut Program, target: &JSType, length: usize) -> Result<(), String> {
+    program.set_property_or_throw(
+        target,
+        &PropertyKey::from("length"),
+        JSType::Int(length as i64),
+    )
+}
+
+/// 复制元素, 保留空位
+fn to_list(
+    program: &mut Program,
+    target: &JSType,
+    start: usize,
+    end: usize,
+) -> Result<Vec<Option<JSType>>, String> {
+    let mut list = Vec::with_capacity(end.saturating_sub(start));
+    for index in start..end {
+        if has_index(program, target, index)? {
+            list.push(Some(get_index(program, target, index)?));
+        } else {
+            list.push(None);
+        }
+    }
+    Ok(list)
+}
+
+/// 复制元素, 空位读取为undefined
+fn to_dense_list(
+    program: &mut Program,
+    target: &JSType,
+    length: usize,
+) -> Result<Vec<JSType>, String> {
+    let mut list = Vec::with_capacity(length);
+    for index in 0..length {
+        list.push(get_index(program, target, index)?);
+    }
+    Ok(list)
+}
+
+/// 通过构造函数创建结果对象, 不是构造函数或为内置Array时返回None, 由调用方创建普通数组
+fn construct_array(
+    program: &mut Program,
+    constructor: &JSType,
+    args: Vec<JSType>,
+) -> Result<Option<JSType>, String> {
+    let array = JSType::Object(program.intrinsics.array);
+    if !program.is_constructor(constructor) || constructor.strict_equal(&array) {
+        return Ok(None);
+    }
+    program.construct(constructor, args, None).map(Some)
+}
+
+/// ArraySpeciesCreate, 与Promise相同按constructor属性查找构造函数
+fn array_species_create(
+    program: &mut Program,
+    original: &JSType,
+    length: usize,
+) -> Result<Option<JSType>, String> {
+    if !program.is_array(original) {
+        return Ok(None);
+    }
+    let constructor = program.get_property(original, "constructor")?;
+    if let JSType::Undefined = constructor {
+        return Ok(None);
+    }
+    if !program.is_constructor(&constructor) {
+        return Err(err(
+            "Uncaught TypeError: object.constructor[Symbol.species] is not a constructor",
+        ));
+    }
+    construct_array(program, &constructor, vec![JSType::Int(length as i64)])
+}
+
+/// 结果写入构造出的对象(CreateDataPropertyOrThrow), 空位跳过, with_length时最后设置length
+fn fill_array(
+    program: &mut Program,
+    target: JSType,
+    list: Vec<Option<JSType>>,
+    with_length: bool,
+) -> Result<JSType, String> {
+    let JSType::Object(id) = target else {
+        return Ok(target);
+    };
+    let length = list.len();
+    for (index, value) in list.into_iter().enumerate() {
+        if let Some(value) = value {
+            let desc = PropertyDescriptor::from(&Property::data(value));
+            program.define_property_or_throw(id, &PropertyKey::from(index.to_string()), &desc)?;
+        }
+    }
+    if with_length {
+        set_length(program, &target, length)?;
+    }
+    Ok(target)
+}
+
+/// 回调参数必须可调用
+fn callback(program: &Program, value: &JSType) -> Result<JSType, String> {
+    if !program.is_callable(value) {
+        return Err(err(&format!(
+            "Uncaught TypeError: {} is not a function",
+            program.display(value)
+        )));
+    }
+    Ok(value.clone())
+}
+
+/// Array.prototype.toString 调用join
+fn array_to_string(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let target = JSType::Object(program.object_value(&this)?);
+    let join = program.get_property(&target, "join")?;
+    if program.is_callable(&join) {
+        return program.call(&join, target, Vec::new());
+    }
+    object_to_string(program, target, Vec::new())
+}
+
+pub(super) fn array_join(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "join")?;
+    let separator = match arg(&args, 0) {
+        JSType::Undefined => JSString::from(","),
+        value => program.js_string_value(&value)?,
+    };
+    let JSType::Object(id) = target else {
+        unreachable!()
+    };
+    //循环引用的数组作为空字符串
+    if program.join_stack.contains(&id) {
+        return Ok(JSType::String(JSString::new()));
+    }
+    program.join_stack.push(id);
+    let result = join_items(program, &target, length, &separator);
+    program.join_stack.pop();
+    result.map(JSType::String)
+}
+
+fn join_items(
+    program: &mut Program,
+    target: &JSType,
+    length: usize,
+    separator: &JSString,
+) -> Result<JSString, String> {
+    let mut result = JSString::new();
+    for index in 0..length {
+        if index > 0 {
+            result.push_js(separator);
+        }
+        match get_index(program, target, index)? {
+            JSType::Undefined | JSType::NULL => {}
+            value => result.push_js(&program.js_string_value(&value)?),
+        }
+    }
+    Ok(result)
+}
+
+pub(super) fn array_at(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "at")?;
+    let index = to_integer(program, &arg(&args, 0))?;
+    let index = if index < 0.0 {
+        length as f64 + index
+    } else {
+        index
+    };
+    if index < 0.0 || index >= length as f64 {
+        return Ok(JSType::Undefined);
+    }
+    get_index(program, &target, index as usize)
+}
+
+fn array_push(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "push")?;
+    let new_length = length + args.len();
+    for (index, value) in args.into_iter().enumerate() {
+        set_index(program, &target, length + index, value)?;
+    }
+    set_length(program, &target, new_length)?;
+    Ok(JSType::Int(new_length as i64))
+}
+
+fn array_pop(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "pop")?;
+    if length == 0 {
+        set_length(program, &target, 0)?;
+        return Ok(JSType::Undefined);
+    }
+    let value = get_index(program, &target, length - 1)?;
+    delete_index(program, &target, length - 1)?;
+    set_length(program, &target, length - 1)?;
+    Ok(value)
+}
+
+/// 把 [from, from+count) 的元素移动到to, 空位同样移动
+fn move_items(
+    program: &mut Program,
+    target: &JSType,
+    from: usize,
+    to: usize,
+    count: usize,
+) -> Result<(), String> {
+    let indexes: Vec<usize> = if from < to {
+        (0..count).rev().collect()
+    } else {
+        (0..count).collect()
+    };
+    for offset in indexes {
+        if has_index(program, target, from + offset)? {
+            let value = get_index(program, target, from + offset)?;
+            set_index(program, target, to + offset, value)?;
+        } else {
+            delete_index(program, target, to + offset)?;
+        }
+    }
+    Ok(())
+}
+
+fn array_shift(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "shift")?;
+    if length == 0 {
+        set_length(program, &target, 0)?;
+        return Ok(JSType::Undefined);
+    }
+    let first = get_index(program, &target, 0)?;
+    move_items(program, &target, 1, 0, length - 1)?;
+    delete_index(program, &target, length - 1)?;
+    set_length(program, &target, length - 1)?;
+    Ok(first)
+}
+
+fn array_unshift(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "unshift")?;
+    let count = args.len();
+    if count > 0 {
+        move_items(program, &target, 0, count, length)?;
+        for (index, value) in args.into_iter().enumerate() {
+            set_index(program, &target, index, value)?;
+        }
+    }
+    set_length(program, &target, length + count)?;
+    Ok(JSType::Int((length + count) as i64))
+}
+
+/// splice/toSpliced的起始位置及删除个数
+fn splice_range(
+    program: &mut Program,
+    args: &[JSType],
+    length: usize,
+) -> Result<(usize, usize), String> {
+    let start = relative_index(to_integer(program, &arg(args, 0))?, length);
+    let delete_count = match args.len() {
+        0 => 0,
+        1 => length - start,
+        _ => {
+            let count = to_integer(program, &args[1])?;
+            count.max(0.0).min((length - start) as f64) as usize
+        }
+    };
+    Ok((start, delete_count))
+}
+
+fn array_splice(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "splice")?;
+    let (start, delete_count) = splice_range(program, &args, length)?;
+    let items: Vec<JSType> = args.into_iter().skip(2).collect();
+    let created = array_species_create(program, &target, delete_count)?;
+    let removed = to_list(program, &target, start, start + delete_count)?;
+    let removed = match created {
+        Some(created) => fill_array(program, created, removed, true)?,
+        None => program.new_sparse_array(removed),
+    };
+    let tail = length - start - delete_count;
+    if items.len() != delete_count {
+        move_items(
+            program,
+            &target,
+            start + delete_count,
+            start + items.len(),
+            tail,
+        )?;
+        //缩短时删除末尾多余的元素
+        for index in (length - delete_count + items.len()..length).rev() {
+            delete_index(program, &target, index)?;
+        }
+    }
+    let new_length = length - delete_count + items.len();
+    for (index, value) in items.into_iter().enumerate() {
+        set_index(program, &target, start + index, value)?;
+    }
+    set_length(program, &target, new_length)?;
+    Ok(removed)
+}
+
+fn array_slice(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "slice")?;
+    let start = relative_index(to_integer(program, &arg(&args, 0))?, length);
+    let end = match arg(&args, 1) {
+        JSType::Undefined => length,
+        value => relative_index(to_integer(program, &value)?, length),
+    };
+    let end = end.max(start);
+    let created = array_species_create(program, &target, end - start)?;
+    let list = to_list(program, &target, start, end)?;
+    match created {
+        Some(created) => fill_array(program, created, list, true),
+        None => Ok(program.new_sparse_array(list)),
+    }
+}
+
+/// 数组参数展开, 其余作为元素
+fn array_concat(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, _) = this_array(program, &this, "concat")?;
+    let created = array_species_create(program, &target, 0)?;
+    let mut list = Vec::new();
+    for item in std::iter::once(target).chain(args) {
+        if program.is_array(&item) {
+            let length = length_of(program, &item)?;
+            list.extend(to_list(program, &item, 0, length)?);
+        } else {
+            list.push(Some(item));
+        }
+    }
+    match created {
+        Some(created) => fill_array(program, created, list, true),
+        None => Ok(program.new_sparse_array(list)),
+    }
+}
+
+pub(super) fn array_reverse(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "reverse")?;
+    for lower in 0..length / 2 {
+        let upper = length - 1 - lower;
+        let lower_value = has_index(program, &target, lower)?
+            .then(|| get_index(program, &target, lower))
+            .transpose()?;
+        let upper_value = has_index(program, &target, upper)?
+            .then(|| get_index(program, &target, upper))
+            .transpose()?;
+        for (index, value) in [(lower, upper_value), (upper, lower_value)] {
+            match value {
+                Some(value) => set_index(program, &target, index, value)?,
+                None => delete_index(program, &target, index)?,
+            }
+        }
+    }
+    Ok(target)
+}
+
+pub(super) fn comparator(program: &Program, value: JSType) -> Result<JSType, String> {
+    if !matches!(value, JSType::Undefined) && !program.is_callable(&value) {
+        return Err(err(
+            "Uncaught TypeError: The comparison function must be either a function or undefined",
+        ));
+    }
+    Ok(value)
+}
+
+/// 没有比较函数时按字符串比较
+fn compare_values(
+    program: &mut Program,
+    a: &JSType,
+    b: &JSType,
+    compare: &JSType,
+) -> Result<Ordering, String> {
+    if let JSType::Undefined = compare {
+        let a = program.js_string_value(a)?;
+        let b = program.js_string_value(b)?;
+        return Ok(a.cmp(&b));
+    }
+    let result = program.call(compare, JSType::Undefined, vec![a.clone(), b.clone()])?;
+    let result = program.number_value(&result)?;
+    Ok(result.partial_cmp(&0.0).unwrap_or(Ordering::Equal))
+}
+
+/// 稳定的归并排序, undefined排在最后
+pub(super) fn sort_values(
+    program: &mut Program,
+    list: Vec<JSType>,
+    compare: &JSType,
+) -> Result<Vec<JSType>, String> {
+    let (undefined, mut list): (Vec<JSType>, Vec<JSType>) = list
+        .into_iter()
+        .partition(|v| matches!(v, JSType::Undefined));
+    let mut sorted = Vec::with_capacity(list.len() + undefined.len());
+    if list.len() > 1 {
+        let right = list.split_off(list.len() / 2);
+        let left = sort_values(program, list, compare)?;
+        let right = sort_values(program, right, compare)?;
+        let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            if compare_values(program, b, a, compare)? == Ordering::Less {
+                sorted.push(right.next().unwrap());
+            } else {
+                sorted.push(left.next().unwrap());
+            }
+        }
+        sorted.extend(left);
+        sorted.extend(right);
+    } else {
+        sorted = list;
+    }
+    sorted.extend(undefined);
+    Ok(sorted)
+}
+
+fn array_sort(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let compare = comparator(program, arg(&args, 0))?;
+    let (target, length) = this_array(program, &this, "sort")?;
+    let values: Vec<JSType> = to_list(program, &target, 0, length)?
+        .into_iter()
+        .flatten()
+        .collect();
+    let count = values.len();
+    let sorted = sort_values(program, values, &compare)?;
+    for (index, value) in sorted.into_iter().enumerate() {
+        set_index(program, &target, index, value)?;
+    }
+    //空位移到末尾
+    for index in count..length {
+        delete_index(program, &target, index)?;
+    }
+    Ok(target)
+}
+
+fn array_to_sorted(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let compare = comparator(program, arg(&args, 0))?;
+    let (target, length) = this_array(program, &this, "toSorted")?;
+    let values = to_dense_list(program, &target, length)?;
+    let sorted = sort_values(program, values, &compare)?;
+    Ok(program.new_array(sorted))
+}
+
+fn array_to_reversed(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "toReversed")?;
+    let mut values = to_dense_list(program, &target, length)?;
+    values.reverse();
+    Ok(program.new_array(values))
+}
+
+fn array_to_spliced(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "toSpliced")?;
+    let (start, delete_count) = splice_range(program, &args, length)?;
+    let mut values = to_dense_list(program, &target, length)?;
+    values.splice(start..start + delete_count, args.into_iter().skip(2));
+    Ok(program.new_array(values))
+}
+
+fn array_with(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "with")?;
+    let relative = to_integer(program, &arg(&args, 0))?;
+    let index = if relative < 0.0 {
+        length as f64 + relative
+    } else {
+        relative
+    };
+    if index < 0.0 || index >= length as f64 {
+        return Err(err(&format!(
+            "Uncaught RangeError: Invalid index : {}",
+            JSType::number(relative).to_key()
+        )));
+    }
+    let mut values = to_dense_list(program, &target, length)?;
+    values[index as usize] = arg(&args, 1);
+    Ok(program.new_array(values))
+}
+
+/// indexOf/includes的起始位置
+fn start_index(program: &mut Program, value: &JSType, length: usize) -> Result<usize, String> {
+    let n = to_integer(program, value)?;
+    if n >= length as f64 {
+        return Ok(length);
+    }
+    Ok(relative_index(n, length))
+}
+
+pub(super) fn array_index_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "indexOf")?;
+    let search = arg(&args, 0);
+    let start = start_index(program, &arg(&args, 1), length)?;
+    for index in start..length {
+        if has_index(program, &target, index)?
+            && get_index(program, &target, index)?.strict_equal(&search)
+        {
+            return Ok(JSType::Int(index as i64));
+        }
+    }
+    Ok(JSType::Int(-1))
+}
+
+pub(super) fn array_last_index_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "lastIndexOf")?;
+    if length == 0 {
+        return Ok(JSType::Int(-1));
+    }
+    let search = arg(&args, 0);
+    let from = match args.get(1) {
+        Some(value) => to_integer(program, value)?,
+        None => length as f64 - 1.0,
+    };
+    let from = if from < 0.0 {
+        length as f64 + from
+    } else {
+        from.min(length as f64 - 1.0)
+    };
+    if from < 0.0 {
+        return Ok(JSType::Int(-1));
+    }
+    for index in (0..=from as usize).rev() {
+        if has_index(program, &target, index)?
+            && get_index(program, &target, index)?.strict_equal(&search)
+        {
+            return Ok(JSType::Int(index as i64));
+        }
+    }
+    Ok(JSType::Int(-1))
+}
+
+/// SameValueZero, 空位作为undefined
+pub(super) fn array_includes(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "includes")?;
+    let search = arg(&args, 0);
+    let start = start_index(program, &arg(&args, 1), length)?;
+    let is_nan = |value: &JSType| matches!(value, JSType::Float(n) if n.is_nan());
+    let search_nan = is_nan(&search);
+    for index in start..length {
+        let value = get_index(program, &target, index)?;
+        if value.strict_equal(&search) || (search_nan && is_nan(&value)) {
+            return Ok(JSType::Bool(true));
+        }
+    }
+    Ok(JSType::Bool(false))
+}
+
+/// find系列方法, 空位作为undefined传给回调
+fn find_item(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+    method: &str,
+    reverse: bool,
+) -> Result<Option<(usize, JSType)>, String> {
+    let (target, length) = this_array(program, &this, method)?;
+    let predicate = callback(program, &arg(&args, 0))?;
+    let this_arg = arg(&args, 1);
+    let indexes: Box<dyn Iterator<Item = usize>> = if reverse {
+        Box::new((0..length).rev())
+    } else {
+        Box::new(0..length)
+    };
+    for index in indexes {
+        let value = get_index(program, &target, index)?;
+        let args = vec![value.clone(), JSType::Int(index as i64), target.clone()];
+        if program
+            .call(&predicate, this_arg.clone(), args)?
+            .is_truthy()
+        {
+            return Ok(Some((index, value)));
+        }
+    }
+    Ok(None)
+}
+
+pub(super) fn array_find(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let found = find_item(program, this, args, "find", false)?;
+    Ok(found.map_or(JSType::Undefined, |(_, value)| value))
+}
+
+pub(super) fn array_find_index(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let found = find_item(program, this, args, "findIndex", false)?;
+    Ok(JSType::Int(found.map_or(-1, |(index, _)| index as i64)))
+}
+
+pub(super) fn array_find_last(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let found = find_item(program, this, args, "findLast", true)?;
+    Ok(found.map_or(JSType::Undefined, |(_, value)| value))
+}
+
+pub(super) fn array_find_last_index(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let found = find_item(program, this, args, "findLastIndex", true)?;
+    Ok(JSType::Int(found.map_or(-1, |(index, _)| index as i64)))
+}
+
+/// 按顺序对存在的元素调用回调, 跳过空位; f返回Some时提前结束
+fn each_item(
+    program: &mut Program,
+    this: JSType,
+    args: &[JSType],
+    method: &str,
+    mut f: impl FnMut(usize, JSType, JSType) -> Option<JSType>,
+) -> Result<Option<JSType>, String> {
+    let (target, length) = this_array(program, &this, method)?;
+    let func = callback(program, &arg(args, 0))?;
+    let this_arg = arg(args, 1);
+    for index in 0..length {
+        if !has_index(program, &target, index)? {
+            continue;
+        }
+        let value = get_index(program, &target, index)?;
+        let call_args = vec![value.clone(), JSType::Int(index as i64), target.clone()];
+        let result = program.call(&func, this_arg.clone(), call_args)?;
+        if let Some(done) = f(index, value, result) {
+            return Ok(Some(done));
+        }
+    }
+    Ok(None)
+}
+
+pub(super) fn array_for_each(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    each_item(program, this, &args, "forEach", |_, _, _| None)?;
+    Ok(JSType::Undefined)
+}
+
+/// map保留空位
+fn array_map(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "map")?;
+    callback(program, &arg(&args, 0))?;
+    let created = array_species_create(program, &target, length)?;
+    let mut list = vec![None; length];
+    each_item(program, this, &args, "map", |index, _, result| {
+        list[index] = Some(result);
+        None
+    })?;
+    match created {
+        Some(created) => fill_array(program, created, list, false),
+        None => Ok(program.new_sparse_array(list)),
+    }
+}
+
+fn array_filter(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, _) = this_array(program, &this, "filter")?;
+    callback(program, &arg(&args, 0))?;
+    let created = array_species_create(program, &target, 0)?;
+    let mut list = Vec::new();
+    each_item(program, this, &args, "filter", |_, value, result| {
+        if result.is_truthy() {
+            list.push(Some(value));
+        }
+        None
+    })?;
+    match created {
+        Some(created) => fill_array(program, created, list, false),
+        None => Ok(program.new_sparse_array(list)),
+    }
+}
+
+pub(super) fn array_some(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let found = each_item(program, this, &args, "some", |_, _, result| {
+        result.is_truthy().then_some(JSType::Bool(true))
+    })?;
+    Ok(found.unwrap_or(JSType::Bool(false)))
+}
+
+pub(super) fn array_every(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let found = each_item(program, this, &args, "every", |_, _, result| {
+        (!result.is_truthy()).then_some(JSType::Bool(false))
+    })?;
+    Ok(found.unwrap_or(JSType::Bool(true)))
+}
+
+/// reduce/reduceRight, 跳过空位
+fn reduce(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+    method: &str,
+    reverse: bool,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, method)?;
+    let func = callback(program, &arg(&args, 0))?;
+    let mut indexes: Box<dyn Iterator<Item = usize>> = if reverse {
+        Box::new((0..length).rev())
+    } else {
+        Box::new(0..length)
+    };
+    let mut accumulator = match args.get(1) {
+        Some(value) => value.clone(),
+        None => loop {
+            let Some(index) = indexes.next() else {
+                return Err(err(
+                    "Uncaught TypeError: Reduce of empty array with no initial value",
+                ));
+            };
+            if has_index(program, &target, index)? {
+                break get_index(program, &target, index)?;
+            }
+        },
+    };
+    for index in indexes {
+        if !has_index(program, &target, index)? {
+            continue;
+        }
+        let value = get_index(program, &target, index)?;
+        let call_args = vec![
+            accumulator,
+            value,
+            JSType::Int(index as i64),
+            target.clone(),
+        ];
+        accumulator = program.call(&func, JSType::Undefined, call_args)?;
+    }
+    Ok(accumulator)
+}
+
+pub(super) fn array_reduce(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    reduce(program, this, args, "reduce", false)
+}
+
+pub(super) fn array_reduce_right(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    reduce(program, this, args, "reduceRight", true)
+}
+
+/// FlattenIntoArray, mapper为flatMap的回调及this
+fn flatten_into(
+    program: &mut Program,
+    list: &mut Vec<JSType>,
+    source: &JSType,
+    depth: f64,
+    mapper: Option<(&JSType, &JSType)>,
+) -> Result<(), String> {
+    let length = length_of(program, source)?;
+    for index in 0..length {
+        if !has_index(program, source, index)? {
+            continue;
+        }
+        let mut value = get_index(program, source, index)?;
+        if let Some((func, this_arg)) = mapper {
+            let args = vec![value, JSType::Int(index as i64), source.clone()];
+            value = program.call(func, this_arg.clone(), args)?;
+        }
+        if depth > 0.0 && program.is_array(&value) {
+            flatten_into(program, list, &value, depth - 1.0, None)?;
+        } else {
+            list.push(value);
+        }
+    }
+    Ok(())
+}
+
+fn array_flat(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (target, _) = this_array(program, &this, "flat")?;
+    let depth = match arg(&args, 0) {
+        JSType::Undefined => 1.0,
+        value => to_integer(program, &value)?,
+    };
+    let created = array_species_create(program, &target, 0)?;
+    let mut list = Vec::new();
+    flatten_into(program, &mut list, &target, depth, None)?;
+    flat_result(program, created, list)
+}
+
+/// flat/flatMap的结果
+fn flat_result(
+    program: &mut Program,
+    created: Option<JSType>,
+    list: Vec<JSType>,
+) -> Result<JSType, String> {
+    match created {
+        Some(created) => fill_array(
+            program,
+            created,
+            list.into_iter().map(Some).collect(),
+            false,
+        ),
+        None => Ok(program.new_array(list)),
+    }
+}
+
+fn array_flat_map(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, _) = this_array(program, &this, "flatMap")?;
+    let func = callback(program, &arg(&args, 0))?;
+    let this_arg = arg(&args, 1);
+    let created = array_species_create(program, &target, 0)?;
+    let mut list = Vec::new();
+    flatten_into(program, &mut list, &target, 1.0, Some((&func, &this_arg)))?;
+    flat_result(program, created, list)
+}
+
+/// 可选的 start/end 参数, end默认为length
+fn range_args(
+    program: &mut Program,
+    start: &JSType,
+    end: &JSType,
+    length: usize,
+) -> Result<(usize, usize), String> {
+    let start = relative_index(to_integer(program, start)?, length);
+    let end = match end {
+        JSType::Undefined => length,
+        value => relative_index(to_integer(program, value)?, length),
+    };
+    Ok((start, end))
+}
+
+pub(super) fn array_fill(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "fill")?;
+    let value = arg(&args, 0);
+    let (start, end) = range_args(program, &arg(&args, 1), &arg(&args, 2), length)?;
+    for index in start..end {
+        set_index(program, &target, index, value.clone())?;
+    }
+    Ok(target)
+}
+
+pub(super) fn array_copy_within(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (target, length) = this_array(program, &this, "copyWithin")?;
+    let to = relative_index(to_integer(program, &arg(&args, 0))?, length);
+    let (from, end) = range_args(program, &arg(&args, 1), &arg(&args, 2), length)?;
+    let count = end.saturating_sub(from).min(length - to);
+    move_items(program, &target, from, to, count)?;
+    Ok(target)
+}
+
+fn array_is_array(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(program.is_array(&arg(&args, 0))))
+}
+
+/// Array.from(items, mapFn, thisArg), 可迭代对象按迭代器读取, 否则作为类数组
+/// this为构造函数时通过其创建结果, 如Array的子类
+fn array_from(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let items = arg(&args, 0);
+    let mapper = match arg(&args, 1) {
+        JSType::Undefined => None,
+        value => Some(callback(program, &value)?),
+    };
+    let this_arg = arg(&args, 2);
+    if let JSType::Undefined | JSType::NULL = items {
+        return Err(err(&format!(
+            "Uncaught TypeError: {} is not iterable (cannot read property Symbol(Symbol.iterator))",
+            program.display(&items)
+        )));
+    }
+    let key = PropertyKey::Symbol(program.intrinsics.symbol_iterator.clone());
+    let method = program.get_property(&items, key)?;
+    let (created, values) = if matches!(method, JSType::Undefined | JSType::NULL) {
+        let target = JSType::Object(program.object_value(&items)?);
+        let length = length_of(program, &target)?;
+        let created = construct_array(program, &this, vec![JSType::Int(length as i64)])?;
+        (created, to_dense_list(program, &target, length)?)
+    } else {
+        let created = construct_array(program, &this, Vec::new())?;
+        (created, program.iterate_to_list(&items)?)
+    };
+    let list = match mapper {
+        Some(mapper) => {
+            let mut list = Vec::with_capacity(values.len());
+            for (index, value) in values.into_iter().enumerate() {
+                let args = vec![value, JSType::Int(index as i64)];
+                list.push(program.call(&mapper, this_arg.clone(), args)?);
+            }
+            list
+        }
+        None => values,
+    };
+    match created {
+        Some(created) => fill_array(program, created, list.into_iter().map(Some).collect(), true),
+        None => Ok(program.new_array(list)),
+    }
+}
+
+/// Array.of(...items), this为构造函数时通过其创建结果
+fn array_of(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let length = JSType::Int(args.len() as i64);
+    match construct_array(program, &this, vec![length])? {
+        Some(created) => fill_array(program, created, args.into_iter().map(Some).collect(), true),
+        None => Ok(program.new_array(args)),
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use num_traits::{One, Signed};
+
+use super::{arg, to_integer};
+use crate::jsparser::object::{JSObject, NativeFn, ObjectKind, Property};
+use crate::jsparser::program::{number_to_bigint, number_to_string, JSType, Program};
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_bigint(&mut self) {
+        let object_prototype = self.intrinsics.object_prototype;
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.bigint_prototype = proto;
+        let methods: [(&'static str, usize, NativeFn); 3] = [
+            ("toString", 0, bigint_to_string),
+            ("toLocaleString", 0, bigint_to_locale_string),
+            ("valueOf", 0, bigint_value_of),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        self.define_to_string_tag(proto, "BigInt");
+
+        //与Symbol相同, 只能作为函数调用
+        let bigint = self.native_function("BigInt", 1, bigint_call, None);
+        let mut prop = Property::readonly(JSType::Object(proto));
+        prop.configurable = false;
+        self.define_property(bigint, "prototype", prop);
+        self.define_property(
+            proto,
+            "constructor",
+            Property::hidden(JSType::Object(bigint)),
+        );
+        self.define_method(bigint, "asIntN", 2, bigint_as_int_n);
+        self.define_method(bigint, "asUintN", 2, bigint_as_uint_n);
+        self.bind_value("BigInt".to_string(), JSType::Object(bigint));
+    }
+}
+
+/// BigInt(value), 整数的Number可以转换, 其余与ToBigInt相同
+fn bigint_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let value = program.primitive(&arg(&args, 0), Some("number"))?;
+    if let JSType::Int(_) | JSType::Float(_) = value {
+        let n = value.to_number();
+        return match number_to_bigint(n) {
+            Some(n) => Ok(JSType::BigInt(Arc::new(n))),
+            None => Err(err(&format!(
+                "Uncaught RangeError: The number {} cannot be converted to a BigInt because it is not an integer",
+                number_to_string(n)
+            ))),
+        };
+    }
+    Ok(JSType::BigInt(program.bigint_value(&value)?))
+}
+
+/// thisBigIntValue
+fn this_bigint_value(
+    program: &Program,
+    this: &JSType,
+    method: &str,
+) -> Result<Arc<BigInt>, String> {
+    match this {
+        JSType::BigInt(n) => return Ok(n.clone()),
+        JSType::Object(id) => {
+            if let ObjectKind::Primitive(JSType::BigInt(n)) = &program.obj(*id).kind {
+                return Ok(n.clone());
+            }
+        }
+        _ => {}
+    }
+    Err(err(&format!(
+        "Uncaught TypeError: BigInt.prototype.{} requires that 'this' be a BigInt",
+        method
+    )))
+}
+
+/// toString(radix)
+fn bigint_to_string(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let n = this_bigint_value(program, &this, "toString")?;
+    let radix = match arg(&args, 0) {
+        JSType::Undefined => 10.0,
+        radix => to_integer(program, &radix)?,
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(err(
+            "Uncaught RangeError: toString() radix must be between 2 and 36",
+        ));
+    }
+    Ok(JSType::String(n.to_str_radix(radix as u32).into()))
+}
+
+/// toLocaleString(locales, options), 格式见Intl.NumberFormat
+fn bigint_to_locale_string(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let n = this_bigint_value(program, &this, "toLocaleString")?;
+    let s = program.number_to_locale_string(&JSType::BigInt(n), &arg(&args, 0), &arg(&args, 1))?;
+    Ok(JSType::String(s.into()))
+}
+
+fn bigint_value_of(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::BigInt(this_bigint_value(
+        program, &this, "valueOf",
+    )?))
+}
+
+/// asIntN/asUintN的参数, bits为ToIndex
+fn bits_and_bigint(program: &mut Program, args: &[JSType]) -> Result<(u64, Arc<BigInt>), String> {
+    let bits = to_integer(program, &arg(args, 0))?;
+    if !(0.0..=9007199254740991.0).contains(&bits) {
+        return Err(err(
+            "Uncaught RangeError: Invalid value: not (convertible to) a safe integer",
+        ));
+    }
+    Ok((bits as u64, program.bigint_value(&arg(args, 1))?))
+}
+
+/// n对2^bits取模, 结果在 [0, 2^bits)
+fn modulo_pow2(n: &BigInt, bits: u64) -> Result<BigInt, String> {
+    if !n.is_negative() && n.bits() <= bits {
+        return Ok(n.clone());
+    }
+    if bits > 1 << 30 {
+        return Err(err("Uncaught RangeError: Maximum BigInt size exceeded"));
+    }
+    let modulus = BigInt::one() << bits;
+    Ok(((n % &modulus) + &modulus) % &modulus)
+}
+
+/// BigInt.asIntN(bits, bigint), 截断为有符号的bits位整数
+fn bigint_as_int_n(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (bits, n) = bits_and_bigint(program, &args)?;
+    if n.bits() < bits {
+        return Ok(JSType::BigInt(n));
+    }
+    let m = modulo_pow2(&n, bits)?;
+    let result = if bits > 0 && m.bit(bits - 1) {
+        m - (BigInt::one() << bits)
+    } else {
+        m
+    };
+    Ok(JSType::BigInt(Arc::new(result)))
+}
+
+/// BigInt.asUintN(bits, bigint), 截断为无符号的bits位整数
+fn bigint_as_uint_n(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let (bits, n) = bits_and_bigint(program, &args)?;
+    Ok(JSType::BigInt(Arc::new(modulo_pow2(&n, bits)?)))
+}
@@ -0,0 +1,511 @@
+use std::collections::{HashMap, HashSet};
+
+use super::arg;
+use crate::jsparser::object::{
+    IterationKind, IteratorState, JSObject, NativeFn, ObjectId, ObjectKind, PropertyKey,
+};
+use crate::jsparser::program::{JSType, MapData, Program};
+use crate::jsparser::utility::err;
+
+/// Map/Set/WeakMap/WeakSet
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Collection {
+    Map,
+    Set,
+    WeakMap,
+    WeakSet,
+}
+
+impl Collection {
+    fn name(self) -> &'static str {
+        match self {
+            Collection::Map => "Map",
+            Collection::Set => "Set",
+            Collection::WeakMap => "WeakMap",
+            Collection::WeakSet => "WeakSet",
+        }
+    }
+    /// 构造时的迭代项为 [key, value]
+    fn is_keyed(self) -> bool {
+        matches!(self, Collection::Map | Collection::WeakMap)
+    }
+}
+
+impl Program {
+    pub(super) fn init_collections(&mut self) {
+        let object_prototype = self.intrinsics.object_prototype;
+        let iterator_prototype = self.intrinsics.iterator_prototype;
+        let symbol_iterator = PropertyKey::Symbol(self.intrinsics.symbol_iterator.clone());
+
+        //Map
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.map_prototype = proto;
+        self.define_constructor("Map", 0, proto, map_call, map_construct);
+        let methods: [(&'static str, usize, NativeFn); 9] = [
+            ("get", 1, map_get),
+            ("set", 2, map_set),
+            ("has", 1, map_has),
+            ("delete", 1, map_delete),
+            ("clear", 0, map_clear),
+            ("forEach", 1, map_for_each),
+            ("keys", 0, map_keys),
+            ("values", 0, map_values),
+            ("entries", 0, map_entries),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        self.define_size_getter(proto, map_size);
+        let entries = self.get_own_property(proto, &"entries".into()).unwrap();
+        self.define_property(proto, symbol_iterator.clone(), entries);
+        self.define_to_string_tag(proto, "Map");
+        let iterator = self.alloc(JSObject::new(
+            ObjectKind::Ordinary,
+            Some(iterator_prototype),
+        ));
+        self.intrinsics.map_iterator_prototype = iterator;
+        self.define_method(iterator, "next", 0, super::iterator_next);
+        self.define_to_string_tag(iterator, "Map Iterator");
+
+        //Set, keys与values为同一个方法
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.set_prototype = proto;
+        self.define_constructor("Set", 0, proto, set_call, set_construct);
+        let methods: [(&'static str, usize, NativeFn); 7] = [
+            ("has", 1, set_has),
+            ("add", 1, set_add),
+            ("delete", 1, set_delete),
+            ("clear", 0, set_clear),
+            ("entries", 0, set_entries),
+            ("forEach", 1, set_for_each),
+            ("values", 0, set_values),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        self.define_size_getter(proto, set_size);
+        let values = self.get_own_property(proto, &"values".into()).unwrap();
+        self.define_property(proto, "keys", values.clone());
+        self.define_property(proto, symbol_iterator, values);
+        self.define_to_string_tag(proto, "Set");
+        let iterator = self.alloc(JSObject::new(
+            ObjectKind::Ordinary,
+            Some(iterator_prototype),
+        ));
+        self.intrinsics.set_iterator_prototype = iterator;
+        self.define_method(iterator, "next", 0, super::iterator_next);
+        self.define_to_string_tag(iterator, "Set Iterator");
+
+        //WeakMap
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.weak_map_prototype = proto;
+        self.define_constructor("WeakMap", 0, proto, weak_map_call, weak_map_construct);
+        self.define_method(proto, "delete", 1, weak_map_delete);
+        self.define_method(proto, "get", 1, weak_map_get);
+        self.define_method(proto, "set", 2, weak_map_set);
+        self.define_method(proto, "has", 1, weak_map_has);
+        self.define_to_string_tag(proto, "WeakMap");
+
+        //WeakSet
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.weak_set_prototype = proto;
+        self.define_constructor("WeakSet", 0, proto, weak_set_call, weak_set_construct);
+        self.define_method(proto, "delete", 1, weak_set_delete);
+        self.define_method(proto, "has", 1, weak_set_has);
+        self.define_method(proto, "add", 1, weak_set_add);
+        self.define_to_string_tag(proto, "WeakSet");
+    }
+
+    fn define_size_getter(&mut self, proto: ObjectId, func: NativeFn) {
+        let getter = self.native_function("get size", 0, func, None);
+        self.define_accessor(proto, &"size".into(), true, JSType::Object(getter), false);
+    }
+}
+
+/// this为对应的集合时返回其id
+fn this_collection(
+    program: &Program,
+    this: &JSType,
+    kind: Collection,
+    method: &str,
+) -> Result<ObjectId, String> {
+    if let JSType::Object(id) = this {
+        let matched = matches!(
+            (&program.obj(*id).kind, kind),
+            (ObjectKind::Map(_), Collection::Map)
+                | (ObjectKind::Set(_), Collection::Set)
+                | (ObjectKind::WeakMap(_), Collection::WeakMap)
+                | (ObjectKind::WeakSet(_), Collection::WeakSet)
+        );
+        if matched {
+            return Ok(*id);
+        }
+    }
+    Err(err(&format!(
+        "Uncaught TypeError: Method {}.prototype.{} called on incompatible receiver {}",
+        kind.name(),
+        method,
+        program.display(this)
+    )))
+}
+
+fn map_data(program: &mut Program, id: ObjectId) -> &mut MapData {
+    match &mut program.obj_mut(id).kind {
+        ObjectKind::Map(data) | ObjectKind::Set(data) => data,
+        _ => unreachable!(),
+    }
+}
+
+fn weak_map_data(program: &mut Program, id: ObjectId) -> &mut HashMap<ObjectId, JSType> {
+    match &mut program.obj_mut(id).kind {
+        ObjectKind::WeakMap(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+fn weak_set_data(program: &mut Program, id: ObjectId) -> &mut HashSet<ObjectId> {
+    match &mut program.obj_mut(id).kind {
+        ObjectKind::WeakSet(set) => set,
+        _ => unreachable!(),
+    }
+}
+
+/// new Map(iterable) 等, 逐项调用set/add
+fn construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+    collection: Collection,
+) -> Result<JSType, String> {
+    let proto = program.get_prototype_from_constructor(&new_target, collection.name())?;
+    let kind = match collection {
+        Collection::Map => ObjectKind::Map(Box::default()),
+        Collection::Set => ObjectKind::Set(Box::default()),
+        Collection::WeakMap => ObjectKind::WeakMap(HashMap::new()),
+        Collection::WeakSet => ObjectKind::WeakSet(HashSet::new()),
+    };
+    let target = JSType::Object(program.alloc(JSObject::new(kind, Some(proto))));
+    let iterable = arg(&args, 0);
+    if let JSType::Undefined | JSType::NULL = iterable {
+        return Ok(target);
+    }
+    let name = if collection.is_keyed() { "set" } else { "add" };
+    let adder = program.get_property(&target, name)?;
+    if !program.is_callable(&adder) {
+        return Err(err(&format!(
+            "Uncaught TypeError: '{}' returned for property '{}' of object '#<{}>' is not a function",
+            program.display(&adder),
+            name,
+            collection.name()
+        )));
+    }
+    let record = program.get_iterator(&iterable)?;
+    while let Some(item) = program.iterator_step(&record)? {
+        let args = if collection.is_keyed() {
+            if !matches!(item, JSType::Object(_)) {
+                program.iterator_close(&record)?;
+                return Err(err(&format!(
+                    "Uncaught TypeError: Iterator value {} is not an entry object",
+                    program.display(&item)
+                )));
+            }
+            let key = program.get_property(&item, "0")?;
+            let value = program.get_property(&item, "1")?;
+            vec![key, value]
+        } else {
+            vec![item]
+        };
+        if let Err(e) = program.call(&adder, target.clone(), args) {
+            _ = program.iterator_close(&record);
+            return Err(e);
+        }
+    }
+    Ok(target)
+}
+
+fn requires_new(collection: Collection) -> Result<JSType, String> {
+    Err(err(&format!(
+        "Uncaught TypeError: Constructor {} requires 'new'",
+        collection.name()
+    )))
+}
+
+/// forEach(callback, thisArg), 迭代中添加的项也会被访问
+fn for_each(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+    collection: Collection,
+) -> Result<JSType, String> {
+    let id = this_collection(program, &this, collection, "forEach")?;
+    let callback = arg(&args, 0);
+    if !program.is_callable(&callback) {
+        return Err(err(&format!(
+            "Uncaught TypeError: {} is not a function",
+            program.display(&callback)
+        )));
+    }
+    let this_arg = arg(&args, 1);
+    let mut pos = 0;
+    loop {
+        let data = map_data(program, id);
+        let Some((index, key, value)) = data.next_entry(pos) else {
+            break;
+        };
+        let call_args = vec![value.clone(), key.clone(), this.clone()];
+        pos = index + 1;
+        program.call(&callback, this_arg.clone(), call_args)?;
+    }
+    Ok(JSType::Undefined)
+}
+
+fn create_iterator(
+    program: &mut Program,
+    this: JSType,
+    collection: Collection,
+    method: &str,
+    kind: IterationKind,
+) -> Result<JSType, String> {
+    let id = this_collection(program, &this, collection, method)?;
+    Ok(program.new_iterator(IteratorState::Map(id, 0, kind)))
+}
+
+fn map_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new(Collection::Map)
+}
+
+fn map_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    construct(program, args, new_target, Collection::Map)
+}
+
+fn map_get(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Map, "get")?;
+    let value = map_data(program, id).get(&arg(&args, 0)).cloned();
+    Ok(value.unwrap_or(JSType::Undefined))
+}
+
+fn map_set(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Map, "set")?;
+    map_data(program, id).set(arg(&args, 0), arg(&args, 1));
+    Ok(this)
+}
+
+fn map_has(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Map, "has")?;
+    Ok(JSType::Bool(map_data(program, id).has(&arg(&args, 0))))
+}
+
+fn map_delete(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Map, "delete")?;
+    Ok(JSType::Bool(map_data(program, id).delete(&arg(&args, 0))))
+}
+
+fn map_clear(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Map, "clear")?;
+    map_data(program, id).clear();
+    Ok(JSType::Undefined)
+}
+
+fn map_for_each(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    for_each(program, this, args, Collection::Map)
+}
+
+fn map_keys(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_iterator(program, this, Collection::Map, "keys", IterationKind::Keys)
+}
+
+fn map_values(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_iterator(
+        program,
+        this,
+        Collection::Map,
+        "values",
+        IterationKind::Values,
+    )
+}
+
+fn map_entries(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_iterator(
+        program,
+        this,
+        Collection::Map,
+        "entries",
+        IterationKind::Entries,
+    )
+}
+
+fn map_size(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Map, "size")?;
+    Ok(JSType::Int(map_data(program, id).len() as i64))
+}
+
+fn set_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new(Collection::Set)
+}
+
+fn set_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    construct(program, args, new_target, Collection::Set)
+}
+
+fn set_has(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Set, "has")?;
+    Ok(JSType::Bool(map_data(program, id).has(&arg(&args, 0))))
+}
+
+fn set_add(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Set, "add")?;
+    let value = arg(&args, 0);
+    let data = map_data(program, id);
+    if !data.has(&value) {
+        data.set(value.clone(), value);
+    }
+    Ok(this)
+}
+
+fn set_delete(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Set, "delete")?;
+    Ok(JSType::Bool(map_data(program, id).delete(&arg(&args, 0))))
+}
+
+fn set_clear(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Set, "clear")?;
+    map_data(program, id).clear();
+    Ok(JSType::Undefined)
+}
+
+fn set_entries(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_iterator(
+        program,
+        this,
+        Collection::Set,
+        "entries",
+        IterationKind::Entries,
+    )
+}
+
+fn set_for_each(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    for_each(program, this, args, Collection::Set)
+}
+
+fn set_values(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    create_iterator(
+        program,
+        this,
+        Collection::Set,
+        "values",
+        IterationKind::Values,
+    )
+}
+
+fn set_size(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::Set, "size")?;
+    Ok(JSType::Int(map_data(program, id).len() as i64))
+}
+
+/// WeakMap的键及WeakSet的值只能是对象
+fn weak_key(value: &JSType, collection: Collection) -> Result<ObjectId, String> {
+    match value {
+        JSType::Object(id) => Ok(*id),
+        _ if collection == Collection::WeakMap => Err(err(
+            "Uncaught TypeError: Invalid value used as weak map key",
+        )),
+        _ => Err(err("Uncaught TypeError: Invalid value used in weak set")),
+    }
+}
+
+fn weak_map_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new(Collection::WeakMap)
+}
+
+fn weak_map_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    construct(program, args, new_target, Collection::WeakMap)
+}
+
+fn weak_map_get(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakMap, "get")?;
+    let JSType::Object(key) = arg(&args, 0) else {
+        return Ok(JSType::Undefined);
+    };
+    let value = weak_map_data(program, id).get(&key).cloned();
+    Ok(value.unwrap_or(JSType::Undefined))
+}
+
+fn weak_map_set(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakMap, "set")?;
+    let key = weak_key(&arg(&args, 0), Collection::WeakMap)?;
+    weak_map_data(program, id).insert(key, arg(&args, 1));
+    Ok(this)
+}
+
+fn weak_map_has(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakMap, "has")?;
+    let JSType::Object(key) = arg(&args, 0) else {
+        return Ok(JSType::Bool(false));
+    };
+    Ok(JSType::Bool(weak_map_data(program, id).contains_key(&key)))
+}
+
+fn weak_map_delete(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakMap, "delete")?;
+    let JSType::Object(key) = arg(&args, 0) else {
+        return Ok(JSType::Bool(false));
+    };
+    Ok(JSType::Bool(
+        weak_map_data(program, id).remove(&key).is_some(),
+    ))
+}
+
+fn weak_set_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new(Collection::WeakSet)
+}
+
+fn weak_set_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    construct(program, args, new_target, Collection::WeakSet)
+}
+
+fn weak_set_add(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakSet, "add")?;
+    let value = weak_key(&arg(&args, 0), Collection::WeakSet)?;
+    weak_set_data(program, id).insert(value);
+    Ok(this)
+}
+
+fn weak_set_has(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakSet, "has")?;
+    let JSType::Object(value) = arg(&args, 0) else {
+        return Ok(JSType::Bool(false));
+    };
+    Ok(JSType::Bool(weak_set_data(program, id).contains(&value)))
+}
+
+fn weak_set_delete(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = this_collection(program, &this, Collection::WeakSet, "delete")?;
+    let JSType::Object(value) = arg(&args, 0) else {
+        return Ok(JSType::Bool(false));
+    };
+    Ok(JSType::Bool(weak_set_data(program, id).remove(&value)))
+}
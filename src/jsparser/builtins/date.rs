@@ -0,0 +1,1073 @@
+use super::arg;
+use crate::jsparser::object::{JSObject, NativeFn, ObjectKind, Property, PropertyKey};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::utility::err;
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+const MS_PER_MINUTE: f64 = 60_000.0;
+const MS_PER_SECOND: f64 = 1_000.0;
+
+const WEEK_DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// 日期的各个部分, 顺序与Date(year, month, date, hours, minutes, seconds, ms)的参数相同
+#[derive(Clone, Copy)]
+enum Field {
+    Year,
+    Month,
+    Date,
+    Hours,
+    Minutes,
+    Seconds,
+    Milliseconds,
+}
+
+impl Program {
+    pub(super) fn init_date(&mut self) {
+        let object_prototype = self.intrinsics.object_prototype;
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.date_prototype = proto;
+        let date = self.define_constructor("Date", 7, proto, date_call, date_construct);
+        self.define_method(date, "now", 0, date_now);
+        self.define_method(date, "parse", 1, date_parse);
+        self.define_method(date, "UTC", 7, date_utc);
+        let methods: [(&'static str, usize, NativeFn); 43] = [
+            ("getTime", 0, date_value_of),
+            ("valueOf", 0, date_value_of),
+            ("getTimezoneOffset", 0, date_get_timezone_offset),
+            ("getFullYear", 0, date_get_full_year),
+            ("getMonth", 0, date_get_month),
+            ("getDate", 0, date_get_date),
+            ("getDay", 0, date_get_day),
+            ("getHours", 0, date_get_hours),
+            ("getMinutes", 0, date_get_minutes),
+            ("getSeconds", 0, date_get_seconds),
+            ("getMilliseconds", 0, date_get_milliseconds),
+            ("getUTCFullYear", 0, date_get_utc_full_year),
+            ("getUTCMonth", 0, date_get_utc_month),
+            ("getUTCDate", 0, date_get_utc_date),
+            ("getUTCDay", 0, date_get_utc_day),
+            ("getUTCHours", 0, date_get_utc_hours),
+            ("getUTCMinutes", 0, date_get_utc_minutes),
+            ("getUTCSeconds", 0, date_get_utc_seconds),
+            ("getUTCMilliseconds", 0, date_get_utc_milliseconds),
+            ("setTime", 1, date_set_time),
+            ("setFullYear", 3, date_set_full_year),
+            ("setMonth", 2, date_set_month),
+            ("setDate", 1, date_set_date),
+            ("setHours", 4, date_set_hours),
+            ("setMinutes", 3, date_set_minutes),
+            ("setSeconds", 2, date_set_seconds),
+            ("setMilliseconds", 1, date_set_milliseconds),
+            ("setUTCFullYear", 3, date_set_utc_full_year),
+            ("setUTCMonth", 2, date_set_utc_month),
+            ("setUTCDate", 1, date_set_utc_date),
+            ("setUTCHours", 4, date_set_utc_hours),
+            ("setUTCMinutes", 3, date_set_utc_minutes),
+            ("setUTCSeconds", 2, date_set_utc_seconds),
+            ("setUTCMilliseconds", 1, date_set_utc_milliseconds),
+            ("toISOString", 0, date_to_iso_string),
+            ("toJSON", 1, date_to_json),
+            ("toString", 0, date_to_string),
+            ("toDateString", 0, date_to_date_string),
+            ("toTimeString", 0, date_to_time_string),
+            ("toUTCString", 0, date_to_utc_string),
+            ("toLocaleString", 0, date_to_locale_string),
+            ("toLocaleDateString", 0, date_to_locale_date_string),
+            ("toLocaleTimeString", 0, date_to_locale_time_string),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        let to_gmt_string = self.get_own_property(proto, &"toUTCString".into()).unwrap();
+        self.define_property(proto, "toGMTString", to_gmt_string);
+        let to_primitive = self.native_function("[Symbol.toPrimitive]", 1, date_to_primitive, None);
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_to_primitive.clone());
+        self.define_property(proto, key, Property::readonly(JSType::Object(to_primitive)));
+    }
+
+    /// LocalTime(t)
+    fn local_time(&self, t: f64) -> f64 {
+        t + self.local_offset(t) as f64 * MS_PER_MINUTE
+    }
+
+    /// UTC(t), t为本地时间
+    fn utc_time(&self, t: f64) -> f64 {
+        if !t.is_finite() {
+            return f64::NAN;
+        }
+        let guess = t - self.local_offset(t) as f64 * MS_PER_MINUTE;
+        t - self.local_offset(guess) as f64 * MS_PER_MINUTE
+    }
+}
+
+//------------------------------ 时间计算 ------------------------------
+
+fn day(t: f64) -> f64 {
+    (t / MS_PER_DAY).floor()
+}
+
+fn day_from_year(y: f64) -> f64 {
+    365.0 * (y - 1970.0) + ((y - 1969.0) / 4.0).floor() - ((y - 1901.0) / 100.0).floor()
+        + ((y - 1601.0) / 400.0).floor()
+}
+
+fn is_leap_year(y: f64) -> bool {
+    y % 4.0 == 0.0 && (y % 100.0 != 0.0 || y % 400.0 == 0.0)
+}
+
+fn year_from_time(t: f64) -> f64 {
+    let mut y = (t / (MS_PER_DAY * 365.2425)).floor() + 1970.0;
+    while day_from_year(y) * MS_PER_DAY > t {
+        y -= 1.0;
+    }
+    while day_from_year(y + 1.0) * MS_PER_DAY <= t {
+        y += 1.0;
+    }
+    y
+}
+
+/// 每月第一天在一年中的位置
+fn month_start(month: usize, leap: bool) -> f64 {
+    const STARTS: [f64; 13] = [
+        0.0, 31.0, 59.0, 90.0, 120.0, 151.0, 181.0, 212.0, 243.0, 273.0, 304.0, 334.0, 365.0,
+    ];
+    STARTS[month] + if leap && month >= 2 { 1.0 } else { 0.0 }
+}
+
+/// 分解为 [年, 月, 日, 时, 分, 秒, 毫秒, 星期]
+pub(super) fn decompose(t: f64) -> [f64; 8] {
+    let year = year_from_time(t);
+    let day_in_year = day(t) - day_from_year(year);
+    let leap = is_leap_year(year);
+    let month = (0..12)
+        .find(|&m| day_in_year < month_start(m + 1, leap))
+        .unwrap_or(11);
+    let date = day_in_year - month_start(month, leap) + 1.0;
+    let time = t.rem_euclid(MS_PER_DAY);
+    [
+        year,
+        month as f64,
+        date,
+        (time / MS_PER_HOUR).floor(),
+        (time / MS_PER_MINUTE).floor() % 60.0,
+        (time / MS_PER_SECOND).floor() % 60.0,
+        time % MS_PER_SECOND,
+        (day(t) + 4.0).rem_euclid(7.0),
+    ]
+}
+
+/// MakeTime
+fn make_time(hour: f64, min: f64, sec: f64, ms: f64) -> f64 {
+    if !(hour.is_finite() && min.is_finite() && sec.is_finite() && ms.is_finite()) {
+        return f64::NAN;
+    }
+    hour.trunc() * MS_PER_HOUR
+        + min.trunc() * MS_PER_MINUTE
+        + sec.trunc() * MS_PER_SECOND
+        + ms.trunc()
+}
+
+/// MakeDay, 月份超出范围时调整年份
+fn make_day(year: f64, month: f64, date: f64) -> f64 {
+    if !(year.is_finite() && month.is_finite() && date.is_finite()) {
+        return f64::NAN;
+    }
+    let (year, month, date) = (year.trunc(), month.trunc(), date.trunc());
+    let year = year + (month / 12.0).floor();
+    if year.abs() > 400_000.0 {
+        return f64::NAN;
+    }
+    let month = month.rem_euclid(12.0) as usize;
+    day_from_year(year) + month_start(month, is_leap_year(year)) + date - 1.0
+}
+
+/// MakeDate
+fn make_date(day: f64, time: f64) -> f64 {
+    let t = day * MS_PER_DAY + time;
+    if t.is_finite() {
+        t
+    } else {
+        f64::NAN
+    }
+}
+
+/// TimeClip, 超出 ±8.64e15 时为NaN
+pub(super) fn time_clip(t: f64) -> f64 {
+    if !t.is_finite() || t.abs() > 8.64e15 {
+        return f64::NAN;
+    }
+    t.trunc() + 0.0
+}
+
+/// 按 [年, 月, 日, 时, 分, 秒, 毫秒] 计算时间
+fn make_date_from_fields(fields: &[f64; 7]) -> f64 {
+    let [year, month, date, hour, min, sec, ms] = *fields;
+    make_date(make_day(year, month, date), make_time(hour, min, sec, ms))
+}
+
+/// Date(year, month, ...)及Date.UTC的参数, 0到99的年份为1900年之后
+fn fields_from_args(program: &mut Program, args: &[JSType]) -> Result<[f64; 7], String> {
+    let mut fields = [f64::NAN, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+    for (i, value) in args.iter().take(7).enumerate() {
+        fields[i] = program.number_value(value)?;
+    }
+    let year = fields[0];
+    if !year.is_nan() && (0.0..=99.0).contains(&year.trunc()) {
+        fields[0] = 1900.0 + year.trunc();
+    }
+    Ok(fields)
+}
+
+//------------------------------ 格式化 ------------------------------
+
+/// 年份, 负数时加负号
+fn year_string(year: f64) -> String {
+    if year < 0.0 {
+        format!("-{:04}", -year as i64)
+    } else {
+        format!("{:04}", year as i64)
+    }
+}
+
+/// toISOString的格式, 超出0到9999的年份使用6位并加符号, 无效时为None
+pub(crate) fn iso_string(t: f64) -> Option<String> {
+    if t.is_nan() {
+        return None;
+    }
+    let [year, month, date, hour, min, sec, ms, _] = decompose(t);
+    let year = if (0.0..=9999.0).contains(&year) {
+        format!("{:04}", year as i64)
+    } else if year < 0.0 {
+        format!("-{:06}", -year as i64)
+    } else {
+        format!("+{:06}", year as i64)
+    };
+    Some(format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month as i64 + 1,
+        date as i64,
+        hour as i64,
+        min as i64,
+        sec as i64,
+        ms as i64
+    ))
+}
+
+/// Thu Jan 01 1970
+fn date_string(t: f64) -> String {
+    let [year, month, date, _, _, _, _, week_day] = decompose(t);
+    format!(
+        "{} {} {:02} {}",
+        WEEK_DAYS[week_day as usize],
+        MONTHS[month as usize],
+        date as i64,
+        year_string(year)
+    )
+}
+
+/// 00:00:00 GMT+0800
+fn time_string(t: f64, offset: i64) -> String {
+    let [_, _, _, hour, min, sec, _, _] = decompose(t);
+    format!(
+        "{:02}:{:02}:{:02} GMT{}{:02}{:02}",
+        hour as i64,
+        min as i64,
+        sec as i64,
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60
+    )
+}
+
+impl Program {
+    /// Date.prototype.toString的格式
+    fn date_to_string_value(&self, t: f64) -> String {
+        if t.is_nan() {
+            return "Invalid Date".to_string();
+        }
+        let local = self.local_time(t);
+        format!(
+            "{} {}",
+            date_string(local),
+            time_string(local, self.local_offset(t))
+        )
+    }
+}
+
+//------------------------------ 解析 ------------------------------
+
+/// Date.parse, 先按ISO格式解析, 失败时按toString/toUTCString/RFC 2822等常见格式解析
+fn parse_date(program: &Program, s: &str) -> f64 {
+    let s = s.trim();
+    let t = match parse_iso(s) {
+        //符合ISO格式但日期或时间超出范围, 如2020-02-30
+        Some((fields, _)) if !valid_iso_fields(&fields) => f64::NAN,
+        Some((fields, Some(offset))) => make_date_from_fields(&fields) - offset * MS_PER_MINUTE,
+        Some((fields, None)) => program.utc_time(make_date_from_fields(&fields)),
+        None => match parse_legacy(s) {
+            Some((fields, Some(offset))) => make_date_from_fields(&fields) - offset * MS_PER_MINUTE,
+            Some((fields, None)) => program.utc_time(make_date_from_fields(&fields)),
+            None => f64::NAN,
+        },
+    };
+    time_clip(t)
+}
+
+/// 读取固定位数的数字
+fn take_digits(chars: &[char], pos: &mut usize, count: usize) -> Option<f64> {
+    let digits = chars.get(*pos..*pos + count)?;
+    if !digits.iter().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    *pos += count;
+    digits.iter().collect::<String>().parse().ok()
+}
+
+/// 当前字符为c时跳过
+fn eat(chars: &[char], pos: &mut usize, c: char) -> bool {
+    if chars.get(*pos) == Some(&c) {
+        *pos += 1;
+        true
+    } else {
+        false
+    }
+}
+
+/// YYYY-MM-DDTHH:mm:ss.sssZ 及其省略形式, 返回各部分及时区偏移(分钟)
+/// 只有日期时为UTC, 有时间但没有偏移时为本地时间
+fn parse_iso(s: &str) -> Option<([f64; 7], Option<f64>)> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut pos = 0;
+    let mut fields = [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+    fields[0] = match chars.first()? {
+        sign @ ('+' | '-') => {
+            pos += 1;
+            let year = take_digits(&chars, &mut pos, 6)?;
+            if *sign == '-' {
+                if year == 0.0 {
+                    return None; //-000000无效
+                }
+                -year
+            } else {
+                year
+            }
+        }
+        _ => take_digits(&chars, &mut pos, 4)?,
+    };
+    if eat(&chars, &mut pos, '-') {
+        fields[1] = take_digits(&chars, &mut pos, 2)? - 1.0;
+        if eat(&chars, &mut pos, '-') {
+            fields[2] = take_digits(&chars, &mut pos, 2)?;
+        }
+    }
+    let mut offset = Some(0.0);
+    if eat(&chars, &mut pos, 'T') {
+        offset = None;
+        fields[3] = take_digits(&chars, &mut pos, 2)?;
+        if !eat(&chars, &mut pos, ':') {
+            return None;
+        }
+        fields[4] = take_digits(&chars, &mut pos, 2)?;
+        if eat(&chars, &mut pos, ':') {
+            fields[5] = take_digits(&chars, &mut pos, 2)?;
+            if eat(&chars, &mut pos, '.') {
+                let start = pos;
+                while chars.get(pos).is_some_and(|c| c.is_ascii_digit()) {
+                    pos += 1;
+                }
+                if pos == start {
+                    return None;
+                }
+                //只取前3位
+                let ms: String = chars[start..pos]
+                    .iter()
+                    .chain(['0'; 2].iter())
+                    .take(3)
+                    .collect();
+                fields[6] = ms.parse().ok()?;
+            }
+        }
+        if eat(&chars, &mut pos, 'Z') {
+            offset = Some(0.0);
+        } else if let Some(sign @ ('+' | '-')) = chars.get(pos).copied() {
+            pos += 1;
+            let hours = take_digits(&chars, &mut pos, 2)?;
+            if !eat(&chars, &mut pos, ':') {
+                return None;
+            }
+            let minutes = take_digits(&chars, &mut pos, 2)?;
+            let minutes = hours * 60.0 + minutes;
+            offset = Some(if sign == '-' { -minutes } else { minutes });
+        }
+    }
+    if pos != chars.len() {
+        return None;
+    }
+    Some((fields, offset))
+}
+
+/// ISO格式各部分的范围, 日期按当月天数(含闰年)检查
+fn valid_iso_fields(fields: &[f64; 7]) -> bool {
+    let [year, month, date, hour, min, sec, ms] = *fields;
+    if !(0.0..12.0).contains(&month) {
+        return false;
+    }
+    let days_in_month = month_start(month as usize + 1, is_leap_year(year))
+        - month_start(month as usize, is_leap_year(year));
+    date >= 1.0
+        && date <= days_in_month
+        && (hour < 24.0 || (hour == 24.0 && min == 0.0 && sec == 0.0 && ms == 0.0))
+        && min < 60.0
+        && sec < 60.0
+}
+
+/// 其他常见格式的单词
+enum Word {
+    Number(f64, usize), //值及位数
+    Alpha(String),
+    Punct(char),
+}
+
+/// Tue Oct 20 2026 10:00:00 GMT+0800 (CST) / Tue, 20 Oct 2026 10:00:00 GMT / 2026/10/20 10:00 PM
+fn parse_legacy(s: &str) -> Option<([f64; 7], Option<f64>)> {
+    let mut words = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '(' {
+            //括号中为注释, 如时区名称
+            let mut depth = 1;
+            for c in chars.by_ref() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    break;
+                }
+            }
+        } else if c.is_ascii_digit() {
+            let mut digits = c.to_string();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(c);
+            }
+            words.push(Word::Number(digits.parse().ok()?, digits.len()));
+        } else if c.is_ascii_alphabetic() {
+            let mut word = c.to_string();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic() || *c == '.') {
+                word.push(c);
+            }
+            words.push(Word::Alpha(word.trim_end_matches('.').to_ascii_lowercase()));
+        } else if !c.is_whitespace() && c != ',' {
+            words.push(Word::Punct(c));
+        }
+    }
+    let mut month = None;
+    let mut numbers = Vec::new(); //日期中的数字
+    let mut time = None;
+    let mut pm = None;
+    let mut offset = None;
+    let mut i = 0;
+    while i < words.len() {
+        match &words[i] {
+            Word::Alpha(word) => {
+                if let Some(m) = MONTHS
+                    .iter()
+                    .position(|m| word.len() >= 3 && m.to_ascii_lowercase() == word[..3])
+                {
+                    month = Some(m as f64);
+                } else if ["am", "pm"].contains(&word.as_str()) {
+                    pm = Some(word == "pm");
+                } else if ["gmt", "utc", "ut", "z"].contains(&word.as_str()) {
+                    offset = Some(0.0);
+                } else if !WEEK_DAYS
+                    .iter()
+                    .any(|d| word.len() >= 3 && d.to_ascii_lowercase() == word[..3])
+                {
+                    return None;
+                }
+            }
+            Word::Number(n, _) if matches!(words.get(i + 1), Some(Word::Punct(':'))) => {
+                //时:分[:秒[.毫秒]]
+                let mut parts = vec![*n];
+                while let (Some(Word::Punct(':')), Some(Word::Number(n, _))) =
+                    (words.get(i + 1), words.get(i + 2))
+                {
+                    parts.push(*n);
+                    i += 2;
+                }
+                let mut ms = 0.0;
+                if let (Some(Word::Punct('.')), Some(Word::Number(n, len))) =
+                    (words.get(i + 1), words.get(i + 2))
+                {
+                    ms = (n / 10f64.powi(*len as i32 - 3)).trunc();
+                    i += 2;
+                }
+                parts.resize(3, 0.0);
+                time = Some([parts[0], parts[1], parts[2], ms]);
+            }
+            Word::Number(n, len) => numbers.push((*n, *len)),
+            Word::Punct(sign @ ('+' | '-')) if time.is_some() || offset.is_some() => {
+                //+0800 / +08:00 / +8
+                let Some(Word::Number(n, len)) = words.get(i + 1) else {
+                    return None;
+                };
+                let mut minutes = if *len <= 2 {
+                    n * 60.0
+                } else {
+                    (n / 100.0).trunc() * 60.0 + n % 100.0
+                };
+                i += 1;
+                if let (Some(Word::Punct(':')), Some(Word::Number(m, _))) =
+                    (words.get(i + 1), words.get(i + 2))
+                {
+                    minutes += m;
+                    i += 2;
+                }
+                offset = Some(if *sign == '-' { -minutes } else { minutes });
+            }
+            Word::Punct('/' | '-' | '.') => {}
+            Word::Punct(_) => return None,
+        }
+        i += 1;
+    }
+    //年份为大于31或超过2位的数字
+    let is_year = |&(n, len): &(f64, usize)| n > 31.0 || len > 2;
+    let (year, month, date) = match (month, numbers.as_slice()) {
+        (Some(m), [a, b]) if is_year(a) => (a.0, m, b.0),
+        (Some(m), [a, b]) => (b.0, m, a.0),
+        (Some(m), [a]) if is_year(a) => (a.0, m, 1.0),
+        (None, [a, b, c]) if is_year(a) => (a.0, b.0 - 1.0, c.0),
+        (None, [a, b, c]) => (c.0, a.0 - 1.0, b.0),
+        _ => return None,
+    };
+    //两位数的年份: 0到49为2000年之后, 50到99为1900年之后
+    let year = match year {
+        y if y < 50.0 && numbers.iter().any(|&(n, len)| n == y && len <= 2) => 2000.0 + y,
+        y if y < 100.0 && numbers.iter().any(|&(n, len)| n == y && len <= 2) => 1900.0 + y,
+        y => y,
+    };
+    let [mut hour, min, sec, ms] = time.unwrap_or([0.0; 4]);
+    match pm {
+        Some(_) if !(1.0..=12.0).contains(&hour) => return None,
+        Some(true) if hour < 12.0 => hour += 12.0,
+        Some(false) if hour == 12.0 => hour = 0.0,
+        _ => {}
+    }
+    if !(0.0..12.0).contains(&month)
+        || !(1.0..=31.0).contains(&date)
+        || hour > 24.0
+        || min > 59.0
+        || sec > 59.0
+    {
+        return None;
+    }
+    Some(([year, month, date, hour, min, sec, ms], offset))
+}
+
+//------------------------------ 内置方法 ------------------------------
+
+/// thisTimeValue
+fn this_time_value(program: &Program, this: &JSType) -> Result<f64, String> {
+    if let JSType::Object(id) = this {
+        if let ObjectKind::Date(t) = program.obj(*id).kind {
+            return Ok(t);
+        }
+    }
+    Err(err("Uncaught TypeError: this is not a Date object."))
+}
+
+fn set_time_value(program: &mut Program, this: &JSType, t: f64) -> JSType {
+    if let JSType::Object(id) = this {
+        program.obj_mut(*id).kind = ObjectKind::Date(t);
+    }
+    JSType::Float(t)
+}
+
+/// 毫秒数为整数时使用Int
+fn time_value(t: f64) -> JSType {
+    if t.is_nan() {
+        JSType::Float(f64::NAN)
+    } else {
+        JSType::Int(t as i64)
+    }
+}
+
+/// Date() 作为函数调用时返回当前时间的字符串
+fn date_call(program: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let now = time_clip(program.current_time());
+    Ok(JSType::String(program.date_to_string_value(now).into()))
+}
+
+/// new Date() / new Date(value) / new Date(year, month, ...)
+fn date_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let t = match args.as_slice() {
+        [] => time_clip(program.current_time()),
+        [value] => {
+            let t = match value {
+                JSType::Object(id) => match program.obj(*id).kind {
+                    ObjectKind::Date(t) => Some(t),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match t {
+                Some(t) => t,
+                None => match program.primitive(value, None)? {
+                    JSType::String(s) => parse_date(program, &s.to_string()),
+                    value => time_clip(program.number_value(&value)?),
+                },
+            }
+        }
+        _ => {
+            let fields = fields_from_args(program, &args)?;
+            time_clip(program.utc_time(make_date_from_fields(&fields)))
+        }
+    };
+    let proto = program.get_prototype_from_constructor(&new_target, "Date")?;
+    Ok(JSType::Object(
+        program.alloc(JSObject::new(ObjectKind::Date(t), Some(proto))),
+    ))
+}
+
+fn date_now(program: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(time_value(time_clip(program.current_time())))
+}
+
+fn date_parse(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = program.string_value(&arg(&args, 0))?;
+    Ok(time_value(parse_date(program, &s)))
+}
+
+/// Date.UTC(year, month = 0, ...)
+fn date_utc(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let fields = fields_from_args(program, &args)?;
+    Ok(time_value(time_clip(make_date_from_fields(&fields))))
+}
+
+fn date_value_of(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(time_value(this_time_value(program, &this)?))
+}
+
+fn date_get_timezone_offset(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    if t.is_nan() {
+        return Ok(JSType::Float(f64::NAN));
+    }
+    Ok(JSType::Int(-program.local_offset(t)))
+}
+
+/// getFullYear等, local为false时为UTC
+fn get_field(
+    program: &Program,
+    this: &JSType,
+    local: bool,
+    index: usize,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, this)?;
+    if t.is_nan() {
+        return Ok(JSType::Float(f64::NAN));
+    }
+    let t = if local { program.local_time(t) } else { t };
+    Ok(JSType::Int(decompose(t)[index] as i64))
+}
+
+fn date_get_full_year(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Year as usize)
+}
+
+fn date_get_month(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Month as usize)
+}
+
+fn date_get_date(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Date as usize)
+}
+
+fn date_get_day(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, true, 7)
+}
+
+fn date_get_hours(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Hours as usize)
+}
+
+fn date_get_minutes(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Minutes as usize)
+}
+
+fn date_get_seconds(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Seconds as usize)
+}
+
+fn date_get_milliseconds(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, true, Field::Milliseconds as usize)
+}
+
+fn date_get_utc_full_year(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Year as usize)
+}
+
+fn date_get_utc_month(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Month as usize)
+}
+
+fn date_get_utc_date(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Date as usize)
+}
+
+fn date_get_utc_day(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    get_field(program, &this, false, 7)
+}
+
+fn date_get_utc_hours(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Hours as usize)
+}
+
+fn date_get_utc_minutes(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Minutes as usize)
+}
+
+fn date_get_utc_seconds(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Seconds as usize)
+}
+
+fn date_get_utc_milliseconds(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    get_field(program, &this, false, Field::Milliseconds as usize)
+}
+
+fn date_set_time(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    this_time_value(program, &this)?;
+    let t = time_clip(program.number_value(&arg(&args, 0))?);
+    Ok(set_time_value(program, &this, t))
+}
+
+/// setFullYear/setHours等, 从first开始依次设置参数中的部分, 最多count个
+/// 除setFullYear外, 原时间无效时结果仍为NaN
+fn set_fields(
+    program: &mut Program,
+    this: &JSType,
+    args: &[JSType],
+    local: bool,
+    first: Field,
+    count: usize,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, this)?;
+    let mut values = Vec::new();
+    for value in args.iter().take(count) {
+        values.push(program.number_value(value)?);
+    }
+    if values.is_empty() {
+        values.push(f64::NAN);
+    }
+    let t = match first {
+        Field::Year if t.is_nan() => 0.0,
+        _ if t.is_nan() => return Ok(JSType::Float(f64::NAN)),
+        _ if local => program.local_time(t),
+        _ => t,
+    };
+    let parts = decompose(t);
+    let mut fields = [0.0; 7];
+    fields.copy_from_slice(&parts[..7]);
+    for (i, value) in values.into_iter().enumerate() {
+        fields[first as usize + i] = value;
+    }
+    let t = make_date_from_fields(&fields);
+    let t = time_clip(if local { program.utc_time(t) } else { t });
+    Ok(set_time_value(program, this, t))
+}
+
+fn date_set_full_year(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Year, 3)
+}
+
+fn date_set_month(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Month, 2)
+}
+
+fn date_set_date(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Date, 1)
+}
+
+fn date_set_hours(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Hours, 4)
+}
+
+fn date_set_minutes(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Minutes, 3)
+}
+
+fn date_set_seconds(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Seconds, 2)
+}
+
+fn date_set_milliseconds(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, true, Field::Milliseconds, 1)
+}
+
+fn date_set_utc_full_year(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Year, 3)
+}
+
+fn date_set_utc_month(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Month, 2)
+}
+
+fn date_set_utc_date(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Date, 1)
+}
+
+fn date_set_utc_hours(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Hours, 4)
+}
+
+fn date_set_utc_minutes(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Minutes, 3)
+}
+
+fn date_set_utc_seconds(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Seconds, 2)
+}
+
+fn date_set_utc_milliseconds(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    set_fields(program, &this, &args, false, Field::Milliseconds, 1)
+}
+
+fn date_to_iso_string(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    match iso_string(t) {
+        Some(s) => Ok(JSType::String(s.into())),
+        None => Err(err("Uncaught RangeError: Invalid time value")),
+    }
+}
+
+/// toJSON, 无效的时间为null, 可用于非Date对象
+fn date_to_json(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let object = JSType::Object(program.object_value(&this)?);
+    let value = program.primitive(&object, Some("number"))?;
+    if let JSType::Float(n) = value {
+        if !n.is_finite() {
+            return Ok(JSType::NULL);
+        }
+    }
+    program.invoke(&object, "toISOString", Vec::new())
+}
+
+fn date_to_string(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    Ok(JSType::String(program.date_to_string_value(t).into()))
+}
+
+fn date_to_date_string(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    if t.is_nan() {
+        return Ok(JSType::String("Invalid Date".into()));
+    }
+    Ok(JSType::String(date_string(program.local_time(t)).into()))
+}
+
+fn date_to_time_string(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    if t.is_nan() {
+        return Ok(JSType::String("Invalid Date".into()));
+    }
+    let s = time_string(program.local_time(t), program.local_offset(t));
+    Ok(JSType::String(s.into()))
+}
+
+/// toLocaleString(locales, options), 格式见Intl.DateTimeFormat
+fn date_to_locale_string(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    let s = program.date_to_locale_string(t, &args, "any", "all")?;
+    Ok(JSType::String(s.into()))
+}
+
+fn date_to_locale_date_string(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    let s = program.date_to_locale_string(t, &args, "date", "date")?;
+    Ok(JSType::String(s.into()))
+}
+
+fn date_to_locale_time_string(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    let s = program.date_to_locale_string(t, &args, "time", "time")?;
+    Ok(JSType::String(s.into()))
+}
+
+/// Thu, 01 Jan 1970 00:00:00 GMT
+fn date_to_utc_string(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let t = this_time_value(program, &this)?;
+    if t.is_nan() {
+        return Ok(JSType::String("Invalid Date".into()));
+    }
+    let [year, month, date, hour, min, sec, _, week_day] = decompose(t);
+    let s = format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEK_DAYS[week_day as usize],
+        date as i64,
+        MONTHS[month as usize],
+        year_string(year),
+        hour as i64,
+        min as i64,
+        sec as i64
+    );
+    Ok(JSType::String(s.into()))
+}
+
+/// Date.prototype[Symbol.toPrimitive](hint), default按string处理
+fn date_to_primitive(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    if !matches!(this, JSType::Object(_)) {
+        return Err(err(
+            "Uncaught TypeError: Date.prototype[Symbol.toPrimitive] called on non-object",
+        ));
+    }
+    let hint = arg(&args, 0);
+    let name = match &hint {
+        JSType::String(s) => s.to_string(),
+        _ => String::new(),
+    };
+    let hint = match name.as_str() {
+        "string" | "default" => "string",
+        "number" => "number",
+        _ => {
+            return Err(err(&format!(
+                "Uncaught TypeError: Invalid hint: {}",
+                program.display(&hint)
+            )))
+        }
+    };
+    program.ordinary_to_primitive(&this, Some(hint))
+}
@@ -0,0 +1,1905 @@
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+use super::date::{decompose, time_clip};
+use super::number::increment;
+use super::{arg, list_from_array_like};
+use crate::jsparser::object::{
+    BoundFunction, JSObject, NativeCtor, NativeFn, ObjectId, ObjectKind, Property,
+};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::string::JSString;
+use crate::jsparser::utility::err;
+
+//------------------------------ 区域数据 ------------------------------
+
+/// 内置的区域数据, 日期格式中的ASCII字母为字段, 其余原样输出
+pub(crate) struct LocaleData {
+    tag: &'static str,
+    group: &'static str,                              //千分位分隔符
+    decimal: &'static str,                            //小数点
+    percent: &'static str,                            //百分比格式, #为数字
+    currency: &'static str,                           //货币格式, ¤为货币符号
+    symbols: &'static [(&'static str, &'static str)], //货币符号, 未列出的使用货币代码
+    months: [[&'static str; 12]; 3],                  //月份 long/short/narrow
+    weekdays: [[&'static str; 7]; 3],                 //星期 long/short/narrow, 从周日开始
+    day_periods: [&'static str; 2],                   //上午/下午
+    hour12: bool,                                     //默认12小时制
+    numeric_dates: [&'static str; 6],                 //数字月份 yMd yM Md y M d
+    text_dates: [&'static str; 4],                    //文字月份 yMMMd yMMM MMMd MMM
+    weekday_date: &'static str,                       //星期及日期, {0}为日期
+    date_styles: [&'static str; 4],                   //dateStyle full/long/medium/short
+    times: [&'static str; 2],                         //12小时制/24小时制
+    zoned_time: &'static str,                         //timeStyle full/long, {0}为时间
+    date_time: &'static str,                          //{0}为日期 {1}为时间
+}
+
+const EN_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const NARROW_MONTHS: [&str; 12] = ["J", "F", "M", "A", "M", "J", "J", "A", "S", "O", "N", "D"];
+const EN_WEEKDAYS: [[&str; 7]; 3] = [
+    [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ],
+    ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+    ["S", "M", "T", "W", "T", "F", "S"],
+];
+const CJK_MONTHS: [&str; 12] = [
+    "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+];
+const NUMERIC_MONTHS: [&str; 12] = [
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12",
+];
+
+/// 内置的区域, 第一个为默认区域, 同一语言的第一个区域作为该语言的默认区域
+static LOCALES: [LocaleData; 5] = [
+    LocaleData {
+        tag: "en-US",
+        group: ",",
+        decimal: ".",
+        percent: "#%",
+        currency: "¤#",
+        symbols: &[
+            ("USD", "$"),
+            ("EUR", "€"),
+            ("GBP", "£"),
+            ("JPY", "¥"),
+            ("CNY", "CN¥"),
+            ("KRW", "₩"),
+            ("INR", "₹"),
+            ("HKD", "HK$"),
+            ("CAD", "CA$"),
+            ("AUD", "A$"),
+        ],
+        months: [
+            EN_MONTHS,
+            [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+            ],
+            NARROW_MONTHS,
+        ],
+        weekdays: EN_WEEKDAYS,
+        day_periods: ["AM", "PM"],
+        hour12: true,
+        numeric_dates: ["M/d/y", "M/y", "M/d", "y", "M", "d"],
+        text_dates: ["MMM d, y", "MMM y", "MMM d", "MMM"],
+        weekday_date: "E, {0}",
+        date_styles: ["EEEE, MMMM d, y", "MMMM d, y", "MMM d, y", "M/d/yy"],
+        times: ["h:mm:ss a", "HH:mm:ss"],
+        zoned_time: "{0} z",
+        date_time: "{0}, {1}",
+    },
+    LocaleData {
+        tag: "en-GB",
+        group: ",",
+        decimal: ".",
+        percent: "#%",
+        currency: "¤#",
+        symbols: &[
+            ("USD", "US$"),
+            ("EUR", "€"),
+            ("GBP", "£"),
+            ("JPY", "JP¥"),
+            ("CNY", "CN¥"),
+            ("KRW", "₩"),
+            ("INR", "₹"),
+            ("HKD", "HK$"),
+            ("CAD", "CA$"),
+            ("AUD", "A$"),
+        ],
+        months: [
+            EN_MONTHS,
+            [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sept", "Oct", "Nov", "Dec",
+            ],
+            NARROW_MONTHS,
+        ],
+        weekdays: EN_WEEKDAYS,
+        day_periods: ["am", "pm"],
+        hour12: false,
+        numeric_dates: ["dd/MM/y", "MM/y", "dd/MM", "y", "M", "d"],
+        text_dates: ["d MMM y", "MMM y", "d MMM", "MMM"],
+        weekday_date: "E {0}",
+        date_styles: ["EEEE d MMMM y", "d MMMM y", "d MMM y", "dd/MM/y"],
+        times: ["h:mm:ss a", "HH:mm:ss"],
+        zoned_time: "{0} z",
+        date_time: "{0}, {1}",
+    },
+    LocaleData {
+        tag: "de-DE",
+        group: ".",
+        decimal: ",",
+        percent: "#\u{a0}%",
+        currency: "#\u{a0}¤",
+        symbols: &[
+            ("USD", "$"),
+            ("EUR", "€"),
+            ("GBP", "£"),
+            ("JPY", "¥"),
+            ("CNY", "CN¥"),
+            ("KRW", "₩"),
+            ("INR", "₹"),
+            ("HKD", "HK$"),
+            ("CAD", "CA$"),
+            ("AUD", "AU$"),
+        ],
+        months: [
+            [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            [
+                "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.",
+                "Nov.", "Dez.",
+            ],
+            NARROW_MONTHS,
+        ],
+        weekdays: [
+            [
+                "Sonntag",
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+            ],
+            ["So.", "Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa."],
+            ["S", "M", "D", "M", "D", "F", "S"],
+        ],
+        day_periods: ["AM", "PM"],
+        hour12: false,
+        numeric_dates: ["d.M.y", "M.y", "d.M.", "y", "M", "d"],
+        text_dates: ["d. MMM y", "MMM y", "d. MMM", "MMM"],
+        weekday_date: "E, {0}",
+        date_styles: ["EEEE, d. MMMM y", "d. MMMM y", "dd.MM.y", "dd.MM.yy"],
+        times: ["h:mm:ss a", "HH:mm:ss"],
+        zoned_time: "{0} z",
+        date_time: "{0}, {1}",
+    },
+    LocaleData {
+        tag: "zh-CN",
+        group: ",",
+        decimal: ".",
+        percent: "#%",
+        currency: "¤#",
+        symbols: &[
+            ("USD", "US$"),
+            ("EUR", "€"),
+            ("GBP", "£"),
+            ("JPY", "JP¥"),
+            ("CNY", "¥"),
+            ("KRW", "￦"),
+            ("INR", "₹"),
+            ("HKD", "HK$"),
+            ("CAD", "CA$"),
+            ("AUD", "AU$"),
+        ],
+        months: [
+            [
+                "一月",
+                "二月",
+                "三月",
+                "四月",
+                "五月",
+                "六月",
+                "七月",
+                "八月",
+                "九月",
+                "十月",
+                "十一月",
+                "十二月",
+            ],
+            CJK_MONTHS,
+            NUMERIC_MONTHS,
+        ],
+        weekdays: [
+            [
+                "星期日",
+                "星期一",
+                "星期二",
+                "星期三",
+                "星期四",
+                "星期五",
+                "星期六",
+            ],
+            ["周日", "周一", "周二", "周三", "周四", "周五", "周六"],
+            ["日", "一", "二", "三", "四", "五", "六"],
+        ],
+        day_periods: ["上午", "下午"],
+        hour12: false,
+        numeric_dates: ["y/M/d", "y/M", "M/d", "y年", "M月", "d日"],
+        text_dates: ["y年M月d日", "y年M月", "M月d日", "MMM"],
+        weekday_date: "{0}E",
+        date_styles: ["y年M月d日EEEE", "y年M月d日", "y年M月d日", "y/M/d"],
+        times: ["ah:mm:ss", "HH:mm:ss"],
+        zoned_time: "z {0}",
+        date_time: "{0} {1}",
+    },
+    LocaleData {
+        tag: "ja-JP",
+        group: ",",
+        decimal: ".",
+        percent: "#%",
+        currency: "¤#",
+        symbols: &[
+            ("USD", "$"),
+            ("EUR", "€"),
+            ("GBP", "£"),
+            ("JPY", "￥"),
+            ("CNY", "元"),
+            ("KRW", "₩"),
+            ("INR", "₹"),
+            ("HKD", "HK$"),
+            ("CAD", "CA$"),
+            ("AUD", "A$"),
+        ],
+        months: [CJK_MONTHS, CJK_MONTHS, NUMERIC_MONTHS],
+        weekdays: [
+            [
+                "日曜日",
+                "月曜日",
+                "火曜日",
+                "水曜日",
+                "木曜日",
+                "金曜日",
+                "土曜日",
+            ],
+            ["日", "月", "火", "水", "木", "金", "土"],
+            ["日", "月", "火", "水", "木", "金", "土"],
+        ],
+        day_periods: ["午前", "午後"],
+        hour12: false,
+        numeric_dates: ["y/M/d", "y/M", "M/d", "y年", "M月", "d日"],
+        text_dates: ["y年M月d日", "y年M月", "M月d日", "MMM"],
+        weekday_date: "{0}E",
+        date_styles: ["y年M月d日EEEE", "y年M月d日", "y/MM/dd", "y/MM/dd"],
+        times: ["aK:mm:ss", "H:mm:ss"],
+        zoned_time: "{0} z",
+        date_time: "{0} {1}",
+    },
+];
+
+/// 各货币通用的窄符号, narrowSymbol
+const NARROW_SYMBOLS: [(&str, &str); 10] = [
+    ("USD", "$"),
+    ("EUR", "€"),
+    ("GBP", "£"),
+    ("JPY", "¥"),
+    ("CNY", "¥"),
+    ("KRW", "₩"),
+    ("INR", "₹"),
+    ("HKD", "$"),
+    ("CAD", "$"),
+    ("AUD", "$"),
+];
+
+/// 货币的小数位数, 未列出的为2位
+const CURRENCY_DIGITS: [(&str, usize); 13] = [
+    ("JPY", 0),
+    ("KRW", 0),
+    ("VND", 0),
+    ("CLP", 0),
+    ("ISK", 0),
+    ("UGX", 0),
+    ("XAF", 0),
+    ("XOF", 0),
+    ("BHD", 3),
+    ("KWD", 3),
+    ("OMR", 3),
+    ("JOD", 3),
+    ("TND", 3),
+];
+
+/// 规范化语言标记 zh-cn => zh-CN, 格式不正确时为None
+fn canonicalize_tag(tag: &str) -> Option<String> {
+    let mut result: Vec<String> = Vec::new();
+    let mut extension = false; //单字母之后为扩展, 如 -u-hc-h23
+    for (index, subtag) in tag.split('-').enumerate() {
+        if subtag.is_empty()
+            || subtag.len() > 8
+            || !subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return None;
+        }
+        let alpha = subtag.chars().all(|c| c.is_ascii_alphabetic());
+        let lower = subtag.to_ascii_lowercase();
+        if index == 0 {
+            if !alpha || subtag.len() == 4 || subtag.len() == 1 {
+                return None;
+            }
+            result.push(lower);
+        } else if extension || subtag.len() == 1 {
+            extension = true;
+            result.push(lower);
+        } else if index == 1 && alpha && subtag.len() == 4 {
+            //文字 Hans
+            result.push(lower[..1].to_ascii_uppercase() + &lower[1..]);
+        } else if (alpha && subtag.len() == 2)
+            || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+        {
+            //地区 CN / 419
+            result.push(subtag.to_ascii_uppercase());
+        } else {
+            result.push(lower);
+        }
+    }
+    if result.last().is_some_and(|s| s.len() == 1) {
+        return None;
+    }
+    Some(result.join("-"))
+}
+
+/// 按前缀查找内置区域 zh-Hans-CN => zh-Hans => zh, 忽略扩展
+fn lookup_locale(tag: &str) -> Option<&'static LocaleData> {
+    let base: Vec<&str> = tag.split('-').take_while(|s| s.len() > 1).collect();
+    let base = base.join("-");
+    let mut candidate = base.as_str();
+    loop {
+        if let Some(data) = LOCALES.iter().find(|l| l.tag == candidate) {
+            return Some(data);
+        }
+        if !candidate.contains('-') {
+            return LOCALES
+                .iter()
+                .find(|l| l.tag.split('-').next() == Some(candidate));
+        }
+        candidate = &candidate[..candidate.rfind('-').unwrap()];
+    }
+}
+
+/// 排序规则与根排序相同的区域, 中文(拼音)/日文的排序需要专门的排序数据, 没有内置
+const COLLATION_LOCALES: [&str; 3] = ["en-US", "en-GB", "de-DE"];
+
+/// Collator支持的区域, 其他区域回退到默认区域
+fn lookup_collation_locale(tag: &str) -> Option<&'static LocaleData> {
+    lookup_locale(tag).filter(|data| COLLATION_LOCALES.contains(&data.tag))
+}
+
+//------------------------------ 选项 ------------------------------
+
+const STYLES: [&str; 4] = ["full", "long", "medium", "short"];
+
+/// DateTimeFormat的日期时间字段及可选值
+const COMPONENTS: [(&str, &[&str]); 7] = [
+    ("weekday", &["narrow", "short", "long"]),
+    ("year", &["2-digit", "numeric"]),
+    ("month", &["2-digit", "numeric", "narrow", "short", "long"]),
+    ("day", &["2-digit", "numeric"]),
+    ("hour", &["2-digit", "numeric"]),
+    ("minute", &["2-digit", "numeric"]),
+    ("second", &["2-digit", "numeric"]),
+];
+
+impl Program {
+    /// CanonicalizeLocaleList, 字符串或字符串数组, 去掉重复
+    fn canonicalize_locale_list(&mut self, locales: &JSType) -> Result<Vec<String>, String> {
+        let list = match locales {
+            JSType::Undefined => return Ok(Vec::new()),
+            JSType::String(_) => vec![locales.clone()],
+            JSType::Object(_) => list_from_array_like(self, locales)?,
+            _ => {
+                self.object_value(locales)?;
+                Vec::new()
+            }
+        };
+        let mut result: Vec<String> = Vec::new();
+        for item in list {
+            if !matches!(item, JSType::String(_) | JSType::Object(_)) {
+                return Err(err(
+                    "Uncaught TypeError: Language ID should be string or object.",
+                ));
+            }
+            let tag = self.string_value(&item)?;
+            let Some(tag) = canonicalize_tag(&tag) else {
+                return Err(err(
+                    "Uncaught RangeError: Incorrect locale information provided",
+                ));
+            };
+            if !result.contains(&tag) {
+                result.push(tag);
+            }
+        }
+        Ok(result)
+    }
+
+    /// 第一个能匹配的区域, 都不支持时为默认区域en-US
+    fn resolve_locale(
+        &mut self,
+        locales: &JSType,
+        lookup: fn(&str) -> Option<&'static LocaleData>,
+    ) -> Result<&'static LocaleData, String> {
+        let list = self.canonicalize_locale_list(locales)?;
+        Ok(list
+            .iter()
+            .find_map(|tag| lookup(tag))
+            .unwrap_or(&LOCALES[0]))
+    }
+
+    /// 选项对象, undefined表示没有选项
+    fn intl_options(&mut self, options: &JSType) -> Result<JSType, String> {
+        match options {
+            JSType::Undefined => Ok(JSType::Undefined),
+            _ => Ok(JSType::Object(self.object_value(options)?)),
+        }
+    }
+
+    /// GetOption, 字符串选项只能是values之一
+    fn string_option(
+        &mut self,
+        options: &JSType,
+        name: &str,
+        values: &[&'static str],
+        service: &str,
+    ) -> Result<Option<&'static str>, String> {
+        if let JSType::Undefined = options {
+            return Ok(None);
+        }
+        let value = self.get_property(options, name)?;
+        if let JSType::Undefined = value {
+            return Ok(None);
+        }
+        let value = self.string_value(&value)?;
+        match values.iter().find(|v| **v == value) {
+            Some(v) => Ok(Some(v)),
+            None => Err(err(&format!(
+                "Uncaught RangeError: Value {} out of range for {} options property {}",
+                value, service, name
+            ))),
+        }
+    }
+
+    fn bool_option(&mut self, options: &JSType, name: &str) -> Result<Option<bool>, String> {
+        if let JSType::Undefined = options {
+            return Ok(None);
+        }
+        match self.get_property(options, name)? {
+            JSType::Undefined => Ok(None),
+            value => Ok(Some(value.is_truthy())),
+        }
+    }
+
+    /// GetNumberOption, 取整并检查范围
+    fn number_option(
+        &mut self,
+        options: &JSType,
+        name: &str,
+        min: usize,
+        max: usize,
+    ) -> Result<Option<usize>, String> {
+        if let JSType::Undefined = options {
+            return Ok(None);
+        }
+        let value = self.get_property(options, name)?;
+        if let JSType::Undefined = value {
+            return Ok(None);
+        }
+        let n = self.number_value(&value)?;
+        if n.is_nan() || n < min as f64 || n > max as f64 {
+            return Err(err(&format!(
+                "Uncaught RangeError: {} value is out of range.",
+                name
+            )));
+        }
+        Ok(Some(n.floor() as usize))
+    }
+
+    /// 绑定了this的内置函数, 用于format/compare访问器
+    fn bound_method(&mut self, this: ObjectId, length: usize, func: NativeFn) -> ObjectId {
+        let target = self.native_function("", length, func, None);
+        let bound = BoundFunction {
+            target,
+            this: JSType::Object(this),
+            args: Vec::new(),
+            constructor: false,
+        };
+        let proto = self.intrinsics.function_prototype;
+        let id = self.alloc(JSObject::new(
+            ObjectKind::Bound(Box::new(bound)),
+            Some(proto),
+        ));
+        self.define_property(id, "length", Property::readonly(JSType::Int(length as i64)));
+        self.define_property(id, "name", Property::readonly(JSType::String("".into())));
+        id
+    }
+
+    /// resolvedOptions() 的结果, 值为None的字段不输出
+    fn resolved_options(&mut self, fields: Vec<(&'static str, Option<JSType>)>) -> JSType {
+        let result = self.new_object();
+        let JSType::Object(id) = result else {
+            unreachable!()
+        };
+        for (name, value) in fields {
+            if let Some(value) = value {
+                self.define_property(id, name, Property::data(value));
+            }
+        }
+        result
+    }
+}
+
+fn string_field(value: &str) -> Option<JSType> {
+    Some(JSType::String(value.into()))
+}
+
+fn incompatible_receiver(program: &Program, method: &str, this: &JSType) -> String {
+    err(&format!(
+        "Uncaught TypeError: Method {} called on incompatible receiver {}",
+        method,
+        program.display(this)
+    ))
+}
+
+//------------------------------ Intl ------------------------------
+
+impl Program {
+    pub(super) fn init_intl(&mut self) {
+        let object_prototype = self.intrinsics.object_prototype;
+        let intl = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.define_to_string_tag(intl, "Intl");
+        self.define_method(intl, "getCanonicalLocales", 1, intl_get_canonical_locales);
+        self.bind_value("Intl".to_string(), JSType::Object(intl));
+
+        let proto = self.define_intl_constructor(
+            intl,
+            "NumberFormat",
+            number_format_call,
+            number_format_construct,
+            intl_supported_locales_of,
+        );
+        self.intrinsics.number_format_prototype = proto;
+        self.define_getter(proto, "format", "get format", number_format_format);
+        self.define_method(proto, "resolvedOptions", 0, number_format_resolved_options);
+
+        let proto = self.define_intl_constructor(
+            intl,
+            "DateTimeFormat",
+            date_time_format_call,
+            date_time_format_construct,
+            intl_supported_locales_of,
+        );
+        self.intrinsics.date_time_format_prototype = proto;
+        self.define_getter(proto, "format", "get format", date_time_format_format);
+        self.define_method(
+            proto,
+            "resolvedOptions",
+            0,
+            date_time_format_resolved_options,
+        );
+
+        let proto = self.define_intl_constructor(
+            intl,
+            "Collator",
+            collator_call,
+            collator_construct,
+            collator_supported_locales_of,
+        );
+        self.intrinsics.collator_prototype = proto;
+        self.define_getter(proto, "compare", "get compare", collator_compare);
+        self.define_method(proto, "resolvedOptions", 0, collator_resolved_options);
+    }
+
+    /// Intl下的构造函数, 返回其prototype
+    fn define_intl_constructor(
+        &mut self,
+        intl: ObjectId,
+        name: &'static str,
+        func: NativeFn,
+        ctor: NativeCtor,
+        supported_locales_of: NativeFn,
+    ) -> ObjectId {
+        let object_prototype = self.intrinsics.object_prototype;
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        let id = self.native_function(name, 0, func, Some(ctor));
+        let mut prop = Property::readonly(JSType::Object(proto));
+        prop.configurable = false;
+        self.define_property(id, "prototype", prop);
+        self.define_property(proto, "constructor", Property::hidden(JSType::Object(id)));
+        self.define_method(id, "supportedLocalesOf", 1, supported_locales_of);
+        self.define_to_string_tag(proto, &format!("Intl.{}", name));
+        self.define_property(intl, name, Property::hidden(JSType::Object(id)));
+        proto
+    }
+}
+
+/// Intl.getCanonicalLocales(locales)
+fn intl_get_canonical_locales(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let list = program.canonicalize_locale_list(&arg(&args, 0))?;
+    let list = list.into_iter().map(|s| JSType::String(s.into())).collect();
+    Ok(program.new_array(list))
+}
+
+/// supportedLocalesOf(locales), 能匹配内置区域的语言标记
+fn intl_supported_locales_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    supported_locales(program, &arg(&args, 0), lookup_locale)
+}
+
+/// Collator.supportedLocalesOf(locales), 只包含排序规则已内置的区域
+fn collator_supported_locales_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    supported_locales(program, &arg(&args, 0), lookup_collation_locale)
+}
+
+fn supported_locales(
+    program: &mut Program,
+    locales: &JSType,
+    lookup: fn(&str) -> Option<&'static LocaleData>,
+) -> Result<JSType, String> {
+    let list = program.canonicalize_locale_list(locales)?;
+    let list = list
+        .into_iter()
+        .filter(|tag| lookup(tag).is_some())
+        .map(|s| JSType::String(s.into()))
+        .collect();
+    Ok(program.new_array(list))
+}
+
+//------------------------------ NumberFormat ------------------------------
+
+/// Intl.NumberFormat的内部数据
+#[derive(Clone)]
+pub(crate) struct NumberFormat {
+    locale: &'static LocaleData,
+    style: &'static str,
+    currency: Option<(String, &'static str)>, //货币代码及currencyDisplay
+    minimum_integer_digits: usize,
+    fraction_digits: (usize, usize),
+    significant_digits: Option<(usize, usize)>,
+    grouping: Option<&'static str>, //None为不分组
+    sign_display: &'static str,
+    pub(crate) bound_format: Option<ObjectId>,
+}
+
+/// 非负的十进制数 0.d1d2d3... × 10^point, digits不含末尾的0
+struct Decimal {
+    digits: Vec<u8>,
+    point: i32,
+}
+
+impl Decimal {
+    /// 使用最短表示的数字, 与Number.prototype.toString相同
+    fn new(x: f64) -> Self {
+        if x == 0.0 {
+            return Decimal {
+                digits: Vec::new(),
+                point: 0,
+            };
+        }
+        let formatted = format!("{:e}", x);
+        let (mantissa, exponent) = formatted.split_once('e').unwrap();
+        let mut decimal = Decimal {
+            digits: mantissa.bytes().filter(|c| *c != b'.').collect(),
+            point: exponent.parse::<i32>().unwrap() + 1,
+        };
+        decimal.trim();
+        decimal
+    }
+
+    /// BigInt的绝对值
+    fn from_bigint(n: &BigInt) -> Self {
+        let digits = n.magnitude().to_string();
+        let mut decimal = Decimal {
+            point: digits.len() as i32,
+            digits: digits.into_bytes(),
+        };
+        decimal.trim();
+        decimal
+    }
+
+    fn trim(&mut self) {
+        while self.digits.last() == Some(&b'0') {
+            self.digits.pop();
+        }
+        if self.digits.is_empty() {
+            self.point = 0;
+        }
+    }
+
+    /// 只保留前count位数字, 恰好一半时远离0
+    fn round(&mut self, count: i32) {
+        if count < 0 {
+            self.digits.clear();
+        } else if (count as usize) < self.digits.len() {
+            let up = self.digits[count as usize] >= b'5';
+            self.digits.truncate(count as usize);
+            if up && increment(&mut self.digits) {
+                self.point += 1;
+            }
+        }
+        self.trim();
+    }
+}
+
+impl NumberFormat {
+    /// x为Number或BigInt
+    fn format(&self, x: &JSType) -> String {
+        let (mut value, negative) = match x {
+            JSType::BigInt(n) => (Decimal::from_bigint(n), n.is_negative()),
+            _ => {
+                let x = x.to_number();
+                let negative = x.is_sign_negative() && !x.is_nan();
+                if x.is_nan() {
+                    return self.signed("NaN".to_string(), false, negative);
+                } else if x.is_infinite() {
+                    return self.signed("∞".to_string(), false, negative);
+                }
+                (Decimal::new(x.abs()), negative)
+            }
+        };
+        if self.style == "percent" {
+            value.point += 2;
+        }
+        let (number, zero) = self.digits(value);
+        self.signed(number, zero, negative)
+    }
+
+    /// 加上符号及货币/百分号
+    fn signed(&self, number: String, zero: bool, negative: bool) -> String {
+        let sign = match self.sign_display {
+            "always" => {
+                if negative {
+                    "-"
+                } else {
+                    "+"
+                }
+            }
+            "exceptZero" if zero => "",
+            "exceptZero" => {
+                if negative {
+                    "-"
+                } else {
+                    "+"
+                }
+            }
+            "never" => "",
+            "negative" if zero => "",
+            _ => {
+                if negative {
+                    "-"
+                } else {
+                    ""
+                }
+            }
+        };
+        let body = match (&self.currency, self.style) {
+            (Some((code, display)), "currency") => self.currency_string(&number, code, display),
+            (_, "percent") => self.locale.percent.replace('#', &number),
+            _ => number,
+        };
+        format!("{}{}", sign, body)
+    }
+
+    /// 按位数舍入并加上千分位, 返回结果及是否为0
+    fn digits(&self, mut value: Decimal) -> (String, bool) {
+        let fraction = match self.significant_digits {
+            Some((min, max)) => {
+                value.round(max as i32);
+                //0作为1位整数
+                let point = if value.digits.is_empty() {
+                    1
+                } else {
+                    value.point
+                };
+                (value.digits.len().max(min) as i32 - point).max(0) as usize
+            }
+            None => {
+                let (min, max) = self.fraction_digits;
+                value.round(value.point + max as i32);
+                let shown = (value.digits.len() as i32 - value.point).max(0) as usize;
+                shown.max(min)
+            }
+        };
+        let zero = value.digits.is_empty();
+        let digit = |i: i32| -> char {
+            match value.digits.get(i as usize) {
+                Some(d) if i >= 0 => *d as char,
+                _ => '0',
+            }
+        };
+        let mut integer: String = (0..value.point.max(0)).map(digit).collect();
+        while integer.len() < self.minimum_integer_digits {
+            integer.insert(0, '0');
+        }
+        let grouped = match self.grouping {
+            Some("min2") => integer.len() >= 5,
+            Some(_) => true,
+            None => false,
+        };
+        let mut result = String::new();
+        for (index, c) in integer.chars().enumerate() {
+            if grouped && index > 0 && (integer.len() - index).is_multiple_of(3) {
+                result.push_str(self.locale.group);
+            }
+            result.push(c);
+        }
+        if fraction > 0 {
+            result.push_str(self.locale.decimal);
+            result.extend((0..fraction as i32).map(|i| digit(value.point + i)));
+        }
+        (result, zero)
+    }
+
+    /// 货币符号与数字相邻且符号为字母时中间加空格, 如 USD 1.00
+    fn currency_string(&self, number: &str, code: &str, display: &str) -> String {
+        let find = |list: &[(&str, &'static str)]| {
+            list.iter()
+                .find(|(c, _)| *c == code)
+                .map(|(_, symbol)| symbol.to_string())
+        };
+        let symbol = match display {
+            "code" => None,
+            "narrowSymbol" => find(&NARROW_SYMBOLS).or_else(|| find(self.locale.symbols)),
+            _ => find(self.locale.symbols),
+        }
+        .unwrap_or_else(|| code.to_string());
+        let pattern = self.locale.currency;
+        let letter = |c: Option<char>| c.is_some_and(char::is_alphabetic);
+        let pattern = if pattern.contains("¤#") && letter(symbol.chars().last()) {
+            pattern.replace("¤#", "¤\u{a0}#")
+        } else if pattern.contains("#¤") && letter(symbol.chars().next()) {
+            pattern.replace("#¤", "#\u{a0}¤")
+        } else {
+            pattern.to_string()
+        };
+        pattern.replace('¤', &symbol).replace('#', number)
+    }
+}
+
+impl Program {
+    /// InitializeNumberFormat
+    fn create_number_format(
+        &mut self,
+        locales: &JSType,
+        options: &JSType,
+    ) -> Result<NumberFormat, String> {
+        const SERVICE: &str = "Intl.NumberFormat";
+        let locale = self.resolve_locale(locales, lookup_locale)?;
+        let options = self.intl_options(options)?;
+        self.string_option(&options, "localeMatcher", &["lookup", "best fit"], SERVICE)?;
+        let style = self
+            .string_option(
+                &options,
+                "style",
+                &["decimal", "percent", "currency"],
+                SERVICE,
+            )?
+            .unwrap_or("decimal");
+        let currency = match self.intl_option_value(&options, "currency")? {
+            Some(code) => {
+                if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(err(&format!(
+                        "Uncaught RangeError: Invalid currency code : {}",
+                        code
+                    )));
+                }
+                Some(code.to_ascii_uppercase())
+            }
+            None => None,
+        };
+        let display = self
+            .string_option(
+                &options,
+                "currencyDisplay",
+                &["code", "symbol", "narrowSymbol"],
+                SERVICE,
+            )?
+            .unwrap_or("symbol");
+        if style == "currency" && currency.is_none() {
+            return Err(err(
+                "Uncaught TypeError: Currency code is required with currency style.",
+            ));
+        }
+        let (default_min, default_max) = match (style, &currency) {
+            ("currency", Some(code)) => {
+                let digits = CURRENCY_DIGITS
+                    .iter()
+                    .find(|(c, _)| c == code)
+                    .map_or(2, |(_, d)| *d);
+                (digits, digits)
+            }
+            ("percent", _) => (0, 0),
+            _ => (0, 3),
+        };
+        let minimum_integer_digits = self
+            .number_option(&options, "minimumIntegerDigits", 1, 21)?
+            .unwrap_or(1);
+        let min_fraction = self.number_option(&options, "minimumFractionDigits", 0, 100)?;
+        let max_fraction = self.number_option(&options, "maximumFractionDigits", 0, 100)?;
+        let min_significant = self.number_option(&options, "minimumSignificantDigits", 1, 21)?;
+        let max_significant = self.number_option(&options, "maximumSignificantDigits", 1, 21)?;
+        let significant_digits = if min_significant.is_some() || max_significant.is_some() {
+            let min = min_significant.unwrap_or(1);
+            let max = max_significant.unwrap_or(21);
+            if min > max {
+                return Err(err(
+                    "Uncaught RangeError: maximumSignificantDigits value is out of range.",
+                ));
+            }
+            Some((min, max))
+        } else {
+            None
+        };
+        let fraction_digits = match (min_fraction, max_fraction) {
+            (None, None) => (default_min, default_max),
+            (min, max) => {
+                let min = min.unwrap_or(default_min.min(max.unwrap_or(default_min)));
+                let max = max.unwrap_or(default_max.max(min));
+                if min > max {
+                    return Err(err(
+                        "Uncaught RangeError: maximumFractionDigits value is out of range.",
+                    ));
+                }
+                (min, max)
+            }
+        };
+        let grouping = self.grouping_option(&options)?;
+        let sign_display = self
+            .string_option(
+                &options,
+                "signDisplay",
+                &["auto", "never", "always", "exceptZero", "negative"],
+                SERVICE,
+            )?
+            .unwrap_or("auto");
+        Ok(NumberFormat {
+            locale,
+            style,
+            currency: currency.map(|code| (code, display)),
+            minimum_integer_digits,
+            fraction_digits,
+            significant_digits,
+            grouping,
+            sign_display,
+            bound_format: None,
+        })
+    }
+
+    /// 字符串选项的值, 不检查范围
+    fn intl_option_value(
+        &mut self,
+        options: &JSType,
+        name: &str,
+    ) -> Result<Option<String>, String> {
+        if let JSType::Undefined = options {
+            return Ok(None);
+        }
+        match self.get_property(options, name)? {
+            JSType::Undefined => Ok(None),
+            value => Ok(Some(self.string_value(&value)?)),
+        }
+    }
+
+    /// useGrouping: true/"always" "auto" "min2" false
+    fn grouping_option(&mut self, options: &JSType) -> Result<Option<&'static str>, String> {
+        if let JSType::Undefined = options {
+            return Ok(Some("auto"));
+        }
+        let value = self.get_property(options, "useGrouping")?;
+        match value {
+            JSType::Undefined => Ok(Some("auto")),
+            JSType::Bool(true) => Ok(Some("always")),
+            _ if !value.is_truthy() => Ok(None),
+            _ => {
+                let name = self.string_value(&value)?;
+                match name.as_str() {
+                    "always" => Ok(Some("always")),
+                    "min2" => Ok(Some("min2")),
+                    "auto" | "true" | "false" => Ok(Some("auto")),
+                    _ => Err(err(&format!(
+                        "Uncaught RangeError: Value {} out of range for Intl.NumberFormat options property useGrouping",
+                        name
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Number.prototype.toLocaleString / BigInt.prototype.toLocaleString
+    pub(super) fn number_to_locale_string(
+        &mut self,
+        x: &JSType,
+        locales: &JSType,
+        options: &JSType,
+    ) -> Result<String, String> {
+        Ok(self.create_number_format(locales, options)?.format(x))
+    }
+
+    /// 新建Intl对象, proto为new.target的prototype
+    fn alloc_intl(
+        &mut self,
+        kind: ObjectKind,
+        new_target: &JSType,
+        name: &str,
+    ) -> Result<JSType, String> {
+        let proto = self.get_prototype_from_constructor(new_target, name)?;
+        Ok(JSType::Object(self.alloc(JSObject::new(kind, Some(proto)))))
+    }
+}
+
+fn this_number_format(
+    program: &Program,
+    this: &JSType,
+    method: &str,
+) -> Result<(ObjectId, NumberFormat), String> {
+    if let JSType::Object(id) = this {
+        if let ObjectKind::NumberFormat(data) = &program.obj(*id).kind {
+            return Ok((*id, data.as_ref().clone()));
+        }
+    }
+    Err(incompatible_receiver(
+        program,
+        &format!("Intl.NumberFormat.prototype.{}", method),
+        this,
+    ))
+}
+
+/// 不使用new调用时同样创建对象
+fn number_format_call(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    number_format_construct(program, args, JSType::Undefined)
+}
+
+fn number_format_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let data = program.create_number_format(&arg(&args, 0), &arg(&args, 1))?;
+    program.alloc_intl(
+        ObjectKind::NumberFormat(Box::new(data)),
+        &new_target,
+        "Intl.NumberFormat",
+    )
+}
+
+/// get format, 每个对象的format函数只创建一次
+fn number_format_format(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (id, data) = this_number_format(program, &this, "format")?;
+    if let Some(bound) = data.bound_format {
+        return Ok(JSType::Object(bound));
+    }
+    let bound = program.bound_method(id, 1, number_format_bound_format);
+    if let ObjectKind::NumberFormat(data) = &mut program.obj_mut(id).kind {
+        data.bound_format = Some(bound);
+    }
+    Ok(JSType::Object(bound))
+}
+
+fn number_format_bound_format(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, data) = this_number_format(program, &this, "format")?;
+    let x = program.numeric_value(&arg(&args, 0))?;
+    Ok(JSType::String(data.format(&x).into()))
+}
+
+fn number_format_resolved_options(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, data) = this_number_format(program, &this, "resolvedOptions")?;
+    let number = |n: usize| Some(JSType::Int(n as i64));
+    let (currency, display) = match &data.currency {
+        Some((code, display)) if data.style == "currency" => {
+            (string_field(code), string_field(display))
+        }
+        _ => (None, None),
+    };
+    let (fraction, significant) = match data.significant_digits {
+        Some((min, max)) => ((None, None), (number(min), number(max))),
+        None => (
+            (
+                number(data.fraction_digits.0),
+                number(data.fraction_digits.1),
+            ),
+            (None, None),
+        ),
+    };
+    let fields = vec![
+        ("locale", string_field(data.locale.tag)),
+        ("numberingSystem", string_field("latn")),
+        ("style", string_field(data.style)),
+        ("currency", currency),
+        ("currencyDisplay", display),
+        ("minimumIntegerDigits", number(data.minimum_integer_digits)),
+        ("minimumFractionDigits", fraction.0),
+        ("maximumFractionDigits", fraction.1),
+        ("minimumSignificantDigits", significant.0),
+        ("maximumSignificantDigits", significant.1),
+        (
+            "useGrouping",
+            Some(
+                data.grouping
+                    .map_or(JSType::Bool(false), |g| JSType::String(g.into())),
+            ),
+        ),
+        ("notation", string_field("standard")),
+        ("signDisplay", string_field(data.sign_display)),
+        ("roundingMode", string_field("halfExpand")),
+    ];
+    Ok(program.resolved_options(fields))
+}
+
+//------------------------------ DateTimeFormat ------------------------------
+
+/// Intl.DateTimeFormat的内部数据, 创建时确定格式
+#[derive(Clone)]
+pub(crate) struct DateTimeFormat {
+    locale: &'static LocaleData,
+    components: [Option<&'static str>; 7], //按COMPONENTS的顺序
+    date_style: Option<&'static str>,
+    time_style: Option<&'static str>,
+    hour12: bool,
+    time_zone: Option<i64>, //固定的时差(分钟), None为本地时间
+    time_zone_name: String,
+    pattern: String,
+    pub(crate) bound_format: Option<ObjectId>,
+}
+
+/// 时区名称 UTC / GMT+8 / GMT+5:30
+fn zone_name(offset: i64) -> String {
+    if offset == 0 {
+        return "UTC".to_string();
+    }
+    let sign = if offset < 0 { '-' } else { '+' };
+    match offset.abs() % 60 {
+        0 => format!("GMT{}{}", sign, offset.abs() / 60),
+        minutes => format!("GMT{}{}:{:02}", sign, offset.abs() / 60, minutes),
+    }
+}
+
+/// timeZone选项: UTC或固定时差 +08:00, 结果为(规范名称, 时差分钟)
+fn parse_time_zone(name: &str) -> Option<(String, i64)> {
+    if ["UTC", "ETC/UTC", "GMT", "ETC/GMT"].contains(&name.to_ascii_uppercase().as_str()) {
+        return Some(("UTC".to_string(), 0));
+    }
+    let sign = match name.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = name[1..].chars().filter(|c| *c != ':').collect();
+    let valid_colon = !name.contains(':') || name.len() == 6 && &name[3..4] == ":";
+    if !valid_colon || !matches!(digits.len(), 2 | 4) || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = if digits.len() == 4 {
+        digits[2..].parse().ok()?
+    } else {
+        0
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    let offset = sign * (hours * 60 + minutes);
+    let sign = if sign < 0 { '-' } else { '+' };
+    Some((format!("{}{:02}:{:02}", sign, hours, minutes), offset))
+}
+
+impl DateTimeFormat {
+    fn component(&self, name: &str) -> Option<&'static str> {
+        let index = COMPONENTS.iter().position(|(n, _)| *n == name).unwrap();
+        self.components[index]
+    }
+
+    /// 按选项确定格式
+    fn build_pattern(&self) -> String {
+        let locale = self.locale;
+        let date = match self.date_style {
+            Some(style) => {
+                let index = STYLES.iter().position(|s| *s == style).unwrap();
+                Some(locale.date_styles[index].to_string())
+            }
+            None => self.date_pattern(),
+        };
+        let time = match self.time_style {
+            Some(style) => {
+                let time = self.time_pattern(true, true, style != "short");
+                if matches!(style, "full" | "long") {
+                    Some(locale.zoned_time.replace("{0}", &time))
+                } else {
+                    Some(time)
+                }
+            }
+            None => {
+                let [_, _, _, _, hour, minute, second] = self.components;
+                if hour.is_none() && minute.is_none() && second.is_none() {
+                    None
+                } else {
+                    Some(self.time_pattern(hour.is_some(), minute.is_some(), second.is_some()))
+                }
+            }
+        };
+        match (date, time) {
+            (Some(date), Some(time)) => {
+                locale.date_time.replace("{0}", &date).replace("{1}", &time)
+            }
+            (Some(date), None) => date,
+            (None, Some(time)) => time,
+            (None, None) => String::new(),
+        }
+    }
+
+    fn date_pattern(&self) -> Option<String> {
+        let locale = self.locale;
+        let [weekday, year, month, day, ..] = self.components;
+        let index = match (year.is_some(), month.is_some(), day.is_some()) {
+            (false, false, false) => return weekday.map(|_| "E".to_string()),
+            (true, true, false) => 1,
+            (false, true, true) => 2,
+            (true, false, false) => 3,
+            (false, true, false) => 4,
+            (false, false, true) => 5,
+            _ => 0,
+        };
+        let text = matches!(month, Some("narrow" | "short" | "long"));
+        let pattern = match index {
+            0..=2 if text => locale.text_dates[index],
+            4 if text => locale.text_dates[3],
+            _ => locale.numeric_dates[index],
+        };
+        Some(match weekday {
+            Some(_) => locale.weekday_date.replace("{0}", pattern),
+            None => pattern.to_string(),
+        })
+    }
+
+    /// 时间格式去掉不需要的字段
+    fn time_pattern(&self, hour: bool, minute: bool, second: bool) -> String {
+        let mut pattern = self.locale.times[if self.hour12 { 0 } else { 1 }].to_string();
+        if !second {
+            pattern = pattern.replace(":ss", "");
+        }
+        if !minute {
+            pattern = pattern.replace(":mm", "");
+        }
+        if !hour {
+            let start = pattern.find(['h', 'H', 'K']).unwrap();
+            let end = pattern[start..]
+                .find(|c: char| !matches!(c, 'h' | 'H' | 'K'))
+                .map_or(pattern.len(), |i| start + i);
+            let end = if pattern[end..].starts_with(':') {
+                end + 1
+            } else {
+                end
+            };
+            pattern.replace_range(start..end, "");
+            pattern = pattern.replace(" a", "").replace("a ", "").replace('a', "");
+        }
+        pattern
+    }
+
+    /// 格式化有效的时间值
+    fn format(&self, program: &Program, t: f64) -> String {
+        let locale = self.locale;
+        let offset = self.time_zone.unwrap_or_else(|| program.local_offset(t));
+        let [year, month, date, hour, minute, second, _, week_day] =
+            decompose(t + offset as f64 * 60_000.0);
+        let (year, month, date, hour) = (year as i64, month as usize, date as i64, hour as i64);
+        let two_digit = |name: &str| self.component(name) == Some("2-digit");
+        let number = |n: i64, pad: bool| {
+            if pad {
+                format!("{:02}", n)
+            } else {
+                n.to_string()
+            }
+        };
+        let text_style = |name: &str, width: usize| match self.component(name) {
+            Some("long") => 0,
+            Some("short") => 1,
+            Some("narrow") => 2,
+            _ => usize::from(width < 4),
+        };
+        let chars: Vec<char> = self.pattern.chars().collect();
+        let mut result = String::new();
+        let mut index = 0;
+        while index < chars.len() {
+            let c = chars[index];
+            let mut width = 1;
+            if c.is_ascii_alphabetic() {
+                while chars.get(index + width) == Some(&c) {
+                    width += 1;
+                }
+            }
+            index += width;
+            let pad = width == 2;
+            let field = match c {
+                'y' if pad || two_digit("year") => format!("{:02}", year.rem_euclid(100)),
+                'y' => year.to_string(),
+                'M' if width >= 3 => locale.months[text_style("month", width)][month].to_string(),
+                'M' => number(month as i64 + 1, pad || two_digit("month")),
+                'd' => number(date, pad || two_digit("day")),
+                'E' => locale.weekdays[text_style("weekday", width)][week_day as usize].to_string(),
+                'h' => number((hour + 11) % 12 + 1, pad || two_digit("hour")),
+                'K' => number(hour % 12, pad || two_digit("hour")),
+                'H' => number(hour, pad || two_digit("hour")),
+                'm' => number(minute as i64, pad),
+                's' => number(second as i64, pad),
+                'a' => locale.day_periods[usize::from(hour >= 12)].to_string(),
+                'z' => zone_name(offset),
+                _ => c.to_string(),
+            };
+            result.push_str(&field);
+        }
+        result
+    }
+}
+
+impl Program {
+    /// CreateDateTimeFormat, required/defaults为 date/time/any 及 date/time/all
+    fn create_date_time_format(
+        &mut self,
+        locales: &JSType,
+        options: &JSType,
+        required: &str,
+        defaults: &str,
+    ) -> Result<DateTimeFormat, String> {
+        const SERVICE: &str = "Intl.DateTimeFormat";
+        let locale = self.resolve_locale(locales, lookup_locale)?;
+        let options = self.intl_options(options)?;
+        self.string_option(&options, "localeMatcher", &["lookup", "best fit"], SERVICE)?;
+        let hour12 = self.bool_option(&options, "hour12")?;
+        let hour_cycle = self.string_option(
+            &options,
+            "hourCycle",
+            &["h11", "h12", "h23", "h24"],
+            SERVICE,
+        )?;
+        let (time_zone_name, time_zone) = match self.intl_option_value(&options, "timeZone")? {
+            Some(name) => match parse_time_zone(&name) {
+                Some((name, offset)) => (name, Some(offset)),
+                None => {
+                    return Err(err(&format!(
+                        "Uncaught RangeError: Invalid time zone specified: {}",
+                        name
+                    )))
+                }
+            },
+            None => {
+                let offset = self.local_offset(self.current_time());
+                match offset {
+                    0 => ("UTC".to_string(), None),
+                    _ => (
+                        parse_time_zone(&format!(
+                            "{}{:02}:{:02}",
+                            if offset < 0 { '-' } else { '+' },
+                            offset.abs() / 60,
+                            offset.abs() % 60
+                        ))
+                        .map_or(String::new(), |(name, _)| name),
+                        None,
+                    ),
+                }
+            }
+        };
+        let mut components = [None; 7];
+        for (index, (name, values)) in COMPONENTS.iter().enumerate() {
+            components[index] = self.string_option(&options, name, values, SERVICE)?;
+        }
+        let date_style = self.string_option(&options, "dateStyle", &STYLES, SERVICE)?;
+        let time_style = self.string_option(&options, "timeStyle", &STYLES, SERVICE)?;
+        if let Some(style) = date_style
+            .map(|_| "dateStyle")
+            .or(time_style.map(|_| "timeStyle"))
+        {
+            if let Some(index) = components.iter().position(Option::is_some) {
+                return Err(err(&format!(
+                    "Uncaught TypeError: Can't set option {} when {} is used",
+                    COMPONENTS[index].0, style
+                )));
+            }
+        }
+        if required == "date" && time_style.is_some() {
+            return Err(err("Uncaught TypeError: Invalid option : timeStyle"));
+        }
+        if required == "time" && date_style.is_some() {
+            return Err(err("Uncaught TypeError: Invalid option : dateStyle"));
+        }
+        //ToDateTimeOptions, 没有指定字段时使用默认的日期/时间字段
+        if date_style.is_none() && time_style.is_none() {
+            let has_date = components[..4].iter().any(Option::is_some);
+            let has_time = components[4..].iter().any(Option::is_some);
+            let need_defaults = match required {
+                "date" => !has_date,
+                "time" => !has_time,
+                _ => !has_date && !has_time,
+            };
+            if need_defaults && defaults != "time" {
+                components[1..4].fill(Some("numeric"));
+            }
+            if need_defaults && defaults != "date" {
+                components[4..].fill(Some("numeric"));
+            }
+        }
+        let hour12 = hour12
+            .or(hour_cycle.map(|c| matches!(c, "h11" | "h12")))
+            .unwrap_or(locale.hour12);
+        let mut format = DateTimeFormat {
+            locale,
+            components,
+            date_style,
+            time_style,
+            hour12,
+            time_zone,
+            time_zone_name,
+            pattern: String::new(),
+            bound_format: None,
+        };
+        format.pattern = format.build_pattern();
+        Ok(format)
+    }
+
+    /// Date.prototype.toLocaleString/toLocaleDateString/toLocaleTimeString
+    pub(super) fn date_to_locale_string(
+        &mut self,
+        t: f64,
+        args: &[JSType],
+        required: &str,
+        defaults: &str,
+    ) -> Result<String, String> {
+        let format =
+            self.create_date_time_format(&arg(args, 0), &arg(args, 1), required, defaults)?;
+        if t.is_nan() {
+            return Ok("Invalid Date".to_string());
+        }
+        Ok(format.format(self, t))
+    }
+}
+
+fn this_date_time_format(
+    program: &Program,
+    this: &JSType,
+    method: &str,
+) -> Result<(ObjectId, DateTimeFormat), String> {
+    if let JSType::Object(id) = this {
+        if let ObjectKind::DateTimeFormat(data) = &program.obj(*id).kind {
+            return Ok((*id, data.as_ref().clone()));
+        }
+    }
+    Err(incompatible_receiver(
+        program,
+        &format!("Intl.DateTimeFormat.prototype.{}", method),
+        this,
+    ))
+}
+
+fn date_time_format_call(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    date_time_format_construct(program, args, JSType::Undefined)
+}
+
+fn date_time_format_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let data = program.create_date_time_format(&arg(&args, 0), &arg(&args, 1), "any", "date")?;
+    program.alloc_intl(
+        ObjectKind::DateTimeFormat(Box::new(data)),
+        &new_target,
+        "Intl.DateTimeFormat",
+    )
+}
+
+fn date_time_format_format(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (id, data) = this_date_time_format(program, &this, "format")?;
+    if let Some(bound) = data.bound_format {
+        return Ok(JSType::Object(bound));
+    }
+    let bound = program.bound_method(id, 1, date_time_format_bound_format);
+    if let ObjectKind::DateTimeFormat(data) = &mut program.obj_mut(id).kind {
+        data.bound_format = Some(bound);
+    }
+    Ok(JSType::Object(bound))
+}
+
+/// format(date), 没有参数时为当前时间, 无效的时间抛出RangeError
+fn date_time_format_bound_format(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, data) = this_date_time_format(program, &this, "format")?;
+    let t = match arg(&args, 0) {
+        JSType::Undefined => program.current_time(),
+        value => program.number_value(&value)?,
+    };
+    let t = time_clip(t);
+    if t.is_nan() {
+        return Err(err("Uncaught RangeError: Invalid time value"));
+    }
+    Ok(JSType::String(data.format(program, t).into()))
+}
+
+fn date_time_format_resolved_options(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, data) = this_date_time_format(program, &this, "resolvedOptions")?;
+    let has_hour = data.component("hour").is_some() || data.time_style.is_some();
+    let hour_cycle = match (data.hour12, data.locale.times[0].contains('K')) {
+        (true, true) => "h11",
+        (true, false) => "h12",
+        _ => "h23",
+    };
+    let mut fields = vec![
+        ("locale", string_field(data.locale.tag)),
+        ("calendar", string_field("gregory")),
+        ("numberingSystem", string_field("latn")),
+        ("timeZone", string_field(&data.time_zone_name)),
+        (
+            "hourCycle",
+            has_hour.then(|| JSType::String(hour_cycle.into())),
+        ),
+        ("hour12", has_hour.then_some(JSType::Bool(data.hour12))),
+    ];
+    for (index, (name, _)) in COMPONENTS.iter().enumerate() {
+        fields.push((name, data.components[index].and_then(string_field)));
+    }
+    fields.push(("dateStyle", data.date_style.and_then(string_field)));
+    fields.push(("timeStyle", data.time_style.and_then(string_field)));
+    Ok(program.resolved_options(fields))
+}
+
+//------------------------------ Collator ------------------------------
+
+/// 带音调的拉丁字母: (组合音调, 基本字母, 对应的预组合字母)
+const LATIN_ACCENTS: [(char, &str, &str); 14] = [
+    ('\u{300}', "AEIOUaeiou", "ÀÈÌÒÙàèìòù"),
+    (
+        '\u{301}',
+        "AEIOUYaeiouyCcLlNnRrSsZz",
+        "ÁÉÍÓÚÝáéíóúýĆćĹĺŃńŔŕŚśŹź",
+    ),
+    (
+        '\u{302}',
+        "AEIOUaeiouCcGgHhJjSsWwYy",
+        "ÂÊÎÔÛâêîôûĈĉĜĝĤĥĴĵŜŝŴŵŶŷ",
+    ),
+    ('\u{303}', "ANOanoIiUu", "ÃÑÕãñõĨĩŨũ"),
+    ('\u{304}', "AaEeIiOoUu", "ĀāĒēĪīŌōŪū"),
+    ('\u{306}', "AaEeGgIiOoUu", "ĂăĔĕĞğĬĭŎŏŬŭ"),
+    ('\u{307}', "CcEeGgIZz", "ĊċĖėĠġİŻż"),
+    ('\u{308}', "AEIOUaeiouyY", "ÄËÏÖÜäëïöüÿŸ"),
+    ('\u{30a}', "AaUu", "ÅåŮů"),
+    ('\u{30b}', "OoUu", "ŐőŰű"),
+    (
+        '\u{30c}',
+        "CcDdEeLlNnRrSsTtZzAaIiOoUu",
+        "ČčĎďĚěĽľŇňŘřŠšŤťŽžǍǎǏǐǑǒǓǔ",
+    ),
+    ('\u{326}', "SsTt", "ȘșȚț"),
+    ('\u{327}', "CcGgKkLlNnRrSsTt", "ÇçĢģĶķĻļŅņŖŗŞşŢţ"),
+    ('\u{328}', "AaEeIiUu", "ĄąĘęĮįŲų"),
+];
+
+/// 把预组合的拉丁字母拆为基本字母及音调, 不在表中的字符原样返回
+fn split_accent(c: char) -> (char, Option<char>) {
+    if c.is_ascii() {
+        return (c, None);
+    }
+    for (mark, bases, letters) in LATIN_ACCENTS {
+        if let Some(i) = letters.chars().position(|l| l == c) {
+            return (bases.chars().nth(i).unwrap(), Some(mark));
+        }
+    }
+    (c, None)
+}
+
+/// Intl.Collator的内部数据
+#[derive(Clone)]
+pub(crate) struct Collator {
+    locale: &'static LocaleData,
+    usage: &'static str,
+    sensitivity: &'static str,
+    ignore_punctuation: bool,
+    numeric: bool,
+    case_first: &'static str,
+    pub(crate) bound_compare: Option<ObjectId>,
+}
+
+/// 排序用的字符: 去掉音调并转为小写的字符, 音调, 是否大写
+struct CollationElement {
+    primary: String,
+    accents: Vec<char>,
+    upper: bool,
+}
+
+impl Collator {
+    /// 分解为排序字符, 只区分拉丁字母的音调及大小写, 其他文字按码位排序
+    fn elements(&self, s: &JSString) -> Vec<CollationElement> {
+        let is_mark = |c: char| ('\u{300}'..='\u{36F}').contains(&c);
+        let mut list: Vec<CollationElement> = Vec::new();
+        for c in char::decode_utf16(s.units().iter().cloned()) {
+            let (c, accent) = split_accent(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+            if is_mark(c) {
+                if let Some(last) = list.last_mut() {
+                    last.accents.push(c);
+                }
+                continue;
+            }
+            if self.ignore_punctuation && (c.is_ascii_punctuation() || c.is_whitespace()) {
+                continue;
+            }
+            list.push(CollationElement {
+                primary: c.to_lowercase().collect(),
+                accents: accent.into_iter().collect(),
+                upper: c.is_uppercase(),
+            });
+        }
+        list
+    }
+
+    /// 第一级比较, numeric时连续的数字按数值比较
+    fn compare_primary(&self, a: &[CollationElement], b: &[CollationElement]) -> Ordering {
+        let is_digit =
+            |e: &CollationElement| e.primary.len() == 1 && e.primary.as_bytes()[0].is_ascii_digit();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if self.numeric && is_digit(&a[i]) && is_digit(&b[j]) {
+                let number = |list: &[CollationElement], start: usize| {
+                    let end = list[start..]
+                        .iter()
+                        .position(|e| !is_digit(e))
+                        .map_or(list.len(), |n| start + n);
+                    let digits: String = list[start..end]
+                        .iter()
+                        .map(|e| e.primary.as_str())
+                        .collect();
+                    (digits.trim_start_matches('0').to_string(), end)
+                };
+                let (x, next_i) = number(a, i);
+                let (y, next_j) = number(b, j);
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                (i, j) = (next_i, next_j);
+                continue;
+            }
+            let ordering = a[i].primary.cmp(&b[j].primary);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            i += 1;
+            j += 1;
+        }
+        (a.len() - i).cmp(&(b.len() - j))
+    }
+
+    fn compare(&self, a: &JSString, b: &JSString) -> Ordering {
+        let (a, b) = (self.elements(a), self.elements(b));
+        let mut ordering = self.compare_primary(&a, &b);
+        if matches!(self.sensitivity, "accent" | "variant") {
+            ordering = ordering.then_with(|| {
+                let accents = |list: &[CollationElement]| -> Vec<Vec<char>> {
+                    list.iter().map(|e| e.accents.clone()).collect()
+                };
+                accents(&a).cmp(&accents(&b))
+            });
+        }
+        if matches!(self.sensitivity, "case" | "variant") {
+            ordering = ordering.then_with(|| {
+                let cases = |list: &[CollationElement]| -> Vec<bool> {
+                    list.iter()
+                        .map(|e| e.upper != (self.case_first == "upper"))
+                        .collect()
+                };
+                cases(&a).cmp(&cases(&b))
+            });
+        }
+        ordering
+    }
+}
+
+impl Program {
+    /// InitializeCollator
+    fn create_collator(&mut self, locales: &JSType, options: &JSType) -> Result<Collator, String> {
+        const SERVICE: &str = "Intl.Collator";
+        let locale = self.resolve_locale(locales, lookup_collation_locale)?;
+        let options = self.intl_options(options)?;
+        let usage = self
+            .string_option(&options, "usage", &["sort", "search"], SERVICE)?
+            .unwrap_or("sort");
+        self.string_option(&options, "localeMatcher", &["lookup", "best fit"], SERVICE)?;
+        let numeric = self.bool_option(&options, "numeric")?.unwrap_or(false);
+        let case_first = self
+            .string_option(&options, "caseFirst", &["upper", "lower", "false"], SERVICE)?
+            .unwrap_or("false");
+        let sensitivity = self
+            .string_option(
+                &options,
+                "sensitivity",
+                &["base", "accent", "case", "variant"],
+                SERVICE,
+            )?
+            .unwrap_or("variant");
+        let ignore_punctuation = self
+            .bool_option(&options, "ignorePunctuation")?
+            .unwrap_or(false);
+        Ok(Collator {
+            locale,
+            usage,
+            sensitivity,
+            ignore_punctuation,
+            numeric,
+            case_first,
+            bound_compare: None,
+        })
+    }
+
+    /// String.prototype.localeCompare
+    pub(super) fn locale_compare(
+        &mut self,
+        a: &JSString,
+        b: &JSString,
+        locales: &JSType,
+        options: &JSType,
+    ) -> Result<Ordering, String> {
+        Ok(self.create_collator(locales, options)?.compare(a, b))
+    }
+}
+
+fn this_collator(
+    program: &Program,
+    this: &JSType,
+    method: &str,
+) -> Result<(ObjectId, Collator), String> {
+    if let JSType::Object(id) = this {
+        if let ObjectKind::Collator(data) = &program.obj(*id).kind {
+            return Ok((*id, data.as_ref().clone()));
+        }
+    }
+    Err(incompatible_receiver(
+        program,
+        &format!("Intl.Collator.prototype.{}", method),
+        this,
+    ))
+}
+
+fn collator_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    collator_construct(program, args, JSType::Undefined)
+}
+
+fn collator_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let data = program.create_collator(&arg(&args, 0), &arg(&args, 1))?;
+    program.alloc_intl(
+        ObjectKind::Collator(Box::new(data)),
+        &new_target,
+        "Intl.Collator",
+    )
+}
+
+/// get compare, 可直接传给Array.prototype.sort
+fn collator_compare(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let (id, data) = this_collator(program, &this, "compare")?;
+    if let Some(bound) = data.bound_compare {
+        return Ok(JSType::Object(bound));
+    }
+    let bound = program.bound_method(id, 2, collator_bound_compare);
+    if let ObjectKind::Collator(data) = &mut program.obj_mut(id).kind {
+        data.bound_compare = Some(bound);
+    }
+    Ok(JSType::Object(bound))
+}
+
+fn collator_bound_compare(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, data) = this_collator(program, &this, "compare")?;
+    let a = program.js_string_value(&arg(&args, 0))?;
+    let b = program.js_string_value(&arg(&args, 1))?;
+    Ok(JSType::Int(match data.compare(&a, &b) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }))
+}
+
+fn collator_resolved_options(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, data) = this_collator(program, &this, "resolvedOptions")?;
+    let fields = vec![
+        ("locale", string_field(data.locale.tag)),
+        ("usage", string_field(data.usage)),
+        ("sensitivity", string_field(data.sensitivity)),
+        (
+            "ignorePunctuation",
+            Some(JSType::Bool(data.ignore_punctuation)),
+        ),
+        ("collation", string_field("default")),
+        ("numeric", Some(JSType::Bool(data.numeric))),
+        ("caseFirst", string_field(data.case_first)),
+    ];
+    Ok(program.resolved_options(fields))
+}
@@ -0,0 +1,24 @@
+use super::arg;
+use crate::jsparser::object::{JSObject, ObjectKind};
+use crate::jsparser::program::{JSType, Program};
+
+impl Program {
+    pub(super) fn init_json(&mut self) {
+        let proto = self.intrinsics.object_prototype;
+        let json = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(proto)));
+        self.define_method(json, "parse", 2, json_parse);
+        self.define_method(json, "stringify", 3, json_stringify);
+        self.define_to_string_tag(json, "JSON");
+        self.bind_value("JSON".to_string(), JSType::Object(json));
+    }
+}
+
+/// JSON.parse(text, reviver)
+fn json_parse(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.json_parse(&arg(&args, 0), &arg(&args, 1))
+}
+
+/// JSON.stringify(value, replacer, space)
+fn json_stringify(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    program.json_stringify(arg(&args, 0), &arg(&args, 1), &arg(&args, 2))
+}
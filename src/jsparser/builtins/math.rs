@@ -0,0 +1,350 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use super::arg;
+use crate::jsparser::object::{JSObject, NativeFn, ObjectKind, Property};
+use crate::jsparser::program::{to_int32, JSType, Program};
+
+/// Math.random使用的xorshift128+, 与V8相同
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: [u64; 2],
+}
+
+impl Random {
+    /// 相同的种子产生相同的序列
+    pub fn new(seed: u64) -> Self {
+        //splitmix64展开种子, 避免全0状态
+        let mut seed = seed;
+        let mut next = || {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            z ^ (z >> 31)
+        };
+        Random {
+            state: [next(), next()],
+        }
+    }
+
+    /// 随机种子
+    pub fn from_entropy() -> Self {
+        Random::new(RandomState::new().build_hasher().finish())
+    }
+
+    /// [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        let [mut s1, s0] = self.state;
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+        self.state = [s0, s1];
+        //取高53位
+        (s0.wrapping_add(s1) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Program {
+    /// 设置Math.random的种子, 用于可重现的执行
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
+    pub(super) fn init_math(&mut self) {
+        let proto = self.intrinsics.object_prototype;
+        let math = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(proto)));
+        let constants = [
+            ("E", std::f64::consts::E),
+            ("LN10", std::f64::consts::LN_10),
+            ("LN2", std::f64::consts::LN_2),
+            ("LOG10E", std::f64::consts::LOG10_E),
+            ("LOG2E", std::f64::consts::LOG2_E),
+            ("PI", std::f64::consts::PI),
+            ("SQRT1_2", std::f64::consts::FRAC_1_SQRT_2),
+            ("SQRT2", std::f64::consts::SQRT_2),
+        ];
+        for (name, value) in constants {
+            let mut prop = Property::readonly(JSType::Float(value));
+            prop.configurable = false;
+            self.define_property(math, name, prop);
+        }
+        let methods: [(&'static str, usize, NativeFn); 35] = [
+            ("abs", 1, math_abs),
+            ("acos", 1, math_acos),
+            ("acosh", 1, math_acosh),
+            ("asin", 1, math_asin),
+            ("asinh", 1, math_asinh),
+            ("atan", 1, math_atan),
+            ("atanh", 1, math_atanh),
+            ("atan2", 2, math_atan2),
+            ("cbrt", 1, math_cbrt),
+            ("ceil", 1, math_ceil),
+            ("clz32", 1, math_clz32),
+            ("cos", 1, math_cos),
+            ("cosh", 1, math_cosh),
+            ("exp", 1, math_exp),
+            ("expm1", 1, math_expm1),
+            ("floor", 1, math_floor),
+            ("fround", 1, math_fround),
+            ("hypot", 2, math_hypot),
+            ("imul", 2, math_imul),
+            ("log", 1, math_log),
+            ("log1p", 1, math_log1p),
+            ("log2", 1, math_log2),
+            ("log10", 1, math_log10),
+            ("max", 2, math_max),
+            ("min", 2, math_min),
+            ("pow", 2, math_pow),
+            ("random", 0, math_random),
+            ("round", 1, math_round),
+            ("sign", 1, math_sign),
+            ("sin", 1, math_sin),
+            ("sinh", 1, math_sinh),
+            ("sqrt", 1, math_sqrt),
+            ("tan", 1, math_tan),
+            ("tanh", 1, math_tanh),
+            ("trunc", 1, math_trunc),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(math, name, length, func);
+        }
+        self.define_to_string_tag(math, "Math");
+        self.bind_value("Math".to_string(), JSType::Object(math));
+    }
+}
+
+/// 第index个参数转为数字
+fn number_arg(program: &mut Program, args: &[JSType], index: usize) -> Result<f64, String> {
+    program.number_value(&arg(args, index))
+}
+
+/// 单参数的数学函数
+fn unary(program: &mut Program, args: &[JSType], f: fn(f64) -> f64) -> Result<JSType, String> {
+    Ok(JSType::number(f(number_arg(program, args, 0)?)))
+}
+
+fn math_abs(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::abs)
+}
+
+fn math_acos(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::acos)
+}
+
+fn math_acosh(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::acosh)
+}
+
+fn math_asin(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::asin)
+}
+
+fn math_asinh(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    //f64::asinh对-0返回+0
+    unary(program, &args, |x| if x == 0.0 { x } else { x.asinh() })
+}
+
+fn math_atan(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::atan)
+}
+
+fn math_atanh(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::atanh)
+}
+
+fn math_atan2(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let y = number_arg(program, &args, 0)?;
+    let x = number_arg(program, &args, 1)?;
+    Ok(JSType::number(y.atan2(x)))
+}
+
+fn math_ceil(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::ceil)
+}
+
+fn math_cbrt(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::cbrt)
+}
+
+fn math_expm1(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::exp_m1)
+}
+
+/// 32位无符号整数的前导0个数
+fn math_clz32(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let n = to_int32(number_arg(program, &args, 0)?) as u32;
+    Ok(JSType::Int(n.leading_zeros() as i64))
+}
+
+fn math_cos(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::cos)
+}
+
+fn math_cosh(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::cosh)
+}
+
+fn math_exp(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::exp)
+}
+
+fn math_floor(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::floor)
+}
+
+fn math_fround(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, |x| x as f32 as f64)
+}
+
+/// 参数中有Infinity时结果为Infinity, 即使同时有NaN
+fn math_hypot(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let mut list = Vec::with_capacity(args.len());
+    for value in &args {
+        list.push(program.number_value(value)?.abs());
+    }
+    if list.iter().any(|x| x.is_infinite()) {
+        return Ok(JSType::Float(f64::INFINITY));
+    }
+    if list.iter().any(|x| x.is_nan()) {
+        return Ok(JSType::Float(f64::NAN));
+    }
+    let max = list.iter().cloned().fold(0.0, f64::max);
+    if max == 0.0 {
+        return Ok(JSType::Int(0));
+    }
+    //按最大值缩放, 避免平方溢出
+    let sum: f64 = list.iter().map(|x| (x / max) * (x / max)).sum();
+    Ok(JSType::number(sum.sqrt() * max))
+}
+
+/// 32位整数乘法, 溢出时截断
+fn math_imul(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let a = to_int32(number_arg(program, &args, 0)?);
+    let b = to_int32(number_arg(program, &args, 1)?);
+    Ok(JSType::Int(a.wrapping_mul(b) as i64))
+}
+
+fn math_log(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::ln)
+}
+
+fn math_log1p(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::ln_1p)
+}
+
+fn math_log2(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::log2)
+}
+
+fn math_log10(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::log10)
+}
+
+/// Math.max/min, 所有参数都会转为数字, 有NaN时为NaN, +0大于-0
+fn extremum(program: &mut Program, args: &[JSType], max: bool) -> Result<JSType, String> {
+    let mut list = Vec::with_capacity(args.len());
+    for value in args {
+        list.push(program.number_value(value)?);
+    }
+    let mut result = if max {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    };
+    for n in list {
+        if n.is_nan() {
+            return Ok(JSType::Float(f64::NAN));
+        }
+        let replace = if n == 0.0 && result == 0.0 {
+            n.is_sign_negative() != max
+        } else if max {
+            n > result
+        } else {
+            n < result
+        };
+        if replace {
+            result = n;
+        }
+    }
+    Ok(JSType::number(result))
+}
+
+fn math_max(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    extremum(program, &args, true)
+}
+
+fn math_min(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    extremum(program, &args, false)
+}
+
+fn math_pow(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let base = number_arg(program, &args, 0)?;
+    let exponent = number_arg(program, &args, 1)?;
+    Ok(JSType::number(pow(base, exponent)))
+}
+
+/// 与IEEE不同, 1 ** NaN 与 (±1) ** ±Infinity 为NaN
+fn pow(base: f64, exponent: f64) -> f64 {
+    if exponent.is_nan() || base.abs() == 1.0 && exponent.is_infinite() {
+        return f64::NAN;
+    }
+    base.powf(exponent)
+}
+
+fn math_random(program: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Float(program.random.next_f64()))
+}
+
+/// 0.5向正无穷方向取整, (-0.5, 0) 为-0
+fn math_round(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, |x| {
+        if !x.is_finite() || x == 0.0 {
+            return x;
+        }
+        if (-0.5..0.0).contains(&x) {
+            return -0.0;
+        }
+        let floor = x.floor();
+        if x - floor >= 0.5 {
+            floor + 1.0
+        } else {
+            floor
+        }
+    })
+}
+
+fn math_sign(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, |x| {
+        if x.is_nan() || x == 0.0 {
+            x
+        } else {
+            x.signum()
+        }
+    })
+}
+
+fn math_sin(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::sin)
+}
+
+fn math_sinh(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::sinh)
+}
+
+fn math_sqrt(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::sqrt)
+}
+
+fn math_tan(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::tan)
+}
+
+fn math_tanh(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::tanh)
+}
+
+fn math_trunc(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    unary(program, &args, f64::trunc)
+}
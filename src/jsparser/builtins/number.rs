@@ -0,0 +1,560 @@
+use super::{arg, to_integer};
+use crate::jsparser::object::{JSObject, NativeFn, ObjectId, ObjectKind, Property};
+use crate::jsparser::program::{digits_to_number, number_to_string, to_int32, JSType, Program};
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_number(&mut self) {
+        //Number.prototype本身是值为0的Number对象
+        let proto = self.intrinsics.number_prototype;
+        self.obj_mut(proto).kind = ObjectKind::Primitive(JSType::Int(0));
+        let number = self.define_constructor("Number", 1, proto, number_call, number_construct);
+        let constants = [
+            ("EPSILON", f64::EPSILON),
+            ("MAX_SAFE_INTEGER", 9007199254740991.0),
+            ("MIN_SAFE_INTEGER", -9007199254740991.0),
+            ("MAX_VALUE", f64::MAX),
+            ("MIN_VALUE", 5e-324),
+            ("NaN", f64::NAN),
+            ("POSITIVE_INFINITY", f64::INFINITY),
+            ("NEGATIVE_INFINITY", f64::NEG_INFINITY),
+        ];
+        for (name, value) in constants {
+            self.define_constant(number, name, JSType::number(value));
+        }
+        let statics: [(&'static str, usize, NativeFn); 4] = [
+            ("isFinite", 1, number_is_finite),
+            ("isInteger", 1, number_is_integer),
+            ("isNaN", 1, number_is_nan),
+            ("isSafeInteger", 1, number_is_safe_integer),
+        ];
+        for (name, length, func) in statics {
+            self.define_method(number, name, length, func);
+        }
+        let methods: [(&'static str, usize, NativeFn); 6] = [
+            ("toString", 1, number_to_string_method),
+            ("toLocaleString", 0, number_to_locale_string),
+            ("toFixed", 1, number_to_fixed),
+            ("toExponential", 1, number_to_exponential),
+            ("toPrecision", 1, number_to_precision),
+            ("valueOf", 0, number_value_of),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+
+        //Boolean
+        let proto = self.intrinsics.boolean_prototype;
+        self.obj_mut(proto).kind = ObjectKind::Primitive(JSType::Bool(false));
+        self.define_constructor("Boolean", 1, proto, boolean_call, boolean_construct);
+        self.define_method(proto, "toString", 0, boolean_to_string);
+        self.define_method(proto, "valueOf", 0, boolean_value_of);
+
+        //全局函数, Number.parseFloat/parseInt与全局的为同一个函数
+        let globals: [(&'static str, usize, NativeFn); 4] = [
+            ("parseFloat", 1, parse_float),
+            ("parseInt", 2, parse_int),
+            ("isNaN", 1, is_nan),
+            ("isFinite", 1, is_finite),
+        ];
+        for (name, length, func) in globals {
+            let id = self.native_function(name, length, func, None);
+            self.bind_value(name.to_string(), JSType::Object(id));
+            if name.starts_with("parse") {
+                self.define_property(number, name, Property::hidden(JSType::Object(id)));
+            }
+        }
+    }
+
+    /// 不可修改的常量属性
+    fn define_constant(&mut self, target: ObjectId, name: &'static str, value: JSType) {
+        let mut prop = Property::readonly(value);
+        prop.configurable = false;
+        self.define_property(target, name, prop);
+    }
+}
+
+//------------------------------ 格式化 ------------------------------
+
+/// 十进制数字串加1, 最高位进位时在前面补1并返回true
+pub(super) fn increment(digits: &mut Vec<u8>) -> bool {
+    for digit in digits.iter_mut().rev() {
+        if *digit == b'9' {
+            *digit = b'0';
+        } else {
+            *digit += 1;
+            return false;
+        }
+    }
+    digits.insert(0, b'1');
+    true
+}
+
+/// x的前count位有效数字及指数, 按精确值四舍五入(恰好一半时进位), x > 0
+fn significant_digits(x: f64, count: usize) -> (String, i32) {
+    //1100位足以表示任何f64的精确值
+    let formatted = format!("{:.1100e}", x);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let mut exponent = exponent.parse::<i32>().unwrap();
+    let all: Vec<u8> = mantissa.bytes().filter(|c| *c != b'.').collect();
+    let mut digits = all[..count].to_vec();
+    if all[count] >= b'5' && increment(&mut digits) {
+        digits.pop();
+        exponent += 1;
+    }
+    (String::from_utf8(digits).unwrap(), exponent)
+}
+
+/// 保留fraction位小数, 按精确值四舍五入(恰好一半时进位), x >= 0
+fn fixed_digits(x: f64, fraction: usize) -> String {
+    let formatted = format!("{:.1100}", x);
+    let (int, frac) = formatted.split_once('.').unwrap();
+    let mut digits: Vec<u8> = int.bytes().chain(frac.bytes().take(fraction)).collect();
+    if frac.as_bytes()[fraction] >= b'5' {
+        increment(&mut digits);
+    }
+    let point = digits.len() - fraction;
+    let digits = String::from_utf8(digits).unwrap();
+    if fraction == 0 {
+        digits
+    } else {
+        format!("{}.{}", &digits[..point], &digits[point..])
+    }
+}
+
+/// d.ddde+x
+fn exponential_string(digits: &str, exponent: i32) -> String {
+    let (first, rest) = digits.split_at(1);
+    let sign = if exponent < 0 { '-' } else { '+' };
+    if rest.is_empty() {
+        format!("{}e{}{}", first, sign, exponent.abs())
+    } else {
+        format!("{}.{}e{}{}", first, rest, sign, exponent.abs())
+    }
+}
+
+/// 非10进制的toString, 小数部分只保留到能区分相邻浮点数的精度, 与V8相同
+fn radix_string(value: f64, radix: u32) -> String {
+    if !value.is_finite() || value == 0.0 {
+        return number_to_string(value);
+    }
+    let digit_char = |d: u32| std::char::from_digit(d, radix).unwrap();
+    let negative = value < 0.0;
+    let value = value.abs();
+    let mut integer = value.floor();
+    let mut fraction = value - integer;
+    //到下一个浮点数距离的一半
+    let mut delta = (0.5 * (f64::from_bits(value.to_bits() + 1) - value)).max(f64::from_bits(1));
+    let mut fraction_digits = Vec::new();
+    if fraction >= delta {
+        loop {
+            fraction *= radix as f64;
+            delta *= radix as f64;
+            let digit = fraction as u32;
+            fraction_digits.push(digit);
+            fraction -= digit as f64;
+            //舍入到偶数, 需要时向前进位
+            if (fraction > 0.5 || (fraction == 0.5 && digit & 1 == 1)) && fraction + delta > 1.0 {
+                loop {
+                    match fraction_digits.pop() {
+                        Some(d) if d + 1 < radix => {
+                            fraction_digits.push(d + 1);
+                            break;
+                        }
+                        Some(_) => {}
+                        None => {
+                            integer += 1.0;
+                            break;
+                        }
+                    }
+                }
+                break;
+            }
+            if fraction < delta {
+                break;
+            }
+        }
+    }
+    //超出精度的整数位为0
+    let mut integer_digits = Vec::new();
+    while integer / radix as f64 >= 9007199254740992.0 {
+        integer /= radix as f64;
+        integer_digits.push('0');
+    }
+    loop {
+        let remainder = integer % radix as f64;
+        integer_digits.push(digit_char(remainder as u32));
+        integer = (integer - remainder) / radix as f64;
+        if integer <= 0.0 {
+            break;
+        }
+    }
+    let mut result: String = if negative {
+        "-".to_string()
+    } else {
+        String::new()
+    };
+    result.extend(integer_digits.iter().rev());
+    if !fraction_digits.is_empty() {
+        result.push('.');
+        result.extend(fraction_digits.into_iter().map(digit_char));
+    }
+    result
+}
+
+//------------------------------ Number ------------------------------
+
+/// thisNumberValue
+fn this_number_value(program: &Program, this: &JSType, method: &str) -> Result<f64, String> {
+    match this {
+        JSType::Int(_) | JSType::Float(_) => return Ok(this.to_number()),
+        JSType::Object(id) => {
+            if let ObjectKind::Primitive(value @ (JSType::Int(_) | JSType::Float(_))) =
+                &program.obj(*id).kind
+            {
+                return Ok(value.to_number());
+            }
+        }
+        _ => {}
+    }
+    Err(err(&format!(
+        "Uncaught TypeError: Number.prototype.{} requires that 'this' be a Number",
+        method
+    )))
+}
+
+/// Number(value), 没有参数时为0, BigInt可以显式转换
+fn number_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    if args.is_empty() {
+        return Ok(JSType::Int(0));
+    }
+    let value = program.numeric_value(&args[0])?;
+    Ok(JSType::number(value.to_number()))
+}
+
+fn number_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let value = number_call(program, JSType::Undefined, args)?;
+    let proto = program.get_prototype_from_constructor(&new_target, "Number")?;
+    Ok(JSType::Object(program.alloc(JSObject::new(
+        ObjectKind::Primitive(value),
+        Some(proto),
+    ))))
+}
+
+/// Number.isFinite, 不转换类型
+fn number_is_finite(_: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let value = arg(&args, 0);
+    let is_number = matches!(value, JSType::Int(_) | JSType::Float(_));
+    Ok(JSType::Bool(is_number && value.to_number().is_finite()))
+}
+
+fn number_is_integer(_: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let value = arg(&args, 0);
+    let is_number = matches!(value, JSType::Int(_) | JSType::Float(_));
+    let n = value.to_number();
+    Ok(JSType::Bool(is_number && n.is_finite() && n.trunc() == n))
+}
+
+fn number_is_nan(_: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(
+        matches!(arg(&args, 0), JSType::Float(n) if n.is_nan()),
+    ))
+}
+
+fn number_is_safe_integer(_: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let value = arg(&args, 0);
+    let is_number = matches!(value, JSType::Int(_) | JSType::Float(_));
+    let n = value.to_number();
+    Ok(JSType::Bool(
+        is_number && n.trunc() == n && n.abs() <= 9007199254740991.0,
+    ))
+}
+
+/// toString(radix)
+fn number_to_string_method(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let x = this_number_value(program, &this, "toString")?;
+    let radix = match arg(&args, 0) {
+        JSType::Undefined => 10.0,
+        radix => to_integer(program, &radix)?,
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(err(
+            "Uncaught RangeError: toString() radix must be between 2 and 36",
+        ));
+    }
+    let s = if radix == 10.0 {
+        number_to_string(x)
+    } else {
+        radix_string(x, radix as u32)
+    };
+    Ok(JSType::String(s.into()))
+}
+
+/// toLocaleString(locales, options), 格式见Intl.NumberFormat
+fn number_to_locale_string(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let x = this_number_value(program, &this, "toLocaleString")?;
+    let s = program.number_to_locale_string(&JSType::number(x), &arg(&args, 0), &arg(&args, 1))?;
+    Ok(JSType::String(s.into()))
+}
+
+/// toFixed(digits), 大于等于1e21时与toString相同
+fn number_to_fixed(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let x = this_number_value(program, &this, "toFixed")?;
+    let f = to_integer(program, &arg(&args, 0))?;
+    if !(0.0..=100.0).contains(&f) {
+        return Err(err(
+            "Uncaught RangeError: toFixed() digits argument must be between 0 and 100",
+        ));
+    }
+    if !x.is_finite() || x.abs() >= 1e21 {
+        return Ok(JSType::String(number_to_string(x).into()));
+    }
+    let s = fixed_digits(x.abs(), f as usize);
+    let s = if x < 0.0 { format!("-{}", s) } else { s };
+    Ok(JSType::String(s.into()))
+}
+
+/// toExponential(digits), digits为undefined时使用最短的数字
+fn number_to_exponential(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let x = this_number_value(program, &this, "toExponential")?;
+    let fraction = arg(&args, 0);
+    let f = to_integer(program, &fraction)?;
+    if !x.is_finite() {
+        return Ok(JSType::String(number_to_string(x).into()));
+    }
+    if !(0.0..=100.0).contains(&f) {
+        return Err(err(
+            "Uncaught RangeError: toExponential() argument must be between 0 and 100",
+        ));
+    }
+    let (digits, exponent) = if x == 0.0 {
+        ("0".repeat(f as usize + 1), 0)
+    } else if let JSType::Undefined = fraction {
+        let formatted = format!("{:e}", x.abs());
+        let (mantissa, exponent) = formatted.split_once('e').unwrap();
+        (mantissa.replace('.', ""), exponent.parse().unwrap())
+    } else {
+        significant_digits(x.abs(), f as usize + 1)
+    };
+    let s = exponential_string(&digits, exponent);
+    let s = if x < 0.0 { format!("-{}", s) } else { s };
+    Ok(JSType::String(s.into()))
+}
+
+/// toPrecision(precision), 指数小于-6或不小于精度时使用指数形式
+fn number_to_precision(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let x = this_number_value(program, &this, "toPrecision")?;
+    let precision = arg(&args, 0);
+    if let JSType::Undefined = precision {
+        return Ok(JSType::String(number_to_string(x).into()));
+    }
+    let p = to_integer(program, &precision)?;
+    if !x.is_finite() {
+        return Ok(JSType::String(number_to_string(x).into()));
+    }
+    if !(1.0..=100.0).contains(&p) {
+        return Err(err(
+            "Uncaught RangeError: toPrecision() argument must be between 1 and 100",
+        ));
+    }
+    let p = p as usize;
+    let (digits, e) = if x == 0.0 {
+        ("0".repeat(p), 0)
+    } else {
+        significant_digits(x.abs(), p)
+    };
+    let s = if e < -6 || e >= p as i32 {
+        exponential_string(&digits, e)
+    } else if e >= 0 {
+        let point = e as usize + 1;
+        if point == p {
+            digits
+        } else {
+            format!("{}.{}", &digits[..point], &digits[point..])
+        }
+    } else {
+        format!("0.{}{}", "0".repeat((-e - 1) as usize), digits)
+    };
+    let s = if x < 0.0 { format!("-{}", s) } else { s };
+    Ok(JSType::String(s.into()))
+}
+
+fn number_value_of(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::number(this_number_value(
+        program, &this, "valueOf",
+    )?))
+}
+
+//------------------------------ Boolean ------------------------------
+
+/// thisBooleanValue
+fn this_boolean_value(program: &Program, this: &JSType, method: &str) -> Result<bool, String> {
+    match this {
+        JSType::Bool(b) => return Ok(*b),
+        JSType::Object(id) => {
+            if let ObjectKind::Primitive(JSType::Bool(b)) = &program.obj(*id).kind {
+                return Ok(*b);
+            }
+        }
+        _ => {}
+    }
+    Err(err(&format!(
+        "Uncaught TypeError: Boolean.prototype.{} requires that 'this' be a Boolean",
+        method
+    )))
+}
+
+fn boolean_call(_: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(arg(&args, 0).is_truthy()))
+}
+
+fn boolean_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let value = JSType::Bool(arg(&args, 0).is_truthy());
+    let proto = program.get_prototype_from_constructor(&new_target, "Boolean")?;
+    Ok(JSType::Object(program.alloc(JSObject::new(
+        ObjectKind::Primitive(value),
+        Some(proto),
+    ))))
+}
+
+fn boolean_to_string(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let b = this_boolean_value(program, &this, "toString")?;
+    Ok(JSType::String(b.to_string().into()))
+}
+
+fn boolean_value_of(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(this_boolean_value(program, &this, "valueOf")?))
+}
+
+//------------------------------ 全局函数 ------------------------------
+
+/// parseInt(string, radix), radix为0或undefined时按前缀判断16进制, 否则为10进制
+fn parse_int(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = program.js_string_value(&arg(&args, 0))?;
+    let s = s.trim(true, false).to_string();
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(&s)),
+    };
+    let radix = program.number_value(&arg(&args, 1))?;
+    let mut radix = to_int32(radix);
+    let mut strip_prefix = true;
+    if radix != 0 {
+        if !(2..=36).contains(&radix) {
+            return Ok(JSType::Float(f64::NAN));
+        }
+        strip_prefix = radix == 16;
+    } else {
+        radix = 10;
+    }
+    let s = match s.get(..2) {
+        Some("0x" | "0X") if strip_prefix => {
+            radix = 16;
+            &s[2..]
+        }
+        _ => s,
+    };
+    let radix = radix as u32;
+    let end = s.find(|c: char| !c.is_digit(radix)).unwrap_or(s.len());
+    let digits = &s[..end];
+    let n = if radix == 10 && !digits.is_empty() {
+        digits.parse().ok()
+    } else {
+        digits_to_number(digits, radix)
+    };
+    Ok(match n {
+        Some(n) => JSType::number(sign * n),
+        None => JSType::Float(f64::NAN),
+    })
+}
+
+/// parseFloat(string), 取开头最长的十进制数字
+fn parse_float(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = program.js_string_value(&arg(&args, 0))?;
+    let s = s.trim(true, false).to_string();
+    let bytes = s.as_bytes();
+    let mut end = 0;
+    if matches!(bytes.first(), Some(b'+' | b'-')) {
+        end += 1;
+    }
+    if s[end..].starts_with("Infinity") {
+        let n = if bytes[0] == b'-' {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        };
+        return Ok(JSType::Float(n));
+    }
+    let digits = |end: &mut usize| {
+        let start = *end;
+        while bytes.get(*end).is_some_and(|c| c.is_ascii_digit()) {
+            *end += 1;
+        }
+        *end - start
+    };
+    let mut count = digits(&mut end);
+    if bytes.get(end) == Some(&b'.') {
+        let mut after = end + 1;
+        let fraction = digits(&mut after);
+        if count + fraction > 0 {
+            end = after;
+            count += fraction;
+        }
+    }
+    if count == 0 {
+        return Ok(JSType::Float(f64::NAN));
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut after = end + 1;
+        if matches!(bytes.get(after), Some(b'+' | b'-')) {
+            after += 1;
+        }
+        if digits(&mut after) > 0 {
+            end = after;
+        }
+    }
+    Ok(JSType::number(s[..end].parse().unwrap_or(f64::NAN)))
+}
+
+/// isNaN(value), 先转为数字
+fn is_nan(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(program.number_value(&arg(&args, 0))?.is_nan()))
+}
+
+fn is_finite(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(
+        program.number_value(&arg(&args, 0))?.is_finite(),
+    ))
+}
@@ -0,0 +1,325 @@
+use super::arg;
+use crate::jsparser::object::{NativeFn, ObjectId, ObjectKind, Property, PropertyKey};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_object(&mut self, object: ObjectId) {
+        let methods: [(&'static str, usize, NativeFn); 19] = [
+            ("keys", 1, object_keys),
+            ("values", 1, object_values),
+            ("entries", 1, object_entries),
+            ("fromEntries", 1, object_from_entries),
+            ("assign", 2, object_assign),
+            ("freeze", 1, object_freeze),
+            ("seal", 1, object_seal),
+            ("preventExtensions", 1, object_prevent_extensions),
+            ("isFrozen", 1, object_is_frozen),
+            ("isSealed", 1, object_is_sealed),
+            ("isExtensible", 1, object_is_extensible),
+            ("defineProperty", 3, object_define_property),
+            ("defineProperties", 2, object_define_properties),
+            (
+                "getOwnPropertyDescriptor",
+                2,
+                object_get_own_property_descriptor,
+            ),
+            (
+                "getOwnPropertyDescriptors",
+                1,
+                object_get_own_property_descriptors,
+            ),
+            ("getOwnPropertyNames", 1, object_get_own_property_names),
+            ("getOwnPropertySymbols", 1, object_get_own_property_symbols),
+            ("hasOwn", 2, object_has_own),
+            ("is", 2, object_is),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(object, name, length, func);
+        }
+        let proto = self.intrinsics.object_prototype;
+        self.define_method(
+            proto,
+            "propertyIsEnumerable",
+            1,
+            object_property_is_enumerable,
+        );
+    }
+
+    /// 自身可枚举属性的 [key, value], 读取时属性可能已被删除
+    fn enumerable_own_entries(&mut self, value: &JSType) -> Result<Vec<(String, JSType)>, String> {
+        let id = self.object_value(value)?;
+        let target = JSType::Object(id);
+        let mut list = Vec::new();
+        for key in self.enumerable_own_keys(id)? {
+            let key = PropertyKey::from(key);
+            if self.own_property(id, &key)?.is_some_and(|p| p.enumerable) {
+                let value = self.get_property(&target, &key)?;
+                list.push((key.to_string(), value));
+            }
+        }
+        Ok(list)
+    }
+}
+
+fn object_keys(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = program.object_value(&arg(&args, 0))?;
+    let keys = program.enumerable_own_keys(id)?;
+    let list = keys.into_iter().map(|k| JSType::String(k.into())).collect();
+    Ok(program.new_array(list))
+}
+
+fn object_values(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let entries = program.enumerable_own_entries(&arg(&args, 0))?;
+    let list = entries.into_iter().map(|(_, v)| v).collect();
+    Ok(program.new_array(list))
+}
+
+fn object_entries(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let entries = program.enumerable_own_entries(&arg(&args, 0))?;
+    let list = entries
+        .into_iter()
+        .map(|(k, v)| program.new_array(vec![JSType::String(k.into()), v]))
+        .collect();
+    Ok(program.new_array(list))
+}
+
+fn object_from_entries(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let iterable = arg(&args, 0);
+    let result = program.new_object();
+    let JSType::Object(id) = result else {
+        unreachable!()
+    };
+    let record = program.get_iterator(&iterable)?;
+    while let Some(entry) = program.iterator_step(&record)? {
+        if !matches!(entry, JSType::Object(_)) {
+            program.iterator_close(&record)?;
+            return Err(err(&format!(
+                "Uncaught TypeError: Iterator value {} is not an entry object",
+                program.display(&entry)
+            )));
+        }
+        let key = program.get_property(&entry, "0")?;
+        let value = program.get_property(&entry, "1")?;
+        let key = program.property_key(&key)?;
+        program.define_property(id, key, Property::data(value));
+    }
+    Ok(result)
+}
+
+/// Object.assign(target, ...sources), 复制可枚举的自身属性(包括Symbol)
+fn object_assign(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let target = JSType::Object(program.object_value(&arg(&args, 0))?);
+    for source in args.iter().skip(1) {
+        if let JSType::Undefined | JSType::NULL = source {
+            continue;
+        }
+        let id = program.object_value(source)?;
+        let from = JSType::Object(id);
+        for key in program.own_property_keys(id)? {
+            if program
+                .own_property(id, &key)?
+                .is_some_and(|p| p.enumerable)
+            {
+                let value = program.get_property(&from, &key)?;
+                program.set_property(&target, key, value)?;
+            }
+        }
+    }
+    Ok(target)
+}
+
+/// 有元素的类型化数组不能冻结或密封
+fn check_buffer_view(program: &Program, id: ObjectId, action: &str) -> Result<(), String> {
+    match &program.obj(id).kind {
+        ObjectKind::TypedArray(_, view) if view.length > 0 => Err(err(&format!(
+            "Uncaught TypeError: Cannot {} array buffer views with elements",
+            action
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn object_freeze(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    if let JSType::Object(id) = target {
+        check_buffer_view(program, id, "freeze")?;
+        if !program.set_integrity_level(id, true)? {
+            return Err(err("Uncaught TypeError: Cannot freeze"));
+        }
+    }
+    Ok(target)
+}
+
+fn object_seal(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    if let JSType::Object(id) = target {
+        check_buffer_view(program, id, "seal")?;
+        if !program.set_integrity_level(id, false)? {
+            return Err(err("Uncaught TypeError: Cannot seal"));
+        }
+    }
+    Ok(target)
+}
+
+fn object_prevent_extensions(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    if let JSType::Object(id) = target {
+        if !program.prevent_extensions(id)? {
+            return Err(err(
+                "Uncaught TypeError: 'preventExtensions' on proxy: trap returned falsish",
+            ));
+        }
+    }
+    Ok(target)
+}
+
+fn object_is_frozen(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(match arg(&args, 0) {
+        JSType::Object(id) => program.test_integrity_level(id, true)?,
+        _ => true,
+    }))
+}
+
+fn object_is_sealed(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(match arg(&args, 0) {
+        JSType::Object(id) => program.test_integrity_level(id, false)?,
+        _ => true,
+    }))
+}
+
+fn object_is_extensible(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    Ok(JSType::Bool(match arg(&args, 0) {
+        JSType::Object(id) => program.is_extensible(id)?,
+        _ => false,
+    }))
+}
+
+fn object_define_property(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    let JSType::Object(id) = target else {
+        return Err(err(
+            "Uncaught TypeError: Object.defineProperty called on non-object",
+        ));
+    };
+    let key = program.property_key(&arg(&args, 1))?;
+    let desc = program.property_descriptor(&arg(&args, 2))?;
+    program.define_property_or_throw(id, &key, &desc)?;
+    Ok(target)
+}
+
+fn object_define_properties(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    let JSType::Object(id) = target else {
+        return Err(err(
+            "Uncaught TypeError: Object.defineProperties called on non-object",
+        ));
+    };
+    program.define_properties(id, &arg(&args, 1))?;
+    Ok(target)
+}
+
+fn object_get_own_property_descriptor(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = program.object_value(&arg(&args, 0))?;
+    let key = program.property_key(&arg(&args, 1))?;
+    match program.own_property(id, &key)? {
+        Some(prop) => Ok(program.descriptor_object(&prop)),
+        None => Ok(JSType::Undefined),
+    }
+}
+
+fn object_get_own_property_descriptors(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = program.object_value(&arg(&args, 0))?;
+    let result = program.new_object();
+    for key in program.own_property_keys(id)? {
+        if let Some(prop) = program.own_property(id, &key)? {
+            let desc = program.descriptor_object(&prop);
+            program.set_property(&result, key, desc)?;
+        }
+    }
+    Ok(result)
+}
+
+fn object_get_own_property_names(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = program.object_value(&arg(&args, 0))?;
+    let list = program
+        .own_property_keys(id)?
+        .into_iter()
+        .filter(|key| key.as_str().is_some())
+        .map(|key| JSType::String(key.to_string().into()))
+        .collect();
+    Ok(program.new_array(list))
+}
+
+fn object_get_own_property_symbols(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = program.object_value(&arg(&args, 0))?;
+    let list = program
+        .own_property_keys(id)?
+        .into_iter()
+        .filter_map(|key| match key {
+            PropertyKey::Symbol(symbol) => Some(JSType::Symbol(symbol)),
+            PropertyKey::String(_) => None,
+        })
+        .collect();
+    Ok(program.new_array(list))
+}
+
+fn object_has_own(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = program.object_value(&arg(&args, 0))?;
+    let key = program.property_key(&arg(&args, 1))?;
+    Ok(JSType::Bool(program.own_property(id, &key)?.is_some()))
+}
+
+fn object_is(_: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Bool(arg(&args, 0).same_value(&arg(&args, 1))))
+}
+
+fn object_property_is_enumerable(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let key = program.property_key(&arg(&args, 0))?;
+    let id = program.object_value(&this)?;
+    Ok(JSType::Bool(
+        program
+            .own_property(id, &key)?
+            .is_some_and(|p| p.enumerable),
+    ))
+}
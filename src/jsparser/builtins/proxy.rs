@@ -0,0 +1,61 @@
+use super::arg;
+use crate::jsparser::object::{BoundFunction, JSObject, ObjectKind, Property};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_proxy(&mut self) {
+        //Proxy没有prototype属性
+        let proxy = self.native_function("Proxy", 2, proxy_call, Some(proxy_construct));
+        self.define_method(proxy, "revocable", 2, proxy_revocable);
+        self.bind_value("Proxy".to_string(), JSType::Object(proxy));
+    }
+}
+
+fn proxy_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Err(err("Uncaught TypeError: Constructor Proxy requires 'new'"))
+}
+
+fn proxy_construct(program: &mut Program, args: Vec<JSType>, _: JSType) -> Result<JSType, String> {
+    let id = program.proxy_create(&arg(&args, 0), &arg(&args, 1))?;
+    Ok(JSType::Object(id))
+}
+
+/// Proxy.revocable(target, handler), 返回 { proxy, revoke }
+fn proxy_revocable(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let proxy = JSType::Object(program.proxy_create(&arg(&args, 0), &arg(&args, 1))?);
+    //revoke是绑定了proxy作为this的内置函数
+    let target = program.native_function("", 0, proxy_revoke, None);
+    let bound = BoundFunction {
+        target,
+        this: proxy.clone(),
+        args: Vec::new(),
+        constructor: false,
+    };
+    let proto = program.intrinsics.function_prototype;
+    let revoke = program.alloc(JSObject::new(
+        ObjectKind::Bound(Box::new(bound)),
+        Some(proto),
+    ));
+    program.define_property(revoke, "length", Property::readonly(JSType::Int(0)));
+    program.define_property(
+        revoke,
+        "name",
+        Property::readonly(JSType::String("".into())),
+    );
+    let result = program.new_object();
+    let JSType::Object(id) = result else {
+        unreachable!()
+    };
+    program.define_property(id, "proxy", Property::data(proxy));
+    program.define_property(id, "revoke", Property::data(JSType::Object(revoke)));
+    Ok(result)
+}
+
+/// 撤销后对Proxy的所有操作都抛出TypeError, 重复调用无效果
+fn proxy_revoke(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    if let JSType::Object(id) = this {
+        program.revoke_proxy(id);
+    }
+    Ok(JSType::Undefined)
+}
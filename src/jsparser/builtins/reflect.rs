@@ -0,0 +1,190 @@
+use super::{arg, check_prototype, list_from_array_like};
+use crate::jsparser::object::{JSObject, NativeFn, ObjectId, ObjectKind, PropertyKey};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_reflect(&mut self) {
+        let proto = self.intrinsics.object_prototype;
+        let reflect = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(proto)));
+        let methods: [(&'static str, usize, NativeFn); 13] = [
+            ("apply", 3, reflect_apply),
+            ("construct", 2, reflect_construct),
+            ("defineProperty", 3, reflect_define_property),
+            ("deleteProperty", 2, reflect_delete_property),
+            ("get", 2, reflect_get),
+            (
+                "getOwnPropertyDescriptor",
+                2,
+                reflect_get_own_property_descriptor,
+            ),
+            ("getPrototypeOf", 1, reflect_get_prototype_of),
+            ("has", 2, reflect_has),
+            ("isExtensible", 1, reflect_is_extensible),
+            ("ownKeys", 1, reflect_own_keys),
+            ("preventExtensions", 1, reflect_prevent_extensions),
+            ("set", 3, reflect_set),
+            ("setPrototypeOf", 2, reflect_set_prototype_of),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(reflect, name, length, func);
+        }
+        self.define_to_string_tag(reflect, "Reflect");
+        self.bind_value("Reflect".to_string(), JSType::Object(reflect));
+    }
+}
+
+/// 第一个参数需为对象
+fn target_object(args: &[JSType], method: &str) -> Result<ObjectId, String> {
+    match args.first() {
+        Some(JSType::Object(id)) => Ok(*id),
+        _ => Err(err(&format!(
+            "Uncaught TypeError: Reflect.{} called on non-object",
+            method
+        ))),
+    }
+}
+
+/// Reflect.apply(target, thisArgument, argumentsList)
+fn reflect_apply(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    if !program.is_callable(&target) {
+        return Err(err(&format!(
+            "Uncaught TypeError: Function.prototype.apply was called on {}, which is {} and not a function",
+            program.display(&target),
+            program.type_of(&target)
+        )));
+    }
+    let list = list_from_array_like(program, &arg(&args, 2))?;
+    program.call(&target, arg(&args, 1), list)
+}
+
+/// Reflect.construct(target, argumentsList, newTarget)
+fn reflect_construct(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let target = arg(&args, 0);
+    let new_target = args.get(2).cloned().unwrap_or_else(|| target.clone());
+    for func in [&target, &new_target] {
+        if !program.is_constructor(func) {
+            return Err(err(&format!(
+                "Uncaught TypeError: {} is not a constructor",
+                program.display(func)
+            )));
+        }
+    }
+    let list = list_from_array_like(program, &arg(&args, 1))?;
+    program.construct(&target, list, Some(new_target))
+}
+
+fn reflect_define_property(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "defineProperty")?;
+    let key = program.property_key(&arg(&args, 1))?;
+    let desc = program.property_descriptor(&arg(&args, 2))?;
+    Ok(JSType::Bool(program.define_own_property(id, &key, &desc)?))
+}
+
+fn reflect_delete_property(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "deleteProperty")?;
+    let key = program.property_key(&arg(&args, 1))?;
+    Ok(JSType::Bool(program.delete_property(id, &key)?))
+}
+
+/// Reflect.get(target, propertyKey, receiver)
+fn reflect_get(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = target_object(&args, "get")?;
+    let key = program.property_key(&arg(&args, 1))?;
+    let receiver = args.get(2).cloned().unwrap_or(JSType::Object(id));
+    program.get_with_receiver(id, &key, receiver)
+}
+
+fn reflect_get_own_property_descriptor(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "getOwnPropertyDescriptor")?;
+    let key = program.property_key(&arg(&args, 1))?;
+    match program.own_property(id, &key)? {
+        Some(prop) => Ok(program.descriptor_object(&prop)),
+        None => Ok(JSType::Undefined),
+    }
+}
+
+fn reflect_get_prototype_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "getPrototypeOf")?;
+    let proto = program.get_prototype_of(&JSType::Object(id))?;
+    Ok(proto.map(JSType::Object).unwrap_or(JSType::NULL))
+}
+
+fn reflect_has(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = target_object(&args, "has")?;
+    let key = program.property_key(&arg(&args, 1))?;
+    Ok(JSType::Bool(program.has_property(id, &key)?))
+}
+
+fn reflect_is_extensible(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "isExtensible")?;
+    Ok(JSType::Bool(program.is_extensible(id)?))
+}
+
+/// 自身所有属性名, 包括不可枚举的及Symbol
+fn reflect_own_keys(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = target_object(&args, "ownKeys")?;
+    let list = program
+        .own_property_keys(id)?
+        .iter()
+        .map(PropertyKey::to_value)
+        .collect();
+    Ok(program.new_array(list))
+}
+
+fn reflect_prevent_extensions(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "preventExtensions")?;
+    Ok(JSType::Bool(program.prevent_extensions(id)?))
+}
+
+/// Reflect.set(target, propertyKey, value, receiver), 不能赋值时返回false
+fn reflect_set(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let id = target_object(&args, "set")?;
+    let key = program.property_key(&arg(&args, 1))?;
+    let receiver = args.get(3).cloned().unwrap_or(JSType::Object(id));
+    Ok(JSType::Bool(program.set_with_receiver(
+        id,
+        &key,
+        arg(&args, 2),
+        receiver,
+    )?))
+}
+
+fn reflect_set_prototype_of(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = target_object(&args, "setPrototypeOf")?;
+    let proto = check_prototype(program, &arg(&args, 1))?;
+    Ok(JSType::Bool(program.set_prototype(id, proto)?))
+}
@@ -0,0 +1,661 @@
+use std::cmp::Ordering;
+
+use icu_normalizer::{ComposingNormalizerBorrowed, DecomposingNormalizerBorrowed};
+
+use super::{arg, relative_index, to_integer};
+use crate::jsparser::object::{NativeFn, ObjectKind};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::string::JSString;
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_string(&mut self) {
+        let proto = self.intrinsics.string_prototype;
+        let methods: [(&'static str, usize, NativeFn); 34] = [
+            ("toString", 0, string_to_string),
+            ("valueOf", 0, string_value_of),
+            ("charAt", 1, string_char_at),
+            ("charCodeAt", 1, string_char_code_at),
+            ("codePointAt", 1, string_code_point_at),
+            ("at", 1, string_at),
+            ("indexOf", 1, string_index_of),
+            ("lastIndexOf", 1, string_last_index_of),
+            ("includes", 1, string_includes),
+            ("startsWith", 1, string_starts_with),
+            ("endsWith", 1, string_ends_with),
+            ("slice", 2, string_slice),
+            ("substring", 2, string_substring),
+            ("substr", 2, string_substr),
+            ("split", 2, string_split),
+            ("replace", 2, string_replace),
+            ("replaceAll", 2, string_replace_all),
+            ("concat", 1, string_concat),
+            ("trim", 0, string_trim),
+            ("trimStart", 0, string_trim_start),
+            ("trimEnd", 0, string_trim_end),
+            ("padStart", 2, string_pad_start),
+            ("padEnd", 2, string_pad_end),
+            ("repeat", 1, string_repeat),
+            ("toUpperCase", 0, string_to_upper_case),
+            ("toLowerCase", 0, string_to_lower_case),
+            ("toLocaleUpperCase", 0, string_to_upper_case),
+            ("toLocaleLowerCase", 0, string_to_lower_case),
+            ("normalize", 0, string_normalize),
+            ("localeCompare", 1, string_locale_compare),
+            ("isWellFormed", 0, string_is_well_formed),
+            ("toWellFormed", 0, string_to_well_formed),
+            ("trimLeft", 0, string_trim_start),
+            ("trimRight", 0, string_trim_end),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        let string = self.define_constructor("String", 1, proto, string_call, string_construct);
+        self.define_method(string, "fromCharCode", 1, string_from_char_code);
+        self.define_method(string, "fromCodePoint", 1, string_from_code_point);
+        self.define_method(string, "raw", 1, string_raw);
+    }
+}
+
+/// String(value), Symbol转为描述字符串
+fn string_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    match args.first() {
+        None => Ok(JSType::String(JSString::new())),
+        Some(JSType::Symbol(symbol)) => Ok(JSType::String(symbol.to_string().into())),
+        Some(value) => Ok(JSType::String(program.js_string_value(value)?)),
+    }
+}
+
+/// new String(value) 创建包装对象
+fn string_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let s = match args.first() {
+        None => JSString::new(),
+        Some(value) => program.js_string_value(value)?,
+    };
+    let proto = program.get_prototype_from_constructor(&new_target, "String")?;
+    let id = program.object_value(&JSType::String(s))?;
+    program.obj_mut(id).proto = Some(proto);
+    Ok(JSType::Object(id))
+}
+
+/// String.prototype上方法的this, 转为字符串
+fn this_string(program: &mut Program, this: &JSType, method: &str) -> Result<JSString, String> {
+    match this {
+        JSType::Undefined | JSType::NULL => Err(err(&format!(
+            "Uncaught TypeError: String.prototype.{} called on null or undefined",
+            method
+        ))),
+        _ => program.js_string_value(this),
+    }
+}
+
+/// toString/valueOf 要求this为字符串或包装对象
+fn this_string_value(program: &Program, this: &JSType, method: &str) -> Result<JSType, String> {
+    match this {
+        JSType::String(_) => Ok(this.clone()),
+        JSType::Object(id) => match &program.obj(*id).kind {
+            ObjectKind::Primitive(value @ JSType::String(_)) => Ok(value.clone()),
+            _ => Err(err(&format!(
+                "Uncaught TypeError: String.prototype.{} requires that 'this' be a String",
+                method
+            ))),
+        },
+        _ => Err(err(&format!(
+            "Uncaught TypeError: String.prototype.{} requires that 'this' be a String",
+            method
+        ))),
+    }
+}
+
+/// 位置限制在 [0, len]
+fn clamp(n: f64, len: usize) -> usize {
+    n.max(0.0).min(len as f64) as usize
+}
+
+fn search_string(program: &mut Program, value: &JSType) -> Result<JSString, String> {
+    program.js_string_value(value)
+}
+
+fn string_to_string(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    this_string_value(program, &this, "toString")
+}
+
+fn string_value_of(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    this_string_value(program, &this, "valueOf")
+}
+
+fn string_char_at(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "charAt")?;
+    let pos = to_integer(program, &arg(&args, 0))?;
+    if pos < 0.0 || pos >= s.len() as f64 {
+        return Ok(JSType::String(JSString::new()));
+    }
+    let pos = pos as usize;
+    Ok(JSType::String(s.slice(pos, pos + 1)))
+}
+
+fn string_char_code_at(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "charCodeAt")?;
+    let pos = to_integer(program, &arg(&args, 0))?;
+    if pos < 0.0 || pos >= s.len() as f64 {
+        return Ok(JSType::Float(f64::NAN));
+    }
+    Ok(JSType::Int(s.units()[pos as usize] as i64))
+}
+
+fn string_code_point_at(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "codePointAt")?;
+    let pos = to_integer(program, &arg(&args, 0))?;
+    if pos < 0.0 || pos >= s.len() as f64 {
+        return Ok(JSType::Undefined);
+    }
+    Ok(s.code_point_at(pos as usize)
+        .map_or(JSType::Undefined, |c| JSType::Int(c as i64)))
+}
+
+/// at(-1) 为最后一个码元
+fn string_at(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "at")?;
+    let pos = to_integer(program, &arg(&args, 0))?;
+    let pos = if pos < 0.0 { s.len() as f64 + pos } else { pos };
+    if pos < 0.0 || pos >= s.len() as f64 {
+        return Ok(JSType::Undefined);
+    }
+    let pos = pos as usize;
+    Ok(JSType::String(s.slice(pos, pos + 1)))
+}
+
+fn string_index_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "indexOf")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    let pos = clamp(to_integer(program, &arg(&args, 1))?, s.len());
+    Ok(JSType::Int(s.find(&search, pos).map_or(-1, |i| i as i64)))
+}
+
+fn string_last_index_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "lastIndexOf")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    //NaN时从末尾查找
+    let pos = program.number_value(&arg(&args, 1))?;
+    let pos = if pos.is_nan() {
+        s.len()
+    } else {
+        clamp(pos.trunc(), s.len())
+    };
+    Ok(JSType::Int(s.rfind(&search, pos).map_or(-1, |i| i as i64)))
+}
+
+fn string_includes(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "includes")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    let pos = clamp(to_integer(program, &arg(&args, 1))?, s.len());
+    Ok(JSType::Bool(s.find(&search, pos).is_some()))
+}
+
+fn string_starts_with(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "startsWith")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    let pos = clamp(to_integer(program, &arg(&args, 1))?, s.len());
+    Ok(JSType::Bool(s.slice(pos, s.len()).starts_with(&search)))
+}
+
+fn string_ends_with(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "endsWith")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    let end = match arg(&args, 1) {
+        JSType::Undefined => s.len(),
+        value => clamp(to_integer(program, &value)?, s.len()),
+    };
+    Ok(JSType::Bool(
+        end >= search.len() && s.slice(end - search.len(), end) == search,
+    ))
+}
+
+fn string_slice(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "slice")?;
+    let start = relative_index(to_integer(program, &arg(&args, 0))?, s.len());
+    let end = match arg(&args, 1) {
+        JSType::Undefined => s.len(),
+        value => relative_index(to_integer(program, &value)?, s.len()),
+    };
+    Ok(JSType::String(s.slice(start, end)))
+}
+
+/// substring的参数小于0时为0, start大于end时交换
+fn string_substring(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "substring")?;
+    let start = clamp(to_integer(program, &arg(&args, 0))?, s.len());
+    let end = match arg(&args, 1) {
+        JSType::Undefined => s.len(),
+        value => clamp(to_integer(program, &value)?, s.len()),
+    };
+    Ok(JSType::String(s.slice(start.min(end), start.max(end))))
+}
+
+fn string_substr(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "substr")?;
+    let start = relative_index(to_integer(program, &arg(&args, 0))?, s.len());
+    let length = match arg(&args, 1) {
+        JSType::Undefined => s.len(),
+        value => clamp(to_integer(program, &value)?, s.len()),
+    };
+    Ok(JSType::String(s.slice(start, start + length)))
+}
+
+/// split(separator, limit), 分隔符为空字符串时按码元拆分
+fn string_split(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "split")?;
+    let limit = match arg(&args, 1) {
+        JSType::Undefined => u32::MAX as usize,
+        value => program.number_value(&value)?.rem_euclid(4294967296.0) as usize,
+    };
+    let separator = arg(&args, 0);
+    let separator = match separator {
+        JSType::Undefined => None,
+        value => Some(program.js_string_value(&value)?),
+    };
+    let mut parts = Vec::new();
+    if limit == 0 {
+        return Ok(program.new_array(parts));
+    }
+    let Some(separator) = separator else {
+        return Ok(program.new_array(vec![JSType::String(s)]));
+    };
+    if separator.is_empty() {
+        let parts = (0..s.len().min(limit))
+            .map(|i| JSType::String(s.slice(i, i + 1)))
+            .collect();
+        return Ok(program.new_array(parts));
+    }
+    let mut start = 0;
+    while let Some(index) = s.find(&separator, start) {
+        parts.push(JSType::String(s.slice(start, index)));
+        if parts.len() >= limit {
+            return Ok(program.new_array(parts));
+        }
+        start = index + separator.len();
+    }
+    parts.push(JSType::String(s.slice(start, s.len())));
+    Ok(program.new_array(parts))
+}
+
+/// 替换字符串中的 $$ $& $` $'
+fn substitution(
+    s: &JSString,
+    matched: &JSString,
+    position: usize,
+    replacement: &JSString,
+) -> JSString {
+    let units = replacement.units();
+    let mut result = JSString::new();
+    let mut index = 0;
+    while index < units.len() {
+        let unit = units[index];
+        if unit == b'$' as u16 && index + 1 < units.len() {
+            let next = units[index + 1];
+            let part = match next {
+                0x24 => Some(JSString::from("$")),
+                0x26 => Some(matched.clone()),
+                0x60 => Some(s.slice(0, position)),
+                0x27 => Some(s.slice(position + matched.len(), s.len())),
+                _ => None,
+            };
+            if let Some(part) = part {
+                result.push_js(&part);
+                index += 2;
+                continue;
+            }
+        }
+        result.push_unit(unit);
+        index += 1;
+    }
+    result
+}
+
+/// 替换值为函数时以 (matched, position, string) 调用
+fn replace_value(
+    program: &mut Program,
+    s: &JSString,
+    search: &JSString,
+    position: usize,
+    replacement: &JSType,
+) -> Result<JSString, String> {
+    if program.is_callable(replacement) {
+        let args = vec![
+            JSType::String(search.clone()),
+            JSType::Int(position as i64),
+            JSType::String(s.clone()),
+        ];
+        let value = program.call(replacement, JSType::Undefined, args)?;
+        return program.js_string_value(&value);
+    }
+    let replacement = program.js_string_value(replacement)?;
+    Ok(substitution(s, search, position, &replacement))
+}
+
+fn string_replace(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "replace")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    let replacement = arg(&args, 1);
+    let Some(position) = s.find(&search, 0) else {
+        return Ok(JSType::String(s));
+    };
+    let value = replace_value(program, &s, &search, position, &replacement)?;
+    let mut result = s.slice(0, position);
+    result.push_js(&value);
+    result.push_js(&s.slice(position + search.len(), s.len()));
+    Ok(JSType::String(result))
+}
+
+/// 空字符串在每个码元之间匹配
+fn string_replace_all(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "replaceAll")?;
+    let search = search_string(program, &arg(&args, 0))?;
+    let replacement = arg(&args, 1);
+    let mut positions = Vec::new();
+    let mut from = 0;
+    while let Some(position) = s.find(&search, from) {
+        positions.push(position);
+        from = position + search.len().max(1);
+    }
+    let mut result = JSString::new();
+    let mut end = 0;
+    for position in positions {
+        result.push_js(&s.slice(end, position));
+        result.push_js(&replace_value(
+            program,
+            &s,
+            &search,
+            position,
+            &replacement,
+        )?);
+        end = position + search.len();
+    }
+    result.push_js(&s.slice(end, s.len()));
+    Ok(JSType::String(result))
+}
+
+fn string_concat(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let mut s = this_string(program, &this, "concat")?;
+    for value in &args {
+        s.push_js(&program.js_string_value(value)?);
+    }
+    Ok(JSType::String(s))
+}
+
+fn string_trim(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "trim")?;
+    Ok(JSType::String(s.trim(true, true)))
+}
+
+fn string_trim_start(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "trimStart")?;
+    Ok(JSType::String(s.trim(true, false)))
+}
+
+fn string_trim_end(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "trimEnd")?;
+    Ok(JSType::String(s.trim(false, true)))
+}
+
+/// 填充到maxLength, 填充字符串默认为空格
+fn pad(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+    start: bool,
+) -> Result<JSType, String> {
+    let method = if start { "padStart" } else { "padEnd" };
+    let s = this_string(program, &this, method)?;
+    let max_length = program.number_value(&arg(&args, 0))?;
+    let fill = match arg(&args, 1) {
+        JSType::Undefined => JSString::from(" "),
+        value => program.js_string_value(&value)?,
+    };
+    if max_length.is_nan() || max_length <= s.len() as f64 || fill.is_empty() {
+        return Ok(JSType::String(s));
+    }
+    if max_length > (1u64 << 29) as f64 {
+        return Err(err("Uncaught RangeError: Invalid string length"));
+    }
+    let fill_length = max_length as usize - s.len();
+    let mut filler = JSString::new();
+    while filler.len() < fill_length {
+        filler.push_js(&fill);
+    }
+    let filler = filler.slice(0, fill_length);
+    Ok(JSType::String(if start {
+        filler.concat(&s)
+    } else {
+        s.concat(&filler)
+    }))
+}
+
+fn string_pad_start(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    pad(program, this, args, true)
+}
+
+fn string_pad_end(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    pad(program, this, args, false)
+}
+
+fn string_repeat(program: &mut Program, this: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let s = this_string(program, &this, "repeat")?;
+    let count = to_integer(program, &arg(&args, 0))?;
+    if count < 0.0 || count.is_infinite() {
+        return Err(err(&format!(
+            "Uncaught RangeError: Invalid count value: {}",
+            JSType::number(count).to_key()
+        )));
+    }
+    if s.len() as f64 * count > (1u64 << 29) as f64 {
+        return Err(err("Uncaught RangeError: Invalid string length"));
+    }
+    Ok(JSType::String(JSString::from_units(
+        s.units().repeat(count as usize),
+    )))
+}
+
+fn string_to_upper_case(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "toUpperCase")?;
+    Ok(JSType::String(s.map_well_formed(str::to_uppercase)))
+}
+
+fn string_to_lower_case(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "toLowerCase")?;
+    Ok(JSType::String(s.map_well_formed(str::to_lowercase)))
+}
+
+/// normalize(form), 默认为NFC
+fn string_normalize(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "normalize")?;
+    let form = match arg(&args, 0) {
+        JSType::Undefined => "NFC".to_string(),
+        value => program.string_value(&value)?,
+    };
+    let units = s.units();
+    let result = match form.as_str() {
+        "NFC" => ComposingNormalizerBorrowed::new_nfc().normalize_utf16(units),
+        "NFD" => DecomposingNormalizerBorrowed::new_nfd().normalize_utf16(units),
+        "NFKC" => ComposingNormalizerBorrowed::new_nfkc().normalize_utf16(units),
+        "NFKD" => DecomposingNormalizerBorrowed::new_nfkd().normalize_utf16(units),
+        _ => return Err(err(
+            "Uncaught RangeError: The normalization form should be one of NFC, NFD, NFKC, NFKD.",
+        )),
+    };
+    Ok(JSType::String(JSString::from_units(result.into_owned())))
+}
+
+/// localeCompare(that, locales, options), 与 Intl.Collator 的比较结果相同
+fn string_locale_compare(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "localeCompare")?;
+    let that = program.js_string_value(&arg(&args, 0))?;
+    let ordering = program.locale_compare(&s, &that, &arg(&args, 1), &arg(&args, 2))?;
+    Ok(JSType::Int(match ordering {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }))
+}
+
+fn string_is_well_formed(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "isWellFormed")?;
+    Ok(JSType::Bool(s.is_well_formed()))
+}
+
+/// 单独的代理项替换为U+FFFD
+fn string_to_well_formed(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let s = this_string(program, &this, "toWellFormed")?;
+    Ok(JSType::String(s.to_string().into()))
+}
+
+/// String.fromCharCode(...codes), 按ToUint16取码元
+fn string_from_char_code(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let mut units = Vec::with_capacity(args.len());
+    for value in &args {
+        let n = program.number_value(value)?;
+        let n = if n.is_finite() { n.trunc() } else { 0.0 };
+        units.push(n.rem_euclid(65536.0) as u16);
+    }
+    Ok(JSType::String(JSString::from_units(units)))
+}
+
+fn string_from_code_point(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let mut s = JSString::new();
+    for value in &args {
+        let n = program.number_value(value)?;
+        if n.fract() != 0.0 || !(0.0..=1114111.0).contains(&n) {
+            let display = match value {
+                JSType::Int(_) | JSType::Float(_) => JSType::number(n).to_key(),
+                _ => program.display(value),
+            };
+            return Err(err(&format!(
+                "Uncaught RangeError: Invalid code point {}",
+                display
+            )));
+        }
+        s.push_code_point(n as u32);
+    }
+    Ok(JSType::String(s))
+}
+
+/// String.raw({ raw: [...] }, ...substitutions)
+fn string_raw(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let template = arg(&args, 0);
+    if let JSType::Undefined | JSType::NULL = template {
+        return Err(err(
+            "Uncaught TypeError: Cannot convert undefined or null to object",
+        ));
+    }
+    let raw = program.get_property(&template, "raw")?;
+    if let JSType::Undefined | JSType::NULL = raw {
+        return Err(err(
+            "Uncaught TypeError: Cannot convert undefined or null to object",
+        ));
+    }
+    let length = program.get_property(&raw, "length")?;
+    let length = to_integer(program, &length)?.max(0.0) as usize;
+    let mut result = JSString::new();
+    for index in 0..length {
+        let part = program.get_property(&raw, index.to_string())?;
+        result.push_js(&program.js_string_value(&part)?);
+        if index + 1 < length {
+            if let Some(value) = args.get(index + 1) {
+                result.push_js(&program.js_string_value(value)?);
+            }
+        }
+    }
+    Ok(JSType::String(result))
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use super::arg;
+use crate::jsparser::object::{JSSymbol, ObjectId, ObjectKind, Property, PropertyKey};
+use crate::jsparser::program::{JSType, Program};
+use crate::jsparser::utility::err;
+
+impl Program {
+    /// 创建内置的Symbol, 需要在其他内置对象之前创建
+    pub(super) fn init_well_known_symbols(&mut self) {
+        self.intrinsics.symbol_iterator = self.new_symbol(Some("Symbol.iterator".to_string()));
+        self.intrinsics.symbol_async_iterator =
+            self.new_symbol(Some("Symbol.asyncIterator".to_string()));
+        self.intrinsics.symbol_has_instance =
+            self.new_symbol(Some("Symbol.hasInstance".to_string()));
+        self.intrinsics.symbol_to_primitive =
+            self.new_symbol(Some("Symbol.toPrimitive".to_string()));
+        self.intrinsics.symbol_to_string_tag =
+            self.new_symbol(Some("Symbol.toStringTag".to_string()));
+    }
+
+    pub(super) fn init_symbol(&mut self) {
+        let symbol_prototype = self.intrinsics.symbol_prototype;
+        self.define_method(symbol_prototype, "toString", 0, symbol_to_string);
+        self.define_method(symbol_prototype, "valueOf", 0, symbol_value_of);
+        let description = self.native_function("description", 0, symbol_description, None);
+        self.define_accessor(
+            symbol_prototype,
+            &"description".into(),
+            true,
+            JSType::Object(description),
+            false,
+        );
+        let to_primitive = self.native_function("[Symbol.toPrimitive]", 1, symbol_value_of, None);
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_to_primitive.clone());
+        self.define_property(
+            symbol_prototype,
+            key,
+            Property::readonly(JSType::Object(to_primitive)),
+        );
+        self.define_to_string_tag(symbol_prototype, "Symbol");
+
+        let symbol = self.native_function("Symbol", 0, symbol_call, None);
+        let mut prop = Property::readonly(JSType::Object(symbol_prototype));
+        prop.configurable = false;
+        self.define_property(symbol, "prototype", prop);
+        self.define_property(
+            symbol_prototype,
+            "constructor",
+            Property::hidden(JSType::Object(symbol)),
+        );
+        self.define_method(symbol, "for", 1, symbol_for);
+        self.define_method(symbol, "keyFor", 1, symbol_key_for);
+        let well_known = [
+            ("iterator", self.intrinsics.symbol_iterator.clone()),
+            (
+                "asyncIterator",
+                self.intrinsics.symbol_async_iterator.clone(),
+            ),
+            ("hasInstance", self.intrinsics.symbol_has_instance.clone()),
+            ("toPrimitive", self.intrinsics.symbol_to_primitive.clone()),
+            ("toStringTag", self.intrinsics.symbol_to_string_tag.clone()),
+        ];
+        for (name, value) in well_known {
+            let mut prop = Property::readonly(JSType::Symbol(value));
+            prop.configurable = false;
+            self.define_property(symbol, name, prop);
+        }
+        self.bind_value("Symbol".to_string(), JSType::Object(symbol));
+    }
+
+    /// 定义 [Symbol.toStringTag], 用于Object.prototype.toString
+    pub(crate) fn define_to_string_tag(&mut self, target: ObjectId, tag: &str) {
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_to_string_tag.clone());
+        self.define_property(target, key, Property::readonly(JSType::String(tag.into())));
+    }
+}
+
+/// Symbol(description), 不能使用new
+fn symbol_call(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let description = match arg(&args, 0) {
+        JSType::Undefined => None,
+        value => Some(program.string_value(&value)?),
+    };
+    Ok(JSType::Symbol(program.new_symbol(description)))
+}
+
+/// thisSymbolValue, 包装对象取出其中的Symbol
+fn this_symbol(program: &Program, this: &JSType, method: &str) -> Result<Arc<JSSymbol>, String> {
+    match this {
+        JSType::Symbol(s) => return Ok(s.clone()),
+        JSType::Object(id) => {
+            if let ObjectKind::Primitive(JSType::Symbol(s)) = &program.obj(*id).kind {
+                return Ok(s.clone());
+            }
+        }
+        _ => {}
+    }
+    Err(err(&format!(
+        "Uncaught TypeError: Symbol.prototype.{} requires that 'this' be a Symbol",
+        method
+    )))
+}
+
+fn symbol_to_string(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let symbol = this_symbol(program, &this, "toString")?;
+    Ok(JSType::String(symbol.to_string().into()))
+}
+
+/// valueOf / [Symbol.toPrimitive]
+fn symbol_value_of(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    let symbol = this_symbol(program, &this, "valueOf")?;
+    Ok(JSType::Symbol(symbol))
+}
+
+fn symbol_description(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let symbol = this_symbol(program, &this, "description")?;
+    Ok(match &symbol.description {
+        Some(d) => JSType::String(d.clone().into()),
+        None => JSType::Undefined,
+    })
+}
+
+/// Symbol.for(key), 相同的key返回同一个Symbol
+fn symbol_for(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let key = program.string_value(&arg(&args, 0))?;
+    Ok(JSType::Symbol(program.registered_symbol(key)))
+}
+
+/// Symbol.keyFor(symbol), 不是通过Symbol.for创建时为undefined
+fn symbol_key_for(program: &mut Program, _: JSType, args: Vec<JSType>) -> Result<JSType, String> {
+    let JSType::Symbol(symbol) = arg(&args, 0) else {
+        return Err(err(&format!(
+            "Uncaught TypeError: {} is not a symbol",
+            program.display(&arg(&args, 0))
+        )));
+    };
+    Ok(match program.symbol_key(&symbol) {
+        Some(key) => JSType::String(key.into()),
+        None => JSType::Undefined,
+    })
+}
@@ -0,0 +1,1130 @@
+use std::cmp::Ordering;
+
+use super::array;
+use super::{arg, list_from_array_like, relative_index, to_integer};
+use crate::jsparser::object::{
+    JSObject, NativeCtor, NativeFn, ObjectId, ObjectKind, Property, PropertyKey,
+};
+use crate::jsparser::program::{number_to_string, BufferView, JSType, Program, TypedArrayKind};
+use crate::jsparser::utility::err;
+
+impl Program {
+    pub(super) fn init_typed_arrays(&mut self) {
+        let object_prototype = self.intrinsics.object_prototype;
+
+        //ArrayBuffer
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.array_buffer_prototype = proto;
+        let array_buffer = self.define_constructor(
+            "ArrayBuffer",
+            1,
+            proto,
+            array_buffer_call,
+            array_buffer_construct,
+        );
+        self.define_method(array_buffer, "isView", 1, array_buffer_is_view);
+        self.define_getter(
+            proto,
+            "byteLength",
+            "get byteLength",
+            array_buffer_byte_length,
+        );
+        self.define_method(proto, "slice", 2, array_buffer_slice);
+        self.define_to_string_tag(proto, "ArrayBuffer");
+
+        //%TypedArray%, 各类型化数组构造函数及原型的原型
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.typed_array_prototype = proto;
+        let typed_array = self.native_function(
+            "TypedArray",
+            0,
+            typed_array_abstract_call,
+            Some(typed_array_abstract_construct),
+        );
+        self.intrinsics.typed_array = typed_array;
+        let mut prop = Property::readonly(JSType::Object(proto));
+        prop.configurable = false;
+        self.define_property(typed_array, "prototype", prop);
+        self.define_property(
+            proto,
+            "constructor",
+            Property::hidden(JSType::Object(typed_array)),
+        );
+        self.define_method(typed_array, "from", 1, typed_array_from);
+        self.define_method(typed_array, "of", 0, typed_array_of);
+        let getters: [(&'static str, &'static str, NativeFn); 4] = [
+            ("buffer", "get buffer", typed_array_buffer),
+            ("byteLength", "get byteLength", typed_array_byte_length),
+            ("byteOffset", "get byteOffset", typed_array_byte_offset),
+            ("length", "get length", typed_array_length),
+        ];
+        for (name, getter_name, func) in getters {
+            self.define_getter(proto, name, getter_name, func);
+        }
+        let getter = self.native_function(
+            "get [Symbol.toStringTag]",
+            0,
+            typed_array_to_string_tag,
+            None,
+        );
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_to_string_tag.clone());
+        self.define_accessor(proto, &key, true, JSType::Object(getter), false);
+        let methods: [(&'static str, usize, NativeFn); 26] = [
+            ("at", 1, typed_array_at),
+            ("copyWithin", 2, typed_array_copy_within),
+            ("entries", 0, typed_array_entries),
+            ("every", 1, typed_array_every),
+            ("fill", 1, typed_array_fill),
+            ("filter", 1, typed_array_filter),
+            ("find", 1, typed_array_find),
+            ("findIndex", 1, typed_array_find_index),
+            ("findLast", 1, typed_array_find_last),
+            ("findLastIndex", 1, typed_array_find_last_index),
+            ("forEach", 1, typed_array_for_each),
+            ("includes", 1, typed_array_includes),
+            ("indexOf", 1, typed_array_index_of),
+            ("join", 1, typed_array_join),
+            ("keys", 0, typed_array_keys),
+            ("lastIndexOf", 1, typed_array_last_index_of),
+            ("map", 1, typed_array_map),
+            ("reduce", 1, typed_array_reduce),
+            ("reduceRight", 1, typed_array_reduce_right),
+            ("reverse", 0, typed_array_reverse),
+            ("set", 1, typed_array_set),
+            ("slice", 2, typed_array_slice),
+            ("some", 1, typed_array_some),
+            ("sort", 1, typed_array_sort),
+            ("subarray", 2, typed_array_subarray),
+            ("values", 0, typed_array_values),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        //toString与Array.prototype.toString为同一个函数
+        let array_prototype = self.intrinsics.array_prototype;
+        let to_string = self.get_own_property(array_prototype, &"toString".into());
+        self.define_property(proto, "toString", to_string.unwrap());
+        let values = self.get_own_property(proto, &"values".into()).unwrap();
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_iterator.clone());
+        self.define_property(proto, key, values);
+
+        //Int8Array ... BigUint64Array
+        let constructors: [NativeCtor; 11] = [
+            typed_array_construct::<0>,
+            typed_array_construct::<1>,
+            typed_array_construct::<2>,
+            typed_array_construct::<3>,
+            typed_array_construct::<4>,
+            typed_array_construct::<5>,
+            typed_array_construct::<6>,
+            typed_array_construct::<7>,
+            typed_array_construct::<8>,
+            typed_array_construct::<9>,
+            typed_array_construct::<10>,
+        ];
+        let calls: [NativeFn; 11] = [
+            typed_array_call::<0>,
+            typed_array_call::<1>,
+            typed_array_call::<2>,
+            typed_array_call::<3>,
+            typed_array_call::<4>,
+            typed_array_call::<5>,
+            typed_array_call::<6>,
+            typed_array_call::<7>,
+            typed_array_call::<8>,
+            typed_array_call::<9>,
+            typed_array_call::<10>,
+        ];
+        let typed_array_prototype = proto;
+        for kind in TypedArrayKind::ALL {
+            let proto = self.alloc(JSObject::new(
+                ObjectKind::Ordinary,
+                Some(typed_array_prototype),
+            ));
+            self.intrinsics.typed_array_prototypes[kind as usize] = proto;
+            let ctor = self.define_constructor(
+                kind.name(),
+                3,
+                proto,
+                calls[kind as usize],
+                constructors[kind as usize],
+            );
+            self.obj_mut(ctor).proto = Some(typed_array);
+            let size = JSType::Int(kind.size() as i64);
+            for target in [ctor, proto] {
+                let mut prop = Property::readonly(size.clone());
+                prop.configurable = false;
+                self.define_property(target, "BYTES_PER_ELEMENT", prop);
+            }
+        }
+
+        //DataView
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(object_prototype)));
+        self.intrinsics.data_view_prototype = proto;
+        self.define_constructor("DataView", 1, proto, data_view_call, data_view_construct);
+        let getters: [(&'static str, &'static str, NativeFn); 3] = [
+            ("buffer", "get buffer", data_view_buffer),
+            ("byteLength", "get byteLength", data_view_byte_length),
+            ("byteOffset", "get byteOffset", data_view_byte_offset),
+        ];
+        for (name, getter_name, func) in getters {
+            self.define_getter(proto, name, getter_name, func);
+        }
+        //下标对应TypedArrayKind::ALL, DataView没有Uint8Clamped
+        let methods: [(&'static str, usize, NativeFn); 20] = [
+            ("getInt8", 1, data_view_get::<0>),
+            ("getUint8", 1, data_view_get::<1>),
+            ("getInt16", 1, data_view_get::<3>),
+            ("getUint16", 1, data_view_get::<4>),
+            ("getInt32", 1, data_view_get::<5>),
+            ("getUint32", 1, data_view_get::<6>),
+            ("getFloat32", 1, data_view_get::<7>),
+            ("getFloat64", 1, data_view_get::<8>),
+            ("getBigInt64", 1, data_view_get::<9>),
+            ("getBigUint64", 1, data_view_get::<10>),
+            ("setInt8", 2, data_view_set::<0>),
+            ("setUint8", 2, data_view_set::<1>),
+            ("setInt16", 2, data_view_set::<3>),
+            ("setUint16", 2, data_view_set::<4>),
+            ("setInt32", 2, data_view_set::<5>),
+            ("setUint32", 2, data_view_set::<6>),
+            ("setFloat32", 2, data_view_set::<7>),
+            ("setFloat64", 2, data_view_set::<8>),
+            ("setBigInt64", 2, data_view_set::<9>),
+            ("setBigUint64", 2, data_view_set::<10>),
+        ];
+        for (name, length, func) in methods {
+            self.define_method(proto, name, length, func);
+        }
+        self.define_to_string_tag(proto, "DataView");
+    }
+
+    /// 创建指定长度的类型化数组, 元素为0
+    fn new_typed_array(
+        &mut self,
+        kind: TypedArrayKind,
+        length: usize,
+        proto: ObjectId,
+    ) -> Result<ObjectId, String> {
+        let byte_length = length
+            .checked_mul(kind.size())
+            .ok_or_else(|| err("Uncaught RangeError: Array buffer allocation failed"))?;
+        let buffer_proto = self.intrinsics.array_buffer_prototype;
+        let buffer = self.allocate_array_buffer(byte_length, buffer_proto)?;
+        let view = BufferView {
+            buffer,
+            offset: 0,
+            length,
+        };
+        Ok(self.alloc(JSObject::new(
+            ObjectKind::TypedArray(kind, view),
+            Some(proto),
+        )))
+    }
+
+    /// 创建类型化数组并写入元素
+    fn typed_array_from_values(
+        &mut self,
+        kind: TypedArrayKind,
+        values: &[JSType],
+        proto: ObjectId,
+    ) -> Result<JSType, String> {
+        let id = self.new_typed_array(kind, values.len(), proto)?;
+        for (index, value) in values.iter().enumerate() {
+            self.typed_array_set(id, index, value);
+        }
+        Ok(JSType::Object(id))
+    }
+
+    /// 类型化数组的所有元素
+    fn typed_array_elements(&self, id: ObjectId, length: usize) -> Vec<JSType> {
+        (0..length)
+            .map(|index| self.typed_array_get(id, index))
+            .collect()
+    }
+}
+
+/// ToIndex, 不是0到2^53-1的整数时抛出RangeError
+fn to_index(
+    program: &mut Program,
+    value: &JSType,
+    message: fn(&str) -> String,
+) -> Result<usize, String> {
+    let n = to_integer(program, value)?;
+    if !(0.0..=9007199254740991.0).contains(&n) {
+        return Err(err(&format!(
+            "Uncaught RangeError: {}",
+            message(&number_to_string(n))
+        )));
+    }
+    Ok(n as usize)
+}
+
+fn requires_new(name: &str) -> Result<JSType, String> {
+    Err(err(&format!(
+        "Uncaught TypeError: Constructor {} requires 'new'",
+        name
+    )))
+}
+
+/// 方法的this类型不符
+fn incompatible_receiver(program: &Program, method: &str, this: &JSType) -> String {
+    err(&format!(
+        "Uncaught TypeError: Method {} called on incompatible receiver {}",
+        method,
+        program.display(this)
+    ))
+}
+
+//------------------------------ ArrayBuffer ------------------------------
+
+fn array_buffer_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new("ArrayBuffer")
+}
+
+/// new ArrayBuffer(length)
+fn array_buffer_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let length = to_index(program, &arg(&args, 0), |_| {
+        "Invalid array buffer length".to_string()
+    })?;
+    let proto = program.get_prototype_from_constructor(&new_target, "ArrayBuffer")?;
+    Ok(JSType::Object(
+        program.allocate_array_buffer(length, proto)?,
+    ))
+}
+
+fn this_array_buffer(program: &Program, this: &JSType, method: &str) -> Result<ObjectId, String> {
+    match this {
+        JSType::Object(id) if matches!(program.obj(*id).kind, ObjectKind::ArrayBuffer(_)) => {
+            Ok(*id)
+        }
+        _ => Err(incompatible_receiver(
+            program,
+            &format!("ArrayBuffer.prototype.{}", method),
+            this,
+        )),
+    }
+}
+
+/// ArrayBuffer.isView, 类型化数组或DataView
+fn array_buffer_is_view(
+    program: &mut Program,
+    _: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    Ok(JSType::Bool(match arg(&args, 0) {
+        JSType::Object(id) => matches!(
+            program.obj(id).kind,
+            ObjectKind::TypedArray(_, _) | ObjectKind::DataView(_)
+        ),
+        _ => false,
+    }))
+}
+
+fn array_buffer_byte_length(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = this_array_buffer(program, &this, "byteLength")?;
+    Ok(JSType::Int(program.buffer_bytes(id).len() as i64))
+}
+
+/// slice(start, end) 复制到新的ArrayBuffer
+fn array_buffer_slice(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let id = this_array_buffer(program, &this, "slice")?;
+    let length = program.buffer_bytes(id).len();
+    let start = relative_index(to_integer(program, &arg(&args, 0))?, length);
+    let end = match arg(&args, 1) {
+        JSType::Undefined => length,
+        value => relative_index(to_integer(program, &value)?, length),
+    };
+    let bytes = program.buffer_bytes(id)[start..end.max(start)].to_vec();
+    Ok(program.new_array_buffer(bytes))
+}
+
+//------------------------------ TypedArray ------------------------------
+
+fn typed_array_abstract_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new("TypedArray")
+}
+
+fn typed_array_abstract_construct(
+    _: &mut Program,
+    _: Vec<JSType>,
+    _: JSType,
+) -> Result<JSType, String> {
+    Err(err(
+        "Uncaught TypeError: Abstract class TypedArray not directly constructable",
+    ))
+}
+
+fn typed_array_call<const K: usize>(
+    _: &mut Program,
+    _: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    requires_new(TypedArrayKind::ALL[K].name())
+}
+
+/// new Uint8Array(length | typedArray | object | buffer, byteOffset, length)
+fn typed_array_construct<const K: usize>(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let kind = TypedArrayKind::ALL[K];
+    let proto = program.get_prototype_from_constructor(&new_target, kind.name())?;
+    let first = arg(&args, 0);
+    let JSType::Object(source) = first else {
+        let length = to_index(program, &first, |n| {
+            format!("Invalid typed array length: {}", n)
+        })?;
+        return Ok(JSType::Object(
+            program.new_typed_array(kind, length, proto)?,
+        ));
+    };
+    let values = match &program.obj(source).kind {
+        ObjectKind::ArrayBuffer(_) => {
+            return typed_array_of_buffer(program, kind, source, &args, proto);
+        }
+        ObjectKind::TypedArray(source_kind, view) => {
+            let length = view.length;
+            check_content_type(kind, *source_kind)?;
+            program.typed_array_elements(source, length)
+        }
+        _ => {
+            let key = PropertyKey::Symbol(program.intrinsics.symbol_iterator.clone());
+            let method = program.get_property(&first, key)?;
+            let list = if matches!(method, JSType::Undefined | JSType::NULL) {
+                list_from_array_like(program, &first)?
+            } else {
+                program.iterate_to_list(&first)?
+            };
+            let mut values = Vec::with_capacity(list.len());
+            for value in &list {
+                values.push(program.element_value(kind, value)?);
+            }
+            values
+        }
+    };
+    program.typed_array_from_values(kind, &values, proto)
+}
+
+/// BigInt类型与Number类型的类型化数组之间不能复制元素
+fn check_content_type(target: TypedArrayKind, source: TypedArrayKind) -> Result<(), String> {
+    if target.is_bigint() != source.is_bigint() {
+        return Err(err(
+            "Uncaught TypeError: Cannot mix BigInt and other types, use explicit conversions",
+        ));
+    }
+    Ok(())
+}
+
+/// 引用已有ArrayBuffer的类型化数组
+fn typed_array_of_buffer(
+    program: &mut Program,
+    kind: TypedArrayKind,
+    buffer: ObjectId,
+    args: &[JSType],
+    proto: ObjectId,
+) -> Result<JSType, String> {
+    let size = kind.size();
+    let offset = to_index(program, &arg(args, 1), |n| {
+        format!("Start offset {} is outside the bounds of the buffer", n)
+    })?;
+    if !offset.is_multiple_of(size) {
+        return Err(err(&format!(
+            "Uncaught RangeError: start offset of {} should be a multiple of {}",
+            kind.name(),
+            size
+        )));
+    }
+    let length = match arg(args, 2) {
+        JSType::Undefined => None,
+        value => Some(to_index(program, &value, |n| {
+            format!("Invalid typed array length: {}", n)
+        })?),
+    };
+    let buffer_length = program.buffer_bytes(buffer).len();
+    let length = match length {
+        Some(length) => {
+            if offset + length * size > buffer_length {
+                return Err(err(&format!(
+                    "Uncaught RangeError: Invalid typed array length: {}",
+                    length
+                )));
+            }
+            length
+        }
+        None => {
+            if !buffer_length.is_multiple_of(size) {
+                return Err(err(&format!(
+                    "Uncaught RangeError: byte length of {} should be a multiple of {}",
+                    kind.name(),
+                    size
+                )));
+            }
+            if offset > buffer_length {
+                return Err(err(&format!(
+                    "Uncaught RangeError: Start offset {} is outside the bounds of the buffer",
+                    offset
+                )));
+            }
+            (buffer_length - offset) / size
+        }
+    };
+    let view = BufferView {
+        buffer,
+        offset,
+        length,
+    };
+    Ok(JSType::Object(program.alloc(JSObject::new(
+        ObjectKind::TypedArray(kind, view),
+        Some(proto),
+    ))))
+}
+
+/// ValidateTypedArray, 返回id/元素类型/范围
+fn this_typed_array(
+    program: &Program,
+    this: &JSType,
+) -> Result<(ObjectId, TypedArrayKind, BufferView), String> {
+    if let JSType::Object(id) = this {
+        if let ObjectKind::TypedArray(kind, view) = &program.obj(*id).kind {
+            return Ok((*id, *kind, view.clone()));
+        }
+    }
+    Err(err("Uncaught TypeError: this is not a typed array."))
+}
+
+/// TypedArrayCreate, 使用构造函数创建长度至少为length的类型化数组
+fn typed_array_create(
+    program: &mut Program,
+    constructor: &JSType,
+    length: usize,
+) -> Result<JSType, String> {
+    let target = program.construct(constructor, vec![JSType::Int(length as i64)], None)?;
+    let (_, _, view) = this_typed_array(program, &target)?;
+    if view.length < length {
+        return Err(err(
+            "Uncaught TypeError: Derived TypedArray constructor created an array which was too small",
+        ));
+    }
+    Ok(target)
+}
+
+/// TypedArray.from(source, mapFn, thisArg)
+fn typed_array_from(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let source = arg(&args, 0);
+    let mapper = match arg(&args, 1) {
+        JSType::Undefined => None,
+        value if program.is_callable(&value) => Some(value),
+        value => {
+            return Err(err(&format!(
+                "Uncaught TypeError: {} is not a function",
+                program.display(&value)
+            )))
+        }
+    };
+    let this_arg = arg(&args, 2);
+    let key = PropertyKey::Symbol(program.intrinsics.symbol_iterator.clone());
+    let method = program.get_property(&source, key)?;
+    let values = if matches!(method, JSType::Undefined | JSType::NULL) {
+        let source = JSType::Object(program.object_value(&source)?);
+        list_from_array_like(program, &source)?
+    } else {
+        program.iterate_to_list(&source)?
+    };
+    let target = typed_array_create(program, &this, values.len())?;
+    for (index, value) in values.into_iter().enumerate() {
+        let value = match &mapper {
+            Some(mapper) => {
+                let args = vec![value, JSType::Int(index as i64)];
+                program.call(mapper, this_arg.clone(), args)?
+            }
+            None => value,
+        };
+        program.set_property(&target, index.to_string(), value)?;
+    }
+    Ok(target)
+}
+
+/// TypedArray.of(...items)
+fn typed_array_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let target = typed_array_create(program, &this, args.len())?;
+    for (index, value) in args.into_iter().enumerate() {
+        program.set_property(&target, index.to_string(), value)?;
+    }
+    Ok(target)
+}
+
+fn typed_array_buffer(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, _, view) = this_typed_array(program, &this)?;
+    Ok(JSType::Object(view.buffer))
+}
+
+fn typed_array_byte_length(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, kind, view) = this_typed_array(program, &this)?;
+    Ok(JSType::Int((view.length * kind.size()) as i64))
+}
+
+fn typed_array_byte_offset(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, _, view) = this_typed_array(program, &this)?;
+    Ok(JSType::Int(view.offset as i64))
+}
+
+fn typed_array_length(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, _, view) = this_typed_array(program, &this)?;
+    Ok(JSType::Int(view.length as i64))
+}
+
+/// get [Symbol.toStringTag], 不是类型化数组时为undefined
+fn typed_array_to_string_tag(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    Ok(match this_typed_array(program, &this) {
+        Ok((_, kind, _)) => JSType::String(kind.name().into()),
+        Err(_) => JSType::Undefined,
+    })
+}
+
+/// 检查this后使用Array.prototype上的通用实现
+fn delegate(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+    func: NativeFn,
+) -> Result<JSType, String> {
+    this_typed_array(program, &this)?;
+    func(program, this, args)
+}
+
+fn typed_array_at(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_at)
+}
+
+fn typed_array_copy_within(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_copy_within)
+}
+
+fn typed_array_every(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_every)
+}
+
+fn typed_array_find(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_find)
+}
+
+fn typed_array_find_index(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_find_index)
+}
+
+fn typed_array_find_last(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_find_last)
+}
+
+fn typed_array_find_last_index(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_find_last_index)
+}
+
+fn typed_array_for_each(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_for_each)
+}
+
+fn typed_array_includes(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_includes)
+}
+
+fn typed_array_index_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_index_of)
+}
+
+fn typed_array_join(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_join)
+}
+
+fn typed_array_last_index_of(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_last_index_of)
+}
+
+fn typed_array_reduce(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_reduce)
+}
+
+fn typed_array_reduce_right(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_reduce_right)
+}
+
+fn typed_array_reverse(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_reverse)
+}
+
+fn typed_array_some(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, array::array_some)
+}
+
+fn typed_array_keys(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, super::array_keys)
+}
+
+fn typed_array_values(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, super::array_values)
+}
+
+fn typed_array_entries(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    delegate(program, this, args, super::array_entries)
+}
+
+/// fill(value, start, end), value只转换一次
+fn typed_array_fill(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, kind, _) = this_typed_array(program, &this)?;
+    let value = program.element_value(kind, &arg(&args, 0))?;
+    let args = vec![value, arg(&args, 1), arg(&args, 2)];
+    array::array_fill(program, this, args)
+}
+
+/// 回调参数必须可调用
+fn callback(program: &Program, value: JSType) -> Result<JSType, String> {
+    if !program.is_callable(&value) {
+        return Err(err(&format!(
+            "Uncaught TypeError: {} is not a function",
+            program.display(&value)
+        )));
+    }
+    Ok(value)
+}
+
+/// map(callback, thisArg), 结果为相同类型的类型化数组
+fn typed_array_map(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (id, kind, view) = this_typed_array(program, &this)?;
+    let func = callback(program, arg(&args, 0))?;
+    let this_arg = arg(&args, 1);
+    let mut values = Vec::with_capacity(view.length);
+    for index in 0..view.length {
+        let value = program.typed_array_get(id, index);
+        let call_args = vec![value, JSType::Int(index as i64), this.clone()];
+        let result = program.call(&func, this_arg.clone(), call_args)?;
+        values.push(program.element_value(kind, &result)?);
+    }
+    let proto = program.intrinsics.typed_array_prototypes[kind as usize];
+    program.typed_array_from_values(kind, &values, proto)
+}
+
+fn typed_array_filter(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (id, kind, view) = this_typed_array(program, &this)?;
+    let func = callback(program, arg(&args, 0))?;
+    let this_arg = arg(&args, 1);
+    let mut values = Vec::new();
+    for index in 0..view.length {
+        let value = program.typed_array_get(id, index);
+        let call_args = vec![value.clone(), JSType::Int(index as i64), this.clone()];
+        if program
+            .call(&func, this_arg.clone(), call_args)?
+            .is_truthy()
+        {
+            values.push(value);
+        }
+    }
+    let proto = program.intrinsics.typed_array_prototypes[kind as usize];
+    program.typed_array_from_values(kind, &values, proto)
+}
+
+/// set(source, offset), 来源为类型化数组时先读取全部元素, 允许与目标重叠
+fn typed_array_set(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (id, kind, view) = this_typed_array(program, &this)?;
+    let offset = to_integer(program, &arg(&args, 1))?;
+    if offset < 0.0 {
+        return Err(err("Uncaught RangeError: offset is out of bounds"));
+    }
+    let source = arg(&args, 0);
+    let values = match &source {
+        JSType::Object(src) if matches!(program.obj(*src).kind, ObjectKind::TypedArray(_, _)) => {
+            let (src, src_kind, src_view) = this_typed_array(program, &source)?;
+            check_content_type(kind, src_kind)?;
+            program.typed_array_elements(src, src_view.length)
+        }
+        _ => {
+            let source = JSType::Object(program.object_value(&source)?);
+            let length = program.get_property(&source, "length")?;
+            let length = to_integer(program, &length)?.max(0.0);
+            if offset + length > view.length as f64 {
+                return Err(err("Uncaught RangeError: offset is out of bounds"));
+            }
+            let mut values = Vec::with_capacity(length as usize);
+            for index in 0..length as usize {
+                let value = program.get_property(&source, index.to_string())?;
+                values.push(program.element_value(kind, &value)?);
+            }
+            values
+        }
+    };
+    if offset + values.len() as f64 > view.length as f64 {
+        return Err(err("Uncaught RangeError: offset is out of bounds"));
+    }
+    for (index, value) in values.iter().enumerate() {
+        program.typed_array_set(id, offset as usize + index, value);
+    }
+    Ok(JSType::Undefined)
+}
+
+/// start/end参数, 负数从末尾计算
+fn typed_array_range(
+    program: &mut Program,
+    args: &[JSType],
+    length: usize,
+) -> Result<(usize, usize), String> {
+    let start = relative_index(to_integer(program, &arg(args, 0))?, length);
+    let end = match arg(args, 1) {
+        JSType::Undefined => length,
+        value => relative_index(to_integer(program, &value)?, length),
+    };
+    Ok((start, end.max(start)))
+}
+
+/// slice(start, end) 复制到新的类型化数组
+fn typed_array_slice(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (id, kind, view) = this_typed_array(program, &this)?;
+    let (start, end) = typed_array_range(program, &args, view.length)?;
+    let values: Vec<JSType> = (start..end)
+        .map(|index| program.typed_array_get(id, index))
+        .collect();
+    let proto = program.intrinsics.typed_array_prototypes[kind as usize];
+    program.typed_array_from_values(kind, &values, proto)
+}
+
+/// subarray(begin, end) 引用同一个ArrayBuffer
+fn typed_array_subarray(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let (_, kind, view) = this_typed_array(program, &this)?;
+    let (begin, end) = typed_array_range(program, &args, view.length)?;
+    let view = BufferView {
+        buffer: view.buffer,
+        offset: view.offset + begin * kind.size(),
+        length: end - begin,
+    };
+    let proto = program.intrinsics.typed_array_prototypes[kind as usize];
+    Ok(JSType::Object(program.alloc(JSObject::new(
+        ObjectKind::TypedArray(kind, view),
+        Some(proto),
+    ))))
+}
+
+/// 默认排序的比较, -0在+0之前, NaN在最后, BigInt按数学值比较
+fn compare_elements(a: &JSType, b: &JSType) -> Ordering {
+    if let (JSType::BigInt(a), JSType::BigInt(b)) = (a, b) {
+        return a.cmp(b);
+    }
+    let (a, b) = (a.to_number(), b.to_number());
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        _ => a.total_cmp(&b),
+    }
+}
+
+/// sort(compareFn), 默认按数值排序
+fn typed_array_sort(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let compare = array::comparator(program, arg(&args, 0))?;
+    let (id, _, view) = this_typed_array(program, &this)?;
+    let mut values = program.typed_array_elements(id, view.length);
+    if let JSType::Undefined = compare {
+        values.sort_by(compare_elements);
+    } else {
+        values = array::sort_values(program, values, &compare)?;
+    }
+    for (index, value) in values.iter().enumerate() {
+        program.typed_array_set(id, index, value);
+    }
+    Ok(this)
+}
+
+//------------------------------ DataView ------------------------------
+
+fn data_view_call(_: &mut Program, _: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    requires_new("DataView")
+}
+
+/// new DataView(buffer, byteOffset, byteLength)
+fn data_view_construct(
+    program: &mut Program,
+    args: Vec<JSType>,
+    new_target: JSType,
+) -> Result<JSType, String> {
+    let buffer =
+        match arg(&args, 0) {
+            JSType::Object(id) if matches!(program.obj(id).kind, ObjectKind::ArrayBuffer(_)) => id,
+            _ => return Err(err(
+                "Uncaught TypeError: First argument to DataView constructor must be an ArrayBuffer",
+            )),
+        };
+    let offset = to_index(program, &arg(&args, 1), |n| {
+        format!("Start offset {} is outside the bounds of the buffer", n)
+    })?;
+    let buffer_length = program.buffer_bytes(buffer).len();
+    if offset > buffer_length {
+        return Err(err(&format!(
+            "Uncaught RangeError: Start offset {} is outside the bounds of the buffer",
+            offset
+        )));
+    }
+    let length = match arg(&args, 2) {
+        JSType::Undefined => buffer_length - offset,
+        value => {
+            let length = to_index(program, &value, |n| {
+                format!("Invalid DataView length {}", n)
+            })?;
+            if offset + length > buffer_length {
+                return Err(err(&format!(
+                    "Uncaught RangeError: Invalid DataView length {}",
+                    length
+                )));
+            }
+            length
+        }
+    };
+    let proto = program.get_prototype_from_constructor(&new_target, "DataView")?;
+    let view = BufferView {
+        buffer,
+        offset,
+        length,
+    };
+    Ok(JSType::Object(program.alloc(JSObject::new(
+        ObjectKind::DataView(view),
+        Some(proto),
+    ))))
+}
+
+fn this_data_view(program: &Program, this: &JSType, method: &str) -> Result<BufferView, String> {
+    if let JSType::Object(id) = this {
+        if let ObjectKind::DataView(view) = &program.obj(*id).kind {
+            return Ok(view.clone());
+        }
+    }
+    Err(incompatible_receiver(
+        program,
+        &format!("DataView.prototype.{}", method),
+        this,
+    ))
+}
+
+fn data_view_buffer(program: &mut Program, this: JSType, _: Vec<JSType>) -> Result<JSType, String> {
+    Ok(JSType::Object(
+        this_data_view(program, &this, "buffer")?.buffer,
+    ))
+}
+
+fn data_view_byte_length(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let view = this_data_view(program, &this, "byteLength")?;
+    Ok(JSType::Int(view.length as i64))
+}
+
+fn data_view_byte_offset(
+    program: &mut Program,
+    this: JSType,
+    _: Vec<JSType>,
+) -> Result<JSType, String> {
+    let view = this_data_view(program, &this, "byteOffset")?;
+    Ok(JSType::Int(view.offset as i64))
+}
+
+/// 读写的起始字节, 超出范围时抛出RangeError
+fn view_index(
+    program: &mut Program,
+    view: &BufferView,
+    value: &JSType,
+    kind: TypedArrayKind,
+) -> Result<usize, String> {
+    let index = to_index(program, value, |_| {
+        "Offset is outside the bounds of the DataView".to_string()
+    })?;
+    if index + kind.size() > view.length {
+        return Err(err(
+            "Uncaught RangeError: Offset is outside the bounds of the DataView",
+        ));
+    }
+    Ok(view.offset + index)
+}
+
+/// getInt8(byteOffset) getUint16(byteOffset, littleEndian) ..., 默认为大端
+fn data_view_get<const K: usize>(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let kind = TypedArrayKind::ALL[K];
+    let view = this_data_view(
+        program,
+        &this,
+        &format!("get{}", &kind.name()[..kind.name().len() - 5]),
+    )?;
+    let start = view_index(program, &view, &arg(&args, 0), kind)?;
+    let little_endian = arg(&args, 1).is_truthy();
+    let bytes = &program.buffer_bytes(view.buffer)[start..];
+    Ok(kind.decode(bytes, little_endian))
+}
+
+/// setInt8(byteOffset, value) setUint16(byteOffset, value, littleEndian) ...
+fn data_view_set<const K: usize>(
+    program: &mut Program,
+    this: JSType,
+    args: Vec<JSType>,
+) -> Result<JSType, String> {
+    let kind = TypedArrayKind::ALL[K];
+    let view = this_data_view(
+        program,
+        &this,
+        &format!("set{}", &kind.name()[..kind.name().len() - 5]),
+    )?;
+    let start = view_index(program, &view, &arg(&args, 0), kind)?;
+    let value = program.element_value(kind, &arg(&args, 1))?;
+    let little_endian = arg(&args, 2).is_truthy();
+    kind.encode(
+        &value,
+        little_endian,
+        &mut program.buffer_bytes_mut(view.buffer)[start..],
+    );
+    Ok(JSType::Undefined)
+}
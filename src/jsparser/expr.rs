@@ -1,47 +1,141 @@
-use std::collections::HashMap;
+use super::string::JSString;
+use num_bigint::BigInt;
+use std::sync::Arc;
+
+/// for await 展开后调用的内置函数, 名称含空格, 代码中无法引用
+pub const ASYNC_ITERATOR_OF: &str = "async iterator of";
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Empty,                   //base
     Unary(Unary, Box<Expr>), // !a  !~+-a
-    Unexpected(String),      //异常
     Identifier(String),
-    Template(Vec<String>, Vec<Expr>), //``模板
-    Literal(String),
-    Call(Box<Expr>, Vec<Expr>),            // a()  a.b()
-    Member(Box<Expr>, Box<Expr>),          //a.b a[b]
-    Sequence(Vec<Expr>),                   // a[1,2,3,4]
+    Template(Vec<String>, Vec<Expr>),             //``模板
+    Literal(JSString),                            //字符串
+    Number(f64),                                  //数字
+    BigInt(Arc<BigInt>),                          //10n
+    Bool(bool),                                   //true false
+    Null,                                         //null
+    This,                                         //this
+    Super,                                        //super 只能用于 super() super.a super[a]
+    Call(Box<Expr>, Vec<Expr>),                   // a()  a.b()
+    New(Box<Expr>, Vec<Expr>),                    // new A()
+    Member(Box<Expr>, Box<Expr>), //a.b => Member(a,Literal(b))  a[b] => Member(a,Identifier(b))
+    PrivateMember(Box<Expr>, String), //a.#b
+    Sequence(Vec<Expr>),          // a,b,c
     Infix(Box<Expr>, Operator, Box<Expr>), //算术符号 a+b  +-*/   a && b  逻辑符号 &&,||,!
-    Update(Box<Expr>, String, bool),       //a++/++a     bool:存放++的前后位置
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>), // a ? b : c
+    Update(Box<Expr>, String, bool), //a++/++a     bool:存放++的前后位置
     Variable(Vec<(Variable, String, Expr)>),
-    Assignment(Vec<(String, Expr)>),
+    Assignment(Box<Expr>, Box<Expr>),    //a = b  a.b = c
+    Spread(Box<Expr>),                   //...a  参数或数组展开
+    Yield(Box<Expr>, bool),              //yield a / yield* a
+    Await(Box<Expr>),                    //await a
     If(Box<Expr>, Box<Expr>, Box<Expr>), //if
     Block(Vec<Expr>),
-    Expression(Box<Expr>),
     Return(Box<Expr>),
     For(Box<Expr>, Box<Expr>, Box<Expr>, Box<Expr>), //for
-    ForIn(Box<Expr>, Box<Expr>),                     //for in
-    ForOf(Box<Expr>, Box<Expr>),                     //for of
-    Break,
-    Continue,
-    Function(Box<Expr>, Vec<Expr>, Box<Expr>), //function
+    ForIn(Box<Expr>, Box<Expr>, Box<Expr>),          //for(a in b)
+    ForOf(Box<Expr>, Box<Expr>, Box<Expr>),          //for(a of b)
+    Break(Option<String>),                           //break / break label
+    Continue(Option<String>),                        //continue / continue label
+    Labeled(String, Box<Expr>),                      //label: statement
+    Function(Arc<Function>),                         //function
+    Class(Arc<Class>),                               //class
     While(Box<Expr>, Box<Expr>),
     DoWhile(Box<Expr>, Box<Expr>), //存放顺序与while一致
     Switch(Box<Expr>, Vec<Expr>),
     SwitchCase(Box<Expr>, Vec<Expr>),
-    Object(HashMap<String, Expr>), //json
-    Ref(String),                   //let a=1; let b ={a};//b.a=a=1;
-    Array(Vec<Expr>),              //array
+    Object(Vec<(PropertyName, ObjectMember)>), //json
+    Ref(String),                               //let a=1; let b ={a};//b.a=a=1;
+    Array(Vec<Expr>),                          //array
+    Import(Box<ImportDeclaration>),            //import a, { b as c } from "m"
+    Export(Box<ExportDeclaration>),            //export
+    ImportCall(Box<Expr>),                     //import("m")
+    /// tag`a${b}c` 标签模板
+    TaggedTemplate(Box<Expr>, Arc<TemplateStrings>, Vec<Expr>),
+}
+
+/// 标签模板的字符串部分, 每个调用位置共用同一个strings数组
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateStrings {
+    pub cooked: Vec<Option<JSString>>, //转义无效时为undefined
+    pub raw: Vec<JSString>,
+}
+
+/// import 声明, 只能出现在模块顶层
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDeclaration {
+    pub specifier: String,
+    pub bindings: Vec<ImportBinding>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportBinding {
+    Named(String, String), //(导入名, 本地名)  import a 的导入名为 default
+    Namespace(String),     //import * as ns
+}
+
+/// export 声明, 只能出现在模块顶层
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportDeclaration {
+    Declaration(Expr),                            //export var/let/const/function/class
+    Default(Expr),                                //export default 表达式/function/class
+    Named(Vec<(String, String)>, Option<String>), //export { a as b } from "m"  (本地名或导入名, 导出名)
+    All(Option<String>, String),                  //export * from "m" / export * as ns from "m"
+}
+
+/// function/方法/getter/setter/构造函数 共用
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: Option<String>,
+    pub params: Vec<Expr>, // Identifier / Assignment(默认值) / Spread(剩余参数)
+    pub body: Vec<Expr>,
+    pub is_generator: bool, //function*
+    pub is_async: bool,     //async function
+    pub strict: bool,       //严格模式, 含 "use strict" 或在class/严格模式代码中
 }
 
-impl Expr {
-    pub fn to_raw(&self) -> String {
-        match self {
-            Expr::Identifier(t) => t.to_string(),
-            Expr::Literal(t) => t.to_string(),
-            _ => format!("<{:?}>", self),
-        }
-    }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Class {
+    pub name: Option<String>,
+    pub extends: Option<Box<Expr>>,
+    pub constructor: Option<Arc<Function>>,
+    pub members: Vec<ClassMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassMember {
+    pub is_static: bool,
+    pub key: PropertyName,
+    pub kind: ClassMemberKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassMemberKind {
+    Method(Arc<Function>),
+    Getter(Arc<Function>),
+    Setter(Arc<Function>),
+    Field(Option<Expr>),
+    StaticBlock(Vec<Expr>),
+}
+
+/// 属性名 a / #a / [a]
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyName {
+    Name(String),
+    Private(String),
+    Computed(Box<Expr>),
+}
+
+/// json 成员
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectMember {
+    Value(Expr),
+    Getter(Arc<Function>),
+    Setter(Arc<Function>),
+    Spread(Expr),
+    Proto(Expr), //__proto__: value, 设置原型而不是定义属性
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -61,14 +155,15 @@ pub enum Unary {
     Minus,
     /// ~
     BitNot,
-}
-#[derive(Debug, Clone, PartialEq)]
-pub enum Prefix {
-    Negate, // -expr
-    Abs,    // +a
-    Not,    // !
+    /// typeof
+    Typeof,
+    /// void
+    Void,
+    /// delete
+    Delete,
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Operator {
     Plus,
@@ -78,11 +173,12 @@ pub enum Operator {
     Modulo,
     Or,
     And,
-    Not,
     LShift,
     RShift,
     Equal,
     NE,
+    Congruent,
+    NotCongruent,
     GT,
     GTE,
     LT,
@@ -90,14 +186,12 @@ pub enum Operator {
     BitOr,
     BitXor,
     BitAnd,
-    INC,
-    DEC,
+    Instanceof,
+    In,
 
     ADD,
     SUB,
     MUL,
     DIV,
     MOD,
-    // In,
-    // Of,
 }
@@ -0,0 +1,74 @@
+/// fetch发出的请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: String, //大写, 默认GET
+    pub url: String,
+    pub headers: Vec<(String, String)>, //名称为小写
+    pub body: Option<String>,
+}
+
+/// 请求结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// 由外部提供的http实现, 测试中可以替换为内存中的模拟
+pub trait HttpClient: Send + Sync {
+    /// 发送请求, Err为网络错误 (非2xx状态不是错误)
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String>;
+}
+
+/// 基于reqwest的实现, 校验证书
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ReqwestClient {
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ReqwestClient {
+    pub fn new() -> Result<Self, String> {
+        let client = reqwest::blocking::Client::builder()
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(ReqwestClient { client })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HttpClient for ReqwestClient {
+    fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .map_err(|_| format!("'{}' is not a valid HTTP method", request.method))?;
+        let mut builder = self.client.request(method, &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = &request.body {
+            builder = builder.body(body.clone());
+        }
+        let response = builder.send().map_err(|e| e.to_string())?;
+        let status = response.status();
+        let url = response.url().to_string();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.as_str().to_string(), value)
+            })
+            .collect();
+        let body = response.text().map_err(|e| e.to_string())?;
+        Ok(HttpResponse {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or_default().to_string(),
+            url,
+            headers,
+            body,
+        })
+    }
+}
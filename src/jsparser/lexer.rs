@@ -13,10 +13,10 @@ pub struct Lexer {
     ch: Option<char>,                // 当前字符
     line: usize,                     // 当前行号
     column: usize,                   // 当前列号
+    braces: Vec<bool>,               // 未闭合的{, true为模板中的${
 }
 impl ILexer for Lexer {
-    fn new(input: String) -> Self
-    {
+    fn new(input: String) -> Self {
         let mut lexer = Lexer {
             input: input.clone(),
             chars: "".chars(), // 初始值
@@ -25,6 +25,7 @@ impl ILexer for Lexer {
             ch: None,
             line: 1,   // 初始行号为1
             column: 0, // 初始列号为0
+            braces: Vec::new(),
         };
         let input_static: &'static str = Box::leak(input.clone().into_boxed_str());
         lexer.chars = input_static.chars();
@@ -44,27 +45,24 @@ impl ILexer for Lexer {
                     if pc2 == Some('=') {
                         //===
                         self.read_char();
-                        self.read_char();
-                        return Token::new(
+                        Token::new(
                             TokenType::Punctuator(TokenPunctuator::Congruent),
                             self.line,
                             self.column,
-                        );
+                        )
                     } else {
-                        self.read_char();
-                        return Token::new(
+                        Token::new(
                             TokenType::Punctuator(TokenPunctuator::Equal),
                             self.line,
                             self.column,
-                        );
+                        )
                     }
                 } else {
-                    self.read_char();
-                    return Token::new(
+                    Token::new(
                         TokenType::Punctuator(TokenPunctuator::MOV),
                         self.line,
                         self.column,
-                    );
+                    )
                 }
             }
             Some('+') => {
@@ -181,16 +179,13 @@ impl ILexer for Lexer {
             }
             Some('%') => {
                 let pc = self.peek_char();
-                if pc == Some('/') {
-                    // //
-                    while let Some(ch) = self.ch {
-                        if ch == '\n' {
-                            self.read_char();
-                            break;
-                        }
-                        self.read_char();
-                    }
-                    return self.next_token();
+                if pc == Some('=') {
+                    self.read_char();
+                    Token::new(
+                        TokenType::Punctuator(TokenPunctuator::MOD),
+                        self.line,
+                        self.column,
+                    )
                 } else {
                     Token::new(
                         TokenType::Punctuator(TokenPunctuator::Modulo),
@@ -209,16 +204,25 @@ impl ILexer for Lexer {
                 self.line,
                 self.column,
             ),
-            Some('{') => Token::new(
-                TokenType::Punctuator(TokenPunctuator::LCParen),
-                self.line,
-                self.column,
-            ),
-            Some('}') => Token::new(
-                TokenType::Punctuator(TokenPunctuator::RCParen),
-                self.line,
-                self.column,
-            ),
+            Some('{') => {
+                self.braces.push(false);
+                Token::new(
+                    TokenType::Punctuator(TokenPunctuator::LCParen),
+                    self.line,
+                    self.column,
+                )
+            }
+            Some('}') => {
+                //${}的结束, 继续读取模板
+                if self.braces.pop() == Some(true) {
+                    return self.read_template(false);
+                }
+                Token::new(
+                    TokenType::Punctuator(TokenPunctuator::RCParen),
+                    self.line,
+                    self.column,
+                )
+            }
             Some('[') => Token::new(
                 TokenType::Punctuator(TokenPunctuator::LSParen),
                 self.line,
@@ -239,11 +243,50 @@ impl ILexer for Lexer {
                 self.line,
                 self.column,
             ),
-            Some('.') => Token::new(
-                TokenType::Punctuator(TokenPunctuator::Dot),
+            Some('.')
+                if self
+                    .chars
+                    .clone()
+                    .next()
+                    .is_some_and(|ch| ch.is_ascii_digit()) =>
+            {
+                //.5
+                let (num, line) = self.read_number();
+                return Token::new(TokenType::Number(num), line, self.column);
+            }
+            Some('.') => {
+                let mut chars = self.chars.clone();
+                if chars.next() == Some('.') && chars.next() == Some('.') {
+                    //...
+                    self.read_char();
+                    self.read_char();
+                    Token::new(
+                        TokenType::Punctuator(TokenPunctuator::Spread),
+                        self.line,
+                        self.column,
+                    )
+                } else {
+                    Token::new(
+                        TokenType::Punctuator(TokenPunctuator::Dot),
+                        self.line,
+                        self.column,
+                    )
+                }
+            }
+            Some('?') => Token::new(
+                TokenType::Punctuator(TokenPunctuator::Question),
                 self.line,
                 self.column,
             ),
+            Some('#') => {
+                //私有属性 #a
+                self.read_char();
+                if !matches!(self.ch, Some(ch) if ch == '$' || ch == '_' || ch.is_alphabetic()) {
+                    return Token::new(TokenType::Illegal, self.line, self.column);
+                }
+                let (ident, line) = self.read_identifier();
+                return Token::new(TokenType::PrivateName(ident), line, self.column);
+            }
             Some(',') => Token::new(
                 TokenType::Punctuator(TokenPunctuator::Comma),
                 self.line,
@@ -259,91 +302,35 @@ impl ILexer for Lexer {
                 self.line,
                 self.column,
             ),
-            Some('`') | Some('"') | Some('\'') => {
-                let p = self.ch.clone().unwrap();
-                let is_template = p == '`';
+            Some('`') => return self.read_template(true),
+            Some('"') | Some('\'') => {
+                let p = self.ch.unwrap();
                 self.read_char();
                 let mut result = String::new();
-                let mut v1: Vec<String> = Vec::new();
-                let mut v2: Vec<String> = Vec::new();
                 let line = self.line;
                 while let Some(ch) = self.ch {
                     if ch == '\\' {
                         let pc = self.peek_char();
+                        //保留转义的原始文本, 由parser处理
                         match pc {
                             Some(t) => {
-                                if t == p {
-                                    self.read_char();
-                                    self.read_char();
-                                    result.push(t);
-                                    continue;
-                                } else if t == '\n' {
-                                    self.read_char();
-                                    self.read_char();
-                                    continue;
-                                } else {
-                                }
-                            }
-                            None => return Token::new(TokenType::SyntaxError, line, self.column),
-                        }
-                    }
-                    if is_template {
-                        if ch == '$' {
-                            let pc = self.peek_char();
-                            if pc.is_none() {
-                                return Token::new(TokenType::SyntaxError, line, self.column);
-                            }
-                            if pc == Some('{') {
-                                let mut count = 0;
-                                self.read_char(); //$
-                                v1.push(result.clone());
-                                result.clear();
-                                loop {
-                                    if self.ch == Some('{') {
-                                        count += 1;
-                                        self.read_char();
-                                    } else if self.ch == Some('}') {
-                                        count -= 1;
-                                        self.read_char();
-                                        if count == 0 {
-                                            v2.push(result.clone());
-                                            result.clear();
-                                            break;
-                                        }
-                                    } else {
-                                        result.push(self.ch.unwrap());
-                                        self.read_char();
-                                    }
-                                }
+                                self.read_char();
+                                self.read_char();
+                                result.push('\\');
+                                result.push(t);
                                 continue;
-                            } else {
-                                break;
                             }
+                            None => return Token::new(TokenType::SyntaxError, line, self.column),
                         }
                     }
-                    if self.ch.is_none() {
-                        break;
-                    }
-                    if self.ch.unwrap() == p {
+                    if ch == p {
                         self.read_char();
                         break;
                     }
-                    result.push(self.ch.unwrap());
+                    result.push(ch);
                     self.read_char();
                 }
-                if is_template {
-                    if result.len() > 0 {
-                        v1.push(result.clone());
-                        result.clear();
-                    }
-                    return Token::new(TokenType::Template(v1, v2), line, self.column);
-                } else {
-                    return Token::new(
-                        TokenType::Literal(format!("{}", result)),
-                        line,
-                        self.column,
-                    );
-                }
+                return Token::new(TokenType::Literal(result), line, self.column);
             }
             Some('&') => {
                 let pc = self.peek_char();
@@ -383,11 +370,21 @@ impl ILexer for Lexer {
                 let pc = self.peek_char();
                 if pc == Some('=') {
                     self.read_char();
-                    Token::new(
-                        TokenType::Punctuator(TokenPunctuator::NE),
-                        self.line,
-                        self.column,
-                    )
+                    if self.peek_char() == Some('=') {
+                        // !==
+                        self.read_char();
+                        Token::new(
+                            TokenType::Punctuator(TokenPunctuator::NotCongruent),
+                            self.line,
+                            self.column,
+                        )
+                    } else {
+                        Token::new(
+                            TokenType::Punctuator(TokenPunctuator::NE),
+                            self.line,
+                            self.column,
+                        )
+                    }
                 } else {
                     Token::new(
                         TokenType::Punctuator(TokenPunctuator::Not),
@@ -396,127 +393,16 @@ impl ILexer for Lexer {
                     )
                 }
             }
-            Some(ch) if ch.is_digit(10) => {
+            Some(ch) if ch.is_ascii_digit() => {
                 let (num, line) = self.read_number();
-                return Token::new(TokenType::Literal(num.to_string()), line, self.column);
+                return Token::new(TokenType::Number(num), line, self.column);
             }
-            Some(ch) if ch.is_alphabetic() => {
+            Some(ch) if *ch == '$' || *ch == '_' || ch.is_alphabetic() => {
                 let (ident, line) = self.read_identifier();
-                match ident.as_str() {
-                    "let" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Let),
-                            line,
-                            self.column,
-                        );
-                    }
-                    "const" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Const),
-                            line,
-                            self.column,
-                        );
-                    }
-                    "var" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Var),
-                            line,
-                            self.column,
-                        );
-                    }
-                    "if" => {
-                        return Token::new(TokenType::Keyword(TokenKeyword::If), line, self.column)
-                    }
-                    "else" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Else),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "return" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Return),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "break" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Break),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "continue" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Continue),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "for" => {
-                        return Token::new(TokenType::Keyword(TokenKeyword::For), line, self.column)
-                    }
-                    "in" => {
-                        return Token::new(TokenType::Keyword(TokenKeyword::In), line, self.column)
-                    }
-                    "of" => {
-                        return Token::new(TokenType::Keyword(TokenKeyword::Of), line, self.column)
-                    }
-                    "delete" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Delete),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "do" => {
-                        return Token::new(TokenType::Keyword(TokenKeyword::Do), line, self.column)
-                    }
-                    "switch" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Swith),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "case" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Case),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "default" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Default),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "function" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::Function),
-                            line,
-                            self.column,
-                        )
-                    }
-                    "while" => {
-                        return Token::new(
-                            TokenType::Keyword(TokenKeyword::While),
-                            line,
-                            self.column,
-                        )
-                    }
-                    _ => {
-                        return Token::new(TokenType::Ident(ident), line, self.column);
-                    }
-                }
-            }
-            Some('$') | Some('_') => {
-                let (ident, line) = self.read_identifier();
-                Token::new(TokenType::Ident(ident), self.line, self.column)
+                return match TokenKeyword::from_raw(&ident) {
+                    Some(key) => Token::new(TokenType::Keyword(key), line, self.column),
+                    None => Token::new(TokenType::Ident(ident), line, self.column),
+                };
             }
             Some('>') => {
                 let pc = self.peek_char();
@@ -575,6 +461,52 @@ impl ILexer for Lexer {
     }
 }
 impl Lexer {
+    /// 模板的一段原始文本, 从`或}读到`或${, 换行统一为\n
+    /// head: 是否从`开始 (不是${}之后的部分)
+    fn read_template(&mut self, head: bool) -> Token {
+        let line = self.line;
+        self.read_char(); //` or }
+        let mut raw = String::new();
+        loop {
+            match self.ch {
+                None => return Token::new(TokenType::SyntaxError, line, self.column),
+                Some('`') => {
+                    self.read_char();
+                    return Token::new(TokenType::Template(raw, head, true), line, self.column);
+                }
+                Some('$') if self.peek_char() == Some('{') => {
+                    self.read_char();
+                    self.read_char();
+                    self.braces.push(true);
+                    return Token::new(TokenType::Template(raw, head, false), line, self.column);
+                }
+                Some('\\') => {
+                    raw.push('\\');
+                    self.read_char();
+                    match self.ch {
+                        None => return Token::new(TokenType::SyntaxError, line, self.column),
+                        Some('\r') => {}
+                        Some(ch) => {
+                            raw.push(ch);
+                            self.read_char();
+                        }
+                    }
+                }
+                Some('\r') => {
+                    raw.push('\n');
+                    self.read_char();
+                    if self.ch == Some('\n') {
+                        self.read_char();
+                    }
+                }
+                Some(ch) => {
+                    raw.push(ch);
+                    self.read_char();
+                }
+            }
+        }
+    }
+
     fn read_char(&mut self) -> bool {
         if let Some(ch) = self.chars.next() {
             self.ch = Some(ch);
@@ -594,7 +526,7 @@ impl Lexer {
         }
     }
 
-    fn peek_char(&mut self) -> Option<char> {
+    fn peek_char(&self) -> Option<char> {
         self.chars.clone().next()
     }
 
@@ -608,25 +540,88 @@ impl Lexer {
         }
     }
 
+    /// 数字字面量, 包括 0x1F / 0o17 / 0b11 / 1.5e-3 / .5 / 10n, 去掉分隔符 1_000
     fn read_number(&mut self) -> (String, usize) {
         let mut result = String::new();
         let line = self.line;
+        let is_digit = |ch: char, radix: u32| ch.is_digit(radix) || ch == '_';
+        if self.ch == Some('0') {
+            if let Some(prefix @ ('x' | 'X' | 'o' | 'O' | 'b' | 'B')) = self.peek_char() {
+                let radix = match prefix.to_ascii_lowercase() {
+                    'x' => 16,
+                    'o' => 8,
+                    _ => 2,
+                };
+                result.push('0');
+                result.push(prefix);
+                self.read_char();
+                self.read_char();
+                while let Some(ch) = self.ch.filter(|ch| is_digit(*ch, radix)) {
+                    if ch != '_' {
+                        result.push(ch);
+                    }
+                    self.read_char();
+                }
+                self.read_bigint_suffix(&mut result);
+                return (result, line);
+            }
+        }
+        let mut has_dot = false;
+        let mut has_exponent = false;
         while let Some(ch) = self.ch {
-            if ch.is_digit(10) || ch == '.' {
-                result.push(ch);
+            if is_digit(ch, 10) || (ch == '.' && !has_dot) {
+                has_dot |= ch == '.';
+                if ch != '_' {
+                    result.push(ch);
+                }
                 self.read_char();
             } else {
                 break;
             }
         }
+        //指数部分, e后面没有数字时不作为指数
+        if let Some(e @ ('e' | 'E')) = self.ch {
+            let mut chars = self.chars.clone();
+            let mut next = chars.next();
+            let sign = next.filter(|ch| *ch == '+' || *ch == '-');
+            if sign.is_some() {
+                next = chars.next();
+            }
+            if next.is_some_and(|ch| ch.is_ascii_digit()) {
+                has_exponent = true;
+                result.push(e);
+                self.read_char();
+                if let Some(sign) = sign {
+                    result.push(sign);
+                    self.read_char();
+                }
+                while let Some(ch) = self.ch.filter(|ch| is_digit(*ch, 10)) {
+                    if ch != '_' {
+                        result.push(ch);
+                    }
+                    self.read_char();
+                }
+            }
+        }
+        if !has_dot && !has_exponent {
+            self.read_bigint_suffix(&mut result);
+        }
         (result, line)
     }
 
+    /// BigInt字面量的后缀n, 保留在数字中由parser检查
+    fn read_bigint_suffix(&mut self, result: &mut String) {
+        if self.ch == Some('n') {
+            result.push('n');
+            self.read_char();
+        }
+    }
+
     fn read_identifier(&mut self) -> (String, usize) {
         let mut result = String::new();
         let line = self.line;
         while let Some(ch) = self.ch {
-            if ch == '$' || ch == '_' || ch.is_alphabetic() || ch.is_digit(10) {
+            if ch == '$' || ch == '_' || ch.is_alphabetic() || ch.is_ascii_digit() {
                 result.push(ch);
                 self.read_char();
             } else {
@@ -646,7 +641,7 @@ impl Lexer {
                 break;
             }
             if line != tok.line && line > 0 {
-                println!("");
+                println!();
             }
             line = tok.line;
             print!("{}", tok);
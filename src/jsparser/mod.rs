@@ -1,6 +1,11 @@
+pub mod builtins;
 pub mod expr;
+pub mod http;
 pub mod lexer;
+pub mod module;
+pub mod object;
 pub mod parser;
 pub mod program;
+pub mod string;
 pub mod token;
 pub mod utility;
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// 由外部提供的模块加载, 负责解析模块名及读取源码
+pub trait ModuleLoader: Send + Sync {
+    /// 解析模块名, referrer为引用方解析后的名称, 入口脚本为None
+    fn resolve(&self, specifier: &str, referrer: Option<&str>) -> Result<String, String>;
+    /// 读取resolve返回的模块的源码
+    fn load(&self, name: &str) -> Result<String, String>;
+}
+
+/// 非相对路径的模块名
+fn bare_specifier_error(specifier: &str) -> String {
+    format!(
+        "Failed to resolve module specifier \"{}\". Relative references must start with either \"/\", \"./\", or \"../\".",
+        specifier
+    )
+}
+
+fn is_path_specifier(specifier: &str) -> bool {
+    specifier.starts_with('/') || specifier.starts_with("./") || specifier.starts_with("../")
+}
+
+/// 从文件系统加载, 入口脚本中的相对路径基于root, 只能加载root目录中的文件
+pub struct FileSystemLoader {
+    root: PathBuf, //绝对路径
+}
+
+impl FileSystemLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let root = std::path::absolute(&root).unwrap_or(root);
+        FileSystemLoader {
+            root: normalize_components(&root),
+        }
+    }
+
+    fn outside_root_error(&self, name: &str) -> String {
+        format!(
+            "Cannot load module '{}' from outside of '{}'",
+            name,
+            self.root.display()
+        )
+    }
+}
+
+/// 按路径规则处理 . 和 .., 不要求文件存在
+fn normalize_components(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+impl ModuleLoader for FileSystemLoader {
+    fn resolve(&self, specifier: &str, referrer: Option<&str>) -> Result<String, String> {
+        if !is_path_specifier(specifier) {
+            return Err(bare_specifier_error(specifier));
+        }
+        let base = match referrer.and_then(|r| Path::new(r).parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => self.root.clone(),
+        };
+        let path = normalize_components(&base.join(specifier));
+        if !path.starts_with(&self.root) {
+            return Err(self.outside_root_error(specifier));
+        }
+        Ok(path.to_string_lossy().into_owned())
+    }
+
+    /// 读取前解析符号链接, 实际的文件也必须在root中
+    fn load(&self, name: &str) -> Result<String, String> {
+        let path = Path::new(name).canonicalize().map_err(|e| e.to_string())?;
+        let root = self.root.canonicalize().map_err(|e| e.to_string())?;
+        if !path.starts_with(&root) {
+            return Err(self.outside_root_error(name));
+        }
+        std::fs::read_to_string(path).map_err(|e| e.to_string())
+    }
+}
+
+/// 内存中的模块, 名称为 /a/b.js 形式的路径, 也可以注册 "lib" 这样的非路径名称
+#[derive(Default)]
+#[allow(dead_code)] //lib中未使用
+pub struct MemoryLoader {
+    modules: HashMap<String, String>,
+}
+
+#[allow(dead_code)] //lib中未使用
+impl MemoryLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 添加模块, 路径形式的名称会被规范化 "a.js" => "/a.js"
+    pub fn insert(&mut self, name: &str, source: &str) {
+        let name = if is_path_specifier(name) || name.contains('/') || name.ends_with(".js") {
+            normalize_path("/", name)
+        } else {
+            name.to_string()
+        };
+        self.modules.insert(name, source.to_string());
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn resolve(&self, specifier: &str, referrer: Option<&str>) -> Result<String, String> {
+        if !is_path_specifier(specifier) {
+            if self.modules.contains_key(specifier) {
+                return Ok(specifier.to_string());
+            }
+            return Err(bare_specifier_error(specifier));
+        }
+        let base = match referrer.and_then(|r| r.rsplit_once('/')) {
+            Some((dir, _)) => format!("{}/", dir),
+            None => "/".to_string(),
+        };
+        Ok(normalize_path(&base, specifier))
+    }
+
+    fn load(&self, name: &str) -> Result<String, String> {
+        self.modules
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Cannot find module '{}'", name))
+    }
+}
+
+/// 以 / 分隔的路径, base为目录
+fn normalize_path(base: &str, specifier: &str) -> String {
+    let full = if specifier.starts_with('/') {
+        specifier.to_string()
+    } else {
+        format!("{}{}", base, specifier)
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in full.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
@@ -0,0 +1,414 @@
+use super::builtins::{Collator, DateTimeFormat, NumberFormat};
+use super::expr::{Expr, Function};
+use super::program::{
+    BufferView, GeneratorState, JSType, MapData, Program, PromiseData, PromiseFunction, ProxyData,
+    TypedArrayKind,
+};
+use super::string::JSString;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// 堆中对象的下标
+pub type ObjectId = usize;
+/// 作用域下标
+pub type ScopeId = usize;
+
+/// 内置方法 (program, this, 参数)
+pub type NativeFn = fn(&mut Program, JSType, Vec<JSType>) -> Result<JSType, String>;
+/// 内置构造函数 (program, 参数, new.target)
+pub type NativeCtor = fn(&mut Program, Vec<JSType>, JSType) -> Result<JSType, String>;
+
+/// Symbol, id唯一, description只用于显示
+#[derive(Debug, Default)]
+pub struct JSSymbol {
+    pub id: usize,
+    pub description: Option<String>,
+}
+
+impl PartialEq for JSSymbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl Eq for JSSymbol {}
+impl std::hash::Hash for JSSymbol {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+impl std::fmt::Display for JSSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Symbol({})",
+            self.description.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// 属性名: 字符串或Symbol
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PropertyKey {
+    String(String),
+    Symbol(Arc<JSSymbol>),
+}
+
+impl PropertyKey {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyKey::String(s) => Some(s),
+            PropertyKey::Symbol(_) => None,
+        }
+    }
+    pub fn is(&self, key: &str) -> bool {
+        self.as_str() == Some(key)
+    }
+    /// 数组下标 "0" "12"
+    pub fn array_index(&self) -> Option<usize> {
+        self.as_str().and_then(super::program::array_index)
+    }
+    /// 作为js值传给函数, 如Proxy的trap
+    pub fn to_value(&self) -> JSType {
+        match self {
+            PropertyKey::String(s) => JSType::String(s.as_str().into()),
+            PropertyKey::Symbol(s) => JSType::Symbol(s.clone()),
+        }
+    }
+    /// 用作函数名, Symbol为 [description]
+    pub fn function_name(&self) -> String {
+        match self {
+            PropertyKey::String(s) => s.clone(),
+            PropertyKey::Symbol(s) => match &s.description {
+                Some(d) => format!("[{}]", d),
+                None => String::new(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for PropertyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyKey::String(s) => write!(f, "{}", s),
+            PropertyKey::Symbol(s) => write!(f, "{}", s),
+        }
+    }
+}
+impl From<&str> for PropertyKey {
+    fn from(value: &str) -> Self {
+        PropertyKey::String(value.to_string())
+    }
+}
+impl From<String> for PropertyKey {
+    fn from(value: String) -> Self {
+        PropertyKey::String(value)
+    }
+}
+impl From<&String> for PropertyKey {
+    fn from(value: &String) -> Self {
+        PropertyKey::String(value.clone())
+    }
+}
+impl From<&PropertyKey> for PropertyKey {
+    fn from(value: &PropertyKey) -> Self {
+        value.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyValue {
+    Data(JSType),
+    Accessor(JSType, JSType), //get set, 未定义时为Undefined
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub value: PropertyValue,
+    pub writable: bool,
+    pub enumerable: bool,
+    pub configurable: bool,
+}
+
+impl Property {
+    /// 普通属性 可写/可枚举/可配置
+    pub fn data(value: JSType) -> Self {
+        Property {
+            value: PropertyValue::Data(value),
+            writable: true,
+            enumerable: true,
+            configurable: true,
+        }
+    }
+    /// 不可枚举属性,内置方法及class方法
+    pub fn hidden(value: JSType) -> Self {
+        Property {
+            value: PropertyValue::Data(value),
+            writable: true,
+            enumerable: false,
+            configurable: true,
+        }
+    }
+    /// 只读属性 如 function.name
+    pub fn readonly(value: JSType) -> Self {
+        Property {
+            value: PropertyValue::Data(value),
+            writable: false,
+            enumerable: false,
+            configurable: true,
+        }
+    }
+    /// 数据属性的值, 访问器为undefined
+    pub fn data_value(&self) -> JSType {
+        match &self.value {
+            PropertyValue::Data(value) => value.clone(),
+            PropertyValue::Accessor(_, _) => JSType::Undefined,
+        }
+    }
+    pub fn accessor(get: JSType, set: JSType, enumerable: bool) -> Self {
+        Property {
+            value: PropertyValue::Accessor(get, set),
+            writable: false,
+            enumerable,
+            configurable: true,
+        }
+    }
+}
+
+/// 按插入顺序保存的属性表
+#[derive(Debug, Clone, Default)]
+pub struct PropertyMap {
+    keys: Vec<PropertyKey>,
+    values: HashMap<PropertyKey, Property>,
+}
+
+impl PropertyMap {
+    pub fn get(&self, key: &PropertyKey) -> Option<&Property> {
+        self.values.get(key)
+    }
+    /// 字符串属性名
+    pub fn get_str(&self, key: &str) -> Option<&Property> {
+        self.values.get(&PropertyKey::from(key))
+    }
+    pub fn get_mut(&mut self, key: &PropertyKey) -> Option<&mut Property> {
+        self.values.get_mut(key)
+    }
+    pub fn insert(&mut self, key: PropertyKey, prop: Property) {
+        if !self.values.contains_key(&key) {
+            self.keys.push(key.clone());
+        }
+        self.values.insert(key, prop);
+    }
+    pub fn remove(&mut self, key: &PropertyKey) -> Option<Property> {
+        let prop = self.values.remove(key)?;
+        self.keys.retain(|k| k != key);
+        Some(prop)
+    }
+    /// 插入顺序的key
+    pub fn keys(&self) -> &Vec<PropertyKey> {
+        &self.keys
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionKind {
+    Normal,
+    Method,                 //对象/class方法,不能new
+    ClassConstructor(bool), //bool:是否为派生类
+}
+
+/// class中需要在实例上初始化的成员
+#[derive(Debug, Clone)]
+pub enum ClassElement {
+    Field(FieldKey, Option<Expr>),
+    PrivateMethod(usize, Property),
+}
+
+#[derive(Debug, Clone)]
+pub enum FieldKey {
+    Name(PropertyKey),
+    Private(usize),
+}
+
+/// js函数
+#[derive(Debug, Clone)]
+pub struct Closure {
+    pub func: Arc<Function>,
+    pub scope: ScopeId,
+    pub kind: FunctionKind,
+    pub home_object: Option<ObjectId>,    //super查找的起点
+    pub elements: Arc<Vec<ClassElement>>, //class构造函数的实例字段
+}
+
+/// Function.prototype.bind 返回的函数
+#[derive(Debug, Clone)]
+pub struct BoundFunction {
+    pub target: ObjectId,
+    pub this: JSType,
+    pub args: Vec<JSType>,
+    pub constructor: bool, //目标函数是否可以new
+}
+
+/// 数组迭代器返回的内容
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IterationKind {
+    Keys,
+    Values,
+    Entries,
+}
+
+/// 内置迭代器的状态
+#[derive(Debug, Clone)]
+pub enum IteratorState {
+    Array(JSType, usize, IterationKind), //数组(或类数组)及下一个下标
+    String(JSString, usize),             //字符串及下一个码元的位置
+    Map(ObjectId, usize, IterationKind), //Map/Set及下一项的位置
+    AsyncFromSync(JSType, JSType),       //for await 包装的同步迭代器及其next方法
+    Done,
+}
+
+/// 模块导出的绑定
+#[derive(Debug, Clone)]
+pub enum ModuleBinding {
+    Variable(ScopeId, String), //模块作用域中的变量, 读取时为最新值
+    Value(JSType),             //export * as ns 的命名空间对象
+}
+
+#[derive(Clone)]
+pub enum ObjectKind {
+    Ordinary,
+    Array(Vec<Option<JSType>>, usize), //None为空位, usize为length, 超出list的元素在properties中
+    Function(Box<Closure>),
+    Native(&'static str, NativeFn, Option<NativeCtor>),
+    Host(String), //外部注册的全局方法
+    Bound(Box<BoundFunction>),
+    Iterator(Box<IteratorState>),
+    Generator(Option<Box<GeneratorState>>), //None: 正在执行
+    Promise(Box<PromiseData>),
+    PromiseFunction(Box<PromiseFunction>), //resolve/reject等内部函数
+    Response(Option<String>),              //fetch结果的body, 读取后为None
+    Headers(Vec<(String, String)>),
+    Namespace(Vec<(String, ModuleBinding)>), //模块命名空间, 按导出名排序
+    Error,
+    Primitive(JSType), //原始值的包装对象 new String("a")
+    Map(Box<MapData>),
+    Set(Box<MapData>),                  //值同时作为键
+    WeakMap(HashMap<ObjectId, JSType>), //键不可达时由回收器删除
+    WeakSet(HashSet<ObjectId>),
+    Date(f64), //时间值, 无效时为NaN
+    ArrayBuffer(Vec<u8>),
+    TypedArray(TypedArrayKind, BufferView),
+    DataView(BufferView),
+    Proxy(Box<ProxyData>),
+    NumberFormat(Box<NumberFormat>),
+    DateTimeFormat(Box<DateTimeFormat>),
+    Collator(Box<Collator>),
+}
+
+impl std::fmt::Debug for ObjectKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjectKind::Ordinary => write!(f, "Ordinary"),
+            ObjectKind::Array(_, len) => write!(f, "Array({})", len),
+            ObjectKind::Function(c) => write!(f, "Function({:?})", c.func.name),
+            ObjectKind::Native(name, _, _) => write!(f, "Native({})", name),
+            ObjectKind::Host(name) => write!(f, "Host({})", name),
+            ObjectKind::Bound(b) => write!(f, "Bound({})", b.target),
+            ObjectKind::Iterator(state) => write!(f, "Iterator({:?})", state),
+            ObjectKind::Generator(_) => write!(f, "Generator"),
+            ObjectKind::Promise(data) => write!(f, "Promise({:?})", data),
+            ObjectKind::PromiseFunction(func) => write!(f, "PromiseFunction({:?})", func),
+            ObjectKind::Response(_) => write!(f, "Response"),
+            ObjectKind::Headers(list) => write!(f, "Headers({})", list.len()),
+            ObjectKind::Namespace(list) => write!(f, "Namespace({})", list.len()),
+            ObjectKind::Error => write!(f, "Error"),
+            ObjectKind::Primitive(value) => write!(f, "Primitive({:?})", value),
+            ObjectKind::Map(data) => write!(f, "Map({})", data.len()),
+            ObjectKind::Set(data) => write!(f, "Set({})", data.len()),
+            ObjectKind::WeakMap(map) => write!(f, "WeakMap({})", map.len()),
+            ObjectKind::WeakSet(set) => write!(f, "WeakSet({})", set.len()),
+            ObjectKind::Date(t) => write!(f, "Date({})", t),
+            ObjectKind::ArrayBuffer(bytes) => write!(f, "ArrayBuffer({})", bytes.len()),
+            ObjectKind::TypedArray(kind, view) => write!(f, "{}({})", kind.name(), view.length),
+            ObjectKind::DataView(view) => write!(f, "DataView({})", view.length),
+            ObjectKind::Proxy(data) => write!(f, "Proxy({:?})", data.handler),
+            ObjectKind::NumberFormat(_) => write!(f, "NumberFormat"),
+            ObjectKind::DateTimeFormat(_) => write!(f, "DateTimeFormat"),
+            ObjectKind::Collator(_) => write!(f, "Collator"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JSObject {
+    pub proto: Option<ObjectId>,
+    pub properties: PropertyMap,
+    pub private: Vec<(usize, Property)>, //class私有属性
+    pub kind: ObjectKind,
+    pub extensible: bool,
+}
+
+impl JSObject {
+    pub fn new(kind: ObjectKind, proto: Option<ObjectId>) -> Self {
+        JSObject {
+            proto,
+            properties: PropertyMap::default(),
+            private: Vec::new(),
+            kind,
+            extensible: true,
+        }
+    }
+    pub fn is_callable(&self) -> bool {
+        match &self.kind {
+            ObjectKind::Function(_)
+            | ObjectKind::Native(_, _, _)
+            | ObjectKind::Host(_)
+            | ObjectKind::Bound(_)
+            | ObjectKind::PromiseFunction(_) => true,
+            ObjectKind::Proxy(data) => data.callable,
+            _ => false,
+        }
+    }
+    pub fn is_constructor(&self) -> bool {
+        match &self.kind {
+            ObjectKind::Function(c) => {
+                c.kind != FunctionKind::Method && !c.func.is_generator && !c.func.is_async
+            }
+            ObjectKind::Bound(b) => b.constructor,
+            ObjectKind::Native(_, _, ctor) => ctor.is_some(),
+            ObjectKind::Proxy(data) => data.constructor,
+            _ => false,
+        }
+    }
+    pub fn get_private(&self, id: usize) -> Option<&Property> {
+        self.private.iter().find(|(k, _)| *k == id).map(|(_, p)| p)
+    }
+}
+
+/// 函数作用域中保存的this等信息
+#[derive(Debug, Clone)]
+pub struct FunctionFrame {
+    pub this: Option<JSType>, //None:派生类构造函数中调用super()之前
+    pub func: Option<ObjectId>,
+    pub new_target: JSType,
+    pub home_object: Option<ObjectId>,
+    pub strict: bool, //是否为严格模式代码
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    pub parent: Option<ScopeId>,
+    pub vars: HashMap<String, (super::expr::Variable, JSType)>,
+    pub function: Option<FunctionFrame>,
+    pub private_names: HashMap<String, usize>, //class作用域中的私有属性名
+    pub imports: HashMap<String, (ScopeId, String)>, //import的绑定: 导出模块的作用域及变量名
+    pub captured: bool,                        //被闭包引用,离开时不能复用
+}
+
+impl Scope {
+    pub fn new(parent: Option<ScopeId>) -> Self {
+        Scope {
+            parent,
+            ..Default::default()
+        }
+    }
+}
@@ -1,9 +1,16 @@
-use std::collections::HashMap;
+use std::sync::Arc;
+
+use num_bigint::BigInt;
 
 use super::{
-    expr::{Expr, Operator, Unary, Variable},
+    expr::{
+        Class, ClassMember, ClassMemberKind, ExportDeclaration, Expr, Function, ImportBinding,
+        ImportDeclaration, ObjectMember, Operator, PropertyName, TemplateStrings, Unary, Variable,
+        ASYNC_ITERATOR_OF,
+    },
     lexer::ILexer,
-    program::Program,
+    program::{digits_to_number, string_to_bigint, string_to_number, Program},
+    string::{unescape, JSString},
     token::{Token, TokenKeyword, TokenPunctuator, TokenType},
 };
 
@@ -11,1075 +18,1828 @@ pub struct Parser<T: ILexer> {
     lexer: T,
     current_token: Token,
     peek_token: Token,
-    allow_fn_name_empty: bool, //是否允许方法名为空
-    allow_return: bool,        //是否允许返回return
-    allow_break: bool,         //是否允许break
-    allow_continue: bool,      //是否允许continue
-    gen_json: bool,            //生成json,默认为block
+    last_line: usize,                 //上一个token所在行,用于自动插入分号
+    allow_return: bool,               //是否允许返回return
+    allow_break: bool,                //是否允许break
+    allow_continue: bool,             //是否允许continue
+    allow_super_call: bool,           //是否允许super() 派生类的构造函数
+    allow_super_property: bool,       //是否允许super.a 方法内
+    allow_in: bool,                   //是否允许in运算符, for初始化部分不允许
+    labels: Vec<(String, bool)>,      //当前可用的label及是否为循环语句的label
+    in_generator: bool,               //是否在生成器函数内, yield为关键字
+    in_async: bool,                   //是否在async函数内或模块顶层, await为关键字
+    private_names: Vec<PrivateScope>, //class中声明及引用的私有属性
+    strict: bool,                     //是否为严格模式代码
+}
+
+/// 严格模式下不能作为标识符的保留字
+const STRICT_RESERVED_WORDS: [&str; 9] = [
+    "implements",
+    "interface",
+    "let",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "static",
+    "yield",
+];
+
+/// 每层class的私有属性,class结束时检查引用是否都已声明
+#[derive(Default)]
+struct PrivateScope {
+    declared: Vec<String>,
+    referenced: Vec<String>,
 }
 
 impl<T: ILexer> Parser<T> {
     pub fn new(lexer: T) -> Self {
         let mut parser = Parser {
-            lexer: lexer,
+            lexer,
             current_token: Token::new(TokenType::EOF, 0, 0),
             peek_token: Token::new(TokenType::EOF, 0, 0),
-            allow_fn_name_empty: false,
+            last_line: 0,
             allow_return: false,
             allow_break: false,
             allow_continue: false,
-            gen_json: false,
+            allow_super_call: false,
+            allow_super_property: false,
+            allow_in: true,
+            labels: Vec::new(),
+            in_generator: false,
+            in_async: false,
+            private_names: Vec::new(),
+            strict: false,
         };
         parser.next_token();
         parser.next_token();
         parser
     }
     pub fn parse_program(&mut self) -> Result<Program, String> {
-        let statements = self.filter_statement(0, true)?;
-        Ok(Program::new(statements))
+        let statements = self.parse_script()?;
+        Ok(Program::new(statements, self.strict))
     }
 
-    fn checked_base(&mut self) -> Result<Expr, String> {
-        match &self.current_token.typ {
-            TokenType::Ident(t) => {
-                if self.peek_token.is_ptor(TokenPunctuator::LParen)
-                    || self.peek_token.is_ptor(TokenPunctuator::Dot)
-                    || self.peek_token.is_ptor(TokenPunctuator::LSParen)
-                {
-                    let expr = self.parse_call_or_member(None)?;
-                    // dbg!(&expr);
-                    Ok(expr)
-                } else {
-                    let expr = Expr::Identifier(t.clone());
-                    self.next_token();
-                    Ok(expr)
-                }
-            }
-            TokenType::Literal(t) => {
-                let expr = Expr::Literal(t.trim_matches('"').to_string());
-                self.next_token();
-                Ok(expr)
-            }
-            TokenType::Punctuator(t) => {
-                if t.is_precedence() {
-                    // let token = self.next_token();
-                    // return self.checked_base(&token.typ);
-                    panic!("")
-                }
-                if matches!(t, TokenPunctuator::LParen) {
-                    self.next_token(); //(
-                    let expr = self.parse(true)?;
-                    self.next_token(); //)
-                    return Ok(expr);
-                }
-                if matches!(t, TokenPunctuator::LSParen) {
-                    self.next_token(); //[
-                    let expr = self.parse(true)?;
-                    self.next_token(); //]
-                    return Ok(expr);
-                }
-                if matches!(t, TokenPunctuator::Dot) {
-                    // self.next_token(); //[
-                    // let expr = self.parse(true)?;
-                    // self.next_token(); //]
-                    // return Ok(expr);
-                    panic!("")
-                }
-                dbg!(&t);
-                return Err(self.err("未知解析"));
-            }
-            _ => return Err(self.err("未知解析")),
-        }
+    /// 解析脚本源码, 开头有 "use strict" 时为严格模式
+    pub fn parse_script(&mut self) -> Result<Vec<Expr>, String> {
+        self.filter_statement()
     }
 
-    fn parser_infix(&mut self, mut left: Expr, precedence: Precedence) -> Result<Expr, String> {
-        if left == Expr::Empty {
-            left = self.checked_base()?;
-        }
-        while precedence < self.get_precedence(self.current_token.typ.clone()) {
-            let _op = self.next_token();
-            let op = self.get_operator(&_op);
-
-            let right = self.checked_base()?;
-            match &left {
-                Expr::Identifier(_)
-                | Expr::Literal(_)
-                | Expr::Template(_,_)
-                | Expr::Unary(_, _)
-                // | Expr::Update(_, _, _)
-                | Expr::Call(_, _)
-                 => {
-                    left = Expr::Infix(Box::new(left), op, Box::new(right));
-                }
-                Expr::Infix(left2, op2, right2) => {
-                    if self.get_precedence_by_operator(&op2.clone())
-                        < self.get_precedence(_op.typ.clone())
-                    {
-                        left = Expr::Infix(
-                            left2.clone(),
-                            op2.clone(),
-                            Box::new(Expr::Infix(right2.clone(), op, Box::new(right))),
-                        );
-                    } else {
-                        left = Expr::Infix(Box::new(left), op, Box::new(right));
-                    }
-                }
-                _ => {
-                    dbg!(&left);
-                    panic!()
-                }
-            }
-        }
-        Ok(left)
+    /// 解析模块源码, 模块总是严格模式, 顶层可以使用await
+    pub fn parse_module(&mut self) -> Result<Vec<Expr>, String> {
+        self.strict = true;
+        self.in_async = true;
+        self.filter_statement()
     }
 
-    fn checked_paren(&mut self, typ: &TokenType) -> Result<bool, String> {
-        match typ {
-            TokenType::Punctuator(ptor) => {
-                if matches!(ptor, TokenPunctuator::RParen) {
-                    self.next_token();
-                    return Ok(true);
-                } else if matches!(ptor, TokenPunctuator::RCParen) {
-                    self.next_token();
-                    return Ok(true);
-                } else if matches!(ptor, TokenPunctuator::RSParen) {
-                    self.next_token();
-                    return Ok(true);
-                }
+    fn filter_statement(&mut self) -> Result<Vec<Expr>, String> {
+        let mut statements: Vec<Expr> = Vec::new();
+        let mut prologue = true;
+        while self.current_token.typ != TokenType::EOF {
+            if prologue {
+                self.check_directive(&mut prologue);
+            }
+            //import/export 只能出现在顶层, 入口脚本出现import/export时按模块执行, 之后的顶层await为关键字
+            let expr = if self.is_import_declaration() {
+                self.in_async = true;
+                self.parse_import_slot()?
+            } else if self.current_token.is_ident() && self.current_token.raw == "export" {
+                self.in_async = true;
+                self.parse_export_slot()?
+            } else {
+                self.parse()?
+            };
+            if expr != Expr::Empty {
+                statements.push(expr);
             }
-            _ => return Ok(false),
         }
-
-        return Ok(false);
+        Ok(statements)
     }
 
-    fn parse_base_typ(&mut self, token: &Token) -> Result<Expr, String> {
-        if token.is_unary() {
-            let unary = self.parse_unary().unwrap();
-            self.next_token();
-            let expr = self.parse(true).unwrap();
-            return Ok(Expr::Unary(unary, Box::new(expr)));
+    /// 解析入口(语句)
+    fn parse(&mut self) -> Result<Expr, String> {
+        if self.current_token.is_ident() && self.peek_token.is_ptor(TokenPunctuator::Colon) {
+            return self.parse_labeled_slot();
         }
-        if token.is_ident() {
-            return Ok(Expr::Identifier(token.raw.clone()));
-        } else if token.is_literal() {
-            if token.raw.starts_with('"') | token.raw.starts_with('\'') {
-                return Ok(Expr::Literal(token.raw.clone()));
-            } else {
-                return Err("".into());
-                //return Ok(Expr::Literal(token.raw.clone(), token.raw.clone()));
-            }
-        } else {
-            Ok(Expr::Empty)
+        if self.is_async_function() {
+            self.next_token(); //async
+            return self.parse_function_slot(true, true);
         }
-    }
-    /// 解析入口
-    fn parse(&mut self, is_skip_semicolon: bool) -> Result<Expr, String> {
-        // let is_skip_semicolon = true;
-        let skip_semicolon = |p: &mut Self| {
-            if p.current_token.is_ptor(TokenPunctuator::Semicolon) {
-                if is_skip_semicolon {
-                    p.next_token();
-                }
-                return true;
-            }
-            false
-        };
-        _ = match &self.current_token.typ {
-            TokenType::Illegal => Err(self.err(&format!("{}", self.current_token.typ.to_raw()))),
-            TokenType::SyntaxError => {
-                Err(self.err(&format!("{}", self.current_token.typ.to_raw())))
+        if self.is_import_declaration() {
+            return Err(self.err("Cannot use import statement outside a module"));
+        }
+        if self.current_token.is_ident() && self.current_token.raw == "export" {
+            return Err(self.err("Unexpected token 'export'"));
+        }
+        if self.strict
+            && self.is_contextual("with")
+            && self.peek_token.is_ptor(TokenPunctuator::LParen)
+        {
+            return Err(self.err("Strict mode code may not include a with statement"));
+        }
+        match &self.current_token.typ {
+            TokenType::Illegal | TokenType::SyntaxError => {
+                Err(self.err(&self.current_token.typ.to_raw()))
             }
             TokenType::EOF => Ok(Expr::Empty),
-            TokenType::Literal(t) => {
-                let expr = self.parser_infix(Expr::Empty, Precedence::Lowest)?;
-                skip_semicolon(self);
-                return Ok(expr);
+            TokenType::Punctuator(TokenPunctuator::LCParen) => self.parse_body_slot(false),
+            TokenType::Punctuator(TokenPunctuator::Semicolon) => {
+                self.next_token();
+                Ok(Expr::Empty)
             }
-            TokenType::Ident(t) => {
-                let ident = t.clone();
-                if self.peek_token.is_eof(true) {
-                    self.next_token();
-                    self.next_token();
-                    return Ok(Expr::Identifier(ident.clone()));
-                }
-                if self.peek_token.checked_keyword() {
-                    if self.current_token.line == self.peek_token.line {
-                        return Err(self.err("Unexpected token"));
-                    }
-                    self.next_token();
-                    return Ok(Expr::Identifier(ident.clone()));
-                }
-                if self.peek_token.is_ptor(TokenPunctuator::Comma) {
-                    self.next_token();
-                    return Ok(Expr::Identifier(ident.clone()));
-                }
-                if self.peek_token.is_ptor(TokenPunctuator::MOV) {
-                    //a=<base_Expr>
-                    let mut gen_json = false;
-                    if !self.gen_json {
-                        gen_json = true;
-                        self.gen_json = true;
-                    }
-                    let ident = self.next_token();
-                    self.next_token();
-                    if self.current_token.is_eof(true) {
-                        return Err(self.err("Unexpected token"));
-                    }
-                    let mut v = Vec::new();
-                    let expr = self.parse(is_skip_semicolon)?;
-                    v.push((ident.raw.clone(), expr));
-                    loop {
-                        if self.current_token.is_ptor(TokenPunctuator::Comma) {
-                            self.next_token();
-                        } else {
-                            break;
-                        }
-                        let expr = self.parse(is_skip_semicolon)?;
-                        if let Expr::Assignment(t) = expr {
-                            for i in t {
-                                v.push(i);
-                            }
-                        } else if let Expr::Identifier(t) = expr {
-                            v.push((t, Expr::Empty));
-                        }
-                    }
-                    if gen_json {
-                        self.gen_json = false;
-                    }
-                    return Ok(Expr::Assignment(v));
-                } else if self.peek_token.is_complex() {
-                    //<base_Expr> 符合: 'a+' , 'a[' , 'a(' , 'a.'
-                    let expr = self.parser_infix(Expr::Empty, Precedence::Lowest)?;
-                    skip_semicolon(self);
-                    return Ok(expr);
-                } else if self.peek_token.is_update() {
-                    //a++ a--
-                    let p = if self.peek_token.is_ptor(TokenPunctuator::INC) {
-                        format!("++")
-                    } else {
-                        format!("--")
-                    };
-                    self.next_token();
-                    self.next_token();
-                    let expr = Expr::Update(Box::new(Expr::Identifier(ident.clone())), p, false);
-                    skip_semicolon(self);
-                    return Ok(expr);
-                } else if self.peek_token.is_ptor(TokenPunctuator::Colon) {
-                    let expr = self.parse_base_typ(&self.current_token.clone())?;
-                    self.next_token();
-                    return Ok(expr);
-                }
-                let cur = self.current_token.clone();
-                let chk = self.checked_paren(&self.peek_token.typ.clone())?;
-                if chk {
-                    let expr = self.parse_base_typ(&cur)?;
-                    return Ok(expr);
-                }
-                panic!()
-            }
-            TokenType::Punctuator(t) => {
-                let cur = self.current_token.clone();
-                if matches!(t, TokenPunctuator::LCParen) {
-                    if self.gen_json {
-                        let expr = self.parse_json_slot()?;
-                        skip_semicolon(self);
-                        return Ok(expr);
-                    } else {
-                        let body = self.parse_body_slot(false)?;
-                        return Ok(body);
-                    }
-                }
-                if matches!(t, TokenPunctuator::LSParen) {
-                    //array
-                    let expr = self.parse_array_slot()?;
-                    skip_semicolon(self);
-                    return Ok(expr);
-                }
-                if matches!(t, TokenPunctuator::Semicolon) {
-                    if self.peek_token.is_eof(false) {
-                        self.next_token();
-                        return Ok(Expr::Empty);
-                    }
-                    let expr = self.parse_base_typ(&cur)?;
-                    return Ok(expr);
-                }
-                if matches!(t, TokenPunctuator::Comma) {
-                    let expr = self.parse_base_typ(&cur)?;
-                    return Ok(expr);
-                }
-                if matches!(t, TokenPunctuator::INC | TokenPunctuator::DEC) {
-                    //++a
-                    let p = if *t == TokenPunctuator::INC {
-                        format!("++")
-                    } else {
-                        format!("--")
-                    };
-                    if self.peek_token.is_ident() {
-                        self.next_token();
-                        let ident = self.checked_base()?;
-                        let expr = Expr::Update(Box::new(ident), p, true);
-                        if self.current_token.is_complex() {
-                            // let result = self.parser_infix(expr.clone(), Precedence::Lowest)?;
-                            // skip_semicolon(self);
-                            // return Ok(result);
-                            return Err(self.err("暂不支持未定义行为"));
-                        }
-                        skip_semicolon(self);
-                        return Ok(expr);
-                    }
-                }
-                if cur.is_unary() {
-                    let unary = self.parse_unary()?;
-                    self.next_token();
-                    let expr = self.parse(is_skip_semicolon)?;
-                    return Ok(Expr::Unary(unary, Box::new(expr)));
-                }
-                let chk = self.checked_paren(&self.current_token.typ.clone())?;
-                if chk {
-                    let expr = self.parse_base_typ(&cur)?;
-                    return Ok(expr);
+            TokenType::Keyword(t) => match t {
+                TokenKeyword::Let | TokenKeyword::Var | TokenKeyword::Const => {
+                    let expr = self.parse_variable_slot()?;
+                    self.skip_semicolon()?;
+                    Ok(expr)
                 }
-                dbg!(&cur);
-                panic!();
-            }
-            TokenType::Keyword(t) => {
-                //let a = <Expr>;
-                if matches!(
-                    t,
-                    TokenKeyword::Let | TokenKeyword::Var | TokenKeyword::Const
-                ) {
-                    let token = self.next_token();
-                    if !self.current_token.is_ident() {
-                        return Err(self.err("Unexpected end of input"));
-                    }
-                    let key = if token.raw == "var" {
-                        Variable::Var
-                    } else if token.raw == "let" {
-                        Variable::Let
-                    } else {
-                        Variable::Const
-                    };
-
-                    let mut v = Vec::new();
-                    self.allow_fn_name_empty = true;
-                    loop {
-                        let expr = self.parse(is_skip_semicolon)?;
-                        if let Expr::Assignment(ass) = expr {
-                            for i in ass {
-                                v.push((key.clone(), i.0, i.1));
-                            }
-                        }
-                        if self.current_token.is_ptor(TokenPunctuator::Comma) {
-                            self.next_token(); // ,
-                        } else {
-                            break;
-                        }
-                    }
-                    self.allow_fn_name_empty = false;
-                    skip_semicolon(self);
-                    return Ok(Expr::Variable(v));
-                } else if matches!(t, TokenKeyword::If) {
-                    let expr = self.parse_if_slot();
-                    skip_semicolon(self);
-                    return expr;
-                } else if matches!(t, TokenKeyword::Else) {
-                    return Err(self.err("Unexpected token else"));
-                } else if matches!(t, TokenKeyword::Swith) {
-                    let expr = self.parse_switch_slot();
-                    skip_semicolon(self);
-                    return expr;
-                } else if matches!(t, TokenKeyword::For) {
-                    let expr = self.parse_for_slot();
-                    skip_semicolon(self);
-                    return expr;
-                } else if matches!(t, TokenKeyword::Break) {
-                    if !self.allow_break {
+                TokenKeyword::If => self.parse_if_slot(),
+                TokenKeyword::Else => Err(self.err("Unexpected token else")),
+                TokenKeyword::Swith => self.parse_switch_slot(),
+                TokenKeyword::For => self.parse_for_slot(),
+                TokenKeyword::While => self.parse_while_slot(),
+                TokenKeyword::Do => self.parse_do_while_slot(),
+                TokenKeyword::Function => self.parse_function_slot(true, false),
+                TokenKeyword::Class => self.parse_class_slot(true),
+                TokenKeyword::Break => {
+                    self.next_token(); //break
+                    let label = self.parse_jump_label(false)?;
+                    if label.is_none() && !self.allow_break {
                         return Err(self.err("Illegal break statement"));
                     }
-                    self.next_token();
-                    skip_semicolon(self);
-                    return Ok(Expr::Break);
-                } else if matches!(t, TokenKeyword::Continue) {
-                    if !self.allow_continue {
+                    self.skip_semicolon()?;
+                    Ok(Expr::Break(label))
+                }
+                TokenKeyword::Continue => {
+                    self.next_token(); //continue
+                    let label = self.parse_jump_label(true)?;
+                    if label.is_none() && !self.allow_continue {
                         return Err(self.err("Illegal continue statement"));
                     }
-                    self.next_token();
-                    skip_semicolon(self);
-                    return Ok(Expr::Continue);
-                } else if matches!(t, TokenKeyword::Return) {
+                    self.skip_semicolon()?;
+                    Ok(Expr::Continue(label))
+                }
+                TokenKeyword::Return => {
                     if !self.allow_return {
                         return Err(self.err("Illegal return statement"));
                     }
-                    self.allow_fn_name_empty = true;
-                    self.next_token();
-                    if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-                        self.next_token();
-                        skip_semicolon(self);
+                    self.next_token(); //return
+                    if self.is_statement_end() {
+                        self.skip_semicolon()?;
                         return Ok(Expr::Return(Box::new(Expr::Empty)));
-                    } else {
-                        let expr = self.parse(is_skip_semicolon)?;
-                        if matches!(
-                            expr,
-                            Expr::Infix(_, _, _)
-                                | Expr::Call(_, _)
-                                | Expr::Function(_, _, _)
-                                | Expr::Identifier(_)
-                                | Expr::Assignment(_)
-                                | Expr::Literal(_)
-                        ) {
-                            self.allow_fn_name_empty = false;
-                            skip_semicolon(self);
-                            skip_semicolon(self);
-                            return Ok(Expr::Return(Box::new(expr)));
-                        }
-                        dbg!(&expr);
-                        return Err(self.err("Unexpected token"));
                     }
-                } else if matches!(t, TokenKeyword::Function) {
-                    let expr = self.parse_function_slot();
-                    skip_semicolon(self);
-                    return expr;
-                } else if matches!(t, TokenKeyword::While) {
-                    let expr = self.parse_while_slot();
-                    skip_semicolon(self);
-                    return expr;
-                } else if matches!(t, TokenKeyword::Do) {
-                    let expr = self.parse_do_while_slot();
-                    skip_semicolon(self);
-                    return expr;
+                    let expr = self.parse_expression()?;
+                    self.skip_semicolon()?;
+                    Ok(Expr::Return(Box::new(expr)))
                 }
-                todo!("{:?}", t);
-            }
-            TokenType::Template(vec, vec2) => {
-                let mut expr_vec = Vec::new();
-                for n in vec2 {
-                    let t = T::new(n.to_string());
-                    let mut parser = Self::new(t);
-                    let expr = parser.parse(false)?;
-                    expr_vec.push(expr);
+                _ => {
+                    let expr = self.parse_expression()?;
+                    self.skip_semicolon()?;
+                    Ok(expr)
                 }
-                let e = Expr::Template(vec.clone(), expr_vec);
-                self.next_token();
-                return Ok(e);
+            },
+            _ => {
+                let expr = self.parse_expression()?;
+                self.skip_semicolon()?;
+                Ok(expr)
             }
-        };
-        Err(self.err("未知解析"))
+        }
     }
 
-    fn filter_statement(
-        &mut self,
-        count: usize,
-        is_skip_semicolon: bool,
-    ) -> Result<Vec<Expr>, String> {
-        let mut statements: Vec<Expr> = Vec::new();
-        while self.current_token.typ != TokenType::EOF {
-            let result = self.parse(true);
-            match result {
-                Ok(expr) => match &expr {
-                    // Expr::Return(_) | Expr::Break | Expr::Continue => {
-                    //     return Err(self.err("Unexpected token"));
-                    // }
-                    _ => {
-                        // dbg!(&expr);
-                        statements.push(expr.clone());
-                    }
-                },
-                Err(msg) => return Err(msg),
+    /// a: b: for(...) 同一语句可以有多个label
+    fn parse_labeled_slot(&mut self) -> Result<Expr, String> {
+        let mut names = Vec::new();
+        while self.current_token.is_ident() && self.peek_token.is_ptor(TokenPunctuator::Colon) {
+            let name = self.current_token.raw.clone();
+            self.check_identifier(&name)?;
+            if names.contains(&name) || self.labels.iter().any(|(l, _)| *l == name) {
+                return Err(self.err(&format!("Label '{}' has already been declared", name)));
             }
+            self.next_token(); //label
+            self.next_token(); //:
+            names.push(name);
         }
-        Ok(statements)
+        //只有循环语句的label可以被continue
+        let is_loop = self.current_token.is_keyword(TokenKeyword::For)
+            || self.current_token.is_keyword(TokenKeyword::While)
+            || self.current_token.is_keyword(TokenKeyword::Do);
+        let count = self.labels.len();
+        self.labels
+            .extend(names.iter().map(|name| (name.clone(), is_loop)));
+        let body = self.parse();
+        self.labels.truncate(count);
+        let mut body = body?;
+        for name in names.into_iter().rev() {
+            body = Expr::Labeled(name, Box::new(body));
+        }
+        Ok(body)
     }
-    fn log(&self) {
-        self.println(
-            31,
-            format!(
-                "<{}>,\n     <{}>",
-                &self.current_token.desc(),
-                &self.peek_token.desc()
-            ),
-        );
+
+    /// break label / continue label, label必须与关键字在同一行
+    fn parse_jump_label(&mut self, is_continue: bool) -> Result<Option<String>, String> {
+        if self.is_statement_end() || !self.current_token.is_ident() {
+            return Ok(None);
+        }
+        let name = self.current_token.raw.clone();
+        match self.labels.iter().find(|(l, _)| *l == name) {
+            None => Err(self.err(&format!("Undefined label '{}'", name))),
+            Some((_, false)) if is_continue => Err(self.err(&format!(
+                "Illegal continue statement: '{}' does not denote an iteration statement",
+                name
+            ))),
+            Some(_) => {
+                self.next_token(); //label
+                Ok(Some(name))
+            }
+        }
+    }
+
+    /// 语句结束: ; } EOF 或者换行
+    fn is_statement_end(&self) -> bool {
+        self.current_token.is_eof(true)
+            || self.current_token.is_ptor(TokenPunctuator::RCParen)
+            || self.current_token.line > self.last_line
     }
 
-    fn println(&self, color: i32, msg: String) {
-        println!("\x1b[{}m{} \x1b[39m ", color, msg);
+    /// 跳过分号,没有分号时按自动插入分号规则处理
+    fn skip_semicolon(&mut self) -> Result<(), String> {
+        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
+            self.next_token();
+            return Ok(());
+        }
+        if self.is_statement_end() {
+            return Ok(());
+        }
+        Err(self.err("Unexpected token"))
     }
 
     fn err(&self, str: &str) -> String {
-        let msg = format!(
-            //"\x1b[31m{}\x1b[39m,token:<\x1b[32m{}\x1b[39m>",
-            "\x1b[31m({}) {} \x1b[39m",
+        format!(
+            "\x1b[31m({}) Uncaught SyntaxError: {} \x1b[39m",
             self.current_token.desc(),
             str,
-        );
-        panic!("{}", msg);
-        return msg;
+        )
+    }
+
+    /// 字符串字面量处理转义
+    fn cook(&self, raw: &str) -> Result<JSString, String> {
+        if self.strict {
+            if let Some(message) = legacy_escape(raw) {
+                return Err(self.err(message));
+            }
+        }
+        unescape(raw, false).map_err(|e| self.err(&e))
+    }
+
+    /// 指令序言: 脚本或函数体开头只有字符串的语句, 返回是否为 "use strict"
+    fn check_directive(&mut self, prologue: &mut bool) -> bool {
+        *prologue = matches!(self.current_token.typ, TokenType::Literal(_))
+            && (self.peek_token.is_eof(true)
+                || self.peek_token.is_ptor(TokenPunctuator::RCParen)
+                || self.peek_token.line != self.current_token.line);
+        //含转义的 "use\x20strict" 不是指令
+        if *prologue && self.current_token.raw == "use strict" {
+            self.strict = true;
+            return true;
+        }
+        false
+    }
+
+    /// 标识符引用, 严格模式下不能使用保留字
+    fn check_identifier(&self, name: &str) -> Result<(), String> {
+        if self.strict && STRICT_RESERVED_WORDS.contains(&name) {
+            return Err(self.err("Unexpected strict mode reserved word"));
+        }
+        Ok(())
+    }
+
+    /// 声明的名称, 严格模式下不能是保留字及eval/arguments
+    fn check_binding(&self, name: &str, strict: bool) -> Result<(), String> {
+        if !strict {
+            return Ok(());
+        }
+        if STRICT_RESERVED_WORDS.contains(&name) {
+            return Err(self.err("Unexpected strict mode reserved word"));
+        }
+        if name == "eval" || name == "arguments" {
+            return Err(self.err("Unexpected eval or arguments in strict mode"));
+        }
+        Ok(())
+    }
+
+    /// 赋值目标, 严格模式下不能赋值给eval/arguments
+    fn check_assign_target(&self, expr: &Expr) -> Result<(), String> {
+        match expr {
+            Expr::Identifier(name) if self.strict && (name == "eval" || name == "arguments") => {
+                Err(self.err("Unexpected eval or arguments in strict mode"))
+            }
+            _ => Ok(()),
+        }
     }
 
     fn next_token(&mut self) -> Token {
+        self.last_line = self.current_token.line;
         let token = self.current_token.clone();
         self.current_token = std::mem::replace(&mut self.peek_token, self.lexer.next_token());
         token
     }
 
-    fn parse_array_slot(&mut self) -> Result<Expr, String> {
-        self.next_token(); //[
-        let mut v = Vec::new();
-        if self.current_token.is_ptor(TokenPunctuator::RSParen) {
+    fn expect_ptor(&mut self, ptor: TokenPunctuator) -> Result<Token, String> {
+        if !self.current_token.is_ptor(ptor) {
+            if self.current_token.is_eof(false) {
+                return Err(self.err("Unexpected end of input"));
+            }
+            return Err(self.err("Unexpected token"));
+        }
+        Ok(self.next_token())
+    }
+
+    /// 逗号表达式 a,b
+    fn parse_expression(&mut self) -> Result<Expr, String> {
+        let expr = self.parse_assign()?;
+        if !self.current_token.is_ptor(TokenPunctuator::Comma) {
+            return Ok(expr);
+        }
+        let mut v = vec![expr];
+        while self.current_token.is_ptor(TokenPunctuator::Comma) {
             self.next_token();
-            return Ok(Expr::Array(v));
+            v.push(self.parse_assign()?);
+        }
+        Ok(Expr::Sequence(v))
+    }
+
+    /// a = b  a += b
+    fn parse_assign(&mut self) -> Result<Expr, String> {
+        if self.in_generator && self.current_token.is_ident() && self.current_token.raw == "yield" {
+            return self.parse_yield();
+        }
+        let left = self.parse_conditional()?;
+        if !self.current_token.is_assign() {
+            return Ok(left);
+        }
+        if !matches!(
+            left,
+            Expr::Identifier(_) | Expr::Member(_, _) | Expr::PrivateMember(_, _)
+        ) {
+            return Err(self.err("Invalid left-hand side in assignment"));
+        }
+        self.check_assign_target(&left)?;
+        let op = self.next_token();
+        let right = self.parse_assign()?;
+        if op.is_ptor(TokenPunctuator::MOV) {
+            return Ok(Expr::Assignment(Box::new(left), Box::new(right)));
+        }
+        let op = self.get_operator(&op);
+        Ok(Expr::Infix(Box::new(left), op, Box::new(right)))
+    }
+
+    /// yield / yield a / yield* a
+    fn parse_yield(&mut self) -> Result<Expr, String> {
+        self.next_token(); //yield
+        let delegate = self.current_token.is_ptor(TokenPunctuator::Multiply)
+            && self.current_token.line == self.last_line;
+        if delegate {
+            self.next_token(); //*
+        } else if self.is_statement_end()
+            || self.current_token.is_ptor(TokenPunctuator::Semicolon)
+            || self.current_token.is_ptor(TokenPunctuator::RParen)
+            || self.current_token.is_ptor(TokenPunctuator::RSParen)
+            || self.current_token.is_ptor(TokenPunctuator::Comma)
+            || self.current_token.is_ptor(TokenPunctuator::Colon)
+        {
+            return Ok(Expr::Yield(Box::new(Expr::Empty), false));
         }
+        let arg = self.parse_assign()?;
+        Ok(Expr::Yield(Box::new(arg), delegate))
+    }
+
+    /// a ? b : c
+    fn parse_conditional(&mut self) -> Result<Expr, String> {
+        let test = self.parser_infix(Precedence::Lowest)?;
+        if !self.current_token.is_ptor(TokenPunctuator::Question) {
+            return Ok(test);
+        }
+        self.next_token(); //?
+        let consequent = self.parse_assign()?;
+        self.expect_ptor(TokenPunctuator::Colon)?;
+        let alternate = self.parse_assign()?;
+        Ok(Expr::Conditional(
+            Box::new(test),
+            Box::new(consequent),
+            Box::new(alternate),
+        ))
+    }
+
+    fn parser_infix(&mut self, precedence: Precedence) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
         loop {
-            self.allow_fn_name_empty = true;
-            let expr = self.parse(false)?;
-            self.allow_fn_name_empty = false;
-            v.push(expr);
-            if self.current_token.is_ptor(TokenPunctuator::Comma) {
-                self.next_token();
-                continue;
-            }
-            if self.current_token.is_ptor(TokenPunctuator::RSParen) {
-                self.next_token();
+            let next = self.get_precedence(&self.current_token.typ);
+            if next <= precedence {
                 break;
-            } else {
-                return Err(self.err("Unexpected token"));
             }
+            let token = self.next_token();
+            let op = self.get_operator(&token);
+            let right = self.parser_infix(next)?;
+            left = Expr::Infix(Box::new(left), op, Box::new(right));
         }
-        dbg!(&v);
-        Ok(Expr::Array(v))
+        Ok(left)
     }
-    /// 暂没处理 {[1+1]:2}这种表达式
-    fn parse_json_slot(&mut self) -> Result<Expr, String> {
-        self.next_token(); //{
-        let mut v = HashMap::new();
-        if self.current_token.is_ptor(TokenPunctuator::RCParen) {
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        let unary = match &self.current_token.typ {
+            TokenType::Punctuator(TokenPunctuator::Not) => Some(Unary::Not),
+            TokenType::Punctuator(TokenPunctuator::Plus) => Some(Unary::Plus),
+            TokenType::Punctuator(TokenPunctuator::Minus) => Some(Unary::Minus),
+            TokenType::Punctuator(TokenPunctuator::BitNot) => Some(Unary::BitNot),
+            TokenType::Keyword(TokenKeyword::Typeof) => Some(Unary::Typeof),
+            TokenType::Keyword(TokenKeyword::Void) => Some(Unary::Void),
+            TokenType::Keyword(TokenKeyword::Delete) => Some(Unary::Delete),
+            _ => None,
+        };
+        if let Some(unary) = unary {
             self.next_token();
-            return Ok(Expr::Object(v));
+            let expr = self.parse_unary()?;
+            if self.strict && unary == Unary::Delete && matches!(expr, Expr::Identifier(_)) {
+                return Err(self.err("Delete of an unqualified identifier in strict mode."));
+            }
+            return Ok(Expr::Unary(unary, Box::new(expr)));
+        }
+        if self.in_async && self.current_token.is_ident() && self.current_token.raw == "await" {
+            self.next_token(); //await
+            let expr = self.parse_unary()?;
+            return Ok(Expr::Await(Box::new(expr)));
         }
+        if self.current_token.is_update() {
+            //++a
+            let op = self.next_token().raw;
+            let expr = self.parse_unary()?;
+            self.checked_update_target(&expr)?;
+            return Ok(Expr::Update(Box::new(expr), op, true));
+        }
+        let expr = self.parse_call_or_member()?;
+        if self.current_token.is_update() && self.current_token.line == self.last_line {
+            //a++
+            self.checked_update_target(&expr)?;
+            let op = self.next_token().raw;
+            return Ok(Expr::Update(Box::new(expr), op, false));
+        }
+        Ok(expr)
+    }
+
+    fn checked_update_target(&self, expr: &Expr) -> Result<(), String> {
+        if matches!(
+            expr,
+            Expr::Identifier(_) | Expr::Member(_, _) | Expr::PrivateMember(_, _)
+        ) {
+            return self.check_assign_target(expr);
+        }
+        Err(self.err("Invalid left-hand side expression in postfix operation"))
+    }
+
+    /// 这里还要处理多级 如: a()[1]  a[1]()    a[1]()[1]()...
+    fn parse_call_or_member(&mut self) -> Result<Expr, String> {
+        let mut expr = if self.current_token.is_keyword(TokenKeyword::New) {
+            self.parse_new_slot()?
+        } else {
+            self.parse_primary()?
+        };
         loop {
-            let key = self.parse(false)?.to_raw();
-            if self.current_token.is_ptor(TokenPunctuator::Colon) {
-                //:
-                self.next_token();
-                self.allow_fn_name_empty = true;
-                let value = self.parse(false)?;
-                self.allow_fn_name_empty = false;
-                v.insert(key.clone(), value);
-                if self.current_token.is_ptor(TokenPunctuator::Comma) {
-                    self.next_token();
-                    continue;
-                }
-            } else if self.current_token.is_ptor(TokenPunctuator::Comma) {
-                //,
-                self.next_token();
-                v.insert(key.clone(), Expr::Ref(key.clone()));
-                continue;
-            }
-            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
-                self.next_token();
-                if v.get(&key.clone()).is_none() {
-                    v.insert(key.clone(), Expr::Ref(key.clone()));
+            if self.current_token.is_ptor(TokenPunctuator::LParen) {
+                if expr == Expr::Super && !self.allow_super_call {
+                    return Err(self.err("'super' keyword unexpected here"));
                 }
-                break;
+                let args = self.parse_call_slot()?;
+                expr = Expr::Call(Box::new(expr), args);
+            } else if self.current_token.is_ptor(TokenPunctuator::Dot)
+                || self.current_token.is_ptor(TokenPunctuator::LSParen)
+            {
+                expr = self.parse_member_slot(expr)?;
+            } else if matches!(self.current_token.typ, TokenType::Template(_, true, _)) {
+                expr = self.parse_tagged_template(expr)?;
             } else {
-                return Err(self.err("Unexpected token"));
+                break;
             }
         }
-        Ok(Expr::Object(v))
-    }
-    fn parse_body_slot(&mut self, allow_single: bool) -> Result<Expr, String> {
-        if !allow_single && !self.current_token.is_ptor(TokenPunctuator::LCParen) {
-            return Err(self.err("Unexpected token"));
+        if expr == Expr::Super {
+            return Err(self.err("'super' keyword unexpected here"));
         }
-        if self.current_token.is_ptor(TokenPunctuator::LCParen) {
-            self.next_token(); // {
-            let mut v = Vec::new();
-            loop {
-                if self.current_token.is_eof(true) {
-                    break;
-                }
-                if self.current_token.is_ptor(TokenPunctuator::RCParen) {
-                    break;
-                }
-                let expr = self.parse(true)?;
-                v.push(expr);
-                // if matches!(expr, Expr::Assignment2(_) | Expr::Call(_, _)) {
-                //     v.push(expr);
-                // } else {
-                //     dbg!(&expr);
-                //     return Err(self.err("Use of future reserved word in strict mode"));
-                // }
-            }
-            if !self.current_token.is_ptor(TokenPunctuator::RCParen) {
-                return Err(self.err("Unexpected token"));
+        Ok(expr)
+    }
+
+    /// `a${b}c${d}e` => (["a","c","e"], [b,d]), 字符串为原始文本
+    fn parse_template_slot(&mut self) -> Result<(Vec<String>, Vec<Expr>), String> {
+        let mut strings = Vec::new();
+        let mut exprs = Vec::new();
+        let allow_in = std::mem::replace(&mut self.allow_in, true);
+        loop {
+            let TokenType::Template(raw, _, tail) = self.next_token().typ else {
+                unreachable!()
+            };
+            strings.push(raw);
+            if tail {
+                break;
             }
-            self.next_token(); //}
-            return Ok(Expr::Block(v));
-        } else {
-            let expr = self.parse(false)?;
-            if matches!(
-                expr,
-                Expr::Assignment(_) | Expr::Call(_, _) | Expr::Return(_) | Expr::Break
-            ) {
-                return Ok(expr);
-            } else {
-                dbg!(&expr);
-                return Err(self.err("Use of future reserved word in strict mode"));
+            exprs.push(self.parse_expression()?);
+            match &self.current_token.typ {
+                TokenType::Template(_, false, _) => {}
+                TokenType::EOF | TokenType::SyntaxError => {
+                    return Err(self.err("Unterminated template literal"))
+                }
+                _ => return Err(self.err("Unexpected token")),
             }
         }
+        self.allow_in = allow_in;
+        Ok((strings, exprs))
     }
 
-    fn is_valid(&self, expr: &Expr) -> bool {
-        match expr {
-            Expr::Unary(_, _)
-            | Expr::Identifier(_)
-            | Expr::Template(_, _)
-            | Expr::Literal(_)
-            | Expr::Call(_, _)
-            | Expr::Member(_, _)
-            | Expr::Sequence(_)
-            | Expr::Infix(_, _, _)
-            | Expr::Update(_, _, _)
-            | Expr::Assignment(_)
-            | Expr::Variable(_) => true,
-            _ => false,
+    /// tag`a${b}` 标签模板允许无效的转义, 此时cooked为undefined
+    fn parse_tagged_template(&mut self, tag: Expr) -> Result<Expr, String> {
+        if tag == Expr::Super {
+            return Err(self.err("'super' keyword unexpected here"));
         }
+        let (strings, exprs) = self.parse_template_slot()?;
+        let cooked = strings.iter().map(|raw| unescape(raw, true).ok()).collect();
+        let raw = strings
+            .iter()
+            .map(|raw| JSString::from(raw.as_str()))
+            .collect();
+        let strings = TemplateStrings { cooked, raw };
+        Ok(Expr::TaggedTemplate(
+            Box::new(tag),
+            Arc::new(strings),
+            exprs,
+        ))
     }
-    fn parse_do_while_slot(&mut self) -> Result<Expr, String> {
-        self.next_token(); //do
-
-        let line = self.current_token.line;
-        self.allow_break = true;
-        self.allow_continue = true;
-        let body = self.parse_body_slot(true)?;
-        self.allow_break = false;
-        self.allow_continue = false;
 
-        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-            self.next_token();
-        } else if line == self.current_token.line {
-            return Err(self.err("Unexpected token"));
+    /// new A.b(1)
+    fn parse_new_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //new
+        let mut callee = if self.current_token.is_keyword(TokenKeyword::New) {
+            self.parse_new_slot()?
+        } else {
+            self.parse_primary()?
+        };
+        loop {
+            if self.current_token.is_ptor(TokenPunctuator::Dot)
+                || self.current_token.is_ptor(TokenPunctuator::LSParen)
+            {
+                callee = self.parse_member_slot(callee)?;
+            } else if matches!(self.current_token.typ, TokenType::Template(_, true, _)) {
+                callee = self.parse_tagged_template(callee)?;
+            } else {
+                break;
+            }
         }
-
-        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-            self.next_token();
-        } else if line == self.current_token.line {
-            return Err(self.err("Unexpected token"));
+        if callee == Expr::Super {
+            return Err(self.err("'super' keyword unexpected here"));
         }
-        if !self.current_token.is_keyword(TokenKeyword::While) {
-            return Err(self.err("Unexpected end of input"));
+        let mut args = Vec::new();
+        if self.current_token.is_ptor(TokenPunctuator::LParen) {
+            args = self.parse_call_slot()?;
         }
-        self.next_token(); // while
-        if !self.current_token.is_ptor(TokenPunctuator::LParen) {
-            return Err(self.err("Unexpected token"));
+        Ok(Expr::New(Box::new(callee), args))
+    }
+
+    /// .a  .#a  [a]
+    fn parse_member_slot(&mut self, object: Expr) -> Result<Expr, String> {
+        if object == Expr::Super && !self.allow_super_property {
+            return Err(self.err("'super' keyword unexpected here"));
         }
-        self.next_token();
-        let test = self.parse(false)?;
-        if !self.is_valid(&test) {
+        let t = self.next_token(); // . or [
+        if t.is_ptor(TokenPunctuator::Dot) {
+            if let TokenType::PrivateName(name) = &self.current_token.typ {
+                if object == Expr::Super {
+                    return Err(self.err("Unexpected private field"));
+                }
+                let name = name.clone();
+                self.use_private_name(&name)?;
+                self.next_token();
+                return Ok(Expr::PrivateMember(Box::new(object), name));
+            }
+            if !self.current_token.is_ident() && !self.current_token.checked_keyword() {
+                return Err(self.err("Unexpected token"));
+            }
+            let ident = self.next_token();
+            return Ok(Expr::Member(
+                Box::new(object),
+                Box::new(Expr::Literal(ident.raw.into())),
+            ));
+        }
+        let property = self.parse_expression()?;
+        self.expect_ptor(TokenPunctuator::RSParen)?; //]
+        Ok(Expr::Member(Box::new(object), Box::new(property)))
+    }
+
+    /// (a, b, ...c)
+    fn parse_call_slot(&mut self) -> Result<Vec<Expr>, String> {
+        self.next_token(); //(
+        let mut v = Vec::new();
+        while !self.current_token.is_ptor(TokenPunctuator::RParen) {
+            if self.current_token.is_ptor(TokenPunctuator::Spread) {
+                self.next_token();
+                v.push(Expr::Spread(Box::new(self.parse_assign()?)));
+            } else {
+                v.push(self.parse_assign()?);
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+                self.next_token();
+            } else {
+                break;
+            }
+        }
+        self.expect_ptor(TokenPunctuator::RParen)?; //)
+        Ok(v)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match &self.current_token.typ {
+            TokenType::Ident(_) if self.is_async_function() => {
+                self.next_token(); //async
+                self.parse_function_slot(false, true)
+            }
+            TokenType::Ident(t)
+                if t == "import" && self.peek_token.is_ptor(TokenPunctuator::LParen) =>
+            {
+                self.next_token(); //import
+                self.next_token(); //(
+                let specifier = self.parse_assign()?;
+                self.expect_ptor(TokenPunctuator::RParen)?; //)
+                Ok(Expr::ImportCall(Box::new(specifier)))
+            }
+            TokenType::Ident(t) => {
+                self.check_identifier(t)?;
+                let expr = Expr::Identifier(t.clone());
+                self.next_token();
+                Ok(expr)
+            }
+            TokenType::Literal(t) => {
+                let expr = Expr::Literal(self.cook(t)?);
+                self.next_token();
+                Ok(expr)
+            }
+            TokenType::Number(t) if t.ends_with('n') => {
+                let num = self.bigint_literal(t)?;
+                self.next_token();
+                Ok(Expr::BigInt(Arc::new(num)))
+            }
+            TokenType::Number(t) => {
+                let num = self.number_literal(t)?;
+                self.next_token();
+                Ok(Expr::Number(num))
+            }
+            TokenType::Template(_, true, _) => {
+                let (strings, exprs) = self.parse_template_slot()?;
+                for part in &strings {
+                    unescape(part, true).map_err(|e| self.err(&e))?;
+                }
+                Ok(Expr::Template(strings, exprs))
+            }
+            TokenType::Punctuator(TokenPunctuator::LParen) => {
+                self.next_token(); //(
+                let expr = self.parse_expression()?;
+                self.expect_ptor(TokenPunctuator::RParen)?; //)
+                Ok(expr)
+            }
+            TokenType::Punctuator(TokenPunctuator::LSParen) => self.parse_array_slot(),
+            TokenType::Punctuator(TokenPunctuator::LCParen) => self.parse_json_slot(),
+            TokenType::Keyword(TokenKeyword::Function) => self.parse_function_slot(false, false),
+            TokenType::Keyword(TokenKeyword::Class) => self.parse_class_slot(false),
+            TokenType::Keyword(TokenKeyword::This) => {
+                self.next_token();
+                Ok(Expr::This)
+            }
+            TokenType::Keyword(TokenKeyword::Super) => {
+                self.next_token();
+                Ok(Expr::Super)
+            }
+            TokenType::Keyword(TokenKeyword::True) => {
+                self.next_token();
+                Ok(Expr::Bool(true))
+            }
+            TokenType::Keyword(TokenKeyword::False) => {
+                self.next_token();
+                Ok(Expr::Bool(false))
+            }
+            TokenType::Keyword(TokenKeyword::Null) => {
+                self.next_token();
+                Ok(Expr::Null)
+            }
+            TokenType::EOF => Err(self.err("Unexpected end of input")),
+            _ => Err(self.err("Unexpected token")),
+        }
+    }
+
+    fn parse_array_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //[
+        let mut v = Vec::new();
+        loop {
+            if self.current_token.is_ptor(TokenPunctuator::RSParen) {
+                break;
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+                //[1,,2] 空位
+                self.next_token();
+                v.push(Expr::Empty);
+                continue;
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Spread) {
+                self.next_token();
+                v.push(Expr::Spread(Box::new(self.parse_assign()?)));
+            } else {
+                v.push(self.parse_assign()?);
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+                self.next_token();
+            } else {
+                break;
+            }
+        }
+        self.expect_ptor(TokenPunctuator::RSParen)?; //]
+        Ok(Expr::Array(v))
+    }
+
+    /// 属性名 a 'a' 1 [a]  #a(仅class)
+    fn parse_property_name(&mut self, allow_private: bool) -> Result<PropertyName, String> {
+        if self.current_token.is_ptor(TokenPunctuator::LSParen) {
+            self.next_token(); //[
+            let expr = self.parse_assign()?;
+            self.expect_ptor(TokenPunctuator::RSParen)?; //]
+            return Ok(PropertyName::Computed(Box::new(expr)));
+        }
+        if let TokenType::PrivateName(name) = &self.current_token.typ {
+            if !allow_private {
+                return Err(self.err("Unexpected identifier"));
+            }
+            let name = name.clone();
+            self.next_token();
+            return Ok(PropertyName::Private(name));
+        }
+        if let TokenType::Number(t) = &self.current_token.typ {
+            if t.ends_with('n') {
+                let name = self.bigint_literal(t)?.to_string();
+                self.next_token();
+                return Ok(PropertyName::Name(name));
+            }
+            //数字属性名按数字格式化 {1.0:1} => "1"
+            let num = self.number_literal(t)?;
+            self.next_token();
+            return Ok(PropertyName::Name(
+                super::program::JSType::number(num).to_key(),
+            ));
+        }
+        if !self.current_token.is_property_name() {
             return Err(self.err("Unexpected token"));
         }
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
+        if let TokenType::Literal(t) = &self.current_token.typ {
+            let name = self.cook(t)?.to_string();
+            self.next_token();
+            return Ok(PropertyName::Name(name));
+        }
+        let token = self.next_token();
+        Ok(PropertyName::Name(token.raw))
+    }
+
+    /// get/set/static 后面跟属性名时才是关键字, 否则是普通属性名
+    fn is_modifier(&self, name: &str) -> bool {
+        self.current_token.is_ident()
+            && self.current_token.raw == name
+            && (self.peek_token.is_property_name()
+                || self.peek_token.is_ptor(TokenPunctuator::LSParen)
+                || matches!(self.peek_token.typ, TokenType::PrivateName(_)))
+    }
+
+    fn parse_json_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //{
+        let mut v = Vec::new();
+        let mut has_proto = false;
+        loop {
+            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
+                break;
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Spread) {
+                //{...a}
+                self.next_token();
+                let expr = self.parse_assign()?;
+                v.push((
+                    PropertyName::Name(String::new()),
+                    ObjectMember::Spread(expr),
+                ));
+            } else if self.is_modifier("get") || self.is_modifier("set") {
+                let is_get = self.next_token().raw == "get";
+                let key = self.parse_property_name(false)?;
+                let func = self.parse_method_slot(&key, false, false, false)?;
+                if is_get {
+                    v.push((key, ObjectMember::Getter(func)));
+                } else {
+                    v.push((key, ObjectMember::Setter(func)));
+                }
+            } else if self.current_token.is_ptor(TokenPunctuator::Multiply) {
+                //{*a(){}}
+                self.next_token(); //*
+                let key = self.parse_property_name(false)?;
+                let func = self.parse_method_slot(&key, false, true, false)?;
+                v.push((key, ObjectMember::Value(Expr::Function(func))));
+            } else if self.is_modifier("async") {
+                //{async a(){}}
+                self.next_token(); //async
+                if self.current_token.is_ptor(TokenPunctuator::Multiply) {
+                    return Err(self.err("Async generators are not supported"));
+                }
+                let key = self.parse_property_name(false)?;
+                let func = self.parse_method_slot(&key, false, false, true)?;
+                v.push((key, ObjectMember::Value(Expr::Function(func))));
+            } else {
+                let is_ident = self.current_token.is_ident();
+                let key = self.parse_property_name(false)?;
+                if self.current_token.is_ptor(TokenPunctuator::Colon) {
+                    self.next_token(); //:
+                    let value = self.parse_assign()?;
+                    //非计算属性名的 __proto__: value
+                    if matches!(&key, PropertyName::Name(name) if name == "__proto__") {
+                        if has_proto {
+                            return Err(self.err(
+                                "Duplicate __proto__ fields are not allowed in object literals",
+                            ));
+                        }
+                        has_proto = true;
+                        v.push((key, ObjectMember::Proto(value)));
+                    } else {
+                        v.push((key, ObjectMember::Value(value)));
+                    }
+                } else if self.current_token.is_ptor(TokenPunctuator::LParen) {
+                    //{a(){}}
+                    let func = self.parse_method_slot(&key, false, false, false)?;
+                    v.push((key, ObjectMember::Value(Expr::Function(func))));
+                } else if is_ident {
+                    //{a}
+                    if let PropertyName::Name(name) = &key {
+                        let name = name.clone();
+                        v.push((key, ObjectMember::Value(Expr::Ref(name))));
+                    }
+                } else {
+                    return Err(self.err("Unexpected token"));
+                }
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+                self.next_token();
+            } else {
+                break;
+            }
+        }
+        self.expect_ptor(TokenPunctuator::RCParen)?; //}
+        Ok(Expr::Object(v))
+    }
+
+    fn parse_body_slot(&mut self, allow_single: bool) -> Result<Expr, String> {
+        if !self.current_token.is_ptor(TokenPunctuator::LCParen) {
+            if !allow_single {
+                return Err(self.err("Unexpected token"));
+            }
+            //if(a) b; 单条语句
+            if self.current_token.is_keyword(TokenKeyword::Let)
+                || self.current_token.is_keyword(TokenKeyword::Const)
+                || self.current_token.is_keyword(TokenKeyword::Class)
+            {
+                return Err(
+                    self.err("Lexical declaration cannot appear in a single-statement context")
+                );
+            }
+            return self.parse();
+        }
+        self.next_token(); // {
+        let mut v = Vec::new();
+        loop {
+            if self.current_token.is_eof(false) {
+                return Err(self.err("Unexpected end of input"));
+            }
+            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
+                break;
+            }
+            let expr = self.parse()?;
+            if expr != Expr::Empty {
+                v.push(expr);
+            }
+        }
+        self.next_token(); //}
+        Ok(Expr::Block(v))
+    }
+
+    /// let a = 1, b;
+    fn parse_variable_slot(&mut self) -> Result<Expr, String> {
+        self.parse_declaration(false)
+    }
+
+    /// for(let a of b) 中的声明可以没有初始值
+    fn parse_declaration(&mut self, in_for: bool) -> Result<Expr, String> {
+        let token = self.next_token();
+        let key = if token.is_keyword(TokenKeyword::Var) {
+            Variable::Var
+        } else if token.is_keyword(TokenKeyword::Let) {
+            Variable::Let
+        } else {
+            Variable::Const
+        };
+        let mut v = Vec::new();
+        loop {
+            if !self.current_token.is_ident() {
+                return Err(self.err("Unexpected token"));
+            }
+            self.check_binding(&self.current_token.raw, self.strict)?;
+            let ident = self.next_token().raw;
+            let mut value = Expr::Empty;
+            if self.current_token.is_ptor(TokenPunctuator::MOV) {
+                self.next_token(); //=
+                value = self.parse_assign()?;
+            } else if in_for && self.is_for_in_of() {
+                v.push((key, ident, value));
+                break;
+            } else if key == Variable::Const {
+                return Err(self.err("Missing initializer in const declaration"));
+            }
+            v.push((key.clone(), ident, value));
+            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+                self.next_token(); // ,
+            } else {
+                break;
+            }
+        }
+        Ok(Expr::Variable(v))
+    }
+
+    /// 循环体内允许 break/continue
+    fn parse_loop_body(&mut self) -> Result<Expr, String> {
+        let allow_break = self.allow_break;
+        let allow_continue = self.allow_continue;
+        self.allow_break = true;
+        self.allow_continue = true;
+        let body = self.parse_body_slot(true);
+        self.allow_break = allow_break;
+        self.allow_continue = allow_continue;
+        body
+    }
+
+    /// (test)
+    fn parse_paren_test(&mut self) -> Result<Expr, String> {
+        self.expect_ptor(TokenPunctuator::LParen)?;
+        let test = self.parse_expression()?;
+        self.expect_ptor(TokenPunctuator::RParen)?;
+        Ok(test)
+    }
+
+    fn parse_do_while_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //do
+        let body = self.parse_loop_body()?;
+        if !self.current_token.is_keyword(TokenKeyword::While) {
             return Err(self.err("Unexpected token"));
         }
-        self.next_token();
+        self.next_token(); // while
+        let test = self.parse_paren_test()?;
+        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
+            self.next_token();
+        }
         Ok(Expr::DoWhile(Box::new(test), Box::new(body)))
     }
     fn parse_while_slot(&mut self) -> Result<Expr, String> {
         self.next_token(); // while
-        if !self.current_token.is_ptor(TokenPunctuator::LParen) {
-            return Err(self.err("Unexpected token"));
+        let test = self.parse_paren_test()?;
+        let body = self.parse_loop_body()?;
+        Ok(Expr::While(Box::new(test), Box::new(body)))
+    }
+
+    /// (a, b = 1, ...c)
+    fn parse_params(&mut self) -> Result<Vec<Expr>, String> {
+        self.expect_ptor(TokenPunctuator::LParen)?; //(
+        let mut args = Vec::new();
+        loop {
+            if self.current_token.is_ptor(TokenPunctuator::RParen) {
+                break;
+            }
+            let is_rest = self.current_token.is_ptor(TokenPunctuator::Spread);
+            if is_rest {
+                self.next_token();
+            }
+            if !self.current_token.is_ident() {
+                return Err(self.err("Unexpected token"));
+            }
+            let ident = Expr::Identifier(self.next_token().raw);
+            if is_rest {
+                args.push(Expr::Spread(Box::new(ident)));
+                if !self.current_token.is_ptor(TokenPunctuator::RParen) {
+                    return Err(self.err("Rest parameter must be last formal parameter"));
+                }
+                break;
+            }
+            if self.current_token.is_ptor(TokenPunctuator::MOV) {
+                self.next_token(); //=
+                let value = self.parse_assign()?;
+                args.push(Expr::Assignment(Box::new(ident), Box::new(value)));
+            } else {
+                args.push(ident);
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+                self.next_token();
+            } else {
+                break;
+            }
         }
-        self.next_token();
-        let test = self.parse(false)?;
-        if !self.is_valid(&test) {
-            return Err(self.err("Unexpected token"));
+        self.expect_ptor(TokenPunctuator::RParen)?; //)
+        Ok(args)
+    }
+
+    /// {...} 函数体, 函数内不能 break/continue 外层循环, 返回语句及是否有 "use strict"
+    fn parse_function_body(
+        &mut self,
+        allow_super_call: bool,
+        allow_super_property: bool,
+        is_generator: bool,
+        is_async: bool,
+    ) -> Result<(Vec<Expr>, bool), String> {
+        let saved = (
+            self.allow_return,
+            self.allow_break,
+            self.allow_continue,
+            self.allow_super_call,
+            self.allow_super_property,
+            self.in_generator,
+            self.in_async,
+            self.strict,
+        );
+        let labels = std::mem::take(&mut self.labels);
+        self.allow_return = true;
+        self.allow_break = false;
+        self.allow_continue = false;
+        self.allow_super_call = allow_super_call;
+        self.allow_super_property = allow_super_property;
+        self.in_generator = is_generator;
+        self.in_async = is_async;
+        let body = self.parse_statement_list();
+        self.labels = labels;
+        (
+            self.allow_return,
+            self.allow_break,
+            self.allow_continue,
+            self.allow_super_call,
+            self.allow_super_property,
+            self.in_generator,
+            self.in_async,
+            self.strict,
+        ) = saved;
+        body
+    }
+
+    /// 函数体 {...}, 开头的指令序言可以开启严格模式
+    fn parse_statement_list(&mut self) -> Result<(Vec<Expr>, bool), String> {
+        self.expect_ptor(TokenPunctuator::LCParen)?; // {
+        let mut v = Vec::new();
+        let mut prologue = true;
+        let mut use_strict = false;
+        loop {
+            if self.current_token.is_eof(false) {
+                return Err(self.err("Unexpected end of input"));
+            }
+            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
+                break;
+            }
+            if prologue && self.check_directive(&mut prologue) {
+                use_strict = true;
+            }
+            let expr = self.parse()?;
+            if expr != Expr::Empty {
+                v.push(expr);
+            }
         }
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
+        self.next_token(); //}
+        Ok((v, use_strict))
+    }
+
+    /// 函数的早期错误, 严格模式/方法/参数不是简单参数时参数名不能重复
+    fn check_function(
+        &self,
+        name: Option<&str>,
+        params: &[Expr],
+        strict: bool,
+        use_strict: bool,
+        is_method: bool,
+    ) -> Result<(), String> {
+        let simple = params.iter().all(|p| matches!(p, Expr::Identifier(_)));
+        if use_strict && !simple {
+            return Err(self
+                .err("Illegal 'use strict' directive in function with non-simple parameter list"));
+        }
+        if let Some(name) = name {
+            self.check_binding(name, strict)?;
+        }
+        let mut names: Vec<&str> = Vec::new();
+        for param in params {
+            let target = match param {
+                Expr::Assignment(target, _) | Expr::Spread(target) => target.as_ref(),
+                _ => param,
+            };
+            let Expr::Identifier(name) = target else {
+                continue;
+            };
+            self.check_binding(name, strict)?;
+            if names.contains(&name.as_str()) && (strict || is_method || !simple) {
+                return Err(self.err("Duplicate parameter name not allowed in this context"));
+            }
+            names.push(name);
+        }
+        Ok(())
+    }
+
+    /// import声明, import(...) 及 import.meta 为表达式
+    fn is_import_declaration(&self) -> bool {
+        self.current_token.is_ident()
+            && self.current_token.raw == "import"
+            && !self.peek_token.is_ptor(TokenPunctuator::LParen)
+            && !self.peek_token.is_ptor(TokenPunctuator::Dot)
+    }
+
+    /// import "m";  import a, { b as c } from "m";  import * as ns from "m";
+    fn parse_import_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //import
+        let mut bindings = Vec::new();
+        if let TokenType::Literal(specifier) = &self.current_token.typ {
+            let specifier = self.cook(specifier)?.to_string();
+            self.next_token();
+            self.skip_semicolon()?;
+            return Ok(Expr::Import(Box::new(ImportDeclaration {
+                specifier,
+                bindings,
+            })));
+        }
+        if self.current_token.is_ident() {
+            let local = self.parse_binding_identifier()?;
+            bindings.push(ImportBinding::Named("default".to_string(), local));
+            if !self.current_token.is_ptor(TokenPunctuator::Comma) {
+                return self.parse_import_from(bindings);
+            }
+            self.next_token(); //,
+        }
+        if self.current_token.is_ptor(TokenPunctuator::Multiply) {
+            self.next_token(); //*
+            self.expect_contextual("as")?;
+            let local = self.parse_binding_identifier()?;
+            bindings.push(ImportBinding::Namespace(local));
+        } else if self.current_token.is_ptor(TokenPunctuator::LCParen) {
+            self.next_token(); //{
+            while !self.current_token.is_ptor(TokenPunctuator::RCParen) {
+                let is_string = matches!(self.current_token.typ, TokenType::Literal(_));
+                let imported = self.parse_module_export_name()?;
+                let local = if self.is_contextual("as") {
+                    self.next_token(); //as
+                    self.parse_binding_identifier()?
+                } else if is_string || self.current_token.checked_keyword() {
+                    return Err(self.err("Unexpected token"));
+                } else {
+                    imported.clone()
+                };
+                bindings.push(ImportBinding::Named(imported, local));
+                if !self.current_token.is_ptor(TokenPunctuator::Comma) {
+                    break;
+                }
+                self.next_token(); //,
+            }
+            self.expect_ptor(TokenPunctuator::RCParen)?; //}
+        } else {
             return Err(self.err("Unexpected token"));
         }
-        self.next_token();
+        self.parse_import_from(bindings)
+    }
 
-        let line = self.current_token.line;
-        self.allow_break = true;
-        self.allow_continue = true;
-        let body = self.parse_body_slot(true)?;
-        self.allow_break = false;
-        self.allow_continue = false;
+    fn parse_import_from(&mut self, bindings: Vec<ImportBinding>) -> Result<Expr, String> {
+        let specifier = self.parse_module_specifier()?;
+        self.skip_semicolon()?;
+        Ok(Expr::Import(Box::new(ImportDeclaration {
+            specifier,
+            bindings,
+        })))
+    }
 
-        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
+    /// export声明/export default/export { a as b }/export * from "m"
+    fn parse_export_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //export
+        let export = match &self.current_token.typ {
+            TokenType::Keyword(TokenKeyword::Default) => {
+                self.next_token(); //default
+                let expr = if self.is_async_function() {
+                    self.next_token(); //async
+                    self.parse_function_slot(false, true)?
+                } else if self.current_token.is_keyword(TokenKeyword::Function) {
+                    self.parse_function_slot(false, false)?
+                } else if self.current_token.is_keyword(TokenKeyword::Class) {
+                    self.parse_class_slot(false)?
+                } else {
+                    let expr = self.parse_assign()?;
+                    self.skip_semicolon()?;
+                    expr
+                };
+                ExportDeclaration::Default(expr)
+            }
+            TokenType::Keyword(TokenKeyword::Let | TokenKeyword::Var | TokenKeyword::Const) => {
+                let expr = self.parse_variable_slot()?;
+                self.skip_semicolon()?;
+                ExportDeclaration::Declaration(expr)
+            }
+            TokenType::Keyword(TokenKeyword::Function) => {
+                ExportDeclaration::Declaration(self.parse_function_slot(true, false)?)
+            }
+            TokenType::Keyword(TokenKeyword::Class) => {
+                ExportDeclaration::Declaration(self.parse_class_slot(true)?)
+            }
+            TokenType::Ident(_) if self.is_async_function() => {
+                self.next_token(); //async
+                ExportDeclaration::Declaration(self.parse_function_slot(true, true)?)
+            }
+            TokenType::Punctuator(TokenPunctuator::Multiply) => {
+                self.next_token(); //*
+                let mut exported = None;
+                if self.is_contextual("as") {
+                    self.next_token(); //as
+                    exported = Some(self.parse_module_export_name()?);
+                }
+                let specifier = self.parse_module_specifier()?;
+                self.skip_semicolon()?;
+                ExportDeclaration::All(exported, specifier)
+            }
+            TokenType::Punctuator(TokenPunctuator::LCParen) => {
+                self.next_token(); //{
+                let mut names = Vec::new();
+                let mut has_string = false;
+                while !self.current_token.is_ptor(TokenPunctuator::RCParen) {
+                    has_string |= matches!(self.current_token.typ, TokenType::Literal(_))
+                        || self.current_token.checked_keyword();
+                    let local = self.parse_module_export_name()?;
+                    let mut exported = local.clone();
+                    if self.is_contextual("as") {
+                        self.next_token(); //as
+                        exported = self.parse_module_export_name()?;
+                    }
+                    names.push((local, exported));
+                    if !self.current_token.is_ptor(TokenPunctuator::Comma) {
+                        break;
+                    }
+                    self.next_token(); //,
+                }
+                self.expect_ptor(TokenPunctuator::RCParen)?; //}
+                let mut specifier = None;
+                if self.is_contextual("from") {
+                    specifier = Some(self.parse_module_specifier()?);
+                } else if has_string {
+                    //没有from时只能导出本地变量
+                    return Err(self.err("Unexpected token"));
+                }
+                self.skip_semicolon()?;
+                ExportDeclaration::Named(names, specifier)
+            }
+            _ => return Err(self.err("Unexpected token")),
+        };
+        Ok(Expr::Export(Box::new(export)))
+    }
+
+    /// from "m"
+    fn parse_module_specifier(&mut self) -> Result<String, String> {
+        self.expect_contextual("from")?;
+        match &self.current_token.typ {
+            TokenType::Literal(specifier) => {
+                let specifier = self.cook(specifier)?.to_string();
+                self.next_token();
+                Ok(specifier)
+            }
+            _ => Err(self.err("Unexpected token")),
+        }
+    }
+
+    /// 导入导出名: 标识符/关键字/字符串
+    fn parse_module_export_name(&mut self) -> Result<String, String> {
+        let valid = self.current_token.is_ident()
+            || self.current_token.checked_keyword()
+            || matches!(self.current_token.typ, TokenType::Literal(_));
+        if !valid {
+            return Err(self.err("Unexpected token"));
+        }
+        if let TokenType::Literal(name) = &self.current_token.typ {
+            let name = self.cook(name)?.to_string();
             self.next_token();
-        } else if line == self.current_token.line {
+            return Ok(name);
+        }
+        Ok(self.next_token().raw)
+    }
+
+    fn parse_binding_identifier(&mut self) -> Result<String, String> {
+        if !self.current_token.is_ident() {
+            return Err(self.err("Unexpected token"));
+        }
+        self.check_binding(&self.current_token.raw, self.strict)?;
+        Ok(self.next_token().raw)
+    }
+
+    /// as/from 等上下文关键字
+    fn is_contextual(&self, word: &str) -> bool {
+        self.current_token.is_ident() && self.current_token.raw == word
+    }
+
+    fn expect_contextual(&mut self, word: &str) -> Result<(), String> {
+        if !self.is_contextual(word) {
             return Err(self.err("Unexpected token"));
         }
-        Ok(Expr::While(Box::new(test), Box::new(body)))
+        self.next_token();
+        Ok(())
+    }
+
+    /// async function a(){}, async与function之间不能换行
+    fn is_async_function(&self) -> bool {
+        self.current_token.is_ident()
+            && self.current_token.raw == "async"
+            && self.peek_token.is_keyword(TokenKeyword::Function)
+            && self.peek_token.line == self.current_token.line
     }
-    fn parse_function_slot(&mut self) -> Result<Expr, String> {
-        self.next_token(); // function
 
-        let mut ident: Option<Token> = None;
-        if self.allow_fn_name_empty {
-            //return funciton(){} 这种情况下可以没有名称
-            if self.current_token.is_ident() {
-                ident = Some(self.next_token()); //ident
+    /// function a(){}  is_statement:是否为声明语句(必须有名称)
+    fn parse_function_slot(&mut self, is_statement: bool, is_async: bool) -> Result<Expr, String> {
+        self.next_token(); // function
+        let is_generator = self.current_token.is_ptor(TokenPunctuator::Multiply);
+        if is_generator {
+            if is_async {
+                return Err(self.err("Async generators are not supported"));
             }
-        } else {
-            if !self.current_token.is_ident() {
-                return Err(self.err("Unexpected token"));
+            self.next_token(); // *
+        }
+        let mut name = None;
+        if self.current_token.is_ident() {
+            name = Some(self.next_token().raw);
+        } else if is_statement {
+            return Err(self.err("Function statements require a function name"));
+        }
+        let params = self.parse_params()?;
+        let (body, use_strict) = self.parse_function_body(false, false, is_generator, is_async)?;
+        let strict = self.strict || use_strict;
+        self.check_function(name.as_deref(), &params, strict, use_strict, false)?;
+        Ok(Expr::Function(Arc::new(Function {
+            name,
+            params,
+            body,
+            is_generator,
+            is_async,
+            strict,
+        })))
+    }
+
+    /// 对象/class中的方法 a(){}
+    fn parse_method_slot(
+        &mut self,
+        key: &PropertyName,
+        allow_super_call: bool,
+        is_generator: bool,
+        is_async: bool,
+    ) -> Result<Arc<Function>, String> {
+        let name = match key {
+            PropertyName::Name(t) => Some(t.clone()),
+            PropertyName::Private(t) => Some(format!("#{}", t)),
+            PropertyName::Computed(_) => None,
+        };
+        let params = self.parse_params()?;
+        let (body, use_strict) =
+            self.parse_function_body(allow_super_call, true, is_generator, is_async)?;
+        let strict = self.strict || use_strict;
+        self.check_function(None, &params, strict, use_strict, true)?;
+        Ok(Arc::new(Function {
+            name,
+            params,
+            body,
+            is_generator,
+            is_async,
+            strict,
+        }))
+    }
+
+    fn use_private_name(&mut self, name: &str) -> Result<(), String> {
+        match self.private_names.last_mut() {
+            Some(scope) => {
+                scope.referenced.push(name.to_string());
+                Ok(())
             }
-            ident = Some(self.next_token()); //ident
+            None => Err(self.err(&format!(
+                "Private field '#{}' must be declared in an enclosing class",
+                name
+            ))),
         }
-        //
+    }
 
-        if !self.current_token.is_ptor(TokenPunctuator::LParen) {
+    /// class A extends B { constructor(){} a(){} static b = 1; #c; get d(){} static {} }
+    /// class的所有部分都是严格模式
+    fn parse_class_slot(&mut self, is_statement: bool) -> Result<Expr, String> {
+        let strict = self.strict;
+        self.strict = true;
+        let class = self.parse_class_declaration(is_statement);
+        self.strict = strict;
+        class
+    }
+
+    fn parse_class_declaration(&mut self, is_statement: bool) -> Result<Expr, String> {
+        self.next_token(); //class
+        let mut name = None;
+        if self.current_token.is_ident() {
+            self.check_binding(&self.current_token.raw, true)?;
+            name = Some(self.next_token().raw);
+        } else if is_statement {
             return Err(self.err("Unexpected token"));
         }
-        self.next_token(); //(
-        let mut args = Vec::new();
+        let mut extends = None;
+        if self.current_token.is_keyword(TokenKeyword::Extends) {
+            self.next_token(); //extends
+            extends = Some(Box::new(self.parse_call_or_member()?));
+        }
+        self.expect_ptor(TokenPunctuator::LCParen)?; //{
+        self.private_names.push(PrivateScope::default());
+        let result = self.parse_class_body(extends.is_some());
+        let scope = self.private_names.pop().unwrap_or_default();
+        let (constructor, members) = result?;
+        for name in scope.referenced {
+            if scope.declared.contains(&name) {
+                continue;
+            }
+            //交给外层class检查
+            self.use_private_name(&name)?;
+        }
+        self.next_token(); //}
+        Ok(Expr::Class(Arc::new(Class {
+            name,
+            extends,
+            constructor,
+            members,
+        })))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn parse_class_body(
+        &mut self,
+        is_derived: bool,
+    ) -> Result<(Option<Arc<Function>>, Vec<ClassMember>), String> {
+        let mut constructor = None;
+        let mut members = Vec::new();
         loop {
-            if self.current_token.is_ptor(TokenPunctuator::RParen) {
+            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
                 break;
             }
-            let expr = self.parse(false)?;
-            args.push(expr);
-            if self.current_token.is_ptor(TokenPunctuator::Comma) {
+            if self.current_token.is_eof(false) {
+                return Err(self.err("Unexpected end of input"));
+            }
+            if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
                 self.next_token();
-            } else {
-                break;
+                continue;
             }
+            let mut is_static = false;
+            if self.is_modifier("static")
+                || (self.current_token.is_ident()
+                    && self.current_token.raw == "static"
+                    && self.peek_token.is_ptor(TokenPunctuator::Multiply))
+            {
+                self.next_token();
+                is_static = true;
+            } else if self.current_token.is_ident()
+                && self.current_token.raw == "static"
+                && self.peek_token.is_ptor(TokenPunctuator::LCParen)
+            {
+                //static {}
+                self.next_token(); //static
+                let (body, _) = self.parse_function_body(false, true, false, false)?;
+                members.push(ClassMember {
+                    is_static: true,
+                    key: PropertyName::Name(String::new()),
+                    kind: ClassMemberKind::StaticBlock(body),
+                });
+                continue;
+            }
+            let mut accessor = None;
+            let is_async = self.is_modifier("async");
+            if is_async {
+                self.next_token(); //async
+            }
+            let is_generator = self.current_token.is_ptor(TokenPunctuator::Multiply);
+            if is_generator {
+                if is_async {
+                    return Err(self.err("Async generators are not supported"));
+                }
+                self.next_token(); //*
+            } else if !is_async && (self.is_modifier("get") || self.is_modifier("set")) {
+                accessor = Some(self.next_token().raw);
+            }
+            let key = self.parse_property_name(true)?;
+            if let PropertyName::Private(name) = &key {
+                if name == "constructor" {
+                    return Err(
+                        self.err("Classes may not have a private field named '#constructor'")
+                    );
+                }
+                if let Some(scope) = self.private_names.last_mut() {
+                    scope.declared.push(name.clone());
+                }
+            }
+            let is_constructor = !is_static && key == PropertyName::Name("constructor".to_string());
+            if self.current_token.is_ptor(TokenPunctuator::LParen) {
+                if is_constructor {
+                    if accessor.is_some() {
+                        return Err(self.err("Class constructor may not be an accessor"));
+                    }
+                    if is_generator {
+                        return Err(self.err("Class constructor may not be a generator"));
+                    }
+                    if is_async {
+                        return Err(self.err("Class constructor may not be an async method"));
+                    }
+                    if constructor.is_some() {
+                        return Err(self.err("A class may only have one constructor"));
+                    }
+                    constructor = Some(self.parse_method_slot(&key, is_derived, false, false)?);
+                    continue;
+                }
+                let func = self.parse_method_slot(&key, false, is_generator, is_async)?;
+                let kind = match accessor.as_deref() {
+                    Some("get") => ClassMemberKind::Getter(func),
+                    Some("set") => ClassMemberKind::Setter(func),
+                    _ => ClassMemberKind::Method(func),
+                };
+                members.push(ClassMember {
+                    is_static,
+                    key,
+                    kind,
+                });
+                continue;
+            }
+            if accessor.is_some() || is_generator || is_async {
+                return Err(self.err("Unexpected token"));
+            }
+            if is_constructor {
+                return Err(self.err("Classes may not have a field named 'constructor'"));
+            }
+            //字段 a = 1;
+            let mut value = None;
+            if self.current_token.is_ptor(TokenPunctuator::MOV) {
+                self.next_token(); //=
+                let saved = (self.allow_super_call, self.allow_super_property);
+                self.allow_super_call = false;
+                self.allow_super_property = true;
+                let expr = self.parse_assign();
+                (self.allow_super_call, self.allow_super_property) = saved;
+                value = Some(expr?);
+            }
+            self.skip_semicolon()?;
+            members.push(ClassMember {
+                is_static,
+                key,
+                kind: ClassMemberKind::Field(value),
+            });
         }
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
-            return Err(self.err("Unexpected end of input"));
-        }
-        self.next_token(); //)
-        self.allow_return = true;
-        let body = self.parse_body_slot(false)?;
-        self.allow_return = false;
-        if let Some(tk) = ident {
-            Ok(Expr::Function(
-                Box::new(Expr::Identifier(tk.raw.clone())),
-                args,
-                Box::new(body),
-            ))
-        } else {
-            Ok(Expr::Function(Box::new(Expr::Empty), args, Box::new(body)))
-        }
+        Ok((constructor, members))
     }
+
     fn parse_switch_slot(&mut self) -> Result<Expr, String> {
         self.next_token(); //switch
-        if !self.current_token.is_ptor(TokenPunctuator::LParen) {
-            return Err(self.err("Unexpected token"));
-        }
-        self.next_token(); //(
-        let test = self.parse(false)?;
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
-            return Err(self.err("Unexpected token"));
-        }
-        self.next_token(); //(
-        if !self.current_token.is_ptor(TokenPunctuator::LCParen) {
-            return Err(self.err("Unexpected token"));
-        }
-        self.next_token(); //{
+        let test = self.parse_paren_test()?;
+        self.expect_ptor(TokenPunctuator::LCParen)?; //{
+        let allow_break = self.allow_break;
+        self.allow_break = true;
         let mut v = Vec::new();
-        loop {
-            let cur = self.current_token.clone();
-            let is_default = cur.is_keyword(TokenKeyword::Default);
-            if cur.is_keyword(TokenKeyword::Case) || is_default {
-                self.next_token(); //case or default
-                let mut case_test = Expr::Empty;
-                if !self.current_token.is_ptor(TokenPunctuator::Colon) {
-                    case_test = self.parse(false)?;
-                }
-                if self.current_token.is_ptor(TokenPunctuator::Colon) {
-                    self.next_token(); //:
-                }
-                if self.current_token.is_keyword(TokenKeyword::Default) {
-                    v.push(Expr::SwitchCase(Box::new(case_test), Vec::new()));
-                    continue;
-                }
-                if self.current_token.is_ptor(TokenPunctuator::RCParen) {
-                    v.push(Expr::SwitchCase(Box::new(case_test), Vec::new()));
-                    break;
-                }
-                let mut allow_break = false;
-                if !self.allow_break {
-                    allow_break = true;
-                    self.allow_break = true;
-                }
-                let mut v_body = Vec::new();
-                loop {
-                    let case_body = self.parse(true)?;
-                    v_body.push(case_body);
-                    if self.current_token.is_keyword(TokenKeyword::Default)
-                        || self.current_token.is_ptor(TokenPunctuator::RCParen)
-                    {
-                        break;
-                    }
+        let mut has_default = false;
+        let result = loop {
+            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
+                break Ok(());
+            }
+            let mut case_test = Expr::Empty;
+            if self.current_token.is_keyword(TokenKeyword::Case) {
+                self.next_token(); //case
+                case_test = match self.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(e) => break Err(e),
+                };
+            } else if self.current_token.is_keyword(TokenKeyword::Default) {
+                if has_default {
+                    break Err(self.err("More than one default clause in switch statement"));
                 }
-                if allow_break {
-                    self.allow_break = false;
+                has_default = true;
+                self.next_token(); //default
+            } else {
+                break Err(self.err("Unexpected token"));
+            }
+            if let Err(e) = self.expect_ptor(TokenPunctuator::Colon) {
+                break Err(e);
+            }
+            let mut v_body = Vec::new();
+            while !self.current_token.is_keyword(TokenKeyword::Case)
+                && !self.current_token.is_keyword(TokenKeyword::Default)
+                && !self.current_token.is_ptor(TokenPunctuator::RCParen)
+            {
+                if self.current_token.is_eof(false) {
+                    return Err(self.err("Unexpected end of input"));
                 }
-                v.push(Expr::SwitchCase(Box::new(case_test), v_body));
-                if is_default {
-                    break;
+                match self.parse() {
+                    Ok(Expr::Empty) => {}
+                    Ok(expr) => v_body.push(expr),
+                    Err(e) => return Err(e),
                 }
             }
-        }
-        if !self.current_token.is_ptor(TokenPunctuator::RCParen) {
-            return Err(self.err("Unexpected token"));
-        }
+            v.push(Expr::SwitchCase(Box::new(case_test), v_body));
+        };
+        self.allow_break = allow_break;
+        result?;
         self.next_token(); //}
         Ok(Expr::Switch(Box::new(test), v))
     }
+
     fn parse_for_slot(&mut self) -> Result<Expr, String> {
         self.next_token(); //for
-        if !self.current_token.is_ptor(TokenPunctuator::LParen) {
-            return Err(self.err("Unexpected token"));
+        let is_await =
+            self.in_async && self.current_token.is_ident() && self.current_token.raw == "await";
+        if is_await {
+            self.next_token(); //await
         }
-        self.next_token(); //(
-
-        // Expr::For((), (), (), ())
-        let mut v = Vec::new();
-        for t in 0..3 {
-            if v.len() == 2 && self.current_token.is_ptor(TokenPunctuator::RParen) {
-                //for(;;)
-                v.push(Expr::Empty);
-                break;
-            }
-            let binary = self.parse(false)?;
-            if !self.is_valid(&binary) && !matches!(binary, Expr::Empty) {
-                dbg!(&binary);
-                return Err(self.err(&format!("Unexpected token {:?}", binary.to_raw())));
-            }
-            if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-                if t == 2 {
-                    //[最后]不能有;
-                    return Err(self.err(&format!("Unexpected token {:?}", self.current_token.raw)));
-                }
-                self.next_token();
+        self.expect_ptor(TokenPunctuator::LParen)?; //(
+        let allow_in = self.allow_in;
+        self.allow_in = false;
+        let init = if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
+            Ok(Expr::Empty)
+        } else if self.current_token.is_keyword(TokenKeyword::Let)
+            || self.current_token.is_keyword(TokenKeyword::Var)
+            || self.current_token.is_keyword(TokenKeyword::Const)
+        {
+            self.parse_declaration(true)
+        } else {
+            self.parse_expression()
+        };
+        self.allow_in = allow_in;
+        let init = init?;
+        if is_await {
+            if !self.current_token.is_keyword(TokenKeyword::Of) {
+                return Err(self.err("Unexpected token"));
             }
-            v.push(binary);
+            return match self.parse_for_in_of_slot(init)? {
+                Expr::ForOf(left, right, body) => Ok(Self::for_await(*left, *right, *body)),
+                _ => unreachable!(),
+            };
         }
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
-            return Err(self.err(&format!("Unexpected token {:?}", self.current_token.desc())));
+        if self.is_for_in_of() {
+            return self.parse_for_in_of_slot(init);
         }
-        self.next_token(); //)
-
-        let line = self.current_token.line;
-        self.allow_break = true;
-        self.allow_continue = true;
-        let body = self.parse_body_slot(true)?;
-        self.allow_break = false;
-        self.allow_continue = false;
-
-        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-            self.next_token();
-        } else if line == self.current_token.line {
-            return Err(self.err("Unexpected token"));
+        self.expect_ptor(TokenPunctuator::Semicolon)?;
+        let mut test = Expr::Empty;
+        if !self.current_token.is_ptor(TokenPunctuator::Semicolon) {
+            test = self.parse_expression()?;
+        }
+        self.expect_ptor(TokenPunctuator::Semicolon)?;
+        let mut update = Expr::Empty;
+        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
+            update = self.parse_expression()?;
         }
+        self.expect_ptor(TokenPunctuator::RParen)?; //)
+        let body = self.parse_loop_body()?;
         Ok(Expr::For(
-            Box::new(v[0].clone()),
-            Box::new(v[1].clone()),
-            Box::new(v[2].clone()),
+            Box::new(init),
+            Box::new(test),
+            Box::new(update),
             Box::new(body),
         ))
     }
-    fn parse_if_slot(&mut self) -> Result<Expr, String> {
-        self.next_token(); //skip if
-        if !self.current_token.is_ptor(TokenPunctuator::LParen) {
-            return Err(self.err("Unexpected token"));
-        }
-        self.next_token(); //(
-        let binary = self.parse(false)?;
-        if !self.is_valid(&binary) {
-            return Err(self.err("Unexpected token"));
-        }
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
-            return Err(self.err("Unexpected token"));
-        }
-        self.next_token(); //)
-        let line = self.current_token.line;
-        let left_expr = self.parse_body_slot(true)?;
 
-        let mut flag = false;
-        if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-            self.next_token();
-            flag = true;
-        }
-        let mut right_expr = Expr::Empty;
-        if self.current_token.is_keyword(TokenKeyword::Else) {
-            if self.current_token.line == line && !flag {
-                return Err(self.err("Unexpected token else"));
+    /// 数字字面量的值, 0开头且只有0-7的整数为旧式八进制 017
+    fn number_literal(&self, t: &str) -> Result<f64, String> {
+        if t.len() > 1 && t.starts_with('0') && t.bytes().all(|c| c.is_ascii_digit()) {
+            if self.strict {
+                return Err(self.err(if t.bytes().all(|c| c < b'8') {
+                    "Octal literals are not allowed in strict mode."
+                } else {
+                    "Decimals with leading zeros are not allowed in strict mode."
+                }));
             }
-            self.next_token(); //else
-            if self.current_token.is_keyword(TokenKeyword::If) {
-                let expr = self.parse_if_slot()?;
-                return Ok(Expr::If(
-                    Box::new(binary),
-                    Box::new(left_expr),
-                    Box::new(expr),
-                ));
-            } else {
-                let line = self.current_token.line;
-                right_expr = self.parse_body_slot(true)?;
-
-                if self.current_token.is_ptor(TokenPunctuator::Semicolon) {
-                    self.next_token();
-                } else if line == self.current_token.line {
-                    return Err(self.err("Unexpected token"));
-                }
+            if let Some(n) = digits_to_number(t, 8) {
+                return Ok(n);
             }
         }
-        Ok(Expr::If(
-            Box::new(binary),
-            Box::new(left_expr),
-            Box::new(right_expr),
-        ))
+        let n = string_to_number(t);
+        if n.is_nan() {
+            return Err(self.err("Invalid or unexpected token"));
+        }
+        Ok(n)
     }
 
-    fn parse_call_slot(&mut self, callee: &mut Expr) -> Result<Expr, String> {
-        self.next_token(); //(
-        if self.current_token.is_ptor(TokenPunctuator::RParen) {
-            self.next_token(); //)
-            return Ok(Expr::Call(Box::new(callee.clone()), Vec::new()));
+    /// BigInt字面量的值, 不能以0开头 01n
+    fn bigint_literal(&self, t: &str) -> Result<BigInt, String> {
+        let digits = &t[..t.len() - 1];
+        if digits.len() > 1 && digits.starts_with('0') && digits.bytes().all(|c| c.is_ascii_digit())
+        {
+            return Err(self.err("Invalid BigInt syntax"));
         }
+        string_to_bigint(&digits.into()).ok_or_else(|| self.err("Invalid BigInt syntax"))
+    }
 
-        let mut v = Vec::new();
-        let mut gen_json = self.gen_json;
-        if !gen_json {
-            gen_json = true;
-            self.gen_json = true;
-        }
-        let expr = self.parse(true)?;
-        v.push(expr);
-        if gen_json {
-            self.gen_json = false;
-        }
-        while self.current_token.is_ptor(TokenPunctuator::Comma) {
-            self.next_token();
-            let expr = self.parse(true)?;
-            if !self.is_valid(&expr) {
-                return Err(self.err("Unexpected token"));
+    /// for await(a of b) 展开为
+    /// { const it = GetIterator(b, async); while(true){ const r = await it.next(); if(r.done) break; a = r.value; ... } }
+    /// 展开中使用的变量名含空格, 不会与代码中的变量冲突
+    fn for_await(left: Expr, right: Expr, body: Expr) -> Expr {
+        let iterator = "for await iterator".to_string();
+        let result = "for await result".to_string();
+        let member = |name: &str, key: &str| {
+            Expr::Member(
+                Box::new(Expr::Identifier(name.to_string())),
+                Box::new(Expr::Literal(key.into())),
+            )
+        };
+        let get_iterator = Expr::Call(
+            Box::new(Expr::Identifier(ASYNC_ITERATOR_OF.to_string())),
+            vec![right],
+        );
+        let next = Expr::Await(Box::new(Expr::Call(
+            Box::new(member(&iterator, "next")),
+            Vec::new(),
+        )));
+        let value = member(&result, "value");
+        let binding = match left {
+            Expr::Variable(mut v) => {
+                v[0].2 = value;
+                Expr::Variable(v)
             }
-            v.push(expr);
-        }
-        if !self.current_token.is_ptor(TokenPunctuator::RParen) {
-            return Err(self.err("Unexpected end of input"));
-        }
-        self.next_token(); //)
-        return Ok(Expr::Call(Box::new(callee.clone()), v));
+            left => Expr::Assignment(Box::new(left), Box::new(value)),
+        };
+        let step = Expr::Block(vec![
+            Expr::Variable(vec![(Variable::Const, result.clone(), next)]),
+            Expr::If(
+                Box::new(member(&result, "done")),
+                Box::new(Expr::Break(None)),
+                Box::new(Expr::Empty),
+            ),
+            binding,
+            body,
+        ]);
+        Expr::Block(vec![
+            Expr::Variable(vec![(Variable::Const, iterator, get_iterator)]),
+            Expr::While(Box::new(Expr::Bool(true)), Box::new(step)),
+        ])
     }
-    fn parse_member_slot(&mut self, mem: &mut Expr) -> Result<(), String> {
-        let t = self.next_token(); // . or [
-        if t.is_ptor(TokenPunctuator::Dot) {
-            if self.current_token.is_ident() {
-                let ident = self.next_token();
-                if let Expr::Member(_, property) = mem {
-                    *property = Box::new(Expr::Identifier(ident.raw));
-                    return Ok(());
-                }
-            }
-        } else if t.is_ptor(TokenPunctuator::LSParen) {
-            let expr = self.parse(true)?;
-            if !self.is_valid(&expr) {
-                return Err(self.err("Unexpected token"));
+
+    /// 当前是否为 in / of
+    fn is_for_in_of(&self) -> bool {
+        self.current_token.is_keyword(TokenKeyword::In)
+            || self.current_token.is_keyword(TokenKeyword::Of)
+    }
+
+    /// for(let a in b) / for(a.b of c)
+    fn parse_for_in_of_slot(&mut self, left: Expr) -> Result<Expr, String> {
+        let is_of = !self.current_token.is_keyword(TokenKeyword::In);
+        let loop_name = if is_of { "for-of" } else { "for-in" };
+        match &left {
+            Expr::Variable(v) if v.len() != 1 => {
+                return Err(self.err(&format!(
+                    "Invalid left-hand side in {} loop: Must have a single binding.",
+                    loop_name
+                )));
             }
-            let mut v = Vec::new();
-            v.push(expr);
-            while self.current_token.is_ptor(TokenPunctuator::Comma) {
-                self.next_token();
-                let e = self.parse(true)?;
-                if !self.is_valid(&e) {
-                    return Err(self.err("Unexpected token"));
-                }
-                v.push(e);
+            Expr::Variable(v) if v[0].2 != Expr::Empty => {
+                return Err(self.err(&format!(
+                    "{} loop variable declaration may not have an initializer.",
+                    loop_name
+                )));
             }
-            if v.len() == 1 {
-                if let Expr::Member(_, property) = mem {
-                    *property = Box::new(v.get(0).unwrap().clone());
-                } else {
-                    return Err(self.err("未解析或异常"));
-                }
-            } else {
-                if let Expr::Member(_, property) = mem {
-                    *property = Box::new(Expr::Sequence(v));
-                } else {
-                    return Err(self.err("未解析或异常"));
-                }
+            Expr::Variable(_) | Expr::Identifier(_) | Expr::Member(_, _) => {
+                self.check_assign_target(&left)?
             }
-            if !self.current_token.is_ptor(TokenPunctuator::RSParen) {
-                return Err(self.err("Unexpected end of input"));
+            _ => {
+                return Err(self.err(&format!("Invalid left-hand side in {} loop", loop_name)));
             }
-            self.next_token(); //]
         }
-        Ok(())
-    }
-    /// 这里还要处理多级 如: a()[1]  a[1]()    a[1]()[1]()...
-    fn parse_call_or_member(&mut self, prefix: Option<Expr>) -> Result<Expr, String> {
-        let mut id = Expr::Empty;
-        if prefix.is_none() {
-            let token = self.next_token();
-            id = Expr::Identifier(token.raw.clone())
+        self.next_token(); //in of
+        let right = if is_of {
+            self.parse_assign()?
         } else {
-            id = prefix.unwrap();
-        }
-        let mut expr = Expr::Empty;
-        loop {
-            if self.current_token.is_eof(true) {
-                break;
-            }
-            if self.current_token.is_ptor(TokenPunctuator::RCParen) {
-                break;
-            } else if self.current_token.is_ptor(TokenPunctuator::RParen) {
-                break;
-            }
-            if self.current_token.is_ptor(TokenPunctuator::LParen) {
-                //(
-                if Expr::Empty == expr {
-                    expr = id.clone();
-                } else {
-                    // let line = self.current_token.line;
-                    expr = self.parse_call_slot(&mut expr)?;
-
-                    // if self.current_token.line == line {
-                    //     if self.current_token.checked_keyword(){
-                    //         break;
-                    //     }
-                    //    //println!("{:?}",self.current_token);
-                    // }
-                    // break;
-                }
-            } else if self.current_token.is_ptor(TokenPunctuator::LSParen)
-                || self.current_token.is_ptor(TokenPunctuator::Dot)
-            {
-                //[
-                if Expr::Empty == expr {
-                    expr = Expr::Member(Box::new(id.clone()), Box::new(Expr::Empty));
-                } else {
-                    expr = Expr::Member(Box::new(expr), Box::new(Expr::Empty));
-                }
-                let _ = self.parse_member_slot(&mut expr);
-            } else {
-                break;
-            }
+            self.parse_expression()?
+        };
+        self.expect_ptor(TokenPunctuator::RParen)?; //)
+        let body = self.parse_loop_body()?;
+        let (left, right, body) = (Box::new(left), Box::new(right), Box::new(body));
+        if is_of {
+            Ok(Expr::ForOf(left, right, body))
+        } else {
+            Ok(Expr::ForIn(left, right, body))
         }
-        Ok(expr)
     }
 
-    fn parse_unary(&mut self) -> Result<Unary, String> {
-        match &self.current_token.typ {
-            TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::Not => Ok(Unary::Not),
-                TokenPunctuator::Plus => Ok(Unary::Plus),
-                TokenPunctuator::Minus => Ok(Unary::Minus),
-                TokenPunctuator::BitNot => Ok(Unary::BitNot),
-                _ => Err(self.err("Unexpected token")),
-            },
-            _ => Err(self.err("Unexpected token")),
+    fn parse_if_slot(&mut self) -> Result<Expr, String> {
+        self.next_token(); //skip if
+        let test = self.parse_paren_test()?;
+        let left_expr = self.parse_body_slot(true)?;
+        let mut right_expr = Expr::Empty;
+        if self.current_token.is_keyword(TokenKeyword::Else) {
+            self.next_token(); //else
+            right_expr = self.parse_body_slot(true)?;
         }
+        Ok(Expr::If(
+            Box::new(test),
+            Box::new(left_expr),
+            Box::new(right_expr),
+        ))
     }
 
     fn get_operator(&self, token: &Token) -> Operator {
-        let op = match &token.typ {
+        match &token.typ {
             TokenType::Punctuator(t) => match t {
                 TokenPunctuator::Plus => Operator::Plus,
                 TokenPunctuator::Minus => Operator::Subtract,
@@ -1088,11 +1848,12 @@ impl<T: ILexer> Parser<T> {
                 TokenPunctuator::Modulo => Operator::Modulo,
                 TokenPunctuator::Or => Operator::Or,
                 TokenPunctuator::And => Operator::And,
-                TokenPunctuator::Not => Operator::Not,
                 TokenPunctuator::LShift => Operator::LShift,
                 TokenPunctuator::RShift => Operator::RShift,
                 TokenPunctuator::Equal => Operator::Equal,
                 TokenPunctuator::NE => Operator::NE,
+                TokenPunctuator::Congruent => Operator::Congruent,
+                TokenPunctuator::NotCongruent => Operator::NotCongruent,
                 TokenPunctuator::GT => Operator::GT,
                 TokenPunctuator::GTE => Operator::GTE,
                 TokenPunctuator::LT => Operator::LT,
@@ -1109,66 +1870,73 @@ impl<T: ILexer> Parser<T> {
 
                 _ => unreachable!(),
             },
+            TokenType::Keyword(TokenKeyword::Instanceof) => Operator::Instanceof,
+            TokenType::Keyword(TokenKeyword::In) => Operator::In,
             _ => unreachable!(),
-        };
-        op
+        }
     }
-    fn get_precedence(&self, typ: TokenType) -> Precedence {
+    fn get_precedence(&self, typ: &TokenType) -> Precedence {
         match typ {
             TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::Plus | TokenPunctuator::Minus => Precedence::Sum,
-                TokenPunctuator::Modulo => Precedence::Modulo,
-                TokenPunctuator::Multiply | TokenPunctuator::Divide => Precedence::Product,
                 TokenPunctuator::Or => Precedence::Or,
                 TokenPunctuator::And => Precedence::And,
-                TokenPunctuator::Not => Precedence::Prefix,
-                TokenPunctuator::LShift | TokenPunctuator::RShift => Precedence::Shift,
-                TokenPunctuator::Equal | TokenPunctuator::NE => Precedence::Equality,
+                TokenPunctuator::BitOr => Precedence::BitOr,
+                TokenPunctuator::BitXor => Precedence::BitXor,
+                TokenPunctuator::BitAnd => Precedence::BitAnd,
+                TokenPunctuator::Equal
+                | TokenPunctuator::NE
+                | TokenPunctuator::Congruent
+                | TokenPunctuator::NotCongruent => Precedence::Equality,
                 TokenPunctuator::GT
                 | TokenPunctuator::GTE
                 | TokenPunctuator::LT
                 | TokenPunctuator::LTE => Precedence::Comparison,
-                TokenPunctuator::BitOr => Precedence::BitOr,
-                TokenPunctuator::BitXor => Precedence::BitXor,
-                TokenPunctuator::BitAnd => Precedence::BitAnd,
-                TokenPunctuator::ADD
-                | TokenPunctuator::SUB
-                | TokenPunctuator::MUL
-                | TokenPunctuator::DIV => Precedence::MOV,
+                TokenPunctuator::LShift | TokenPunctuator::RShift => Precedence::Shift,
+                TokenPunctuator::Plus | TokenPunctuator::Minus => Precedence::Sum,
+                TokenPunctuator::Multiply | TokenPunctuator::Divide | TokenPunctuator::Modulo => {
+                    Precedence::Product
+                }
                 _ => Precedence::Lowest,
             },
+            TokenType::Keyword(TokenKeyword::Instanceof) => Precedence::Comparison,
+            //for(a in b) 的初始化部分不能包含in
+            TokenType::Keyword(TokenKeyword::In) if self.allow_in => Precedence::Comparison,
             _ => Precedence::Lowest,
         }
     }
-    fn get_precedence_by_operator(&self, typ: &Operator) -> Precedence {
-        match typ {
-            Operator::Plus | Operator::Subtract => Precedence::Sum,
-            Operator::Multiply | Operator::Divide => Precedence::Product,
-            Operator::Modulo => Precedence::Modulo,
-            Operator::ADD | Operator::SUB | Operator::MUL | Operator::DIV | Operator::MOD => {
-                Precedence::MOV
-            }
-            _ => unreachable!(),
-        }
-    }
 }
 
-/// 不处理a++等未定义行为
-#[derive(Debug, PartialEq, PartialOrd)]
+/// 优先级从低到高
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 enum Precedence {
     Lowest,
     Or,         // ||
     And,        // &&
-    Equality,   // ==, !=
-    Comparison, // <, >, <=, >=
     BitOr,      // |
     BitXor,     // ^
     BitAnd,     // &
+    Equality,   // ==, !=, ===, !==
+    Comparison, // <, >, <=, >=, instanceof
     Shift,      // <<, >>
     Sum,        // + -
-    Modulo,     // %
-    Product,    // * /
-    Prefix,     //
+    Product,    // * / %
+}
 
-    MOV, //+= -= *= /=  %=
+/// 旧式八进制转义 \1 \01 及 \8 \9, 严格模式下不允许
+fn legacy_escape(raw: &str) -> Option<&'static str> {
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        match chars.next() {
+            Some('0') if !chars.peek().is_some_and(char::is_ascii_digit) => {}
+            Some('0'..='7') => {
+                return Some("Octal escape sequences are not allowed in strict mode.")
+            }
+            Some('8' | '9') => return Some("\\8 and \\9 are not allowed in strict mode."),
+            _ => {}
+        }
+    }
+    None
 }
@@ -1,31 +1,155 @@
-use super::expr::{Expr, Operator, Variable};
+use super::builtins::{iso_string, Intrinsics, Random};
+use super::expr::{
+    ExportDeclaration, Expr, Function, ObjectMember, Operator, PropertyName, TemplateStrings,
+    Unary, Variable,
+};
+use super::http::HttpClient;
+use super::module::ModuleLoader;
+use super::object::{
+    Closure, FunctionFrame, FunctionKind, JSObject, JSSymbol, ModuleBinding, ObjectId, ObjectKind,
+    Property, PropertyKey, PropertyMap, PropertyValue, Scope, ScopeId,
+};
+use super::string::{unescape, JSString};
 use super::utility::err;
-use std::collections::HashMap;
+use bigint::compare_bigint_number;
+use num_bigint::BigInt;
+use num_traits::Zero;
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+mod bigint;
+mod class;
+mod collection;
+mod commonjs;
+mod descriptor;
+mod event_loop;
+mod fetch;
+mod gc;
+mod generator;
+mod iteration;
+mod json;
+mod module;
+mod promise;
+mod proxy;
+mod typed_array;
+
+pub(crate) use bigint::{bigint_to_number, number_to_bigint, string_to_bigint};
+pub(crate) use collection::MapData;
+use commonjs::CommonJs;
+pub(crate) use descriptor::PropertyDescriptor;
+use event_loop::EventLoop;
+#[allow(unused_imports)] //lib中未使用
+pub use event_loop::VirtualClock;
+pub use event_loop::{default_clock, Clock};
+use generator::Tape;
+pub(crate) use generator::{GeneratorState, ResumeKind};
+use module::{is_module, ModuleId, ModuleRecord, DEFAULT_BINDING};
+use promise::Job;
+pub use promise::RejectionCallback;
+pub(crate) use promise::{Combinator, PromiseData, PromiseFunction, PromiseState};
+pub(crate) use proxy::ProxyData;
+pub(crate) use typed_array::{BufferView, TypedArrayKind};
+
+/// 外部注册的全局方法
+pub type HostCallback = dyn Fn(&mut Program, Vec<JSType>) -> Result<JSType, String> + Send + Sync;
+type HostFn = Arc<HostCallback>;
+
+/// 最大调用深度,超出时抛出 RangeError
+const MAX_CALL_DEPTH: usize = 2000;
+
+/// 执行线程的栈大小未指定时按std::thread的默认值估计, 宿主应通过set_stack_size指定实际大小
+#[cfg(not(target_arch = "wasm32"))]
+const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+#[cfg(target_arch = "wasm32")]
+const DEFAULT_STACK_SIZE: usize = 1024 * 1024;
+
+/// 调用时剩余的栈少于该值则抛出 RangeError, 避免栈溢出
+const STACK_RESERVE: usize = 256 * 1024;
+
+/// 数组下标超出list末尾的距离不小于该值时, 元素保存在properties中
+const MAX_ARRAY_GAP: usize = 1024;
 
 pub struct Program {
     statements: Vec<Expr>,
-    global_fn_map:
-        HashMap<String, Box<dyn Fn(Vec<JSType>) -> Result<JSType, String> + Send + 'static>>, //外部注册的全局方法
-    fn_map: HashMap<String, Expr>,                         //方法
-    global_value_map: HashMap<String, JSType>,             //外部注册的全局变量
-    local_value: Vec<HashMap<String, (Variable, JSType)>>, //HashMap<usize, HashMap<String, (Variable, JSType)>>, //变量
-    call_value: Vec<Vec<JSType>>,                          //函数变量(暂没用到)
-    block_index: usize,                                    //block层级下标
+    global_fn_map: HashMap<String, HostFn>, //外部注册的全局方法
+    heap: Vec<JSObject>,                    //对象
+    free_objects: Vec<ObjectId>,            //已回收可复用的对象
+    gc_threshold: usize,                    //存活对象超过该数量时自动回收
+    scopes: Vec<Scope>,                     //作用域
+    free_scopes: Vec<ScopeId>,              //可复用的作用域
+    scope: ScopeId,                         //当前作用域
+    pub(crate) intrinsics: Intrinsics,      //内置原型
+    private_name_count: usize,              //class私有属性名计数
+    symbol_count: usize,                    //Symbol计数
+    symbol_registry: HashMap<String, Arc<JSSymbol>>, //Symbol.for注册的Symbol
+    call_depth: usize,
+    stack_base: usize,                                 //最外层调用时的栈地址
+    stack_size: usize,                                 //执行线程的栈大小
+    tape: Option<Tape>,                                //生成器中包含yield的表达式
+    microtasks: VecDeque<Job>,                         //微任务队列
+    pending_rejections: Vec<ObjectId>,                 //被拒绝且未处理的Promise
+    rejection_handler: Option<Arc<RejectionCallback>>, //外部处理未捕获的Promise拒绝
+    thrown: Option<(String, JSType)>,                  //最近抛出的js值及错误信息
+    event_loop: EventLoop,                             //宏任务及定时器
+    http_client: Option<Arc<dyn HttpClient>>,          //fetch使用的http实现
+    modules: Vec<ModuleRecord>,                        //已加载的模块
+    module_map: HashMap<String, ModuleId>,             //模块名 => 模块
+    module_loader: Option<Arc<dyn ModuleLoader>>,      //import使用的模块加载
+    commonjs: Option<CommonJs>,                        //CommonJS模式, None时没有require
+    pub(crate) random: Random,                         //Math.random
+    pub(crate) join_stack: Vec<ObjectId>,              //正在join的数组, 循环引用时为空字符串
+    gc_roots: Vec<ObjectId>,                           //Rust中跨宏任务持有的对象
+    template_objects: HashMap<usize, (Arc<TemplateStrings>, ObjectId)>, //标签模板每个位置的strings数组
 }
 
 impl Program {
-    pub fn new(statements: Vec<Expr>) -> Self {
-        let mut local_value = Vec::new();
-        local_value.push(HashMap::new());
-        Program {
-            statements: statements,
+    pub fn new(statements: Vec<Expr>, strict: bool) -> Self {
+        let mut global = Scope::new(None);
+        global.function = Some(FunctionFrame {
+            this: Some(JSType::Undefined),
+            func: None,
+            new_target: JSType::Undefined,
+            home_object: None,
+            strict,
+        });
+        let mut program = Program {
+            statements,
             global_fn_map: HashMap::new(),
-            fn_map: HashMap::new(),
-            global_value_map: HashMap::new(),
-            local_value,
-            call_value: Vec::new(),
-            block_index: 0,
-        }
+            heap: Vec::new(),
+            free_objects: Vec::new(),
+            gc_threshold: gc::MIN_GC_THRESHOLD,
+            scopes: vec![global],
+            free_scopes: Vec::new(),
+            scope: 0,
+            intrinsics: Intrinsics::default(),
+            private_name_count: 0,
+            symbol_count: 0,
+            symbol_registry: HashMap::new(),
+            call_depth: 0,
+            stack_base: 0,
+            stack_size: DEFAULT_STACK_SIZE,
+            tape: None,
+            microtasks: VecDeque::new(),
+            pending_rejections: Vec::new(),
+            rejection_handler: None,
+            thrown: None,
+            event_loop: EventLoop::default(),
+            http_client: None,
+            modules: Vec::new(),
+            module_map: HashMap::new(),
+            module_loader: None,
+            commonjs: None,
+            random: Random::from_entropy(),
+            join_stack: Vec::new(),
+            gc_roots: Vec::new(),
+            template_objects: HashMap::new(),
+        };
+        program.init_builtins();
+        //脚本顶层的this为全局对象
+        let global = JSType::Object(program.intrinsics.global_object);
+        program.function_frame_mut().this = Some(global);
+        program
     }
 
     pub fn print_tree(&self) {
@@ -36,600 +160,2896 @@ impl Program {
         println!("/*-----tree-end------*/");
     }
 
-    pub fn run(&mut self) {
-        //需要最先加载所有方法
-        for (_, expr) in self.statements.clone().iter().enumerate() {
-            match &expr {
-                Expr::Function(ident, _, _) => {
-                    //首次运行时,需要先注册全局函数
-                    if let Expr::Identifier(t) = ident.as_ref() {
-                        self.fn_map.insert(t.clone(), expr.clone());
+    pub fn run(&mut self) -> Result<(), String> {
+        let statements = self.statements.clone();
+        let result = if is_module(&statements) {
+            self.run_main_module(statements)
+        } else {
+            //需要最先加载所有方法
+            self.hoist_declarations(&statements, true)?;
+            match self.run_top_level(&statements) {
+                Ok(true) => Ok(()),
+                Ok(false) => self
+                    .run_statements(&statements)
+                    .and_then(|_| self.run_until_idle()),
+                Err(msg) => Err(msg),
+            }
+        };
+        if let Err(msg) = result {
+            println!("\x1b[31m{}\x1b[39m", msg);
+            return Err(msg);
+        }
+        Ok(())
+    }
+
+    pub fn register_method(&mut self, ident: String, callback: Box<HostCallback>) {
+        let func = self.alloc(JSObject::new(
+            ObjectKind::Host(ident.clone()),
+            Some(self.intrinsics.function_prototype),
+        ));
+        self.define_property(
+            func,
+            "name",
+            Property::readonly(JSType::String(ident.clone().into())),
+        );
+        self.global_fn_map
+            .insert(ident.clone(), Arc::from(callback));
+        self.bind_value(ident, JSType::Object(func));
+    }
+
+    /// 全局变量, 作为全局对象不可枚举的属性
+    pub fn bind_value(&mut self, ident: String, value: JSType) {
+        let global = self.intrinsics.global_object;
+        self.define_property(global, ident, Property::hidden(value));
+    }
+
+    fn err(&self, str: &str) -> String {
+        err(str)
+    }
+
+    //------------------------------ 堆 ------------------------------
+
+    pub(crate) fn alloc(&mut self, obj: JSObject) -> ObjectId {
+        if let Some(id) = self.free_objects.pop() {
+            self.heap[id] = obj;
+            return id;
+        }
+        self.heap.push(obj);
+        self.heap.len() - 1
+    }
+    pub(crate) fn obj(&self, id: ObjectId) -> &JSObject {
+        &self.heap[id]
+    }
+    pub(crate) fn obj_mut(&mut self, id: ObjectId) -> &mut JSObject {
+        &mut self.heap[id]
+    }
+    pub fn new_object(&mut self) -> JSType {
+        let proto = self.intrinsics.object_prototype;
+        JSType::Object(self.alloc(JSObject::new(ObjectKind::Ordinary, Some(proto))))
+    }
+    pub fn new_array(&mut self, list: Vec<JSType>) -> JSType {
+        let proto = self.intrinsics.array_prototype;
+        let len = list.len();
+        let list = list.into_iter().map(Some).collect();
+        JSType::Object(self.alloc(JSObject::new(ObjectKind::Array(list, len), Some(proto))))
+    }
+    pub(crate) fn is_callable(&self, value: &JSType) -> bool {
+        match value {
+            JSType::Object(id) => self.obj(*id).is_callable(),
+            _ => false,
+        }
+    }
+    pub(crate) fn is_constructor(&self, value: &JSType) -> bool {
+        match value {
+            JSType::Object(id) => self.obj(*id).is_constructor(),
+            _ => false,
+        }
+    }
+
+    //------------------------------ 作用域 ------------------------------
+
+    fn enter_scope(&mut self, parent: ScopeId) -> ScopeId {
+        let scope = Scope::new(Some(parent));
+        let id = if let Some(id) = self.free_scopes.pop() {
+            self.scopes[id] = scope;
+            id
+        } else {
+            self.scopes.push(scope);
+            self.scopes.len() - 1
+        };
+        self.scope = id;
+        id
+    }
+    /// 离开作用域, 没有被闭包引用时可复用
+    fn leave_scope(&mut self, id: ScopeId, restore: ScopeId) {
+        self.scope = restore;
+        if !self.scopes[id].captured {
+            self.scopes[id] = Scope::default();
+            self.free_scopes.push(id);
+        }
+    }
+    /// 闭包引用了当前作用域链
+    fn capture_scope(&mut self, id: ScopeId) {
+        let mut cur = Some(id);
+        while let Some(i) = cur {
+            if self.scopes[i].captured {
+                break;
+            }
+            self.scopes[i].captured = true;
+            cur = self.scopes[i].parent;
+        }
+    }
+
+    /// var 所在的函数作用域
+    fn var_scope(&self) -> ScopeId {
+        let mut cur = self.scope;
+        loop {
+            if self.scopes[cur].function.is_some() {
+                return cur;
+            }
+            match self.scopes[cur].parent {
+                Some(p) => cur = p,
+                None => return cur,
+            }
+        }
+    }
+
+    fn function_frame(&self) -> &FunctionFrame {
+        let mut cur = self.scope;
+        loop {
+            if let Some(frame) = &self.scopes[cur].function {
+                return frame;
+            }
+            cur = self.scopes[cur].parent.unwrap_or(0);
+        }
+    }
+    fn function_frame_mut(&mut self) -> &mut FunctionFrame {
+        let mut cur = self.scope;
+        while self.scopes[cur].function.is_none() {
+            cur = self.scopes[cur].parent.unwrap_or(0);
+        }
+        self.scopes[cur].function.as_mut().unwrap()
+    }
+
+    /// 当前执行的代码是否为严格模式
+    fn is_strict(&self) -> bool {
+        self.function_frame().strict
+    }
+
+    /// let/const/class 声明在当前作用域, var/function 提升到函数作用域
+    fn declare(&mut self, typ: Variable, arg: &str, value: JSType) -> Result<(), String> {
+        if typ == Variable::Var {
+            let scope = self.var_scope();
+            self.scopes[scope]
+                .vars
+                .insert(arg.to_string(), (Variable::Var, value));
+            return Ok(());
+        }
+        let scope = &mut self.scopes[self.scope];
+        if scope.vars.contains_key(arg) || scope.imports.contains_key(arg) {
+            return Err(self.err(&format!(
+                "Uncaught SyntaxError: Identifier '{}' has already been declared",
+                arg
+            )));
+        }
+        scope.vars.insert(arg.to_string(), (typ, value));
+        Ok(())
+    }
+
+    /// 赋值给已存在的变量, 未声明时作为全局对象的属性, 严格模式下全局对象上也没有时抛出ReferenceError
+    fn bind_local_arg(&mut self, arg: &str, value: JSType) -> Result<(), String> {
+        let mut cur = Some(self.scope);
+        while let Some(i) = cur {
+            if let Some((v, val)) = self.scopes[i].vars.get_mut(arg) {
+                if *v == Variable::Const {
+                    return Err(self.err("Uncaught TypeError: Assignment to constant variable."));
+                }
+                *val = value;
+                return Ok(());
+            }
+            if self.scopes[i].imports.contains_key(arg) {
+                return Err(self.err("Uncaught TypeError: Assignment to constant variable."));
+            }
+            cur = self.scopes[i].parent;
+        }
+        let global = self.intrinsics.global_object;
+        let key = PropertyKey::from(arg);
+        if !self.is_strict() {
+            return self.set_property(&JSType::Object(global), key, value);
+        }
+        if !self.has_property(global, &key)? {
+            return Err(self.err(&format!("Uncaught ReferenceError: {} is not defined", arg)));
+        }
+        self.set_property_or_throw(&JSType::Object(global), &key, value)
+    }
+
+    /// 变量是否已声明或为全局对象的属性
+    fn has_binding(&mut self, key: &str) -> Result<bool, String> {
+        let mut cur = Some(self.scope);
+        while let Some(i) = cur {
+            if self.scopes[i].vars.contains_key(key) || self.scopes[i].imports.contains_key(key) {
+                return Ok(true);
+            }
+            cur = self.scopes[i].parent;
+        }
+        let global = self.intrinsics.global_object;
+        self.has_property(global, &PropertyKey::from(key))
+    }
+
+    /// 作用域链中的变量, 最后查找全局对象(包括其原型链)
+    fn get_value(&mut self, key: &str) -> Result<JSType, String> {
+        let mut cur = Some(self.scope);
+        while let Some(i) = cur {
+            if let Some(v) = self.scopes[i].vars.get(key) {
+                return Ok(v.1.clone());
+            }
+            if self.scopes[i].imports.contains_key(key) {
+                return self.binding_value(i, key);
+            }
+            cur = self.scopes[i].parent;
+        }
+        let global = self.intrinsics.global_object;
+        if self.has_property(global, &PropertyKey::from(key))? {
+            return self.get_property(&JSType::Object(global), key);
+        }
+        Err(self.err(&format!("Uncaught ReferenceError: {} is not defined", key)))
+    }
+
+    fn get_this(&self) -> Result<JSType, String> {
+        match &self.function_frame().this {
+            Some(this) => Ok(this.clone()),
+            None => Err(self.err("Uncaught ReferenceError: Must call super constructor in derived class before accessing 'this' or returning from derived constructor")),
+        }
+    }
+
+    /// 变量提升: var 及 function 声明
+    fn hoist_declarations(&mut self, statements: &[Expr], is_function: bool) -> Result<(), String> {
+        if is_function {
+            let mut names = Vec::new();
+            for stmt in statements {
+                collect_var_names(stmt, &mut names);
+            }
+            let scope = self.var_scope();
+            for name in names {
+                self.scopes[scope]
+                    .vars
+                    .entry(name)
+                    .or_insert((Variable::Var, JSType::Undefined));
+            }
+        }
+        for stmt in statements {
+            //export function f(){}  export default function(){}
+            let (stmt, default) = match stmt {
+                Expr::Export(export) => match export.as_ref() {
+                    ExportDeclaration::Declaration(decl) => (decl, false),
+                    ExportDeclaration::Default(expr) => (expr, true),
+                    _ => continue,
+                },
+                _ => (stmt, false),
+            };
+            if let Expr::Function(f) = stmt {
+                if default && f.name.is_none() {
+                    let func =
+                        self.create_function(f.clone(), FunctionKind::Normal, None, "default");
+                    self.scopes[self.scope].vars.insert(
+                        DEFAULT_BINDING.to_string(),
+                        (Variable::Var, JSType::Object(func)),
+                    );
+                }
+                if let Some(name) = &f.name {
+                    let func = self.create_function(f.clone(), FunctionKind::Normal, None, name);
+                    let typ = if is_function {
+                        Variable::Var
                     } else {
-                        panic!("expr::function");
-                    }
+                        Variable::Let
+                    };
+                    self.scopes[self.scope]
+                        .vars
+                        .insert(name.clone(), (typ, JSType::Object(func)));
                 }
-                _ => {}
             }
         }
-        for (_, expr) in self.statements.clone().iter().enumerate() {
-            match &expr {
-                Expr::Function(_, _, _) => {
-                    //这里要跳过function,当call执行时才会调用
+        Ok(())
+    }
+
+    /// 执行语句列表, 遇到 break/continue/return 时提前返回
+    fn run_statements(&mut self, statements: &[Expr]) -> Result<JSType, String> {
+        for stmt in statements {
+            match stmt {
+                //函数声明已提升
+                Expr::Function(f) if f.name.is_some() => continue,
+                Expr::Class(c) if c.name.is_some() => {
+                    let class = self.parse_class(c, None)?;
+                    let name = c.name.clone().unwrap_or_default();
+                    self.declare(Variable::Let, &name, class)?;
                     continue;
                 }
-                _ => {
-                    let result = self.parse(expr);
-                    match result {
-                        Ok(res) => {
-                            // self.reset_index_value(0);
-                            // println!("{:?}",res);
-                        }
-                        Err(msg) => {
-                            println!("\x1b[31m{}\x1b[39m", msg);
-                            return;
-                        }
+                _ => {}
+            }
+            let result = self.parse(stmt)?;
+            if let JSType::Flag(_) = result {
+                return Ok(result);
+            }
+        }
+        Ok(JSType::Undefined)
+    }
+
+    //------------------------------ 属性 ------------------------------
+
+    pub(crate) fn define_property(
+        &mut self,
+        id: ObjectId,
+        key: impl Into<PropertyKey>,
+        prop: Property,
+    ) {
+        let key = key.into();
+        //类型化数组的数字下标只能写入元素
+        if let Some(index) = self.integer_index(id, &key) {
+            if let (Some(index), PropertyValue::Data(value)) = (index, &prop.value) {
+                self.typed_array_set(id, index, value);
+            }
+            return;
+        }
+        let obj = self.obj_mut(id);
+        if let ObjectKind::Array(list, length) = &mut obj.kind {
+            if key.is("length") {
+                if let Some(len) = array_length(&prop.data_value()) {
+                    truncate_array(list, length, &mut obj.properties, len);
+                }
+                //length只记录是否可写
+                if prop.writable {
+                    obj.properties.remove(&key);
+                } else {
+                    obj.properties.insert(key, prop);
+                }
+                return;
+            }
+            if let Some(index) = key.array_index() {
+                *length = (*length).max(index + 1);
+                //距离末尾太远的下标保存在properties中, 不为中间的空位分配
+                if index >= list.len() + MAX_ARRAY_GAP {
+                    obj.properties.insert(key, prop);
+                    return;
+                }
+                if index >= list.len() {
+                    list.resize(index + 1, None);
+                }
+                //特殊的属性保存在properties中, list中为空位
+                match &prop.value {
+                    PropertyValue::Data(value)
+                        if prop.writable && prop.enumerable && prop.configurable =>
+                    {
+                        list[index] = Some(value.clone());
+                        obj.properties.remove(&key);
+                        return;
+                    }
+                    _ => list[index] = None,
+                }
+            }
+        }
+        obj.properties.insert(key, prop);
+    }
+
+    /// 数组的length不可写时, 不能添加不小于length的下标
+    pub(crate) fn exceeds_fixed_length(&self, id: ObjectId, key: &PropertyKey) -> bool {
+        let obj = self.obj(id);
+        let ObjectKind::Array(_, length) = &obj.kind else {
+            return false;
+        };
+        let fixed = obj
+            .properties
+            .get(&PropertyKey::from("length"))
+            .is_some_and(|p| !p.writable);
+        fixed && key.array_index().is_some_and(|index| index >= *length)
+    }
+
+    /// 自身属性(不查找原型链)
+    pub(crate) fn get_own_property(&self, id: ObjectId, key: &PropertyKey) -> Option<Property> {
+        let obj = self.obj(id);
+        if let ObjectKind::Namespace(list) = &obj.kind {
+            //导出名之外只有 [Symbol.toStringTag]
+            let Some((_, binding)) = list.iter().find(|(name, _)| key.is(name)) else {
+                return obj.properties.get(key).cloned();
+            };
+            let value = match binding {
+                ModuleBinding::Variable(scope, name) => self
+                    .binding_value(*scope, name)
+                    .unwrap_or(JSType::Undefined),
+                ModuleBinding::Value(value) => value.clone(),
+            };
+            let mut prop = Property::data(value);
+            prop.configurable = false;
+            return Some(prop);
+        }
+        if let Some(index) = self.integer_index(id, key) {
+            return index.map(|index| Property::data(self.typed_array_get(id, index)));
+        }
+        if let ObjectKind::Array(list, length) = &obj.kind {
+            if key.is("length") {
+                let mut prop = Property::data(JSType::Int(*length as i64));
+                prop.writable = obj.properties.get(key).is_none_or(|p| p.writable);
+                prop.enumerable = false;
+                prop.configurable = false;
+                return Some(prop);
+            }
+            if let Some(index) = key.array_index() {
+                if let Some(Some(value)) = list.get(index) {
+                    return Some(Property::data(value.clone()));
+                }
+            }
+        }
+        obj.properties.get(key).cloned()
+    }
+
+    /// 自身属性名, 整数在前(升序) 其余字符串按插入顺序, 最后是Symbol
+    pub(crate) fn own_keys(&self, id: ObjectId) -> Vec<PropertyKey> {
+        let obj = self.obj(id);
+        let mut keys = Vec::new();
+        if let ObjectKind::Namespace(list) = &obj.kind {
+            keys.extend(list.iter().map(|(name, _)| PropertyKey::from(name)));
+        }
+        let mut index_keys: Vec<(usize, PropertyKey)> = obj
+            .properties
+            .keys()
+            .iter()
+            .filter_map(|k| k.array_index().map(|i| (i, k.clone())))
+            .collect();
+        if let ObjectKind::Array(list, _) = &obj.kind {
+            for (index, item) in list.iter().enumerate() {
+                if item.is_some() {
+                    index_keys.push((index, PropertyKey::String(index.to_string())));
+                }
+            }
+        }
+        if let ObjectKind::TypedArray(_, view) = &obj.kind {
+            index_keys.extend((0..view.length).map(|i| (i, PropertyKey::String(i.to_string()))));
+        }
+        index_keys.sort_by_key(|(i, _)| *i);
+        keys.extend(index_keys.into_iter().map(|(_, k)| k));
+        let is_array = matches!(obj.kind, ObjectKind::Array(..));
+        if is_array {
+            keys.push(PropertyKey::from("length"));
+        }
+        let names = obj.properties.keys().iter();
+        keys.extend(
+            names
+                .clone()
+                .filter(|k| k.as_str().is_some() && k.array_index().is_none())
+                .filter(|k| !(is_array && k.is("length")))
+                .cloned(),
+        );
+        keys.extend(names.filter(|k| k.as_str().is_none()).cloned());
+        keys
+    }
+
+    /// [[GetOwnProperty]], Proxy调用getOwnPropertyDescriptor
+    pub(crate) fn own_property(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+    ) -> Result<Option<Property>, String> {
+        if self.is_proxy(id) {
+            return self.proxy_get_own_property(id, key);
+        }
+        Ok(self.get_own_property(id, key))
+    }
+
+    /// [[OwnPropertyKeys]], Proxy调用ownKeys
+    pub(crate) fn own_property_keys(&mut self, id: ObjectId) -> Result<Vec<PropertyKey>, String> {
+        if self.is_proxy(id) {
+            return self.proxy_own_keys(id);
+        }
+        Ok(self.own_keys(id))
+    }
+
+    /// 自身可枚举的字符串属性名
+    pub(crate) fn enumerable_own_keys(&mut self, id: ObjectId) -> Result<Vec<String>, String> {
+        let mut keys = Vec::new();
+        for key in self.own_property_keys(id)? {
+            if key.as_str().is_some() && self.own_property(id, &key)?.is_some_and(|p| p.enumerable)
+            {
+                keys.push(key.to_string());
+            }
+        }
+        Ok(keys)
+    }
+
+    fn proto_of_primitive(&self, target: &JSType) -> Option<ObjectId> {
+        match target {
+            JSType::String(_) => Some(self.intrinsics.string_prototype),
+            JSType::Int(_) | JSType::Float(_) => Some(self.intrinsics.number_prototype),
+            JSType::BigInt(_) => Some(self.intrinsics.bigint_prototype),
+            JSType::Bool(_) => Some(self.intrinsics.boolean_prototype),
+            JSType::Symbol(_) => Some(self.intrinsics.symbol_prototype),
+            _ => None,
+        }
+    }
+
+    pub fn get_property(
+        &mut self,
+        target: &JSType,
+        key: impl Into<PropertyKey>,
+    ) -> Result<JSType, String> {
+        let key = key.into();
+        match target {
+            JSType::Object(id) => self.get_with_receiver(*id, &key, target.clone()),
+            JSType::Undefined | JSType::NULL => Err(self.err(&format!(
+                "Uncaught TypeError: Cannot read properties of {} (reading '{}')",
+                target.type_name(),
+                key
+            ))),
+            JSType::String(s) => {
+                if key.is("length") {
+                    return Ok(JSType::Int(s.len() as i64));
+                }
+                if let Some(index) = key.array_index() {
+                    if index < s.len() {
+                        return Ok(JSType::String(s.slice(index, index + 1)));
                     }
                 }
+                let proto = self.intrinsics.string_prototype;
+                self.get_with_receiver(proto, &key, target.clone())
             }
+            _ => match self.proto_of_primitive(target) {
+                Some(proto) => self.get_with_receiver(proto, &key, target.clone()),
+                None => Ok(JSType::Undefined),
+            },
         }
     }
-    pub fn register_method(
+
+    /// 沿原型链查找, getter 的 this 为 receiver
+    pub(crate) fn get_with_receiver(
         &mut self,
-        ident: String,
-        callback: Box<dyn Fn(Vec<JSType>) -> Result<JSType, String> + Send + 'static>,
-    ) {
-        self.global_fn_map.insert(ident, callback);
+        id: ObjectId,
+        key: &PropertyKey,
+        receiver: JSType,
+    ) -> Result<JSType, String> {
+        let mut cur = Some(id);
+        while let Some(o) = cur {
+            if self.is_proxy(o) {
+                return self.proxy_get(o, key, receiver);
+            }
+            if let Some(prop) = self.get_own_property(o, key) {
+                return match prop.value {
+                    PropertyValue::Data(value) => Ok(value),
+                    PropertyValue::Accessor(get, _) => {
+                        if matches!(get, JSType::Undefined) {
+                            return Ok(JSType::Undefined);
+                        }
+                        self.call(&get, receiver, Vec::new())
+                    }
+                };
+            }
+            //类型化数组的数字下标不查找原型链
+            if self.integer_index(o, key).is_some() {
+                return Ok(JSType::Undefined);
+            }
+            cur = self.obj(o).proto;
+        }
+        Ok(JSType::Undefined)
     }
 
-    pub fn bind_value(&mut self, ident: String, value: JSType) {
-        self.global_value_map.insert(ident, value);
+    pub fn set_property(
+        &mut self,
+        target: &JSType,
+        key: impl Into<PropertyKey>,
+        value: JSType,
+    ) -> Result<(), String> {
+        let key = key.into();
+        match target {
+            JSType::Object(id) => self
+                .set_with_receiver(*id, &key, value, target.clone())
+                .map(|_| ()),
+            JSType::Undefined | JSType::NULL => Err(self.err(&format!(
+                "Uncaught TypeError: Cannot set properties of {} (setting '{}')",
+                target.type_name(),
+                key
+            ))),
+            _ => Ok(()),
+        }
     }
 
-    fn bind_local_arg(
+    /// 赋值表达式中的属性赋值, 严格模式下不能赋值时抛出TypeError
+    fn put_property(
         &mut self,
-        typ: Option<Variable>,
-        arg: String,
+        target: &JSType,
+        key: &PropertyKey,
         value: JSType,
     ) -> Result<(), String> {
-        // println!("{:?} {:?}={:?}", typ, arg, value);
-        let mut get_val = || -> Result<bool, String> {
-            while self.block_index + 1 > 0 {
-                if let Some((v, val)) = self.local_value[self.block_index].get_mut(&arg) {
-                    if typ.is_none() {
-                        *val = value.clone();
-                    } else if !(matches!(v, Variable::Var) && typ.clone().unwrap() == Variable::Var)
-                    {
-                        return Err(
-                            self.err("Uncaught TypeError: Assignment to constant variable.")
-                        );
-                    } else {
-                        *val = value.clone();
+        if !self.is_strict() {
+            return self.set_property(target, key, value);
+        }
+        self.set_property_or_throw(target, key, value)
+    }
+
+    /// Set(O, P, V, true), 不能赋值时抛出TypeError, 用于严格模式的赋值及数组方法
+    pub(crate) fn set_property_or_throw(
+        &mut self,
+        target: &JSType,
+        key: &PropertyKey,
+        value: JSType,
+    ) -> Result<(), String> {
+        match target {
+            JSType::Object(id) => {
+                if self.set_with_receiver(*id, key, value, target.clone())? {
+                    return Ok(());
+                }
+                //不可扩展的对象上添加新属性
+                if !self.is_proxy(*id)
+                    && !self.obj(*id).extensible
+                    && self.get_own_property(*id, key).is_none()
+                {
+                    return Err(self.err(&format!(
+                        "Uncaught TypeError: Cannot add property {}, object is not extensible",
+                        key
+                    )));
+                }
+                if self.exceeds_fixed_length(*id, key) {
+                    return Err(self.err(
+                        "Uncaught TypeError: Cannot assign to read only property 'length' of object '[object Array]'",
+                    ));
+                }
+                Err(self.err(&format!(
+                    "Uncaught TypeError: Cannot assign to read only property '{}' of object '{}'",
+                    key,
+                    self.display(target)
+                )))
+            }
+            JSType::Undefined | JSType::NULL => self.set_property(target, key, value),
+            _ => Err(self.err(&format!(
+                "Uncaught TypeError: Cannot create property '{}' on {} '{}'",
+                key,
+                target.type_name(),
+                self.display(target)
+            ))),
+        }
+    }
+
+    /// [[Set]], 属性只读或没有setter等不能赋值时返回false
+    pub(crate) fn set_with_receiver(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        value: JSType,
+        receiver: JSType,
+    ) -> Result<bool, String> {
+        let mut cur = Some(id);
+        while let Some(o) = cur {
+            if self.is_proxy(o) {
+                return self.proxy_set(o, key, value, receiver);
+            }
+            //类型化数组的数字下标不查找原型链, 越界时忽略
+            if let Some(index) = self.integer_index(o, key) {
+                if matches!(receiver, JSType::Object(r) if r == o) {
+                    let kind = self.typed_array_kind(o).unwrap();
+                    let value = self.element_value(kind, &value)?;
+                    if let Some(index) = index {
+                        self.typed_array_set(o, index, &value);
                     }
                     return Ok(true);
                 }
-                if self.block_index == 0 {
-                    break;
+                if index.is_none() {
+                    return Ok(true);
+                }
+                break;
+            }
+            if let Some(prop) = self.get_own_property(o, key) {
+                match prop.value {
+                    PropertyValue::Accessor(_, set) => {
+                        if matches!(set, JSType::Undefined) {
+                            return Ok(false);
+                        }
+                        self.call(&set, receiver, vec![value])?;
+                        return Ok(true);
+                    }
+                    PropertyValue::Data(_) => {
+                        if !prop.writable {
+                            return Ok(false);
+                        }
+                        break;
+                    }
                 }
-                self.block_index -= 1;
             }
-            Ok(false)
+            cur = self.obj(o).proto;
+        }
+        let JSType::Object(rid) = receiver else {
+            return Ok(false);
         };
+        //receiver为Proxy时通过其defineProperty写入
+        if self.is_proxy(rid) {
+            let desc = match self.own_property(rid, key)? {
+                Some(prop) => {
+                    if !prop.writable || matches!(prop.value, PropertyValue::Accessor(_, _)) {
+                        return Ok(false);
+                    }
+                    PropertyDescriptor {
+                        value: Some(value),
+                        ..Default::default()
+                    }
+                }
+                None => PropertyDescriptor::from(&Property::data(value)),
+            };
+            return self.define_own_property(rid, key, &desc);
+        }
+        let obj = self.obj_mut(rid);
+        if let ObjectKind::Array(list, length) = &mut obj.kind {
+            if key.is("length") {
+                let len = array_length(&value)
+                    .ok_or_else(|| err("Uncaught RangeError: Invalid array length"))?;
+                return Ok(truncate_array(list, length, &mut obj.properties, len));
+            }
+        }
+        if let Some(prop) = self.obj_mut(rid).properties.get_mut(key) {
+            return match &mut prop.value {
+                PropertyValue::Data(v) if prop.writable => {
+                    *v = value;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            };
+        }
+        if !self.obj(rid).extensible || self.exceeds_fixed_length(rid, key) {
+            return Ok(false);
+        }
+        self.define_property(rid, key, Property::data(value));
+        Ok(true)
+    }
 
-        if !get_val()? {
-            self.local_value[self.block_index]
-                .entry(arg.clone())
-                .or_insert((typ.unwrap_or(Variable::Var), value));
+    /// [[HasProperty]], key in obj, 包含原型链
+    pub(crate) fn has_property(&mut self, id: ObjectId, key: &PropertyKey) -> Result<bool, String> {
+        let mut cur = Some(id);
+        while let Some(o) = cur {
+            if self.is_proxy(o) {
+                return self.proxy_has(o, key);
+            }
+            if let Some(index) = self.integer_index(o, key) {
+                return Ok(index.is_some());
+            }
+            if self.get_own_property(o, key).is_some() {
+                return Ok(true);
+            }
+            cur = self.obj(o).proto;
         }
-        Ok(())
+        Ok(false)
     }
-    /// 调用前先执行一次 self.update_index(true);
-    fn bind_local_args(&mut self, typ: Variable, args: &Vec<Expr>, values: Vec<JSType>) {
-        let mut list: HashMap<String, (Variable, JSType)> = HashMap::new();
-        for (index, e) in args.iter().enumerate() {
-            match e {
-                Expr::Identifier(t) => {
-                    list.insert(t.clone(), (typ.clone(), values[index].clone()));
-                }
-                _ => {
-                    panic!("")
+
+    /// Object.getPrototypeOf, 原始值使用对应的内置原型
+    pub(crate) fn get_prototype_of(&mut self, value: &JSType) -> Result<Option<ObjectId>, String> {
+        match value {
+            JSType::Object(id) if self.is_proxy(*id) => self.proxy_get_prototype_of(*id),
+            JSType::Object(id) => Ok(self.obj(*id).proto),
+            JSType::Undefined | JSType::NULL => {
+                Err(self.err("Uncaught TypeError: Cannot convert undefined or null to object"))
+            }
+            _ => Ok(self.proto_of_primitive(value)),
+        }
+    }
+
+    /// [[SetPrototypeOf]], 不可扩展或形成循环时返回false
+    pub(crate) fn set_prototype(
+        &mut self,
+        id: ObjectId,
+        proto: Option<ObjectId>,
+    ) -> Result<bool, String> {
+        if self.is_proxy(id) {
+            return self.proxy_set_prototype(id, proto);
+        }
+        if self.obj(id).proto == proto {
+            return Ok(true);
+        }
+        if !self.obj(id).extensible {
+            return Ok(false);
+        }
+        //原型链中的Proxy不再继续检查
+        let mut cur = proto;
+        while let Some(o) = cur {
+            if o == id {
+                return Ok(false);
+            }
+            if self.is_proxy(o) {
+                break;
+            }
+            cur = self.obj(o).proto;
+        }
+        self.obj_mut(id).proto = proto;
+        Ok(true)
+    }
+
+    /// Object.setPrototypeOf, 不允许形成循环
+    pub(crate) fn set_prototype_of(
+        &mut self,
+        id: ObjectId,
+        proto: Option<ObjectId>,
+    ) -> Result<(), String> {
+        if self.set_prototype(id, proto)? {
+            return Ok(());
+        }
+        if self.is_proxy(id) {
+            return Err(
+                self.err("Uncaught TypeError: 'setPrototypeOf' on proxy: trap returned falsish")
+            );
+        }
+        if !self.obj(id).extensible {
+            return Err(self.err("Uncaught TypeError: #<Object> is not extensible"));
+        }
+        Err(self.err("Uncaught TypeError: Cyclic __proto__ value"))
+    }
+
+    /// [[IsExtensible]]
+    pub(crate) fn is_extensible(&mut self, id: ObjectId) -> Result<bool, String> {
+        if self.is_proxy(id) {
+            return self.proxy_is_extensible(id);
+        }
+        Ok(self.obj(id).extensible)
+    }
+
+    /// [[PreventExtensions]]
+    pub(crate) fn prevent_extensions(&mut self, id: ObjectId) -> Result<bool, String> {
+        if self.is_proxy(id) {
+            return self.proxy_prevent_extensions(id);
+        }
+        self.obj_mut(id).extensible = false;
+        Ok(true)
+    }
+
+    /// [[Delete]], 不可配置的属性返回false
+    pub(crate) fn delete_property(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+    ) -> Result<bool, String> {
+        if self.is_proxy(id) {
+            return self.proxy_delete(id, key);
+        }
+        if let Some(index) = self.integer_index(id, key) {
+            return Ok(index.is_none());
+        }
+        let obj = self.obj_mut(id);
+        if let ObjectKind::Array(list, _) = &mut obj.kind {
+            if key.is("length") {
+                return Ok(false);
+            }
+            if let Some(index) = key.array_index() {
+                if let Some(item @ Some(_)) = list.get_mut(index) {
+                    *item = None;
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(match obj.properties.get(key) {
+            Some(prop) if !prop.configurable => false,
+            Some(_) => {
+                obj.properties.remove(key);
+                true
+            }
+            None => true,
+        })
+    }
+
+    /// DeletePropertyOrThrow, 不可配置的属性抛出TypeError
+    pub(crate) fn delete_property_or_throw(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+    ) -> Result<(), String> {
+        if self.delete_property(id, key)? {
+            return Ok(());
+        }
+        let tag = if self.is_array(&JSType::Object(id)) {
+            "Array"
+        } else if self.obj(id).is_callable() {
+            "Function"
+        } else {
+            "Object"
+        };
+        Err(self.err(&format!(
+            "Uncaught TypeError: Cannot delete property '{}' of [object {}]",
+            key, tag
+        )))
+    }
+
+    //------------------------------ 函数调用 ------------------------------
+
+    pub(crate) fn create_function(
+        &mut self,
+        func: Arc<Function>,
+        kind: FunctionKind,
+        home_object: Option<ObjectId>,
+        name: &str,
+    ) -> ObjectId {
+        self.capture_scope(self.scope);
+        let is_normal = kind == FunctionKind::Normal && !func.is_async;
+        let closure_is_generator = func.is_generator;
+        let length = func
+            .params
+            .iter()
+            .take_while(|p| matches!(p, Expr::Identifier(_)))
+            .count();
+        let closure = Closure {
+            func,
+            scope: self.scope,
+            kind,
+            home_object,
+            elements: Arc::new(Vec::new()),
+        };
+        let proto = self.intrinsics.function_prototype;
+        let id = self.alloc(JSObject::new(
+            ObjectKind::Function(Box::new(closure)),
+            Some(proto),
+        ));
+        self.define_property(id, "length", Property::readonly(JSType::Int(length as i64)));
+        self.define_property(id, "name", Property::readonly(JSType::String(name.into())));
+        if closure_is_generator {
+            //生成器函数的prototype是生成器对象的原型, 没有constructor
+            let generator_prototype = self.intrinsics.generator_prototype;
+            let prototype = self.alloc(JSObject::new(
+                ObjectKind::Ordinary,
+                Some(generator_prototype),
+            ));
+            let mut prop = Property::hidden(JSType::Object(prototype));
+            prop.configurable = false;
+            self.define_property(id, "prototype", prop);
+        } else if is_normal {
+            //普通函数可以new, 需要prototype
+            let prototype = self.new_object();
+            if let JSType::Object(prototype_id) = prototype {
+                self.define_property(
+                    prototype_id,
+                    "constructor",
+                    Property::hidden(JSType::Object(id)),
+                );
+            }
+            let mut prop = Property::hidden(prototype);
+            prop.configurable = false;
+            self.define_property(id, "prototype", prop);
+        }
+        id
+    }
+
+    /// 函数表达式 有名称时函数体内可以通过名称调用自身
+    fn parse_function_expression(
+        &mut self,
+        func: &Arc<Function>,
+        name_hint: Option<&str>,
+    ) -> JSType {
+        let Some(name) = &func.name else {
+            let id = self.create_function(
+                func.clone(),
+                FunctionKind::Normal,
+                None,
+                name_hint.unwrap_or_default(),
+            );
+            return JSType::Object(id);
+        };
+        let outer = self.scope;
+        let scope = self.enter_scope(outer);
+        let id = self.create_function(func.clone(), FunctionKind::Normal, None, name);
+        self.scopes[scope]
+            .vars
+            .insert(name.clone(), (Variable::Const, JSType::Object(id)));
+        self.scope = outer;
+        JSType::Object(id)
+    }
+
+    pub fn call(
+        &mut self,
+        func: &JSType,
+        this: JSType,
+        args: Vec<JSType>,
+    ) -> Result<JSType, String> {
+        let JSType::Object(id) = func else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a function",
+                self.display(func)
+            )));
+        };
+        let id = *id;
+        match &self.obj(id).kind {
+            ObjectKind::Function(closure) => {
+                if let FunctionKind::ClassConstructor(_) = closure.kind {
+                    let name = self.function_name(id);
+                    return Err(self.err(&format!(
+                        "Uncaught TypeError: Class constructor {} cannot be invoked without 'new'",
+                        name
+                    )));
+                }
+                self.call_closure(id, Some(this), args, JSType::Undefined)
+            }
+            ObjectKind::Native(_, native, _) => {
+                let native = *native;
+                self.enter_call()?;
+                let result = native(self, this, args);
+                self.call_depth -= 1;
+                result
+            }
+            ObjectKind::Host(name) => {
+                let Some(host) = self.global_fn_map.get(name).cloned() else {
+                    return Err(
+                        self.err(&format!("Uncaught TypeError: {} is not a function", name))
+                    );
+                };
+                self.enter_call()?;
+                let result = host(self, args);
+                self.call_depth -= 1;
+                result
+            }
+            ObjectKind::PromiseFunction(_) => {
+                self.enter_call()?;
+                let result = self.call_promise_function(id, args);
+                self.call_depth -= 1;
+                result
+            }
+            ObjectKind::Bound(bound) => {
+                let target = JSType::Object(bound.target);
+                let this = bound.this.clone();
+                let mut list = bound.args.clone();
+                list.extend(args);
+                self.call(&target, this, list)
+            }
+            ObjectKind::Proxy(data) if data.callable => {
+                self.enter_call()?;
+                let result = self.proxy_call(id, this, args);
+                self.call_depth -= 1;
+                result
+            }
+            _ => Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a function",
+                self.display(func)
+            ))),
+        }
+    }
+
+    /// new func(...args)
+    pub fn construct(
+        &mut self,
+        func: &JSType,
+        args: Vec<JSType>,
+        new_target: Option<JSType>,
+    ) -> Result<JSType, String> {
+        if !self.is_constructor(func) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a constructor",
+                self.display(func)
+            )));
+        }
+        let JSType::Object(id) = func else {
+            unreachable!()
+        };
+        let id = *id;
+        let new_target = new_target.unwrap_or_else(|| func.clone());
+        match &self.obj(id).kind {
+            ObjectKind::Native(_, _, Some(ctor)) => {
+                let ctor = *ctor;
+                self.enter_call()?;
+                let result = ctor(self, args, new_target);
+                self.call_depth -= 1;
+                result
+            }
+            ObjectKind::Function(closure) => {
+                let derived = closure.kind == FunctionKind::ClassConstructor(true);
+                let mut this = None;
+                if !derived {
+                    let proto = self.get_prototype_from_constructor(&new_target, "object")?;
+                    let obj = self.alloc(JSObject::new(ObjectKind::Ordinary, Some(proto)));
+                    self.initialize_instance(obj, id)?;
+                    this = Some(JSType::Object(obj));
+                }
+                self.call_closure(id, this, args, new_target)
+            }
+            ObjectKind::Bound(bound) => {
+                //new.target为绑定函数本身时使用目标函数
+                let target = JSType::Object(bound.target);
+                let mut list = bound.args.clone();
+                list.extend(args);
+                let new_target = if new_target.strict_equal(func) {
+                    target.clone()
+                } else {
+                    new_target
+                };
+                self.construct(&target, list, Some(new_target))
+            }
+            ObjectKind::Proxy(_) => {
+                self.enter_call()?;
+                let result = self.proxy_construct(id, args, new_target);
+                self.call_depth -= 1;
+                result
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// new.target.prototype, 不是对象时使用内置原型
+    pub(crate) fn get_prototype_from_constructor(
+        &mut self,
+        new_target: &JSType,
+        default: &str,
+    ) -> Result<ObjectId, String> {
+        if let JSType::Object(_) = new_target {
+            if let JSType::Object(proto) = self.get_property(new_target, "prototype")? {
+                return Ok(proto);
+            }
+        }
+        Ok(self.intrinsics.get(default))
+    }
+
+    /// 执行线程的栈大小, 调用深度受其限制, 应在run之前调用, 未调用时按2MB计算
+    pub fn set_stack_size(&mut self, size: usize) {
+        self.stack_size = size;
+    }
+
+    /// 调试版本中每层调用占用的栈较多, 除了深度还要检查已使用的栈
+    fn enter_call(&mut self) -> Result<(), String> {
+        let marker = 0u8;
+        let address = std::ptr::addr_of!(marker) as usize;
+        if self.call_depth == 0 {
+            self.stack_base = address;
+        }
+        let used = self.stack_base.saturating_sub(address);
+        if self.call_depth >= MAX_CALL_DEPTH || used + STACK_RESERVE > self.stack_size {
+            return Err(self.err("Uncaught RangeError: Maximum call stack size exceeded"));
+        }
+        self.call_depth += 1;
+        Ok(())
+    }
+
+    /// this为None时表示派生类构造函数, 需要调用super()后才能使用this
+    fn call_closure(
+        &mut self,
+        id: ObjectId,
+        this: Option<JSType>,
+        args: Vec<JSType>,
+        new_target: JSType,
+    ) -> Result<JSType, String> {
+        let ObjectKind::Function(closure) = &self.obj(id).kind else {
+            unreachable!()
+        };
+        let func = closure.func.clone();
+        let parent = closure.scope;
+        let home_object = closure.home_object;
+        let derived = closure.kind == FunctionKind::ClassConstructor(true);
+        let is_construct = !matches!(new_target, JSType::Undefined);
+        //非严格模式函数的this为undefined/null时为全局对象, 原始值时转为包装对象
+        let this = match this {
+            Some(JSType::Undefined | JSType::NULL) if !func.strict => {
+                Some(JSType::Object(self.intrinsics.global_object))
+            }
+            Some(value) if !func.strict && !matches!(value, JSType::Object(_)) => {
+                Some(JSType::Object(self.object_value(&value)?))
+            }
+            this => this,
+        };
+
+        self.enter_call()?;
+        let outer = self.scope;
+        let outer_tape = self.tape.take();
+        let scope = self.enter_scope(parent);
+        self.scopes[scope].function = Some(FunctionFrame {
+            this,
+            func: Some(id),
+            new_target,
+            home_object,
+            strict: func.strict,
+        });
+        let result = if func.is_generator {
+            self.start_generator(id, &func, args)
+        } else if func.is_async {
+            self.start_async(&func, args)
+        } else {
+            self.call_body(&func, args)
+        };
+        let this = self.scopes[scope]
+            .function
+            .as_ref()
+            .and_then(|f| f.this.clone());
+        self.leave_scope(scope, outer);
+        self.tape = outer_tape;
+        self.call_depth -= 1;
+
+        let result = result?;
+        if !is_construct {
+            return Ok(result);
+        }
+        //构造函数返回对象时使用该对象, 否则返回this
+        if let JSType::Object(_) = result {
+            return Ok(result);
+        }
+        if derived && !matches!(result, JSType::Undefined) {
+            return Err(self.err(
+                "Uncaught TypeError: Derived constructors may only return object or undefined",
+            ));
+        }
+        this.ok_or_else(|| self.err("Uncaught ReferenceError: Must call super constructor in derived class before accessing 'this' or returning from derived constructor"))
+    }
+
+    fn call_body(&mut self, func: &Function, args: Vec<JSType>) -> Result<JSType, String> {
+        self.bind_local_args(&func.params, args)?;
+        self.hoist_declarations(&func.body, true)?;
+        let result = self.run_statements(&func.body)?;
+        match result {
+            JSType::Flag(JSTypeFlag::Return(value)) => Ok(*value),
+            _ => Ok(JSType::Undefined),
+        }
+    }
+
+    /// 绑定参数 a / a = 1 / ...a
+    fn bind_local_args(&mut self, params: &[Expr], values: Vec<JSType>) -> Result<(), String> {
+        let mut values = values.into_iter();
+        for param in params {
+            match param {
+                Expr::Identifier(name) => {
+                    let value = values.next().unwrap_or(JSType::Undefined);
+                    self.declare(Variable::Var, name, value)?;
+                }
+                Expr::Assignment(target, default) => {
+                    let Expr::Identifier(name) = target.as_ref() else {
+                        return Err(self
+                            .err("Uncaught SyntaxError: Invalid destructuring assignment target"));
+                    };
+                    let mut value = values.next().unwrap_or(JSType::Undefined);
+                    if let JSType::Undefined = value {
+                        value = self.parse_named(default, name)?;
+                    }
+                    self.declare(Variable::Var, name, value)?;
+                }
+                Expr::Spread(target) => {
+                    let Expr::Identifier(name) = target.as_ref() else {
+                        return Err(self
+                            .err("Uncaught SyntaxError: Invalid destructuring assignment target"));
+                    };
+                    let rest = values.by_ref().collect();
+                    let rest = self.new_array(rest);
+                    self.declare(Variable::Var, name, rest)?;
+                }
+                _ => return Err(self.err("Uncaught SyntaxError: Invalid parameter")),
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn function_name(&mut self, id: ObjectId) -> String {
+        match self.get_own_property(id, &"name".into()) {
+            Some(Property {
+                value: PropertyValue::Data(JSType::String(name)),
+                ..
+            }) => name.to_string(),
+            _ => String::new(),
+        }
+    }
+
+    //------------------------------ 执行 ------------------------------
+
+    ///语法解析及执行，生成器中包含yield的表达式需要可以恢复执行
+    fn parse(&mut self, e: &Expr) -> Result<JSType, String> {
+        if self.tape.is_some() {
+            return self.parse_resumable(e);
+        }
+        self.evaluate(e)
+    }
+
+    ///使用递归处理所有语句
+    fn evaluate(&mut self, e: &Expr) -> Result<JSType, String> {
+        match e {
+            Expr::Empty => Ok(JSType::Undefined),
+            Expr::Literal(val) => Ok(JSType::String(val.clone())),
+            Expr::Number(val) => Ok(JSType::number(*val)),
+            Expr::BigInt(val) => Ok(JSType::BigInt(val.clone())),
+            Expr::Bool(val) => Ok(JSType::Bool(*val)),
+            Expr::Null => Ok(JSType::NULL),
+            Expr::Identifier(key) | Expr::Ref(key) => self.get_value(key),
+            Expr::This => self.get_this(),
+            Expr::Template(vec, vec2) => self.parse_template(vec, vec2),
+            Expr::TaggedTemplate(tag, strings, exprs) => {
+                self.parse_tagged_template(tag, strings, exprs)
+            }
+            Expr::Unary(op, expr) => self.parse_unary(op, expr),
+            Expr::Infix(left, op, right) => self.parse_infix(left, op, right),
+            Expr::Conditional(test, left, right) => {
+                if self.parse(test)?.is_truthy() {
+                    self.parse(left)
+                } else {
+                    self.parse(right)
+                }
+            }
+            Expr::Update(target, op, prefix) => self.parse_update(target, op, *prefix),
+            Expr::Assignment(target, value) => {
+                let name = match target.as_ref() {
+                    Expr::Identifier(name) => Some(name.as_str()),
+                    _ => None,
+                };
+                let value = match name {
+                    Some(name) => self.parse_named(value, name)?,
+                    None => self.parse(value)?,
+                };
+                self.assign(target, value.clone())?;
+                Ok(value)
+            }
+            Expr::Sequence(list) => {
+                let mut result = JSType::Undefined;
+                for expr in list {
+                    result = self.parse(expr)?;
+                }
+                Ok(result)
+            }
+            Expr::Call(callee, args) => self.parse_call(callee, args),
+            Expr::New(callee, args) => {
+                let func = self.parse(callee)?;
+                let args = self.parse_args(args)?;
+                if !self.is_constructor(&func) {
+                    return Err(self.err(&format!(
+                        "Uncaught TypeError: {} is not a constructor",
+                        callee_name(callee)
+                    )));
+                }
+                self.construct(&func, args, None)
+            }
+            Expr::Member(object, property) => {
+                if object.as_ref() == &Expr::Super {
+                    let key = self.parse_key(property)?;
+                    return self.get_super_property(&key);
+                }
+                let object = self.parse(object)?;
+                let key = self.parse_key(property)?;
+                self.get_property(&object, &key)
+            }
+            Expr::PrivateMember(object, name) => {
+                let object = self.parse(object)?;
+                self.get_private(&object, name)
+            }
+            Expr::Object(members) => self.parse_object(members),
+            Expr::Array(list) => {
+                let mut data = Vec::new();
+                for item in list {
+                    match item {
+                        Expr::Empty => data.push(None),
+                        Expr::Spread(expr) => {
+                            let value = self.parse(expr)?;
+                            for v in self.iterate_to_list(&value)? {
+                                data.push(Some(v));
+                            }
+                        }
+                        _ => data.push(Some(self.parse(item)?)),
+                    }
+                }
+                let proto = self.intrinsics.array_prototype;
+                let len = data.len();
+                Ok(JSType::Object(self.alloc(JSObject::new(
+                    ObjectKind::Array(data, len),
+                    Some(proto),
+                ))))
+            }
+            Expr::Function(func) => Ok(self.parse_function_expression(func, None)),
+            Expr::Class(class) => self.parse_class(class, None),
+            Expr::Variable(v) => {
+                for (typ, name, value) in v {
+                    if *typ == Variable::Var && *value == Expr::Empty {
+                        continue;
+                    }
+                    let result = self.parse_named(value, name)?;
+                    if *typ == Variable::Var {
+                        self.bind_local_arg(name, result)?;
+                    } else {
+                        self.declare(typ.clone(), name, result)?;
+                    }
+                }
+                Ok(JSType::Undefined)
+            }
+            Expr::Block(list) => {
+                let outer = self.scope;
+                let scope = self.enter_scope(outer);
+                let result = self
+                    .hoist_declarations(list, false)
+                    .and_then(|_| self.run_statements(list));
+                self.leave_scope(scope, outer);
+                result
+            }
+            Expr::If(test, left, right) => {
+                if self.parse(test)?.is_truthy() {
+                    self.parse_statement(left)
+                } else {
+                    self.parse_statement(right)
+                }
+            }
+            Expr::For(..)
+            | Expr::ForIn(..)
+            | Expr::ForOf(..)
+            | Expr::While(..)
+            | Expr::DoWhile(..) => self.parse_loop(e, &[]),
+            Expr::Labeled(..) => self.parse_labeled(e),
+            //yield只出现在生成器中, 由 parse_resumable 处理
+            //import 在链接时已绑定
+            Expr::Import(_) => Ok(JSType::Undefined),
+            Expr::Export(export) => match export.as_ref() {
+                //函数声明已提升
+                ExportDeclaration::Declaration(Expr::Function(_))
+                | ExportDeclaration::Default(Expr::Function(_)) => Ok(JSType::Undefined),
+                ExportDeclaration::Declaration(Expr::Class(class))
+                | ExportDeclaration::Default(Expr::Class(class))
+                    if class.name.is_some() =>
+                {
+                    let value = self.parse_class(class, None)?;
+                    let name = class.name.clone().unwrap_or_default();
+                    self.declare(Variable::Let, &name, value)?;
+                    Ok(JSType::Undefined)
+                }
+                ExportDeclaration::Declaration(decl) => self.parse(decl),
+                ExportDeclaration::Default(expr) => {
+                    let value = self.parse_named(expr, "default")?;
+                    self.declare(Variable::Let, DEFAULT_BINDING, value)?;
+                    Ok(JSType::Undefined)
+                }
+                ExportDeclaration::Named(..) | ExportDeclaration::All(..) => Ok(JSType::Undefined),
+            },
+            Expr::ImportCall(specifier) => {
+                let specifier = self.parse(specifier)?;
+                self.dynamic_import(&specifier)
+            }
+            Expr::Yield(..) => Err(self.err("Uncaught SyntaxError: Unexpected yield")),
+            Expr::Await(_) => Err(self.err("Uncaught SyntaxError: Unexpected await")),
+            Expr::Return(expr) => {
+                let value = self.parse(expr)?;
+                Ok(JSType::Flag(JSTypeFlag::Return(Box::new(value))))
+            }
+            Expr::Break(label) => Ok(JSType::Flag(JSTypeFlag::Break(label.clone()))),
+            Expr::Continue(label) => Ok(JSType::Flag(JSTypeFlag::Continue(label.clone()))),
+            Expr::Switch(test, cases) => self.parse_switch(test, cases),
+            Expr::Spread(_) | Expr::Super | Expr::SwitchCase(_, _) => {
+                Err(self.err(&format!("功能暂未完成,{:?}", e)))
+            }
+        }
+    }
+
+    /// 单条语句(if/循环体), 函数/class声明需要放到块中执行
+    fn parse_statement(&mut self, e: &Expr) -> Result<JSType, String> {
+        match e {
+            Expr::Function(f) if f.name.is_some() => self.parse(&Expr::Block(vec![e.clone()])),
+            _ => self.parse(e),
+        }
+    }
+
+    /// 匿名函数/class 赋值时使用变量名作为name
+    fn parse_named(&mut self, e: &Expr, name: &str) -> Result<JSType, String> {
+        match e {
+            Expr::Function(func) if func.name.is_none() => {
+                Ok(self.parse_function_expression(func, Some(name)))
+            }
+            Expr::Class(class) if class.name.is_none() => self.parse_class(class, Some(name)),
+            _ => self.parse(e),
+        }
+    }
+
+    fn parse_template(&mut self, vec: &[String], vec2: &[Expr]) -> Result<JSType, String> {
+        let mut result = JSString::new();
+        for (index, item) in vec.iter().enumerate() {
+            //模板保存原始文本, 执行时处理转义 (解析时已检查)
+            result.push_js(&unescape(item, true).map_err(|e| self.err(&e))?);
+            if let Some(expr) = vec2.get(index) {
+                let value = self.parse(expr)?;
+                result.push_js(&self.js_string_value(&value)?);
+            }
+        }
+        Ok(JSType::String(result))
+    }
+
+    /// 属性名 a.b => "b"  a[b] => ToPropertyKey(b)
+    fn parse_key(&mut self, property: &Expr) -> Result<PropertyKey, String> {
+        let key = self.parse(property)?;
+        self.property_key(&key)
+    }
+
+    fn parse_property_name(&mut self, name: &PropertyName) -> Result<PropertyKey, String> {
+        match name {
+            PropertyName::Name(t) => Ok(t.into()),
+            PropertyName::Private(t) => Ok(format!("#{}", t).into()),
+            PropertyName::Computed(expr) => self.parse_key(expr),
+        }
+    }
+
+    fn parse_object(&mut self, members: &[(PropertyName, ObjectMember)]) -> Result<JSType, String> {
+        let object = self.new_object();
+        let JSType::Object(id) = object else {
+            unreachable!()
+        };
+        for (name, member) in members {
+            match member {
+                ObjectMember::Spread(expr) => {
+                    let source = self.parse(expr)?;
+                    if let JSType::Object(src) = source {
+                        for key in self.own_property_keys(src)? {
+                            match self.own_property(src, &key)? {
+                                Some(prop) if prop.enumerable => {
+                                    let value = self.get_property(&source, &key)?;
+                                    self.define_property(id, &key, Property::data(value));
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                ObjectMember::Value(expr) => {
+                    let key = self.parse_property_name(name)?;
+                    let value = match expr {
+                        // 方法需要记录home_object 用于super
+                        Expr::Function(func) if func.name.as_deref() == key.as_str() => {
+                            JSType::Object(self.create_function(
+                                func.clone(),
+                                FunctionKind::Method,
+                                Some(id),
+                                &key.function_name(),
+                            ))
+                        }
+                        _ => self.parse_named(expr, &key.function_name())?,
+                    };
+                    self.define_property(id, &key, Property::data(value));
+                }
+                //值不是对象或null时忽略
+                ObjectMember::Proto(expr) => match self.parse(expr)? {
+                    JSType::Object(proto) => self.obj_mut(id).proto = Some(proto),
+                    JSType::NULL => self.obj_mut(id).proto = None,
+                    _ => {}
+                },
+                ObjectMember::Getter(func) | ObjectMember::Setter(func) => {
+                    let key = self.parse_property_name(name)?;
+                    let is_get = matches!(member, ObjectMember::Getter(_));
+                    let prefix = if is_get { "get" } else { "set" };
+                    let f = self.create_function(
+                        func.clone(),
+                        FunctionKind::Method,
+                        Some(id),
+                        &format!("{} {}", prefix, key.function_name()),
+                    );
+                    self.define_accessor(id, &key, is_get, JSType::Object(f), true);
+                }
+            }
+        }
+        Ok(object)
+    }
+
+    /// 定义getter/setter, 已有同名访问器时合并
+    pub(crate) fn define_accessor(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        is_get: bool,
+        func: JSType,
+        enumerable: bool,
+    ) {
+        let (mut get, mut set) = match self.obj(id).properties.get(key) {
+            Some(Property {
+                value: PropertyValue::Accessor(get, set),
+                ..
+            }) => (get.clone(), set.clone()),
+            _ => (JSType::Undefined, JSType::Undefined),
+        };
+        if is_get {
+            get = func;
+        } else {
+            set = func;
+        }
+        self.define_property(id, key, Property::accessor(get, set, enumerable));
+    }
+
+    fn parse_args(&mut self, args: &[Expr]) -> Result<Vec<JSType>, String> {
+        let mut list = Vec::new();
+        for arg in args {
+            if let Expr::Spread(expr) = arg {
+                let value = self.parse(expr)?;
+                list.extend(self.iterate_to_list(&value)?);
+            } else {
+                list.push(self.parse(arg)?);
+            }
+        }
+        Ok(list)
+    }
+
+    fn parse_call(&mut self, callee: &Expr, args: &[Expr]) -> Result<JSType, String> {
+        if callee == &Expr::Super {
+            let args = self.parse_args(args)?;
+            return self.parse_super_call(args);
+        }
+        let (func, this) = self.parse_callee(callee)?;
+        let args = self.parse_args(args)?;
+        if !self.is_callable(&func) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a function",
+                callee_name(callee)
+            )));
+        }
+        self.call(&func, this, args)
+    }
+
+    /// 被调用的函数及this, a.b() 的this为a
+    fn parse_callee(&mut self, callee: &Expr) -> Result<(JSType, JSType), String> {
+        Ok(match callee {
+            Expr::Member(object, property) => {
+                if object.as_ref() == &Expr::Super {
+                    let key = self.parse_key(property)?;
+                    (self.get_super_property(&key)?, self.get_this()?)
+                } else {
+                    let this = self.parse(object)?;
+                    let key = self.parse_key(property)?;
+                    (self.get_property(&this, &key)?, this)
+                }
+            }
+            Expr::PrivateMember(object, name) => {
+                let this = self.parse(object)?;
+                (self.get_private(&this, name)?, this)
+            }
+            _ => (self.parse(callee)?, JSType::Undefined),
+        })
+    }
+
+    /// tag`a${b}c` => tag(strings, b)
+    fn parse_tagged_template(
+        &mut self,
+        tag: &Expr,
+        strings: &Arc<TemplateStrings>,
+        exprs: &[Expr],
+    ) -> Result<JSType, String> {
+        let (func, this) = self.parse_callee(tag)?;
+        let mut args = vec![self.template_object(strings)?];
+        for expr in exprs {
+            args.push(self.parse(expr)?);
+        }
+        if !self.is_callable(&func) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a function",
+                callee_name(tag)
+            )));
+        }
+        self.call(&func, this, args)
+    }
+
+    /// GetTemplateObject, 同一位置每次返回同一个冻结的数组, raw属性为原始文本
+    fn template_object(&mut self, strings: &Arc<TemplateStrings>) -> Result<JSType, String> {
+        //Arc由缓存持有, 地址不会被复用
+        let site = Arc::as_ptr(strings) as usize;
+        if let Some((_, id)) = self.template_objects.get(&site) {
+            return Ok(JSType::Object(*id));
+        }
+        let cooked = strings
+            .cooked
+            .iter()
+            .map(|s| s.clone().map_or(JSType::Undefined, JSType::String))
+            .collect();
+        let raw = strings.raw.iter().cloned().map(JSType::String).collect();
+        let (JSType::Object(id), JSType::Object(raw_id)) =
+            (self.new_array(cooked), self.new_array(raw))
+        else {
+            unreachable!()
+        };
+        self.define_property(id, "raw", Property::readonly(JSType::Object(raw_id)));
+        self.set_integrity_level(raw_id, true)?;
+        self.set_integrity_level(id, true)?;
+        self.template_objects.insert(site, (strings.clone(), id));
+        Ok(JSType::Object(id))
+    }
+
+    /// 给变量/属性赋值
+    fn assign(&mut self, target: &Expr, value: JSType) -> Result<(), String> {
+        match target {
+            Expr::Identifier(name) => self.bind_local_arg(name, value),
+            Expr::Member(object, property) => {
+                if object.as_ref() == &Expr::Super {
+                    let key = self.parse_key(property)?;
+                    return self.set_super_property(&key, value);
+                }
+                let object = self.parse(object)?;
+                let key = self.parse_key(property)?;
+                self.put_property(&object, &key, value)
+            }
+            Expr::PrivateMember(object, name) => {
+                let object = self.parse(object)?;
+                self.set_private(&object, name, value)
+            }
+            _ => Err(self.err("Uncaught SyntaxError: Invalid left-hand side in assignment")),
+        }
+    }
+
+    /// 读取赋值目标, 返回(对象,属性名)避免重复计算 a[i++] += 1
+    fn parse_reference(&mut self, target: &Expr) -> Result<Reference, String> {
+        match target {
+            Expr::Identifier(name) => Ok(Reference::Variable(name.clone())),
+            Expr::Member(object, property) => {
+                if object.as_ref() == &Expr::Super {
+                    let key = self.parse_key(property)?;
+                    return Ok(Reference::Super(key));
+                }
+                let object = self.parse(object)?;
+                let key = self.parse_key(property)?;
+                Ok(Reference::Property(object, key))
+            }
+            Expr::PrivateMember(object, name) => {
+                let object = self.parse(object)?;
+                Ok(Reference::Private(object, name.clone()))
+            }
+            _ => Err(self.err("Uncaught SyntaxError: Invalid left-hand side in assignment")),
+        }
+    }
+    fn get_reference(&mut self, reference: &Reference) -> Result<JSType, String> {
+        match reference {
+            Reference::Variable(name) => self.get_value(name),
+            Reference::Property(object, key) => self.get_property(object, key),
+            Reference::Private(object, name) => self.get_private(object, name),
+            Reference::Super(key) => self.get_super_property(key),
+        }
+    }
+    fn put_reference(&mut self, reference: &Reference, value: JSType) -> Result<(), String> {
+        match reference {
+            Reference::Variable(name) => self.bind_local_arg(name, value),
+            Reference::Property(object, key) => self.put_property(object, key, value),
+            Reference::Private(object, name) => self.set_private(object, name, value),
+            Reference::Super(key) => self.set_super_property(key, value),
+        }
+    }
+
+    fn parse_update(&mut self, target: &Expr, op: &str, prefix: bool) -> Result<JSType, String> {
+        let reference = self.parse_reference(target)?;
+        let old = self.get_reference(&reference)?;
+        let old = self.numeric_value(&old)?;
+        let new = match &old {
+            JSType::BigInt(n) if op == "++" => JSType::BigInt(Arc::new(n.as_ref() + 1)),
+            JSType::BigInt(n) => JSType::BigInt(Arc::new(n.as_ref() - 1)),
+            _ if op == "++" => JSType::number(old.to_number() + 1.0),
+            _ => JSType::number(old.to_number() - 1.0),
+        };
+        self.put_reference(&reference, new.clone())?;
+        if prefix {
+            Ok(new)
+        } else {
+            Ok(old)
+        }
+    }
+
+    fn parse_infix(&mut self, left: &Expr, op: &Operator, right: &Expr) -> Result<JSType, String> {
+        match op {
+            Operator::And => {
+                let left = self.parse(left)?;
+                if !left.is_truthy() {
+                    return Ok(left);
+                }
+                return self.parse(right);
+            }
+            Operator::Or => {
+                let left = self.parse(left)?;
+                if left.is_truthy() {
+                    return Ok(left);
+                }
+                return self.parse(right);
+            }
+            Operator::ADD | Operator::SUB | Operator::MUL | Operator::DIV | Operator::MOD => {
+                //a += b
+                let reference = self.parse_reference(left)?;
+                let old = self.get_reference(&reference)?;
+                let right = self.parse(right)?;
+                let op = match op {
+                    Operator::ADD => Operator::Plus,
+                    Operator::SUB => Operator::Subtract,
+                    Operator::MUL => Operator::Multiply,
+                    Operator::DIV => Operator::Divide,
+                    _ => Operator::Modulo,
+                };
+                let value = self.binary(&old, &op, &right)?;
+                self.put_reference(&reference, value.clone())?;
+                return Ok(value);
+            }
+            _ => {}
+        }
+        let left = self.parse(left)?;
+        let right = self.parse(right)?;
+        self.binary(&left, op, &right)
+    }
+
+    /// 二元运算
+    fn binary(&mut self, left: &JSType, op: &Operator, right: &JSType) -> Result<JSType, String> {
+        let result = match op {
+            Operator::Plus => {
+                let left = self.primitive(left, None)?;
+                let right = self.primitive(right, None)?;
+                if let JSType::String(l) = &left {
+                    let r = self.js_string_value(&right)?;
+                    return Ok(JSType::String(l.concat(&r)));
+                }
+                if let JSType::String(r) = &right {
+                    let l = self.js_string_value(&left)?;
+                    return Ok(JSType::String(l.concat(r)));
+                }
+                let left = self.numeric_value(&left)?;
+                let right = self.numeric_value(&right)?;
+                if matches!(left, JSType::BigInt(_)) || matches!(right, JSType::BigInt(_)) {
+                    return self.bigint_binary(&left, op, &right);
+                }
+                JSType::number(left.to_number() + right.to_number())
+            }
+            Operator::Subtract
+            | Operator::Multiply
+            | Operator::Divide
+            | Operator::Modulo
+            | Operator::LShift
+            | Operator::RShift
+            | Operator::BitAnd
+            | Operator::BitOr
+            | Operator::BitXor => {
+                let left = self.numeric_value(left)?;
+                let right = self.numeric_value(right)?;
+                if matches!(left, JSType::BigInt(_)) || matches!(right, JSType::BigInt(_)) {
+                    return self.bigint_binary(&left, op, &right);
+                }
+                let (l, r) = (left.to_number(), right.to_number());
+                match op {
+                    Operator::Subtract => JSType::number(l - r),
+                    Operator::Multiply => JSType::number(l * r),
+                    Operator::Divide => JSType::number(l / r),
+                    Operator::Modulo => JSType::number(l % r),
+                    Operator::LShift => {
+                        JSType::Int(to_int32(l).wrapping_shl(to_int32(r) as u32 & 0x1f) as i64)
+                    }
+                    Operator::RShift => {
+                        JSType::Int((to_int32(l) >> (to_int32(r) as u32 & 0x1f)) as i64)
+                    }
+                    Operator::BitAnd => JSType::Int((to_int32(l) & to_int32(r)) as i64),
+                    Operator::BitOr => JSType::Int((to_int32(l) | to_int32(r)) as i64),
+                    _ => JSType::Int((to_int32(l) ^ to_int32(r)) as i64),
+                }
+            }
+            Operator::Equal => JSType::Bool(self.loose_equal(left, right)?),
+            Operator::NE => JSType::Bool(!self.loose_equal(left, right)?),
+            Operator::Congruent => JSType::Bool(left.strict_equal(right)),
+            Operator::NotCongruent => JSType::Bool(!left.strict_equal(right)),
+            Operator::LT => JSType::Bool(self.compare(left, right, false)? == Some(true)),
+            Operator::GT => JSType::Bool(self.compare(right, left, true)? == Some(true)),
+            Operator::LTE => JSType::Bool(self.compare(right, left, true)? == Some(false)),
+            Operator::GTE => JSType::Bool(self.compare(left, right, false)? == Some(false)),
+            Operator::Instanceof => JSType::Bool(self.instance_of(left, right)?),
+            Operator::In => {
+                let JSType::Object(id) = right else {
+                    return Err(self.err(&format!(
+                        "Uncaught TypeError: Cannot use 'in' operator to search for '{}' in {}",
+                        left.to_key(),
+                        self.display(right)
+                    )));
+                };
+                let key = self.property_key(left)?;
+                JSType::Bool(self.has_property(*id, &key)?)
+            }
+            _ => unreachable!("{:?}", op),
+        };
+        Ok(result)
+    }
+
+    /// 比较 left < right, 结果为None时表示存在NaN
+    fn compare(
+        &mut self,
+        left: &JSType,
+        right: &JSType,
+        right_first: bool,
+    ) -> Result<Option<bool>, String> {
+        let (left, right) = if right_first {
+            let r = self.primitive(right, Some("number"))?;
+            let l = self.primitive(left, Some("number"))?;
+            (l, r)
+        } else {
+            let l = self.primitive(left, Some("number"))?;
+            let r = self.primitive(right, Some("number"))?;
+            (l, r)
+        };
+        match (&left, &right) {
+            (JSType::String(a), JSType::String(b)) => return Ok(Some(a < b)),
+            (JSType::BigInt(a), JSType::BigInt(b)) => return Ok(Some(a < b)),
+            //字符串不能转为BigInt时结果为undefined
+            (JSType::BigInt(a), JSType::String(b)) => {
+                return Ok(string_to_bigint(b).map(|b| a.as_ref() < &b))
+            }
+            (JSType::String(a), JSType::BigInt(b)) => {
+                return Ok(string_to_bigint(a).map(|a| &a < b.as_ref()))
+            }
+            (JSType::BigInt(a), _) => {
+                let b = self.number_value(&right)?;
+                return Ok(compare_bigint_number(a, b).map(|o| o == Ordering::Less));
+            }
+            (_, JSType::BigInt(b)) => {
+                let a = self.number_value(&left)?;
+                return Ok(compare_bigint_number(b, a).map(|o| o == Ordering::Greater));
+            }
+            _ => {}
+        }
+        let a = self.number_value(&left)?;
+        let b = self.number_value(&right)?;
+        if a.is_nan() || b.is_nan() {
+            return Ok(None);
+        }
+        Ok(Some(a < b))
+    }
+
+    /// ==
+    pub(crate) fn loose_equal(&mut self, left: &JSType, right: &JSType) -> Result<bool, String> {
+        match (left, right) {
+            (JSType::NULL | JSType::Undefined, JSType::NULL | JSType::Undefined) => Ok(true),
+            (JSType::NULL | JSType::Undefined, _) | (_, JSType::NULL | JSType::Undefined) => {
+                Ok(false)
+            }
+            (JSType::Object(_), JSType::Object(_)) => Ok(left.strict_equal(right)),
+            (JSType::Object(_), _) => {
+                let left = self.primitive(left, None)?;
+                self.loose_equal(&left, right)
+            }
+            (_, JSType::Object(_)) => {
+                let right = self.primitive(right, None)?;
+                self.loose_equal(left, &right)
+            }
+            (JSType::String(a), JSType::String(b)) => Ok(a == b),
+            (JSType::Bool(a), JSType::Bool(b)) => Ok(a == b),
+            (JSType::Symbol(_), JSType::Symbol(_)) => Ok(left.strict_equal(right)),
+            (JSType::Symbol(_), _) | (_, JSType::Symbol(_)) => Ok(false),
+            (JSType::BigInt(a), JSType::BigInt(b)) => Ok(a == b),
+            (JSType::BigInt(a), JSType::String(s)) | (JSType::String(s), JSType::BigInt(a)) => {
+                Ok(string_to_bigint(s).is_some_and(|b| a.as_ref() == &b))
+            }
+            (JSType::BigInt(a), other) | (other, JSType::BigInt(a)) => {
+                let n = self.number_value(other)?;
+                Ok(compare_bigint_number(a, n) == Some(Ordering::Equal))
+            }
+            _ => Ok(self.number_value(left)? == self.number_value(right)?),
+        }
+    }
+
+    /// a instanceof B, 优先使用 B[Symbol.hasInstance]
+    pub(crate) fn instance_of(&mut self, left: &JSType, right: &JSType) -> Result<bool, String> {
+        if !matches!(right, JSType::Object(_)) {
+            return Err(
+                self.err("Uncaught TypeError: Right-hand side of 'instanceof' is not an object")
+            );
+        }
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_has_instance.clone());
+        let method = self.get_property(right, key)?;
+        if !matches!(method, JSType::Undefined | JSType::NULL) {
+            if !self.is_callable(&method) {
+                return Err(self.err(&format!(
+                    "Uncaught TypeError: {} is not a function",
+                    self.display(&method)
+                )));
+            }
+            let result = self.call(&method, right.clone(), vec![left.clone()])?;
+            return Ok(result.is_truthy());
+        }
+        if !self.is_callable(right) {
+            return Err(
+                self.err("Uncaught TypeError: Right-hand side of 'instanceof' is not callable")
+            );
+        }
+        self.ordinary_has_instance(right, left)
+    }
+
+    /// 沿 value 的原型链查找 func.prototype
+    pub(crate) fn ordinary_has_instance(
+        &mut self,
+        func: &JSType,
+        value: &JSType,
+    ) -> Result<bool, String> {
+        let JSType::Object(id) = func else {
+            return Ok(false);
+        };
+        if let ObjectKind::Bound(bound) = &self.obj(*id).kind {
+            let target = JSType::Object(bound.target);
+            return self.instance_of(value, &target);
+        }
+        let JSType::Object(value) = value else {
+            return Ok(false);
+        };
+        let JSType::Object(proto) = self.get_property(func, "prototype")? else {
+            return Err(self
+                .err("Uncaught TypeError: Function has non-object prototype in instanceof check"));
+        };
+        let mut cur = self.get_prototype_of(&JSType::Object(*value))?;
+        while let Some(o) = cur {
+            if o == proto {
+                return Ok(true);
+            }
+            cur = self.get_prototype_of(&JSType::Object(o))?;
+        }
+        Ok(false)
+    }
+
+    fn parse_unary(&mut self, op: &Unary, expr: &Expr) -> Result<JSType, String> {
+        match op {
+            Unary::Typeof => {
+                //未声明的变量 typeof 为 undefined
+                if let Expr::Identifier(name) = expr {
+                    if !self.has_binding(name)? {
+                        return Ok(JSType::String("undefined".into()));
+                    }
+                }
+                let value = self.parse(expr)?;
+                Ok(JSType::String(self.type_of(&value).into()))
+            }
+            Unary::Delete => match expr {
+                Expr::Member(object, property) => {
+                    let object = self.parse(object)?;
+                    let key = self.parse_key(property)?;
+                    match object {
+                        //严格模式下删除不可配置的属性抛出TypeError
+                        JSType::Object(id) if self.is_strict() => {
+                            self.delete_property_or_throw(id, &key)?;
+                            Ok(JSType::Bool(true))
+                        }
+                        JSType::Object(id) => Ok(JSType::Bool(self.delete_property(id, &key)?)),
+                        _ => Ok(JSType::Bool(true)),
+                    }
+                }
+                Expr::Identifier(_) => Ok(JSType::Bool(false)),
+                _ => {
+                    self.parse(expr)?;
+                    Ok(JSType::Bool(true))
+                }
+            },
+            _ => {
+                let value = self.parse(expr)?;
+                match op {
+                    Unary::Not => Ok(JSType::Bool(!value.is_truthy())),
+                    Unary::Plus => Ok(JSType::number(self.number_value(&value)?)),
+                    Unary::Minus => match self.numeric_value(&value)? {
+                        JSType::BigInt(n) => Ok(JSType::BigInt(Arc::new(-n.as_ref()))),
+                        value => Ok(JSType::number(-value.to_number())),
+                    },
+                    Unary::BitNot => match self.numeric_value(&value)? {
+                        JSType::BigInt(n) => Ok(JSType::BigInt(Arc::new(!n.as_ref()))),
+                        value => Ok(JSType::Int(!to_int32(value.to_number()) as i64)),
+                    },
+                    _ => Ok(JSType::Undefined),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn type_of(&self, value: &JSType) -> &'static str {
+        match value {
+            JSType::Undefined => "undefined",
+            JSType::NULL => "object",
+            JSType::Bool(_) => "boolean",
+            JSType::Int(_) | JSType::Float(_) => "number",
+            JSType::BigInt(_) => "bigint",
+            JSType::String(_) => "string",
+            JSType::Object(id) => {
+                if self.obj(*id).is_callable() {
+                    "function"
+                } else {
+                    "object"
+                }
+            }
+            JSType::Symbol(_) => "symbol",
+            JSType::Flag(_) => "undefined",
+        }
+    }
+
+    /// label: statement, break label 在此处结束
+    fn parse_labeled(&mut self, e: &Expr) -> Result<JSType, String> {
+        let mut labels = Vec::new();
+        let mut body = e;
+        while let Expr::Labeled(label, stmt) = body {
+            labels.push(label.clone());
+            body = stmt;
+        }
+        let result = match body {
+            Expr::For(..)
+            | Expr::ForIn(..)
+            | Expr::ForOf(..)
+            | Expr::While(..)
+            | Expr::DoWhile(..) => self.parse_loop(body, &labels)?,
+            _ => self.parse_statement(body)?,
+        };
+        match result {
+            JSType::Flag(JSTypeFlag::Break(Some(label))) if labels.contains(&label) => {
+                Ok(JSType::Undefined)
+            }
+            _ => Ok(result),
+        }
+    }
+
+    /// 循环语句, labels为循环的label, 用于continue label
+    fn parse_loop(&mut self, e: &Expr, labels: &[String]) -> Result<JSType, String> {
+        match e {
+            Expr::For(init, test, update, body) => self.parse_for(init, test, update, body, labels),
+            Expr::ForIn(left, right, body) => self.parse_for_in(left, right, body, labels),
+            Expr::ForOf(left, right, body) => self.parse_for_of(left, right, body, labels),
+            Expr::While(test, body) => self.parse_while(false, test, body, labels),
+            Expr::DoWhile(test, body) => self.parse_while(true, test, body, labels),
+            _ => unreachable!(),
+        }
+    }
+
+    /// 循环体执行结果: None继续循环, Some(value)跳出
+    fn loop_body(&mut self, body: &Expr, labels: &[String]) -> Result<Option<JSType>, String> {
+        let result = self.parse_statement(body)?;
+        match result {
+            JSType::Flag(JSTypeFlag::Break(None)) => Ok(Some(JSType::Undefined)),
+            JSType::Flag(JSTypeFlag::Continue(None)) => Ok(None),
+            JSType::Flag(JSTypeFlag::Continue(Some(label))) if labels.contains(&label) => Ok(None),
+            JSType::Flag(_) => Ok(Some(result)),
+            _ => Ok(None),
+        }
+    }
+
+    /// while/do-while
+    fn parse_while(
+        &mut self,
+        is_do: bool,
+        test: &Expr,
+        body: &Expr,
+        labels: &[String],
+    ) -> Result<JSType, String> {
+        let mut first = is_do;
+        loop {
+            if !first && !self.parse(test)?.is_truthy() {
+                break;
+            }
+            first = false;
+            if let Some(result) = self.loop_body(body, labels)? {
+                return Ok(result);
+            }
+        }
+        Ok(JSType::Undefined)
+    }
+
+    /// for(init;test;update) let声明的变量每次循环都是新的绑定
+    fn parse_for(
+        &mut self,
+        init: &Expr,
+        test: &Expr,
+        update: &Expr,
+        body: &Expr,
+        labels: &[String],
+    ) -> Result<JSType, String> {
+        let outer = self.scope;
+        let mut scope = self.enter_scope(outer);
+        let per_iteration =
+            matches!(init, Expr::Variable(v) if v.iter().any(|i| i.0 != Variable::Var));
+        let result = (|| -> Result<JSType, String> {
+            self.parse(init)?;
+            //复制上次循环的变量, 闭包引用的是每次循环各自的变量
+            let mut next_iteration = |this: &mut Self| {
+                if per_iteration {
+                    let vars = this.scopes[scope].vars.clone();
+                    let next = this.enter_scope(outer);
+                    this.scopes[next].vars = vars;
+                    this.leave_scope(scope, next);
+                    scope = next;
+                }
+            };
+            next_iteration(self);
+            loop {
+                if *test != Expr::Empty && !self.parse(test)?.is_truthy() {
+                    break;
+                }
+                if let Some(result) = self.loop_body(body, labels)? {
+                    return Ok(result);
+                }
+                next_iteration(self);
+                self.parse(update)?;
+            }
+            Ok(JSType::Undefined)
+        })();
+        self.leave_scope(scope, outer);
+        result
+    }
+
+    /// switch 使用 === 匹配case, 从匹配的case开始向下执行直到break
+    fn parse_switch(&mut self, test: &Expr, cases: &[Expr]) -> Result<JSType, String> {
+        let value = self.parse(test)?;
+        let outer = self.scope;
+        let scope = self.enter_scope(outer);
+        let result = (|| -> Result<JSType, String> {
+            //所有case共用一个块级作用域
+            let statements: Vec<Expr> = cases
+                .iter()
+                .filter_map(|case| match case {
+                    Expr::SwitchCase(_, body) => Some(body.clone()),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+            self.hoist_declarations(&statements, false)?;
+            let mut start = None;
+            let mut default = None;
+            for (index, case) in cases.iter().enumerate() {
+                let Expr::SwitchCase(case_test, _) = case else {
+                    continue;
+                };
+                if **case_test == Expr::Empty {
+                    default = Some(index);
+                    continue;
+                }
+                if self.parse(case_test)?.strict_equal(&value) {
+                    start = Some(index);
+                    break;
+                }
+            }
+            let Some(start) = start.or(default) else {
+                return Ok(JSType::Undefined);
+            };
+            for case in &cases[start..] {
+                let Expr::SwitchCase(_, body) = case else {
+                    continue;
+                };
+                match self.run_statements(body)? {
+                    JSType::Flag(JSTypeFlag::Break(None)) => break,
+                    result @ JSType::Flag(_) => return Ok(result),
+                    _ => {}
+                }
+            }
+            Ok(JSType::Undefined)
+        })();
+        self.leave_scope(scope, outer);
+        result
+    }
+
+    //------------------------------ 类型转换 ------------------------------
+
+    /// ToPrimitive, hint: "string" / "number" / None(default)
+    /// 优先调用 value[Symbol.toPrimitive](hint), 否则依次尝试valueOf/toString
+    pub(crate) fn primitive(
+        &mut self,
+        value: &JSType,
+        hint: Option<&str>,
+    ) -> Result<JSType, String> {
+        if !matches!(value, JSType::Object(_)) {
+            return Ok(value.clone());
+        }
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_to_primitive.clone());
+        let exotic = self.get_property(value, key)?;
+        if !matches!(exotic, JSType::Undefined | JSType::NULL) {
+            if !self.is_callable(&exotic) {
+                return Err(self.err(&format!(
+                    "Uncaught TypeError: {} is not a function",
+                    self.display(&exotic)
+                )));
+            }
+            let hint = JSType::String(hint.unwrap_or("default").into());
+            let result = self.call(&exotic, value.clone(), vec![hint])?;
+            if matches!(result, JSType::Object(_)) {
+                return Err(
+                    self.err("Uncaught TypeError: Cannot convert object to primitive value")
+                );
+            }
+            return Ok(result);
+        }
+        self.ordinary_to_primitive(value, hint)
+    }
+
+    /// OrdinaryToPrimitive, hint为"string"时先调用toString, 否则先调用valueOf
+    pub(crate) fn ordinary_to_primitive(
+        &mut self,
+        value: &JSType,
+        hint: Option<&str>,
+    ) -> Result<JSType, String> {
+        let methods = if hint == Some("string") {
+            ["toString", "valueOf"]
+        } else {
+            ["valueOf", "toString"]
+        };
+        for name in methods {
+            let func = self.get_property(value, name)?;
+            if self.is_callable(&func) {
+                let result = self.call(&func, value.clone(), Vec::new())?;
+                if !matches!(result, JSType::Object(_)) {
+                    return Ok(result);
                 }
             }
         }
-        self.local_value.pop();
-        self.local_value.push(list);
+        Err(self.err("Uncaught TypeError: Cannot convert object to primitive value"))
     }
 
-    pub fn execute_func(&mut self, func: JSType, result: Vec<JSType>) -> Result<JSType, String> {
-        if let JSType::Function(a, b, c) = func {
-            let len = result.len() > 0;
-            if len {
-                self.update_index(true);
-                self.bind_local_args(Variable::Var, &b, result);
+    /// ToObject, 原始值转为包装对象, 字符串的码元作为只读的索引属性
+    pub(crate) fn object_value(&mut self, value: &JSType) -> Result<ObjectId, String> {
+        match value {
+            JSType::Object(id) => return Ok(*id),
+            JSType::Undefined | JSType::NULL => {
+                return Err(
+                    self.err("Uncaught TypeError: Cannot convert undefined or null to object")
+                )
             }
-            let result = self.parse(&c).unwrap();
-            if len {
-                self.update_index(false);
+            _ => {}
+        }
+        let proto = self.proto_of_primitive(value);
+        let id = self.alloc(JSObject::new(ObjectKind::Primitive(value.clone()), proto));
+        if let JSType::String(s) = value {
+            for index in 0..s.len() {
+                let mut prop = Property::readonly(JSType::String(s.slice(index, index + 1)));
+                prop.enumerable = true;
+                prop.configurable = false;
+                self.define_property(id, index.to_string().as_str(), prop);
             }
-            return Ok(result);
+            let mut prop = Property::readonly(JSType::Int(s.len() as i64));
+            prop.configurable = false;
+            self.define_property(id, "length", prop);
         }
-        Err(self.err(&format!("{func:?} is not function")))
+        Ok(id)
     }
 
-    /// index层级变化时调用,最好是在相近的语句块中添加跟移除操作
-    fn update_index(&mut self, is_inc: bool) {
-        if is_inc {
-            self.block_index += 1;
-            self.local_value.push(HashMap::new());
-        } else {
-            if self.block_index > 0 {
-                self.block_index -= 1;
-                self.local_value.pop();
+    pub(crate) fn number_value(&mut self, value: &JSType) -> Result<f64, String> {
+        match value {
+            JSType::Object(_) => {
+                let value = self.primitive(value, Some("number"))?;
+                self.number_value(&value)
             }
+            JSType::Symbol(_) => Err(err(
+                "Uncaught TypeError: Cannot convert a Symbol value to a number",
+            )),
+            JSType::BigInt(_) => Err(err(
+                "Uncaught TypeError: Cannot convert a BigInt value to a number",
+            )),
+            _ => Ok(value.to_number()),
         }
     }
 
-    pub fn log_value_print(&mut self) {
-        dbg!(&self.local_value);
-        dbg!(&self.call_value);
+    /// ToString, 单独的代理项转为U+FFFD
+    pub fn string_value(&mut self, value: &JSType) -> Result<String, String> {
+        Ok(self.js_string_value(value)?.to_string())
     }
 
-    fn err(&self, str: &str) -> String {
-        err(str)
+    /// ToString, 保留UTF-16码元
+    pub(crate) fn js_string_value(&mut self, value: &JSType) -> Result<JSString, String> {
+        match value {
+            JSType::Object(_) => {
+                let value = self.primitive(value, Some("string"))?;
+                self.js_string_value(&value)
+            }
+            JSType::String(s) => Ok(s.clone()),
+            _ => Ok(value.to_string()?.into()),
+        }
     }
 
-    fn parse_body_slot(&mut self, _expr: &Expr) -> Result<JSType, String> {
-        match _expr {
-            Expr::Break => {
-                return Ok(JSType::Flag(JSTypeFlag::Break));
-            }
-            Expr::Continue => {
-                return Ok(JSType::Flag(JSTypeFlag::Continue));
-            }
-            Expr::Return(expr) => {
-                if let Expr::Function(a, b, c) = expr.as_ref() {
-                    return Ok(JSType::Function(
-                        a.as_ref().clone(),
-                        b.clone(),
-                        c.as_ref().clone(),
-                    ));
-                } else if matches!(expr.as_ref(), Expr::Empty) {
-                    return Ok(JSType::Flag(JSTypeFlag::Return));
-                } else if matches!(
-                    expr.as_ref(),
-                    Expr::Identifier(_)
-                        | Expr::Call(_, _)
-                        | Expr::Literal(_)
-                        | Expr::Infix(_, _, _)
-                ) {
-                    let result = self.parse(&expr)?;
-                    return Ok(result);
-                } else {
-                    dbg!(&expr);
-                    panic!("{:?}", expr);
-                }
-            }
-            _ => {
-                // dbg!(&_expr);
-                let _expr = self.parse(_expr)?;
-                match _expr {
-                    JSType::Flag(jstype_flag) => {
-                        return Ok(JSType::Flag(jstype_flag));
-                    }
-                    JSType::NULL => {
-                        return Ok(_expr);
-                    }
-                    _ => {}
-                }
-                return Err(self.err(&format!("{:?}", _expr)));
+    /// ToPropertyKey
+    pub(crate) fn property_key(&mut self, value: &JSType) -> Result<PropertyKey, String> {
+        match value {
+            JSType::String(s) => Ok(PropertyKey::String(s.to_string())),
+            JSType::Symbol(s) => Ok(PropertyKey::Symbol(s.clone())),
+            JSType::Object(_) => {
+                let value = self.primitive(value, Some("string"))?;
+                self.property_key(&value)
             }
+            _ => Ok(PropertyKey::String(value.to_key())),
         }
     }
-    fn parse_call_function(
-        &mut self,
-        values: Vec<JSType>,
-        fn_body: Expr,
-    ) -> Result<JSType, String> {
-        if let Expr::Function(ident, args, body) = fn_body {
-            self.update_index(true);
-            self.bind_local_args(Variable::Var, &args, values); //绑定参数
-            let result = self.parse(&body);
-            self.update_index(false);
-            return result;
+
+    /// Symbol(description)
+    pub(crate) fn new_symbol(&mut self, description: Option<String>) -> Arc<JSSymbol> {
+        self.symbol_count += 1;
+        Arc::new(JSSymbol {
+            id: self.symbol_count,
+            description,
+        })
+    }
+
+    /// Symbol.for(key), 全局注册表中没有时创建
+    pub(crate) fn registered_symbol(&mut self, key: String) -> Arc<JSSymbol> {
+        if let Some(symbol) = self.symbol_registry.get(&key) {
+            return symbol.clone();
         }
-        Ok(JSType::Undefined)
+        let symbol = self.new_symbol(Some(key.clone()));
+        self.symbol_registry.insert(key, symbol.clone());
+        symbol
     }
 
-    /// for/while/do-while
-    fn parse_while_and_for(
-        &mut self,
-        is_do: bool,
-        init: Option<&Box<Expr>>,   //let i=0;
-        test: &Box<Expr>,           //i<10;
-        update: Option<&Box<Expr>>, //i++;
-        body: &Box<Expr>,
-    ) -> Result<JSType, String> {
-        let mut action =
-            |p: &mut Self, is_break: &mut bool, is_return: &mut bool| -> Result<_, String> {
-                match body.as_ref() {
-                    Expr::Block(vec) => {
-                        for i in vec.iter().enumerate() {
-                            let result = p.parse(i.1)?;
-                            match result {
-                                JSType::Flag(jstype_flag) => {
-                                    if matches!(jstype_flag, JSTypeFlag::Break) {
-                                        *is_break = true;
-                                        break;
-                                    } else if matches!(jstype_flag, JSTypeFlag::Continue) {
-                                        break;
-                                    } else if matches!(jstype_flag, JSTypeFlag::Return) {
-                                        *is_return = true;
-                                        return Ok(JSTypeFlag::Return);
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                        if let Some(update) = update {
-                            p.parse(update)?;
-                        }
-                    }
-                    Expr::Call(_, _) => {
-                        p.parse(body)?;
-                        if let Some(update) = update {
-                            p.parse(update)?;
-                        }
-                    }
-                    _ => {
-                        dbg!(&body);
-                        return Err(p.err("功能暂未实现"));
-                    }
-                }
-                Ok(JSTypeFlag::None)
-            };
+    /// Symbol.keyFor(symbol)
+    pub(crate) fn symbol_key(&self, symbol: &JSSymbol) -> Option<String> {
+        self.symbol_registry
+            .iter()
+            .find(|(_, s)| s.as_ref() == symbol)
+            .map(|(key, _)| key.clone())
+    }
 
-        if let Some(init) = init {
-            _ = self.parse(init.as_ref())?;
-        }
-        let mut is_break = false;
-        let mut is_return = false;
-        let mut do_count = 0;
-        loop {
-            if is_break {
-                break;
-            }
-            if is_return {
-                return Ok(JSType::Flag(JSTypeFlag::Return));
-            }
-            if matches!(test.as_ref(), Expr::Empty) {
-                _ = action(self, &mut is_break, &mut is_return);
-            } else {
-                let test = self.parse(test.as_ref())?;
-                if let JSType::Bool(mut flag) = test {
-                    do_count += 1;
-                    if is_do && do_count == 1 {
-                        //do首次会执行
-                        flag = true;
-                    }
-                    if flag {
-                        _ = action(self, &mut is_break, &mut is_return);
-                    } else {
-                        break;
-                    }
-                } else {
-                    dbg!(&test);
-                    return Err(self.err(&format!("表达式异常")));
-                }
-            }
+    //------------------------------ 输出 ------------------------------
+
+    /// log输出, 字符串不加引号
+    pub fn display(&self, value: &JSType) -> String {
+        match value {
+            JSType::String(s) => s.to_string(),
+            _ => self.inspect(value, &mut Vec::new()),
         }
-        Ok(JSType::NULL)
     }
 
-    fn get_value(&self, key: &str) -> Result<JSType, String> {
-        let last_index = self.block_index.clone();
-        let mut index = (self.local_value.len() as i32) - 1;
-        loop {
-            if let Some(val) = self.local_value.get(index as usize).clone() {
-                if let Some(v) = val.get(key) {
-                    return Ok(v.1.clone());
-                }
-            }
-            if index <= 0 {
-                break;
-            }
-            index -= 1;
-        }
-        if let Some(val) = self.global_value_map.get(key) {
-            return Ok(val.clone());
+    fn inspect(&self, value: &JSType, seen: &mut Vec<ObjectId>) -> String {
+        let JSType::Object(id) = value else {
+            return match value {
+                JSType::String(s) => format!("'{}'", s),
+                JSType::Float(n) if *n == 0.0 => "-0".to_string(),
+                JSType::BigInt(n) => format!("{}n", n),
+                _ => value.to_key(),
+            };
+        };
+        let id = *id;
+        if seen.contains(&id) {
+            return "[Circular]".to_string();
         }
-        if cfg!(debug_assertions) {
-            dbg!(&last_index);
-            dbg!(&self.local_value);
+        let obj = self.obj(id);
+        //Proxy显示其目标
+        if let ObjectKind::Proxy(data) = &obj.kind {
+            return match data.handler {
+                Some((target, _)) => self.inspect(&JSType::Object(target), seen),
+                None => "<Revoked Proxy>".to_string(),
+            };
         }
-        return Err(self.err(&format!("Uncaught ReferenceError: {} is not defined", key)));
-    }
-
-    ///语法解析及执行，使用递归处理所有语句
-    fn parse(&mut self, e: &Expr) -> Result<JSType, String> {
-        match e {
-            Expr::Infix(_left, op, _right) => {
-                let left = self.parse(_left)?;
-                let right = self.parse(_right)?;
-                return match &op {
-                    Operator::Plus => left.add(&right),
-                    Operator::ADD => {
-                        let result = left.add(&right)?;
-                        if let Expr::Identifier(id) = _left.as_ref() {
-                            _ = self.bind_local_arg(None, id.clone(), result.clone());
-                            return Ok(left);
-                        } else {
-                            return Err(self.err(&format!("暂不支持其他表达式")));
-                        }
-                    }
-                    Operator::Subtract => left.subtract(&right),
-                    Operator::SUB => {
-                        let result = left.subtract(&right)?;
-                        if let Expr::Identifier(id) = _left.as_ref() {
-                            _ = self.bind_local_arg(None, id.clone(), result.clone());
-                            return Ok(left);
-                        } else {
-                            return Err(self.err(&format!("暂不支持其他表达式")));
-                        }
-                    }
-                    Operator::Multiply => left.multiply(&right),
-                    Operator::MUL => {
-                        let result = left.multiply(&right)?;
-                        if let Expr::Identifier(id) = _left.as_ref() {
-                            _ = self.bind_local_arg(None, id.clone(), result.clone());
-                            return Ok(left);
-                        } else {
-                            return Err(self.err(&format!("暂不支持其他表达式")));
-                        }
-                    }
-                    Operator::Divide => left.divide(&right),
-                    Operator::DIV => {
-                        let result = left.divide(&right)?;
-                        if let Expr::Identifier(id) = _left.as_ref() {
-                            _ = self.bind_local_arg(None, id.clone(), result.clone());
-                            return Ok(left);
+        let own_string = |key: &str| match obj.properties.get_str(key) {
+            Some(Property {
+                value: PropertyValue::Data(JSType::String(s)),
+                ..
+            }) => Some(s.to_string()),
+            _ => None,
+        };
+        let mut prefix = String::new();
+        match &obj.kind {
+            ObjectKind::Function(closure) => {
+                let name = own_string("name").unwrap_or_default();
+                if let FunctionKind::ClassConstructor(_) = closure.kind {
+                    let mut s = format!(
+                        "[class {}",
+                        if name.is_empty() {
+                            "(anonymous)"
                         } else {
-                            return Err(self.err(&format!("暂不支持其他表达式")));
+                            &name
                         }
-                    }
-                    Operator::Modulo => left.modulo(&right),
-                    Operator::MOD => {
-                        let result = left.modulo(&right)?;
-                        if let Expr::Identifier(id) = _left.as_ref() {
-                            _ = self.bind_local_arg(None, id.clone(), result.clone());
-                            return Ok(left);
-                        } else {
-                            return Err(self.err(&format!("暂不支持其他表达式")));
+                    );
+                    if let Some(parent) = obj.proto {
+                        if let ObjectKind::Function(_) = self.obj(parent).kind {
+                            let parent_name = match self.obj(parent).properties.get_str("name") {
+                                Some(Property {
+                                    value: PropertyValue::Data(JSType::String(s)),
+                                    ..
+                                }) => s.clone(),
+                                _ => String::new().into(),
+                            };
+                            s.push_str(&format!(" extends {}", parent_name));
                         }
                     }
-                    Operator::Equal => Ok(JSType::Bool(left.equal(&right))),
-                    Operator::NE => Ok(JSType::Bool(!left.equal(&right))),
-                    Operator::GT => Ok(JSType::Bool(left.GT(&right))),
-                    Operator::GTE => Ok(JSType::Bool(left.GTE(&right))),
-                    Operator::LT => Ok(JSType::Bool(left.LT(&right))),
-                    Operator::LTE => Ok(JSType::Bool(left.LTE(&right))),
-                    _ => unreachable!("{:?}", &op),
+                    return s + "]";
+                }
+                return if name.is_empty() {
+                    "[Function (anonymous)]".to_string()
+                } else {
+                    format!("[Function: {}]", name)
                 };
             }
-            Expr::Unary(left, value) => {
-                dbg!(&left);
-                dbg!(&value);
-            }
-            Expr::Literal(val) => {
-                // if raw.starts_with('"') || raw.starts_with('\'') {
-                //     return Ok(JSType::String(val.to_string()));
-                // }
-                let i = val.parse::<i64>();
-                if !i.is_err() {
-                    return Ok(JSType::Int(i.unwrap()));
-                }
-                let f = val.parse::<f64>();
-                if !f.is_err() {
-                    return Ok(JSType::Float(f.unwrap()));
-                }
-                return Ok(JSType::String(val.clone()));
-            }
-            Expr::Identifier(key) => {
-                return self.get_value(key);
-            }
-            Expr::Call(ee, args) => {
-                match ee.as_ref() {
-                    Expr::Identifier(t) => {
-                        let mut list = Vec::new();
-                        for i in args {
-                            // dbg!(&i);
-                            list.push(self.parse(i)?);
-                        }
-                        if let Some(e) = self.fn_map.get(&t.clone()) {
-                            let expr = self.parse_call_function(list, e.clone())?;
-                            return Ok(expr);
-                        }
-                        if let Some(e) = self.global_fn_map.get(&t.clone()) {
-                            let result = e(list)?;
-                            return Ok(result);
-                        }
-                        return Err(self.err(&format!("暂未实现")));
-                    }
-                    Expr::Call(ee2, args2) => {
-                        let expr = self.parse(ee)?;
-                        let mut list = Vec::new();
-                        for i in args {
-                            list.push(self.parse(i)?);
-                        }
-                        if let JSType::Function(a, b, c) = expr {
-                            let body = Expr::Function(Box::new(a), b, Box::new(c));
-                            let expr2 = self.parse_call_function(list, body)?;
-                            // dbg!(&expr2);
-                            return Ok(expr2);
-                        }
-                        return Ok(expr);
+            ObjectKind::Native(name, _, _) => return format!("[Function: {}]", name),
+            ObjectKind::Host(name) => return format!("[Function: {}]", name),
+            ObjectKind::Bound(_) => {
+                return format!("[Function: {}]", own_string("name").unwrap_or_default());
+            }
+            ObjectKind::PromiseFunction(_) => return "[Function (anonymous)]".to_string(),
+            ObjectKind::Promise(data) => {
+                return match &data.state {
+                    PromiseState::Pending => "Promise { <pending> }".to_string(),
+                    PromiseState::Fulfilled(value) => {
+                        format!("Promise {{ {} }}", self.inspect(value, seen))
                     }
-                    _ => {
-                        panic!("")
+                    PromiseState::Rejected(reason) => {
+                        format!("Promise {{ <rejected> {} }}", self.inspect(reason, seen))
                     }
                 };
             }
-            Expr::Variable(v) => {
-                for i in v {
-                    let result = self.parse(&i.2.clone())?;
-                    _ = self.bind_local_arg(Some(i.0.clone()), i.1.clone(), result);
-                }
-            }
-            Expr::For(init, test, update, body) => {
-                self.update_index(true);
-                let result =
-                    self.parse_while_and_for(false, Some(init), test, Some(update), body)?;
-                self.update_index(false);
-                if matches!(result, JSType::Flag(JSTypeFlag::Return)) {
-                    return Ok(JSType::Flag(JSTypeFlag::Return));
-                }
-            }
-            Expr::ForIn(_, _) => {}
-            Expr::ForOf(_, _) => {}
-            Expr::Update(ident, op, _) => {
-                let val = self.parse(&ident)?.INC()?;
-                if let Expr::Identifier(id) = ident.as_ref() {
-                    _ = self.bind_local_arg(None, id.clone(), val.clone());
-                    return Ok(val);
-                } else {
-                    return Err(self.err(&format!("暂不支持其他表达式")));
+            ObjectKind::Error => {
+                //Error: message
+                let mut cur = Some(id);
+                let mut name = None;
+                while let Some(o) = cur {
+                    if let Some(Property {
+                        value: PropertyValue::Data(JSType::String(s)),
+                        ..
+                    }) = self.obj(o).properties.get_str("name")
+                    {
+                        name = Some(s.to_string());
+                        break;
+                    }
+                    cur = self.obj(o).proto;
                 }
+                let name = name.unwrap_or_else(|| "Error".into());
+                return match own_string("message") {
+                    Some(msg) if !msg.is_empty() => format!("{}: {}", name, msg),
+                    _ => name,
+                };
+            }
+            ObjectKind::Primitive(value) => {
+                let name = match value {
+                    JSType::String(_) => "String",
+                    JSType::Bool(_) => "Boolean",
+                    JSType::Symbol(_) => "Symbol",
+                    JSType::BigInt(_) => "BigInt",
+                    _ => "Number",
+                };
+                return format!("[{}: {}]", name, self.inspect(value, seen));
             }
-            Expr::If(e, left, right) => {
-                let result = self.parse(e)?;
-                if let JSType::Bool(r) = result {
-                    if r {
-                        let left = left.as_ref();
-                        let result = self.parse_body_slot(left)?;
-                        return Ok(result);
+            ObjectKind::Map(data) | ObjectKind::Set(data) => {
+                let is_map = matches!(obj.kind, ObjectKind::Map(_));
+                seen.push(id);
+                let mut parts = Vec::new();
+                for (key, value) in data.iter() {
+                    parts.push(if is_map {
+                        format!(
+                            "{} => {}",
+                            self.inspect(key, seen),
+                            self.inspect(value, seen)
+                        )
                     } else {
-                        let right = right.as_ref();
-                        let result = self.parse_body_slot(right)?;
-                        return Ok(result);
-                    }
-                } else {
-                    return Err(self.err(&format!("if解析异常")));
-                }
-            }
-            Expr::Expression(expr) => {
-                return self.parse(expr);
-            }
-            Expr::Block(t) => {
-                if t.len() > 0 {
-                    for i in t {
-                        // dbg!(&i);
-                        let result = self.parse_body_slot(i)?;
-                        match result {
-                            JSType::Flag(jstype_flag) => {
-                                match jstype_flag {
-                                    //提前跳出循环
-                                    JSTypeFlag::Break | JSTypeFlag::Return => {
-                                        return Ok(JSType::Flag(jstype_flag))
-                                    }
-                                    _ => panic!("暂未处理{jstype_flag:?}"),
-                                }
-                            }
-                            JSType::NULL => {}
-                            _ => panic!("暂未处理{result:?}"),
-                        }
-                    }
+                        self.inspect(key, seen)
+                    });
+                }
+                seen.pop();
+                let name = if is_map { "Map" } else { "Set" };
+                if parts.is_empty() {
+                    return format!("{}({}) {{}}", name, data.len());
+                }
+                return format!("{}({}) {{ {} }}", name, data.len(), parts.join(", "));
+            }
+            ObjectKind::WeakMap(_) => return "WeakMap { <items unknown> }".to_string(),
+            ObjectKind::WeakSet(_) => return "WeakSet { <items unknown> }".to_string(),
+            ObjectKind::Date(t) => return iso_string(*t).unwrap_or("Invalid Date".to_string()),
+            ObjectKind::ArrayBuffer(bytes) => {
+                //最多显示50个字节
+                let mut contents: Vec<String> = bytes
+                    .iter()
+                    .take(50)
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                if bytes.len() > 50 {
+                    contents.push(format!("... {} more bytes", bytes.len() - 50));
                 }
+                return format!(
+                    "ArrayBuffer {{ [Uint8Contents]: <{}>, byteLength: {} }}",
+                    contents.join(" "),
+                    bytes.len()
+                );
             }
-            Expr::While(test, body) => {
-                _ = self.parse_while_and_for(false, None, test, None, body);
+            ObjectKind::TypedArray(kind, view) => {
+                let items: Vec<String> = (0..view.length)
+                    .map(|index| self.inspect(&self.typed_array_get(id, index), seen))
+                    .collect();
+                if items.is_empty() {
+                    return format!("{}({}) []", kind.name(), view.length);
+                }
+                return format!("{}({}) [ {} ]", kind.name(), view.length, items.join(", "));
             }
-            Expr::DoWhile(test, body) => {
-                _ = self.parse_while_and_for(true, None, test, None, body);
+            ObjectKind::DataView(view) => {
+                return format!(
+                    "DataView {{ byteLength: {}, byteOffset: {}, buffer: {} }}",
+                    view.length,
+                    view.offset,
+                    self.inspect(&JSType::Object(view.buffer), seen)
+                );
             }
-            Expr::Object(map) => {
-                let mut data = HashMap::new();
-                for n in map {
-                    let key = n.0.clone();
-                    let mut val = JSType::NULL;
-                    match n.1 {
-                        // 先不处理json/member中的方法
-                        Expr::Function(a, b, c) => {
-                            val =
-                                JSType::Function(a.as_ref().clone(), b.clone(), c.as_ref().clone());
-                        }
-                        Expr::Ref(a) => {
-                            val = self.get_value(a)?;
-                        }
-                        _ => {
-                            val = self.parse(&n.1)?.clone();
+            ObjectKind::Namespace(_) => prefix = "[Module: null prototype] ".to_string(),
+            _ => {}
+        }
+        //非Object.prototype/Array.prototype的实例显示构造函数名称
+        if let Some(proto) = obj.proto {
+            if proto != self.intrinsics.object_prototype && proto != self.intrinsics.array_prototype
+            {
+                if let Some(Property {
+                    value: PropertyValue::Data(JSType::Object(ctor)),
+                    ..
+                }) = self.obj(proto).properties.get_str("constructor")
+                {
+                    if let Some(Property {
+                        value: PropertyValue::Data(JSType::String(name)),
+                        ..
+                    }) = self.obj(*ctor).properties.get_str("name")
+                    {
+                        if !name.is_empty() {
+                            prefix = format!("{} ", name);
                         }
                     }
-                    data.insert(key, val);
                 }
-                return Ok(JSType::Object(data));
             }
-            Expr::Array(arr) => {
-                let mut data = Vec::new();
-                for n in arr {
-                    match n {
-                        // 先不处理json/member中的方法
-                        Expr::Function(a, b, c) => {
-                            let val =
-                                JSType::Function(a.as_ref().clone(), b.clone(), c.as_ref().clone());
-                            data.push(val);
-                        }
-                        Expr::Ref(a) => {
-                            let val = self.get_value(&a)?;
-                            data.push(val);
-                        }
-                        _ => {
-                            let val = self.parse(n)?.clone();
-                            data.push(val);
-                        }
+        }
+        seen.push(id);
+        let mut parts = Vec::new();
+        let is_array = matches!(obj.kind, ObjectKind::Array(..));
+        if let ObjectKind::Array(list, length) = &obj.kind {
+            //只读等特殊的元素及稀疏的元素在properties中
+            let mut items: Vec<(usize, &JSType)> = list
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| item.as_ref().map(|v| (index, v)))
+                .collect();
+            for key in obj.properties.keys() {
+                if let (Some(index), Some(prop)) = (key.array_index(), obj.properties.get(key)) {
+                    if let PropertyValue::Data(v) = &prop.value {
+                        items.push((index, v));
                     }
                 }
-                return Ok(JSType::Array(data));
             }
-            Expr::Empty => {}
-            Expr::Function(a, b, c) => {
-                return Ok(JSType::Function(
-                    a.as_ref().clone(),
-                    b.clone(),
-                    c.as_ref().clone(),
-                ));
+            items.sort_by_key(|(index, _)| *index);
+            let mut next = 0;
+            for (index, v) in items {
+                if index > next {
+                    parts.push(empty_items(index - next));
+                }
+                parts.push(self.inspect(v, seen));
+                next = index + 1;
             }
-            Expr::Switch(a, b) => {
-                return Err(self.err(&format!(
-                    "switch执行功能暂未完成,后续有可能直接移除掉,个人感觉不太需要这个语法==!"
-                )));
+            if *length > next {
+                parts.push(empty_items(length - next));
             }
-            Expr::Template(vec, vec2) => {
-                let sub = vec.len() - vec2.len();
-                if sub > 1 {
-                    return Err(self.err(&format!("Template异常")));
-                }
-                let mut v2 = Vec::new();
-                for n in vec2 {
-                    let expr = self.parse(n)?;
-                    v2.push(expr.to_string().unwrap());
-                }
-                let mut result = String::new();
-                for n in 0..vec2.len() {
-                    result.push_str(&vec[n].clone());
-                    result.push_str(&v2[n]);
+        }
+        if let ObjectKind::Namespace(list) = &obj.kind {
+            for (name, binding) in list {
+                let value = match binding {
+                    ModuleBinding::Variable(scope, local) => {
+                        match self.binding_value(*scope, local) {
+                            Ok(value) => self.inspect(&value, seen),
+                            Err(_) => "<uninitialized>".to_string(),
+                        }
+                    }
+                    ModuleBinding::Value(value) => self.inspect(value, seen),
+                };
+                parts.push(format!("{}: {}", name, value));
+            }
+        }
+        for key in obj.properties.keys() {
+            let prop = &obj.properties.get(key).unwrap();
+            if !prop.enumerable || (is_array && key.array_index().is_some()) {
+                continue;
+            }
+            let value = match &prop.value {
+                PropertyValue::Data(v) => self.inspect(v, seen),
+                PropertyValue::Accessor(get, set) => {
+                    match (
+                        matches!(get, JSType::Undefined),
+                        matches!(set, JSType::Undefined),
+                    ) {
+                        (false, false) => "[Getter/Setter]".to_string(),
+                        (false, true) => "[Getter]".to_string(),
+                        _ => "[Setter]".to_string(),
+                    }
                 }
-                if sub == 1 {
-                    result.push_str(vec.last().unwrap());
+            };
+            let key = match key {
+                PropertyKey::Symbol(s) => format!("[{}]", s),
+                PropertyKey::String(k) if is_identifier_name(k) => k.clone(),
+                PropertyKey::String(k) => format!("'{}'", k),
+            };
+            parts.push(format!("{}: {}", key, value));
+        }
+        seen.pop();
+        if is_array {
+            if parts.is_empty() {
+                return format!("{}[]", prefix);
+            }
+            return format!("{}[ {} ]", prefix, parts.join(", "));
+        }
+        if parts.is_empty() {
+            return format!("{}{{}}", prefix);
+        }
+        format!("{}{{ {} }}", prefix, parts.join(", "))
+    }
+}
+
+fn empty_items(count: usize) -> String {
+    if count == 1 {
+        "<1 empty item>".to_string()
+    } else {
+        format!("<{} empty items>", count)
+    }
+}
+
+fn is_identifier_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c == '$' || c == '_' || c.is_alphabetic() => {}
+        _ => return false,
+    }
+    chars.all(|c| c == '$' || c == '_' || c.is_alphanumeric())
+}
+
+/// 赋值目标
+enum Reference {
+    Variable(String),
+    Property(JSType, PropertyKey),
+    Private(JSType, String),
+    Super(PropertyKey),
+}
+
+/// 报错时显示的调用名称 a.b.c
+fn callee_name(expr: &Expr) -> String {
+    match expr {
+        Expr::Identifier(t) => t.clone(),
+        Expr::This => "this".to_string(),
+        Expr::Super => "super".to_string(),
+        Expr::Member(object, property) => match property.as_ref() {
+            Expr::Literal(t) => format!("{}.{}", callee_name(object), t),
+            _ => format!("{}[...]", callee_name(object)),
+        },
+        Expr::PrivateMember(object, name) => format!("{}.#{}", callee_name(object), name),
+        Expr::Call(callee, _) => format!("{}(...)", callee_name(callee)),
+        _ => "expression".to_string(),
+    }
+}
+
+/// 收集var声明的变量名(不进入函数内部)
+fn collect_var_names(expr: &Expr, names: &mut Vec<String>) {
+    match expr {
+        Expr::Variable(v) => {
+            for (typ, name, _) in v {
+                if *typ == Variable::Var && !names.contains(name) {
+                    names.push(name.clone());
                 }
-                return Ok(JSType::String(result));
             }
-            _ => {
-                dbg!(&e);
-                return Err(self.err(&format!("功能暂未完成,{:?}", e)));
+        }
+        Expr::Block(list) => list.iter().for_each(|e| collect_var_names(e, names)),
+        Expr::If(_, left, right) => {
+            collect_var_names(left, names);
+            collect_var_names(right, names);
+        }
+        Expr::For(init, _, _, body) | Expr::ForIn(init, _, body) | Expr::ForOf(init, _, body) => {
+            collect_var_names(init, names);
+            collect_var_names(body, names);
+        }
+        Expr::While(_, body) | Expr::DoWhile(_, body) | Expr::Labeled(_, body) => {
+            collect_var_names(body, names)
+        }
+        Expr::Switch(_, cases) => cases.iter().for_each(|e| collect_var_names(e, names)),
+        Expr::SwitchCase(_, list) => list.iter().for_each(|e| collect_var_names(e, names)),
+        Expr::Export(export) => {
+            if let ExportDeclaration::Declaration(decl) = export.as_ref() {
+                collect_var_names(decl, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 数组下标 "0" "12", 不允许前导0
+pub(crate) fn array_index(key: &str) -> Option<usize> {
+    if key.is_empty() || (key.len() > 1 && key.starts_with('0')) {
+        return None;
+    }
+    if !key.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let index = key.parse::<u64>().ok()?;
+    if index >= u32::MAX as u64 {
+        return None;
+    }
+    Some(index as usize)
+}
+
+/// 修改数组长度, 删除超出长度的特殊属性
+fn truncate_array(
+    list: &mut Vec<Option<JSType>>,
+    length: &mut usize,
+    properties: &mut PropertyMap,
+    len: usize,
+) -> bool {
+    let mut len = len;
+    let mut result = true;
+    if len < *length {
+        let removed: Vec<(usize, PropertyKey)> = properties
+            .keys()
+            .iter()
+            .filter_map(|k| {
+                k.array_index()
+                    .filter(|i| *i >= len)
+                    .map(|i| (i, k.clone()))
+            })
+            .collect();
+        //不可配置的元素不能删除, length停在最大的不可配置元素之后
+        let fixed = removed
+            .iter()
+            .filter(|(_, k)| properties.get(k).is_some_and(|p| !p.configurable))
+            .map(|(i, _)| *i)
+            .max();
+        if let Some(index) = fixed {
+            len = index + 1;
+            result = false;
+        }
+        for (index, key) in removed {
+            if index >= len {
+                properties.remove(&key);
             }
         }
-        Ok(JSType::NULL)
     }
+    list.truncate(len);
+    *length = len;
+    result
+}
+
+/// 合法的数组长度
+fn array_length(value: &JSType) -> Option<usize> {
+    let n = value.to_number();
+    if n >= 0.0 && n.fract() == 0.0 && n < u32::MAX as f64 + 1.0 {
+        return Some(n as usize);
+    }
+    None
+}
+
+pub(crate) fn to_int32(n: f64) -> i32 {
+    if !n.is_finite() {
+        return 0;
+    }
+    let n = n.trunc() % 4294967296.0;
+    let n = if n < 0.0 { n + 4294967296.0 } else { n };
+    n as u32 as i32
+}
+
+/// Number::toString(x, 10), 使用能还原x的最短的十进制数字
+pub(crate) fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        return "NaN".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if n == 0.0 {
+        return "0".to_string();
+    }
+    if n < 0.0 {
+        return format!("-{}", number_to_string(-n));
+    }
+    //Rust的{:e}输出最短的数字, 如 1.2345e-7
+    let formatted = format!("{:e}", n);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1; //小数点的位置
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, (n - 1).abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, (n - 1).abs())
+        }
+    }
+}
+
+/// StringToNumber, 已去掉首尾空白; 支持 0x/0o/0b 前缀及Infinity, 不合法时为NaN
+pub(crate) fn string_to_number(s: &str) -> f64 {
+    match s {
+        "" => return 0.0,
+        "Infinity" | "+Infinity" => return f64::INFINITY,
+        "-Infinity" => return f64::NEG_INFINITY,
+        _ => {}
+    }
+    let radix = match s.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        return digits_to_number(&s[2..], radix).unwrap_or(f64::NAN);
+    }
+    if !s
+        .bytes()
+        .all(|c| c.is_ascii_digit() || matches!(c, b'.' | b'e' | b'E' | b'+' | b'-'))
+    {
+        return f64::NAN;
+    }
+    s.parse::<f64>().unwrap_or(f64::NAN)
+}
+
+/// 按进制解析整数, 有不合法的数字或为空时为None
+pub(crate) fn digits_to_number(s: &str, radix: u32) -> Option<f64> {
+    if s.is_empty() {
+        return None;
+    }
+    //128位以内一次转换, 保证正确的舍入
+    let mut exact = Some(0u128);
+    let mut value = 0.0;
+    for ch in s.chars() {
+        let digit = ch.to_digit(radix)?;
+        exact = exact
+            .and_then(|n| n.checked_mul(radix as u128))
+            .and_then(|n| n.checked_add(digit as u128));
+        value = value * radix as f64 + digit as f64;
+    }
+    Some(exact.map(|n| n as f64).unwrap_or(value))
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone)]
 pub enum JSType {
     Flag(JSTypeFlag), // 程序控制的状态
@@ -638,217 +3058,130 @@ pub enum JSType {
     Undefined,
     Int(i64),
     Float(f64),
-    String(String),
+    BigInt(Arc<BigInt>), //123n
+    String(JSString),
     Bool(bool),
-    Function(Expr, Vec<Expr>, Expr),
-    Object(HashMap<String, JSType>), //json or member
-    Array(Vec<JSType>),              //array
+    Symbol(Arc<JSSymbol>),
+    Object(ObjectId), //json/array/function等, 保存在堆中
 }
 #[derive(Debug, Clone)]
 pub enum JSTypeFlag {
-    None,
-    Continue,
-    Break,
-    Return,
-    Ref,
+    Continue(Option<String>), //continue label
+    Break(Option<String>),    //break label
+    Return(Box<JSType>),
 }
 
 impl JSType {
-    pub fn to_string(&self) -> Result<String, String> {
-        match self {
-            JSType::NULL => Err(err("Cannot read properties of null")),
-            JSType::Int(t) => Ok(t.to_string()),
-            JSType::Float(t) => Ok(t.to_string()),
-            JSType::String(t) => Ok(t.to_string()),
-            JSType::Bool(t) => Ok(t.to_string()),
-            JSType::Function(t, _, _) => Ok(err(&format!("function:{}", t.to_raw()))),
-            JSType::Flag(jstype_flag) => todo!(),
-            JSType::Undefined => Ok("".to_string()),
-            _ => Ok("".to_string()),
-        }
-    }
-    pub fn add(&self, other: &JSType) -> Result<JSType, String> {
-        match (self, other) {
-            (JSType::Int(a), JSType::Int(b)) => Ok(JSType::Int(a + b)),
-            (JSType::Float(a), JSType::Float(b)) => Ok(JSType::Float(a + b)),
-            (JSType::Int(a), JSType::Float(b)) => Ok(JSType::Float(*a as f64 + b)),
-            (JSType::Float(a), JSType::Int(b)) => Ok(JSType::Float(a + *b as f64)),
-            (JSType::String(a), JSType::String(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            (JSType::NULL, JSType::String(b)) => Ok(JSType::String(format!("null{}", b))),
-            (JSType::Int(a), JSType::String(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            (JSType::Float(a), JSType::String(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            (JSType::String(a), JSType::NULL) => Ok(JSType::String(format!("{}null", a))),
-            (JSType::String(a), JSType::Int(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            (JSType::String(a), JSType::Float(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            (JSType::String(a), JSType::Bool(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            (JSType::Bool(a), JSType::String(b)) => Ok(JSType::String(format!("{}{}", a, b))),
-            _ => {
-                dbg!(&self);
-                dbg!(&other);
-                Err(err("Unsupported types for addition"))
-            }
+    /// 整数使用Int保存, 其余(小数/NaN/-0/超出安全整数)使用Float
+    pub fn number(n: f64) -> JSType {
+        if n.fract() == 0.0 && n.abs() <= 9007199254740992.0 && !(n == 0.0 && n.is_sign_negative())
+        {
+            JSType::Int(n as i64)
+        } else {
+            JSType::Float(n)
         }
     }
-
-    pub fn subtract(&self, other: &JSType) -> Result<JSType, String> {
-        match (self, other) {
-            (JSType::Int(a), JSType::Int(b)) => Ok(JSType::Int(a - b)),
-            (JSType::Float(a), JSType::Float(b)) => Ok(JSType::Float(a - b)),
-            (JSType::Int(a), JSType::Float(b)) => Ok(JSType::Float(*a as f64 - b)),
-            (JSType::Float(a), JSType::Int(b)) => Ok(JSType::Float(a - *b as f64)),
-            _ => Err(err("Unsupported types for subtraction")),
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JSType::NULL => "null",
+            JSType::Undefined => "undefined",
+            JSType::Int(_) | JSType::Float(_) => "number",
+            JSType::BigInt(_) => "bigint",
+            JSType::String(_) => "string",
+            JSType::Bool(_) => "boolean",
+            JSType::Symbol(_) => "symbol",
+            JSType::Object(_) => "object",
+            JSType::Flag(_) => "flag",
         }
     }
-
-    pub fn multiply(&self, other: &JSType) -> Result<JSType, String> {
-        match (self, other) {
-            (JSType::Int(a), JSType::Int(b)) => Ok(JSType::Int(a * b)),
-            (JSType::Float(a), JSType::Float(b)) => Ok(JSType::Float(a * b)),
-            (JSType::Int(a), JSType::Float(b)) => Ok(JSType::Float(*a as f64 * b)),
-            (JSType::Float(a), JSType::Int(b)) => Ok(JSType::Float(a * *b as f64)),
-            _ => Err(err("Unsupported types for multiplication")),
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            JSType::NULL | JSType::Undefined | JSType::Flag(_) => false,
+            JSType::Int(t) => *t != 0,
+            JSType::Float(t) => !(*t == 0.0 || t.is_nan()),
+            JSType::BigInt(t) => !t.is_zero(),
+            JSType::String(t) => !t.is_empty(),
+            JSType::Bool(t) => *t,
+            JSType::Symbol(_) | JSType::Object(_) => true,
         }
     }
-
-    pub fn divide(&self, other: &JSType) -> Result<JSType, String> {
-        match (self, other) {
-            (JSType::Int(a), JSType::Int(b)) => {
-                if *b == 0 {
-                    Err(err("Cannot divide by zero"))
-                } else {
-                    Ok(JSType::Int(a / b))
-                }
-            }
-            (JSType::Float(a), JSType::Float(b)) => {
-                if *b == 0.0 {
-                    Err(err("Cannot divide by zero"))
-                } else {
-                    Ok(JSType::Float(a / b))
-                }
-            }
-            (JSType::Int(a), JSType::Float(b)) => {
-                if *b == 0.0 {
-                    Err(err("Cannot divide by zero"))
-                } else {
-                    Ok(JSType::Float(*a as f64 / b))
-                }
-            }
-            (JSType::Float(a), JSType::Int(b)) => {
-                if *b == 0 {
-                    Err(err("Cannot divide by zero"))
+    /// 原始值转数字, 对象为NaN
+    pub fn to_number(&self) -> f64 {
+        match self {
+            JSType::NULL => 0.0,
+            JSType::Int(t) => *t as f64,
+            JSType::Float(t) => *t,
+            JSType::BigInt(t) => bigint_to_number(t),
+            JSType::Bool(t) => {
+                if *t {
+                    1.0
                 } else {
-                    Ok(JSType::Float(a / *b as f64))
+                    0.0
                 }
             }
-            _ => Err(err("Unsupported types for division")),
+            JSType::String(t) => string_to_number(&t.trim(true, true).to_string()),
+            _ => f64::NAN,
         }
     }
-
-    pub fn modulo(&self, other: &JSType) -> Result<JSType, String> {
-        match (self, other) {
-            (JSType::Int(a), JSType::Int(b)) => {
-                if *b == 0 {
-                    Err(err("Cannot modulo by zero"))
-                } else {
-                    Ok(JSType::Int(a % b))
-                }
-            }
-            (JSType::Float(a), JSType::Float(b)) => {
-                if *b == 0.0 {
-                    Err(err("Cannot modulo by zero"))
-                } else {
-                    Ok(JSType::Float(a % b))
-                }
-            }
-            (JSType::Int(a), JSType::Float(b)) => {
-                if *b == 0.0 {
-                    Err(err("Cannot modulo by zero"))
-                } else {
-                    Ok(JSType::Float(*a as f64 % b))
-                }
-            }
-            (JSType::Float(a), JSType::Int(b)) => {
-                if *b == 0 {
-                    Err(err("Cannot modulo by zero"))
-                } else {
-                    Ok(JSType::Float(a % *b as f64))
-                }
-            }
-            _ => Err(err("Unsupported types for modulo")),
+    /// 原始值作为属性名
+    pub fn to_key(&self) -> String {
+        match self {
+            JSType::NULL => "null".to_string(),
+            JSType::Undefined => "undefined".to_string(),
+            JSType::Int(t) => t.to_string(),
+            JSType::Float(t) => number_to_string(*t),
+            JSType::BigInt(t) => t.to_string(),
+            JSType::String(t) => t.to_string(),
+            JSType::Bool(t) => t.to_string(),
+            JSType::Symbol(t) => t.to_string(),
+            JSType::Object(_) => "[object Object]".to_string(),
+            JSType::Flag(_) => String::new(),
         }
     }
-
-    pub fn equal(&self, other: &JSType) -> bool {
-        match (self, other) {
-            (JSType::NULL, JSType::NULL) => true,
-            (JSType::Int(a), JSType::Int(b)) => a == b,
-            (JSType::Int(a), JSType::Float(b)) => *a as f64 == *b,
-            (JSType::Int(a), JSType::String(b)) => a.to_string() == b.to_string(),
-            (JSType::Int(a), JSType::Bool(b)) => (*a != 0) == *b,
-            (JSType::Float(a), JSType::Int(b)) => *a == *b as f64,
-            (JSType::Float(a), JSType::Float(b)) => a == b,
-            (JSType::Float(a), JSType::String(b)) => a.to_string() == b.to_string(),
-            (JSType::Float(a), JSType::Bool(b)) => (*a != 0.0) == *b,
-            (JSType::String(a), JSType::Int(b)) => a.to_string() == b.to_string(),
-            (JSType::String(a), JSType::Float(b)) => a.to_string() == b.to_string(),
-            (JSType::String(a), JSType::String(b)) => a.to_string() == b.to_string(),
-            (JSType::String(a), JSType::Bool(b)) => a.to_string() == b.to_string(),
-            (JSType::Bool(a), JSType::Int(b)) => (*a && *b == 1) || (!*a && *b == 0),
-            (JSType::Bool(a), JSType::Float(b)) => (*a && *b == 1.0) || (!*a && *b == 0.0),
-            (JSType::Bool(a), JSType::Bool(b)) => a == b,
-            _ => false,
+    pub fn to_string(&self) -> Result<String, String> {
+        match self {
+            JSType::Flag(_) => Err(err("Uncaught SyntaxError: Illegal statement")),
+            JSType::Symbol(_) => Err(err(
+                "Uncaught TypeError: Cannot convert a Symbol value to a string",
+            )),
+            _ => Ok(self.to_key()),
         }
     }
-    /// >
-    pub fn GT(&self, other: &JSType) -> bool {
+    /// ===
+    pub fn strict_equal(&self, other: &JSType) -> bool {
         match (self, other) {
-            (JSType::NULL, JSType::NULL) => false,
-            (JSType::Int(a), JSType::Int(b)) => a > b,
-            (JSType::Int(a), JSType::Float(b)) => *a as f64 > *b,
-            // (JSType::Int(a), JSType::String(b)) => false,
-            (JSType::Int(a), JSType::Bool(b)) => (*a != 0) == *b,
-            (JSType::Float(a), JSType::Int(b)) => *a == *b as f64,
-            (JSType::Float(a), JSType::Float(b)) => a == b,
-            // (JSType::Float(a), JSType::String(b)) => false,
-            (JSType::Float(a), JSType::Bool(b)) => (*a != 0.0) == *b,
-            // (JSType::String(a), JSType::Int(b)) => false,
-            // (JSType::String(a), JSType::Float(b)) => false,
+            (JSType::NULL, JSType::NULL) => true,
+            (JSType::Undefined, JSType::Undefined) => true,
+            (JSType::Int(_) | JSType::Float(_), JSType::Int(_) | JSType::Float(_)) => {
+                self.to_number() == other.to_number()
+            }
+            (JSType::BigInt(a), JSType::BigInt(b)) => a == b,
             (JSType::String(a), JSType::String(b)) => a == b,
-            // (JSType::String(a), JSType::Bool(b)) => false,
-            // (JSType::Bool(a), JSType::Int(b)) => false,
-            // (JSType::Bool(a), JSType::Float(b)) => false,
             (JSType::Bool(a), JSType::Bool(b)) => a == b,
-            // (JSType::Bool(a), JSType::String(b)) => false,
-            _ => panic!("暂不支持其他操作符"),
+            (JSType::Symbol(a), JSType::Symbol(b)) => a == b,
+            (JSType::Object(a), JSType::Object(b)) => a == b,
+            _ => false,
         }
     }
-    /// >=
-    pub fn GTE(&self, other: &JSType) -> bool {
-        self.GT(other) || self.equal(other)
-    }
-    /// <
-    pub fn LT(&self, other: &JSType) -> bool {
-        !self.GTE(other)
-    }
-    /// <=
-    pub fn LTE(&self, other: &JSType) -> bool {
-        !self.GT(other)
-    }
-
-    pub fn INC(&self) -> Result<JSType, String> {
-        match self {
-            JSType::Int(t) => Ok(JSType::Int(t + 1)),
-            _ => Err(err(
-                "Uncaught SyntaxError: Invalid left-hand side expression in postfix operation",
-            )),
+    /// SameValue, NaN等于NaN, +0不等于-0
+    pub fn same_value(&self, other: &JSType) -> bool {
+        match (self, other) {
+            (JSType::Int(_) | JSType::Float(_), JSType::Int(_) | JSType::Float(_)) => {
+                let (a, b) = (self.to_number(), other.to_number());
+                (a.is_nan() && b.is_nan())
+                    || (a == b && a.is_sign_negative() == b.is_sign_negative())
+            }
+            _ => self.strict_equal(other),
         }
     }
-    pub fn DEC(&self) -> Result<JSType, String> {
-        match self {
-            JSType::Int(t) => Ok(JSType::Int(t - 1)),
-            _ => Err(err(
-                "Uncaught SyntaxError: Invalid left-hand side expression in postfix operation",
+    /// 原始值相加 (外部注册的方法中使用)
+    pub fn add(&self, other: &JSType) -> Result<JSType, String> {
+        match (self, other) {
+            (JSType::String(l), JSType::String(r)) => Ok(JSType::String(l.concat(r))),
+            (JSType::String(_), _) | (_, JSType::String(_)) => Ok(JSType::String(
+                format!("{}{}", self.to_string()?, other.to_string()?).into(),
             )),
+            _ => Ok(JSType::number(self.to_number() + other.to_number())),
         }
     }
 }
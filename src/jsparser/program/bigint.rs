@@ -0,0 +1,159 @@
+use super::{JSType, Program};
+use crate::jsparser::expr::Operator;
+use crate::jsparser::string::JSString;
+use crate::jsparser::utility::err;
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+/// BigInt的最大位数, 与V8相同
+const MAX_BIGINT_BITS: u64 = 1 << 30;
+
+/// StringToBigInt, 空串为0, 支持0x/0o/0b前缀, 不允许小数/指数/分隔符
+pub(crate) fn string_to_bigint(s: &JSString) -> Option<BigInt> {
+    let s = s.trim(true, true).to_string();
+    if s.is_empty() {
+        return Some(BigInt::zero());
+    }
+    let (radix, negative, digits) = match s.get(..2) {
+        Some("0x" | "0X") => (16, false, &s[2..]),
+        Some("0o" | "0O") => (8, false, &s[2..]),
+        Some("0b" | "0B") => (2, false, &s[2..]),
+        _ => match s.strip_prefix('-') {
+            Some(digits) => (10, true, digits),
+            None => (10, false, s.strip_prefix('+').unwrap_or(&s)),
+        },
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let n = BigInt::parse_bytes(digits.as_bytes(), radix)?;
+    Some(if negative { -n } else { n })
+}
+
+/// NumberToBigInt, 有小数部分或不是有限值时为None
+pub(crate) fn number_to_bigint(n: f64) -> Option<BigInt> {
+    if !n.is_finite() || n.trunc() != n {
+        return None;
+    }
+    BigInt::from_f64(n)
+}
+
+/// BigInt与Number按数学值比较, Number为NaN时为None
+pub(crate) fn compare_bigint_number(a: &BigInt, b: f64) -> Option<Ordering> {
+    if b.is_nan() {
+        return None;
+    }
+    if b.is_infinite() {
+        return Some(if b > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+    let floor = BigInt::from_f64(b.floor())?;
+    Some(match a.cmp(&floor) {
+        Ordering::Equal if b.fract() != 0.0 => Ordering::Less,
+        ordering => ordering,
+    })
+}
+
+/// BigInt转为Number, 超出范围时为Infinity
+pub(crate) fn bigint_to_number(n: &BigInt) -> f64 {
+    n.to_f64().unwrap_or(if n.is_negative() {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    })
+}
+
+/// n << shift, shift为负数时右移, 右移向负无穷舍入
+fn shift_left(n: &BigInt, shift: &BigInt) -> Result<BigInt, String> {
+    if n.is_zero() {
+        return Ok(BigInt::zero());
+    }
+    let bits = shift.abs().to_u64().unwrap_or(u64::MAX);
+    if !shift.is_negative() {
+        if bits + n.bits() > MAX_BIGINT_BITS {
+            return Err(err("Uncaught RangeError: Maximum BigInt size exceeded"));
+        }
+        return Ok(n << bits);
+    }
+    if bits >= n.bits() {
+        return Ok(if n.is_negative() {
+            -BigInt::one()
+        } else {
+            BigInt::zero()
+        });
+    }
+    Ok(n >> bits)
+}
+
+impl Program {
+    /// ToNumeric, 结果为Number或BigInt
+    pub(crate) fn numeric_value(&mut self, value: &JSType) -> Result<JSType, String> {
+        match self.primitive(value, Some("number"))? {
+            JSType::BigInt(n) => Ok(JSType::BigInt(n)),
+            value => Ok(JSType::number(self.number_value(&value)?)),
+        }
+    }
+
+    /// ToBigInt, Number不能隐式转换
+    pub(crate) fn bigint_value(&mut self, value: &JSType) -> Result<Arc<BigInt>, String> {
+        let value = self.primitive(value, Some("number"))?;
+        match &value {
+            JSType::BigInt(n) => Ok(n.clone()),
+            JSType::Bool(b) => Ok(Arc::new(BigInt::from(*b as i64))),
+            JSType::String(s) => match string_to_bigint(s) {
+                Some(n) => Ok(Arc::new(n)),
+                None => Err(self.err(&format!(
+                    "Uncaught SyntaxError: Cannot convert {} to a BigInt",
+                    s
+                ))),
+            },
+            _ => Err(self.err(&format!(
+                "Uncaught TypeError: Cannot convert {} to a BigInt",
+                self.display(&value)
+            ))),
+        }
+    }
+
+    /// 两个操作数都是BigInt时的算术及位运算, 与Number混用时抛出TypeError
+    pub(crate) fn bigint_binary(
+        &self,
+        left: &JSType,
+        op: &Operator,
+        right: &JSType,
+    ) -> Result<JSType, String> {
+        let (JSType::BigInt(a), JSType::BigInt(b)) = (left, right) else {
+            return Err(self.err(
+                "Uncaught TypeError: Cannot mix BigInt and other types, use explicit conversions",
+            ));
+        };
+        let (a, b) = (a.as_ref(), b.as_ref());
+        let result = match op {
+            Operator::Plus => a + b,
+            Operator::Subtract => a - b,
+            Operator::Multiply => {
+                if a.bits() + b.bits() > MAX_BIGINT_BITS {
+                    return Err(self.err("Uncaught RangeError: Maximum BigInt size exceeded"));
+                }
+                a * b
+            }
+            //整数除法向0取整, 余数的符号与被除数相同
+            Operator::Divide | Operator::Modulo if b.is_zero() => {
+                return Err(self.err("Uncaught RangeError: Division by zero"));
+            }
+            Operator::Divide => a / b,
+            Operator::Modulo => a % b,
+            Operator::LShift => shift_left(a, b)?,
+            Operator::RShift => shift_left(a, &-b)?,
+            Operator::BitAnd => a & b,
+            Operator::BitOr => a | b,
+            Operator::BitXor => a ^ b,
+            _ => unreachable!("{:?}", op),
+        };
+        Ok(JSType::BigInt(Arc::new(result)))
+    }
+}
@@ -0,0 +1,482 @@
+use super::{JSType, Program};
+use crate::jsparser::expr::{Class, ClassMemberKind, Expr, Function, PropertyName, Variable};
+use crate::jsparser::object::{
+    ClassElement, FieldKey, FunctionFrame, FunctionKind, JSObject, ObjectId, ObjectKind, Property,
+    PropertyKey, PropertyValue, ScopeId,
+};
+use std::sync::Arc;
+
+/// 定义class后按顺序执行的静态成员
+enum StaticElement {
+    Field(FieldKey, Option<Expr>),
+    Block(Vec<Expr>),
+}
+
+impl Program {
+    /// class A extends B {}, name_hint 为匿名class赋值时的变量名
+    pub(super) fn parse_class(
+        &mut self,
+        class: &Arc<Class>,
+        name_hint: Option<&str>,
+    ) -> Result<JSType, String> {
+        let outer = self.scope;
+        let class_scope = self.enter_scope(outer);
+        //每个class的私有属性名都是唯一的
+        for member in &class.members {
+            if let PropertyName::Private(name) = &member.key {
+                if !self.scopes[class_scope].private_names.contains_key(name) {
+                    let id = self.private_name_count;
+                    self.private_name_count += 1;
+                    self.scopes[class_scope]
+                        .private_names
+                        .insert(name.clone(), id);
+                }
+            }
+        }
+        let result = self.parse_class_body(class, class_scope, name_hint);
+        self.leave_scope(class_scope, outer);
+        result
+    }
+
+    fn parse_class_body(
+        &mut self,
+        class: &Arc<Class>,
+        class_scope: ScopeId,
+        name_hint: Option<&str>,
+    ) -> Result<JSType, String> {
+        let function_prototype = self.intrinsics.function_prototype;
+        let (proto_parent, ctor_parent, derived) = match &class.extends {
+            None => (
+                Some(self.intrinsics.object_prototype),
+                function_prototype,
+                false,
+            ),
+            Some(expr) => {
+                let parent = self.parse(expr)?;
+                match parent {
+                    JSType::NULL => (None, function_prototype, true),
+                    JSType::Object(id) if self.obj(id).is_constructor() => {
+                        let proto = match self.get_property(&parent, "prototype")? {
+                            JSType::Object(proto) => Some(proto),
+                            JSType::NULL => None,
+                            value => {
+                                return Err(self.err(&format!(
+                                    "Uncaught TypeError: Class extends value does not have valid prototype property {}",
+                                    self.display(&value)
+                                )))
+                            }
+                        };
+                        (proto, id, true)
+                    }
+                    _ => {
+                        return Err(self.err(&format!(
+                        "Uncaught TypeError: Class extends value {} is not a constructor or null",
+                        self.display(&parent)
+                    )))
+                    }
+                }
+            }
+        };
+        let proto = self.alloc(JSObject::new(ObjectKind::Ordinary, proto_parent));
+        let name = class
+            .name
+            .as_deref()
+            .or(name_hint)
+            .unwrap_or_default()
+            .to_string();
+        let constructor = match &class.constructor {
+            Some(func) => func.clone(),
+            None => default_constructor(&name, derived),
+        };
+        let ctor = self.create_function(
+            constructor,
+            FunctionKind::ClassConstructor(derived),
+            Some(proto),
+            &name,
+        );
+        self.obj_mut(ctor).proto = Some(ctor_parent);
+        let mut prop = Property::readonly(JSType::Object(proto));
+        prop.configurable = false;
+        self.define_property(ctor, "prototype", prop);
+        self.define_property(proto, "constructor", Property::hidden(JSType::Object(ctor)));
+
+        let mut instance_elements: Vec<ClassElement> = Vec::new();
+        let mut static_elements = Vec::new();
+        for member in &class.members {
+            let target = if member.is_static { ctor } else { proto };
+            match &member.kind {
+                ClassMemberKind::Method(func)
+                | ClassMemberKind::Getter(func)
+                | ClassMemberKind::Setter(func) => {
+                    let key = self.parse_property_name(&member.key)?;
+                    let (value, name) = match &member.kind {
+                        ClassMemberKind::Method(_) => (None, key.function_name()),
+                        ClassMemberKind::Getter(_) => {
+                            (Some(true), format!("get {}", key.function_name()))
+                        }
+                        _ => (Some(false), format!("set {}", key.function_name())),
+                    };
+                    let f = self.create_function(
+                        func.clone(),
+                        FunctionKind::Method,
+                        Some(target),
+                        &name,
+                    );
+                    let f = JSType::Object(f);
+                    if let PropertyName::Private(private) = &member.key {
+                        let id = self.resolve_private_name(private)?;
+                        let prop = match value {
+                            None => Property::readonly(f),
+                            Some(is_get) => {
+                                let existing = if member.is_static {
+                                    self.obj(ctor).get_private(id).cloned()
+                                } else {
+                                    instance_elements.iter().find_map(|e| match e {
+                                        ClassElement::PrivateMethod(i, p) if *i == id => {
+                                            Some(p.clone())
+                                        }
+                                        _ => None,
+                                    })
+                                };
+                                let (mut get, mut set) = match existing {
+                                    Some(Property {
+                                        value: PropertyValue::Accessor(get, set),
+                                        ..
+                                    }) => (get, set),
+                                    _ => (JSType::Undefined, JSType::Undefined),
+                                };
+                                if is_get {
+                                    get = f;
+                                } else {
+                                    set = f;
+                                }
+                                Property::accessor(get, set, false)
+                            }
+                        };
+                        if member.is_static {
+                            let obj = self.obj_mut(ctor);
+                            obj.private.retain(|(i, _)| *i != id);
+                            obj.private.push((id, prop));
+                        } else {
+                            instance_elements.retain(
+                                |e| !matches!(e, ClassElement::PrivateMethod(i, _) if *i == id),
+                            );
+                            instance_elements.push(ClassElement::PrivateMethod(id, prop));
+                        }
+                    } else {
+                        match value {
+                            None => self.define_property(target, &key, Property::hidden(f)),
+                            Some(is_get) => self.define_accessor(target, &key, is_get, f, false),
+                        }
+                    }
+                }
+                ClassMemberKind::Field(init) => {
+                    let key = match &member.key {
+                        PropertyName::Private(private) => {
+                            FieldKey::Private(self.resolve_private_name(private)?)
+                        }
+                        name => FieldKey::Name(self.parse_property_name(name)?),
+                    };
+                    if member.is_static {
+                        static_elements.push(StaticElement::Field(key, init.clone()));
+                    } else {
+                        instance_elements.push(ClassElement::Field(key, init.clone()));
+                    }
+                }
+                ClassMemberKind::StaticBlock(body) => {
+                    static_elements.push(StaticElement::Block(body.clone()));
+                }
+            }
+        }
+        if let ObjectKind::Function(closure) = &mut self.obj_mut(ctor).kind {
+            closure.elements = Arc::new(instance_elements);
+        }
+        if let Some(name) = &class.name {
+            self.scopes[class_scope]
+                .vars
+                .insert(name.clone(), (Variable::Const, JSType::Object(ctor)));
+        }
+
+        //静态字段及静态块, this为构造函数
+        let this = JSType::Object(ctor);
+        for element in static_elements {
+            match element {
+                StaticElement::Field(key, init) => {
+                    let value =
+                        self.run_initializer(init.as_ref(), &key, &this, ctor, class_scope)?;
+                    self.define_field(ctor, key, value)?;
+                }
+                StaticElement::Block(body) => {
+                    let outer = self.scope;
+                    let scope = self.enter_initializer_scope(&this, ctor, class_scope);
+                    let result = self
+                        .hoist_declarations(&body, true)
+                        .and_then(|_| self.run_statements(&body));
+                    self.leave_scope(scope, outer);
+                    result?;
+                }
+            }
+        }
+        Ok(this)
+    }
+
+    /// 字段初始化及静态块的作用域, 可以使用this及super.x
+    fn enter_initializer_scope(
+        &mut self,
+        this: &JSType,
+        home: ObjectId,
+        parent: ScopeId,
+    ) -> ScopeId {
+        let scope = self.enter_scope(parent);
+        self.scopes[scope].function = Some(FunctionFrame {
+            this: Some(this.clone()),
+            func: None,
+            new_target: JSType::Undefined,
+            home_object: Some(home),
+            strict: true,
+        });
+        scope
+    }
+
+    fn run_initializer(
+        &mut self,
+        init: Option<&Expr>,
+        key: &FieldKey,
+        this: &JSType,
+        home: ObjectId,
+        class_scope: ScopeId,
+    ) -> Result<JSType, String> {
+        let Some(init) = init else {
+            return Ok(JSType::Undefined);
+        };
+        let outer = self.scope;
+        let scope = self.enter_initializer_scope(this, home, class_scope);
+        let result = match key {
+            FieldKey::Name(name) => self.parse_named(init, &name.function_name()),
+            FieldKey::Private(_) => self.parse(init),
+        };
+        self.leave_scope(scope, outer);
+        result
+    }
+
+    fn define_field(&mut self, id: ObjectId, key: FieldKey, value: JSType) -> Result<(), String> {
+        match key {
+            FieldKey::Name(name) => {
+                self.define_property(id, &name, Property::data(value));
+            }
+            FieldKey::Private(private) => {
+                if self.obj(id).get_private(private).is_some() {
+                    return Err(self.err(
+                        "Uncaught TypeError: Cannot initialize private field twice on the same object",
+                    ));
+                }
+                self.obj_mut(id)
+                    .private
+                    .push((private, Property::data(value)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 初始化实例的私有方法及字段, 基类在执行构造函数前, 派生类在super()返回后
+    pub(super) fn initialize_instance(
+        &mut self,
+        obj: ObjectId,
+        ctor: ObjectId,
+    ) -> Result<(), String> {
+        let ObjectKind::Function(closure) = &self.obj(ctor).kind else {
+            return Ok(());
+        };
+        if closure.elements.is_empty() {
+            return Ok(());
+        }
+        let elements = closure.elements.clone();
+        let class_scope = closure.scope;
+        let Some(home) = closure.home_object else {
+            return Ok(());
+        };
+        for element in elements.iter() {
+            if let ClassElement::PrivateMethod(id, prop) = element {
+                if self.obj(obj).get_private(*id).is_some() {
+                    return Err(self.err(
+                        "Uncaught TypeError: Cannot initialize private methods of class twice on the same object",
+                    ));
+                }
+                self.obj_mut(obj).private.push((*id, prop.clone()));
+            }
+        }
+        let this = JSType::Object(obj);
+        for element in elements.iter() {
+            if let ClassElement::Field(key, init) = element {
+                let value = self.run_initializer(init.as_ref(), key, &this, home, class_scope)?;
+                self.define_field(obj, key.clone(), value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// super(...args)
+    pub(super) fn parse_super_call(&mut self, args: Vec<JSType>) -> Result<JSType, String> {
+        let frame = self.function_frame();
+        let (Some(func), new_target) = (frame.func, frame.new_target.clone()) else {
+            return Err(self.err("Uncaught SyntaxError: 'super' keyword unexpected here"));
+        };
+        let parent = match self.obj(func).proto {
+            Some(parent) if self.obj(parent).is_constructor() => JSType::Object(parent),
+            parent => {
+                let parent = parent.map(JSType::Object).unwrap_or(JSType::NULL);
+                return Err(self.err(&format!(
+                    "Uncaught TypeError: Super constructor {} of anonymous class is not a constructor",
+                    self.display(&parent)
+                )));
+            }
+        };
+        let result = self.construct(&parent, args, Some(new_target))?;
+        let frame = self.function_frame_mut();
+        if frame.this.is_some() {
+            return Err(
+                self.err("Uncaught ReferenceError: Super constructor may only be called once")
+            );
+        }
+        frame.this = Some(result.clone());
+        if let JSType::Object(obj) = result {
+            self.initialize_instance(obj, func)?;
+        }
+        Ok(result)
+    }
+
+    /// super.x 从 home_object 的原型开始查找
+    fn super_base(&self) -> Result<Option<ObjectId>, String> {
+        match self.function_frame().home_object {
+            Some(home) => Ok(self.obj(home).proto),
+            None => Err(self.err("Uncaught SyntaxError: 'super' keyword unexpected here")),
+        }
+    }
+
+    pub(super) fn get_super_property(&mut self, key: &PropertyKey) -> Result<JSType, String> {
+        let this = self.get_this()?;
+        match self.super_base()? {
+            Some(base) => self.get_with_receiver(base, key, this),
+            None => Ok(JSType::Undefined),
+        }
+    }
+
+    pub(super) fn set_super_property(
+        &mut self,
+        key: &PropertyKey,
+        value: JSType,
+    ) -> Result<(), String> {
+        let this = self.get_this()?;
+        match self.super_base()? {
+            Some(base) => self.set_with_receiver(base, key, value, this).map(|_| ()),
+            None => self.set_property(&this, key.clone(), value),
+        }
+    }
+
+    fn resolve_private_name(&self, name: &str) -> Result<usize, String> {
+        let mut cur = Some(self.scope);
+        while let Some(i) = cur {
+            if let Some(id) = self.scopes[i].private_names.get(name) {
+                return Ok(*id);
+            }
+            cur = self.scopes[i].parent;
+        }
+        Err(self.err(&format!(
+            "Uncaught SyntaxError: Private field '#{}' must be declared in an enclosing class",
+            name
+        )))
+    }
+
+    /// a.#b
+    pub(super) fn get_private(&mut self, object: &JSType, name: &str) -> Result<JSType, String> {
+        let id = self.resolve_private_name(name)?;
+        let prop = match object {
+            JSType::Object(obj) => self.obj(*obj).get_private(id).cloned(),
+            _ => None,
+        };
+        match prop.map(|p| p.value) {
+            None => Err(self.err(&format!(
+                "Uncaught TypeError: Cannot read private member #{} from an object whose class did not declare it",
+                name
+            ))),
+            Some(PropertyValue::Data(value)) => Ok(value),
+            Some(PropertyValue::Accessor(JSType::Undefined, _)) => Err(self.err(&format!(
+                "Uncaught TypeError: '#{}' was defined without a getter",
+                name
+            ))),
+            Some(PropertyValue::Accessor(get, _)) => self.call(&get, object.clone(), Vec::new()),
+        }
+    }
+
+    /// a.#b = c
+    pub(super) fn set_private(
+        &mut self,
+        object: &JSType,
+        name: &str,
+        value: JSType,
+    ) -> Result<(), String> {
+        let id = self.resolve_private_name(name)?;
+        let prop = match object {
+            JSType::Object(obj) => self.obj(*obj).get_private(id).cloned(),
+            _ => None,
+        };
+        let Some(prop) = prop else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Cannot write private member #{} to an object whose class did not declare it",
+                name
+            )));
+        };
+        match prop.value {
+            PropertyValue::Data(_) if !prop.writable => Err(self.err(&format!(
+                "Uncaught TypeError: Private method '#{}' is not writable",
+                name
+            ))),
+            PropertyValue::Data(_) => {
+                let JSType::Object(obj) = object else {
+                    unreachable!()
+                };
+                if let Some((_, p)) = self
+                    .obj_mut(*obj)
+                    .private
+                    .iter_mut()
+                    .find(|(i, _)| *i == id)
+                {
+                    p.value = PropertyValue::Data(value);
+                }
+                Ok(())
+            }
+            PropertyValue::Accessor(_, JSType::Undefined) => Err(self.err(&format!(
+                "Uncaught TypeError: '#{}' was defined without a setter",
+                name
+            ))),
+            PropertyValue::Accessor(_, set) => {
+                self.call(&set, object.clone(), vec![value])?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 未定义constructor时的默认构造函数, 派生类为 constructor(...args){ super(...args) }
+fn default_constructor(name: &str, derived: bool) -> Arc<Function> {
+    let args = || Box::new(Expr::Identifier("args".to_string()));
+    let (params, body) = if derived {
+        (
+            vec![Expr::Spread(args())],
+            vec![Expr::Call(
+                Box::new(Expr::Super),
+                vec![Expr::Spread(args())],
+            )],
+        )
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    Arc::new(Function {
+        name: Some(name.to_string()),
+        params,
+        body,
+        is_generator: false,
+        is_async: false,
+        strict: true,
+    })
+}
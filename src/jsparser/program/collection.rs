@@ -0,0 +1,107 @@
+use super::JSType;
+use crate::jsparser::object::ObjectId;
+use crate::jsparser::string::JSString;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Map/Set的键, 按SameValueZero比较: NaN等于NaN, +0等于-0, 1等于1.0
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MapKey {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(u64),
+    BigInt(Arc<BigInt>),
+    String(JSString),
+    Symbol(usize),
+    Object(ObjectId),
+}
+
+impl MapKey {
+    fn new(value: &JSType) -> Self {
+        match value {
+            JSType::NULL => MapKey::Null,
+            JSType::Bool(b) => MapKey::Bool(*b),
+            JSType::Int(_) | JSType::Float(_) => {
+                let n = value.to_number();
+                let bits = if n.is_nan() {
+                    f64::NAN.to_bits()
+                } else if n == 0.0 {
+                    0
+                } else {
+                    n.to_bits()
+                };
+                MapKey::Number(bits)
+            }
+            JSType::BigInt(n) => MapKey::BigInt(n.clone()),
+            JSType::String(s) => MapKey::String(s.clone()),
+            JSType::Symbol(s) => MapKey::Symbol(s.id),
+            JSType::Object(id) => MapKey::Object(*id),
+            JSType::Undefined | JSType::Flag(_) => MapKey::Undefined,
+        }
+    }
+}
+
+/// Map/Set的内部数据, 按插入顺序保存
+/// 删除的项保留为None, 迭代中途删除/添加的项对正在进行的迭代可见
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MapData {
+    entries: Vec<Option<(JSType, JSType)>>,
+    index: HashMap<MapKey, usize>, //键 => entries中的位置
+}
+
+impl MapData {
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+    pub fn get(&self, key: &JSType) -> Option<&JSType> {
+        let pos = self.index.get(&MapKey::new(key))?;
+        self.entries[*pos].as_ref().map(|(_, v)| v)
+    }
+    pub fn has(&self, key: &JSType) -> bool {
+        self.index.contains_key(&MapKey::new(key))
+    }
+    /// 已有的键只替换值, 保留原来的位置; -0作为键时保存为+0
+    pub fn set(&mut self, key: JSType, value: JSType) {
+        let map_key = MapKey::new(&key);
+        if let Some(pos) = self.index.get(&map_key) {
+            if let Some((_, v)) = &mut self.entries[*pos] {
+                *v = value;
+            }
+            return;
+        }
+        let key = match key {
+            JSType::Float(0.0) => JSType::Int(0),
+            key => key,
+        };
+        self.index.insert(map_key, self.entries.len());
+        self.entries.push(Some((key, value)));
+    }
+    pub fn delete(&mut self, key: &JSType) -> bool {
+        match self.index.remove(&MapKey::new(key)) {
+            Some(pos) => {
+                self.entries[pos] = None;
+                true
+            }
+            None => false,
+        }
+    }
+    /// 位置不会重新使用, 正在进行的迭代可以继续看到之后添加的项
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+    }
+    /// pos及之后的第一项, 返回其位置
+    pub fn next_entry(&self, pos: usize) -> Option<(usize, &JSType, &JSType)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .skip(pos)
+            .find_map(|(i, entry)| entry.as_ref().map(|(k, v)| (i, k, v)))
+    }
+    /// 按插入顺序的键值
+    pub fn iter(&self) -> impl Iterator<Item = (&JSType, &JSType)> {
+        self.entries.iter().flatten().map(|(k, v)| (k, v))
+    }
+}
@@ -0,0 +1,207 @@
+use super::gc::{Marker, Trace};
+use super::module::syntax_error;
+use super::{HostFn, JSType, Program};
+use crate::jsparser::lexer::{ILexer, Lexer};
+use crate::jsparser::module::ModuleLoader;
+use crate::jsparser::object::{BoundFunction, JSObject, ObjectId, ObjectKind, Property};
+use crate::jsparser::parser::Parser;
+use std::sync::Arc;
+
+/// 模块内require使用的宿主方法, 第一个参数为引用方的文件名
+const REQUIRE_FROM: &str = "require from";
+
+/// CommonJS模式的状态
+pub(crate) struct CommonJs {
+    loader: Arc<dyn ModuleLoader>,
+    cache: ObjectId, //require.cache, 文件名 => module对象
+}
+
+impl Trace for CommonJs {
+    fn trace(&self, marker: &mut Marker) {
+        marker.mark(self.cache);
+    }
+}
+
+/// 文件所在目录, 与node的path.dirname相同
+fn dirname(filename: &str) -> &str {
+    match filename.rfind('/') {
+        Some(0) => "/",
+        Some(i) => &filename[..i],
+        None => ".",
+    }
+}
+
+impl Program {
+    /// 开启CommonJS模式, 注册全局require, 模块名由loader解析
+    pub fn enable_commonjs(&mut self, loader: Arc<dyn ModuleLoader>) {
+        let cache = self.alloc(JSObject::new(ObjectKind::Ordinary, None));
+        self.commonjs = Some(CommonJs { loader, cache });
+        //入口脚本中的require, 相对路径基于loader的根目录
+        self.register_method(
+            "require".to_string(),
+            Box::new(|pg, args| {
+                let id = args.first().cloned().unwrap_or(JSType::Undefined);
+                pg.require(&id, None)
+            }),
+        );
+        let require_from: HostFn = Arc::new(|pg: &mut Program, args: Vec<JSType>| {
+            let mut args = args.into_iter();
+            let referrer = match args.next() {
+                Some(JSType::String(referrer)) => referrer,
+                _ => {
+                    return Err(pg.err(
+                        "Uncaught TypeError: The \"referrer\" argument must be of type string",
+                    ))
+                }
+            };
+            let id = args.next().unwrap_or(JSType::Undefined);
+            pg.require(&id, Some(&referrer.to_string()))
+        });
+        self.global_fn_map
+            .insert(REQUIRE_FROM.to_string(), require_from);
+        let global = self.intrinsics.global_object;
+        if let Some(JSType::Object(require)) = self
+            .get_own_property(global, &"require".into())
+            .map(|p| p.data_value())
+        {
+            self.define_property(require, "cache", Property::data(JSType::Object(cache)));
+        }
+    }
+
+    /// require(id), 已缓存的模块直接返回exports, 循环引用时返回未执行完的exports
+    fn require(&mut self, id: &JSType, referrer: Option<&str>) -> Result<JSType, String> {
+        let Some(commonjs) = &self.commonjs else {
+            return Err(self.err("Uncaught ReferenceError: require is not defined"));
+        };
+        let (loader, cache) = (commonjs.loader.clone(), commonjs.cache);
+        let id =
+            match id {
+                JSType::String(id) if !id.is_empty() => id.to_string(),
+                JSType::String(_) => return Err(self.err(
+                    "Uncaught TypeError: The argument 'id' must be a non-empty string. Received ''",
+                )),
+                _ => {
+                    return Err(self.err(&format!(
+                    "Uncaught TypeError: The \"id\" argument must be of type string. Received {}",
+                    self.display(id)
+                )))
+                }
+            };
+        let not_found = format!("Uncaught Error: Cannot find module '{}'", id);
+        let Ok(filename) = loader.resolve(&id, referrer) else {
+            return Err(self.err(&not_found));
+        };
+        let cached = self.get_property(&JSType::Object(cache), filename.as_str())?;
+        if let JSType::Object(_) = cached {
+            return self.get_property(&cached, "exports");
+        }
+        let Ok(source) = loader.load(&filename) else {
+            return Err(self.err(&not_found));
+        };
+        let module = self.new_commonjs_module(&filename);
+        //先加入缓存, 循环引用时可以找到
+        self.define_property(cache, filename.as_str(), Property::data(module.clone()));
+        if let Err(e) = self.run_commonjs_module(&module, &filename, source) {
+            self.delete_property(cache, &filename.as_str().into())?;
+            return Err(e);
+        }
+        self.set_property(&module, "loaded", JSType::Bool(true))?;
+        self.get_property(&module, "exports")
+    }
+
+    /// module对象 { id, path, exports, filename, loaded }
+    fn new_commonjs_module(&mut self, filename: &str) -> JSType {
+        let module = self.new_object();
+        let JSType::Object(id) = module else {
+            unreachable!()
+        };
+        let exports = self.new_object();
+        let props = [
+            ("id", JSType::String(filename.into())),
+            ("path", JSType::String(dirname(filename).into())),
+            ("exports", exports),
+            ("filename", JSType::String(filename.into())),
+            ("loaded", JSType::Bool(false)),
+        ];
+        for (name, value) in props {
+            self.define_property(id, name, Property::data(value));
+        }
+        module
+    }
+
+    /// 执行模块代码, 源码包装为 function (exports, require, module, __filename, __dirname)
+    fn run_commonjs_module(
+        &mut self,
+        module: &JSType,
+        filename: &str,
+        source: String,
+    ) -> Result<(), String> {
+        if filename.ends_with(".json") {
+            let value = self.parse_json(&source).map_err(|e| {
+                e.replacen("SyntaxError: ", &format!("SyntaxError: {}: ", filename), 1)
+            })?;
+            return self.set_property(module, "exports", value);
+        }
+        let wrapped = format!(
+            "(function (exports, require, module, __filename, __dirname) {{{}\n}})",
+            source
+        );
+        let mut parser = Parser::new(Lexer::new(wrapped));
+        let statements = parser
+            .parse_script()
+            .map_err(|e| syntax_error(&e, filename))?;
+        //源码中多余的 } 会提前结束包装函数
+        let [wrapper] = statements.as_slice() else {
+            return Err(self.err(&format!(
+                "Uncaught SyntaxError: Unexpected token '}}' ({})",
+                filename
+            )));
+        };
+        let saved = self.scope;
+        self.scope = 0;
+        let func = self.evaluate(wrapper);
+        self.scope = saved;
+        let func = func?;
+        let require = self.module_require(filename);
+        let exports = self.get_property(module, "exports")?;
+        let args = vec![
+            exports.clone(),
+            require,
+            module.clone(),
+            JSType::String(filename.into()),
+            JSType::String(dirname(filename).into()),
+        ];
+        self.call(&func, exports, args).map(|_| ())
+    }
+
+    /// 模块内的require, 相对路径基于模块文件所在目录
+    fn module_require(&mut self, filename: &str) -> JSType {
+        let Some(commonjs) = &self.commonjs else {
+            unreachable!()
+        };
+        let cache = commonjs.cache;
+        let proto = self.intrinsics.function_prototype;
+        let target = self.alloc(JSObject::new(
+            ObjectKind::Host(REQUIRE_FROM.to_string()),
+            Some(proto),
+        ));
+        let bound = BoundFunction {
+            target,
+            this: JSType::Undefined,
+            args: vec![JSType::String(filename.into())],
+            constructor: false,
+        };
+        let id = self.alloc(JSObject::new(
+            ObjectKind::Bound(Box::new(bound)),
+            Some(proto),
+        ));
+        self.define_property(id, "length", Property::readonly(JSType::Int(1)));
+        self.define_property(
+            id,
+            "name",
+            Property::readonly(JSType::String("require".into())),
+        );
+        self.define_property(id, "cache", Property::data(JSType::Object(cache)));
+        JSType::Object(id)
+    }
+}
@@ -0,0 +1,353 @@
+use super::{array_length, JSType, Program};
+use crate::jsparser::object::{ObjectId, ObjectKind, Property, PropertyKey, PropertyValue};
+
+/// 属性描述, 未指定的字段为None
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PropertyDescriptor {
+    pub value: Option<JSType>,
+    pub writable: Option<bool>,
+    pub get: Option<JSType>,
+    pub set: Option<JSType>,
+    pub enumerable: Option<bool>,
+    pub configurable: Option<bool>,
+}
+
+impl PropertyDescriptor {
+    fn is_accessor(&self) -> bool {
+        self.get.is_some() || self.set.is_some()
+    }
+    fn is_data(&self) -> bool {
+        self.value.is_some() || self.writable.is_some()
+    }
+    /// 新属性, 未指定的字段为false/undefined
+    pub(super) fn to_property(&self) -> Property {
+        let value = if self.is_accessor() {
+            PropertyValue::Accessor(
+                self.get.clone().unwrap_or(JSType::Undefined),
+                self.set.clone().unwrap_or(JSType::Undefined),
+            )
+        } else {
+            PropertyValue::Data(self.value.clone().unwrap_or(JSType::Undefined))
+        };
+        Property {
+            value,
+            writable: self.writable.unwrap_or(false),
+            enumerable: self.enumerable.unwrap_or(false),
+            configurable: self.configurable.unwrap_or(false),
+        }
+    }
+    /// 修改已有的属性, 数据属性和访问器属性可以互相转换
+    fn apply(&self, mut prop: Property) -> Property {
+        match &mut prop.value {
+            PropertyValue::Data(_) if self.is_accessor() => {
+                prop.value = PropertyValue::Accessor(
+                    self.get.clone().unwrap_or(JSType::Undefined),
+                    self.set.clone().unwrap_or(JSType::Undefined),
+                );
+                prop.writable = false;
+            }
+            PropertyValue::Accessor(_, _) if self.is_data() => {
+                prop.value = PropertyValue::Data(self.value.clone().unwrap_or(JSType::Undefined));
+                prop.writable = self.writable.unwrap_or(false);
+            }
+            PropertyValue::Data(value) => {
+                if let Some(v) = &self.value {
+                    *value = v.clone();
+                }
+                if let Some(writable) = self.writable {
+                    prop.writable = writable;
+                }
+            }
+            PropertyValue::Accessor(get, set) => {
+                if let Some(g) = &self.get {
+                    *get = g.clone();
+                }
+                if let Some(s) = &self.set {
+                    *set = s.clone();
+                }
+            }
+        }
+        if let Some(enumerable) = self.enumerable {
+            prop.enumerable = enumerable;
+        }
+        if let Some(configurable) = self.configurable {
+            prop.configurable = configurable;
+        }
+        prop
+    }
+    /// 不可配置的属性只允许不改变属性的修改, 及把可写改为只读
+    fn is_compatible(&self, current: &Property) -> bool {
+        if current.configurable {
+            return true;
+        }
+        if self.configurable == Some(true)
+            || self.enumerable.is_some_and(|e| e != current.enumerable)
+        {
+            return false;
+        }
+        let same =
+            |new: &Option<JSType>, old: &JSType| new.as_ref().is_none_or(|v| v.same_value(old));
+        match &current.value {
+            PropertyValue::Data(value) => {
+                !self.is_accessor()
+                    && (current.writable
+                        || (self.writable != Some(true) && same(&self.value, value)))
+            }
+            PropertyValue::Accessor(get, set) => {
+                !self.is_data() && same(&self.get, get) && same(&self.set, set)
+            }
+        }
+    }
+    /// IsCompatiblePropertyDescriptor, 属性不存在时对象需可扩展
+    pub(super) fn is_compatible_with(&self, extensible: bool, current: Option<&Property>) -> bool {
+        match current {
+            Some(current) => self.is_compatible(current),
+            None => extensible,
+        }
+    }
+}
+
+impl From<&Property> for PropertyDescriptor {
+    fn from(prop: &Property) -> Self {
+        match &prop.value {
+            PropertyValue::Data(value) => PropertyDescriptor {
+                value: Some(value.clone()),
+                writable: Some(prop.writable),
+                enumerable: Some(prop.enumerable),
+                configurable: Some(prop.configurable),
+                ..Default::default()
+            },
+            PropertyValue::Accessor(get, set) => PropertyDescriptor {
+                get: Some(get.clone()),
+                set: Some(set.clone()),
+                enumerable: Some(prop.enumerable),
+                configurable: Some(prop.configurable),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Program {
+    /// ToPropertyDescriptor, 描述对象 {value, writable, enumerable, configurable, get, set}
+    pub(crate) fn property_descriptor(
+        &mut self,
+        desc: &JSType,
+    ) -> Result<PropertyDescriptor, String> {
+        let JSType::Object(id) = desc else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Property description must be an object: {}",
+                self.display(desc)
+            )));
+        };
+        let id = *id;
+        let field = |program: &mut Self, key: &str| -> Result<Option<JSType>, String> {
+            if program.has_property(id, &key.into())? {
+                return Ok(Some(program.get_property(desc, key)?));
+            }
+            Ok(None)
+        };
+        let mut result = PropertyDescriptor {
+            enumerable: field(self, "enumerable")?.map(|v| v.is_truthy()),
+            configurable: field(self, "configurable")?.map(|v| v.is_truthy()),
+            value: field(self, "value")?,
+            writable: field(self, "writable")?.map(|v| v.is_truthy()),
+            ..Default::default()
+        };
+        for (index, key) in ["get", "set"].iter().enumerate() {
+            let Some(func) = field(self, key)? else {
+                continue;
+            };
+            if !matches!(func, JSType::Undefined) && !self.is_callable(&func) {
+                let name = if index == 0 { "Getter" } else { "Setter" };
+                return Err(self.err(&format!(
+                    "Uncaught TypeError: {} must be a function: {}",
+                    name,
+                    self.display(&func)
+                )));
+            }
+            if index == 0 {
+                result.get = Some(func);
+            } else {
+                result.set = Some(func);
+            }
+        }
+        if result.is_accessor() && result.is_data() {
+            return Err(self.err("Uncaught TypeError: Invalid property descriptor. Cannot both specify accessors and a value or writable attribute"));
+        }
+        Ok(result)
+    }
+
+    /// FromPropertyDescriptor, Object.getOwnPropertyDescriptor的结果
+    pub(crate) fn descriptor_object(&mut self, prop: &Property) -> JSType {
+        self.partial_descriptor_object(&PropertyDescriptor::from(prop))
+    }
+
+    /// 只包含已指定的字段, 传给Proxy的defineProperty
+    pub(crate) fn partial_descriptor_object(&mut self, desc: &PropertyDescriptor) -> JSType {
+        let result = self.new_object();
+        let JSType::Object(id) = result else {
+            unreachable!()
+        };
+        let fields = [
+            ("value", desc.value.clone()),
+            ("writable", desc.writable.map(JSType::Bool)),
+            ("get", desc.get.clone()),
+            ("set", desc.set.clone()),
+            ("enumerable", desc.enumerable.map(JSType::Bool)),
+            ("configurable", desc.configurable.map(JSType::Bool)),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                self.define_property(id, name, Property::data(value));
+            }
+        }
+        result
+    }
+
+    /// [[DefineOwnProperty]], ValidateAndApplyPropertyDescriptor, 不允许修改时返回false
+    pub(crate) fn define_own_property(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        desc: &PropertyDescriptor,
+    ) -> Result<bool, String> {
+        if self.is_proxy(id) {
+            return self.proxy_define_own_property(id, key, desc);
+        }
+        //类型化数组的元素只能是可写/可枚举/可配置的数据属性
+        if let Some(index) = self.integer_index(id, key) {
+            let Some(index) = index else {
+                return Ok(false);
+            };
+            if desc.is_accessor()
+                || desc.configurable == Some(false)
+                || desc.enumerable == Some(false)
+                || desc.writable == Some(false)
+            {
+                return Ok(false);
+            }
+            if let Some(value) = &desc.value {
+                let kind = self.typed_array_kind(id).unwrap();
+                let value = self.element_value(kind, value)?;
+                self.typed_array_set(id, index, &value);
+            }
+            return Ok(true);
+        }
+        let Some(current) = self.get_own_property(id, key) else {
+            if !self.obj(id).extensible || self.exceeds_fixed_length(id, key) {
+                return Ok(false);
+            }
+            self.define_property(id, key, desc.to_property());
+            return Ok(true);
+        };
+        if !desc.is_compatible(&current) {
+            return Ok(false);
+        }
+        self.define_property(id, key, desc.apply(current));
+        //数组缩短时遇到不可配置的元素, length没有改为指定的值
+        if let (ObjectKind::Array(_, length), Some(value)) = (&self.obj(id).kind, &desc.value) {
+            if key.is("length") && array_length(value) != Some(*length) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Object.defineProperty, 不允许修改时抛出TypeError
+    pub(crate) fn define_property_or_throw(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        desc: &PropertyDescriptor,
+    ) -> Result<(), String> {
+        if self.define_own_property(id, key, desc)? {
+            return Ok(());
+        }
+        if self.is_proxy(id) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: 'defineProperty' on proxy: trap returned falsish for property '{}'",
+                key
+            )));
+        }
+        if self.integer_index(id, key).is_some() {
+            return Err(self.err("Uncaught TypeError: Invalid typed array index"));
+        }
+        if self.get_own_property(id, key).is_none() {
+            //数组length不可写时添加下标与不可扩展的提示相同
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Cannot define property {}, object is not extensible",
+                key
+            )));
+        }
+        Err(self.err(&format!(
+            "Uncaught TypeError: Cannot redefine property: {}",
+            key
+        )))
+    }
+
+    /// Object.defineProperties / Object.create 的第二个参数
+    pub(crate) fn define_properties(&mut self, id: ObjectId, props: &JSType) -> Result<(), String> {
+        let src = self.object_value(props)?;
+        let mut list = Vec::new();
+        for key in self.own_property_keys(src)? {
+            if let Some(prop) = self.own_property(src, &key)? {
+                if prop.enumerable {
+                    let desc = self.get_property(props, &key)?;
+                    list.push((key, self.property_descriptor(&desc)?));
+                }
+            }
+        }
+        for (key, desc) in list {
+            self.define_property_or_throw(id, &key, &desc)?;
+        }
+        Ok(())
+    }
+
+    /// SetIntegrityLevel, 所有属性改为不可配置, 冻结时数据属性改为只读
+    pub(crate) fn set_integrity_level(
+        &mut self,
+        id: ObjectId,
+        frozen: bool,
+    ) -> Result<bool, String> {
+        if !self.prevent_extensions(id)? {
+            return Ok(false);
+        }
+        for key in self.own_property_keys(id)? {
+            let mut desc = PropertyDescriptor {
+                configurable: Some(false),
+                ..Default::default()
+            };
+            if frozen {
+                let Some(prop) = self.own_property(id, &key)? else {
+                    continue;
+                };
+                if let PropertyValue::Data(_) = prop.value {
+                    desc.writable = Some(false);
+                }
+            }
+            self.define_property_or_throw(id, &key, &desc)?;
+        }
+        Ok(true)
+    }
+
+    /// TestIntegrityLevel, Object.isFrozen / Object.isSealed
+    pub(crate) fn test_integrity_level(
+        &mut self,
+        id: ObjectId,
+        frozen: bool,
+    ) -> Result<bool, String> {
+        if self.is_extensible(id)? {
+            return Ok(false);
+        }
+        for key in self.own_property_keys(id)? {
+            if let Some(prop) = self.own_property(id, &key)? {
+                let writable = prop.writable && matches!(prop.value, PropertyValue::Data(_));
+                if prop.configurable || frozen && writable {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
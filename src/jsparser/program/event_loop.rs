@@ -0,0 +1,287 @@
+use super::gc::{Marker, Trace};
+use super::promise::Job;
+use super::{JSType, Program};
+use crate::jsparser::http::HttpRequest;
+use crate::jsparser::object::ObjectId;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// 事件循环及Date使用的时钟, 单位毫秒
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u64;
+    /// 等待到指定时间, 没有可执行的任务时调用
+    fn wait_until(&self, deadline: u64);
+    /// 当前的Unix时间, Date.now及new Date()使用
+    fn time(&self) -> f64;
+    /// 本地时区与UTC的偏移(分钟, 东区为正), utc为Unix时间, 可按时间返回夏令时的偏移
+    fn local_offset(&self, _utc: f64) -> i64 {
+        0
+    }
+}
+
+/// 系统时钟, 等待时阻塞当前线程
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+    fn wait_until(&self, deadline: u64) {
+        let now = self.now();
+        if deadline > now {
+            std::thread::sleep(std::time::Duration::from_millis(deadline - now));
+        }
+    }
+    fn time(&self) -> f64 {
+        match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as f64,
+            Err(e) => -(e.duration().as_millis() as f64),
+        }
+    }
+}
+
+/// 虚拟时钟, 等待时直接跳到指定时间, 用于测试及wasm
+/// Date的时间为 epoch + 经过的时间, 默认从1970-01-01T00:00:00Z开始, 时区为UTC
+#[derive(Clone, Default)]
+#[allow(dead_code)] //lib中未使用
+pub struct VirtualClock {
+    now: Arc<Mutex<u64>>,
+    epoch: f64,  //now为0时的Unix时间
+    offset: i64, //本地时区与UTC的偏移(分钟)
+}
+
+#[allow(dead_code)] //lib中未使用
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// 从指定的Unix时间开始, 使用固定的时区偏移
+    pub fn with_time(epoch: f64, offset: i64) -> Self {
+        VirtualClock {
+            epoch,
+            offset,
+            ..Self::default()
+        }
+    }
+    /// 时间前进 ms 毫秒
+    pub fn advance(&self, ms: u64) {
+        *self.now.lock().unwrap() += ms;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> u64 {
+        *self.now.lock().unwrap()
+    }
+    fn wait_until(&self, deadline: u64) {
+        let mut now = self.now.lock().unwrap();
+        *now = (*now).max(deadline);
+    }
+    fn time(&self) -> f64 {
+        self.epoch + self.now() as f64
+    }
+    fn local_offset(&self, _utc: f64) -> i64 {
+        self.offset
+    }
+}
+
+/// 宏任务
+pub(crate) enum Task {
+    Timer(u64),                               //到期的定时器, 执行前已清除时跳过
+    Fetch(ObjectId, HttpRequest),             //fetch返回的promise及请求
+    Import(ObjectId, String, Option<String>), //import()返回的promise, 模块名及引用方
+}
+
+struct Timer {
+    callback: JSType,
+    args: Vec<JSType>,
+    interval: Option<u64>, //setInterval的间隔
+}
+
+/// 宏任务队列及定时器
+pub(crate) struct EventLoop {
+    clock: Arc<dyn Clock>,
+    tasks: VecDeque<Task>,
+    timers: HashMap<u64, Timer>,
+    queue: BinaryHeap<Reverse<(u64, u64, u64)>>, //(到期时间, 序号, 定时器id)
+    timer_count: u64,
+    sequence: u64,
+}
+
+/// 默认时钟, wasm中不能阻塞线程, 使用虚拟时钟
+pub fn default_clock() -> Arc<dyn Clock> {
+    #[cfg(target_arch = "wasm32")]
+    return Arc::new(VirtualClock::default());
+    #[cfg(not(target_arch = "wasm32"))]
+    return Arc::new(SystemClock::default());
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        EventLoop {
+            clock: default_clock(),
+            tasks: VecDeque::new(),
+            timers: HashMap::new(),
+            queue: BinaryHeap::new(),
+            timer_count: 0,
+            sequence: 0,
+        }
+    }
+}
+
+impl Trace for EventLoop {
+    fn trace(&self, marker: &mut Marker) {
+        for task in &self.tasks {
+            match task {
+                Task::Fetch(promise, _) | Task::Import(promise, _, _) => marker.mark(*promise),
+                Task::Timer(_) => {}
+            }
+        }
+        for timer in self.timers.values() {
+            timer.callback.trace(marker);
+            timer.args.trace(marker);
+        }
+    }
+}
+
+impl EventLoop {
+    fn schedule(&mut self, id: u64, delay: u64) {
+        self.sequence += 1;
+        let when = self.clock.now().saturating_add(delay);
+        self.queue.push(Reverse((when, self.sequence, id)));
+    }
+
+    /// 最早到期的定时器时间, 跳过已清除的
+    fn next_deadline(&mut self) -> Option<u64> {
+        while let Some(Reverse((when, _, id))) = self.queue.peek() {
+            if self.timers.contains_key(id) {
+                return Some(*when);
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    /// 到期的定时器加入宏任务队列
+    fn queue_due_timers(&mut self) {
+        let now = self.clock.now();
+        while let Some(when) = self.next_deadline() {
+            if when > now {
+                break;
+            }
+            if let Some(Reverse((_, _, id))) = self.queue.pop() {
+                self.tasks.push_back(Task::Timer(id));
+            }
+        }
+    }
+}
+
+impl Program {
+    /// 替换事件循环的时钟, 应在run之前调用
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.event_loop.clock = clock;
+    }
+
+    /// 当前的Unix时间
+    pub(crate) fn current_time(&self) -> f64 {
+        self.event_loop.clock.time()
+    }
+
+    /// 本地时区与UTC的偏移(分钟)
+    pub(crate) fn local_offset(&self, utc: f64) -> i64 {
+        self.event_loop.clock.local_offset(utc)
+    }
+
+    /// setTimeout/setInterval, 返回定时器id
+    pub(crate) fn set_timer(
+        &mut self,
+        callback: JSType,
+        delay: u64,
+        args: Vec<JSType>,
+        repeat: bool,
+    ) -> u64 {
+        let event_loop = &mut self.event_loop;
+        event_loop.timer_count += 1;
+        let id = event_loop.timer_count;
+        let timer = Timer {
+            callback,
+            args,
+            interval: repeat.then_some(delay),
+        };
+        event_loop.timers.insert(id, timer);
+        event_loop.schedule(id, delay);
+        id
+    }
+
+    /// clearTimeout/clearInterval, 已进入队列的任务也不再执行
+    pub(crate) fn clear_timer(&mut self, id: u64) {
+        self.event_loop.timers.remove(&id);
+    }
+
+    /// 加入宏任务队列
+    pub(super) fn post_task(&mut self, task: Task) {
+        self.event_loop.tasks.push_back(task);
+    }
+
+    /// queueMicrotask
+    pub(crate) fn queue_microtask(&mut self, callback: JSType) {
+        self.microtasks.push_back(Job::Callback(callback));
+    }
+
+    /// 执行微任务及宏任务直到全部完成, 定时器未到期时由时钟等待
+    pub fn run_until_idle(&mut self) -> Result<(), String> {
+        loop {
+            self.run_microtasks()?;
+            self.event_loop.queue_due_timers();
+            if self.event_loop.tasks.is_empty() {
+                let Some(deadline) = self.event_loop.next_deadline() else {
+                    return Ok(());
+                };
+                self.event_loop.clock.wait_until(deadline);
+                self.event_loop.queue_due_timers();
+            }
+            self.maybe_collect_garbage();
+            if let Some(task) = self.event_loop.tasks.pop_front() {
+                self.run_task(task)?;
+            }
+        }
+    }
+
+    fn run_task(&mut self, task: Task) -> Result<(), String> {
+        match task {
+            Task::Timer(id) => {
+                let event_loop = &mut self.event_loop;
+                let Some(timer) = event_loop.timers.get(&id) else {
+                    return Ok(());
+                };
+                let (callback, args) = (timer.callback.clone(), timer.args.clone());
+                match timer.interval {
+                    Some(interval) => event_loop.schedule(id, interval),
+                    None => {
+                        event_loop.timers.remove(&id);
+                    }
+                }
+                self.call(&callback, JSType::Undefined, args)?;
+            }
+            Task::Fetch(promise, request) => self.run_fetch(promise, request),
+            Task::Import(promise, specifier, referrer) => {
+                self.run_import(promise, specifier, referrer)
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use super::event_loop::Task;
+use super::{JSType, Program};
+use crate::jsparser::http::{HttpClient, HttpRequest, HttpResponse};
+use crate::jsparser::object::{JSObject, ObjectId, ObjectKind, Property, PropertyKey};
+use std::sync::Arc;
+
+impl Program {
+    /// 设置fetch使用的http实现, 未设置时fetch返回被拒绝的Promise
+    pub fn set_http_client(&mut self, client: Arc<dyn HttpClient>) {
+        self.http_client = Some(client);
+    }
+
+    /// fetch(url, {method, headers, body}), 请求在宏任务中发送
+    pub(crate) fn fetch(&mut self, input: &JSType, init: &JSType) -> JSType {
+        let proto = self.intrinsics.promise_prototype;
+        let promise = self.new_promise(proto);
+        match self.fetch_request(input, init) {
+            Ok(request) => self.post_task(Task::Fetch(promise, request)),
+            Err(e) => {
+                let reason = self.error_value(e);
+                self.reject_promise(promise, reason);
+            }
+        }
+        JSType::Object(promise)
+    }
+
+    fn fetch_request(&mut self, input: &JSType, init: &JSType) -> Result<HttpRequest, String> {
+        let mut request = HttpRequest {
+            method: "GET".to_string(),
+            url: self.string_value(input)?,
+            headers: Vec::new(),
+            body: None,
+        };
+        if !matches!(init, JSType::Object(_)) {
+            return Ok(request);
+        }
+        let method = self.get_property(init, "method")?;
+        if !matches!(method, JSType::Undefined) {
+            request.method = self.string_value(&method)?.to_uppercase();
+        }
+        if let JSType::Object(headers) = self.get_property(init, "headers")? {
+            request.headers = match &self.obj(headers).kind {
+                ObjectKind::Headers(list) => list.clone(),
+                _ => self.header_list(headers)?,
+            };
+        }
+        let body = self.get_property(init, "body")?;
+        if !matches!(body, JSType::Undefined | JSType::NULL) {
+            if matches!(request.method.as_str(), "GET" | "HEAD") {
+                return Err(
+                    self.err("Uncaught TypeError: Request with GET/HEAD method cannot have body.")
+                );
+            }
+            request.body = Some(self.string_value(&body)?);
+        }
+        Ok(request)
+    }
+
+    /// 普通对象 { name: value } 转为请求头
+    fn header_list(&mut self, id: ObjectId) -> Result<Vec<(String, String)>, String> {
+        let mut headers = Vec::new();
+        for key in self.own_property_keys(id)? {
+            let PropertyKey::String(name) = &key else {
+                continue;
+            };
+            if !self.own_property(id, &key)?.is_some_and(|p| p.enumerable) {
+                continue;
+            }
+            let value = self.get_property(&JSType::Object(id), name.as_str())?;
+            headers.push((name.to_lowercase(), self.string_value(&value)?));
+        }
+        Ok(headers)
+    }
+
+    /// 执行请求并完成fetch返回的Promise
+    pub(super) fn run_fetch(&mut self, promise: ObjectId, request: HttpRequest) {
+        let result = match &self.http_client {
+            Some(client) => client.send(&request),
+            None => Err("no HTTP client".to_string()),
+        };
+        match result {
+            Ok(response) => {
+                let response = self.new_response(response);
+                self.resolve_promise(promise, response);
+            }
+            Err(e) => {
+                let reason = self.error_value(format!("Uncaught TypeError: fetch failed: {}", e));
+                self.reject_promise(promise, reason);
+            }
+        }
+    }
+
+    fn new_response(&mut self, response: HttpResponse) -> JSType {
+        let proto = self.intrinsics.headers_prototype;
+        let headers = self.alloc(JSObject::new(
+            ObjectKind::Headers(response.headers),
+            Some(proto),
+        ));
+        let proto = self.intrinsics.response_prototype;
+        let id = self.alloc(JSObject::new(
+            ObjectKind::Response(Some(response.body)),
+            Some(proto),
+        ));
+        let ok = (200..300).contains(&response.status);
+        let props = [
+            ("status", JSType::Int(response.status as i64)),
+            ("statusText", JSType::String(response.status_text.into())),
+            ("ok", JSType::Bool(ok)),
+            ("url", JSType::String(response.url.into())),
+            ("headers", JSType::Object(headers)),
+        ];
+        for (name, value) in props {
+            self.define_property(id, name, Property::readonly(value));
+        }
+        JSType::Object(id)
+    }
+
+    /// Response.text()/json(), body只能读取一次
+    pub(crate) fn response_body(&mut self, this: &JSType, json: bool) -> Result<JSType, String> {
+        let body = match this {
+            JSType::Object(id) => match &mut self.obj_mut(*id).kind {
+                ObjectKind::Response(body) => body.take(),
+                _ => return Err(self.err("Uncaught TypeError: Illegal invocation")),
+            },
+            _ => return Err(self.err("Uncaught TypeError: Illegal invocation")),
+        };
+        let proto = self.intrinsics.promise_prototype;
+        let promise = self.new_promise(proto);
+        let result = match body {
+            None => {
+                Err(self.err("Uncaught TypeError: Body is unusable: Body has already been read"))
+            }
+            Some(text) if json => self.parse_json(&text),
+            Some(text) => Ok(JSType::String(text.into())),
+        };
+        match result {
+            Ok(value) => self.resolve_promise(promise, value),
+            Err(e) => {
+                let reason = self.error_value(e);
+                self.reject_promise(promise, reason);
+            }
+        }
+        Ok(JSType::Object(promise))
+    }
+
+    /// Headers.get(), 同名的值以 ", " 连接
+    pub(crate) fn header_value(&mut self, this: &JSType, name: &JSType) -> Result<JSType, String> {
+        let name = self.string_value(name)?.to_lowercase();
+        let JSType::Object(id) = this else {
+            return Err(self.err("Uncaught TypeError: Illegal invocation"));
+        };
+        let ObjectKind::Headers(list) = &self.obj(*id).kind else {
+            return Err(self.err("Uncaught TypeError: Illegal invocation"));
+        };
+        let values: Vec<&str> = list
+            .iter()
+            .filter(|(k, _)| k.to_lowercase() == name)
+            .map(|(_, v)| v.as_str())
+            .collect();
+        Ok(if values.is_empty() {
+            JSType::NULL
+        } else {
+            JSType::String(values.join(", ").into())
+        })
+    }
+}
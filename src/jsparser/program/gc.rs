@@ -0,0 +1,361 @@
+use super::{JSType, JSTypeFlag, Program};
+use crate::jsparser::builtins::Intrinsics;
+use crate::jsparser::object::{
+    FunctionFrame, IteratorState, JSObject, ModuleBinding, ObjectId, ObjectKind, Property,
+    PropertyMap, PropertyValue, Scope, ScopeId,
+};
+
+/// 存活对象少于该数量时不自动回收
+pub(super) const MIN_GC_THRESHOLD: usize = 10_000;
+
+/// 可能引用堆中对象的结构
+pub(crate) trait Trace {
+    fn trace(&self, marker: &mut Marker);
+}
+
+/// 标记阶段的状态
+pub(crate) struct Marker {
+    marked: Vec<bool>,
+    stack: Vec<ObjectId>, //已标记但未扫描的对象
+    marked_scopes: Vec<bool>,
+    scope_stack: Vec<ScopeId>, //已标记但未扫描的作用域
+}
+
+impl Marker {
+    pub fn mark(&mut self, id: ObjectId) {
+        if !self.marked[id] {
+            self.marked[id] = true;
+            self.stack.push(id);
+        }
+    }
+
+    /// 闭包/生成器/模块引用的作用域, 扫描时继续标记外层作用域
+    pub fn mark_scope(&mut self, id: ScopeId) {
+        if !self.marked_scopes[id] {
+            self.marked_scopes[id] = true;
+            self.scope_stack.push(id);
+        }
+    }
+}
+
+impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, marker: &mut Marker) {
+        if let Some(value) = self {
+            value.trace(marker);
+        }
+    }
+}
+
+impl<T: Trace + ?Sized> Trace for &T {
+    fn trace(&self, marker: &mut Marker) {
+        (*self).trace(marker);
+    }
+}
+
+impl<T: Trace> Trace for [T] {
+    fn trace(&self, marker: &mut Marker) {
+        self.iter().for_each(|value| value.trace(marker));
+    }
+}
+
+impl Trace for JSType {
+    fn trace(&self, marker: &mut Marker) {
+        match self {
+            JSType::Object(id) => marker.mark(*id),
+            JSType::Flag(JSTypeFlag::Return(value)) => value.trace(marker),
+            _ => {}
+        }
+    }
+}
+
+impl Trace for Property {
+    fn trace(&self, marker: &mut Marker) {
+        match &self.value {
+            PropertyValue::Data(value) => value.trace(marker),
+            PropertyValue::Accessor(get, set) => {
+                get.trace(marker);
+                set.trace(marker);
+            }
+        }
+    }
+}
+
+impl Trace for PropertyMap {
+    fn trace(&self, marker: &mut Marker) {
+        for key in self.keys() {
+            self.get(key).trace(marker);
+        }
+    }
+}
+
+impl Trace for JSObject {
+    fn trace(&self, marker: &mut Marker) {
+        if let Some(proto) = self.proto {
+            marker.mark(proto);
+        }
+        self.properties.trace(marker);
+        for (_, prop) in &self.private {
+            prop.trace(marker);
+        }
+        match &self.kind {
+            ObjectKind::Array(list, _) => list.trace(marker),
+            ObjectKind::Function(closure) => {
+                marker.mark_scope(closure.scope);
+                if let Some(home) = closure.home_object {
+                    marker.mark(home);
+                }
+            }
+            ObjectKind::Bound(bound) => {
+                marker.mark(bound.target);
+                bound.this.trace(marker);
+                bound.args.trace(marker);
+            }
+            ObjectKind::Iterator(state) => match state.as_ref() {
+                IteratorState::Array(target, _, _) => target.trace(marker),
+                IteratorState::Map(target, _, _) => marker.mark(*target),
+                IteratorState::AsyncFromSync(iterator, next) => {
+                    iterator.trace(marker);
+                    next.trace(marker);
+                }
+                IteratorState::String(_, _) | IteratorState::Done => {}
+            },
+            ObjectKind::Generator(state) => state.as_deref().trace(marker),
+            ObjectKind::Promise(data) => data.trace(marker),
+            ObjectKind::PromiseFunction(func) => func.trace(marker),
+            ObjectKind::Namespace(list) => {
+                for (_, binding) in list {
+                    match binding {
+                        ModuleBinding::Value(value) => value.trace(marker),
+                        ModuleBinding::Variable(scope, _) => marker.mark_scope(*scope),
+                    }
+                }
+            }
+            ObjectKind::Primitive(value) => value.trace(marker),
+            ObjectKind::TypedArray(_, view) | ObjectKind::DataView(view) => {
+                marker.mark(view.buffer)
+            }
+            ObjectKind::Proxy(data) => {
+                if let Some((target, handler)) = data.handler {
+                    marker.mark(target);
+                    marker.mark(handler);
+                }
+            }
+            ObjectKind::Map(data) | ObjectKind::Set(data) => {
+                for (key, value) in data.iter() {
+                    key.trace(marker);
+                    value.trace(marker);
+                }
+            }
+            //缓存的format/compare函数
+            ObjectKind::NumberFormat(data) => {
+                if let Some(id) = data.bound_format {
+                    marker.mark(id);
+                }
+            }
+            ObjectKind::DateTimeFormat(data) => {
+                if let Some(id) = data.bound_format {
+                    marker.mark(id);
+                }
+            }
+            ObjectKind::Collator(data) => {
+                if let Some(id) = data.bound_compare {
+                    marker.mark(id);
+                }
+            }
+            //WeakMap的值在键可达时才标记, 见collect_garbage
+            _ => {}
+        }
+    }
+}
+
+impl Trace for Scope {
+    fn trace(&self, marker: &mut Marker) {
+        if let Some(parent) = self.parent {
+            marker.mark_scope(parent);
+        }
+        for (scope, _) in self.imports.values() {
+            marker.mark_scope(*scope);
+        }
+        for (_, value) in self.vars.values() {
+            value.trace(marker);
+        }
+        self.function.trace(marker);
+    }
+}
+
+impl Trace for FunctionFrame {
+    fn trace(&self, marker: &mut Marker) {
+        self.this.trace(marker);
+        self.new_target.trace(marker);
+        for id in [self.func, self.home_object].into_iter().flatten() {
+            marker.mark(id);
+        }
+    }
+}
+
+impl Trace for Intrinsics {
+    fn trace(&self, marker: &mut Marker) {
+        for id in [
+            self.object_prototype,
+            self.function_prototype,
+            self.array_prototype,
+            self.error_prototype,
+            self.type_error_prototype,
+            self.range_error_prototype,
+            self.reference_error_prototype,
+            self.syntax_error_prototype,
+            self.aggregate_error_prototype,
+            self.string_prototype,
+            self.number_prototype,
+            self.boolean_prototype,
+            self.symbol_prototype,
+            self.bigint_prototype,
+            self.iterator_prototype,
+            self.array_iterator_prototype,
+            self.string_iterator_prototype,
+            self.map_iterator_prototype,
+            self.set_iterator_prototype,
+            self.async_from_sync_iterator_prototype,
+            self.generator_prototype,
+            self.promise,
+            self.promise_prototype,
+            self.response_prototype,
+            self.headers_prototype,
+            self.map_prototype,
+            self.set_prototype,
+            self.weak_map_prototype,
+            self.weak_set_prototype,
+            self.date_prototype,
+            self.array_buffer_prototype,
+            self.data_view_prototype,
+            self.number_format_prototype,
+            self.date_time_format_prototype,
+            self.collator_prototype,
+            self.typed_array,
+            self.typed_array_prototype,
+            self.global_object,
+            self.array,
+        ]
+        .iter()
+        .chain(&self.typed_array_prototypes)
+        {
+            marker.mark(*id);
+        }
+    }
+}
+
+impl Program {
+    /// 标记-清除, 回收不可达的对象和作用域并删除WeakMap/WeakSet中键已不可达的项, 返回回收的对象数量
+    /// 只能在Rust中没有持有对象和作用域时调用, 如事件循环的宏任务之间
+    pub fn collect_garbage(&mut self) -> usize {
+        let mut marker = Marker {
+            marked: vec![false; self.heap.len()],
+            stack: Vec::new(),
+            marked_scopes: vec![false; self.scopes.len()],
+            scope_stack: Vec::new(),
+        };
+        self.trace_roots(&mut marker);
+        let mut weak_maps = Vec::new();
+        loop {
+            while !marker.stack.is_empty() || !marker.scope_stack.is_empty() {
+                while let Some(id) = marker.stack.pop() {
+                    let obj = &self.heap[id];
+                    if let ObjectKind::WeakMap(_) = obj.kind {
+                        weak_maps.push(id);
+                    }
+                    obj.trace(&mut marker);
+                }
+                while let Some(id) = marker.scope_stack.pop() {
+                    self.scopes[id].trace(&mut marker);
+                }
+            }
+            //键可达时值也可达, 值中可能引用其他WeakMap的键, 重复到没有新标记的对象
+            for &id in &weak_maps {
+                if let ObjectKind::WeakMap(map) = &self.heap[id].kind {
+                    for (key, value) in map.iter() {
+                        if marker.marked[*key] {
+                            value.trace(&mut marker);
+                        }
+                    }
+                }
+            }
+            if marker.stack.is_empty() {
+                break;
+            }
+        }
+        self.sweep_scopes(&marker.marked_scopes);
+        let mut free = vec![false; self.heap.len()];
+        for &id in &self.free_objects {
+            free[id] = true;
+        }
+        let marked = &marker.marked;
+        let mut count = 0;
+        for (id, obj) in self.heap.iter_mut().enumerate() {
+            if marked[id] {
+                match &mut obj.kind {
+                    ObjectKind::WeakMap(map) => map.retain(|key, _| marked[*key]),
+                    ObjectKind::WeakSet(set) => set.retain(|key| marked[*key]),
+                    _ => {}
+                }
+            } else if !free[id] {
+                *obj = JSObject::new(ObjectKind::Ordinary, None);
+                self.free_objects.push(id);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 不可达的作用域加入free_scopes复用, 包括离开时被闭包引用而未释放的作用域
+    fn sweep_scopes(&mut self, marked: &[bool]) {
+        let mut free = vec![false; self.scopes.len()];
+        for &id in &self.free_scopes {
+            free[id] = true;
+        }
+        for (id, scope) in self.scopes.iter_mut().enumerate() {
+            if !marked[id] && !free[id] {
+                *scope = Scope::default();
+                self.free_scopes.push(id);
+            }
+        }
+    }
+
+    /// 存活对象超过阈值时回收, 之后的阈值为回收后存活数量的两倍
+    pub(super) fn maybe_collect_garbage(&mut self) {
+        let live = self.heap.len() - self.free_objects.len();
+        if self.call_depth > 0 || live < self.gc_threshold {
+            return;
+        }
+        self.collect_garbage();
+        let live = self.heap.len() - self.free_objects.len();
+        self.gc_threshold = (live * 2).max(MIN_GC_THRESHOLD);
+    }
+
+    fn trace_roots(&self, marker: &mut Marker) {
+        self.intrinsics.trace(marker);
+        //全局作用域和当前的作用域链, 其他作用域由闭包等引用时才可达
+        marker.mark_scope(0);
+        marker.mark_scope(self.scope);
+        for job in &self.microtasks {
+            job.trace(marker);
+        }
+        if let Some((_, value)) = &self.thrown {
+            value.trace(marker);
+        }
+        self.event_loop.trace(marker);
+        for module in &self.modules {
+            module.trace(marker);
+        }
+        self.commonjs.trace(marker);
+        self.tape.trace(marker);
+        for &id in self
+            .pending_rejections
+            .iter()
+            .chain(&self.join_stack)
+            .chain(&self.gc_roots)
+            .chain(self.template_objects.values().map(|(_, id)| id))
+        {
+            marker.mark(id);
+        }
+    }
+}
@@ -0,0 +1,1022 @@
+use super::gc::{Marker, Trace};
+use super::iteration::IteratorRecord;
+use super::{JSType, Program};
+use crate::jsparser::expr::{
+    ClassMemberKind, ExportDeclaration, Expr, Function, ObjectMember, PropertyName, Variable,
+};
+use crate::jsparser::object::{JSObject, ObjectId, ObjectKind};
+use std::sync::Arc;
+
+/// yield时通过Err向上返回, 由生成器捕获后挂起
+pub(super) const GENERATOR_SUSPEND: &str = "\u{0}generator suspend";
+
+/// 生成器函数体编译后的指令, 控制流语句转为跳转, 表达式仍由 parse 执行
+#[derive(Debug)]
+pub(crate) enum Op {
+    Statement(Expr, bool),           //声明/表达式语句, bool: 是否包含yield
+    Return(Expr, bool),              //return
+    Jump(usize),                     //跳转
+    JumpIf(Expr, bool, bool, usize), //条件, 是否包含yield, 结果等于该值时跳转, 目标
+    EnterScope(Vec<Expr>),           //进入块级作用域, 提升其中的函数声明
+    LeaveScope,                      //离开块级作用域
+    CopyScope,                       //for(let...) 每次循环复制变量
+    ForOf(Expr, bool),               //for-of 创建迭代器
+    ForIn(Expr, bool),               //for-in 收集属性名
+    ForNext(Expr, usize),            //取下一个值绑定到变量(进入新作用域), 结束时跳转
+    CloseIterator,                   //break跳出for-of时关闭迭代器
+    PushValue(Expr, bool),           //switch的值
+    CaseJump(Expr, bool, usize),     //case与switch的值相等时跳转
+    PopValue,                        //switch结束
+}
+
+/// for-of/for-in 循环中的迭代器
+#[derive(Clone)]
+pub(crate) enum ForIterator {
+    Of(IteratorRecord),
+    In(JSType, Vec<String>, usize),
+}
+
+/// 表达式中遇到yield时挂起, 恢复后重新执行该表达式, 已完成的节点直接使用记录的结果
+#[derive(Clone, Default)]
+pub(crate) struct Tape {
+    results: Vec<Option<(usize, JSType)>>, //节点序号 => (子节点结束后的序号, 结果)
+    counter: usize,
+    pending: Option<YieldPoint>,
+}
+
+#[derive(Clone)]
+struct YieldPoint {
+    index: usize,
+    end: usize,
+    value: JSType,
+    kind: SuspendKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SuspendKind {
+    Yield,
+    Delegate, //yield*
+    Await,
+}
+
+impl Tape {
+    /// yield 表达式的结果
+    fn resolve(&mut self, value: JSType) {
+        if let Some(point) = self.pending.take() {
+            if self.results.len() <= point.index {
+                self.results.resize(point.index + 1, None);
+            }
+            self.results[point.index] = Some((point.end, value));
+        }
+        self.counter = 0;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum GeneratorStatus {
+    SuspendedStart,
+    SuspendedYield,
+    Completed,
+}
+
+/// 生成器的执行状态
+#[derive(Clone)]
+pub(crate) struct GeneratorState {
+    code: Arc<Vec<Op>>,
+    pc: usize,
+    scope: usize,
+    status: GeneratorStatus,
+    iterators: Vec<ForIterator>,
+    values: Vec<JSType>,
+    tape: Tape,
+    delegate: Option<IteratorRecord>,     //yield* 委托的迭代器
+    pub(super) promise: Option<ObjectId>, //async函数返回的Promise
+}
+
+impl Trace for ForIterator {
+    fn trace(&self, marker: &mut Marker) {
+        match self {
+            ForIterator::Of(record) => record.trace(marker),
+            ForIterator::In(target, _, _) => target.trace(marker),
+        }
+    }
+}
+
+impl Trace for Tape {
+    fn trace(&self, marker: &mut Marker) {
+        for (_, value) in self.results.iter().flatten() {
+            value.trace(marker);
+        }
+        if let Some(point) = &self.pending {
+            point.value.trace(marker);
+        }
+    }
+}
+
+impl Trace for GeneratorState {
+    fn trace(&self, marker: &mut Marker) {
+        marker.mark_scope(self.scope);
+        self.iterators.trace(marker);
+        self.values.trace(marker);
+        self.tape.trace(marker);
+        self.delegate.trace(marker);
+        if let Some(promise) = self.promise {
+            marker.mark(promise);
+        }
+    }
+}
+
+/// next / return / throw
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ResumeKind {
+    Next,
+    Return,
+    Throw,
+}
+
+/// 执行到yield/await或结束
+pub(super) enum Completion {
+    Yield(JSType),
+    Await(JSType),
+    Return(JSType),
+}
+
+impl Program {
+    /// 调用生成器函数: 绑定参数后返回生成器对象, 函数体在next()时执行
+    pub(super) fn start_generator(
+        &mut self,
+        id: usize,
+        func: &Function,
+        args: Vec<JSType>,
+    ) -> Result<JSType, String> {
+        self.bind_local_args(&func.params, args)?;
+        self.hoist_declarations(&func.body, true)?;
+        let state = self.generator_state(&func.body);
+        let proto = match self.get_property(&JSType::Object(id), "prototype")? {
+            JSType::Object(proto) => proto,
+            _ => self.intrinsics.generator_prototype,
+        };
+        Ok(JSType::Object(self.alloc(JSObject::new(
+            ObjectKind::Generator(Some(Box::new(state))),
+            Some(proto),
+        ))))
+    }
+
+    /// 编译函数体, 在当前作用域中执行
+    pub(super) fn generator_state(&mut self, body: &[Expr]) -> GeneratorState {
+        self.capture_scope(self.scope);
+        GeneratorState {
+            code: Arc::new(compile(body)),
+            pc: 0,
+            scope: self.scope,
+            status: GeneratorStatus::SuspendedStart,
+            iterators: Vec::new(),
+            values: Vec::new(),
+            tape: Tape::default(),
+            delegate: None,
+            promise: None,
+        }
+    }
+
+    /// generator.next(v) / return(v) / throw(e)
+    pub(crate) fn generator_resume(
+        &mut self,
+        this: &JSType,
+        kind: ResumeKind,
+        value: JSType,
+        method: &str,
+    ) -> Result<JSType, String> {
+        let id = match this {
+            JSType::Object(id) if matches!(self.obj(*id).kind, ObjectKind::Generator(_)) => *id,
+            _ => {
+                return Err(self.err(&format!(
+                    "Uncaught TypeError: {} method called on incompatible receiver {}",
+                    method,
+                    self.display(this)
+                )))
+            }
+        };
+        match self.resume_generator(id, kind, value)? {
+            Completion::Yield(value) => Ok(self.iter_result(value, false)),
+            Completion::Return(value) => Ok(self.iter_result(value, true)),
+            Completion::Await(_) => Err(self.err("Uncaught SyntaxError: Unexpected await")),
+        }
+    }
+
+    /// 恢复执行生成器对象中的函数体
+    pub(super) fn resume_generator(
+        &mut self,
+        id: ObjectId,
+        kind: ResumeKind,
+        value: JSType,
+    ) -> Result<Completion, String> {
+        let ObjectKind::Generator(slot) = &mut self.obj_mut(id).kind else {
+            unreachable!()
+        };
+        let Some(mut state) = slot.take() else {
+            return Err(self.err("Uncaught TypeError: Generator is already running"));
+        };
+        let result = self.resume_state(&mut state, kind, value);
+        if let ObjectKind::Generator(slot) = &mut self.obj_mut(id).kind {
+            *slot = Some(state);
+        }
+        result
+    }
+
+    fn resume_state(
+        &mut self,
+        state: &mut GeneratorState,
+        kind: ResumeKind,
+        value: JSType,
+    ) -> Result<Completion, String> {
+        match (state.status, kind) {
+            (GeneratorStatus::Completed, ResumeKind::Next) => {
+                return Ok(Completion::Return(JSType::Undefined))
+            }
+            (GeneratorStatus::Completed, ResumeKind::Return)
+            | (GeneratorStatus::SuspendedStart, ResumeKind::Return) => {
+                state.status = GeneratorStatus::Completed;
+                return Ok(Completion::Return(value));
+            }
+            (GeneratorStatus::Completed, ResumeKind::Throw)
+            | (GeneratorStatus::SuspendedStart, ResumeKind::Throw) => {
+                state.status = GeneratorStatus::Completed;
+                return Err(self.throw_value(&value));
+            }
+            _ => {}
+        }
+        let outer_scope = self.scope;
+        let outer_tape = self.tape.take();
+        self.enter_call()?;
+        self.scope = state.scope;
+        let result = self.resume_body(state, kind, value);
+        state.scope = self.scope;
+        self.scope = outer_scope;
+        self.tape = outer_tape;
+        self.call_depth -= 1;
+        state.status = match result {
+            Ok(Completion::Yield(_)) | Ok(Completion::Await(_)) => GeneratorStatus::SuspendedYield,
+            _ => GeneratorStatus::Completed,
+        };
+        result
+    }
+
+    /// 在yield处恢复执行
+    fn resume_body(
+        &mut self,
+        state: &mut GeneratorState,
+        kind: ResumeKind,
+        value: JSType,
+    ) -> Result<Completion, String> {
+        if state.status == GeneratorStatus::SuspendedYield {
+            if state.delegate.is_some() {
+                if let Some(completion) = self.resume_delegate(state, kind, value)? {
+                    return Ok(completion);
+                }
+            } else {
+                match kind {
+                    ResumeKind::Next => state.tape.resolve(value),
+                    ResumeKind::Return => return self.generator_return(state, value),
+                    ResumeKind::Throw => {
+                        let e = self.throw_value(&value);
+                        self.close_for_iterators(state);
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        match self.run_ops(state) {
+            Err(e) => {
+                self.close_for_iterators(state);
+                Err(e)
+            }
+            result => result,
+        }
+    }
+
+    /// yield* 将next/return/throw转发给内部迭代器, 内部迭代器结束时返回None继续执行
+    fn resume_delegate(
+        &mut self,
+        state: &mut GeneratorState,
+        kind: ResumeKind,
+        value: JSType,
+    ) -> Result<Option<Completion>, String> {
+        let Some(record) = state.delegate.clone() else {
+            return Ok(None);
+        };
+        let result = match kind {
+            ResumeKind::Next => self.call(&record.next, record.iterator.clone(), vec![value]),
+            ResumeKind::Throw => {
+                let method = self.get_property(&record.iterator, "throw")?;
+                if let JSType::Undefined | JSType::NULL = method {
+                    state.delegate = None;
+                    self.iterator_close(&record)?;
+                    return Err(self.err(
+                        "Uncaught TypeError: The iterator does not provide a 'throw' method",
+                    ));
+                }
+                self.call(&method, record.iterator.clone(), vec![value])
+            }
+            ResumeKind::Return => {
+                let method = self.get_property(&record.iterator, "return")?;
+                if let JSType::Undefined | JSType::NULL = method {
+                    state.delegate = None;
+                    return self.generator_return(state, value).map(Some);
+                }
+                self.call(&method, record.iterator.clone(), vec![value])
+            }
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(e) => {
+                state.delegate = None;
+                self.close_for_iterators(state);
+                return Err(e);
+            }
+        };
+        if !matches!(result, JSType::Object(_)) {
+            state.delegate = None;
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Iterator result {} is not an object",
+                self.display(&result)
+            )));
+        }
+        let value = self.get_property(&result, "value")?;
+        if !self.get_property(&result, "done")?.is_truthy() {
+            return Ok(Some(Completion::Yield(value)));
+        }
+        state.delegate = None;
+        if kind == ResumeKind::Return {
+            return self.generator_return(state, value).map(Some);
+        }
+        state.tape.resolve(value);
+        Ok(None)
+    }
+
+    /// return: 关闭循环中的迭代器后结束
+    fn generator_return(
+        &mut self,
+        state: &mut GeneratorState,
+        value: JSType,
+    ) -> Result<Completion, String> {
+        while let Some(iterator) = state.iterators.pop() {
+            if let ForIterator::Of(record) = iterator {
+                self.iterator_close(&record)?;
+            }
+        }
+        Ok(Completion::Return(value))
+    }
+
+    /// 异常结束时关闭迭代器, 忽略return()的异常
+    fn close_for_iterators(&mut self, state: &mut GeneratorState) {
+        while let Some(iterator) = state.iterators.pop() {
+            if let ForIterator::Of(record) = iterator {
+                let _ = self.iterator_close(&record);
+            }
+        }
+    }
+
+    fn run_ops(&mut self, state: &mut GeneratorState) -> Result<Completion, String> {
+        let code = state.code.clone();
+        while let Some(op) = code.get(state.pc) {
+            match self.run_op(state, op) {
+                Ok(Some(completion)) => return Ok(completion),
+                Ok(None) => {}
+                Err(e) if e == GENERATOR_SUSPEND => {
+                    let Some(point) = state.tape.pending.clone() else {
+                        return Err(e);
+                    };
+                    match point.kind {
+                        SuspendKind::Yield => return Ok(Completion::Yield(point.value)),
+                        SuspendKind::Await => return Ok(Completion::Await(point.value)),
+                        SuspendKind::Delegate => {}
+                    }
+                    //yield* 先取第一个值
+                    state.delegate = Some(self.get_iterator(&point.value)?);
+                    if let Some(completion) =
+                        self.resume_delegate(state, ResumeKind::Next, JSType::Undefined)?
+                    {
+                        return Ok(completion);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Completion::Return(JSType::Undefined))
+    }
+
+    /// 执行表达式, 包含yield时记录各节点的结果
+    fn eval_op(
+        &mut self,
+        state: &mut GeneratorState,
+        expr: &Expr,
+        has_yield: bool,
+    ) -> Result<JSType, String> {
+        if !has_yield {
+            return self.parse(expr);
+        }
+        state.tape.counter = 0;
+        self.tape = Some(std::mem::take(&mut state.tape));
+        let result = if let Expr::Variable(_) = expr {
+            self.run_statements(std::slice::from_ref(expr))
+        } else {
+            self.parse(expr)
+        };
+        state.tape = self.tape.take().unwrap_or_default();
+        if result.is_ok() {
+            state.tape = Tape::default();
+        }
+        result
+    }
+
+    fn run_op(
+        &mut self,
+        state: &mut GeneratorState,
+        op: &Op,
+    ) -> Result<Option<Completion>, String> {
+        let mut next = state.pc + 1;
+        match op {
+            Op::Statement(expr, has_yield) => {
+                if *has_yield {
+                    self.eval_op(state, expr, true)?;
+                } else {
+                    self.run_statements(std::slice::from_ref(expr))?;
+                }
+            }
+            Op::Return(expr, has_yield) => {
+                let value = self.eval_op(state, expr, *has_yield)?;
+                return self.generator_return(state, value).map(Some);
+            }
+            Op::Jump(target) => next = *target,
+            Op::JumpIf(test, has_yield, when, target) => {
+                if self.eval_op(state, test, *has_yield)?.is_truthy() == *when {
+                    next = *target;
+                }
+            }
+            Op::EnterScope(functions) => {
+                self.enter_scope(self.scope);
+                self.hoist_declarations(functions, false)?;
+            }
+            Op::LeaveScope => self.leave_block(),
+            Op::CopyScope => {
+                let scope = self.scope;
+                let vars = self.scopes[scope].vars.clone();
+                let next_scope = self.enter_scope(self.scopes[scope].parent.unwrap_or(0));
+                self.scopes[next_scope].vars = vars;
+                self.leave_scope(scope, next_scope);
+            }
+            Op::ForOf(expr, has_yield) => {
+                let iterable = self.eval_op(state, expr, *has_yield)?;
+                let record = self.get_iterator(&iterable)?;
+                state.iterators.push(ForIterator::Of(record));
+            }
+            Op::ForIn(expr, has_yield) => {
+                let target = self.eval_op(state, expr, *has_yield)?;
+                let keys = self.for_in_target_keys(&target)?;
+                state.iterators.push(ForIterator::In(target, keys, 0));
+            }
+            Op::ForNext(left, exit) => {
+                let value = match state.iterators.last_mut() {
+                    Some(ForIterator::Of(record)) => {
+                        let record = record.clone();
+                        self.iterator_step(&record)?
+                    }
+                    Some(ForIterator::In(target, keys, index)) => {
+                        let (target, keys, start) = (target.clone(), keys.clone(), *index);
+                        let mut found = None;
+                        for (i, key) in keys.iter().enumerate().skip(start) {
+                            if self.for_in_key_exists(&target, key)? {
+                                found = Some((i, key.clone()));
+                                break;
+                            }
+                        }
+                        if let Some(ForIterator::In(_, _, index)) = state.iterators.last_mut() {
+                            *index = found.as_ref().map(|(i, _)| i + 1).unwrap_or(keys.len());
+                        }
+                        found.map(|(_, key)| JSType::String(key.into()))
+                    }
+                    None => None,
+                };
+                match value {
+                    Some(value) => {
+                        self.enter_scope(self.scope);
+                        self.bind_for_target(left, value)?;
+                    }
+                    None => {
+                        state.iterators.pop();
+                        next = *exit;
+                    }
+                }
+            }
+            Op::CloseIterator => {
+                if let Some(ForIterator::Of(record)) = state.iterators.pop() {
+                    self.iterator_close(&record)?;
+                }
+            }
+            Op::PushValue(expr, has_yield) => {
+                let value = self.eval_op(state, expr, *has_yield)?;
+                state.values.push(value);
+            }
+            Op::CaseJump(test, has_yield, target) => {
+                let value = self.eval_op(state, test, *has_yield)?;
+                if state.values.last().is_some_and(|v| v.strict_equal(&value)) {
+                    next = *target;
+                }
+            }
+            Op::PopValue => {
+                state.values.pop();
+            }
+        }
+        state.pc = next;
+        Ok(None)
+    }
+
+    /// 离开当前块级作用域
+    fn leave_block(&mut self) {
+        let scope = self.scope;
+        let parent = self.scopes[scope].parent.unwrap_or(0);
+        self.leave_scope(scope, parent);
+    }
+
+    /// 表达式中包含yield时, 恢复执行后已完成的节点直接返回之前的结果
+    pub(super) fn parse_resumable(&mut self, e: &Expr) -> Result<JSType, String> {
+        let Some(tape) = self.tape.as_mut() else {
+            return self.evaluate(e);
+        };
+        let index = tape.counter;
+        tape.counter += 1;
+        if let Some(Some((end, value))) = tape.results.get(index) {
+            let value = value.clone();
+            tape.counter = *end;
+            return Ok(value);
+        }
+        let suspend = match e {
+            Expr::Yield(arg, false) => Some((arg, SuspendKind::Yield)),
+            Expr::Yield(arg, true) => Some((arg, SuspendKind::Delegate)),
+            Expr::Await(arg) => Some((arg, SuspendKind::Await)),
+            _ => None,
+        };
+        if let Some((arg, kind)) = suspend {
+            let value = self.parse(arg)?;
+            if let Some(tape) = self.tape.as_mut() {
+                tape.pending = Some(YieldPoint {
+                    index,
+                    end: tape.counter,
+                    value,
+                    kind,
+                });
+            }
+            return Err(GENERATOR_SUSPEND.to_string());
+        }
+        let value = self.evaluate(e)?;
+        if let Some(tape) = self.tape.as_mut() {
+            if tape.results.len() <= index {
+                tape.results.resize(index + 1, None);
+            }
+            tape.results[index] = Some((tape.counter, value.clone()));
+        }
+        Ok(value)
+    }
+
+    /// 抛出js值, 错误对象显示为 "Error: message"
+    pub(crate) fn throw_value(&mut self, value: &JSType) -> String {
+        let message = self.err(&format!(
+            "Uncaught {}",
+            self.inspect(value, &mut Vec::new())
+        ));
+        //转回js值时使用原来的值
+        self.thrown = Some((message.clone(), value.clone()));
+        message
+    }
+}
+
+/// break/continue 的目标
+struct JumpTarget {
+    labels: Vec<String>,
+    kind: TargetKind,
+    break_depth: usize,
+    continue_depth: usize,
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+#[derive(PartialEq)]
+enum TargetKind {
+    Loop,
+    Switch,
+    Block,
+}
+
+/// 运行时压栈的内容, break/continue跳出时需要释放
+#[derive(Clone, Copy)]
+enum Resource {
+    Scope,
+    Iterator,
+    Value,
+}
+
+#[derive(Default)]
+struct Compiler {
+    code: Vec<Op>,
+    resources: Vec<Resource>,
+    targets: Vec<JumpTarget>,
+}
+
+/// 编译生成器函数体
+fn compile(body: &[Expr]) -> Vec<Op> {
+    let mut compiler = Compiler::default();
+    for stmt in body {
+        compiler.statement(stmt);
+    }
+    compiler.code
+}
+
+impl Compiler {
+    fn pc(&self) -> usize {
+        self.code.len()
+    }
+
+    fn patch(&mut self, at: usize, target: usize) {
+        match &mut self.code[at] {
+            Op::Jump(t) | Op::JumpIf(_, _, _, t) | Op::ForNext(_, t) | Op::CaseJump(_, _, t) => {
+                *t = target
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn statement(&mut self, stmt: &Expr) {
+        match stmt {
+            //函数声明已提升
+            Expr::Function(f) if f.name.is_some() => {}
+            Expr::Empty => {}
+            Expr::Export(export) => match export.as_ref() {
+                ExportDeclaration::Declaration(decl) => self.statement(decl),
+                _ => self
+                    .code
+                    .push(Op::Statement(stmt.clone(), contains_await(stmt))),
+            },
+            Expr::Block(list) => self.block(list),
+            Expr::Variable(list) => {
+                //每个变量单独执行, 恢复时不会重复声明
+                for item in list {
+                    let expr = Expr::Variable(vec![item.clone()]);
+                    let has_yield = contains_suspend(&item.2);
+                    self.code.push(Op::Statement(expr, has_yield));
+                }
+            }
+            Expr::If(test, left, right) => {
+                let jump_else = self.pc();
+                self.code
+                    .push(Op::JumpIf(*test.clone(), contains_suspend(test), false, 0));
+                self.single(left);
+                if **right == Expr::Empty {
+                    let end = self.pc();
+                    self.patch(jump_else, end);
+                    return;
+                }
+                let jump_end = self.pc();
+                self.code.push(Op::Jump(0));
+                let else_pc = self.pc();
+                self.patch(jump_else, else_pc);
+                self.single(right);
+                let end = self.pc();
+                self.patch(jump_end, end);
+            }
+            Expr::For(..)
+            | Expr::ForIn(..)
+            | Expr::ForOf(..)
+            | Expr::While(..)
+            | Expr::DoWhile(..) => self.loop_statement(stmt, Vec::new()),
+            Expr::Labeled(..) => {
+                let mut labels = Vec::new();
+                let mut body = stmt;
+                while let Expr::Labeled(label, inner) = body {
+                    labels.push(label.clone());
+                    body = inner;
+                }
+                match body {
+                    Expr::For(..)
+                    | Expr::ForIn(..)
+                    | Expr::ForOf(..)
+                    | Expr::While(..)
+                    | Expr::DoWhile(..) => self.loop_statement(body, labels),
+                    _ => {
+                        self.push_target(labels, TargetKind::Block);
+                        self.single(body);
+                        self.pop_target(None);
+                    }
+                }
+            }
+            Expr::Switch(test, cases) => self.switch(test, cases),
+            Expr::Return(expr) => self
+                .code
+                .push(Op::Return(*expr.clone(), contains_suspend(expr))),
+            Expr::Break(label) => self.jump(label.as_deref(), false),
+            Expr::Continue(label) => self.jump(label.as_deref(), true),
+            _ => self
+                .code
+                .push(Op::Statement(stmt.clone(), contains_suspend(stmt))),
+        }
+    }
+
+    /// if/循环体中的单条语句
+    fn single(&mut self, stmt: &Expr) {
+        match stmt {
+            Expr::Function(f) if f.name.is_some() => self.block(std::slice::from_ref(stmt)),
+            _ => self.statement(stmt),
+        }
+    }
+
+    fn block(&mut self, list: &[Expr]) {
+        self.enter_scope(list);
+        for stmt in list {
+            self.statement(stmt);
+        }
+        self.leave_scope();
+    }
+
+    fn enter_scope(&mut self, list: &[Expr]) {
+        let functions = list
+            .iter()
+            .filter(|e| matches!(e, Expr::Function(f) if f.name.is_some()))
+            .cloned()
+            .collect();
+        self.code.push(Op::EnterScope(functions));
+        self.resources.push(Resource::Scope);
+    }
+
+    fn leave_scope(&mut self) {
+        self.resources.pop();
+        self.code.push(Op::LeaveScope);
+    }
+
+    fn push_target(&mut self, labels: Vec<String>, kind: TargetKind) {
+        let depth = self.resources.len();
+        self.targets.push(JumpTarget {
+            labels,
+            kind,
+            break_depth: depth,
+            continue_depth: depth,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+    }
+
+    /// 结束break/continue的目标, 回填跳转位置
+    fn pop_target(&mut self, continue_pc: Option<usize>) {
+        let Some(target) = self.targets.pop() else {
+            return;
+        };
+        let end = self.pc();
+        for at in target.breaks {
+            self.patch(at, end);
+        }
+        for at in target.continues {
+            self.patch(at, continue_pc.unwrap_or(end));
+        }
+    }
+
+    /// break/continue: 释放目标之后压栈的内容再跳转
+    fn jump(&mut self, label: Option<&str>, is_continue: bool) {
+        let found = self.targets.iter().rposition(|t| match label {
+            Some(label) => t.labels.iter().any(|l| l == label),
+            None if is_continue => t.kind == TargetKind::Loop,
+            None => t.kind != TargetKind::Block,
+        });
+        let Some(index) = found else {
+            return;
+        };
+        let depth = if is_continue {
+            self.targets[index].continue_depth
+        } else {
+            self.targets[index].break_depth
+        };
+        for resource in self.resources[depth..].iter().rev() {
+            self.code.push(match resource {
+                Resource::Scope => Op::LeaveScope,
+                Resource::Iterator => Op::CloseIterator,
+                Resource::Value => Op::PopValue,
+            });
+        }
+        let at = self.pc();
+        self.code.push(Op::Jump(0));
+        let target = &mut self.targets[index];
+        if is_continue {
+            target.continues.push(at);
+        } else {
+            target.breaks.push(at);
+        }
+    }
+
+    fn loop_statement(&mut self, stmt: &Expr, labels: Vec<String>) {
+        match stmt {
+            Expr::While(test, body) => {
+                let start = self.pc();
+                self.code
+                    .push(Op::JumpIf(*test.clone(), contains_suspend(test), false, 0));
+                self.push_target(labels, TargetKind::Loop);
+                self.single(body);
+                self.code.push(Op::Jump(start));
+                let end = self.pc();
+                self.patch(start, end);
+                self.pop_target(Some(start));
+            }
+            Expr::DoWhile(test, body) => {
+                let start = self.pc();
+                self.push_target(labels, TargetKind::Loop);
+                self.single(body);
+                let test_pc = self.pc();
+                self.code.push(Op::JumpIf(
+                    *test.clone(),
+                    contains_suspend(test),
+                    true,
+                    start,
+                ));
+                self.pop_target(Some(test_pc));
+            }
+            Expr::For(init, test, update, body) => {
+                let per_iteration = matches!(init.as_ref(), Expr::Variable(v) if v.iter().any(|i| i.0 != Variable::Var));
+                self.enter_scope(&[]);
+                self.statement(init);
+                if per_iteration {
+                    self.code.push(Op::CopyScope);
+                }
+                let start = self.pc();
+                let exit = if **test != Expr::Empty {
+                    self.code
+                        .push(Op::JumpIf(*test.clone(), contains_suspend(test), false, 0));
+                    Some(start)
+                } else {
+                    None
+                };
+                self.push_target(labels, TargetKind::Loop);
+                self.single(body);
+                let continue_pc = self.pc();
+                if per_iteration {
+                    self.code.push(Op::CopyScope);
+                }
+                if **update != Expr::Empty {
+                    self.code
+                        .push(Op::Statement(*update.clone(), contains_suspend(update)));
+                }
+                self.code.push(Op::Jump(start));
+                let end = self.pc();
+                if let Some(exit) = exit {
+                    self.patch(exit, end);
+                }
+                self.pop_target(Some(continue_pc));
+                self.leave_scope();
+            }
+            Expr::ForOf(left, right, body) | Expr::ForIn(left, right, body) => {
+                let has_yield = contains_suspend(right);
+                let break_depth = self.resources.len();
+                if let Expr::ForOf(..) = stmt {
+                    self.code.push(Op::ForOf(*right.clone(), has_yield));
+                } else {
+                    self.code.push(Op::ForIn(*right.clone(), has_yield));
+                }
+                self.resources.push(Resource::Iterator);
+                let start = self.pc();
+                self.code.push(Op::ForNext(*left.clone(), 0));
+                self.push_target(labels, TargetKind::Loop);
+                if let Some(target) = self.targets.last_mut() {
+                    target.break_depth = break_depth;
+                }
+                self.resources.push(Resource::Scope);
+                self.single(body);
+                self.resources.pop();
+                self.code.push(Op::LeaveScope);
+                self.code.push(Op::Jump(start));
+                self.resources.pop();
+                let end = self.pc();
+                self.patch(start, end);
+                self.pop_target(Some(start));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn switch(&mut self, test: &Expr, cases: &[Expr]) {
+        self.code
+            .push(Op::PushValue(test.clone(), contains_suspend(test)));
+        self.resources.push(Resource::Value);
+        let statements: Vec<Expr> = cases
+            .iter()
+            .filter_map(|case| match case {
+                Expr::SwitchCase(_, body) => Some(body.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        self.enter_scope(&statements);
+        self.push_target(Vec::new(), TargetKind::Switch);
+        let mut jumps = Vec::new();
+        for case in cases {
+            if let Expr::SwitchCase(case_test, _) = case {
+                if **case_test != Expr::Empty {
+                    jumps.push(Some(self.pc()));
+                    self.code.push(Op::CaseJump(
+                        *case_test.clone(),
+                        contains_suspend(case_test),
+                        0,
+                    ));
+                } else {
+                    jumps.push(None);
+                }
+            }
+        }
+        let default_jump = self.pc();
+        self.code.push(Op::Jump(0));
+        let mut default_pc = None;
+        for (case, jump) in cases.iter().zip(jumps) {
+            let Expr::SwitchCase(_, body) = case else {
+                continue;
+            };
+            let pc = self.pc();
+            match jump {
+                Some(at) => self.patch(at, pc),
+                None => default_pc = Some(pc),
+            }
+            for stmt in body {
+                self.statement(stmt);
+            }
+        }
+        let end = self.pc();
+        self.patch(default_jump, default_pc.unwrap_or(end));
+        self.pop_target(None);
+        self.leave_scope();
+        self.resources.pop();
+        self.code.push(Op::PopValue);
+    }
+}
+
+/// 表达式是否包含yield/await (不包括内部函数)
+pub(super) fn contains_suspend(expr: &Expr) -> bool {
+    match expr {
+        Expr::Yield(..) | Expr::Await(_) => true,
+        Expr::Unary(_, e)
+        | Expr::Spread(e)
+        | Expr::ImportCall(e)
+        | Expr::Return(e)
+        | Expr::PrivateMember(e, _)
+        | Expr::Update(e, _, _) => contains_suspend(e),
+        Expr::Member(a, b) | Expr::Infix(a, _, b) | Expr::Assignment(a, b) => {
+            contains_suspend(a) || contains_suspend(b)
+        }
+        Expr::Conditional(a, b, c) => contains_suspend(a) || contains_suspend(b) || contains_suspend(c),
+        Expr::Call(callee, args)
+        | Expr::New(callee, args)
+        | Expr::TaggedTemplate(callee, _, args) => {
+            contains_suspend(callee) || args.iter().any(contains_suspend)
+        }
+        Expr::Template(_, list) | Expr::Sequence(list) | Expr::Array(list) => {
+            list.iter().any(contains_suspend)
+        }
+        Expr::Variable(list) => list.iter().any(|(_, _, e)| contains_suspend(e)),
+        Expr::Object(members) => members.iter().any(|(key, member)| {
+            let in_key = matches!(key, PropertyName::Computed(e) if contains_suspend(e));
+            in_key
+                || matches!(member, ObjectMember::Value(e) | ObjectMember::Spread(e) | ObjectMember::Proto(e) if contains_suspend(e))
+        }),
+        Expr::Class(class) => {
+            class.extends.as_ref().is_some_and(|e| contains_suspend(e))
+                || class.members.iter().any(|m| {
+                    matches!(&m.key, PropertyName::Computed(e) if contains_suspend(e))
+                        && !matches!(m.kind, ClassMemberKind::StaticBlock(_))
+                })
+        }
+        _ => false,
+    }
+}
+
+/// 语句中是否包含await (顶层await)
+pub(super) fn contains_await(stmt: &Expr) -> bool {
+    match stmt {
+        Expr::Block(list) => list.iter().any(contains_await),
+        Expr::If(test, left, right) => {
+            contains_suspend(test) || contains_await(left) || contains_await(right)
+        }
+        Expr::For(init, test, update, body) => {
+            contains_await(init)
+                || contains_suspend(test)
+                || contains_suspend(update)
+                || contains_await(body)
+        }
+        Expr::ForIn(_, right, body) | Expr::ForOf(_, right, body) => {
+            contains_suspend(right) || contains_await(body)
+        }
+        Expr::While(test, body) | Expr::DoWhile(test, body) => {
+            contains_suspend(test) || contains_await(body)
+        }
+        Expr::Switch(test, cases) => contains_suspend(test) || cases.iter().any(contains_await),
+        Expr::SwitchCase(test, body) => contains_suspend(test) || body.iter().any(contains_await),
+        Expr::Labeled(_, body) => contains_await(body),
+        Expr::Export(export) => match export.as_ref() {
+            ExportDeclaration::Declaration(decl) => contains_await(decl),
+            ExportDeclaration::Default(expr) => contains_suspend(expr),
+            _ => false,
+        },
+        _ => contains_suspend(stmt),
+    }
+}
@@ -0,0 +1,274 @@
+use super::gc::{Marker, Trace};
+use super::promise::PromiseFunction;
+use super::{JSType, Program};
+use crate::jsparser::expr::{Expr, Variable};
+use crate::jsparser::object::{IteratorState, ObjectId, ObjectKind, PropertyKey};
+
+/// 迭代器及其next方法
+#[derive(Clone)]
+pub(crate) struct IteratorRecord {
+    pub iterator: JSType,
+    pub next: JSType,
+}
+
+impl Trace for IteratorRecord {
+    fn trace(&self, marker: &mut Marker) {
+        self.iterator.trace(marker);
+        self.next.trace(marker);
+    }
+}
+
+impl Program {
+    /// GetIterator: 调用 value[Symbol.iterator]()
+    pub(crate) fn get_iterator(&mut self, value: &JSType) -> Result<IteratorRecord, String> {
+        let method = match value {
+            JSType::Undefined | JSType::NULL => JSType::Undefined,
+            _ => {
+                let key = PropertyKey::Symbol(self.intrinsics.symbol_iterator.clone());
+                self.get_property(value, key)?
+            }
+        };
+        if !self.is_callable(&method) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not iterable",
+                self.display(value)
+            )));
+        }
+        let iterator = self.call(&method, value.clone(), Vec::new())?;
+        if !matches!(iterator, JSType::Object(_)) {
+            return Err(self
+                .err("Uncaught TypeError: Result of the Symbol.iterator method is not an object"));
+        }
+        let next = self.get_property(&iterator, "next")?;
+        Ok(IteratorRecord { iterator, next })
+    }
+
+    /// GetIterator(value, async): 调用 value[Symbol.asyncIterator](), 没有时包装同步迭代器
+    pub(crate) fn get_async_iterator(&mut self, value: &JSType) -> Result<JSType, String> {
+        let method = match value {
+            JSType::Undefined | JSType::NULL => JSType::Undefined,
+            _ => {
+                let key = PropertyKey::Symbol(self.intrinsics.symbol_async_iterator.clone());
+                self.get_property(value, key)?
+            }
+        };
+        if let JSType::Undefined | JSType::NULL = method {
+            let record = self.get_iterator(value)?;
+            let state = IteratorState::AsyncFromSync(record.iterator, record.next);
+            return Ok(self.new_iterator(state));
+        }
+        if !self.is_callable(&method) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not async iterable",
+                self.display(value)
+            )));
+        }
+        let iterator = self.call(&method, value.clone(), Vec::new())?;
+        if !matches!(iterator, JSType::Object(_)) {
+            return Err(self.err(
+                "Uncaught TypeError: Result of the Symbol.asyncIterator method is not an object",
+            ));
+        }
+        Ok(iterator)
+    }
+
+    /// 包装的同步迭代器的next, 等待value完成后返回 { value, done }
+    pub(crate) fn async_from_sync_next(&mut self, this: &JSType) -> Result<JSType, String> {
+        let record = match this {
+            JSType::Object(id) => match &self.obj(*id).kind {
+                ObjectKind::Iterator(state) => match state.as_ref() {
+                    IteratorState::AsyncFromSync(iterator, next) => Some(IteratorRecord {
+                        iterator: iterator.clone(),
+                        next: next.clone(),
+                    }),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some(record) = record else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Method [Async-from-Sync Iterator].prototype.next called on incompatible receiver {}",
+                self.display(this)
+            )));
+        };
+        let result = self.call(&record.next, record.iterator.clone(), Vec::new())?;
+        if !matches!(result, JSType::Object(_)) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Iterator result {} is not an object",
+                self.display(&result)
+            )));
+        }
+        let done = self.get_property(&result, "done")?.is_truthy();
+        let value = self.get_property(&result, "value")?;
+        let promise = JSType::Object(self.intrinsics.promise);
+        let value = self.promise_resolve(&promise, value)?;
+        let unwrap = self.promise_function(PromiseFunction::IteratorResult(done), 1);
+        self.promise_then(&value, unwrap, JSType::Undefined)
+    }
+
+    /// IteratorStep: 调用next, 结束时返回None
+    pub(crate) fn iterator_step(
+        &mut self,
+        record: &IteratorRecord,
+    ) -> Result<Option<JSType>, String> {
+        let result = self.call(&record.next, record.iterator.clone(), Vec::new())?;
+        if !matches!(result, JSType::Object(_)) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Iterator result {} is not an object",
+                self.display(&result)
+            )));
+        }
+        if self.get_property(&result, "done")?.is_truthy() {
+            return Ok(None);
+        }
+        Ok(Some(self.get_property(&result, "value")?))
+    }
+
+    /// IteratorClose: 提前结束时调用return()
+    pub(crate) fn iterator_close(&mut self, record: &IteratorRecord) -> Result<(), String> {
+        let method = self.get_property(&record.iterator, "return")?;
+        if let JSType::Undefined | JSType::NULL = method {
+            return Ok(());
+        }
+        let result = self.call(&method, record.iterator.clone(), Vec::new())?;
+        if !matches!(result, JSType::Object(_)) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Iterator result {} is not an object",
+                self.display(&result)
+            )));
+        }
+        Ok(())
+    }
+
+    /// 迭代全部的值 ...a / Array.from
+    pub(crate) fn iterate_to_list(&mut self, value: &JSType) -> Result<Vec<JSType>, String> {
+        let record = self.get_iterator(value)?;
+        let mut list = Vec::new();
+        while let Some(value) = self.iterator_step(&record)? {
+            list.push(value);
+        }
+        Ok(list)
+    }
+
+    /// for(a of b) 每次循环都是新的作用域, break/return/异常时关闭迭代器
+    pub(super) fn parse_for_of(
+        &mut self,
+        left: &Expr,
+        right: &Expr,
+        body: &Expr,
+        labels: &[String],
+    ) -> Result<JSType, String> {
+        let iterable = self.parse(right)?;
+        let record = self.get_iterator(&iterable)?;
+        while let Some(value) = self.iterator_step(&record)? {
+            match self.for_iteration(left, value, body, labels) {
+                Ok(None) => {}
+                Ok(Some(result)) => {
+                    self.iterator_close(&record)?;
+                    return Ok(result);
+                }
+                Err(e) => {
+                    //已有异常时忽略return()的异常
+                    let _ = self.iterator_close(&record);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(JSType::Undefined)
+    }
+
+    /// for(a in b) 遍历原型链上可枚举的字符串属性
+    pub(super) fn parse_for_in(
+        &mut self,
+        left: &Expr,
+        right: &Expr,
+        body: &Expr,
+        labels: &[String],
+    ) -> Result<JSType, String> {
+        let target = self.parse(right)?;
+        for key in self.for_in_target_keys(&target)? {
+            if !self.for_in_key_exists(&target, &key)? {
+                continue;
+            }
+            if let Some(result) =
+                self.for_iteration(left, JSType::String(key.into()), body, labels)?
+            {
+                return Ok(result);
+            }
+        }
+        Ok(JSType::Undefined)
+    }
+
+    /// for-in 遍历的属性名, 字符串为各字符的下标
+    pub(super) fn for_in_target_keys(&mut self, target: &JSType) -> Result<Vec<String>, String> {
+        match target {
+            JSType::Object(id) => self.for_in_keys(*id),
+            JSType::String(s) => Ok((0..s.len()).map(|i| i.to_string()).collect()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 循环中被删除的属性不再访问
+    pub(super) fn for_in_key_exists(&mut self, target: &JSType, key: &str) -> Result<bool, String> {
+        match target {
+            JSType::Object(id) => self.has_property(*id, &PropertyKey::from(key)),
+            _ => Ok(true),
+        }
+    }
+
+    /// 自身及原型链上可枚举的属性名, 已出现的同名属性会遮蔽原型上的
+    fn for_in_keys(&mut self, id: ObjectId) -> Result<Vec<String>, String> {
+        let mut visited: Vec<PropertyKey> = Vec::new();
+        let mut keys = Vec::new();
+        let mut cur = Some(id);
+        while let Some(obj) = cur {
+            for key in self.own_property_keys(obj)? {
+                if key.as_str().is_none() || visited.contains(&key) {
+                    continue;
+                }
+                let enumerable = self.own_property(obj, &key)?.is_some_and(|p| p.enumerable);
+                if enumerable {
+                    keys.push(key.to_string());
+                }
+                visited.push(key);
+            }
+            cur = self.get_prototype_of(&JSType::Object(obj))?;
+        }
+        Ok(keys)
+    }
+
+    /// 绑定循环变量并执行一次循环体, 返回Some时结束循环
+    fn for_iteration(
+        &mut self,
+        left: &Expr,
+        value: JSType,
+        body: &Expr,
+        labels: &[String],
+    ) -> Result<Option<JSType>, String> {
+        let outer = self.scope;
+        let scope = self.enter_scope(outer);
+        let result = (|| -> Result<Option<JSType>, String> {
+            self.bind_for_target(left, value)?;
+            self.loop_body(body, labels)
+        })();
+        self.leave_scope(scope, outer);
+        result
+    }
+
+    /// 将循环的值绑定到 var/let/const 声明或赋值目标
+    pub(super) fn bind_for_target(&mut self, left: &Expr, value: JSType) -> Result<(), String> {
+        match left {
+            Expr::Variable(v) => {
+                let (typ, name, _) = &v[0];
+                if *typ == Variable::Var {
+                    self.bind_local_arg(name, value)
+                } else {
+                    self.declare(typ.clone(), name, value)
+                }
+            }
+            _ => self.assign(left, value),
+        }
+    }
+}
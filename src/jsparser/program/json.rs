@@ -0,0 +1,465 @@
+use super::{JSType, Program};
+use crate::jsparser::object::{ObjectId, ObjectKind, Property, PropertyKey};
+use crate::jsparser::string::JSString;
+
+/// JSON文本解析
+struct JsonParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    program: &'a mut Program,
+}
+
+impl JsonParser<'_> {
+    fn error(&self) -> String {
+        match self.chars.get(self.pos) {
+            Some(c) => format!(
+                "Uncaught SyntaxError: Unexpected token '{}' in JSON at position {}",
+                c, self.pos
+            ),
+            None => "Uncaught SyntaxError: Unexpected end of JSON input".to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.get(self.pos), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.chars.get(self.pos) != Some(&c) {
+            return Err(self.error());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, word: &str, value: JSType) -> Result<JSType, String> {
+        for c in word.chars() {
+            self.expect(c)?;
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<JSType, String> {
+        self.skip_whitespace();
+        let value = match self.chars.get(self.pos) {
+            Some('{') => self.parse_object()?,
+            Some('[') => self.parse_array()?,
+            Some('"') => JSType::String(self.parse_string()?),
+            Some('t') => self.keyword("true", JSType::Bool(true))?,
+            Some('f') => self.keyword("false", JSType::Bool(false))?,
+            Some('n') => self.keyword("null", JSType::NULL)?,
+            Some('-' | '0'..='9') => self.parse_number()?,
+            _ => return Err(self.error()),
+        };
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    fn parse_object(&mut self) -> Result<JSType, String> {
+        self.expect('{')?;
+        let object = self.program.new_object();
+        let JSType::Object(id) = object else {
+            unreachable!()
+        };
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&'}') {
+            self.pos += 1;
+            return Ok(object);
+        }
+        loop {
+            self.skip_whitespace();
+            if self.chars.get(self.pos) != Some(&'"') {
+                return Err(self.error());
+            }
+            let key = self.parse_string()?.to_string();
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            self.program
+                .define_property(id, key.as_str(), Property::data(value));
+            match self.chars.get(self.pos) {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(object);
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JSType, String> {
+        self.expect('[')?;
+        let mut list = Vec::new();
+        self.skip_whitespace();
+        if self.chars.get(self.pos) == Some(&']') {
+            self.pos += 1;
+            return Ok(self.program.new_array(list));
+        }
+        loop {
+            list.push(self.parse_value()?);
+            match self.chars.get(self.pos) {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(self.program.new_array(list));
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let Some(digit) = self.chars.get(self.pos).and_then(|c| c.to_digit(16)) else {
+                return Err(self.error());
+            };
+            code = code * 16 + digit;
+            self.pos += 1;
+        }
+        Ok(code)
+    }
+
+    fn parse_string(&mut self) -> Result<JSString, String> {
+        self.expect('"')?;
+        let mut result = JSString::new();
+        loop {
+            let Some(&c) = self.chars.get(self.pos) else {
+                return Err(self.error());
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(result),
+                '\\' => {
+                    let Some(&escape) = self.chars.get(self.pos) else {
+                        return Err(self.error());
+                    };
+                    self.pos += 1;
+                    match escape {
+                        '"' | '\\' | '/' => result.push_code_point(escape as u32),
+                        'b' => result.push_str("\u{8}"),
+                        'f' => result.push_str("\u{c}"),
+                        'n' => result.push_str("\n"),
+                        'r' => result.push_str("\r"),
+                        't' => result.push_str("\t"),
+                        'u' => {
+                            let mut code = self.parse_hex4()?;
+                            //代理对
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.chars.get(self.pos) == Some(&'\\')
+                                && self.chars.get(self.pos + 1) == Some(&'u')
+                            {
+                                let start = self.pos;
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                if (0xDC00..0xE000).contains(&low) {
+                                    code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                                } else {
+                                    self.pos = start;
+                                }
+                            }
+                            result.push_code_point(code);
+                        }
+                        _ => {
+                            self.pos -= 1;
+                            return Err(self.error());
+                        }
+                    }
+                }
+                c if (c as u32) < 0x20 => {
+                    self.pos -= 1;
+                    return Err(self.error());
+                }
+                c => result.push_code_point(c as u32),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JSType, String> {
+        let start = self.pos;
+        if self.chars.get(self.pos) == Some(&'-') {
+            self.pos += 1;
+        }
+        match self.chars.get(self.pos) {
+            Some('0') => self.pos += 1,
+            Some('1'..='9') => self.skip_digits(),
+            _ => return Err(self.error()),
+        }
+        if self.chars.get(self.pos) == Some(&'.') {
+            self.pos += 1;
+            if !self.chars.get(self.pos).is_some_and(char::is_ascii_digit) {
+                return Err(self.error());
+            }
+            self.skip_digits();
+        }
+        if matches!(self.chars.get(self.pos), Some('e' | 'E')) {
+            self.pos += 1;
+            if matches!(self.chars.get(self.pos), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            if !self.chars.get(self.pos).is_some_and(char::is_ascii_digit) {
+                return Err(self.error());
+            }
+            self.skip_digits();
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        Ok(JSType::number(text.parse().unwrap_or(f64::NAN)))
+    }
+
+    fn skip_digits(&mut self) {
+        while self.chars.get(self.pos).is_some_and(char::is_ascii_digit) {
+            self.pos += 1;
+        }
+    }
+}
+
+impl Program {
+    /// 解析JSON文本, 格式错误时抛出 SyntaxError
+    pub(crate) fn parse_json(&mut self, text: &str) -> Result<JSType, String> {
+        let mut parser = JsonParser {
+            chars: text.chars().collect(),
+            pos: 0,
+            program: self,
+        };
+        let value = parser.parse_value()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error());
+        }
+        Ok(value)
+    }
+}
+
+/// JSON.stringify的状态
+struct JsonWriter {
+    replacer: Option<JSType>,           //replacer函数
+    property_list: Option<Vec<String>>, //replacer数组, 只输出其中的属性
+    gap: String,                        //每层缩进
+    indent: String,                     //当前缩进
+    stack: Vec<ObjectId>,               //正在序列化的对象, 用于检测循环引用
+}
+
+/// 字符串转为JSON字符串字面量, 单独的代理项转为 \uXXXX
+fn quote_json(s: &JSString) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in char::decode_utf16(s.units().iter().cloned()) {
+        match c {
+            Ok('"') => result.push_str("\\\""),
+            Ok('\\') => result.push_str("\\\\"),
+            Ok('\u{8}') => result.push_str("\\b"),
+            Ok('\u{c}') => result.push_str("\\f"),
+            Ok('\n') => result.push_str("\\n"),
+            Ok('\r') => result.push_str("\\r"),
+            Ok('\t') => result.push_str("\\t"),
+            Ok(c) if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            Ok(c) => result.push(c),
+            Err(e) => result.push_str(&format!("\\u{:04x}", e.unpaired_surrogate())),
+        }
+    }
+    result.push('"');
+    result
+}
+
+impl Program {
+    /// JSON.parse(text, reviver)
+    pub(crate) fn json_parse(&mut self, text: &JSType, reviver: &JSType) -> Result<JSType, String> {
+        let text = self.string_value(text)?;
+        let value = self.parse_json(&text)?;
+        if !self.is_callable(reviver) {
+            return Ok(value);
+        }
+        let JSType::Object(root) = self.new_object() else {
+            unreachable!()
+        };
+        self.define_property(root, "", Property::data(value));
+        self.internalize_json(root, "".into(), reviver)
+    }
+
+    /// 由内向外对每个属性调用reviver, 返回undefined时删除该属性
+    fn internalize_json(
+        &mut self,
+        holder: ObjectId,
+        key: PropertyKey,
+        reviver: &JSType,
+    ) -> Result<JSType, String> {
+        let value = self.get_property(&JSType::Object(holder), &key)?;
+        if let JSType::Object(id) = value {
+            let keys: Vec<String> = match &self.obj(id).kind {
+                ObjectKind::Array(_, len) => (0..*len).map(|i| i.to_string()).collect(),
+                _ => self.enumerable_own_keys(id)?,
+            };
+            for name in keys {
+                let name = PropertyKey::from(name);
+                let element = self.internalize_json(id, name.clone(), reviver)?;
+                if let JSType::Undefined = element {
+                    self.delete_property(id, &name)?;
+                } else {
+                    self.define_property(id, name, Property::data(element));
+                }
+            }
+        }
+        let args = vec![JSType::String(key.to_string().into()), value];
+        self.call(reviver, JSType::Object(holder), args)
+    }
+
+    /// JSON.stringify(value, replacer, space), 无法序列化时为undefined
+    pub(crate) fn json_stringify(
+        &mut self,
+        value: JSType,
+        replacer: &JSType,
+        space: &JSType,
+    ) -> Result<JSType, String> {
+        let mut writer = JsonWriter {
+            replacer: None,
+            property_list: None,
+            gap: String::new(),
+            indent: String::new(),
+            stack: Vec::new(),
+        };
+        if self.is_callable(replacer) {
+            writer.replacer = Some(replacer.clone());
+        } else if let JSType::Object(id) = replacer {
+            if let ObjectKind::Array(list, _) = &self.obj(*id).kind {
+                let mut names: Vec<String> = Vec::new();
+                for item in list.iter().flatten() {
+                    let name = match item {
+                        JSType::String(s) => s.to_string(),
+                        JSType::Int(_) | JSType::Float(_) => item.to_key(),
+                        _ => continue,
+                    };
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                writer.property_list = Some(names);
+            }
+        }
+        writer.gap = match space {
+            JSType::Int(_) | JSType::Float(_) => {
+                let n = space.to_number().clamp(0.0, 10.0);
+                " ".repeat(n as usize)
+            }
+            JSType::String(s) => s.slice(0, 10).to_string(),
+            _ => String::new(),
+        };
+        let JSType::Object(wrapper) = self.new_object() else {
+            unreachable!()
+        };
+        self.define_property(wrapper, "", Property::data(value));
+        let result = self.serialize_json_property(&mut writer, wrapper, "".into())?;
+        Ok(result.map_or(JSType::Undefined, |s| JSType::String(s.into())))
+    }
+
+    /// holder[key]的JSON文本, 函数/undefined/Symbol为None
+    fn serialize_json_property(
+        &mut self,
+        writer: &mut JsonWriter,
+        holder: ObjectId,
+        key: PropertyKey,
+    ) -> Result<Option<String>, String> {
+        let mut value = self.get_property(&JSType::Object(holder), &key)?;
+        if let JSType::Object(_) | JSType::BigInt(_) = value {
+            let to_json = self.get_property(&value, "toJSON")?;
+            if self.is_callable(&to_json) {
+                let args = vec![JSType::String(key.to_string().into())];
+                value = self.call(&to_json, value.clone(), args)?;
+            }
+        }
+        if let Some(replacer) = writer.replacer.clone() {
+            let args = vec![JSType::String(key.to_string().into()), value];
+            value = self.call(&replacer, JSType::Object(holder), args)?;
+        }
+        //包装对象序列化为其原始值
+        if let JSType::Object(id) = &value {
+            value = match &self.obj(*id).kind {
+                ObjectKind::Primitive(JSType::Int(_) | JSType::Float(_)) => {
+                    JSType::Float(self.number_value(&value)?)
+                }
+                ObjectKind::Primitive(JSType::String(_)) => {
+                    JSType::String(self.js_string_value(&value)?)
+                }
+                ObjectKind::Primitive(JSType::Bool(b)) => JSType::Bool(*b),
+                ObjectKind::Primitive(JSType::BigInt(n)) => JSType::BigInt(n.clone()),
+                _ => value,
+            };
+        }
+        if let JSType::BigInt(_) = value {
+            return Err(self.err("Uncaught TypeError: Do not know how to serialize a BigInt"));
+        }
+        Ok(match value {
+            JSType::NULL => Some("null".to_string()),
+            JSType::Bool(b) => Some(b.to_string()),
+            JSType::String(s) => Some(quote_json(&s)),
+            JSType::Int(n) => Some(n.to_string()),
+            JSType::Float(n) if n.is_finite() => Some(value.to_key()),
+            JSType::Float(_) => Some("null".to_string()),
+            JSType::Object(id) if !self.is_callable(&value) => {
+                Some(self.serialize_json_object(writer, id)?)
+            }
+            _ => None,
+        })
+    }
+
+    /// 对象及数组, 有缩进时每项一行
+    fn serialize_json_object(
+        &mut self,
+        writer: &mut JsonWriter,
+        id: ObjectId,
+    ) -> Result<String, String> {
+        if writer.stack.contains(&id) {
+            return Err(self.err("Uncaught TypeError: Converting circular structure to JSON"));
+        }
+        writer.stack.push(id);
+        let stepback = writer.indent.clone();
+        writer.indent.push_str(&writer.gap.clone());
+        let is_array = matches!(self.obj(id).kind, ObjectKind::Array(..));
+        let mut parts = Vec::new();
+        if is_array {
+            let length = self.get_property(&JSType::Object(id), "length")?;
+            let length = self.number_value(&length)? as usize;
+            for index in 0..length {
+                let item = self.serialize_json_property(writer, id, index.to_string().into())?;
+                parts.push(item.unwrap_or_else(|| "null".to_string()));
+            }
+        } else {
+            let keys = match &writer.property_list {
+                Some(list) => list.clone(),
+                None => self.enumerable_own_keys(id)?,
+            };
+            let colon = if writer.gap.is_empty() { ":" } else { ": " };
+            for key in keys {
+                let value = self.serialize_json_property(writer, id, key.as_str().into())?;
+                if let Some(value) = value {
+                    parts.push(format!(
+                        "{}{}{}",
+                        quote_json(&key.as_str().into()),
+                        colon,
+                        value
+                    ));
+                }
+            }
+        }
+        let (open, close) = if is_array { ("[", "]") } else { ("{", "}") };
+        let result = if parts.is_empty() {
+            format!("{}{}", open, close)
+        } else if writer.gap.is_empty() {
+            format!("{}{}{}", open, parts.join(","), close)
+        } else {
+            let separator = format!(",\n{}", writer.indent);
+            format!(
+                "{}\n{}{}\n{}{}",
+                open,
+                writer.indent,
+                parts.join(&separator),
+                stepback,
+                close
+            )
+        };
+        writer.stack.pop();
+        writer.indent = stepback;
+        Ok(result)
+    }
+}
@@ -0,0 +1,559 @@
+use super::event_loop::Task;
+use super::gc::{Marker, Trace};
+use super::{JSType, Program};
+use crate::jsparser::expr::{ExportDeclaration, Expr, ImportBinding, ImportDeclaration, Variable};
+use crate::jsparser::lexer::{ILexer, Lexer};
+use crate::jsparser::module::ModuleLoader;
+use crate::jsparser::object::{
+    FunctionFrame, JSObject, ModuleBinding, ObjectId, ObjectKind, Property, PropertyKey, Scope,
+    ScopeId,
+};
+use crate::jsparser::parser::Parser;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub(crate) type ModuleId = usize;
+
+/// export default 表达式的绑定名
+pub(super) const DEFAULT_BINDING: &str = "*default*";
+
+#[derive(Debug, Clone, PartialEq)]
+enum ModuleStatus {
+    Unlinked,
+    Linking,
+    Linked,
+    Evaluating,
+    Evaluated,
+    Errored(String),
+}
+
+/// 去掉解析错误的颜色及token位置, 作为SyntaxError抛出
+pub(super) fn syntax_error(e: &str, name: &str) -> String {
+    let message = e.find("Uncaught ").map_or(e, |i| &e[i..]);
+    let message = message.trim_end_matches("\x1b[39m").trim_end();
+    format!("{} ({})", message, name)
+}
+
+/// 模块的导出项
+#[derive(Debug, Clone)]
+enum ExportEntry {
+    Local(String, String),            //(导出名, 本地变量名)
+    Indirect(String, String, String), //export { a as b } from "m"  (导出名, 模块名, 导入名)
+    Namespace(String, String),        //export * as ns from "m"
+    Star(String),                     //export * from "m"
+}
+
+/// 导出名解析的结果
+#[derive(Debug, Clone, PartialEq)]
+enum ResolvedBinding {
+    Variable(ModuleId, String),
+    Namespace(ModuleId),
+}
+
+pub(crate) struct ModuleRecord {
+    name: String, //ModuleLoader::resolve 返回的名称
+    statements: Arc<Vec<Expr>>,
+    scope: ScopeId,
+    status: ModuleStatus,
+    imports: Vec<ImportDeclaration>,
+    exports: Vec<ExportEntry>,
+    requested: HashMap<String, ModuleId>, //模块名 => 依赖的模块
+    namespace: Option<ObjectId>,
+}
+
+/// 语句中是否包含import/export声明, 包含时入口脚本作为模块执行
+pub(super) fn is_module(statements: &[Expr]) -> bool {
+    statements
+        .iter()
+        .any(|stmt| matches!(stmt, Expr::Import(_) | Expr::Export(_)))
+}
+
+/// export声明的导出项
+fn export_entries(statements: &[Expr]) -> Vec<ExportEntry> {
+    let mut entries = Vec::new();
+    for stmt in statements {
+        let Expr::Export(export) = stmt else {
+            continue;
+        };
+        match export.as_ref() {
+            ExportDeclaration::Declaration(decl) => {
+                for name in declared_names(decl) {
+                    entries.push(ExportEntry::Local(name.clone(), name));
+                }
+            }
+            ExportDeclaration::Default(expr) => {
+                let local = match expr {
+                    Expr::Function(f) => f.name.clone(),
+                    Expr::Class(c) => c.name.clone(),
+                    _ => None,
+                };
+                let local = local.unwrap_or_else(|| DEFAULT_BINDING.to_string());
+                entries.push(ExportEntry::Local("default".to_string(), local));
+            }
+            ExportDeclaration::Named(names, None) => {
+                for (local, exported) in names {
+                    entries.push(ExportEntry::Local(exported.clone(), local.clone()));
+                }
+            }
+            ExportDeclaration::Named(names, Some(specifier)) => {
+                for (imported, exported) in names {
+                    entries.push(ExportEntry::Indirect(
+                        exported.clone(),
+                        specifier.clone(),
+                        imported.clone(),
+                    ));
+                }
+            }
+            ExportDeclaration::All(Some(exported), specifier) => {
+                entries.push(ExportEntry::Namespace(exported.clone(), specifier.clone()));
+            }
+            ExportDeclaration::All(None, specifier) => {
+                entries.push(ExportEntry::Star(specifier.clone()));
+            }
+        }
+    }
+    entries
+}
+
+fn declared_names(decl: &Expr) -> Vec<String> {
+    match decl {
+        Expr::Variable(list) => list.iter().map(|(_, name, _)| name.clone()).collect(),
+        Expr::Function(f) => f.name.iter().cloned().collect(),
+        Expr::Class(c) => c.name.iter().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 依赖的模块名, 按出现顺序
+fn requested_modules(statements: &[Expr]) -> Vec<String> {
+    let mut list: Vec<String> = Vec::new();
+    for stmt in statements {
+        let specifier = match stmt {
+            Expr::Import(import) => &import.specifier,
+            Expr::Export(export) => match export.as_ref() {
+                ExportDeclaration::Named(_, Some(specifier))
+                | ExportDeclaration::All(_, specifier) => specifier,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        if !list.contains(specifier) {
+            list.push(specifier.clone());
+        }
+    }
+    list
+}
+
+impl Trace for ModuleRecord {
+    fn trace(&self, marker: &mut Marker) {
+        marker.mark_scope(self.scope);
+        if let Some(namespace) = self.namespace {
+            marker.mark(namespace);
+        }
+    }
+}
+
+impl Program {
+    /// 设置import使用的模块加载, 未设置时import报错
+    pub fn set_module_loader(&mut self, loader: Arc<dyn ModuleLoader>) {
+        self.module_loader = Some(loader);
+    }
+
+    /// 入口脚本作为模块执行
+    pub(super) fn run_main_module(&mut self, statements: Vec<Expr>) -> Result<(), String> {
+        let id = self.add_module("<main>".to_string(), statements, None)?;
+        self.link_module(id)?;
+        self.evaluate_module(id)?;
+        self.run_until_idle()
+    }
+
+    /// 读取并解析模块, 已加载时直接返回
+    fn load_module(&mut self, specifier: &str, referrer: Option<&str>) -> Result<ModuleId, String> {
+        let Some(loader) = self.module_loader.clone() else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Failed to resolve module specifier \"{}\": no module loader",
+                specifier
+            )));
+        };
+        let name = loader
+            .resolve(specifier, referrer)
+            .map_err(|e| self.err(&format!("Uncaught TypeError: {}", e)))?;
+        if let Some(id) = self.module_map.get(&name) {
+            return Ok(*id);
+        }
+        let source = loader.load(&name).map_err(|e| {
+            self.err(&format!(
+                "Uncaught TypeError: Failed to load module '{}': {}",
+                name, e
+            ))
+        })?;
+        let mut parser = Parser::new(Lexer::new(source));
+        let statements = parser.parse_module().map_err(|e| syntax_error(&e, &name))?;
+        self.add_module(name.clone(), statements, Some(name))
+    }
+
+    /// 创建模块记录并加载依赖, 记录先于依赖加入, 循环引用时可以找到
+    fn add_module(
+        &mut self,
+        name: String,
+        statements: Vec<Expr>,
+        referrer: Option<String>,
+    ) -> Result<ModuleId, String> {
+        let mut scope = Scope::new(Some(0));
+        scope.function = Some(FunctionFrame {
+            this: Some(JSType::Undefined),
+            func: None,
+            new_target: JSType::Undefined,
+            home_object: None,
+            strict: true,
+        });
+        scope.captured = true;
+        self.scopes.push(scope);
+        let id = self.modules.len();
+        self.modules.push(ModuleRecord {
+            name: name.clone(),
+            imports: statements
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    Expr::Import(import) => Some(import.as_ref().clone()),
+                    _ => None,
+                })
+                .collect(),
+            exports: export_entries(&statements),
+            statements: Arc::new(statements),
+            scope: self.scopes.len() - 1,
+            status: ModuleStatus::Unlinked,
+            requested: HashMap::new(),
+            namespace: None,
+        });
+        self.module_map.insert(name, id);
+        for specifier in requested_modules(&self.modules[id].statements.clone()) {
+            match self.load_module(&specifier, referrer.as_deref()) {
+                Ok(dep) => {
+                    self.modules[id].requested.insert(specifier, dep);
+                }
+                Err(e) => {
+                    self.modules[id].status = ModuleStatus::Errored(e.clone());
+                    return Err(e);
+                }
+            }
+        }
+        Ok(id)
+    }
+
+    /// 绑定import的变量并提升函数声明, 循环依赖中的函数在执行前即可调用
+    fn link_module(&mut self, id: ModuleId) -> Result<(), String> {
+        match &self.modules[id].status {
+            ModuleStatus::Unlinked => {}
+            ModuleStatus::Errored(e) => return Err(e.clone()),
+            _ => return Ok(()),
+        }
+        self.modules[id].status = ModuleStatus::Linking;
+        let result = self.link_module_body(id);
+        self.modules[id].status = match &result {
+            Ok(_) => ModuleStatus::Linked,
+            Err(e) => ModuleStatus::Errored(e.clone()),
+        };
+        result
+    }
+
+    fn link_module_body(&mut self, id: ModuleId) -> Result<(), String> {
+        let deps: Vec<ModuleId> = self.modules[id].requested.values().copied().collect();
+        for dep in deps {
+            self.link_module(dep)?;
+        }
+        let scope = self.modules[id].scope;
+        for import in self.modules[id].imports.clone() {
+            let dep = self.modules[id].requested[&import.specifier];
+            for binding in &import.bindings {
+                let (imported, local) = match binding {
+                    ImportBinding::Namespace(local) => {
+                        let namespace = self.module_namespace(dep);
+                        self.declare_import(scope, local, ModuleBinding::Value(namespace))?;
+                        continue;
+                    }
+                    ImportBinding::Named(imported, local) => (imported, local),
+                };
+                let binding = match self.resolve_export(dep, imported, &mut Vec::new())? {
+                    Some(ResolvedBinding::Variable(module, name)) => {
+                        ModuleBinding::Variable(self.modules[module].scope, name)
+                    }
+                    Some(ResolvedBinding::Namespace(module)) => {
+                        ModuleBinding::Value(self.module_namespace(module))
+                    }
+                    None => {
+                        return Err(self.err(&format!(
+                            "Uncaught SyntaxError: The requested module '{}' does not provide an export named '{}'",
+                            import.specifier, imported
+                        )))
+                    }
+                };
+                self.declare_import(scope, local, binding)?;
+            }
+        }
+        for entry in self.modules[id].exports.clone() {
+            if let ExportEntry::Indirect(_, specifier, imported) = entry {
+                let dep = self.modules[id].requested[&specifier];
+                if self
+                    .resolve_export(dep, &imported, &mut Vec::new())?
+                    .is_none()
+                {
+                    return Err(self.err(&format!(
+                        "Uncaught SyntaxError: The requested module '{}' does not provide an export named '{}'",
+                        specifier, imported
+                    )));
+                }
+            }
+        }
+        let saved = self.scope;
+        self.scope = scope;
+        let statements = self.modules[id].statements.clone();
+        let result = self.hoist_declarations(&statements, true);
+        self.scope = saved;
+        result
+    }
+
+    fn declare_import(
+        &mut self,
+        scope: ScopeId,
+        local: &str,
+        binding: ModuleBinding,
+    ) -> Result<(), String> {
+        let target = &mut self.scopes[scope];
+        if target.vars.contains_key(local) || target.imports.contains_key(local) {
+            return Err(self.err(&format!(
+                "Uncaught SyntaxError: Identifier '{}' has already been declared",
+                local
+            )));
+        }
+        match binding {
+            ModuleBinding::Variable(module_scope, name) => {
+                target
+                    .imports
+                    .insert(local.to_string(), (module_scope, name));
+            }
+            ModuleBinding::Value(value) => {
+                target
+                    .vars
+                    .insert(local.to_string(), (Variable::Const, value));
+            }
+        }
+        Ok(())
+    }
+
+    /// 查找导出名对应的绑定, None为未找到, export * 中同名且来源不同时报错
+    fn resolve_export(
+        &self,
+        id: ModuleId,
+        name: &str,
+        visited: &mut Vec<(ModuleId, String)>,
+    ) -> Result<Option<ResolvedBinding>, String> {
+        if visited.iter().any(|(m, n)| *m == id && n == name) {
+            return Ok(None); //循环的export * from
+        }
+        visited.push((id, name.to_string()));
+        let module = &self.modules[id];
+        for entry in &module.exports {
+            match entry {
+                ExportEntry::Local(exported, local) if exported == name => {
+                    return Ok(Some(ResolvedBinding::Variable(id, local.clone())));
+                }
+                ExportEntry::Indirect(exported, specifier, imported) if exported == name => {
+                    return self.resolve_export(module.requested[specifier], imported, visited);
+                }
+                ExportEntry::Namespace(exported, specifier) if exported == name => {
+                    return Ok(Some(ResolvedBinding::Namespace(
+                        module.requested[specifier],
+                    )));
+                }
+                _ => {}
+            }
+        }
+        //export * 不包含default
+        if name == "default" {
+            return Ok(None);
+        }
+        let mut found = None;
+        for entry in &module.exports {
+            let ExportEntry::Star(specifier) = entry else {
+                continue;
+            };
+            let Some(binding) = self.resolve_export(module.requested[specifier], name, visited)?
+            else {
+                continue;
+            };
+            match &found {
+                None => found = Some(binding),
+                Some(existing) if *existing == binding => {}
+                Some(_) => {
+                    return Err(self.err(&format!(
+                        "Uncaught SyntaxError: The requested module '{}' contains conflicting star exports for name '{}'",
+                        module.name, name
+                    )))
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// 全部导出名, 包括export *中的名称
+    fn exported_names(&self, id: ModuleId, visited: &mut Vec<ModuleId>) -> Vec<String> {
+        if visited.contains(&id) {
+            return Vec::new();
+        }
+        visited.push(id);
+        let module = &self.modules[id];
+        let mut names = Vec::new();
+        for entry in &module.exports {
+            match entry {
+                ExportEntry::Local(name, _)
+                | ExportEntry::Indirect(name, _, _)
+                | ExportEntry::Namespace(name, _) => names.push(name.clone()),
+                ExportEntry::Star(specifier) => {
+                    let dep = module.requested[specifier];
+                    for name in self.exported_names(dep, visited) {
+                        if name != "default" && !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// 模块命名空间对象, 属性按名称排序且为只读的实时绑定
+    pub(crate) fn module_namespace(&mut self, id: ModuleId) -> JSType {
+        if let Some(namespace) = self.modules[id].namespace {
+            return JSType::Object(namespace);
+        }
+        let namespace = self.alloc(JSObject::new(ObjectKind::Namespace(Vec::new()), None));
+        let key = PropertyKey::Symbol(self.intrinsics.symbol_to_string_tag.clone());
+        let mut tag = Property::readonly(JSType::String("Module".into()));
+        tag.configurable = false;
+        self.define_property(namespace, key, tag);
+        self.obj_mut(namespace).extensible = false;
+        self.modules[id].namespace = Some(namespace);
+        let mut names = self.exported_names(id, &mut Vec::new());
+        names.sort();
+        let mut bindings = Vec::new();
+        for name in names {
+            //有冲突的export *名称不出现在命名空间中
+            let binding = match self.resolve_export(id, &name, &mut Vec::new()) {
+                Ok(Some(ResolvedBinding::Variable(module, local))) => {
+                    ModuleBinding::Variable(self.modules[module].scope, local)
+                }
+                Ok(Some(ResolvedBinding::Namespace(module))) => {
+                    ModuleBinding::Value(self.module_namespace(module))
+                }
+                _ => continue,
+            };
+            bindings.push((name, binding));
+        }
+        self.obj_mut(namespace).kind = ObjectKind::Namespace(bindings);
+        JSType::Object(namespace)
+    }
+
+    /// 先执行依赖再执行自身, 循环依赖中正在执行的模块直接跳过
+    fn evaluate_module(&mut self, id: ModuleId) -> Result<(), String> {
+        match &self.modules[id].status {
+            ModuleStatus::Evaluated | ModuleStatus::Evaluating => return Ok(()),
+            ModuleStatus::Errored(e) => return Err(e.clone()),
+            _ => {}
+        }
+        self.modules[id].status = ModuleStatus::Evaluating;
+        let result = self.evaluate_module_body(id);
+        self.modules[id].status = match &result {
+            Ok(_) => ModuleStatus::Evaluated,
+            Err(e) => ModuleStatus::Errored(e.clone()),
+        };
+        result
+    }
+
+    fn evaluate_module_body(&mut self, id: ModuleId) -> Result<(), String> {
+        let statements = self.modules[id].statements.clone();
+        for specifier in requested_modules(&statements) {
+            let dep = self.modules[id].requested[&specifier];
+            self.evaluate_module(dep)?;
+        }
+        let saved = self.scope;
+        self.scope = self.modules[id].scope;
+        let result = match self.run_top_level(&statements) {
+            Ok(true) => Ok(()),
+            Ok(false) => self.run_statements(&statements).map(|_| ()),
+            Err(e) => Err(e),
+        };
+        self.scope = saved;
+        result
+    }
+
+    /// 当前代码所在的模块名, 用于解析import()中的相对路径
+    fn current_module_name(&self) -> Option<String> {
+        let mut cur = Some(self.scope);
+        while let Some(scope) = cur {
+            if let Some(module) = self.modules.iter().find(|m| m.scope == scope) {
+                return Some(module.name.clone()).filter(|name| name != "<main>");
+            }
+            cur = self.scopes[scope].parent;
+        }
+        None
+    }
+
+    /// import(specifier), 模块在宏任务中加载执行
+    pub(super) fn dynamic_import(&mut self, specifier: &JSType) -> Result<JSType, String> {
+        let proto = self.intrinsics.promise_prototype;
+        let promise = self.new_promise(proto);
+        match self.string_value(specifier) {
+            Ok(specifier) => {
+                let referrer = self.current_module_name();
+                self.post_task(Task::Import(promise, specifier, referrer));
+            }
+            Err(e) => {
+                let reason = self.error_value(e);
+                self.reject_promise(promise, reason);
+            }
+        }
+        Ok(JSType::Object(promise))
+    }
+
+    pub(super) fn run_import(
+        &mut self,
+        promise: ObjectId,
+        specifier: String,
+        referrer: Option<String>,
+    ) {
+        let result = self
+            .load_module(&specifier, referrer.as_deref())
+            .and_then(|id| {
+                self.link_module(id)?;
+                self.evaluate_module(id)?;
+                Ok(self.module_namespace(id))
+            });
+        match result {
+            Ok(namespace) => self.resolve_promise(promise, namespace),
+            Err(e) => {
+                let reason = self.error_value(e);
+                self.reject_promise(promise, reason);
+            }
+        }
+    }
+
+    /// import绑定的当前值, 导出的变量尚未初始化时报错
+    pub(super) fn binding_value(&self, scope: ScopeId, name: &str) -> Result<JSType, String> {
+        let (mut scope, mut name) = (scope, name);
+        loop {
+            if let Some((_, value)) = self.scopes[scope].vars.get(name) {
+                return Ok(value.clone());
+            }
+            match self.scopes[scope].imports.get(name) {
+                Some((target, target_name)) => (scope, name) = (*target, target_name),
+                None => {
+                    return Err(self.err(&format!(
+                        "Uncaught ReferenceError: Cannot access '{}' before initialization",
+                        name
+                    )))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,826 @@
+use super::gc::{Marker, Trace};
+use super::generator::{contains_await, Completion};
+use super::{JSType, Program, ResumeKind};
+use crate::jsparser::expr::{Expr, Function};
+use crate::jsparser::object::{JSObject, ObjectId, ObjectKind, Property};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// 外部处理未被捕获的Promise拒绝
+pub type RejectionCallback = dyn Fn(&mut Program, JSType) + Send + Sync;
+
+#[derive(Debug, Clone)]
+pub(crate) enum PromiseState {
+    Pending,
+    Fulfilled(JSType),
+    Rejected(JSType),
+}
+
+/// Promise对象的内部状态
+#[derive(Debug, Clone)]
+pub(crate) struct PromiseData {
+    pub state: PromiseState,
+    fulfill_reactions: Vec<PromiseReaction>,
+    reject_reactions: Vec<PromiseReaction>,
+    handled: bool, //是否已注册过then, 拒绝时未注册的需要报告给外部
+}
+
+/// new C(executor) 得到的promise及resolve/reject
+#[derive(Debug, Clone)]
+pub(crate) struct PromiseCapability {
+    pub promise: JSType,
+    pub resolve: JSType,
+    pub reject: JSType,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PromiseReaction {
+    capability: Option<PromiseCapability>,
+    handler: ReactionHandler,
+}
+
+#[derive(Debug, Clone)]
+enum ReactionHandler {
+    Empty,            //then未传入函数, 直接传递结果
+    Function(JSType), //then(f)
+    Async(ObjectId),  //await, 恢复执行async函数
+}
+
+/// 微任务
+pub(crate) enum Job {
+    Reaction(PromiseReaction, bool, JSType),   //bool: 是否为拒绝
+    ResolveThenable(ObjectId, JSType, JSType), //promise, thenable, then
+    Callback(JSType),                          //queueMicrotask
+}
+
+/// Promise.all/allSettled/any/race
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Combinator {
+    All,
+    AllSettled,
+    Any,
+    Race,
+}
+
+/// Promise内部使用的函数对象
+#[derive(Debug, Clone)]
+pub(crate) enum PromiseFunction {
+    Resolve(ObjectId, Arc<AtomicBool>), //同一对resolve/reject只生效一次
+    Reject(ObjectId, Arc<AtomicBool>),
+    Executor(JSType, JSType), //NewPromiseCapability 保存resolve/reject
+    Element(Box<CombinatorElement>),
+    ThenFinally(JSType, JSType), //onFinally, 构造函数
+    CatchFinally(JSType, JSType),
+    ValueThunk(JSType),
+    Thrower(JSType),
+    IteratorResult(bool), //for await 中包装同步迭代器的结果, 参数为done
+}
+
+/// Promise.all等每一项完成时的回调
+#[derive(Debug, Clone)]
+pub(crate) struct CombinatorElement {
+    kind: Combinator,
+    rejected: bool,
+    index: usize,
+    values: ObjectId,
+    remaining: Arc<AtomicUsize>,
+    capability: PromiseCapability,
+    called: Arc<AtomicBool>,
+}
+
+impl Trace for PromiseData {
+    fn trace(&self, marker: &mut Marker) {
+        if let PromiseState::Fulfilled(value) | PromiseState::Rejected(value) = &self.state {
+            value.trace(marker);
+        }
+        self.fulfill_reactions.trace(marker);
+        self.reject_reactions.trace(marker);
+    }
+}
+
+impl Trace for PromiseCapability {
+    fn trace(&self, marker: &mut Marker) {
+        self.promise.trace(marker);
+        self.resolve.trace(marker);
+        self.reject.trace(marker);
+    }
+}
+
+impl Trace for PromiseReaction {
+    fn trace(&self, marker: &mut Marker) {
+        self.capability.trace(marker);
+        match &self.handler {
+            ReactionHandler::Empty => {}
+            ReactionHandler::Function(func) => func.trace(marker),
+            ReactionHandler::Async(generator) => marker.mark(*generator),
+        }
+    }
+}
+
+impl Trace for Job {
+    fn trace(&self, marker: &mut Marker) {
+        match self {
+            Job::Reaction(reaction, _, value) => {
+                reaction.trace(marker);
+                value.trace(marker);
+            }
+            Job::ResolveThenable(promise, thenable, then) => {
+                marker.mark(*promise);
+                thenable.trace(marker);
+                then.trace(marker);
+            }
+            Job::Callback(callback) => callback.trace(marker),
+        }
+    }
+}
+
+impl Trace for PromiseFunction {
+    fn trace(&self, marker: &mut Marker) {
+        match self {
+            PromiseFunction::Resolve(promise, _) | PromiseFunction::Reject(promise, _) => {
+                marker.mark(*promise)
+            }
+            PromiseFunction::Executor(a, b)
+            | PromiseFunction::ThenFinally(a, b)
+            | PromiseFunction::CatchFinally(a, b) => {
+                a.trace(marker);
+                b.trace(marker);
+            }
+            PromiseFunction::ValueThunk(value) | PromiseFunction::Thrower(value) => {
+                value.trace(marker)
+            }
+            PromiseFunction::Element(element) => {
+                marker.mark(element.values);
+                element.capability.trace(marker);
+            }
+            PromiseFunction::IteratorResult(_) => {}
+        }
+    }
+}
+
+impl Program {
+    pub(crate) fn new_promise(&mut self, proto: ObjectId) -> ObjectId {
+        let data = PromiseData {
+            state: PromiseState::Pending,
+            fulfill_reactions: Vec::new(),
+            reject_reactions: Vec::new(),
+            handled: false,
+        };
+        self.alloc(JSObject::new(
+            ObjectKind::Promise(Box::new(data)),
+            Some(proto),
+        ))
+    }
+
+    pub(crate) fn as_promise(&self, value: &JSType) -> Option<ObjectId> {
+        match value {
+            JSType::Object(id) if matches!(self.obj(*id).kind, ObjectKind::Promise(_)) => Some(*id),
+            _ => None,
+        }
+    }
+
+    fn promise_data(&mut self, id: ObjectId) -> &mut PromiseData {
+        match &mut self.obj_mut(id).kind {
+            ObjectKind::Promise(data) => data,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn promise_state(&self, id: ObjectId) -> Option<&PromiseState> {
+        match &self.obj(id).kind {
+            ObjectKind::Promise(data) => Some(&data.state),
+            _ => None,
+        }
+    }
+
+    pub(super) fn promise_function(&mut self, func: PromiseFunction, length: i64) -> JSType {
+        let proto = self.intrinsics.function_prototype;
+        let id = self.alloc(JSObject::new(
+            ObjectKind::PromiseFunction(Box::new(func)),
+            Some(proto),
+        ));
+        self.define_property(id, "length", Property::readonly(JSType::Int(length)));
+        self.define_property(
+            id,
+            "name",
+            Property::readonly(JSType::String(String::new().into())),
+        );
+        JSType::Object(id)
+    }
+
+    /// CreateResolvingFunctions
+    pub(crate) fn create_resolving_functions(&mut self, promise: ObjectId) -> (JSType, JSType) {
+        let done = Arc::new(AtomicBool::new(false));
+        let resolve = self.promise_function(PromiseFunction::Resolve(promise, done.clone()), 1);
+        let reject = self.promise_function(PromiseFunction::Reject(promise, done), 1);
+        (resolve, reject)
+    }
+
+    /// 使用值完成promise, 值为thenable时等待其结果
+    pub(crate) fn resolve_promise(&mut self, promise: ObjectId, resolution: JSType) {
+        let JSType::Object(id) = resolution else {
+            return self.settle_promise(promise, PromiseState::Fulfilled(resolution));
+        };
+        if id == promise {
+            let reason = self.error_value(
+                "Uncaught TypeError: Chaining cycle detected for promise #<Promise>".to_string(),
+            );
+            return self.settle_promise(promise, PromiseState::Rejected(reason));
+        }
+        match self.get_property(&resolution, "then") {
+            Err(e) => {
+                let reason = self.error_value(e);
+                self.settle_promise(promise, PromiseState::Rejected(reason));
+            }
+            Ok(then) if self.is_callable(&then) => {
+                self.microtasks
+                    .push_back(Job::ResolveThenable(promise, resolution, then));
+            }
+            Ok(_) => self.settle_promise(promise, PromiseState::Fulfilled(resolution)),
+        }
+    }
+
+    pub(crate) fn reject_promise(&mut self, promise: ObjectId, reason: JSType) {
+        self.settle_promise(promise, PromiseState::Rejected(reason));
+    }
+
+    fn settle_promise(&mut self, promise: ObjectId, state: PromiseState) {
+        let data = self.promise_data(promise);
+        if !matches!(data.state, PromiseState::Pending) {
+            return;
+        }
+        let fulfill = std::mem::take(&mut data.fulfill_reactions);
+        let reject = std::mem::take(&mut data.reject_reactions);
+        let handled = data.handled;
+        data.state = state.clone();
+        let (reactions, is_reject, argument) = match state {
+            PromiseState::Fulfilled(value) => (fulfill, false, value),
+            PromiseState::Rejected(reason) => {
+                if !handled {
+                    self.pending_rejections.push(promise);
+                }
+                (reject, true, reason)
+            }
+            PromiseState::Pending => return,
+        };
+        for reaction in reactions {
+            self.microtasks
+                .push_back(Job::Reaction(reaction, is_reject, argument.clone()));
+        }
+    }
+
+    /// PerformPromiseThen
+    fn perform_then(
+        &mut self,
+        promise: ObjectId,
+        on_fulfilled: ReactionHandler,
+        on_rejected: ReactionHandler,
+        capability: Option<PromiseCapability>,
+    ) {
+        let fulfill = PromiseReaction {
+            capability: capability.clone(),
+            handler: on_fulfilled,
+        };
+        let reject = PromiseReaction {
+            capability,
+            handler: on_rejected,
+        };
+        let data = self.promise_data(promise);
+        let was_handled = std::mem::replace(&mut data.handled, true);
+        match data.state.clone() {
+            PromiseState::Pending => {
+                data.fulfill_reactions.push(fulfill);
+                data.reject_reactions.push(reject);
+            }
+            PromiseState::Fulfilled(value) => {
+                self.microtasks
+                    .push_back(Job::Reaction(fulfill, false, value));
+            }
+            PromiseState::Rejected(reason) => {
+                if !was_handled {
+                    self.pending_rejections.retain(|id| *id != promise);
+                }
+                self.microtasks
+                    .push_back(Job::Reaction(reject, true, reason));
+            }
+        }
+    }
+
+    /// NewPromiseCapability: new C(executor)
+    pub(crate) fn new_promise_capability(
+        &mut self,
+        constructor: &JSType,
+    ) -> Result<PromiseCapability, String> {
+        if !self.is_constructor(constructor) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a constructor",
+                self.display(constructor)
+            )));
+        }
+        let executor = self.promise_function(
+            PromiseFunction::Executor(JSType::Undefined, JSType::Undefined),
+            2,
+        );
+        let promise = self.construct(constructor, vec![executor.clone()], None)?;
+        let JSType::Object(executor) = executor else {
+            unreachable!()
+        };
+        let (resolve, reject) = match &self.obj(executor).kind {
+            ObjectKind::PromiseFunction(f) => match f.as_ref() {
+                PromiseFunction::Executor(resolve, reject) => (resolve.clone(), reject.clone()),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        if !self.is_callable(&resolve) || !self.is_callable(&reject) {
+            return Err(
+                self.err("Uncaught TypeError: Promise resolve or reject function is not callable")
+            );
+        }
+        Ok(PromiseCapability {
+            promise,
+            resolve,
+            reject,
+        })
+    }
+
+    /// PromiseResolve: 已经是C的实例时直接返回
+    pub(crate) fn promise_resolve(
+        &mut self,
+        constructor: &JSType,
+        value: JSType,
+    ) -> Result<JSType, String> {
+        if self.as_promise(&value).is_some() {
+            let value_constructor = self.get_property(&value, "constructor")?;
+            if value_constructor.strict_equal(constructor) {
+                return Ok(value);
+            }
+        }
+        let capability = self.new_promise_capability(constructor)?;
+        self.call(&capability.resolve, JSType::Undefined, vec![value])?;
+        Ok(capability.promise)
+    }
+
+    /// SpeciesConstructor, constructor为undefined时使用内置Promise
+    fn species_constructor(&mut self, value: &JSType) -> Result<JSType, String> {
+        match self.get_property(value, "constructor")? {
+            JSType::Undefined => Ok(JSType::Object(self.intrinsics.promise)),
+            constructor @ JSType::Object(_) => Ok(constructor),
+            _ => Err(self.err("Uncaught TypeError: The .constructor property is not an object")),
+        }
+    }
+
+    /// promise.then(onFulfilled, onRejected)
+    pub(crate) fn promise_then(
+        &mut self,
+        this: &JSType,
+        on_fulfilled: JSType,
+        on_rejected: JSType,
+    ) -> Result<JSType, String> {
+        let Some(promise) = self.as_promise(this) else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Method Promise.prototype.then called on incompatible receiver {}",
+                self.display(this)
+            )));
+        };
+        let constructor = self.species_constructor(this)?;
+        let capability = self.new_promise_capability(&constructor)?;
+        let handler = |program: &Program, f: JSType| {
+            if program.is_callable(&f) {
+                ReactionHandler::Function(f)
+            } else {
+                ReactionHandler::Empty
+            }
+        };
+        let on_fulfilled = handler(self, on_fulfilled);
+        let on_rejected = handler(self, on_rejected);
+        let result = capability.promise.clone();
+        self.perform_then(promise, on_fulfilled, on_rejected, Some(capability));
+        Ok(result)
+    }
+
+    /// promise.finally(onFinally)
+    pub(crate) fn promise_finally(
+        &mut self,
+        this: &JSType,
+        on_finally: JSType,
+    ) -> Result<JSType, String> {
+        if !matches!(this, JSType::Object(_)) {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Method Promise.prototype.finally called on incompatible receiver {}",
+                self.display(this)
+            )));
+        }
+        let constructor = self.species_constructor(this)?;
+        let (then_finally, catch_finally) = if self.is_callable(&on_finally) {
+            (
+                self.promise_function(
+                    PromiseFunction::ThenFinally(on_finally.clone(), constructor.clone()),
+                    1,
+                ),
+                self.promise_function(PromiseFunction::CatchFinally(on_finally, constructor), 1),
+            )
+        } else {
+            (on_finally.clone(), on_finally)
+        };
+        self.invoke(this, "then", vec![then_finally, catch_finally])
+    }
+
+    /// 调用对象上的方法
+    pub(crate) fn invoke(
+        &mut self,
+        target: &JSType,
+        name: &str,
+        args: Vec<JSType>,
+    ) -> Result<JSType, String> {
+        let method = self.get_property(target, name)?;
+        self.call(&method, target.clone(), args)
+    }
+
+    /// 调用Promise内部函数
+    pub(crate) fn call_promise_function(
+        &mut self,
+        id: ObjectId,
+        args: Vec<JSType>,
+    ) -> Result<JSType, String> {
+        let ObjectKind::PromiseFunction(func) = &self.obj(id).kind else {
+            unreachable!()
+        };
+        let func = func.as_ref().clone();
+        let value = args.first().cloned().unwrap_or(JSType::Undefined);
+        let is_then_finally = matches!(func, PromiseFunction::ThenFinally(..));
+        match func {
+            PromiseFunction::Resolve(promise, done) => {
+                if !done.swap(true, Ordering::SeqCst) {
+                    self.resolve_promise(promise, value);
+                }
+                Ok(JSType::Undefined)
+            }
+            PromiseFunction::Reject(promise, done) => {
+                if !done.swap(true, Ordering::SeqCst) {
+                    self.reject_promise(promise, value);
+                }
+                Ok(JSType::Undefined)
+            }
+            PromiseFunction::Executor(resolve, reject) => {
+                if !matches!(resolve, JSType::Undefined) || !matches!(reject, JSType::Undefined) {
+                    return Err(self.err(
+                        "Uncaught TypeError: Promise executor has already been invoked with non-undefined arguments",
+                    ));
+                }
+                let reject = args.get(1).cloned().unwrap_or(JSType::Undefined);
+                if let ObjectKind::PromiseFunction(f) = &mut self.obj_mut(id).kind {
+                    **f = PromiseFunction::Executor(value, reject);
+                }
+                Ok(JSType::Undefined)
+            }
+            PromiseFunction::Element(element) => {
+                if element.called.swap(true, Ordering::SeqCst) {
+                    return Ok(JSType::Undefined);
+                }
+                let value = match (element.kind, element.rejected) {
+                    (Combinator::AllSettled, rejected) => {
+                        let result = self.new_object();
+                        let (status, key) = if rejected {
+                            ("rejected", "reason")
+                        } else {
+                            ("fulfilled", "value")
+                        };
+                        self.set_property(&result, "status", JSType::String(status.into()))?;
+                        self.set_property(&result, key, value)?;
+                        result
+                    }
+                    _ => value,
+                };
+                let values = JSType::Object(element.values);
+                self.set_property(&values, element.index.to_string(), value)?;
+                if element.remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    self.finish_combinator(element.kind, element.values, &element.capability)?;
+                }
+                Ok(JSType::Undefined)
+            }
+            PromiseFunction::ThenFinally(on_finally, constructor)
+            | PromiseFunction::CatchFinally(on_finally, constructor) => {
+                let result = self.call(&on_finally, JSType::Undefined, Vec::new())?;
+                let promise = self.promise_resolve(&constructor, result)?;
+                let thunk = if is_then_finally {
+                    PromiseFunction::ValueThunk(value)
+                } else {
+                    PromiseFunction::Thrower(value)
+                };
+                let thunk = self.promise_function(thunk, 0);
+                self.invoke(&promise, "then", vec![thunk])
+            }
+            PromiseFunction::ValueThunk(value) => Ok(value),
+            PromiseFunction::Thrower(reason) => Err(self.throw_value(&reason)),
+            PromiseFunction::IteratorResult(done) => Ok(self.iter_result(value, done)),
+        }
+    }
+
+    /// Promise.all/allSettled/any/race
+    pub(crate) fn promise_combinator(
+        &mut self,
+        constructor: &JSType,
+        iterable: &JSType,
+        kind: Combinator,
+    ) -> Result<JSType, String> {
+        let capability = self.new_promise_capability(constructor)?;
+        if let Err(e) = self.perform_combinator(constructor, iterable, kind, &capability) {
+            let reason = self.error_value(e);
+            self.call(&capability.reject, JSType::Undefined, vec![reason])?;
+        }
+        Ok(capability.promise)
+    }
+
+    fn perform_combinator(
+        &mut self,
+        constructor: &JSType,
+        iterable: &JSType,
+        kind: Combinator,
+        capability: &PromiseCapability,
+    ) -> Result<(), String> {
+        let resolve = self.get_property(constructor, "resolve")?;
+        if !self.is_callable(&resolve) {
+            return Err(
+                self.err("Uncaught TypeError: Promise resolve or reject function is not callable")
+            );
+        }
+        let record = self.get_iterator(iterable)?;
+        let JSType::Object(values) = self.new_array(Vec::new()) else {
+            unreachable!()
+        };
+        let remaining = Arc::new(AtomicUsize::new(1));
+        let mut index = 0;
+        while let Some(value) = self.iterator_step(&record)? {
+            let element = |program: &mut Program, rejected: bool, called: &Arc<AtomicBool>| {
+                let element = CombinatorElement {
+                    kind,
+                    rejected,
+                    index,
+                    values,
+                    remaining: remaining.clone(),
+                    capability: capability.clone(),
+                    called: called.clone(),
+                };
+                program.promise_function(PromiseFunction::Element(Box::new(element)), 1)
+            };
+            let called = Arc::new(AtomicBool::new(false));
+            let (on_fulfilled, on_rejected) = match kind {
+                Combinator::Race => (capability.resolve.clone(), capability.reject.clone()),
+                Combinator::All => (element(self, false, &called), capability.reject.clone()),
+                Combinator::AllSettled => {
+                    (element(self, false, &called), element(self, true, &called))
+                }
+                Combinator::Any => (capability.resolve.clone(), element(self, true, &called)),
+            };
+            let step = (|| -> Result<(), String> {
+                if kind != Combinator::Race {
+                    self.set_property(
+                        &JSType::Object(values),
+                        index.to_string(),
+                        JSType::Undefined,
+                    )?;
+                    remaining.fetch_add(1, Ordering::SeqCst);
+                }
+                let next = self.call(&resolve, constructor.clone(), vec![value])?;
+                self.invoke(&next, "then", vec![on_fulfilled, on_rejected])?;
+                Ok(())
+            })();
+            if let Err(e) = step {
+                let _ = self.iterator_close(&record);
+                return Err(e);
+            }
+            index += 1;
+        }
+        if kind != Combinator::Race && remaining.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.finish_combinator(kind, values, capability)?;
+        }
+        Ok(())
+    }
+
+    /// 全部完成: all/allSettled返回结果数组, any全部拒绝时返回AggregateError
+    fn finish_combinator(
+        &mut self,
+        kind: Combinator,
+        values: ObjectId,
+        capability: &PromiseCapability,
+    ) -> Result<(), String> {
+        if kind == Combinator::Any {
+            let error = self.aggregate_error(JSType::Object(values), "All promises were rejected");
+            self.call(&capability.reject, JSType::Undefined, vec![error])?;
+        } else {
+            self.call(
+                &capability.resolve,
+                JSType::Undefined,
+                vec![JSType::Object(values)],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// new AggregateError(errors, message)
+    pub(crate) fn aggregate_error(&mut self, errors: JSType, message: &str) -> JSType {
+        let proto = self.intrinsics.aggregate_error_prototype;
+        let id = self.alloc(JSObject::new(ObjectKind::Error, Some(proto)));
+        self.define_property(
+            id,
+            "message",
+            Property::hidden(JSType::String(message.into())),
+        );
+        self.define_property(id, "errors", Property::hidden(errors));
+        JSType::Object(id)
+    }
+
+    fn run_job(&mut self, job: Job) -> Result<(), String> {
+        match job {
+            Job::Reaction(reaction, is_reject, argument) => {
+                let result = match reaction.handler {
+                    ReactionHandler::Empty if is_reject => Err(argument),
+                    ReactionHandler::Empty => Ok(argument),
+                    ReactionHandler::Function(f) => self
+                        .call(&f, JSType::Undefined, vec![argument])
+                        .map_err(|e| self.error_value(e)),
+                    ReactionHandler::Async(generator) => {
+                        let kind = if is_reject {
+                            ResumeKind::Throw
+                        } else {
+                            ResumeKind::Next
+                        };
+                        self.async_step(generator, kind, argument);
+                        return Ok(());
+                    }
+                };
+                let Some(capability) = reaction.capability else {
+                    return Ok(());
+                };
+                match result {
+                    Ok(value) => self.call(&capability.resolve, JSType::Undefined, vec![value])?,
+                    Err(reason) => {
+                        self.call(&capability.reject, JSType::Undefined, vec![reason])?
+                    }
+                };
+            }
+            Job::ResolveThenable(promise, thenable, then) => {
+                let (resolve, reject) = self.create_resolving_functions(promise);
+                if let Err(e) = self.call(&then, thenable, vec![resolve, reject.clone()]) {
+                    let reason = self.error_value(e);
+                    self.call(&reject, JSType::Undefined, vec![reason])?;
+                }
+            }
+            Job::Callback(callback) => {
+                self.call(&callback, JSType::Undefined, Vec::new())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 执行全部微任务, 之后报告未处理的Promise拒绝
+    pub fn run_microtasks(&mut self) -> Result<(), String> {
+        while let Some(job) = self.microtasks.pop_front() {
+            self.run_job(job)?;
+        }
+        for promise in std::mem::take(&mut self.pending_rejections) {
+            let ObjectKind::Promise(data) = &self.obj(promise).kind else {
+                continue;
+            };
+            let PromiseState::Rejected(reason) = &data.state else {
+                continue;
+            };
+            if data.handled {
+                continue;
+            }
+            let reason = reason.clone();
+            match self.rejection_handler.clone() {
+                Some(handler) => handler(self, reason),
+                None => {
+                    return Err(
+                        self.err(&format!("Uncaught (in promise) {}", self.display(&reason)))
+                    )
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 注册未处理的Promise拒绝的回调, 未注册时 run 返回错误
+    pub fn set_rejection_handler(&mut self, callback: Box<RejectionCallback>) {
+        self.rejection_handler = Some(Arc::from(callback));
+    }
+
+    /// 错误信息转为js值, 由throw_value抛出的值保持不变
+    pub(crate) fn error_value(&mut self, message: String) -> JSType {
+        if let Some((thrown, value)) = self.thrown.take() {
+            if thrown == message {
+                return value;
+            }
+        }
+        let text = message.strip_prefix("Uncaught ").unwrap_or(&message);
+        let (name, msg) = text.split_once(": ").unwrap_or((text, ""));
+        if !matches!(
+            name,
+            "Error" | "TypeError" | "RangeError" | "ReferenceError" | "SyntaxError"
+        ) {
+            return JSType::String(text.into());
+        }
+        let proto = self.intrinsics.get(name);
+        let id = self.alloc(JSObject::new(ObjectKind::Error, Some(proto)));
+        if !msg.is_empty() {
+            self.define_property(id, "message", Property::hidden(JSType::String(msg.into())));
+        }
+        JSType::Object(id)
+    }
+
+    /// 调用async函数: 执行到第一个await, 返回Promise
+    pub(super) fn start_async(
+        &mut self,
+        func: &Function,
+        args: Vec<JSType>,
+    ) -> Result<JSType, String> {
+        let proto = self.intrinsics.promise_prototype;
+        let promise = self.new_promise(proto);
+        let started = self
+            .bind_local_args(&func.params, args)
+            .and_then(|_| self.hoist_declarations(&func.body, true));
+        if let Err(e) = started {
+            let reason = self.error_value(e);
+            self.reject_promise(promise, reason);
+            return Ok(JSType::Object(promise));
+        }
+        let mut state = self.generator_state(&func.body);
+        state.promise = Some(promise);
+        let generator = self.alloc(JSObject::new(
+            ObjectKind::Generator(Some(Box::new(state))),
+            None,
+        ));
+        self.async_step(generator, ResumeKind::Next, JSType::Undefined);
+        Ok(JSType::Object(promise))
+    }
+
+    /// 恢复执行async函数, 遇到await时等待promise完成
+    fn async_step(&mut self, generator: ObjectId, mut kind: ResumeKind, mut value: JSType) {
+        let promise = match &self.obj(generator).kind {
+            ObjectKind::Generator(Some(state)) => state.promise,
+            _ => None,
+        };
+        let Some(promise) = promise else {
+            return;
+        };
+        loop {
+            match self.resume_generator(generator, kind, value) {
+                Ok(Completion::Await(awaited)) => {
+                    let constructor = JSType::Object(self.intrinsics.promise);
+                    match self.promise_resolve(&constructor, awaited) {
+                        Ok(awaited) => {
+                            let Some(awaited) = self.as_promise(&awaited) else {
+                                return;
+                            };
+                            self.perform_then(
+                                awaited,
+                                ReactionHandler::Async(generator),
+                                ReactionHandler::Async(generator),
+                                None,
+                            );
+                            return;
+                        }
+                        Err(e) => {
+                            kind = ResumeKind::Throw;
+                            value = self.error_value(e);
+                        }
+                    }
+                }
+                Ok(Completion::Return(result)) | Ok(Completion::Yield(result)) => {
+                    return self.resolve_promise(promise, result);
+                }
+                Err(e) => {
+                    let reason = self.error_value(e);
+                    return self.reject_promise(promise, reason);
+                }
+            }
+        }
+    }
+
+    /// 顶层使用了await时, 整个程序按async函数执行
+    pub(super) fn run_top_level(&mut self, statements: &[Expr]) -> Result<bool, String> {
+        if !statements.iter().any(contains_await) {
+            return Ok(false);
+        }
+        let proto = self.intrinsics.promise_prototype;
+        let promise = self.new_promise(proto);
+        self.promise_data(promise).handled = true;
+        let mut state = self.generator_state(statements);
+        state.promise = Some(promise);
+        let generator = self.alloc(JSObject::new(
+            ObjectKind::Generator(Some(Box::new(state))),
+            None,
+        ));
+        self.async_step(generator, ResumeKind::Next, JSType::Undefined);
+        self.gc_roots.push(promise);
+        let result = self.run_until_idle();
+        self.gc_roots.pop();
+        result?;
+        if let Some(PromiseState::Rejected(reason)) = self.promise_state(promise).cloned() {
+            return Err(self.throw_value(&reason));
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,588 @@
+use super::descriptor::PropertyDescriptor;
+use super::{JSType, Program};
+use crate::jsparser::object::{
+    JSObject, ObjectId, ObjectKind, Property, PropertyKey, PropertyValue,
+};
+
+/// Proxy的目标及处理器
+#[derive(Debug, Clone)]
+pub(crate) struct ProxyData {
+    pub handler: Option<(ObjectId, ObjectId)>, //目标及处理器, 撤销后为None
+    pub callable: bool,                        //目标是否可以调用
+    pub constructor: bool,                     //目标是否可以new
+}
+
+/// 处理器中的trap及调用时的this
+type Trap = Option<(JSType, JSType)>;
+
+impl Program {
+    pub(crate) fn is_proxy(&self, id: ObjectId) -> bool {
+        matches!(self.obj(id).kind, ObjectKind::Proxy(_))
+    }
+
+    /// ProxyCreate, target和handler需为对象
+    pub(crate) fn proxy_create(
+        &mut self,
+        target: &JSType,
+        handler: &JSType,
+    ) -> Result<ObjectId, String> {
+        let (JSType::Object(target), JSType::Object(handler)) = (target, handler) else {
+            return Err(self.err(
+                "Uncaught TypeError: Cannot create proxy with a non-object as target or handler",
+            ));
+        };
+        let obj = self.obj(*target);
+        let data = ProxyData {
+            handler: Some((*target, *handler)),
+            callable: obj.is_callable(),
+            constructor: obj.is_constructor(),
+        };
+        Ok(self.alloc(JSObject::new(ObjectKind::Proxy(Box::new(data)), None)))
+    }
+
+    pub(crate) fn revoke_proxy(&mut self, id: ObjectId) {
+        if let ObjectKind::Proxy(data) = &mut self.obj_mut(id).kind {
+            data.handler = None;
+        }
+    }
+
+    /// 透过Proxy的最内层目标, 已撤销时为None
+    pub(crate) fn proxy_target(&self, mut id: ObjectId) -> Option<ObjectId> {
+        while let ObjectKind::Proxy(data) = &self.obj(id).kind {
+            id = data.handler?.0;
+        }
+        Some(id)
+    }
+
+    /// 目标及处理器中名为name的trap, 已撤销时抛出TypeError
+    fn proxy_trap(&mut self, id: ObjectId, name: &str) -> Result<(ObjectId, Trap), String> {
+        let ObjectKind::Proxy(data) = &self.obj(id).kind else {
+            unreachable!()
+        };
+        let Some((target, handler)) = data.handler else {
+            return Err(self.err(&format!(
+                "Uncaught TypeError: Cannot perform '{}' on a proxy that has been revoked",
+                name
+            )));
+        };
+        let handler = JSType::Object(handler);
+        match self.get_property(&handler, name)? {
+            JSType::Undefined | JSType::NULL => Ok((target, None)),
+            trap if self.is_callable(&trap) => Ok((target, Some((trap, handler)))),
+            trap => Err(self.err(&format!(
+                "Uncaught TypeError: {} is not a function",
+                self.display(&trap)
+            ))),
+        }
+    }
+
+    fn trap_error(&self, trap: &str, message: &str) -> String {
+        self.err(&format!(
+            "Uncaught TypeError: '{}' on proxy: {}",
+            trap, message
+        ))
+    }
+
+    pub(super) fn proxy_get_prototype_of(
+        &mut self,
+        id: ObjectId,
+    ) -> Result<Option<ObjectId>, String> {
+        let (target, trap) = self.proxy_trap(id, "getPrototypeOf")?;
+        let Some((trap, handler)) = trap else {
+            return self.get_prototype_of(&JSType::Object(target));
+        };
+        let proto = match self.call(&trap, handler, vec![JSType::Object(target)])? {
+            JSType::Object(proto) => Some(proto),
+            JSType::NULL => None,
+            _ => {
+                return Err(
+                    self.trap_error("getPrototypeOf", "trap returned neither object nor null")
+                )
+            }
+        };
+        if !self.is_extensible(target)?
+            && proto != self.get_prototype_of(&JSType::Object(target))?
+        {
+            return Err(self.trap_error(
+                "getPrototypeOf",
+                "proxy target is non-extensible but the trap did not return its actual prototype",
+            ));
+        }
+        Ok(proto)
+    }
+
+    pub(super) fn proxy_set_prototype(
+        &mut self,
+        id: ObjectId,
+        proto: Option<ObjectId>,
+    ) -> Result<bool, String> {
+        let (target, trap) = self.proxy_trap(id, "setPrototypeOf")?;
+        let Some((trap, handler)) = trap else {
+            return self.set_prototype(target, proto);
+        };
+        let value = proto.map_or(JSType::NULL, JSType::Object);
+        if !self
+            .call(&trap, handler, vec![JSType::Object(target), value])?
+            .is_truthy()
+        {
+            return Ok(false);
+        }
+        if !self.is_extensible(target)?
+            && proto != self.get_prototype_of(&JSType::Object(target))?
+        {
+            return Err(self.trap_error(
+                "setPrototypeOf",
+                "trap returned truish for setting a new prototype on the non-extensible proxy target",
+            ));
+        }
+        Ok(true)
+    }
+
+    pub(super) fn proxy_is_extensible(&mut self, id: ObjectId) -> Result<bool, String> {
+        let (target, trap) = self.proxy_trap(id, "isExtensible")?;
+        let Some((trap, handler)) = trap else {
+            return self.is_extensible(target);
+        };
+        let result = self
+            .call(&trap, handler, vec![JSType::Object(target)])?
+            .is_truthy();
+        let expected = self.is_extensible(target)?;
+        if result != expected {
+            return Err(self.trap_error(
+                "isExtensible",
+                &format!(
+                    "trap result does not reflect extensibility of proxy target (which is '{}')",
+                    expected
+                ),
+            ));
+        }
+        Ok(result)
+    }
+
+    pub(super) fn proxy_prevent_extensions(&mut self, id: ObjectId) -> Result<bool, String> {
+        let (target, trap) = self.proxy_trap(id, "preventExtensions")?;
+        let Some((trap, handler)) = trap else {
+            return self.prevent_extensions(target);
+        };
+        let result = self
+            .call(&trap, handler, vec![JSType::Object(target)])?
+            .is_truthy();
+        if result && self.is_extensible(target)? {
+            return Err(self.trap_error(
+                "preventExtensions",
+                "trap returned truish but the proxy target is extensible",
+            ));
+        }
+        Ok(result)
+    }
+
+    pub(super) fn proxy_get_own_property(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+    ) -> Result<Option<Property>, String> {
+        let name = "getOwnPropertyDescriptor";
+        let (target, trap) = self.proxy_trap(id, name)?;
+        let Some((trap, handler)) = trap else {
+            return self.own_property(target, key);
+        };
+        let result = self.call(&trap, handler, vec![JSType::Object(target), key.to_value()])?;
+        if !matches!(result, JSType::Object(_) | JSType::Undefined) {
+            return Err(self.trap_error(
+                name,
+                &format!(
+                    "trap returned neither object nor undefined for property '{}'",
+                    key
+                ),
+            ));
+        }
+        let current = self.own_property(target, key)?;
+        if let JSType::Undefined = result {
+            let Some(current) = current else {
+                return Ok(None);
+            };
+            if !current.configurable {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap returned undefined for property '{}' which is non-configurable in the proxy target",
+                        key
+                    ),
+                ));
+            }
+            if !self.is_extensible(target)? {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap returned undefined for property '{}' which exists in the non-extensible proxy target",
+                        key
+                    ),
+                ));
+            }
+            return Ok(None);
+        }
+        let extensible = self.is_extensible(target)?;
+        let desc = self.property_descriptor(&result)?;
+        if !desc.is_compatible_with(extensible, current.as_ref()) {
+            return Err(self.trap_error(
+                name,
+                &format!(
+                    "trap returned descriptor for property '{}' that is incompatible with the existing property in the proxy target",
+                    key
+                ),
+            ));
+        }
+        let prop = desc.to_property();
+        if !prop.configurable {
+            let Some(current) = current.filter(|p| !p.configurable) else {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap reported non-configurability for property '{}' which is either non-existent or configurable in the proxy target",
+                        key
+                    ),
+                ));
+            };
+            if desc.writable == Some(false) && current.writable {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap reported non-configurable and writable for property '{}' which is non-configurable, non-writable in the proxy target",
+                        key
+                    ),
+                ));
+            }
+        }
+        Ok(Some(prop))
+    }
+
+    pub(super) fn proxy_define_own_property(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        desc: &PropertyDescriptor,
+    ) -> Result<bool, String> {
+        let name = "defineProperty";
+        let (target, trap) = self.proxy_trap(id, name)?;
+        let Some((trap, handler)) = trap else {
+            return self.define_own_property(target, key, desc);
+        };
+        let desc_object = self.partial_descriptor_object(desc);
+        let args = vec![JSType::Object(target), key.to_value(), desc_object];
+        if !self.call(&trap, handler, args)?.is_truthy() {
+            return Ok(false);
+        }
+        let current = self.own_property(target, key)?;
+        let extensible = self.is_extensible(target)?;
+        let non_configurable = desc.configurable == Some(false);
+        let Some(current) = current else {
+            if !extensible {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap returned truish for adding property '{}'  to the non-extensible proxy target",
+                        key
+                    ),
+                ));
+            }
+            if non_configurable {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap returned truish for defining non-configurable property '{}' which is either non-existent or configurable in the proxy target",
+                        key
+                    ),
+                ));
+            }
+            return Ok(true);
+        };
+        if !desc.is_compatible_with(extensible, Some(&current)) {
+            return Err(self.trap_error(
+                name,
+                &format!(
+                    "trap returned truish for adding property '{}'  that is incompatible with the existing property in the proxy target",
+                    key
+                ),
+            ));
+        }
+        if non_configurable && current.configurable {
+            return Err(self.trap_error(
+                name,
+                &format!(
+                    "trap returned truish for defining non-configurable property '{}' which is either non-existent or configurable in the proxy target",
+                    key
+                ),
+            ));
+        }
+        let writable_data = current.writable && matches!(current.value, PropertyValue::Data(_));
+        if writable_data && !current.configurable && desc.writable == Some(false) {
+            return Err(self.trap_error(
+                name,
+                &format!(
+                    "trap returned truish for defining non-configurable property '{}' which cannot be non-writable, unless there exists a corresponding non-configurable, non-writable own property of the target object.",
+                    key
+                ),
+            ));
+        }
+        Ok(true)
+    }
+
+    pub(super) fn proxy_has(&mut self, id: ObjectId, key: &PropertyKey) -> Result<bool, String> {
+        let (target, trap) = self.proxy_trap(id, "has")?;
+        let Some((trap, handler)) = trap else {
+            return self.has_property(target, key);
+        };
+        let args = vec![JSType::Object(target), key.to_value()];
+        if self.call(&trap, handler, args)?.is_truthy() {
+            return Ok(true);
+        }
+        if let Some(current) = self.own_property(target, key)? {
+            if !current.configurable {
+                return Err(self.trap_error(
+                    "has",
+                    &format!(
+                        "trap returned falsish for property '{}' which exists in the proxy target as non-configurable",
+                        key
+                    ),
+                ));
+            }
+            if !self.is_extensible(target)? {
+                return Err(self.trap_error(
+                    "has",
+                    &format!(
+                        "trap returned falsish for property '{}' but the proxy target is not extensible",
+                        key
+                    ),
+                ));
+            }
+        }
+        Ok(false)
+    }
+
+    pub(super) fn proxy_get(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        receiver: JSType,
+    ) -> Result<JSType, String> {
+        let (target, trap) = self.proxy_trap(id, "get")?;
+        let Some((trap, handler)) = trap else {
+            return self.get_with_receiver(target, key, receiver);
+        };
+        let args = vec![JSType::Object(target), key.to_value(), receiver];
+        let value = self.call(&trap, handler, args)?;
+        let Some(current) = self.own_property(target, key)?.filter(|p| !p.configurable) else {
+            return Ok(value);
+        };
+        match &current.value {
+            PropertyValue::Data(expected) if !current.writable && !value.same_value(expected) => {
+                Err(self.trap_error(
+                    "get",
+                    &format!(
+                        "property '{}' is a read-only and non-configurable data property on the proxy target but the proxy did not return its actual value (expected '{}' but got '{}')",
+                        key,
+                        self.display(expected),
+                        self.display(&value)
+                    ),
+                ))
+            }
+            PropertyValue::Accessor(JSType::Undefined, _)
+                if !matches!(value, JSType::Undefined) =>
+            {
+                Err(self.trap_error(
+                    "get",
+                    &format!(
+                        "property '{}' is a non-configurable accessor property on the proxy target and does not have a getter function, but the trap did not return 'undefined' (got '{}')",
+                        key,
+                        self.display(&value)
+                    ),
+                ))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    pub(super) fn proxy_set(
+        &mut self,
+        id: ObjectId,
+        key: &PropertyKey,
+        value: JSType,
+        receiver: JSType,
+    ) -> Result<bool, String> {
+        let (target, trap) = self.proxy_trap(id, "set")?;
+        let Some((trap, handler)) = trap else {
+            return self.set_with_receiver(target, key, value, receiver);
+        };
+        let args = vec![
+            JSType::Object(target),
+            key.to_value(),
+            value.clone(),
+            receiver,
+        ];
+        if !self.call(&trap, handler, args)?.is_truthy() {
+            return Ok(false);
+        }
+        let Some(current) = self.own_property(target, key)?.filter(|p| !p.configurable) else {
+            return Ok(true);
+        };
+        match &current.value {
+            PropertyValue::Data(expected) if !current.writable && !value.same_value(expected) => {
+                Err(self.trap_error(
+                    "set",
+                    &format!(
+                        "trap returned truish for property '{}' which exists in the proxy target as a non-configurable and non-writable data property with a different value",
+                        key
+                    ),
+                ))
+            }
+            PropertyValue::Accessor(_, JSType::Undefined) => Err(self.trap_error(
+                "set",
+                &format!(
+                    "trap returned truish for property '{}' which exists in the proxy target as a non-configurable and non-writable accessor property without a setter",
+                    key
+                ),
+            )),
+            _ => Ok(true),
+        }
+    }
+
+    pub(super) fn proxy_delete(&mut self, id: ObjectId, key: &PropertyKey) -> Result<bool, String> {
+        let name = "deleteProperty";
+        let (target, trap) = self.proxy_trap(id, name)?;
+        let Some((trap, handler)) = trap else {
+            return self.delete_property(target, key);
+        };
+        let args = vec![JSType::Object(target), key.to_value()];
+        if !self.call(&trap, handler, args)?.is_truthy() {
+            return Ok(false);
+        }
+        if let Some(current) = self.own_property(target, key)? {
+            if !current.configurable {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap returned truish for property '{}' which is non-configurable in the proxy target",
+                        key
+                    ),
+                ));
+            }
+            if !self.is_extensible(target)? {
+                return Err(self.trap_error(
+                    name,
+                    &format!(
+                        "trap returned truish for property '{}' but the proxy target is non-extensible",
+                        key
+                    ),
+                ));
+            }
+        }
+        Ok(true)
+    }
+
+    pub(super) fn proxy_own_keys(&mut self, id: ObjectId) -> Result<Vec<PropertyKey>, String> {
+        let (target, trap) = self.proxy_trap(id, "ownKeys")?;
+        let Some((trap, handler)) = trap else {
+            return self.own_property_keys(target);
+        };
+        let result = self.call(&trap, handler, vec![JSType::Object(target)])?;
+        let JSType::Object(list) = result else {
+            return Err(
+                self.err("Uncaught TypeError: CreateListFromArrayLike called on non-object")
+            );
+        };
+        let length = self.get_property(&result, "length")?;
+        let length = self.number_value(&length)?;
+        let length = if length > 0.0 { length as usize } else { 0 };
+        let mut keys: Vec<PropertyKey> = Vec::with_capacity(length);
+        for index in 0..length {
+            let key =
+                match self.get_with_receiver(list, &index.to_string().into(), result.clone())? {
+                    JSType::String(s) => PropertyKey::String(s.to_string()),
+                    JSType::Symbol(s) => PropertyKey::Symbol(s),
+                    value => {
+                        return Err(self.err(&format!(
+                            "Uncaught TypeError: {} is not a valid property name",
+                            self.display(&value)
+                        )))
+                    }
+                };
+            if keys.contains(&key) {
+                return Err(self.trap_error("ownKeys", "trap returned duplicate entries"));
+            }
+            keys.push(key);
+        }
+        let extensible = self.is_extensible(target)?;
+        let mut configurable = Vec::new();
+        let mut non_configurable = Vec::new();
+        for key in self.own_property_keys(target)? {
+            match self.own_property(target, &key)? {
+                Some(prop) if !prop.configurable => non_configurable.push(key),
+                _ => configurable.push(key),
+            }
+        }
+        if extensible && non_configurable.is_empty() {
+            return Ok(keys);
+        }
+        //不可配置的属性必须出现, 目标不可扩展时结果必须与目标的属性完全一致
+        let mut unchecked = keys.clone();
+        let required = if extensible {
+            non_configurable
+        } else {
+            non_configurable.into_iter().chain(configurable).collect()
+        };
+        for key in required {
+            let Some(index) = unchecked.iter().position(|k| *k == key) else {
+                return Err(
+                    self.trap_error("ownKeys", &format!("trap result did not include '{}'", key))
+                );
+            };
+            unchecked.remove(index);
+        }
+        if !extensible && !unchecked.is_empty() {
+            return Err(self.trap_error(
+                "ownKeys",
+                "trap returned extra keys but proxy target is non-extensible",
+            ));
+        }
+        Ok(keys)
+    }
+
+    pub(super) fn proxy_call(
+        &mut self,
+        id: ObjectId,
+        this: JSType,
+        args: Vec<JSType>,
+    ) -> Result<JSType, String> {
+        let (target, trap) = self.proxy_trap(id, "apply")?;
+        let Some((trap, handler)) = trap else {
+            return self.call(&JSType::Object(target), this, args);
+        };
+        let args = self.new_array(args);
+        self.call(&trap, handler, vec![JSType::Object(target), this, args])
+    }
+
+    pub(super) fn proxy_construct(
+        &mut self,
+        id: ObjectId,
+        args: Vec<JSType>,
+        new_target: JSType,
+    ) -> Result<JSType, String> {
+        let (target, trap) = self.proxy_trap(id, "construct")?;
+        let Some((trap, handler)) = trap else {
+            return self.construct(&JSType::Object(target), args, Some(new_target));
+        };
+        let args = self.new_array(args);
+        let result = self.call(
+            &trap,
+            handler,
+            vec![JSType::Object(target), args, new_target],
+        )?;
+        if !matches!(result, JSType::Object(_)) {
+            return Err(self.trap_error(
+                "construct",
+                &format!("trap returned non-object ('{}')", self.display(&result)),
+            ));
+        }
+        Ok(result)
+    }
+}
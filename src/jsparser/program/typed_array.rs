@@ -0,0 +1,263 @@
+use super::{number_to_string, string_to_number, JSType, Program};
+use crate::jsparser::object::{JSObject, ObjectId, ObjectKind, PropertyKey};
+use crate::jsparser::utility::err;
+use num_bigint::BigInt;
+use num_traits::Signed;
+use std::sync::Arc;
+
+/// 类型化数组的元素类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TypedArrayKind {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    BigInt64,
+    BigUint64,
+}
+
+impl TypedArrayKind {
+    pub const ALL: [TypedArrayKind; 11] = [
+        TypedArrayKind::Int8,
+        TypedArrayKind::Uint8,
+        TypedArrayKind::Uint8Clamped,
+        TypedArrayKind::Int16,
+        TypedArrayKind::Uint16,
+        TypedArrayKind::Int32,
+        TypedArrayKind::Uint32,
+        TypedArrayKind::Float32,
+        TypedArrayKind::Float64,
+        TypedArrayKind::BigInt64,
+        TypedArrayKind::BigUint64,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TypedArrayKind::Int8 => "Int8Array",
+            TypedArrayKind::Uint8 => "Uint8Array",
+            TypedArrayKind::Uint8Clamped => "Uint8ClampedArray",
+            TypedArrayKind::Int16 => "Int16Array",
+            TypedArrayKind::Uint16 => "Uint16Array",
+            TypedArrayKind::Int32 => "Int32Array",
+            TypedArrayKind::Uint32 => "Uint32Array",
+            TypedArrayKind::Float32 => "Float32Array",
+            TypedArrayKind::Float64 => "Float64Array",
+            TypedArrayKind::BigInt64 => "BigInt64Array",
+            TypedArrayKind::BigUint64 => "BigUint64Array",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// 每个元素的字节数
+    pub fn size(self) -> usize {
+        match self {
+            TypedArrayKind::Int8 | TypedArrayKind::Uint8 | TypedArrayKind::Uint8Clamped => 1,
+            TypedArrayKind::Int16 | TypedArrayKind::Uint16 => 2,
+            TypedArrayKind::Int32 | TypedArrayKind::Uint32 | TypedArrayKind::Float32 => 4,
+            TypedArrayKind::Float64 | TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64 => 8,
+        }
+    }
+
+    /// 元素为BigInt的类型
+    pub fn is_bigint(self) -> bool {
+        matches!(self, TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64)
+    }
+
+    /// 从字节读取数值
+    pub fn decode(self, bytes: &[u8], little_endian: bool) -> JSType {
+        let mut buf = [0u8; 8];
+        buf[..self.size()].copy_from_slice(&bytes[..self.size()]);
+        if !little_endian {
+            buf[..self.size()].reverse();
+        }
+        let n = match self {
+            TypedArrayKind::Int8 => buf[0] as i8 as f64,
+            TypedArrayKind::Uint8 | TypedArrayKind::Uint8Clamped => buf[0] as f64,
+            TypedArrayKind::Int16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            TypedArrayKind::Uint16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            TypedArrayKind::Int32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            TypedArrayKind::Uint32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            TypedArrayKind::Float32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            TypedArrayKind::Float64 => f64::from_le_bytes(buf),
+            TypedArrayKind::BigInt64 => {
+                return JSType::BigInt(Arc::new(BigInt::from(i64::from_le_bytes(buf))))
+            }
+            TypedArrayKind::BigUint64 => {
+                return JSType::BigInt(Arc::new(BigInt::from(u64::from_le_bytes(buf))))
+            }
+        };
+        JSType::number(n)
+    }
+
+    /// 数值写入字节, 整数按2^n取模回绕, Uint8Clamped截断到0~255并舍入到偶数
+    /// value需已按类型转换为Number或BigInt
+    pub fn encode(self, value: &JSType, little_endian: bool, bytes: &mut [u8]) {
+        let n = value.to_number();
+        let buf = match (self, value) {
+            //BigInt按补码取低64位
+            (TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64, JSType::BigInt(n)) => {
+                let fill = if n.is_negative() { 0xff } else { 0 };
+                let mut buf = n.to_signed_bytes_le();
+                buf.resize(8, fill);
+                buf
+            }
+            (TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64, _) => {
+                (if n.is_finite() { n as i64 } else { 0 })
+                    .to_le_bytes()
+                    .to_vec()
+            }
+            (TypedArrayKind::Float32, _) => (n as f32).to_le_bytes().to_vec(),
+            (TypedArrayKind::Float64, _) => n.to_le_bytes().to_vec(),
+            (TypedArrayKind::Uint8Clamped, _) => {
+                let n = if n.is_nan() { 0.0 } else { n };
+                vec![n.clamp(0.0, 255.0).round_ties_even() as u8]
+            }
+            _ => {
+                let modulo = 2f64.powi(self.size() as i32 * 8);
+                let n = if n.is_finite() {
+                    n.trunc().rem_euclid(modulo)
+                } else {
+                    0.0
+                };
+                (n as u64).to_le_bytes().to_vec()
+            }
+        };
+        let target = &mut bytes[..self.size()];
+        target.copy_from_slice(&buf[..self.size()]);
+        if !little_endian {
+            target.reverse();
+        }
+    }
+}
+
+/// 类型化数组及DataView引用的ArrayBuffer范围
+#[derive(Debug, Clone)]
+pub(crate) struct BufferView {
+    pub buffer: ObjectId,
+    pub offset: usize, //起始字节
+    pub length: usize, //类型化数组为元素个数, DataView为字节数
+}
+
+/// CanonicalNumericIndexString, "1" "-1" "1.5" "-0" "NaN" 是数字下标, "01" "1e3" 不是
+pub(crate) fn canonical_numeric_index(key: &PropertyKey) -> Option<f64> {
+    let s = key.as_str()?;
+    if s == "-0" {
+        return Some(-0.0);
+    }
+    let n = string_to_number(s);
+    (number_to_string(n) == s).then_some(n)
+}
+
+impl Program {
+    /// 创建ArrayBuffer, 直接使用bytes而不复制
+    pub fn new_array_buffer(&mut self, bytes: Vec<u8>) -> JSType {
+        let proto = self.intrinsics.array_buffer_prototype;
+        JSType::Object(self.alloc(JSObject::new(ObjectKind::ArrayBuffer(bytes), Some(proto))))
+    }
+
+    /// ArrayBuffer的内容, 类型化数组及DataView为其引用的范围
+    #[allow(dead_code)] //lib中未使用
+    pub fn array_buffer_bytes(&self, value: &JSType) -> Option<&[u8]> {
+        let JSType::Object(id) = value else {
+            return None;
+        };
+        match &self.obj(*id).kind {
+            ObjectKind::ArrayBuffer(bytes) => Some(bytes),
+            ObjectKind::TypedArray(kind, view) => {
+                let bytes = self.buffer_bytes(view.buffer);
+                Some(&bytes[view.offset..view.offset + view.length * kind.size()])
+            }
+            ObjectKind::DataView(view) => {
+                let bytes = self.buffer_bytes(view.buffer);
+                Some(&bytes[view.offset..view.offset + view.length])
+            }
+            _ => None,
+        }
+    }
+
+    /// 分配填充0的ArrayBuffer, 内存不足时抛出RangeError
+    pub(crate) fn allocate_array_buffer(
+        &mut self,
+        byte_length: usize,
+        proto: ObjectId,
+    ) -> Result<ObjectId, String> {
+        let mut bytes = Vec::new();
+        if bytes.try_reserve_exact(byte_length).is_err() {
+            return Err(err("Uncaught RangeError: Array buffer allocation failed"));
+        }
+        bytes.resize(byte_length, 0);
+        Ok(self.alloc(JSObject::new(ObjectKind::ArrayBuffer(bytes), Some(proto))))
+    }
+
+    pub(crate) fn buffer_bytes(&self, buffer: ObjectId) -> &[u8] {
+        match &self.obj(buffer).kind {
+            ObjectKind::ArrayBuffer(bytes) => bytes,
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn buffer_bytes_mut(&mut self, buffer: ObjectId) -> &mut [u8] {
+        match &mut self.obj_mut(buffer).kind {
+            ObjectKind::ArrayBuffer(bytes) => bytes,
+            _ => &mut [],
+        }
+    }
+
+    /// 类型化数组的数字下标: 不是类型化数组或不是数字下标时为None, 越界时为Some(None)
+    pub(crate) fn integer_index(&self, id: ObjectId, key: &PropertyKey) -> Option<Option<usize>> {
+        let ObjectKind::TypedArray(_, view) = &self.obj(id).kind else {
+            return None;
+        };
+        let n = canonical_numeric_index(key)?;
+        let valid = n.trunc() == n && !(n == 0.0 && n.is_sign_negative());
+        Some((valid && n >= 0.0 && n < view.length as f64).then_some(n as usize))
+    }
+
+    /// TypedArrayGetElement, index需在范围内
+    pub(crate) fn typed_array_get(&self, id: ObjectId, index: usize) -> JSType {
+        let ObjectKind::TypedArray(kind, view) = &self.obj(id).kind else {
+            return JSType::Undefined;
+        };
+        let start = view.offset + index * kind.size();
+        kind.decode(&self.buffer_bytes(view.buffer)[start..], true)
+    }
+
+    /// TypedArraySetElement, index需在范围内, value需已经过element_value转换
+    pub(crate) fn typed_array_set(&mut self, id: ObjectId, index: usize, value: &JSType) {
+        let ObjectKind::TypedArray(kind, view) = &self.obj(id).kind else {
+            return;
+        };
+        let (kind, start) = (*kind, view.offset + index * kind.size());
+        let buffer = view.buffer;
+        kind.encode(value, true, &mut self.buffer_bytes_mut(buffer)[start..]);
+    }
+
+    /// 写入元素前的类型转换, BigInt64Array/BigUint64Array为ToBigInt, 其余为ToNumber
+    pub(crate) fn element_value(
+        &mut self,
+        kind: TypedArrayKind,
+        value: &JSType,
+    ) -> Result<JSType, String> {
+        if kind.is_bigint() {
+            Ok(JSType::BigInt(self.bigint_value(value)?))
+        } else {
+            Ok(JSType::number(self.number_value(value)?))
+        }
+    }
+
+    /// 类型化数组的元素类型
+    pub(crate) fn typed_array_kind(&self, id: ObjectId) -> Option<TypedArrayKind> {
+        match &self.obj(id).kind {
+            ObjectKind::TypedArray(kind, _) => Some(*kind),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::fmt;
+
+/// js字符串, 以UTF-16码元保存, 可以包含单独的代理项
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JSString(Vec<u16>);
+
+impl JSString {
+    pub fn new() -> Self {
+        JSString(Vec::new())
+    }
+    pub fn from_units(units: Vec<u16>) -> Self {
+        JSString(units)
+    }
+    pub fn units(&self) -> &[u16] {
+        &self.0
+    }
+    /// 码元个数, 即 length
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn push_str(&mut self, s: &str) {
+        self.0.extend(s.encode_utf16());
+    }
+    pub fn push_unit(&mut self, unit: u16) {
+        self.0.push(unit);
+    }
+    pub fn push_js(&mut self, s: &JSString) {
+        self.0.extend_from_slice(&s.0);
+    }
+    /// 添加码点, 代理项范围内的值作为单独的码元
+    pub fn push_code_point(&mut self, code: u32) {
+        match char::from_u32(code) {
+            Some(c) => {
+                let mut buf = [0; 2];
+                self.0.extend_from_slice(c.encode_utf16(&mut buf));
+            }
+            None => self.0.push(code as u16),
+        }
+    }
+    pub fn concat(&self, other: &JSString) -> JSString {
+        let mut units = Vec::with_capacity(self.len() + other.len());
+        units.extend_from_slice(&self.0);
+        units.extend_from_slice(&other.0);
+        JSString(units)
+    }
+    /// [start, end) 范围的码元, 超出时截断
+    pub fn slice(&self, start: usize, end: usize) -> JSString {
+        let end = end.min(self.len());
+        let start = start.min(end);
+        JSString(self.0[start..end].to_vec())
+    }
+    /// 从from开始第一次出现的位置
+    pub fn find(&self, pattern: &JSString, from: usize) -> Option<usize> {
+        let n = pattern.len();
+        if from + n > self.len() {
+            return None;
+        }
+        (from..=self.len() - n).find(|&i| self.0[i..i + n] == pattern.0[..])
+    }
+    /// 不大于from的最后一次出现的位置
+    pub fn rfind(&self, pattern: &JSString, from: usize) -> Option<usize> {
+        let n = pattern.len();
+        if n > self.len() {
+            return None;
+        }
+        let start = from.min(self.len() - n);
+        (0..=start)
+            .rev()
+            .find(|&i| self.0[i..i + n] == pattern.0[..])
+    }
+    pub fn starts_with(&self, pattern: &JSString) -> bool {
+        self.0.starts_with(&pattern.0)
+    }
+    /// index处的码点, 高代理项后跟低代理项时合并
+    pub fn code_point_at(&self, index: usize) -> Option<u32> {
+        let first = *self.0.get(index)?;
+        if (0xD800..0xDC00).contains(&first) {
+            if let Some(&second) = self.0.get(index + 1) {
+                if (0xDC00..0xE000).contains(&second) {
+                    return Some(
+                        0x10000 + ((first as u32 - 0xD800) << 10) + (second as u32 - 0xDC00),
+                    );
+                }
+            }
+        }
+        Some(first as u32)
+    }
+    /// 不包含单独的代理项
+    pub fn is_well_formed(&self) -> bool {
+        char::decode_utf16(self.0.iter().cloned()).all(|c| c.is_ok())
+    }
+    /// 按js的空白字符去掉首尾
+    pub fn trim(&self, start: bool, end: bool) -> JSString {
+        let mut from = 0;
+        let mut to = self.len();
+        if start {
+            while from < to && is_whitespace(self.0[from]) {
+                from += 1;
+            }
+        }
+        if end {
+            while to > from && is_whitespace(self.0[to - 1]) {
+                to -= 1;
+            }
+        }
+        self.slice(from, to)
+    }
+    /// 对合法的部分做转换, 单独的代理项保持不变
+    pub fn map_well_formed(&self, f: impl Fn(&str) -> String) -> JSString {
+        let mut result = JSString::new();
+        let mut run = String::new();
+        for c in char::decode_utf16(self.0.iter().cloned()) {
+            match c {
+                Ok(c) => run.push(c),
+                Err(e) => {
+                    result.push_str(&f(&run));
+                    run.clear();
+                    result.push_unit(e.unpaired_surrogate());
+                }
+            }
+        }
+        result.push_str(&f(&run));
+        result
+    }
+}
+
+/// js的空白及换行字符
+pub fn is_whitespace(unit: u16) -> bool {
+    matches!(
+        unit,
+        0x09..=0x0D
+            | 0x20
+            | 0xA0
+            | 0x1680
+            | 0x2000..=0x200A
+            | 0x2028
+            | 0x2029
+            | 0x202F
+            | 0x205F
+            | 0x3000
+            | 0xFEFF
+    )
+}
+
+/// 单独的代理项转为U+FFFD
+impl fmt::Display for JSString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in char::decode_utf16(self.0.iter().cloned()) {
+            fmt::Write::write_char(f, c.unwrap_or(char::REPLACEMENT_CHARACTER))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for JSString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl From<&str> for JSString {
+    fn from(value: &str) -> Self {
+        JSString(value.encode_utf16().collect())
+    }
+}
+impl From<String> for JSString {
+    fn from(value: String) -> Self {
+        JSString::from(value.as_str())
+    }
+}
+impl From<&String> for JSString {
+    fn from(value: &String) -> Self {
+        JSString::from(value.as_str())
+    }
+}
+
+impl PartialEq<str> for JSString {
+    fn eq(&self, other: &str) -> bool {
+        self.0.iter().cloned().eq(other.encode_utf16())
+    }
+}
+impl PartialEq<&str> for JSString {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+/// 处理字符串字面量中的转义, 模板字符串中不允许八进制转义
+pub fn unescape(raw: &str, template: bool) -> Result<JSString, String> {
+    let mut result = JSString::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push_code_point(c as u32);
+            continue;
+        }
+        let Some(c) = chars.next() else {
+            return Err("Invalid or unexpected token".to_string());
+        };
+        match c {
+            'n' => result.push_str("\n"),
+            't' => result.push_str("\t"),
+            'r' => result.push_str("\r"),
+            'b' => result.push_str("\u{8}"),
+            'f' => result.push_str("\u{c}"),
+            'v' => result.push_str("\u{b}"),
+            //换行前的 \ 为续行
+            '\n' | '\u{2028}' | '\u{2029}' => {}
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+            }
+            'x' => {
+                let code = hex_digits(&mut chars, 2)
+                    .ok_or_else(|| "Invalid hexadecimal escape sequence".to_string())?;
+                result.push_code_point(code);
+            }
+            'u' => {
+                let invalid = || "Invalid Unicode escape sequence".to_string();
+                let code = if chars.peek() == Some(&'{') {
+                    chars.next();
+                    let mut code: u32 = 0;
+                    let mut count = 0;
+                    loop {
+                        match chars.next() {
+                            Some('}') if count > 0 => break,
+                            Some(c) if c.is_ascii_hexdigit() => {
+                                code = code.saturating_mul(16) + c.to_digit(16).unwrap();
+                                count += 1;
+                            }
+                            _ => return Err(invalid()),
+                        }
+                    }
+                    if code > 0x10FFFF {
+                        return Err("Undefined Unicode code-point".to_string());
+                    }
+                    code
+                } else {
+                    hex_digits(&mut chars, 4).ok_or_else(invalid)?
+                };
+                result.push_code_point(code);
+            }
+            '0' if !chars.peek().is_some_and(char::is_ascii_digit) => result.push_str("\0"),
+            '0'..='7' if template => {
+                return Err("Octal escape sequences are not allowed in template strings".to_string())
+            }
+            //八进制转义, 最大 \377
+            '0'..='7' => {
+                let mut code = c.to_digit(8).unwrap();
+                let max_len = if c <= '3' { 3 } else { 2 };
+                for _ in 1..max_len {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            code = code * 8 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                result.push_code_point(code);
+            }
+            '8' | '9' if template => {
+                return Err("\\8 and \\9 are not allowed in template strings".to_string())
+            }
+            c => result.push_code_point(c as u32),
+        }
+    }
+    Ok(result)
+}
+
+fn hex_digits(chars: &mut impl Iterator<Item = char>, count: usize) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..count {
+        code = code * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(code)
+}
@@ -1,13 +1,16 @@
 use std::fmt;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     Illegal,
     SyntaxError,
     EOF,
-    Literal(String),                    // 1  "a"
-    Template(Vec<String>, Vec<String>), //``
-    Ident(String),                      //a
+    Literal(String),              // "a"
+    Number(String),               // 1 1.5
+    Template(String, bool, bool), //`a${  }b${  }c`  (原始文本, 是否从`开始, 是否以`结束)
+    Ident(String),                //a
+    PrivateName(String),          //#a
     Punctuator(TokenPunctuator),
     Keyword(TokenKeyword),
 }
@@ -15,31 +18,26 @@ impl TokenType {
     pub fn to_raw(&self) -> String {
         match &self {
             TokenType::Illegal => "Illegal".to_string(),
-            TokenType::SyntaxError => "Uncaught SyntaxError: Invalid or unexpected token".to_string(),
+            TokenType::SyntaxError => {
+                "Uncaught SyntaxError: Invalid or unexpected token".to_string()
+            }
             TokenType::EOF => "EOF".to_string(),
             TokenType::Punctuator(t) => t.to_raw(),
             TokenType::Keyword(t) => t.to_raw(),
             TokenType::Literal(t) => t.to_string(),
-            TokenType::Template(t, t2) => {
-                let sub = t.len() - t2.len();
-                if sub > 1 {
-                    return super::utility::err("Template is error");
-                }
-                let mut v = String::new();
-                for n in 0..t2.len() {
-                    v.push_str(&t[n].clone());
-                    v.push_str(&format!("${{{}}}", &t2[n]));
-                }
-                if sub == 1 {
-                    v.push_str(&t.last().unwrap());
-                }
-                return v;
+            TokenType::Number(t) => t.to_string(),
+            TokenType::Template(t, head, tail) => {
+                let open = if *head { "`" } else { "}" };
+                let close = if *tail { "`" } else { "${" };
+                format!("{}{}{}", open, t, close)
             }
             TokenType::Ident(t) => t.to_string(),
+            TokenType::PrivateName(t) => format!("#{}", t),
         }
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenPunctuator {
     ///=
@@ -48,6 +46,8 @@ pub enum TokenPunctuator {
     Equal, //==
     ///===
     Congruent, //===
+    /// !==
+    NotCongruent, // !==
     ///+
     Plus, //+
     ///+=
@@ -121,8 +121,12 @@ pub enum TokenPunctuator {
     And, // &&
     ///||
     Or, // ||
-    ///!
+    /// !
     Not, // !
+    ///?
+    Question, // ?
+    ///...
+    Spread, // ...
 }
 
 impl TokenPunctuator {
@@ -131,6 +135,7 @@ impl TokenPunctuator {
             TokenPunctuator::MOV => String::from("="),
             TokenPunctuator::Equal => String::from("=="),
             TokenPunctuator::Congruent => String::from("==="),
+            TokenPunctuator::NotCongruent => String::from("!=="),
             TokenPunctuator::Plus => String::from("+"),
             TokenPunctuator::ADD => String::from("+="),
             TokenPunctuator::INC => String::from("++"),
@@ -167,43 +172,21 @@ impl TokenPunctuator {
             TokenPunctuator::MUL => String::from("*="),
             TokenPunctuator::DIV => String::from("/="),
             TokenPunctuator::MOD => String::from("%="),
+            TokenPunctuator::Question => String::from("?"),
+            TokenPunctuator::Spread => String::from("..."),
         }
     }
-    pub fn is_precedence(&self) -> bool {
-        match &self {
-            TokenPunctuator::Plus | TokenPunctuator::Minus => true,
-            TokenPunctuator::Multiply | TokenPunctuator::Divide => true,
-            TokenPunctuator::Modulo => true,
-            TokenPunctuator::Or => true,
-            TokenPunctuator::And => true,
-            TokenPunctuator::Not => true,
-            TokenPunctuator::LShift | TokenPunctuator::RShift => true,
-            TokenPunctuator::Equal | TokenPunctuator::NE => true,
-            TokenPunctuator::GT
-            | TokenPunctuator::GTE
-            | TokenPunctuator::LT
-            | TokenPunctuator::LTE => true,
-            TokenPunctuator::BitOr => true,
-            TokenPunctuator::BitXor => true,
-            TokenPunctuator::BitAnd => true,
-
-            TokenPunctuator::ADD
-            | TokenPunctuator::SUB
-            | TokenPunctuator::MUL
-            | TokenPunctuator::DIV
-            | TokenPunctuator::MOD => true,
-
-            _ => return false,
-        }
-    }
-    pub fn is_unary(&self) -> bool {
-        match &self {
-            TokenPunctuator::BitNot
-            | TokenPunctuator::Not
-            | TokenPunctuator::Plus
-            | TokenPunctuator::Minus => true,
-            _ => false,
-        }
+    /// 赋值运算符 = += -= *= /= %=
+    pub fn is_assign(&self) -> bool {
+        matches!(
+            self,
+            TokenPunctuator::MOV
+                | TokenPunctuator::ADD
+                | TokenPunctuator::SUB
+                | TokenPunctuator::MUL
+                | TokenPunctuator::DIV
+                | TokenPunctuator::MOD
+        )
     }
 }
 
@@ -214,26 +197,35 @@ impl std::fmt::Display for TokenPunctuator {
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKeyword {
-    None,
-
-    Let,      //let
-    Const,    //const
-    Var,      //var
-    If,       //if
-    Else,     //else
-    Return,   //return
-    For,      //for
-    In,       //in
-    Of,       //of
-    Break,    //break
-    Continue, //continue
-    Delete,   //delete
-    Do,       //do
-    Function, //function
-    While,    //while
-    Swith,    //switch
-    Case,     //case
-    Default,  //default
+    Let,        //let
+    Const,      //const
+    Var,        //var
+    If,         //if
+    Else,       //else
+    Return,     //return
+    For,        //for
+    In,         //in
+    Of,         //of
+    Break,      //break
+    Continue,   //continue
+    Delete,     //delete
+    Do,         //do
+    Function,   //function
+    While,      //while
+    Swith,      //switch
+    Case,       //case
+    Default,    //default
+    Class,      //class
+    Extends,    //extends
+    Super,      //super
+    New,        //new
+    This,       //this
+    Typeof,     //typeof
+    Void,       //void
+    True,       //true
+    False,      //false
+    Null,       //null
+    Instanceof, //instanceof
 }
 impl std::fmt::Display for TokenKeyword {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -258,14 +250,60 @@ impl TokenKeyword {
             TokenKeyword::In => String::from("in"),
             TokenKeyword::Of => String::from("of"),
             TokenKeyword::Delete => String::from("delete"),
-            TokenKeyword::None => String::from(""),
             TokenKeyword::Continue => String::from("continue"),
             TokenKeyword::Function => String::from("function"),
             TokenKeyword::While => String::from("while"),
             TokenKeyword::Case => String::from("case"),
             TokenKeyword::Default => String::from("default"),
+            TokenKeyword::Class => String::from("class"),
+            TokenKeyword::Extends => String::from("extends"),
+            TokenKeyword::Super => String::from("super"),
+            TokenKeyword::New => String::from("new"),
+            TokenKeyword::This => String::from("this"),
+            TokenKeyword::Typeof => String::from("typeof"),
+            TokenKeyword::Void => String::from("void"),
+            TokenKeyword::True => String::from("true"),
+            TokenKeyword::False => String::from("false"),
+            TokenKeyword::Null => String::from("null"),
+            TokenKeyword::Instanceof => String::from("instanceof"),
         }
     }
+    /// 标识符转关键字,非关键字返回None
+    pub fn from_raw(raw: &str) -> Option<TokenKeyword> {
+        let key = match raw {
+            "let" => TokenKeyword::Let,
+            "const" => TokenKeyword::Const,
+            "var" => TokenKeyword::Var,
+            "if" => TokenKeyword::If,
+            "else" => TokenKeyword::Else,
+            "return" => TokenKeyword::Return,
+            "for" => TokenKeyword::For,
+            "in" => TokenKeyword::In,
+            "of" => TokenKeyword::Of,
+            "break" => TokenKeyword::Break,
+            "continue" => TokenKeyword::Continue,
+            "delete" => TokenKeyword::Delete,
+            "do" => TokenKeyword::Do,
+            "function" => TokenKeyword::Function,
+            "while" => TokenKeyword::While,
+            "switch" => TokenKeyword::Swith,
+            "case" => TokenKeyword::Case,
+            "default" => TokenKeyword::Default,
+            "class" => TokenKeyword::Class,
+            "extends" => TokenKeyword::Extends,
+            "super" => TokenKeyword::Super,
+            "new" => TokenKeyword::New,
+            "this" => TokenKeyword::This,
+            "typeof" => TokenKeyword::Typeof,
+            "void" => TokenKeyword::Void,
+            "true" => TokenKeyword::True,
+            "false" => TokenKeyword::False,
+            "null" => TokenKeyword::Null,
+            "instanceof" => TokenKeyword::Instanceof,
+            _ => return None,
+        };
+        Some(key)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -287,132 +325,52 @@ impl Token {
         }
     }
     pub fn desc(&self) -> String {
-        String::from(format!(
-            "{:?},line:{},column:{}",
-            self.typ, self.line, self.column
-        ))
+        format!("{:?},line:{},column:{}", self.typ, self.line, self.column)
     }
 
     pub fn checked_keyword(&self) -> bool {
-        match &self.typ {
-            TokenType::Keyword(t) => {
-                return true;
-            }
-            _ => false,
-        }
+        matches!(&self.typ, TokenType::Keyword(_))
     }
     pub fn is_keyword(&self, key: TokenKeyword) -> bool {
         match &self.typ {
-            TokenType::Keyword(t) => {
-                return key == *t;
-            }
-            _ => false,
-        }
-    }
-    pub fn is_precedence(&self) -> bool {
-        match &self.typ {
-            TokenType::Punctuator(t) => t.is_precedence(),
-            _ => false,
-        }
-    }
-    pub fn is_complex(&self) -> bool {
-        if self.is_precedence() {
-            return true;
-        }
-        match &self.typ {
-            TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::LCParen
-                | TokenPunctuator::LParen
-                | TokenPunctuator::LSParen
-                | TokenPunctuator::Dot => true,
-                _ => false,
-            },
+            TokenType::Keyword(t) => key == *t,
             _ => false,
         }
     }
-    pub fn is_binary(&self) -> bool {
+    pub fn is_assign(&self) -> bool {
         match &self.typ {
-            TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::Equal
-                | TokenPunctuator::Congruent
-                | TokenPunctuator::GT
-                | TokenPunctuator::GTE
-                | TokenPunctuator::LT
-                | TokenPunctuator::LTE
-                | TokenPunctuator::NE
-                | TokenPunctuator::LShift
-                | TokenPunctuator::RShift => true,
-                _ => false,
-            },
-            _ => false,
-        }
-    }
-    pub fn is_logical(&self) -> bool {
-        match &self.typ {
-            TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::And | TokenPunctuator::Or => true,
-                _ => false,
-            },
+            TokenType::Punctuator(t) => t.is_assign(),
             _ => false,
         }
     }
     pub fn is_update(&self) -> bool {
-        match &self.typ {
-            TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::INC | TokenPunctuator::DEC => true,
-                _ => false,
-            },
-            _ => false,
-        }
+        self.is_ptor(TokenPunctuator::INC) || self.is_ptor(TokenPunctuator::DEC)
     }
     pub fn is_eof(&self, is_semicolon: bool) -> bool {
         match &self.typ {
             TokenType::EOF => true,
-            TokenType::Punctuator(t) => match &t {
-                TokenPunctuator::Semicolon => is_semicolon,
-                _ => false,
-            },
+            TokenType::Punctuator(TokenPunctuator::Semicolon) => is_semicolon,
             _ => false,
         }
     }
     pub fn is_ptor(&self, ptor: TokenPunctuator) -> bool {
         match &self.typ {
-            TokenType::Punctuator(t) => {
-                return ptor == *t;
-            }
-            _ => false,
-        }
-    }
-    pub fn is_unary(&self) -> bool {
-        match &self.typ {
-            TokenType::Punctuator(t) => t.is_unary(),
+            TokenType::Punctuator(t) => ptor == *t,
             _ => false,
         }
     }
     pub fn is_ident(&self) -> bool {
-        match &self.typ {
-            TokenType::Ident(t) => true,
-            _ => false,
-        }
-    }
-    pub fn is_literal(&self) -> bool {
-        match &self.typ {
-            TokenType::Literal(_) => true,
-            _ => false,
-        }
-    }
-    /// 这个表达式只能做+运算
-    pub fn is_ident_num_is_literal_template(&self) -> bool {
-        self.is_ident() || self.is_literal() || self.is_literal() || self.is_template_literal()
-    }
-    pub fn is_ident_num(&self) -> bool {
-        self.is_ident() || self.is_literal() || self.is_literal()
+        matches!(&self.typ, TokenType::Ident(_))
     }
-    pub fn is_template_literal(&self) -> bool {
-        match &self.typ {
-            //TokenType::Template(_) => true,
-            _ => false,
-        }
+    /// 属性名:标识符、关键字、字符串及数字都可以作为属性名 如 a.class {default:1}
+    pub fn is_property_name(&self) -> bool {
+        matches!(
+            &self.typ,
+            TokenType::Ident(_)
+                | TokenType::Keyword(_)
+                | TokenType::Literal(_)
+                | TokenType::Number(_)
+        )
     }
 }
 
@@ -423,10 +381,12 @@ impl std::fmt::Display for Token {
             TokenType::SyntaxError => write!(f, "<syntax:\x1b[31m{}\x1b[39m> ", self.typ.to_raw()),
             TokenType::EOF => write!(f, ""),
             TokenType::Ident(t) => write!(f, "<\x1b[31m{}\x1b[39m> ", t),
+            TokenType::PrivateName(t) => write!(f, "<\x1b[31m#{}\x1b[39m> ", t),
             TokenType::Punctuator(t) => write!(f, "<\x1b[36m{}\x1b[39m> ", t.to_raw()),
             TokenType::Keyword(t) => write!(f, "<key:\x1b[33m{}\x1b[39m> ", t),
             TokenType::Literal(t) => write!(f, "<\x1b[35m{}\x1b[39m> ", t),
-            TokenType::Template(t, t2) => {
+            TokenType::Number(t) => write!(f, "<\x1b[35m{}\x1b[39m> ", t),
+            TokenType::Template(..) => {
                 write!(f, "<temp:\x1b[33m {} \x1b[39m> ", &self.typ.to_raw())
             }
         }
@@ -3,181 +3,166 @@ use std::{
     time::Instant,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::jsparser::http::ReqwestClient;
 use crate::jsparser::{
+    http::HttpClient,
     lexer::{ILexer, Lexer},
+    module::{FileSystemLoader, ModuleLoader},
     parser::Parser,
-    program::JSType,
+    program::{default_clock, Clock, JSType},
 };
 
 pub fn err(str: &str) -> String {
-    if cfg!(debug_assertions) {
-        let msg = format!(
-            //"\x1b[31m{}\x1b[39m,token:<\x1b[32m{}\x1b[39m>",
-            "{}",
-            str,
-        );
-        panic!("\x1b[31m{}\x1b[39m", msg);
-    } else {
-        let msg = format!(
-            //"\x1b[31m{}\x1b[39m,token:<\x1b[32m{}\x1b[39m>",
-            "{}",
-            str,
-        );
-        return msg;
+    str.to_string()
+}
+
+/// 宿主提供的时钟/http/模块加载/随机数等实现
+pub struct HostOptions {
+    pub clock: Arc<dyn Clock>,
+    pub http_client: Option<Arc<dyn HttpClient>>, //None时fetch返回被拒绝的Promise
+    pub module_loader: Option<Arc<dyn ModuleLoader>>, //None时import报错
+    pub commonjs: Option<Arc<dyn ModuleLoader>>,  //require使用的模块加载, None时不开启CommonJS模式
+    pub random_seed: Option<u64>,                 //Math.random的种子, None时随机
+    pub stack_size: Option<usize>, //执行脚本的线程的栈大小, None时为SCRIPT_STACK_SIZE
+}
+
+impl Default for HostOptions {
+    fn default() -> Self {
+        //wasm中没有可用的http实现
+        #[cfg(target_arch = "wasm32")]
+        let http_client = None;
+        #[cfg(not(target_arch = "wasm32"))]
+        let http_client = ReqwestClient::new()
+            .ok()
+            .map(|client| Arc::new(client) as Arc<dyn HttpClient>);
+        HostOptions {
+            clock: default_clock(),
+            http_client,
+            module_loader: None,
+            commonjs: None,
+            random_seed: None,
+            stack_size: None,
+        }
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-pub fn get(url: &str) -> Result<String, Box<dyn std::error::Error>> {
-    Err("webassembly暂不支持reqwest请求库".into())
+#[allow(dead_code)] //bin中未使用
+pub fn run_web(code: String, func: Box<dyn Fn(String) + Send + 'static>) -> Result<(), String> {
+    run_web_with(code, func, HostOptions::default())
 }
 
+/// 执行脚本的线程默认的栈大小, 调试版本中每层调用约占30KB, 需要足够达到最大调用深度
 #[cfg(not(target_arch = "wasm32"))]
-pub fn get(url: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let client = reqwest::blocking::Client::builder()
-        .danger_accept_invalid_certs(true) // 忽略无效证书
-        .build()?;
-    let response = client.get(url).send()?;
-    let result = response.text()?;
-    Ok(result)
+const SCRIPT_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// 使用指定的宿主实现执行, 测试中使用虚拟时钟及模拟的http
+/// 脚本在栈大小已知的新线程中执行, 递归过深时抛出RangeError而不会栈溢出
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_web_with(
+    code: String,
+    func: Box<dyn Fn(String) + Send + 'static>,
+    options: HostOptions,
+) -> Result<(), String> {
+    let stack_size = options.stack_size.unwrap_or(SCRIPT_STACK_SIZE);
+    let options = HostOptions {
+        stack_size: Some(stack_size),
+        ..options
+    };
+    std::thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || run_program(code, func, options))
+        .map_err(|e| e.to_string())?
+        .join()
+        .unwrap_or_else(|e| std::panic::resume_unwind(e))
 }
 
-pub fn run_web(code: String, func: Box<dyn Fn(String) + Send + 'static>) -> Result<(), String> {
+/// wasm中没有线程, 栈大小为HostOptions::stack_size或Program的默认值
+#[cfg(target_arch = "wasm32")]
+pub fn run_web_with(
+    code: String,
+    func: Box<dyn Fn(String) + Send + 'static>,
+    options: HostOptions,
+) -> Result<(), String> {
+    run_program(code, func, options)
+}
+
+fn run_program(
+    code: String,
+    func: Box<dyn Fn(String) + Send + 'static>,
+    options: HostOptions,
+) -> Result<(), String> {
     let action = Arc::new(Mutex::new(func));
 
     let mut lexer = Lexer::new(code);
     lexer.print(); //打印token
     let mut parser = Parser::new(lexer);
-    let program = Arc::new(Mutex::new(parser.parse_program()?));
-    let pg = program.clone();
-
-    if let Ok(mut pg_locked) = pg.try_lock() {
-        pg_locked.print_tree(); //打印树
-
-        //绑定全局变量
-        pg_locked.bind_value(String::from("a"), JSType::Int(12));
+    let mut program = parser.parse_program()?;
+    program.print_tree(); //打印树
+    program.set_clock(options.clock);
+    if let Some(client) = options.http_client {
+        program.set_http_client(client);
+    }
+    if let Some(loader) = options.module_loader {
+        program.set_module_loader(loader);
+    }
+    if let Some(loader) = options.commonjs {
+        program.enable_commonjs(loader);
+    }
+    if let Some(seed) = options.random_seed {
+        program.set_random_seed(seed);
+    }
+    if let Some(size) = options.stack_size {
+        program.set_stack_size(size);
+    }
 
-        //注册全局方法
-        pg_locked.register_method(
-            String::from("log"),
-            Box::new({
-                let action = action.clone();
-                move |args| {
-                    // println!("\x1b[33m log => {:?}\x1b[39m", args);
-                    // dbg!(&args);
-                    action.lock().unwrap()(format!("\x1b[33m log => {:?}\x1b[39m", args));
-                    return Ok(JSType::NULL);
-                }
-            }),
-        );
-        pg_locked.register_method(
-            String::from("add"),
-            Box::new({
-                move |args| {
-                    if args.len() > 0 {
-                        let mut val = JSType::Int(0);
-                        for i in args.clone() {
-                            val = val.add(&i)?;
-                        }
-                        return Ok(val);
-                    } else {
-                        Ok(JSType::Int(0))
-                    }
-                }
-            }),
-        );
+    //绑定全局变量
+    program.bind_value(String::from("a"), JSType::Int(12));
 
-        pg_locked.register_method(
-            "ajax".to_string(),
-            Box::new({
-                let action = action.clone();
-                let pg = pg.clone();
-                move |arg| {
-                    if arg.len() == 0 {
-                        action.lock().unwrap()(format!("ajax注册失败,缺少相关参数"));
-                        return Ok(JSType::NULL);
-                    }
-                    if let JSType::Object(json) = arg.get(0).unwrap() {
-                        let url = json
-                            .get("url")
-                            .expect("缺少相关参数:url")
-                            .to_string()
-                            .unwrap();
-                        let typ = json
-                            .get("type")
-                            .or(Some(&JSType::String("get".to_owned())))
-                            .unwrap()
-                            .to_string()
-                            .unwrap()
-                            .to_lowercase();
-                        let success = json.get("success").expect("缺少相关参数:success");
-                        if typ == "get".to_string() {
-                            //注:wasm不支持多线程,可正常编译但运行会出错
-                            if cfg!(target_arch = "wasm32") {
-                                match get(&url) {
-                                    Ok(result) => {
-                                        loop {
-                                            if let Ok(mut pg) = pg.try_lock() {
-                                                _ = pg.execute_func(
-                                                    success.clone(),
-                                                    vec![JSType::String(result)],
-                                                );
-                                                break;
-                                            } else {
-                                                std::thread::sleep(
-                                                    std::time::Duration::from_millis(200),
-                                                );
-                                            }
-                                        }
-                                    }
-                                    Err(err) => action.lock().unwrap()(format!("{:?}", err)),
-                                }
-                            } else {
-                                std::thread::spawn({
-                                    let pg = pg.clone();
-                                    let success = success.clone();
-                                    let action = action.clone();
-                                    move || loop {
-                                        std::thread::sleep(std::time::Duration::from_millis(200));
-                                        match get(&url) {
-                                            Ok(result) => {
-                                                // 当循环多次结果:如果没有输出,执行到一定次数后程序会崩溃 memory allocation of 12288 bytes failed
-                                                // action.lock().unwrap()(format!("{result}"));
-                                                if let Ok(mut pg) = pg.try_lock() {
-                                                    _ = pg.execute_func(
-                                                        success.clone(),
-                                                        vec![JSType::String(result)],
-                                                    );
-                                                    break;
-                                                }
-                                            }
-                                            Err(err) => {
-                                                action.lock().unwrap()(format!("{:?}", err));
-                                                break;
-                                            }
-                                        };
-                                    }
-                                });
-                            }
-                        }
-                    }
-                    action.lock().unwrap()(format!("ajax注册成功"));
-                    return Ok(JSType::NULL);
+    //注册全局方法
+    program.register_method(
+        String::from("log"),
+        Box::new({
+            let action = action.clone();
+            move |pg, args| {
+                let args: Vec<String> = args.iter().map(|arg| pg.display(arg)).collect();
+                action.lock().unwrap()(format!("\x1b[33m log => {}\x1b[39m", args.join(" ")));
+                Ok(JSType::Undefined)
+            }
+        }),
+    );
+    //未处理的Promise拒绝
+    program.set_rejection_handler(Box::new({
+        let action = action.clone();
+        move |pg, reason| {
+            action.lock().unwrap()(format!("Uncaught (in promise) {}", pg.display(&reason)));
+        }
+    }));
+    program.register_method(
+        String::from("add"),
+        Box::new({
+            move |_, args| {
+                let mut val = JSType::Int(0);
+                for i in args {
+                    val = val.add(&i)?;
                 }
-            }),
-        );
-        pg_locked.run();
-    } else {
-        println!("程序异常");
-    }
-
-    Ok(())
+                Ok(val)
+            }
+        }),
+    );
+    program.run()
 }
 
+#[allow(dead_code)] //lib中未使用
 pub fn run_console(code: String) -> Result<(), String> {
     // let count = Arc::new(Mutex::new(0));
     let start = Instant::now();
-    _ = run_web(
+    //命令行中允许import当前目录中的文件
+    let options = HostOptions {
+        module_loader: Some(Arc::new(FileSystemLoader::new("."))),
+        ..HostOptions::default()
+    };
+    _ = run_web_with(
         code,
         Box::new(move |msg| {
             println!("msg:{}", msg);
@@ -185,6 +170,7 @@ pub fn run_console(code: String) -> Result<(), String> {
             // *i += 1;
             // println!("count:{}", *i);
         }),
+        options,
     );
     let duration = start.elapsed();
     let micros = duration.as_micros();
@@ -11,8 +11,9 @@ extern "C" {
     fn alert(s: &str);
 }
 
+/// 未捕获的异常作为js异常抛给调用方
 #[wasm_bindgen]
-pub fn run_code(code: &str) {
+pub fn run_code(code: &str) -> Result<(), JsValue> {
     run_web(
         code.to_string(),
         Box::new(|msg| {
@@ -20,5 +21,5 @@ pub fn run_code(code: &str) {
             log(&msg);
         }),
     )
-    .unwrap();
+    .map_err(|msg| JsValue::from_str(&msg))
 }
@@ -4,30 +4,210 @@ use jsparser::utility::run_console;
 fn main() -> Result<(), String> {
     _ = run_console(
         r#"
-            for(let i=0;i<10;i++){
-                if(i>=3){ break;}
-                log("第"+(i+1)+"次调用ajax");
-                ajax({
-                    url:'http://ipinfo.io',
-                    type:'get',
-                    success:function(e){
-                        // log("第"+i+"次调用ajax");// log => [String("第4次调用ajax")]
-                        log(e);
-                    }
-                });
-            }
+            fetch('https://ipinfo.io', { headers: { accept: 'application/json' } })
+                .then(function(res){
+                    log(res.status, res.headers.get('content-type'));
+                    return res.json();
+                })
+                .then(function(info){ log(info.ip); }, function(e){ log(e); });
     "#
         .to_owned(),
     );
-
-    let mut input = String::new();
-    std::io::stdin().read_line(&mut input).unwrap();
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use jsparser::http::{HttpClient, HttpRequest, HttpResponse};
+    use jsparser::lexer::{ILexer, Lexer};
+    use jsparser::module::{FileSystemLoader, MemoryLoader, ModuleLoader};
+    use jsparser::object::ObjectKind;
+    use jsparser::parser::Parser;
+    use jsparser::program::{JSType, Program, VirtualClock};
+    use jsparser::utility::{run_web_with, HostOptions};
+    use std::sync::{Arc, Mutex};
+
+    /// 测试用的http实现, 按url返回固定内容
+    struct MockHttp;
+
+    impl HttpClient for MockHttp {
+        fn send(&self, request: &HttpRequest) -> Result<HttpResponse, String> {
+            let response = |status: u16, status_text: &str, body: String| HttpResponse {
+                status,
+                status_text: status_text.to_string(),
+                url: request.url.clone(),
+                headers: vec![("content-type".to_string(), "application/json".to_string())],
+                body,
+            };
+            match request.url.as_str() {
+                "https://example.com/user" => Ok(response(
+                    200,
+                    "OK",
+                    r#"{"name":"js","tags":["a","b"],"id":1.5}"#.to_string(),
+                )),
+                "https://example.com/echo" => {
+                    let headers: Vec<String> = request
+                        .headers
+                        .iter()
+                        .map(|(k, v)| format!("{}={}", k, v))
+                        .collect();
+                    let body = format!(
+                        "{} {} {}",
+                        request.method,
+                        headers.join(","),
+                        request.body.as_deref().unwrap_or("-")
+                    );
+                    Ok(response(200, "OK", body))
+                }
+                "https://example.com/missing" => {
+                    Ok(response(404, "Not Found", "not found".to_string()))
+                }
+                _ => Err("connection refused".to_string()),
+            }
+        }
+    }
+
+    /// 测试用的模块
+    fn test_modules() -> MemoryLoader {
+        let mut loader = MemoryLoader::new();
+        loader.insert(
+            "/lib/math.js",
+            r#"
+            export let count = 0;
+            export function inc() { count++; }
+            export const PI = 3.14;
+            export default function add(a, b) { return a + b; }
+            log("math loaded");
+        "#,
+        );
+        loader.insert(
+            "/lib/index.js",
+            r#"
+            export * from "./math.js";
+            export { default as add } from "./math.js";
+            export * as util from "./util.js";
+            export { name as default } from "./util.js";
+        "#,
+        );
+        loader.insert(
+            "/lib/util.js",
+            r#"
+            export const name = "util";
+            export default class { hi() { return "hi"; } }
+        "#,
+        );
+        loader.insert(
+            "/a.js",
+            r#"
+            import { b, fromB } from "./b.js";
+            export function a() { return "a"; }
+            log("a runs", b(), fromB);
+        "#,
+        );
+        loader.insert(
+            "/b.js",
+            r#"
+            import { a } from "./a.js";
+            export function b() { return "b"; }
+            export const fromB = a();
+            log("b runs");
+        "#,
+        );
+        loader.insert("/broken.js", "export const x = ;");
+        loader.insert(
+            "/async.js",
+            r#"
+            export const value = await new Promise(function(r){ setTimeout(function(){ r(42); }, 10); });
+        "#,
+        );
+        loader
+    }
+
+    /// require使用的模块
+    fn test_commonjs() -> MemoryLoader {
+        let mut loader = MemoryLoader::new();
+        loader.insert(
+            "/cjs/counter.js",
+            r#"
+            var count = 0;
+            log("counter loaded", __filename, __dirname);
+            exports.inc = function() { return ++count; };
+        "#,
+        );
+        loader.insert(
+            "/cjs/shape.js",
+            r#"
+            module.exports = function Shape(name) { this.name = name; };
+            module.exports.sides = require("./data.json").sides;
+        "#,
+        );
+        loader.insert("/cjs/data.json", r#"{ "sides": [3, 4] }"#);
+        loader.insert(
+            "/cjs/a.js",
+            r#"
+            exports.done = false;
+            var b = require("./b.js");
+            log("in a, b.done =", b.done);
+            exports.done = true;
+        "#,
+        );
+        loader.insert(
+            "/cjs/b.js",
+            r#"
+            exports.done = false;
+            var a = require("./a.js");
+            log("in b, a.done =", a.done);
+            exports.done = true;
+        "#,
+        );
+        loader.insert(
+            "/cjs/sloppy.js",
+            r#"
+            var implements = 010;
+            leaked = implements;
+            exports.value = (function(a, a) { return a; })(1, 2);
+        "#,
+        );
+        loader.insert("/cjs/throws.js", "log('throws runs'); null.x;");
+        loader.insert("/cjs/esm.js", "export const x = 1;");
+        loader.insert("/cjs/bad.json", "{ sides: 1 }");
+        loader
+    }
+
+    /// 执行代码并返回log输出, 出错时最后一项为错误信息
+    fn run_log(code: &str) -> Vec<String> {
+        run_log_with_clock(code, VirtualClock::new())
+    }
+
+    /// 使用指定的虚拟时钟执行
+    fn run_log_with_clock(code: &str, clock: VirtualClock) -> Vec<String> {
+        let logs = Arc::new(Mutex::new(Vec::new()));
+        let result = run_web_with(
+            code.to_owned(),
+            Box::new({
+                let logs = logs.clone();
+                move |msg| {
+                    let msg = msg.trim_start_matches("\x1b[33m log => ");
+                    let msg = msg.trim_end_matches("\x1b[39m");
+                    logs.lock().unwrap().push(msg.to_string());
+                }
+            }),
+            HostOptions {
+                clock: Arc::new(clock),
+                http_client: Some(Arc::new(MockHttp)),
+                module_loader: Some(Arc::new(test_modules())),
+                commonjs: Some(Arc::new(test_commonjs())),
+                random_seed: Some(7),
+                stack_size: None,
+            },
+        );
+        let mut logs = logs.lock().unwrap().clone();
+        if let Err(msg) = result {
+            logs.push(msg);
+        }
+        logs
+    }
 
     #[test]
     fn test_str() {
@@ -51,40 +231,68 @@ mod tests {
     "#
             .to_owned(),
         );
-        // _ = run_console(
-        //     r#"
-        //     let a =1;
-        //     switch(a){
-        //         case 2:{}break;
-        //         default:
-        //     }
-        //     let d= 1;
-        // "#
-        //     .to_owned(),
-        // );
+        _ = run_console(
+            r#"
+            let a =1;
+            switch(a){
+                case 2:{}break;
+                default:
+            }
+            let d= 1;
+        "#
+            .to_owned(),
+        );
+        let logs = run_log(
+            r#"
+            let a = 1;
+            switch (a) {
+                case 2: {} break;
+                default:
+            }
+            let d = 1;
+            log(d);
+        "#,
+        );
+        assert_eq!(logs, vec!["1"]);
     }
 
     #[test]
-    fn test_ajax() {
-        _ = run_console(
-            r#"       
-            for(let i=0;i<5;i++){
-                if(i>=3){
-                    break;
+    fn test_switch_semantics() {
+        let logs = run_log(
+            r#"
+            function t(x) {
+                let out = [];
+                switch (x) {
+                    case 1: out[out.length] = "one";
+                    case 2: out[out.length] = "two"; break;
+                    default: out[out.length] = "def";
+                    case 3: { let y = 3; out[out.length] = "three" + y; }
                 }
-                log("第"+(i+1)+"次调用ajax");
-                ajax({
-                    url:'https://ipinfo.io',
-                    type:'get',
-                    success:function(e){
-                        // log("第"+i+"次调用ajax");// log => [String("第4次调用ajax")]
-                        log(e);
-                    }
-                });
+                return out;
             }
-    "#
-            .to_owned(),
+            log(t(1), t(3), t(9), t("1"));
+            let s = "";
+            for (let i = 0; i < 5; i++) {
+                switch (i % 2) { case 0: continue; default: s += i; }
+                s += ",";
+            }
+            log(s);
+            switch (1) { case 1: let v = 1; log(v); break; case 2: let w = 2; }
+            let v = 5;
+            log(v);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "[ 'one', 'two' ] [ 'three3' ] [ 'def', 'three3' ] [ 'def', 'three3' ]",
+                "1,3,",
+                "1",
+                "5",
+            ]
         );
+        let logs = run_log("switch (1) { case 1: let a; case 2: let a; }");
+        assert!(logs[0].contains("Identifier 'a' has already been declared"));
     }
 
     #[test]
@@ -126,6 +334,39 @@ mod tests {
             .to_owned(),
         );
     }
+    #[test]
+    fn test_call_depth() {
+        let logs = run_log(
+            r#"
+            function depth(n) { return n == 0 ? 0 : 1 + depth(n - 1); }
+            log(depth(1000));
+            new Promise(function(){ depth(5000); }).catch(function(e){ log(e.name, e.message); });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            ["1000", "RangeError Maximum call stack size exceeded"]
+        );
+        //在默认大小(2MB)的线程中直接执行, 递归过深时抛出RangeError而不是栈溢出
+        let logs = std::thread::spawn(|| {
+            let code = "function depth(n) { return n == 0 ? 0 : 1 + depth(n - 1); } depth(100000);";
+            let mut parser = Parser::new(Lexer::new(code.to_string()));
+            let mut program = parser.parse_program().unwrap();
+            let result = program.run();
+            let deep = run_log("function f(n) { return n == 0 ? 0 : 1 + f(n - 1); } f(100000);");
+            (result, deep)
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            logs,
+            (
+                Err("Uncaught RangeError: Maximum call stack size exceeded".to_string()),
+                vec!["Uncaught RangeError: Maximum call stack size exceeded".to_string()]
+            )
+        );
+    }
+
     #[test]
     fn test_for() {
         let _ = run_console(
@@ -189,4 +430,1723 @@ mod tests {
             .to_owned(),
         );
     }
+
+    #[test]
+    fn test_class() {
+        let logs = run_log(
+            r#"
+            class Animal {
+                name;
+                #age = 1;
+                static count = 0;
+                constructor(name) {
+                    this.name = name;
+                    Animal.count++;
+                }
+                get age() { return this.#age; }
+                set age(v) { this.#age = v; }
+                speak() { return this.name + " makes a sound"; }
+                static create(name) { return new this(name); }
+            }
+            class Dog extends Animal {
+                constructor(name) {
+                    super(name);
+                    this.kind = "dog";
+                }
+                speak() { return super.speak() + " (woof)"; }
+            }
+            let d = new Dog("Rex");
+            d.age = 3;
+            log(d.speak(), d.age, Animal.count);
+            log(d);
+            log(Animal.create("Tom").name, Animal.count);
+            log(Dog, typeof Dog);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "Rex makes a sound (woof) 3 1",
+                "Dog { name: 'Rex', kind: 'dog' }",
+                "Tom 2",
+                "[class Dog extends Animal] function",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_class_private_and_static_block() {
+        let logs = run_log(
+            r#"
+            class Counter {
+                #count = 0;
+                static #instances = 0;
+                static initialized;
+                static {
+                    this.initialized = true;
+                }
+                constructor() { Counter.#instances++; }
+                #inc() { this.#count++; }
+                tick() { this.#inc(); return this.#count; }
+                static instances() { return Counter.#instances; }
+            }
+            let c = new Counter();
+            c.tick();
+            log(c.tick(), Counter.instances(), Counter.initialized);
+            log(typeof c.tick, c.hasOwnProperty("tick"));
+            let read = function(o) { return 1; };
+            class Other { #x = 1; static read(o) { return o.#x; } }
+            Other.read(c);
+        "#,
+        );
+        assert_eq!(logs[0], "2 1 true");
+        assert_eq!(logs[1], "function false");
+        assert!(logs[2].contains(
+            "TypeError: Cannot read private member #x from an object whose class did not declare it"
+        ));
+    }
+
+    #[test]
+    fn test_class_extends_builtin() {
+        let logs = run_log(
+            r#"
+            class MyError extends Error {
+                constructor(message) {
+                    super(message);
+                    this.code = 42;
+                }
+            }
+            let e = new MyError("boom");
+            log(e.message, e.code, e.toString());
+            class List extends Array {
+                sum() {
+                    let total = 0;
+                    for (let i = 0; i < this.length; i++) total += this[i];
+                    return total;
+                }
+            }
+            let l = new List(1, 2, 3);
+            l[3] = 4;
+            log(l.length, l.sum());
+            const f = List.from([1, 2, 3]);
+            log(f instanceof List, f.sum(), List.of(4, 5) instanceof List, List.from({ length: 2, 0: "x" }));
+            const even = function(x) { return x % 2 == 0 };
+            const pair = function(x) { return [x, x] };
+            log(f.map(even) instanceof List, f.filter(even).sum(), f.slice(1).sum(), f.concat([4]).sum(), f.flat() instanceof List, f.flatMap(pair).sum());
+            log(f.splice(0, 1) instanceof List, f, [1].map(even) instanceof List, Array.of.call(undefined, 1));
+            const bad = [1]; bad.constructor = 5;
+            new Promise(function(){ bad.slice(); }).catch(function(e){ log(e.message) });
+            class Empty extends null {}
+            log(typeof Empty);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "boom 42 Error: boom",
+                "4 10",
+                "true 6 true List [ 'x', undefined ]",
+                "true 2 5 10 true 12",
+                "true List [ 2, 3 ] false [ 1 ]",
+                "function",
+                "object.constructor[Symbol.species] is not a constructor",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_class_errors() {
+        let logs = run_log("class A {} A();");
+        assert!(logs[0].contains("Class constructor A cannot be invoked without 'new'"));
+        let logs = run_log("class A extends 1 {}");
+        assert!(logs[0].contains("Class extends value 1 is not a constructor or null"));
+        let logs =
+            run_log("class A {} class B extends A { constructor(){ this.x = 1; } } new B();");
+        assert!(logs[0].contains("Must call super constructor in derived class"));
+        let logs = run_log("class A { m(){ return this.#y; } }");
+        assert!(logs[0].contains("Private field '#y' must be declared in an enclosing class"));
+    }
+
+    #[test]
+    fn test_prototype() {
+        let logs = run_log(
+            r#"
+            function Person(name) { this.name = name; }
+            Person.prototype.greet = function() { return "hi " + this.name; };
+            function Emp(name, title) { Person.call(this, name); this.title = title; }
+            Emp.prototype = Object.create(Person.prototype);
+            Emp.prototype.constructor = Emp;
+            let e = new Emp("Bob", "dev");
+            log(e.greet(), e instanceof Person, e instanceof Emp, e instanceof Array);
+            log(Object.getPrototypeOf(e) === Emp.prototype, e);
+            let base = { kind: "base" };
+            let child = Object.setPrototypeOf({}, base);
+            log(child.kind, base.isPrototypeOf(child), Object.getPrototypeOf(Object.create(null)));
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "hi Bob true true false",
+                "true Emp { name: 'Bob', title: 'dev' }",
+                "base true null",
+            ]
+        );
+        let logs = run_log(
+            r#"
+            var p = { hi: function() { return "hi" } };
+            var o = { __proto__: p, a: 1 }, n = { "__proto__": null };
+            var __proto__ = 5;
+            var s = { __proto__ }, c = { ["__proto__"]: p }, m = { __proto__() { return 1 } };
+            log(Object.getPrototypeOf(o) === p, o.hi(), Object.keys(o), Object.getPrototypeOf(n), Object.keys(s), Object.keys(c), typeof m.__proto__);
+            var r = {};
+            r.__proto__ = p; r.__proto__ = 3;
+            log(r.hi(), o.__proto__ === p, Object.getPrototypeOf({ __proto__: 1 }) === Object.prototype, (1).__proto__ === Number.prototype);
+            new Promise(function(){ p.__proto__ = o; }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "true hi [ 'a' ] null [ '__proto__' ] [ '__proto__' ] function",
+                "hi true true true",
+                "Cyclic __proto__ value",
+            ]
+        );
+        let logs = run_log("var x = { __proto__: 1, __proto__: 2 };");
+        assert!(logs[0].contains("Duplicate __proto__ fields are not allowed in object literals"));
+    }
+
+    #[test]
+    fn test_call_apply_bind() {
+        let logs = run_log(
+            r#"
+            function add(a, b) { return a + b + this.x; }
+            log(add.call({ x: 1 }, 2, 3), add.apply({ x: 10 }, [1, 2]));
+            let bound = add.bind({ x: 100 }, 1);
+            log(bound(2), bound.name, bound.length);
+            function Point(x, y) { this.x = x; this.y = y; }
+            let P = Point.bind(null, 1);
+            let p = new P(2);
+            log(p.x, p.y, p instanceof Point, p instanceof P);
+        "#,
+        );
+        assert_eq!(logs, vec!["6 13", "103 bound add 1", "1 2 true true"]);
+        let logs = run_log("let a = {}; let b = Object.create(a); Object.setPrototypeOf(a, b);");
+        assert!(logs[0].contains("TypeError: Cyclic __proto__ value"));
+        let logs = run_log("let o = { m() {} }; new o.m();");
+        assert!(logs[0].contains("TypeError: o.m is not a constructor"));
+        for code in [
+            "add.apply(p, 0)",
+            "add.apply(p, 'ab')",
+            "add.apply(p, true)",
+        ] {
+            let logs = run_log(&format!("function add() {{}} let p = {{}}; {}", code));
+            assert_eq!(
+                logs,
+                ["Uncaught TypeError: CreateListFromArrayLike called on non-object"],
+                "{}",
+                code
+            );
+        }
+        let logs = run_log("function f(a, b) { return [a, b] } log(f.apply(null, null), f.apply(null, { length: 2, 0: 1 }));");
+        assert_eq!(logs, ["[ undefined, undefined ] [ 1, undefined ]"]);
+    }
+
+    #[test]
+    fn test_for_of() {
+        let logs = run_log(
+            r#"
+            let s = "";
+            for (const x of [1, 2, 3]) s += x;
+            for (const c of "a😀b") s += "|" + c;
+            log(s);
+            const fns = [];
+            for (let i of [1, 2, 3]) fns[fns.length] = function () { return i; };
+            log(fns[0](), fns[1](), fns[2]());
+            let closed = 0;
+            const it = {
+                [Symbol.iterator]() {
+                    let n = 0;
+                    return {
+                        next() { n++; return { value: n, done: n > 5 }; },
+                        return() { closed++; return {}; },
+                    };
+                },
+            };
+            for (const v of it) { if (v == 2) break; }
+            function first() { for (const v of it) return v; }
+            log(first(), closed);
+            for (const v of it) continue;
+            log(closed);
+            for (const e of ["x", "y"].entries()) log(e);
+            let x;
+            for (x of [7, 8]);
+            log(x, [..."héllo"], [][Symbol.iterator] === [].values);
+            for (const v of 5) {}
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "123|a|😀|b",
+                "1 2 3",
+                "1 2",
+                "2",
+                "[ 0, 'x' ]",
+                "[ 1, 'y' ]",
+                "8 [ 'h', 'é', 'l', 'l', 'o' ] true",
+                "Uncaught TypeError: 5 is not iterable",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_for_in() {
+        let logs = run_log(
+            r#"
+            const proto = { a: 1, b: 2 };
+            const o = Object.create(proto);
+            o.c = 3;
+            o.a = 5;
+            o[1] = 0;
+            let s = "";
+            for (const k in o) s += k + "=" + o[k] + ";";
+            log(s);
+            for (var i in "ab");
+            log(i, "a" in o, "z" in o, 0 in [1]);
+            const d = { a: 1, b: 2 };
+            for (const k in d) { delete d.b; log(k); }
+            for (const k in null) log("never");
+        "#,
+        );
+        assert_eq!(logs, vec!["1=0;c=3;a=5;b=2;", "1 true false true", "a"]);
+        let logs = run_log("log(1); for (let a, b of [1]) {}");
+        assert_eq!(
+            logs,
+            vec!["\x1b[31m(Keyword(Of),line:1,column:25) Uncaught SyntaxError: Invalid left-hand side in for-of loop: Must have a single binding. \x1b[39m"]
+        );
+    }
+
+    #[test]
+    fn test_labeled_statements() {
+        let logs = run_log(
+            r#"
+            let s = "";
+            outer: for (let i = 0; i < 3; i++) {
+                for (let j = 0; j < 3; j++) {
+                    if (j == 1) continue outer;
+                    if (i == 2) break outer;
+                    s += i + "" + j + ",";
+                }
+            }
+            log(s);
+            blk: { s = "in"; break blk; s = "never"; }
+            log(s);
+            let n = 0;
+            a: b: do { n++; if (n < 3) continue a; } while (n < 5);
+            log(n);
+            each: for (const x of [1, 2, 3]) {
+                for (const k in { p: 1 }) { if (x == 2) continue each; log(x, k); }
+            }
+            sw: switch (1) { case 1: while (true) { break sw; } }
+            log("done");
+        "#,
+        );
+        assert_eq!(logs, vec!["00,10,", "in", "5", "1 p", "3 p", "done"]);
+        let logs = run_log("log(1); for (;;) { break foo; }");
+        assert_eq!(
+            logs,
+            vec!["\x1b[31m(Ident(\"foo\"),line:1,column:29) Uncaught SyntaxError: Undefined label 'foo' \x1b[39m"]
+        );
+        let logs = run_log("x: { continue x; }");
+        assert_eq!(
+            logs,
+            vec!["\x1b[31m(Ident(\"x\"),line:1,column:16) Uncaught SyntaxError: Illegal continue statement: 'x' does not denote an iteration statement \x1b[39m"]
+        );
+    }
+
+    #[test]
+    fn test_generator() {
+        let logs = run_log(
+            r#"
+            let calls = 0;
+            function one() { calls++; return 1; }
+            function* gen(a) {
+                let x = yield a;
+                let y = one() + (yield x * 2);
+                log("sum", y, calls);
+                for (let i = 0; i < 2; i++) { yield i; }
+                return "end";
+            }
+            const it = gen(5);
+            log(it.next().value, it.next(10).value, it.next(3).value);
+            let r = it.next(); log(r.value, r.done);
+            r = it.next(); log(r.value, r.done);
+            r = it.next(); log(r.value, r.done);
+            log(Object.getPrototypeOf(it) === gen.prototype, it[Symbol.iterator]() === it);
+            function* inner() { const v = yield 1; log("inner", v); return "done"; }
+            function* outer() { const v = yield* inner(); log("outer", v); yield* [2, 3]; }
+            const list = [];
+            for (const v of outer()) { list[list.length] = v; }
+            log(list);
+            const obj = { *keys() { yield "k"; } };
+            log([...obj.keys()]);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "sum 4 1",
+                "5 20 0",
+                "1 false",
+                "end true",
+                "undefined true",
+                "true true",
+                "inner undefined",
+                "outer done",
+                "[ 1, 2, 3 ]",
+                "[ 'k' ]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generator_return_throw() {
+        let logs = run_log(
+            r#"
+            let closed = 0;
+            const iterable = {};
+            iterable[Symbol.iterator] = function () {
+                return {
+                    next: function () { return { value: 1, done: false }; },
+                    return: function () { closed++; return {}; },
+                };
+            };
+            function* gen() { for (const v of iterable) { yield v; } }
+            const a = gen();
+            a.next();
+            const r = a.return(7);
+            log(r.value, r.done, closed, a.next().done);
+            function* self() { me.next(); }
+            const me = self();
+            me.next();
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "7 true 1 true",
+                "Uncaught TypeError: Generator is already running"
+            ]
+        );
+        let logs = run_log(
+            "function* g() { yield 1; } const it = g(); it.next(); it.throw(new Error(\"boom\"));",
+        );
+        assert_eq!(logs, vec!["Uncaught Error: boom"]);
+        let logs = run_log("function* g() { yield 1; } new g();");
+        assert_eq!(logs, vec!["Uncaught TypeError: g is not a constructor"]);
+    }
+
+    #[test]
+    fn test_promise() {
+        let logs = run_log(
+            r#"
+            log("start");
+            const p = new Promise(function (resolve) { log("executor"); resolve(1); });
+            p.then(function (v) { log("then", v); return v + 1; })
+                .then(function (v) { log("chained", v); });
+            Promise.resolve().then(function () { log("microtask"); });
+            Promise.all([1, Promise.resolve(2)]).then(function (v) { log("all", v); });
+            Promise.allSettled([Promise.reject(new Error("x")), 5]).then(function (v) {
+                log("allSettled", v[0].status, v[0].reason.message, v[1].value);
+            });
+            Promise.race([new Promise(function () {}), "fast"]).then(function (v) { log("race", v); });
+            Promise.any([Promise.reject(1), Promise.reject(2)]).catch(function (e) {
+                log("any", e instanceof AggregateError, e.errors);
+            });
+            Promise.resolve(9).finally(function () { log("finally"); })
+                .then(function (v) { log("after finally", v); });
+            Promise.resolve({ then: function (r) { r("thenable"); } }).then(function (v) { log(v); });
+            log(p, "end");
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "start",
+                "executor",
+                "Promise { 1 } end",
+                "then 1",
+                "microtask",
+                "finally",
+                "chained 2",
+                "all [ 1, 2 ]",
+                "allSettled rejected x 5",
+                "race fast",
+                "any true [ 1, 2 ]",
+                "thenable",
+                "after finally 9",
+            ]
+        );
+        let logs = run_log("Promise.reject(new Error(\"nobody\")); log(\"done\");");
+        assert_eq!(logs, vec!["done", "Uncaught (in promise) Error: nobody"]);
+    }
+
+    #[test]
+    fn test_async_await() {
+        let logs = run_log(
+            r#"
+            export {};
+            async function add(x) {
+                log("add", x);
+                const y = await x;
+                const z = await new Promise(function (r) { r(y * 10); });
+                return z + 1;
+            }
+            add(3).then(function (v) { log("result", v); });
+            async function fail() { await 1; missing(); }
+            fail().catch(function (e) { log("caught", e instanceof ReferenceError); });
+            const obj = { v: 7, async get() { return this.v; } };
+            class K { async run() { return await obj.get(); } }
+            log(typeof add, add.prototype);
+            const v = await new K().run();
+            log("top-level", v);
+            for (let i = 0; i < 2; i++) { log("loop", await i); }
+        "#,
+        );
+        assert_eq!(
+            logs,
+            vec![
+                "add 3",
+                "function undefined",
+                "caught true",
+                "top-level 7",
+                "result 31",
+                "loop 0",
+                "loop 1",
+            ]
+        );
+        let logs =
+            run_log("export {}; log(1); await Promise.reject(new RangeError(\"top\")); log(2);");
+        assert_eq!(logs, vec!["1", "Uncaught RangeError: top"]);
+        //脚本顶层的await是普通标识符
+        let logs = run_log(
+            "var await = 3; log(await); async function f() { return await 4; } f().then(function(v){ log(v); });",
+        );
+        assert_eq!(logs, vec!["3", "4"]);
+    }
+
+    #[test]
+    fn test_timers() {
+        let logs = run_log(
+            r#"
+            log("start");
+            setTimeout(function(){ log("timeout 20"); }, 20);
+            var t = setTimeout(function(){ log("never"); }, 5);
+            clearTimeout(t);
+            setTimeout(function(a, b){
+                log("timeout 0", a, b);
+                Promise.resolve().then(function(){ log("micro in timeout"); });
+            }, 0, 1, 2);
+            setTimeout(function(){ log("timeout 0 second"); }, 0);
+            queueMicrotask(function(){ log("microtask"); });
+            Promise.resolve().then(function(){ log("then"); });
+            var n = 0;
+            var iv = setInterval(function(){
+                n++;
+                log("interval", n);
+                if (n == 3) clearInterval(iv);
+            }, 7);
+            log("end");
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "start",
+                "end",
+                "microtask",
+                "then",
+                "timeout 0 1 2",
+                "micro in timeout",
+                "timeout 0 second",
+                "interval 1",
+                "interval 2",
+                "timeout 20",
+                "interval 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timers_await() {
+        let logs = run_log(
+            r#"
+            export {};
+            function sleep(ms) { return new Promise(function(r){ setTimeout(r, ms); }); }
+            log("a");
+            await sleep(100000);
+            log("b");
+            async function f() { await sleep(10); log("in f"); return 5; }
+            log(await f());
+            setTimeout("x", 1);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "a",
+                "b",
+                "in f",
+                "5",
+                "Uncaught TypeError: The \"callback\" argument must be of type function. Received x",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fetch() {
+        let logs = run_log(
+            r#"
+            export {};
+            fetch("https://example.com/user").then(function(res){
+                log(res.status, res.ok, res.statusText, res.headers.get("Content-Type"));
+                return res.json();
+            }).then(function(user){
+                log(user.name, user.tags.length, user.tags[1], user.id);
+            });
+            var res = await fetch("https://example.com/echo", {
+                method: "post",
+                headers: { "X-Token": "abc" },
+                body: "hello"
+            });
+            log(await res.text());
+            res.text().then(null, function(e){ log(e.name, e.message); });
+            var missing = await fetch("https://example.com/missing");
+            log(missing.status, missing.ok, missing.headers.has("x-none"));
+            fetch("https://other.com").catch(function(e){ log(e.name, e.message); });
+            fetch("https://example.com/echo", { body: "x" }).catch(function(e){ log(e.message); });
+            log("sync");
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "200 true OK application/json",
+                "js 2 b 1.5",
+                "POST x-token=abc hello",
+                "TypeError Body is unusable: Body has already been read",
+                "404 false false",
+                "sync",
+                "Request with GET/HEAD method cannot have body.",
+                "TypeError fetch failed: connection refused",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_import() {
+        let logs = run_log(
+            r#"
+            import add, { count, inc, PI as pi } from "./lib/math.js";
+            import * as lib from "./lib/index.js";
+            import utilName, { util } from "./lib/index.js";
+            log(add(1, 2), count, pi);
+            inc();
+            inc();
+            log(count, lib.count);
+            log(lib);
+            log(utilName, new util.default().hi());
+            log(Reflect.set(lib, "count", 5), lib.count);
+            var tag = Object.getOwnPropertyDescriptor(lib, Symbol.toStringTag);
+            log(lib[Symbol.toStringTag], tag.writable, tag.enumerable, tag.configurable);
+            log(Object.prototype.toString.call(lib), Reflect.set(lib, Symbol.toStringTag, "x"));
+            log(Object.keys(lib).length, Reflect.ownKeys(lib).length);
+            count = 1;
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "math loaded",
+                "3 0 3.14",
+                "2 2",
+                "[Module: null prototype] { PI: 3.14, add: [Function: add], count: 2, default: 'util', inc: [Function: inc], util: [Module: null prototype] { default: [class default], name: 'util' } }",
+                "util hi",
+                "false 2",
+                "Module false false false",
+                "[object Module] false",
+                "6 7",
+                "Uncaught TypeError: Assignment to constant variable.",
+            ]
+        );
+        let logs = run_log(r#"import { nope } from "./lib/math.js";"#);
+        assert_eq!(
+            logs,
+            ["Uncaught SyntaxError: The requested module './lib/math.js' does not provide an export named 'nope'"]
+        );
+        let logs = run_log(r#"if (1) { import x from "./a.js"; }"#);
+        assert!(logs[0].contains("Cannot use import statement outside a module"));
+        let logs = run_log("function f(){ export const a = 1; }");
+        assert!(logs[0].contains("Unexpected token 'export'"));
+        let loader = FileSystemLoader::new("/app");
+        assert_eq!(
+            loader.resolve("../lib/a.js", Some("/app/src/main.js")),
+            Ok("/app/lib/a.js".to_string())
+        );
+        assert_eq!(loader.resolve("./b.js", None), Ok("/app/b.js".to_string()));
+        assert!(loader.resolve("lodash", None).is_err());
+        assert!(loader.resolve("/etc/passwd", None).is_err());
+        assert!(loader
+            .resolve("../../etc/passwd", Some("/app/src/main.js"))
+            .is_err());
+        assert!(loader.resolve("../app2/a.js", None).is_err());
+        //直接load时同样不能读取root以外的文件
+        let loader = FileSystemLoader::new("src");
+        assert!(loader.load("Cargo.toml").is_err());
+        assert!(loader.load("src/../Cargo.toml").is_err());
+        assert!(loader.load("src/main.rs").is_ok());
+    }
+
+    #[test]
+    fn test_module_dynamic_import() {
+        let logs = run_log(
+            r#"
+            export default function() {}
+            import("./a.js").then(function(ns){ log("dyn", ns.a()); });
+            import("./missing.js").catch(function(e){ log(e); });
+            import("./broken.js").catch(function(e){ log(e); });
+            import("./async.js").then(function(ns){ log("async", ns.value); });
+            var ns = await import("./lib/index.js");
+            log(ns.default, ns.add(2, 3));
+            log("sync");
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "b runs",
+                "a runs b a",
+                "dyn a",
+                "TypeError: Failed to load module '/missing.js': Cannot find module '/missing.js'",
+                "SyntaxError: Unexpected token (/broken.js)",
+                "math loaded",
+                "util 5",
+                "sync",
+                "async 42",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commonjs_require() {
+        let logs = run_log(
+            r#"
+            var c = require("./cjs/counter.js");
+            log(c.inc(), c.inc(), require("./cjs/counter.js").inc());
+            var Shape = require("./cjs/shape.js");
+            log(new Shape("sq").name, Shape.sides);
+            var a = require("./cjs/a.js");
+            log("main", a.done, require("./cjs/b.js").done);
+            log(require.cache["/cjs/a.js"].loaded, require.cache["/cjs/a.js"].exports === a);
+            delete require.cache["/cjs/counter.js"];
+            log(require("./cjs/counter.js").inc());
+            log(require("./cjs/sloppy.js").value, leaked);
+            new Promise(function(){ require("./cjs/throws.js"); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ require("./cjs/throws.js"); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ require("./cjs/esm.js"); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ require("./cjs/bad.json"); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ require("./nope.js"); }).catch(function(e){ log(e.message); });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "counter loaded /cjs/counter.js /cjs",
+                "1 2 3",
+                "sq [ 3, 4 ]",
+                "in b, a.done = false",
+                "in a, b.done = true",
+                "main true true",
+                "true true",
+                "counter loaded /cjs/counter.js /cjs",
+                "1",
+                "2 8",
+                "throws runs",
+                "throws runs",
+                "Cannot read properties of null (reading 'x')",
+                "Cannot read properties of null (reading 'x')",
+                "Unexpected token 'export' (/cjs/esm.js)",
+                "/cjs/bad.json: Unexpected token 's' in JSON at position 2",
+                "Cannot find module './nope.js'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_math() {
+        let logs = run_log(
+            r#"
+            log(Math.floor(-1.5), Math.ceil(-0.5), Math.round(2.5), Math.round(-2.5), Math.round(-0.4), Math.round(0.49999999999999994));
+            log(Math.max(), Math.min(), Math.max(1, NaN, 3), Math.max(-0, 0), Math.min(0, -0), Math.max("3", 2));
+            log(Math.sign(-3), Math.sign(-0), Math.trunc(-4.7), Math.cbrt(-27), Math.hypot(3, 4), Math.hypot(NaN, Infinity), Math.hypot());
+            log(Math.pow(2, 10), Math.pow(1, Infinity), Math.pow(NaN, 0), Math.pow(2, -1), Math.sqrt(-1));
+            log(Math.clz32(1), Math.clz32(0), Math.imul(4294967295, 5), Math.fround(5.5), Math.fround(5.05));
+            log(Math.PI, Math.E, Math.atan2(1, 1), Math.log10(1000), Math.log2(8), Math.expm1(-0));
+            Math.PI = 3;
+            log(Math.PI, Math.max.length, Math.floor.name, Math.abs(null), Math.abs("-1"), Math.abs());
+            var r = Math.random();
+            log(r >= 0 && r < 1, Math.random() !== r);
+            log(Math.floor({ valueOf: function(){ return 2.7; } }));
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "-2 -0 3 -2 -0 0",
+                "-Infinity Infinity NaN 0 -0 3",
+                "-1 -0 -4 -3 5 Infinity 0",
+                "1024 NaN 1 0.5 NaN",
+                "31 32 -5 5.5 5.050000190734863",
+                "3.141592653589793 2.718281828459045 0.7853981633974483 3 3 -0",
+                "3.141592653589793 2 floor 0 1 NaN",
+                "true true",
+                "2",
+            ]
+        );
+        //相同的种子产生相同的序列
+        let code = "log(Math.random(), Math.random(), Math.random());";
+        assert_eq!(run_log(code), run_log(code));
+    }
+
+    #[test]
+    fn test_json_builtin() {
+        let logs = run_log(
+            r#"
+            export {};
+            var o = JSON.parse('{"a":[1,2,{"b":null}],"c":"x\\tyA","d":true, "e": -1.5e2}');
+            log(o, o.c);
+            log(JSON.stringify(o));
+            log(JSON.stringify(o, null, 2));
+            log(JSON.stringify([undefined, function(){}, NaN, Infinity, "q\"" + JSON.parse('"\\u0001"')], null, "--"));
+            log(JSON.stringify({u: undefined, f: function(){}, n: 1}), JSON.stringify(undefined), JSON.stringify("s"), JSON.stringify(null));
+            log(JSON.stringify({a:1,b:2,c:{a:3,d:4}}, ["a","c"]));
+            log(JSON.stringify({a:1,b:"x"}, function(k,v){ return typeof v === "number" ? v*10 : v; }));
+            log(JSON.stringify({d:{toJSON:function(k){ return "key:" + k; }}}));
+            log(JSON.parse('[1,[2,3],{"x":4}]', function(k,v){ return typeof v === "number" ? v+1 : v; }));
+            log(JSON.parse('{"a":1,"b":2}', function(k,v){ if (k === "a") return undefined; return v; }));
+            log(JSON.stringify({}, null, 4), JSON.stringify([], null, 4), JSON.stringify(0.1), JSON.stringify(-0));
+            log(JSON.stringify(new String("s")), JSON.stringify({ s: Object("t") }));
+            log(JSON.stringify([Object(5), new Number(1.5), new Boolean(false), Object(Symbol("x"))]));
+            var c = {}; c.self = c;
+            new Promise(function(){ JSON.stringify(c); }).catch(function(e){ log(e.name, e.message); });
+            new Promise(function(){ JSON.parse('{"a":1,}'); }).catch(function(e){ log(e.name, e.message); });
+            new Promise(function(){ JSON.parse('[1] x'); }).catch(function(e){ log(e.message); });
+            await null;
+            JSON.parse('');
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "{ a: [ 1, 2, { b: null } ], c: 'x\tyA', d: true, e: -150 } x\tyA",
+                r#"{"a":[1,2,{"b":null}],"c":"x\tyA","d":true,"e":-150}"#,
+                "{\n  \"a\": [\n    1,\n    2,\n    {\n      \"b\": null\n    }\n  ],\n  \"c\": \"x\\tyA\",\n  \"d\": true,\n  \"e\": -150\n}",
+                "[\n--null,\n--null,\n--null,\n--null,\n--\"q\\\"\\u0001\"\n]",
+                r#"{"n":1} undefined "s" null"#,
+                r#"{"a":1,"c":{"a":3}}"#,
+                r#"{"a":10,"b":"x"}"#,
+                r#"{"d":"key:d"}"#,
+                "[ 2, [ 3, 4 ], { x: 5 } ]",
+                "{ b: 2 }",
+                "{} [] 0.1 0",
+                r#""s" {"s":"t"}"#,
+                "[5,1.5,false,{}]",
+                "TypeError Converting circular structure to JSON",
+                "SyntaxError Unexpected token '}' in JSON at position 7",
+                "Unexpected token 'x' in JSON at position 4",
+                "Uncaught SyntaxError: Unexpected end of JSON input",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_string_methods() {
+        let logs = run_log(
+            r#"
+            export {};
+            var e = "a😀b";
+            log(e.length, e.charCodeAt(1), e.codePointAt(1), e.codePointAt(2), e.at(-1), [...e].length);
+            var lone = "\uD800x";
+            log(lone.length, lone.codePointAt(0), lone.isWellFormed(), lone.toWellFormed() === "�x", "\u{1F600}" === "😀");
+            log("tab\there".split("\t"), "a,b,,c".split(","), "abc".split(""), "a,b,c".split(",", 2), "".split(","));
+            log("hello".indexOf("l"), "hello".lastIndexOf("l"), "hello".includes("ell"), "hello".startsWith("he"), "hello".endsWith("lo"));
+            log("hello".slice(1, -1), "hello".substring(3, 1), "hello".slice(-3), "hello".charAt(9) === "");
+            log("a-b-c".replace("-", "+"), "a-b-c".replaceAll("-", "+"), "abc".replace("b", "[$&$`$']"), "aXbX".replaceAll("X", function(m, i){ return i; }), "ab".replaceAll("", "_"));
+            log("  hi  ".trim() + "|", "  hi  ".trimStart() + "|", "  hi  ".trimEnd() + "|");
+            log("5".padStart(3, "0"), "abc".padEnd(6, "12"), "ab".repeat(3), "Straße".toUpperCase(), "ÀB".toLowerCase());
+            log("Å".normalize("NFD").length, "Å".normalize() === "Å", "ﬁ".normalize("NFKC"));
+            log("a".localeCompare("b"), "b".localeCompare("a"), "a".localeCompare("a"), "a".localeCompare("B"));
+            log(String.fromCharCode(72, 105, 65601), String.fromCodePoint(128512, 65), String.raw({raw: ["x", "y", "z"]}, 1, 2), String(Symbol("s")));
+            log(new String("ab"), new String("ab")[1], "x".concat(1, 2), 'it\'s', "\x41\101\0".length, `A${1 + 1}\n`.length);
+            new Promise(function(){ "a".repeat(-1); }).catch(function(e){ log(e.name, e.message); });
+            new Promise(function(){ String.fromCodePoint(1.5); }).catch(function(e){ log(e.message); });
+            await null;
+            "a".normalize("X");
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "4 55357 128512 56832 b 3",
+                "2 55296 false true true",
+                "[ 'tab', 'here' ] [ 'a', 'b', '', 'c' ] [ 'a', 'b', 'c' ] [ 'a', 'b' ] [ '' ]",
+                "2 3 true true true",
+                "ell el llo true",
+                "a+b-c a+b+c a[bac]c a1b3 _a_b_",
+                "hi| hi  |   hi|",
+                "005 abc121 ababab STRASSE àb",
+                "2 true fi",
+                "-1 1 0 -1",
+                "HiA 😀A x1y2z Symbol(s)",
+                "[String: 'ab'] b x12 it's 3 3",
+                "RangeError Invalid count value: -1",
+                "Invalid code point 1.5",
+                "Uncaught RangeError: The normalization form should be one of NFC, NFD, NFKC, NFKD.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_methods() {
+        let logs = run_log(
+            r#"
+            export {};
+            var s = [1, 2, 3, 4, 5];
+            log(s.splice(1, 2, "a", "b", "c"), s.push(6), s.pop(), s.shift(), s.unshift(0), s);
+            log([1, 2, 3, 4].slice(1, -1), [1].concat([2, [3]], 4), [1, null, undefined, 2].join("-"), [3, 1, 2].reverse());
+            var c = [1, 2]; c.push(c); log(c.join());
+            log([10, 9, 1, 100].sort(), [10, 9, 1, 100].sort(function(a, b){ return a - b; }), ["b", undefined, "a"].sort());
+            var st = [{k:1,v:"a"},{k:0,v:"b"},{k:1,v:"c"},{k:0,v:"d"}].sort(function(x, y){ return x.k - y.k; });
+            log(st.map(function(x){ return x.v; }).join(""));
+            log([1, NaN].indexOf(NaN), [1, NaN].includes(NaN), [1, 2, 1].lastIndexOf(1), [1, 2, 3].find(function(x){ return x > 1; }), [1, 2, 3].findLastIndex(function(x){ return x < 3; }));
+            log([1, 2, 3].filter(function(x){ return x % 2; }), [1, 2, 3].reduce(function(a, b){ return a + b; }), ["a", "b"].reduceRight(function(a, b){ return a + b; }, ">"));
+            log([1, 2].some(function(x){ return x > 1; }), [1, 2].every(function(x){ return x > 1; }), [1, [2, [3, [4]]]].flat(Infinity), [1, 2].flatMap(function(x){ return [x, x * 10]; }));
+            var h = [1, , 3], count = 0;
+            h.forEach(function(){ count++; });
+            log(h.map(function(x){ return x * 2; }), h.indexOf(undefined), h.includes(undefined), count, [, "a"].concat([, "b"]), h.toSorted());
+            log(new Array(3).fill(0), [1, 2, 3].at(-1), [1, 2, 3].with(0, 7), [3, 1, 2].toSorted(), [3, 1, 2].toReversed(), [1, 2, 3].toSpliced(1, 1));
+            log(Array.isArray([]), Array.isArray({}), Array.from("a😀"), Array.from({length: 3}, function(v, i){ return i * i; }), Array.of(7, 8));
+            log(Array.prototype.join.call({length: 2, 0: "x", 1: "y"}, "+"), String([1, [2, 3]]), [1, 2, 3, 4, 5].copyWithin(0, 3));
+            new Promise(function(){ [].reduce(function(){}); }).catch(function(e){ log(e.name, e.message); });
+            new Promise(function(){ [1].with(5, 0); }).catch(function(e){ log(e.name, e.message); });
+            await null;
+            [1].map(3);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "[ 2, 3 ] 7 6 1 6 [ 0, 'a', 'b', 'c', 4, 5 ]",
+                "[ 2, 3 ] [ 1, 2, [ 3 ], 4 ] 1---2 [ 2, 1, 3 ]",
+                "1,2,",
+                "[ 1, 10, 100, 9 ] [ 1, 9, 10, 100 ] [ 'a', 'b', undefined ]",
+                "bdac",
+                "-1 true 2 2 1",
+                "[ 1, 3 ] 6 >ba",
+                "true false [ 1, 2, 3, 4 ] [ 1, 10, 2, 20 ]",
+                "[ 2, <1 empty item>, 6 ] -1 true 2 [ <1 empty item>, 'a', <1 empty item>, 'b' ] [ 1, 3, undefined ]",
+                "[ 0, 0, 0 ] 3 [ 7, 2, 3 ] [ 1, 2, 3 ] [ 2, 1, 3 ] [ 1, 3 ]",
+                "true false [ 'a', '😀' ] [ 0, 1, 4 ] [ 7, 8 ]",
+                "x+y 1,2,3 [ 4, 5, 3, 4, 5 ]",
+                "TypeError Reduce of empty array with no initial value",
+                "RangeError Invalid index : 5",
+                "Uncaught TypeError: 3 is not a function",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sparse_arrays() {
+        let logs = run_log(
+            r#"
+            var a = new Array(4294967295);
+            log(a.length, a);
+            var b = [1, 2, 3];
+            b.length = 4294967295;
+            log(b.length, b);
+            var c = [1];
+            c[4e9] = 2;
+            log(c.length, c[4e9], c, Object.keys(c));
+            c.length = 2;
+            log(c.length, c, c[4e9]);
+            var d = [];
+            d[2000] = 1;
+            for (var i = 0; i < 2000; i++) { d[i] = i; }
+            log(d.length, d[1999], d[2000], d.indexOf(1), Object.keys(d).length);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "4294967295 [ <4294967295 empty items> ]",
+                "4294967295 [ 1, 2, 3, <4294967292 empty items> ]",
+                "4000000001 2 [ 1, <3999999999 empty items>, 2 ] [ '0', '4000000000' ]",
+                "2 [ 1, <1 empty item> ] undefined",
+                "2001 1999 1 1 2001",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_array_integrity() {
+        let logs = run_log(
+            r#"
+            var a = [1, 2, 3];
+            Object.defineProperty(a, 1, { configurable: false });
+            a.length = 0;
+            log(a, a.length, delete a[1], a);
+            new Promise(function(){ "use strict"; a.length = 0; }).catch(function(e){ log(e.message); });
+            new Promise(function(){ Object.defineProperty(a, "length", { value: 0 }); }).catch(function(e){ log(e.message); });
+            var s = Object.seal([1, 2]);
+            new Promise(function(){ s.pop(); }).catch(function(e){ log(e.message); });
+            s.length = 0;
+            log(s, s.length);
+            var f = Object.freeze([1, 2]);
+            new Promise(function(){ "use strict"; f.push(3); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ "use strict"; f.pop(); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ f.shift(); }).catch(function(e){ log(e.message); });
+            log(f);
+            var l = [1, 2];
+            Object.defineProperty(l, "length", { writable: false });
+            l[2] = 3;
+            log(l, l.length, Reflect.set(l, 4, 1), Reflect.defineProperty(l, 4, { value: 1 }), Reflect.set(l, 0, 1));
+            new Promise(function(){ "use strict"; l[2] = 3; }).catch(function(e){ log(e.message); });
+            new Promise(function(){ Object.defineProperty(l, 5, { value: 1 }); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ l.push(3); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ l.unshift(0); }).catch(function(e){ log(e.message); });
+            new Promise(function(){ l.splice(0, 1, "a", "b"); }).catch(function(e){ log(e.message); });
+            log(l, l.length);
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "[ 1, 2 ] 2 false [ 1, 2 ]",
+                "[ 1, 2 ] 2",
+                "[ 1, 2 ]",
+                "[ 1, 2 ] 2 false false true",
+                "[ 1, 2 ] 2",
+                "Cannot assign to read only property 'length' of object '[ 1, 2 ]'",
+                "Cannot redefine property: length",
+                "Cannot delete property '1' of [object Array]",
+                "Cannot add property 2, object is not extensible",
+                "Cannot delete property '1' of [object Array]",
+                "Cannot assign to read only property '0' of object '[ 1, 2 ]'",
+                "Cannot assign to read only property 'length' of object '[object Array]'",
+                "Cannot define property 5, object is not extensible",
+                "Cannot assign to read only property 'length' of object '[object Array]'",
+                "Cannot assign to read only property 'length' of object '[object Array]'",
+                "Cannot assign to read only property 'length' of object '[object Array]'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_object_statics() {
+        let logs = run_log(
+            r#"
+            log(Object.keys({b:1, 2:1, a:1, 1:1}));
+            const o = {x:1};
+            Object.defineProperty(o, 'y', {value:2});
+            log(Object.keys(o), o.y, Object.getOwnPropertyNames(o));
+            log(Object.getOwnPropertyDescriptor(o, 'y'));
+            log(Object.getOwnPropertyDescriptors({get a(){ return 1 }}));
+            const a = Object.freeze([1,2,3]);
+            a[0] = 9; a.length = 0;
+            log(a, Object.isFrozen(a), Object.isSealed(a), Object.isFrozen({}));
+            log(Object.entries({a:1,b:'x'}), Object.values('ab'), Object.fromEntries([['a',1],['b',2]]));
+            log(Object.assign({}, {a:1}, null, {b:2}), Object.is(NaN, NaN), Object.is(0, -0), Object.hasOwn({a:1}, 'a'));
+            const s = Object.seal({q:1}); s.q = 2; s.r = 3; delete s.q;
+            log(s, Object.isSealed(s), Object.isFrozen(s));
+            const p = Object.defineProperties({}, {a:{value:1, enumerable:true}, b:{get(){ return this.a+1 }}});
+            log(p, p.b, Object.keys(p));
+            new Promise(function(){ Object.defineProperty(o, 'y', {value:3}); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Object.defineProperty(Object.preventExtensions({}), 'z', {value:3}); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Object.fromEntries([1]); }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "[ '1', '2', 'b', 'a' ]",
+                "[ 'x' ] 2 [ 'x', 'y' ]",
+                "{ value: 2, writable: false, enumerable: false, configurable: false }",
+                "{ a: { get: [Function: get a], set: undefined, enumerable: true, configurable: true } }",
+                "[ 1, 2, 3 ] true true false",
+                "[ [ 'a', 1 ], [ 'b', 'x' ] ] [ 'a', 'b' ] { a: 1, b: 2 }",
+                "{ a: 1, b: 2 } true false true",
+                "{ q: 2 } true false",
+                "{ a: 1 } 2 [ 'a' ]",
+                "Cannot redefine property: y",
+                "Cannot define property z, object is not extensible",
+                "Iterator value 1 is not an entry object",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collections() {
+        let logs = run_log(
+            r#"
+            const m = new Map([['a', 1], [NaN, 2]]);
+            m.set(-0, 'zero').set({}, 'obj');
+            log(m, m.size, m.get(NaN), m.get(0), m.has('a'), m.get('b'));
+            m.delete('a');
+            log([...m.keys()], [...m.entries()].length, [...m][0]);
+            const s = new Set([1, 2, 2, '2', 1.0]);
+            log(s, s.size, [...s.entries()], s.keys === s.values);
+            const seen = [];
+            s.forEach(function(v, k, set) { seen.push(v); if (v === 1) { set.delete(2); set.add(3); } });
+            log(seen, Object.prototype.toString.call(s));
+            const wm = new WeakMap([[m, 1]]), ws = new WeakSet(), key = {};
+            wm.set(key, 'v'); ws.add(key);
+            log(wm.get(key), wm.get(m), wm.has({}), ws.has(key), wm, wm.delete(key), wm.has(key));
+            new Promise(function(){ wm.set(1, 2); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Map(); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ new Set(5); }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "Map(4) { 'a' => 1, NaN => 2, 0 => 'zero', {} => 'obj' } 4 2 zero true undefined",
+                "[ NaN, 0, {} ] 3 [ NaN, 2 ]",
+                "Set(3) { 1, 2, '2' } 3 [ [ 1, 1 ], [ 2, 2 ], [ '2', '2' ] ] true",
+                "[ 1, '2', 3 ] [object Set]",
+                "v 1 false true WeakMap { <items unknown> } true false",
+                "Invalid value used as weak map key",
+                "Constructor Map requires 'new'",
+                "5 is not iterable",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        let logs = run_log(
+            r#"
+            const app = Symbol.for('app'), k = Symbol('k');
+            log(app === Symbol.for('app'), Symbol.keyFor(app), Symbol.keyFor(k), k.description, String(k));
+            const o = { [k]: 1, x: 2 };
+            log(Object.keys(o), o[k], Object.getOwnPropertySymbols(o).length);
+            const money = { [Symbol.toPrimitive]: function(hint) { return hint === 'number' ? 42 : 'money:' + hint; } };
+            log(+money, `${money}`, money + '');
+            class Even { static [Symbol.hasInstance](n) { return n % 2 === 0; } }
+            log(2 instanceof Even, 3 instanceof Even, [] instanceof Array);
+            const tagged = { get [Symbol.toStringTag]() { return 'Custom'; } };
+            log(String(tagged), Object.prototype.toString.call(new Map().keys()), Object.prototype.toString.call(k));
+            log(k == k, k == Symbol('k'), k == "k", k == 1, Object(k) == k, k == Object(k), Object(k) == Object(k));
+            const stream = { [Symbol.asyncIterator]: function() {
+                let i = 0;
+                return { next: function() { i++; return Promise.resolve({ value: i, done: i > 3 }); } };
+            } };
+            (async function() {
+                const seen = [];
+                for await (const v of stream) { if (v === 2) continue; seen.push(v); }
+                for await (const v of [Promise.resolve('p'), 'q']) seen.push(v);
+                log(seen);
+            })();
+            new Promise(function(){ 1 instanceof 2; }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Symbol.keyFor('app'); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ +{ [Symbol.toPrimitive]: function() { return {}; } }; }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "true app undefined k Symbol(k)",
+                "[ 'x' ] 1 1",
+                "42 money:string money:default",
+                "true false true",
+                "[object Custom] [object Map Iterator] [object Symbol]",
+                "true false false false true true false",
+                "Right-hand side of 'instanceof' is not an object",
+                "app is not a symbol",
+                "Cannot convert object to primitive value",
+                "[ 1, 3, 'p', 'q' ]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_date() {
+        //2024-02-29T13:05:09.007Z, 本地时间为UTC+08:00
+        let clock = VirtualClock::with_time(1709211909007.0, 480);
+        let logs = run_log_with_clock(
+            r#"
+            const now = new Date();
+            log(Date.now(), now, String(now), now.getHours(), now.getUTCHours(), now.getTimezoneOffset());
+            const d = new Date(2024, 0, 31, 23, 59);
+            log(d.toISOString(), d.getDay(), d.setMonth(1), d.getDate(), d.toDateString(), d.toTimeString());
+            log(Date.parse('2024-02-29'), Date.parse('2024-02-29T08:00'), Date.parse('2024-02-29T08:00:00.5-01:30'));
+            log(Date.parse(now.toString()), Date.parse(now.toUTCString()), Date.parse('Feb 29, 2024 9:05 PM'), Date.parse('29 Feb 2024 13:05:09 +0100'));
+            log(Date.parse('2020-02-30'), Date.parse('2023-02-29'), Date.parse('2024-04-31T08:00Z'), Date.parse('2024-13-01'), Date.parse('2000-02-29'));
+            log(Date.UTC(2000, 1, 29), new Date(Date.UTC(-1, 0)).toISOString(), new Date(Date.UTC(275760, 8, 13, 0, 0, 0, 1)));
+            log(JSON.stringify({ now }), now - new Date(2024, 1, 29), `${now}` === now.toString(), Object.prototype.toString.call(now));
+            const e = new Date(NaN);
+            log(e, String(e));
+            log(e.setHours(1), e.setFullYear(2001), e.toISOString());
+            setTimeout(function() { log(Date.now() - now.getTime()); }, 1500);
+            new Promise(function(){ new Date('x').toISOString(); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Date.prototype.getTime.call({}); }).catch(function(e){ log(e.message) });
+        "#,
+            clock,
+        );
+        assert_eq!(
+            logs,
+            [
+                "1709211909007 2024-02-29T13:05:09.007Z Thu Feb 29 2024 21:05:09 GMT+0800 21 13 -480",
+                "2024-01-31T15:59:00.000Z 3 1709395140000 2 Sat Mar 02 2024 23:59:00 GMT+0800",
+                "1709164800000 1709164800000 1709199000500",
+                "1709211909000 1709211909000 1709211900000 1709208309000",
+                "NaN NaN NaN NaN 951782400000",
+                "951782400000 -000001-01-01T00:00:00.000Z Invalid Date",
+                "{\"now\":\"2024-02-29T13:05:09.007Z\"} 75909007 true [object Date]",
+                "Invalid Date Invalid Date",
+                "NaN 978278400000 2000-12-31T16:00:00.000Z",
+                "Invalid time value",
+                "this is not a Date object.",
+                "1500",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_number() {
+        let logs = run_log(
+            r#"
+            log(1e21, 1e20, 0.000001, 1e-7, 0.1 + 0.2, 5e-324, 1.7976931348623157e308);
+            log(0x1F, 0o17, 0b101, 1_000, .5, 010, 019, 2e3);
+            log((2.5).toFixed(0), (1.005).toFixed(2), (1e21).toFixed(2), (-1.5).toFixed(0));
+            log((123.456).toPrecision(4), (1234.5).toPrecision(2), (0.000001234).toPrecision(2), (123456).toExponential(2), (1.5).toExponential());
+            log((255).toString(16), (0.5).toString(2), (-255).toString(36), (3.14).toString(8));
+            log(parseInt("  0x1F"), parseInt("12px"), parseInt("z", 36), parseInt("10", 1), parseFloat("3.14abc"), parseFloat("-Infinityx"), parseFloat("1e"));
+            log(isNaN("abc"), isFinite("12"), Number.isNaN("abc"), Number.isInteger(5.0), Number.isSafeInteger(9007199254740992), Number.parseInt === parseInt);
+            log(Number("  12  "), Number(""), Number("0x10"), Number("1_0"), Number(), Number.MAX_SAFE_INTEGER);
+            const n = new Number(5);
+            log(typeof n, n + 1, n.toFixed(1), Object.prototype.toString.call(n), typeof Object(3), Object(true) instanceof Boolean);
+            log(Boolean(""), new Boolean(false) ? 1 : 2, (true).toString(), Boolean.prototype.valueOf());
+            new Promise(function(){ (1).toString(1); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Number.prototype.toFixed.call("1"); }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "1e+21 100000000000000000000 0.000001 1e-7 0.30000000000000004 5e-324 1.7976931348623157e+308",
+                "31 15 5 1000 0.5 8 19 2000",
+                "3 1.00 1e+21 -2",
+                "123.5 1.2e+3 0.0000012 1.23e+5 1.5e+0",
+                "ff 0.1 -73 3.10753412172702437",
+                "31 12 35 NaN 3.14 -Infinity 1",
+                "true true false true false true",
+                "12 0 16 NaN 0 9007199254740991",
+                "object 6 5.0 [object Number] object true",
+                "false 1 true false",
+                "toString() radix must be between 2 and 36",
+                "Number.prototype.toFixed requires that 'this' be a Number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bigint() {
+        let logs = run_log(
+            r#"
+            log(12345678901234567890n * 10n, 0x1fn, 0o17n, 0b101n, -7n / 2n, -7n % 2n, 1n << 70n, -9n >> 2n, 5n & 3n, ~5n);
+            log(typeof 1n, 1n == 1, 1n === 1, 2n > 1, 1n < 1.5, 10n == "10", 0n ? 1 : 2, 1n + "", [1n, -2n], Object(1n));
+            log(BigInt(42), BigInt("0x10"), BigInt(true), Number(2n * 3n), BigInt.asIntN(8, 255n), BigInt.asUintN(8, -1n), BigInt.asIntN(64, 1n << 63n));
+            log((255n).toString(16), (-10n).toString(2), (1234567n).toLocaleString("de-DE"), Object.prototype.toString.call(1n), JSON.stringify({ a: 1 }));
+            var m = new Map([[1n, "a"]]); var x = 1n; x++;
+            log(m.get(1n), m.has(1), x, -x, [3n, 1n, 2n].sort().join());
+            new Promise(function(){ 1n + 1; }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ 1n / 0n; }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ BigInt(1.5); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ BigInt("1.5"); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ new BigInt(1); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ JSON.stringify({ a: 1n }); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ +1n; }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ Math.max(1n); }).catch(function(e){ log(e.name, e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "123456789012345678900n 31n 15n 5n -3n -1n 1180591620717411303424n -3n 1n -6n",
+                "bigint true false true true true 2 1 [ 1n, -2n ] [BigInt: 1n]",
+                "42n 16n 1n 6 -1n 255n -9223372036854775808n",
+                "ff -1010 1.234.567 [object BigInt] {\"a\":1}",
+                "a false 2n -2n 1,2,3",
+                "TypeError Cannot mix BigInt and other types, use explicit conversions",
+                "RangeError Division by zero",
+                "RangeError The number 1.5 cannot be converted to a BigInt because it is not an integer",
+                "SyntaxError Cannot convert 1.5 to a BigInt",
+                "TypeError BigInt is not a constructor",
+                "TypeError Do not know how to serialize a BigInt",
+                "TypeError Cannot convert a BigInt value to a number",
+                "TypeError Cannot convert a BigInt value to a number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_weak_collections_gc() {
+        let code = r#"
+            var wm = new WeakMap(), ws = new WeakSet(), live = {};
+            (function(){
+                var temp = {};
+                wm.set(temp, { list: [1, 2, 3] });
+                ws.add(temp);
+            })();
+            wm.set(live, { kept: true });
+            ws.add(live);
+            keep(wm, ws, live);
+        "#;
+        let kept = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = Parser::new(Lexer::new(code.to_string()));
+        let mut program = parser.parse_program().unwrap();
+        program.register_method(
+            "keep".to_string(),
+            Box::new({
+                let kept = kept.clone();
+                move |_, args| {
+                    kept.lock().unwrap().extend(args);
+                    Ok(JSType::Undefined)
+                }
+            }),
+        );
+        program.run().unwrap();
+        let kept = kept.lock().unwrap();
+        let (JSType::Object(wm), JSType::Object(ws), JSType::Object(live)) =
+            (&kept[0], &kept[1], &kept[2])
+        else {
+            panic!("expected objects");
+        };
+        let sizes = |program: &Program| match (&program.obj(*wm).kind, &program.obj(*ws).kind) {
+            (ObjectKind::WeakMap(map), ObjectKind::WeakSet(set)) => (map.len(), set.len()),
+            _ => panic!("expected WeakMap and WeakSet"),
+        };
+        assert_eq!(sizes(&program), (2, 2));
+        program.collect_garbage();
+        //临时对象不可达后其条目被删除, 仍可达的键保留
+        assert_eq!(sizes(&program), (1, 1));
+        match (&program.obj(*wm).kind, &program.obj(*ws).kind) {
+            (ObjectKind::WeakMap(map), ObjectKind::WeakSet(set)) => {
+                assert!(set.contains(live));
+                assert_eq!(program.display(&map[live]), "{ kept: true }");
+            }
+            _ => panic!("expected WeakMap and WeakSet"),
+        }
+    }
+
+    #[test]
+    fn test_weak_map_closure_keys_gc() {
+        let code = r#"
+            var wm = new WeakMap();
+            function make(j) {
+                var key = { j: j };
+                wm.set(key, j);
+                return function() { return key; };
+            }
+            function plain(j) {
+                wm.set({ j: j }, j);
+            }
+            for (var j = 0; j < 100; j++) {
+                make(j);
+                plain(j);
+            }
+            var held = make(100);
+            keep(wm);
+        "#;
+        let kept = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = Parser::new(Lexer::new(code.to_string()));
+        let mut program = parser.parse_program().unwrap();
+        program.register_method(
+            "keep".to_string(),
+            Box::new({
+                let kept = kept.clone();
+                move |_, args| {
+                    kept.lock().unwrap().extend(args);
+                    Ok(JSType::Undefined)
+                }
+            }),
+        );
+        program.run().unwrap();
+        program.collect_garbage();
+        let kept = kept.lock().unwrap();
+        let JSType::Object(wm) = &kept[0] else {
+            panic!("expected object");
+        };
+        //只有仍被闭包引用的键保留
+        match &program.obj(*wm).kind {
+            ObjectKind::WeakMap(map) => {
+                assert_eq!(map.len(), 1);
+                assert_eq!(program.display(map.values().next().unwrap()), "100");
+            }
+            _ => panic!("expected WeakMap"),
+        }
+    }
+
+    #[test]
+    fn test_typed_arrays() {
+        let logs = run_log(
+            r#"
+            const u8 = new Uint8Array([1, 2, 300, -1, 1.7]);
+            log(u8, u8[2], u8[10], 2 in u8, 10 in u8, Object.keys(u8).length);
+            log(new Uint8ClampedArray([300, -5, 1.5, 2.5, NaN]), new Int8Array([127, 128, 255, -129]), new Float32Array([0.1]));
+            const buf = new ArrayBuffer(8);
+            const dv = new DataView(buf);
+            dv.setUint16(0, 0x1234); dv.setUint16(2, 0x1234, true); dv.setFloat32(4, 1.5);
+            log(buf, dv.getUint16(0), dv.getUint16(0, true), dv.getInt8(1), dv.getFloat32(4));
+            const u16 = new Uint16Array(buf, 2, 2);
+            log(u16, u16.byteOffset, u16.byteLength, u16.buffer === buf);
+            const sub = u8.subarray(1, 3); sub[0] = 99;
+            const sl = u8.slice(-2); sl[0] = 7;
+            log(sub, sl, u8);
+            const t = new Int16Array(6); t.set([1, 2, 3], 1); t.set(new Int8Array([-1, -2]), 4);
+            log(t, t.map(function(x){ return x * 2 }), t.filter(function(x){ return x > 1 }), t.join("-"), t.indexOf(3));
+            log(new Float64Array([3, -0, 0, NaN, -Infinity]).sort(), Uint8Array.from([1, 2], function(x){ return x * 10 }), Int32Array.of(1, 2));
+            log(Object.prototype.toString.call(u8), String(u8), Array.from(u8.keys()), JSON.stringify(new Uint8Array([5, 6])));
+            u8[7] = 1;
+            log(u8[7], delete u8[0], Object.getPrototypeOf(Uint8Array) === Object.getPrototypeOf(Int32Array));
+            new Promise(function(){ Uint8Array(2); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ new Int32Array(new ArrayBuffer(6)); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ dv.getFloat64(1); }).catch(function(e){ log(e.message) });
+            new Promise(function(){ t.set([1, 2, 3], 5); }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "Uint8Array(5) [ 1, 2, 44, 255, 1 ] 44 undefined true false 5",
+                "Uint8ClampedArray(5) [ 255, 0, 2, 2, 0 ] Int8Array(4) [ 127, -128, -1, 127 ] Float32Array(1) [ 0.10000000149011612 ]",
+                "ArrayBuffer { [Uint8Contents]: <12 34 34 12 3f c0 00 00>, byteLength: 8 } 4660 13330 52 1.5",
+                "Uint16Array(2) [ 4660, 49215 ] 2 4 true",
+                "Uint8Array(2) [ 99, 44 ] Uint8Array(2) [ 7, 1 ] Uint8Array(5) [ 1, 99, 44, 255, 1 ]",
+                "Int16Array(6) [ 0, 1, 2, 3, -1, -2 ] Int16Array(6) [ 0, 2, 4, 6, -2, -4 ] Int16Array(2) [ 2, 3 ] 0-1-2-3--1--2 3",
+                "Float64Array(5) [ -Infinity, -0, 0, 3, NaN ] Uint8Array(2) [ 10, 20 ] Int32Array(2) [ 1, 2 ]",
+                "[object Uint8Array] 1,99,44,255,1 [ 0, 1, 2, 3, 4 ] {\"0\":5,\"1\":6}",
+                "undefined false true",
+                "Constructor Uint8Array requires 'new'",
+                "byte length of Int32Array should be a multiple of 4",
+                "Offset is outside the bounds of the DataView",
+                "offset is out of bounds",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bigint_typed_arrays() {
+        let logs = run_log(
+            r#"
+            const a = new BigInt64Array(3);
+            a[0] = 1n; a[1] = -1n; a[2] = 1n << 63n;
+            const u = new BigUint64Array(a.buffer);
+            log(a, u[1], u[2], BigInt64Array.BYTES_PER_ELEMENT, Object.prototype.toString.call(u));
+            const b = BigInt64Array.from([5n, 3n, -2n]).sort();
+            b.set([9n], 1);
+            log(b.join(), b.slice(1).join(), b.indexOf(9n), new BigUint64Array([1n, 2n]).map(function(x){ return x * 10n }).join(), new BigInt64Array(2).fill(7n).join());
+            a[0] = "12"; a[1] = true;
+            log(a[0], a[1]);
+            const dv = new DataView(new ArrayBuffer(16));
+            dv.setBigInt64(0, -2n); dv.setBigUint64(8, 0x0102030405060708n, true);
+            log(dv.getBigInt64(0), dv.getBigUint64(0), dv.getBigUint64(8, true), dv.getUint8(8));
+            new Promise(function(){ a[0] = 1; }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ new Float64Array([1n]); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ new BigInt64Array(new Int8Array(2)); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ new Int8Array(2).set(new BigInt64Array(1)); }).catch(function(e){ log(e.name, e.message) });
+            new Promise(function(){ dv.setBigInt64(0, 1); }).catch(function(e){ log(e.name, e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "BigInt64Array(3) [ 1n, -1n, -9223372036854775808n ] 18446744073709551615n 9223372036854775808n 8 [object BigUint64Array]",
+                "-2,9,5 9,5 1 10,20 7,7",
+                "12n 1n",
+                "-2n 18446744073709551614n 72623859790382856n 8",
+                "TypeError Cannot convert 1 to a BigInt",
+                "TypeError Cannot convert a BigInt value to a number",
+                "TypeError Cannot mix BigInt and other types, use explicit conversions",
+                "TypeError Cannot mix BigInt and other types, use explicit conversions",
+                "TypeError Cannot convert 1 to a BigInt",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_host_array_buffer() {
+        let code = r#"
+            const bytes = new Uint8Array(payload());
+            const view = new DataView(bytes.buffer);
+            keep(view.getUint32(0, true), bytes.subarray(2));
+            bytes[3] = 0xff;
+        "#;
+        let kept = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = Parser::new(Lexer::new(code.to_string()));
+        let mut program = parser.parse_program().unwrap();
+        program.register_method(
+            "payload".to_string(),
+            Box::new(|program, _| Ok(program.new_array_buffer(vec![1, 2, 3, 4, 5]))),
+        );
+        program.register_method(
+            "keep".to_string(),
+            Box::new({
+                let kept = kept.clone();
+                move |_, args| {
+                    kept.lock().unwrap().extend(args);
+                    Ok(JSType::Undefined)
+                }
+            }),
+        );
+        program.run().unwrap();
+        let kept = kept.lock().unwrap();
+        assert_eq!(program.display(&kept[0]), "67305985");
+        //subarray与脚本共享同一块内存
+        assert_eq!(program.array_buffer_bytes(&kept[1]), Some(&[3, 255, 5][..]));
+    }
+
+    #[test]
+    fn test_proxy_reflect() {
+        let logs = run_log(
+            r#"
+            const target = { a: 1, b: 2 };
+            const p = new Proxy(target, {
+                get: function(t, k) { return k in t ? t[k] : "missing" },
+                set: function(t, k, v) { t[k] = v * 2; return true },
+                has: function(t, k) { return k !== "b" },
+                deleteProperty: function(t, k) { delete t[k]; return true },
+                ownKeys: function() { return ["b", "a"] },
+            });
+            p.c = 5;
+            log(p.a, p.zz, target.c, "b" in p, "a" in p, Object.keys(p));
+            delete p.a;
+            log(target, p, typeof p, Array.isArray(new Proxy([], {})));
+            const f = new Proxy(function(x) { return x + 1 }, {
+                apply: function(t, self, args) { return t(args[0]) * 10 },
+                construct: function(t, args) { return { made: args[0] } },
+            });
+            log(typeof f, f(1), new f(7).made);
+            const defined = [];
+            const obs = new Proxy({}, { defineProperty: function(t, k, d) { defined.push(k + ":" + d.value); return Reflect.defineProperty(t, k, d) } });
+            obs.n = 3;
+            log(defined, JSON.stringify(obs), Object.create(new Proxy({}, { get: function(t, k) { return "proto " + k } })).x);
+            function A(x) { this.x = x }
+            function B() {}
+            const a = Reflect.construct(A, [4], B);
+            const o = {};
+            log(a.x, a instanceof B, Reflect.ownKeys({ a: 1, [Symbol.iterator]: 2 }), Reflect.get({ get v() { return this.w } }, "v", { w: 9 }));
+            log(Reflect.defineProperty(o, "q", { value: 1 }), Reflect.set(o, "q", 2), Reflect.deleteProperty(o, "q"), Reflect.preventExtensions(o), Reflect.setPrototypeOf(o, null), Reflect.apply(Math.max, null, [1, 3]));
+            const r = Proxy.revocable({}, {});
+            r.proxy.x = 1;
+            log(r.proxy.x, String(Reflect));
+            r.revoke();
+            const nc = Object.defineProperty({}, "x", { value: 1 });
+            new Promise(function(){ r.proxy.x }).catch(function(e){ log(e.message) });
+            new Promise(function(){ new Proxy(Object.freeze({ k: 1 }), { get: function() { return 2 } }).k }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Object.keys(new Proxy(nc, { ownKeys: function() { return [] } })) }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Proxy({}, {}) }).catch(function(e){ log(e.message) });
+            new Promise(function(){ Reflect.get(1, "a") }).catch(function(e){ log(e.message) });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "1 missing 10 false true [ 'b', 'a' ]",
+                "{ b: 2, c: 10 } { b: 2, c: 10 } object true",
+                "function 20 7",
+                "[ 'n:3' ] {\"n\":3} proto x",
+                "4 true [ 'a', Symbol(Symbol.iterator) ] 9",
+                "true false false true false 3",
+                "1 [object Reflect]",
+                "Cannot perform 'get' on a proxy that has been revoked",
+                "'get' on proxy: property 'k' is a read-only and non-configurable data property on the proxy target but the proxy did not return its actual value (expected '1' but got '2')",
+                "'ownKeys' on proxy: trap result did not include 'x'",
+                "Constructor Proxy requires 'new'",
+                "Reflect.get called on non-object",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tagged_templates() {
+        let logs = run_log(
+            r#"
+            function tag(s, ...values) {
+                const cooked = s.map(function(x) { return x === undefined ? "U" : x });
+                return cooked.join("|") + " " + s.raw.join("|") + " " + values.join(",");
+            }
+            const a = 1, b = 2;
+            log(tag`x${a}y${b}z`, tag`\unicode${a}\x41`);
+            function site(s) { return s }
+            const list = [];
+            for (let i = 0; i < 2; i++) list.push(site`a${i}b`);
+            log(list[0] === list[1], site`a` === site`a`, Object.isFrozen(list[0]), Object.isFrozen(list[0].raw), Object.keys(list[0]));
+            const o = { n: "o", m: function(s) { return this.n + s[0] } };
+            log(o.m`!`, String.raw`C:\new\t${a}x`, String.raw({ raw: ["a", "b", "c"] }, 1, 2, 3));
+            log(`outer ${`inner ${a + b} }`} end`, `${ { x: "}" }.x }`, `a${[1, 2].map(function(x) { return `<${x}>` }).join("")}b`);
+            function* g() { const r = yield 1; log(tag`v${r}w${yield 2}`) }
+            const it = g();
+            it.next();
+            it.next("R");
+            it.next("S");
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "x|y|z x|y|z 1,2 U|A \\unicode|\\x41 1",
+                "true false true true [ '0', '1' ]",
+                "o! C:\\new\\t1x a1b2c",
+                "outer inner 3 } end } a<1><2>b",
+                "v|w| v|w| R,S",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_intl() {
+        let clock = VirtualClock::with_time(1709211909007.0, 480);
+        let logs = run_log_with_clock(
+            r#"
+            log((1234567.891).toLocaleString(), (1234567.891).toLocaleString("de-DE"), (0.256).toLocaleString("zh-CN", { style: "percent" }));
+            const usd = new Intl.NumberFormat("en-US", { style: "currency", currency: "USD" });
+            const cny = Intl.NumberFormat("zh-CN", { style: "currency", currency: "CNY" });
+            log(usd.format(-1234.5), cny.format(1234.5), [1, 22].map(usd.format).join(" "), usd.format === usd.format);
+            log(new Intl.NumberFormat("en", { maximumSignificantDigits: 3 }).format(123456), (1.5).toLocaleString("en", { minimumFractionDigits: 2 }), (1234).toLocaleString("en", { useGrouping: false }));
+            log(new Intl.NumberFormat("fr-FR").resolvedOptions().locale, new Intl.Collator("zh").resolvedOptions().locale, Intl.getCanonicalLocales(["EN-us", "zh-hans-cn"]).join(), Intl.DateTimeFormat.supportedLocalesOf(["fr", "zh"]).join());
+            const d = new Date(Date.UTC(2024, 0, 5, 15, 4, 9));
+            log(d.toLocaleString(), d.toLocaleString("zh-CN"), d.toLocaleDateString("en-US", { timeZone: "UTC" }), d.toLocaleTimeString("zh-CN", { timeZone: "UTC" }));
+            log(new Intl.DateTimeFormat("en-US", { weekday: "long", year: "numeric", month: "long", day: "numeric" }).format(d));
+            log(new Intl.DateTimeFormat("zh-CN", { dateStyle: "full", timeStyle: "short", timeZone: "UTC" }).format(d), new Intl.DateTimeFormat("en-US", { hour: "numeric", minute: "2-digit" }).format(d));
+            const c = new Intl.Collator("en");
+            log(["b", "a", "B", "á", "A"].sort(c.compare).join(), ["a10", "a2", "a1"].sort(new Intl.Collator("en", { numeric: true }).compare).join(), "a".localeCompare("Á", "en", { sensitivity: "base" }));
+            log(new Intl.Collator(["ja", "de"]).resolvedOptions().locale, Intl.Collator.supportedLocalesOf(["zh-CN", "en-GB", "ja"]).join());
+            log(Object.prototype.toString.call(Intl), Object.prototype.toString.call(c), new Date(NaN).toLocaleString());
+            new Promise(function() { new Intl.NumberFormat("en", { style: "currency" }) }).catch(function(e) { log(e.message) });
+            new Promise(function() { new Intl.NumberFormat("x_y") }).catch(function(e) { log(e.message) });
+            new Promise(function() { new Intl.DateTimeFormat("en", { timeZone: "Mars/Base" }) }).catch(function(e) { log(e.message) });
+        "#,
+            clock,
+        );
+        assert_eq!(
+            logs,
+            [
+                "1,234,567.891 1.234.567,891 26%",
+                "-$1,234.50 ¥1,234.50 $1.00 $22.00 true",
+                "123,000 1.50 1234",
+                "en-US en-US en-US,zh-Hans-CN zh",
+                "1/5/2024, 11:04:09 PM 2024/1/5 23:04:09 1/5/2024 15:04:09",
+                "Friday, January 5, 2024",
+                "2024年1月5日星期五 15:04 11:04 PM",
+                "a,A,á,b,B a1,a2,a10 0",
+                "de-DE en-GB",
+                "[object Intl] [object Intl.Collator] Invalid Date",
+                "Currency code is required with currency style.",
+                "Incorrect locale information provided",
+                "Invalid time zone specified: Mars/Base",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let logs = run_log(
+            r#"
+            function sloppy() { return typeof this }
+            function strict() { "use strict"; return typeof this }
+            class A { m() { return typeof this } }
+            log(sloppy.call(1), strict.call(1), strict(), A.prototype.m.call("s"), 010, "\101");
+            implicit = 1;
+            log(implicit);
+            function plain() { return this }
+            function strictPlain() { "use strict"; return this }
+            log(plain() === globalThis, strictPlain() === undefined, plain.call(null) === globalThis, this === globalThis);
+            log(globalThis.implicit, globalThis.Math === Math, Object.keys(globalThis), Object.getOwnPropertyDescriptor(globalThis, "NaN").writable);
+            function leak() { "use strict"; undeclared = 1 }
+            function write() { "use strict"; const o = Object.freeze({ a: 1 }); o.a = 2 }
+            function create() { "use strict"; "abc".x = 1 }
+            function remove() { "use strict"; delete Object.prototype }
+            function extend() { "use strict"; var o = Object.seal({ a: 1 }); o.a = 2; o.b = 1 }
+            log(delete Object.prototype, delete Object.freeze({ a: 1 }).a);
+            [leak, write, create, remove, extend].forEach(function(f) {
+                new Promise(function() { f() }).catch(function(e) { log(e.name, e.message) });
+            });
+        "#,
+        );
+        assert_eq!(
+            logs,
+            [
+                "object number undefined string 8 A",
+                "1",
+                "true true true true",
+                "1 true [ 'implicit' ] false",
+                "false false",
+                "ReferenceError undeclared is not defined",
+                "TypeError Cannot assign to read only property 'a' of object '{ a: 1 }'",
+                "TypeError Cannot create property 'x' on string 'abc'",
+                "TypeError Cannot delete property 'prototype' of [object Function]",
+                "TypeError Cannot add property b, object is not extensible",
+            ]
+        );
+        let logs = run_log(r#""use strict"; var x = 1; { var y = x + 1 } log(y); z = 1;"#);
+        assert_eq!(logs, ["2", "Uncaught ReferenceError: z is not defined"]);
+        for (code, message) in [
+            (
+                r#""use strict"; function f(a, a) {}"#,
+                "Duplicate parameter name not allowed in this context",
+            ),
+            (
+                r#"function f(a, a) { "use strict" }"#,
+                "Duplicate parameter name not allowed in this context",
+            ),
+            (
+                r#"function f(a = 1) { "use strict" }"#,
+                "Illegal 'use strict' directive in function with non-simple parameter list",
+            ),
+            (
+                r#""use strict"; with (o) {}"#,
+                "Strict mode code may not include a with statement",
+            ),
+            (
+                r#""use strict"; 010"#,
+                "Octal literals are not allowed in strict mode.",
+            ),
+            (
+                r#"function f() { "use strict"; "\1" }"#,
+                "Octal escape sequences are not allowed in strict mode.",
+            ),
+            (
+                r#""use strict"; var implements"#,
+                "Unexpected strict mode reserved word",
+            ),
+            (
+                "class A { m() { let static } }",
+                "Unexpected strict mode reserved word",
+            ),
+            (
+                r#""use strict"; eval = 1"#,
+                "Unexpected eval or arguments in strict mode",
+            ),
+            (
+                r#"function arguments() { "use strict" }"#,
+                "Unexpected eval or arguments in strict mode",
+            ),
+        ] {
+            let logs = run_log(code);
+            assert!(logs[0].contains(message), "{}: {:?}", code, logs);
+        }
+    }
 }